@@ -352,6 +352,8 @@ impl SessionContext {
             precision: Some(precision),
             tenant: Some(tenant),
             db: Some(db),
+            table: None,
+            lenient: None,
         };
 
         let mut builder = self
@@ -4,6 +4,8 @@ pub use reqwest::header::{HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
 // header
 // privateKey
 pub const PRIVATE_KEY: &str = "X-CnosDB-PrivateKey";
+// write malformed lines leniently, "true" or "false"
+pub const LENIENT_WRITE: &str = "X-CnosDB-Lenient-Write";
 
 // value
 pub const APPLICATION_PREFIX: &str = "application/";
@@ -14,6 +16,11 @@ pub const APPLICATION_NDJSON: &str = "application/nd-json";
 pub const APPLICATION_TABLE: &str = "application/table";
 pub const APPLICATION_STAR: &str = "application/*";
 pub const STAR_STAR: &str = "*/*";
+pub const APPLICATION_PROTOBUF: &str = "application/x-protobuf";
+pub const APPLICATION_ARROW_STREAM: &str = "application/vnd.apache.arrow.stream";
+pub const APPLICATION_PARQUET: &str = "application/vnd.apache.parquet";
+pub const APPLICATION_STREAMED_PROTOBUF_CHUNKED_READ_RESPONSE: &str =
+    "application/x-streamed-protobuf; proto=prometheus.ChunkedReadResponse";
 
 // basic auth
 pub const BASIC_PREFIX: &str = "Basic ";
@@ -31,3 +38,4 @@ pub const DEFLATE: &str = "deflate";
 pub const BROTLI: &str = "br";
 pub const ZSTD: &str = "zstd";
 pub const IDENTITY: &str = "identity";
+pub const SNAPPY: &str = "snappy";
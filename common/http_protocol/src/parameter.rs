@@ -17,6 +17,10 @@ pub struct WriteParam {
     pub precision: Option<String>,
     pub tenant: Option<String>,
     pub db: Option<String>,
+    // Target table of arrow ipc or parquet data.
+    pub table: Option<String>,
+    // Skip malformed lines of line protocol instead of rejecting the request.
+    pub lenient: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -30,3 +34,78 @@ pub struct DumpParam {
 pub struct DebugParam {
     pub id: Option<u32>,
 }
+
+/// Parameters of prometheus `/api/v1/query` and `/api/v1/query_range`,
+/// which may be sent in the url or in a form body.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PromQueryParam {
+    pub tenant: Option<String>,
+    pub db: Option<String>,
+    pub query: Option<String>,
+    pub time: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub step: Option<String>,
+}
+
+impl PromQueryParam {
+    /// Parameters in `other` take precedence.
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            tenant: other.tenant.or(self.tenant),
+            db: other.db.or(self.db),
+            query: other.query.or(self.query),
+            time: other.time.or(self.time),
+            start: other.start.or(self.start),
+            end: other.end.or(self.end),
+            step: other.step.or(self.step),
+        }
+    }
+}
+
+/// Parameters of InfluxDB v1 `/write`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct InfluxWriteParam {
+    pub db: Option<String>,
+    /// Retention policy, which is ignored.
+    pub rp: Option<String>,
+    pub precision: Option<String>,
+    pub u: Option<String>,
+    pub p: Option<String>,
+    pub tenant: Option<String>,
+}
+
+/// Parameters of InfluxDB v2 `/api/v2/write`, the organization and the bucket
+/// are the tenant and the database.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct InfluxV2WriteParam {
+    pub org: Option<String>,
+    pub bucket: Option<String>,
+    pub precision: Option<String>,
+}
+
+/// Parameters of InfluxDB v1 `/query`, which may be sent in the url or in a form body.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct InfluxQueryParam {
+    pub q: Option<String>,
+    pub db: Option<String>,
+    pub epoch: Option<String>,
+    pub u: Option<String>,
+    pub p: Option<String>,
+    pub tenant: Option<String>,
+}
+
+impl InfluxQueryParam {
+    /// Parameters in `other` take precedence.
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            q: other.q.or(self.q),
+            db: other.db.or(self.db),
+            epoch: other.epoch.or(self.epoch),
+            u: other.u.or(self.u),
+            p: other.p.or(self.p),
+            tenant: other.tenant.or(self.tenant),
+        }
+    }
+}
@@ -14,6 +14,23 @@ pub struct ErrorResponse {
     error_message: String,
 }
 
+/// Response of a lenient line protocol write.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct LenientWriteResponse {
+    /// Number of lines written.
+    pub written: usize,
+    pub rejected: Vec<RejectedLine>,
+}
+
+/// A line which is not written, `line` starts from 1.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RejectedLine {
+    pub line: usize,
+    pub error: String,
+}
+
 impl ErrorResponse {
     pub fn new(error_code: &dyn ErrorCode) -> ErrorResponse {
         Self {
@@ -1,6 +1,6 @@
 use bcrypt::BcryptError;
 use openssl::error::ErrorStack;
-pub use password::{bcrypt_hash, bcrypt_verify};
+pub use password::{bcrypt_hash, bcrypt_verify, PasswordPolicy};
 use snafu::Snafu;
 
 use crate::auth::privilege::DatabasePrivilege;
@@ -25,6 +25,23 @@ pub enum AuthError {
     #[snafu(display("Password not set"))]
     PasswordNotSet,
 
+    #[snafu(display("Password does not satisfy the password policy: {}", reason))]
+    PasswordPolicy { reason: String },
+
+    #[snafu(display(
+        "The password of user '{}' has expired or must be changed, \
+    please change it by ALTER USER {} SET PASSWORD",
+        user_name,
+        user_name
+    ))]
+    PasswordChangeRequired { user_name: String },
+
+    #[snafu(display("Invalid bearer token: {}", reason))]
+    InvalidToken { reason: String },
+
+    #[snafu(display("Invalid JWKS: {}", reason))]
+    Jwks { reason: String },
+
     #[snafu(display("Access denied for user '{}' (using {}) {}", user_name, auth_type, err))]
     AccessDenied {
         user_name: String,
@@ -60,6 +77,30 @@ pub enum AuthError {
         role: String,
     },
 
+    #[snafu(display(
+        "{} would widen {} granted to the role {}, please revoke it first",
+        privilege,
+        granted,
+        role
+    ))]
+    PrivilegeWidened {
+        privilege: String,
+        granted: String,
+        role: String,
+    },
+
+    #[snafu(display(
+        "{} is covered by {} granted to the role {}, please revoke it first",
+        privilege,
+        granted,
+        role
+    ))]
+    PrivilegeCovered {
+        privilege: String,
+        granted: String,
+        role: String,
+    },
+
     #[snafu(display("The user {} already exists", user))]
     UserAlreadyExists { user: String },
 
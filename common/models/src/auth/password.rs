@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::auth::AuthError;
 
 pub fn bcrypt_hash(password: &str) -> Result<String, AuthError> {
@@ -7,3 +9,107 @@ pub fn bcrypt_hash(password: &str) -> Result<String, AuthError> {
 pub fn bcrypt_verify(password: &str, hash_password: &str) -> Result<bool, AuthError> {
     Ok(bcrypt::verify(password, hash_password)?)
 }
+
+/// 密码策略, 创建用户或修改密码时校验密码复杂度, 并计算密码过期时间
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    pub require_special: bool,
+    /// 密码有效期, None 表示永不过期
+    pub lifetime: Option<Duration>,
+}
+
+impl PasswordPolicy {
+    pub fn validate(&self, password: &str) -> Result<(), AuthError> {
+        let mut unsatisfied = vec![];
+        if password.chars().count() < self.min_length {
+            unsatisfied.push(format!("at least {} characters", self.min_length));
+        }
+        if self.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+            unsatisfied.push("an uppercase letter".to_string());
+        }
+        if self.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+            unsatisfied.push("a lowercase letter".to_string());
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            unsatisfied.push("a digit".to_string());
+        }
+        if self.require_special && password.chars().all(|c| c.is_alphanumeric()) {
+            unsatisfied.push("a special character".to_string());
+        }
+
+        if unsatisfied.is_empty() {
+            return Ok(());
+        }
+
+        Err(AuthError::PasswordPolicy {
+            reason: format!("the password must contain {}", unsatisfied.join(", ")),
+        })
+    }
+
+    /// 是否配置了密码复杂度要求, 配置后不能绕过校验直接设置 hash_password
+    pub fn has_complexity_rules(&self) -> bool {
+        self.min_length > 0
+            || self.require_uppercase
+            || self.require_lowercase
+            || self.require_digit
+            || self.require_special
+    }
+
+    /// 在 now 设置的密码的过期时间(纳秒)
+    pub fn password_expire_at(&self, now: i64) -> Option<i64> {
+        self.lifetime
+            .map(|lifetime| now.saturating_add(lifetime.as_nanos().min(i64::MAX as u128) as i64))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::PasswordPolicy;
+
+    #[test]
+    fn test_password_policy() {
+        let policy = PasswordPolicy::default();
+        assert!(policy.validate("").is_ok());
+        assert!(!policy.has_complexity_rules());
+
+        let policy = PasswordPolicy {
+            min_length: 8,
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            require_special: true,
+            lifetime: None,
+        };
+        assert!(policy.has_complexity_rules());
+        assert!(policy.validate("Abcdef1!").is_ok());
+        assert!(policy.validate("Abcde1!").is_err());
+        assert!(policy.validate("abcdef1!").is_err());
+        assert!(policy.validate("ABCDEF1!").is_err());
+        assert!(policy.validate("Abcdefg!").is_err());
+        assert!(policy.validate("Abcdefg1").is_err());
+
+        let err = policy.validate("abc").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Password does not satisfy the password policy: the password must contain at least 8 characters, an uppercase letter, a digit, a special character"
+        );
+    }
+
+    #[test]
+    fn test_password_expire_at() {
+        let policy = PasswordPolicy::default();
+        assert_eq!(policy.password_expire_at(100), None);
+
+        let policy = PasswordPolicy {
+            lifetime: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        assert_eq!(policy.password_expire_at(100), Some(1_000_000_100));
+    }
+}
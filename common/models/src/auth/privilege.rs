@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::fmt::Display;
 use std::hash::Hash;
 
@@ -120,6 +121,8 @@ pub enum TenantObjectPrivilege {
     // T: database_name
     // None: all databases in this tenant
     Database(DatabasePrivilege, Option<String>),
+    // The privilege on a table or on some columns of a table
+    Table(TablePrivilege),
 }
 
 impl Display for TenantObjectPrivilege {
@@ -142,6 +145,9 @@ impl Display for TenantObjectPrivilege {
                     write!(f, "{:?} on all databases", p)
                 }
             },
+            Self::Table(p) => {
+                write!(f, "{}", p)
+            }
         }
     }
 }
@@ -156,6 +162,11 @@ impl PrivilegeChecker for TenantObjectPrivilege {
             (Self::Database(s, Some(s_t)), Self::Database(o, Some(o_t))) => {
                 s_t == o_t && s.check_privilege(o)
             }
+            (Self::Database(s, None), Self::Table(o)) => s.check_privilege(&o.privilege),
+            (Self::Database(s, Some(s_t)), Self::Table(o)) => {
+                s_t == &o.database && s.check_privilege(&o.privilege)
+            }
+            (Self::Table(s), Self::Table(o)) => s.check_privilege(o),
             (l, r) => l == r,
         }
     }
@@ -188,3 +199,133 @@ impl PrivilegeChecker for DatabasePrivilege {
         }
     }
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TablePrivilege {
+    pub privilege: DatabasePrivilege,
+    pub database: String,
+    pub table: String,
+    // Some: only these columns of the table
+    // None: all columns of the table
+    pub columns: Option<BTreeSet<String>>,
+}
+
+impl TablePrivilege {
+    pub fn new(
+        privilege: DatabasePrivilege,
+        database: impl Into<String>,
+        table: impl Into<String>,
+        columns: Option<BTreeSet<String>>,
+    ) -> Self {
+        Self {
+            privilege,
+            database: database.into(),
+            table: table.into(),
+            columns,
+        }
+    }
+}
+
+impl Display for TablePrivilege {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.columns {
+            Some(columns) => {
+                let columns = columns.iter().cloned().collect::<Vec<_>>().join(", ");
+                write!(
+                    f,
+                    "{:?} on columns ({}) of table {}.{}",
+                    self.privilege, columns, self.database, self.table
+                )
+            }
+            None => {
+                write!(
+                    f,
+                    "{:?} on table {}.{}",
+                    self.privilege, self.database, self.table
+                )
+            }
+        }
+    }
+}
+
+impl PrivilegeChecker for TablePrivilege {
+    fn check_privilege(&self, other: &Self) -> bool {
+        if self.database != other.database
+            || self.table != other.table
+            || !self.privilege.check_privilege(&other.privilege)
+        {
+            return false;
+        }
+
+        match (&self.columns, &other.columns) {
+            (None, _) => true,
+            (Some(s), Some(o)) => o.is_subset(s),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use super::{DatabasePrivilege, PrivilegeChecker, TablePrivilege, TenantObjectPrivilege};
+
+    fn columns(columns: &[&str]) -> Option<BTreeSet<String>> {
+        Some(columns.iter().map(|c| c.to_string()).collect())
+    }
+
+    #[test]
+    fn test_table_privilege() {
+        let read_table = TenantObjectPrivilege::Table(TablePrivilege::new(
+            DatabasePrivilege::Read,
+            "db",
+            "t",
+            None,
+        ));
+        let read_columns = TenantObjectPrivilege::Table(TablePrivilege::new(
+            DatabasePrivilege::Read,
+            "db",
+            "t",
+            columns(&["time", "host"]),
+        ));
+        let read_host = TenantObjectPrivilege::Table(TablePrivilege::new(
+            DatabasePrivilege::Read,
+            "db",
+            "t",
+            columns(&["host"]),
+        ));
+        let write_table = TenantObjectPrivilege::Table(TablePrivilege::new(
+            DatabasePrivilege::Write,
+            "db",
+            "t",
+            None,
+        ));
+        let read_other = TenantObjectPrivilege::Table(TablePrivilege::new(
+            DatabasePrivilege::Read,
+            "db",
+            "t2",
+            None,
+        ));
+
+        // database privileges cover the tables of the database
+        let read_db = TenantObjectPrivilege::Database(DatabasePrivilege::Read, Some("db".into()));
+        let read_all = TenantObjectPrivilege::Database(DatabasePrivilege::Read, None);
+        let read_db2 = TenantObjectPrivilege::Database(DatabasePrivilege::Read, Some("db2".into()));
+        assert!(read_db.check_privilege(&read_table));
+        assert!(read_all.check_privilege(&read_columns));
+        assert!(!read_db.check_privilege(&write_table));
+        assert!(!read_db2.check_privilege(&read_table));
+
+        // table privileges don't cover the database
+        assert!(!read_table.check_privilege(&read_db));
+
+        assert!(read_table.check_privilege(&read_columns));
+        assert!(read_columns.check_privilege(&read_host));
+        assert!(!read_host.check_privilege(&read_columns));
+        assert!(!read_columns.check_privilege(&read_table));
+        assert!(write_table.check_privilege(&read_host));
+        assert!(!read_table.check_privilege(&write_table));
+        assert!(!read_table.check_privilege(&read_other));
+    }
+}
@@ -6,7 +6,9 @@ use std::sync::Arc;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 
-use super::privilege::{DatabasePrivilege, GlobalPrivilege, Privilege, TenantObjectPrivilege};
+use super::privilege::{
+    DatabasePrivilege, GlobalPrivilege, Privilege, TablePrivilege, TenantObjectPrivilege,
+};
 use super::Result;
 use crate::auth::AuthError;
 use crate::oid::{Id, Identifier};
@@ -161,6 +163,13 @@ pub struct CustomTenantRole<T> {
     // database_name -> privileges
     // only add database privilege
     additional_privileges: HashMap<String, DatabasePrivilege>,
+    // privileges on the tables or columns of tables
+    // a role inheriting member only reads the granted databases and tables if any read is granted
+    #[serde(default)]
+    table_privileges: Vec<TablePrivilege>,
+    // the members of the role only see the rows matching the policies
+    #[serde(default)]
+    row_policies: Vec<RowPolicy>,
 }
 
 impl<T> CustomTenantRole<T> {
@@ -177,6 +186,8 @@ impl<T> CustomTenantRole<T> {
             name,
             system_role,
             additional_privileges,
+            table_privileges: vec![],
+            row_policies: vec![],
         }
     }
 
@@ -187,11 +198,95 @@ impl<T> CustomTenantRole<T> {
     pub fn additional_privileges(&self) -> &HashMap<String, DatabasePrivilege> {
         &self.additional_privileges
     }
+
+    pub fn table_privileges(&self) -> &[TablePrivilege] {
+        &self.table_privileges
+    }
+
+    pub fn row_policies(&self) -> &[RowPolicy] {
+        &self.row_policies
+    }
+
+    /// The policies on the table, the rows matching any of them are visible
+    pub fn table_row_policies<'a>(
+        &'a self,
+        database_name: &'a str,
+        table_name: &'a str,
+    ) -> impl Iterator<Item = &'a RowPolicy> {
+        self.row_policies
+            .iter()
+            .filter(move |p| p.database == database_name && p.table == table_name)
+    }
+
+    pub fn get_row_policy(
+        &self,
+        name: &str,
+        database_name: &str,
+        table_name: &str,
+    ) -> Option<&RowPolicy> {
+        self.table_row_policies(database_name, table_name)
+            .find(|p| p.name == name)
+    }
+
+    /// Replace the policy with the same name on the same table
+    pub fn add_row_policy(&mut self, policy: RowPolicy) {
+        self.row_policies.retain(|p| {
+            p.name != policy.name || p.database != policy.database || p.table != policy.table
+        });
+        self.row_policies.push(policy);
+    }
+
+    pub fn remove_row_policy(&mut self, name: &str, database_name: &str, table_name: &str) -> bool {
+        let len = self.row_policies.len();
+        self.row_policies
+            .retain(|p| p.name != name || p.database != database_name || p.table != table_name);
+        len != self.row_policies.len()
+    }
+}
+
+/// Row-level security policy of a table,
+/// the members of the role only see the rows satisfying `predicate`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RowPolicy {
+    pub name: String,
+    pub database: String,
+    pub table: String,
+    /// Sql expression on the tag columns of the table, e.g. `customer = current_user()`
+    pub predicate: String,
+}
+
+impl RowPolicy {
+    pub fn new(
+        name: impl Into<String>,
+        database: impl Into<String>,
+        table: impl Into<String>,
+        predicate: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            database: database.into(),
+            table: table.into(),
+            predicate: predicate.into(),
+        }
+    }
 }
 
 impl<T: Id> CustomTenantRole<T> {
     pub fn to_privileges(&self, tenant_id: &T) -> HashSet<Privilege<T>> {
-        let privileges = self.system_role.to_privileges(tenant_id);
+        let mut privileges = self.system_role.to_privileges(tenant_id);
+
+        let has_table_read = self
+            .table_privileges
+            .iter()
+            .any(|p| p.privilege == DatabasePrivilege::Read);
+        if has_table_read && self.system_role == SystemTenantRole::Member {
+            // The reads of the role are restricted to the granted databases and tables,
+            // so the read privilege of all databases is not inherited.
+            privileges.remove(&Privilege::TenantObject(
+                TenantObjectPrivilege::Database(DatabasePrivilege::Read, None),
+                Some(tenant_id.clone()),
+            ));
+        }
 
         let additiona_privileges = self
             .additional_privileges
@@ -204,7 +299,22 @@ impl<T: Id> CustomTenantRole<T> {
             })
             .collect::<HashSet<Privilege<T>>>();
 
-        privileges.union(&additiona_privileges).cloned().collect()
+        let table_privileges = self
+            .table_privileges
+            .iter()
+            .map(|privilege| {
+                Privilege::TenantObject(
+                    TenantObjectPrivilege::Table(privilege.clone()),
+                    Some(tenant_id.clone()),
+                )
+            })
+            .collect::<HashSet<Privilege<T>>>();
+
+        privileges
+            .union(&additiona_privileges)
+            .chain(table_privileges.iter())
+            .cloned()
+            .collect()
     }
 
     pub fn grant_privilege(
@@ -240,6 +350,125 @@ impl<T: Id> CustomTenantRole<T> {
             })
         }
     }
+
+    /// Replace the privilege of the same kind on the same table.
+    ///
+    /// Write and all privileges imply read on all columns, so they are rejected if the
+    /// read privilege on the table is restricted to some columns, which must be revoked first.
+    /// For the same reason, read on some columns is rejected if the role can read all columns
+    /// of the table by a privilege on the table or the database.
+    pub fn grant_table_privilege(&mut self, privilege: TablePrivilege) -> Result<()> {
+        self.check_grant_table_privilege(&privilege)?;
+        self.table_privileges.retain(|p| {
+            p.database != privilege.database
+                || p.table != privilege.table
+                || p.privilege != privilege.privilege
+        });
+        self.table_privileges.push(privilege);
+
+        Ok(())
+    }
+
+    pub fn check_grant_table_privilege(&self, privilege: &TablePrivilege) -> Result<()> {
+        if privilege.privilege == DatabasePrivilege::Read {
+            return match privilege.columns {
+                Some(_) => self.check_grant_column_read(privilege),
+                None => Ok(()),
+            };
+        }
+        let restricted_read = self.table_privileges.iter().find(|p| {
+            p.database == privilege.database
+                && p.table == privilege.table
+                && p.privilege == DatabasePrivilege::Read
+                && p.columns.is_some()
+        });
+        match restricted_read {
+            Some(read) => Err(AuthError::PrivilegeWidened {
+                privilege: privilege.to_string(),
+                granted: read.to_string(),
+                role: self.name.to_owned(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn check_grant_column_read(&self, privilege: &TablePrivilege) -> Result<()> {
+        let covered = |granted: String| AuthError::PrivilegeCovered {
+            privilege: privilege.to_string(),
+            granted,
+            role: self.name.to_owned(),
+        };
+
+        if self.system_role == SystemTenantRole::Owner {
+            return Err(covered(
+                TenantObjectPrivilege::Database(DatabasePrivilege::Full, None).to_string(),
+            ));
+        }
+        if let Some(p) = self.additional_privileges.get(&privilege.database) {
+            return Err(covered(
+                TenantObjectPrivilege::Database(p.clone(), Some(privilege.database.clone()))
+                    .to_string(),
+            ));
+        }
+        let table_privilege = self.table_privileges.iter().find(|p| {
+            p.database == privilege.database
+                && p.table == privilege.table
+                && p.privilege != DatabasePrivilege::Read
+        });
+        match table_privilege {
+            Some(p) => Err(covered(p.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Privileges on a database imply read on all columns of its tables, so they are rejected
+    /// if the read privilege on any table of the database is restricted to some columns.
+    pub fn check_grant_privilege(
+        &self,
+        database_name: &str,
+        privilege: &DatabasePrivilege,
+    ) -> Result<()> {
+        let restricted_read = self.table_privileges.iter().find(|p| {
+            p.database == database_name
+                && p.privilege == DatabasePrivilege::Read
+                && p.columns.is_some()
+        });
+        match restricted_read {
+            Some(read) => Err(AuthError::PrivilegeWidened {
+                privilege: TenantObjectPrivilege::Database(
+                    privilege.clone(),
+                    Some(database_name.to_string()),
+                )
+                .to_string(),
+                granted: read.to_string(),
+                role: self.name.to_owned(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub fn revoke_table_privilege(
+        &mut self,
+        database_name: &str,
+        table_name: &str,
+        privilege: &DatabasePrivilege,
+    ) -> Result<bool> {
+        let position = self.table_privileges.iter().position(|p| {
+            p.database == database_name && p.table == table_name && &p.privilege == privilege
+        });
+
+        match position {
+            Some(idx) => {
+                self.table_privileges.remove(idx);
+                Ok(true)
+            }
+            None => Err(AuthError::PrivilegeNotFound {
+                db: format!("{database_name}.{table_name}"),
+                privilege: privilege.to_owned(),
+                role: self.name.to_owned(),
+            }),
+        }
+    }
 }
 
 impl<T> Identifier<T> for CustomTenantRole<T> {
@@ -251,3 +480,124 @@ impl<T> Identifier<T> for CustomTenantRole<T> {
         &self.name
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeSet, HashMap};
+
+    use super::{
+        CustomTenantRole, DatabasePrivilege, Privilege, SystemTenantRole, TablePrivilege,
+        TenantObjectPrivilege,
+    };
+
+    fn columns(columns: &[&str]) -> Option<BTreeSet<String>> {
+        Some(columns.iter().map(|c| c.to_string()).collect())
+    }
+
+    #[test]
+    fn test_grant_table_privilege() {
+        let mut role = CustomTenantRole::new(
+            1_u128,
+            "r".to_string(),
+            SystemTenantRole::Member,
+            HashMap::new(),
+        );
+
+        // Privileges of the other kinds on the same table are kept.
+        let read = TablePrivilege::new(DatabasePrivilege::Read, "db", "t", None);
+        let write = TablePrivilege::new(DatabasePrivilege::Write, "db", "t", None);
+        role.grant_table_privilege(read.clone()).unwrap();
+        role.grant_table_privilege(write.clone()).unwrap();
+        assert_eq!(role.table_privileges, vec![read, write.clone()]);
+
+        // The privilege of the same kind is replaced.
+        let read_columns =
+            TablePrivilege::new(DatabasePrivilege::Read, "db", "t", columns(&["time", "a"]));
+        // Read on some columns would be covered by the write privilege.
+        assert!(role.grant_table_privilege(read_columns.clone()).is_err());
+        role.revoke_table_privilege("db", "t", &DatabasePrivilege::Write)
+            .unwrap();
+        role.grant_table_privilege(read_columns.clone()).unwrap();
+        assert_eq!(role.table_privileges, vec![read_columns.clone()]);
+
+        // Write and all privileges would widen the read of the columns.
+        let mut role = CustomTenantRole::new(
+            1_u128,
+            "r".to_string(),
+            SystemTenantRole::Member,
+            HashMap::new(),
+        );
+        role.grant_table_privilege(read_columns.clone()).unwrap();
+        for privilege in [DatabasePrivilege::Write, DatabasePrivilege::Full] {
+            assert!(role
+                .grant_table_privilege(TablePrivilege::new(privilege, "db", "t", None))
+                .is_err());
+        }
+        role.grant_table_privilege(TablePrivilege::new(
+            DatabasePrivilege::Write,
+            "db",
+            "t2",
+            None,
+        ))
+        .unwrap();
+        assert_eq!(role.table_privileges.len(), 2);
+
+        // Privileges on the database cover read on some columns and are widened by it.
+        let mut role = CustomTenantRole::new(
+            1_u128,
+            "r".to_string(),
+            SystemTenantRole::Member,
+            HashMap::from([("db".to_string(), DatabasePrivilege::Read)]),
+        );
+        assert!(role.grant_table_privilege(read_columns.clone()).is_err());
+        role.revoke_privilege("db", &DatabasePrivilege::Read)
+            .unwrap();
+        role.grant_table_privilege(read_columns.clone()).unwrap();
+        assert!(role
+            .check_grant_privilege("db", &DatabasePrivilege::Write)
+            .is_err());
+        assert!(role
+            .check_grant_privilege("db2", &DatabasePrivilege::Write)
+            .is_ok());
+
+        let mut role = CustomTenantRole::new(
+            1_u128,
+            "r".to_string(),
+            SystemTenantRole::Owner,
+            HashMap::new(),
+        );
+        assert!(role.grant_table_privilege(read_columns).is_err());
+    }
+
+    #[test]
+    fn test_member_read_restricted_by_table_read() {
+        let all_databases_read = Privilege::TenantObject(
+            TenantObjectPrivilege::Database(DatabasePrivilege::Read, None),
+            Some(1_u128),
+        );
+        let mut role = CustomTenantRole::new(
+            1_u128,
+            "r".to_string(),
+            SystemTenantRole::Member,
+            HashMap::new(),
+        );
+
+        role.grant_table_privilege(TablePrivilege::new(
+            DatabasePrivilege::Write,
+            "db",
+            "t",
+            None,
+        ))
+        .unwrap();
+        assert!(role.to_privileges(&1_u128).contains(&all_databases_read));
+
+        role.grant_table_privilege(TablePrivilege::new(
+            DatabasePrivilege::Read,
+            "db",
+            "t2",
+            None,
+        ))
+        .unwrap();
+        assert!(!role.to_privileges(&1_u128).contains(&all_databases_read));
+    }
+}
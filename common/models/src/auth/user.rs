@@ -1,11 +1,12 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::fmt::Display;
 
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 
 use super::privilege::{
-    DatabasePrivilege, GlobalPrivilege, Privilege, PrivilegeChecker, TenantObjectPrivilege,
+    DatabasePrivilege, GlobalPrivilege, Privilege, PrivilegeChecker, TablePrivilege,
+    TenantObjectPrivilege,
 };
 use super::role::{TenantRoleIdentifier, UserRole};
 use super::{rsa_utils, AuthError, Result};
@@ -20,6 +21,9 @@ pub struct User {
     desc: UserDesc,
     privileges: HashSet<Privilege<Oid>>,
     role: Option<TenantRoleIdentifier>,
+    /// 密码已过期或被要求修改, 修改密码前只允许修改自身的密码
+    #[serde(default)]
+    password_change_required: bool,
 }
 
 impl User {
@@ -35,9 +39,19 @@ impl User {
             desc,
             privileges,
             role,
+            password_change_required: false,
         }
     }
 
+    pub fn with_password_change_required(mut self) -> Self {
+        self.password_change_required = true;
+        self
+    }
+
+    pub fn password_change_required(&self) -> bool {
+        self.password_change_required
+    }
+
     pub fn role(&self) -> Option<&TenantRoleIdentifier> {
         self.role.as_ref()
     }
@@ -47,6 +61,10 @@ impl User {
     }
 
     pub fn check_privilege(&self, privilege: &Privilege<Oid>) -> bool {
+        if self.password_change_required {
+            return privilege == &Privilege::Global(GlobalPrivilege::User(Some(*self.desc.id())));
+        }
+
         self.privileges.iter().any(|e| e.check_privilege(privilege))
     }
 
@@ -70,6 +88,48 @@ impl User {
         );
         self.check_privilege(&privilege)
     }
+
+    /// Returns the columns of the table that the user can read,
+    /// None if the user can read all columns or isn't granted any column of the table.
+    pub fn readable_columns(
+        &self,
+        tenant_id: Oid,
+        database_name: &str,
+        table_name: &str,
+    ) -> Option<BTreeSet<String>> {
+        let privilege = Privilege::TenantObject(
+            TenantObjectPrivilege::Table(TablePrivilege::new(
+                DatabasePrivilege::Read,
+                database_name,
+                table_name,
+                None,
+            )),
+            Some(tenant_id),
+        );
+        if self.check_privilege(&privilege) {
+            return None;
+        }
+
+        let columns = self
+            .privileges
+            .iter()
+            .filter_map(|p| match p {
+                Privilege::TenantObject(TenantObjectPrivilege::Table(p), Some(t))
+                    if t == &tenant_id
+                        && p.database == database_name
+                        && p.table == table_name
+                        && p.privilege.check_privilege(&DatabasePrivilege::Read) =>
+                {
+                    p.columns.as_ref()
+                }
+                _ => None,
+            })
+            .flatten()
+            .cloned()
+            .collect::<BTreeSet<_>>();
+
+        (!columns.is_empty()).then_some(columns)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +139,8 @@ pub struct UserDesc {
     name: String,
     options: UserOptions,
     is_root_admin: bool,
+    #[serde(default, skip_serializing_if = "LoginStatus::is_clean")]
+    login_status: LoginStatus,
 }
 
 impl UserDesc {
@@ -88,6 +150,7 @@ impl UserDesc {
             name,
             options,
             is_root_admin,
+            login_status: LoginStatus::default(),
         }
     }
 
@@ -117,6 +180,70 @@ impl UserDesc {
         self.name = new_name;
         self
     }
+
+    pub fn with_options(mut self, options: UserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn login_status(&self) -> &LoginStatus {
+        &self.login_status
+    }
+
+    pub fn login_status_mut(&mut self) -> &mut LoginStatus {
+        &mut self.login_status
+    }
+
+    /// 密码是否需要修改: 被要求修改或者已经过期
+    pub fn password_change_required(&self, now: i64) -> bool {
+        self.options.must_change_password().unwrap_or_default()
+            || self
+                .options
+                .password_expire_at()
+                .is_some_and(|expire_at| expire_at <= now)
+    }
+}
+
+/// 用户的登录状态, 用于连续登录失败后锁定用户
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginStatus {
+    /// 连续登录失败的次数
+    failed_attempts: u32,
+    /// 锁定的截止时间(纳秒)
+    locked_until: Option<i64>,
+}
+
+impl LoginStatus {
+    pub fn failed_attempts(&self) -> u32 {
+        self.failed_attempts
+    }
+
+    pub fn locked_until(&self) -> Option<i64> {
+        self.locked_until
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.failed_attempts == 0 && self.locked_until.is_none()
+    }
+
+    pub fn is_locked(&self, now: i64) -> bool {
+        self.locked_until.is_some_and(|until| until > now)
+    }
+
+    /// 记录一次登录失败, 失败次数达到 max_failed_attempts 时锁定到 locked_until,
+    /// max_failed_attempts 为 0 表示不锁定
+    pub fn record_failure(&mut self, max_failed_attempts: u32, locked_until: i64) {
+        self.failed_attempts += 1;
+        if max_failed_attempts > 0 && self.failed_attempts >= max_failed_attempts {
+            self.failed_attempts = 0;
+            self.locked_until = Some(locked_until);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.failed_attempts = 0;
+        self.locked_until = None;
+    }
 }
 
 impl Eq for UserDesc {}
@@ -149,6 +276,9 @@ pub struct UserOptions {
     comment: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     granted_admin: Option<bool>,
+    /// 密码的过期时间(纳秒)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password_expire_at: Option<i64>,
 }
 
 impl UserOptions {
@@ -167,14 +297,28 @@ impl UserOptions {
     pub fn granted_admin(&self) -> Option<bool> {
         self.granted_admin
     }
+    pub fn password_expire_at(&self) -> Option<i64> {
+        self.password_expire_at
+    }
 
     pub fn merge(self, other: Self) -> Self {
+        // 修改密码后, 不再沿用旧密码的强制修改标记和过期时间
+        let (must_change_password, password_expire_at) = if self.hash_password.is_some() {
+            (self.must_change_password, self.password_expire_at)
+        } else {
+            (
+                self.must_change_password.or(other.must_change_password),
+                self.password_expire_at.or(other.password_expire_at),
+            )
+        };
+
         Self {
             hash_password: self.hash_password.or(other.hash_password),
-            must_change_password: self.must_change_password.or(other.must_change_password),
+            must_change_password,
             rsa_public_key: self.rsa_public_key.or(other.rsa_public_key),
             comment: self.comment.or(other.comment),
             granted_admin: self.granted_admin.or(other.granted_admin),
+            password_expire_at,
         }
     }
     pub fn hidden_password(&mut self) {
@@ -208,6 +352,10 @@ impl Display for UserOptions {
             write!(f, "granted_admin={},", e)?;
         }
 
+        if let Some(ref e) = self.password_expire_at {
+            write!(f, "password_expire_at={},", e)?;
+        }
+
         Ok(())
     }
 }
@@ -287,3 +435,57 @@ pub fn admin_user(desc: UserDesc, role: Option<TenantRoleIdentifier>) -> User {
     let privileges = UserRole::Dba.to_privileges();
     User::new(desc, privileges, role)
 }
+
+#[cfg(test)]
+mod test {
+    use super::{LoginStatus, UserOptionsBuilder};
+
+    #[test]
+    fn test_merge_password_options() {
+        let old = UserOptionsBuilder::default()
+            .hash_password("old")
+            .must_change_password(true)
+            .password_expire_at(100_i64)
+            .comment("c")
+            .build()
+            .unwrap();
+
+        let comment = UserOptionsBuilder::default().comment("d").build().unwrap();
+        let merged = comment.merge(old.clone());
+        assert_eq!(merged.must_change_password(), Some(true));
+        assert_eq!(merged.password_expire_at(), Some(100));
+        assert_eq!(merged.comment(), Some("d"));
+
+        let password = UserOptionsBuilder::default()
+            .hash_password("new")
+            .build()
+            .unwrap();
+        let merged = password.merge(old);
+        assert_eq!(merged.hash_password(), Some("new"));
+        assert_eq!(merged.must_change_password(), None);
+        assert_eq!(merged.password_expire_at(), None);
+        assert_eq!(merged.comment(), Some("c"));
+    }
+
+    #[test]
+    fn test_login_status() {
+        let mut status = LoginStatus::default();
+        assert!(status.is_clean());
+
+        status.record_failure(0, 100);
+        status.record_failure(0, 100);
+        assert_eq!(status.failed_attempts(), 2);
+        assert!(!status.is_locked(0));
+
+        let mut status = LoginStatus::default();
+        status.record_failure(2, 100);
+        assert!(!status.is_locked(0));
+        status.record_failure(2, 100);
+        assert!(status.is_locked(99));
+        assert!(!status.is_locked(100));
+        assert_eq!(status.failed_attempts(), 0);
+
+        status.reset();
+        assert!(status.is_clean());
+    }
+}
@@ -7,7 +7,7 @@ use crate::auth::role::{CustomTenantRole, TenantRoleIdentifier};
 use crate::node_info::NodeStatus;
 use crate::oid::Oid;
 use crate::predicate::domain::TimeRange;
-use crate::schema::{DatabaseSchema, ResourceInfo, TableSchema};
+use crate::schema::{DatabaseSchema, ResourceInfo, RollupRange, TableSchema};
 
 pub type VnodeId = u32;
 pub type NodeId = u64;
@@ -198,6 +198,9 @@ pub struct DatabaseInfo {
     pub schema: DatabaseSchema,
     pub buckets: Vec<BucketInfo>,
     pub tables: HashMap<String, TableSchema>,
+    // rollup table -> range aggregated into the rollup table
+    #[serde(default)]
+    pub rollups: HashMap<String, RollupRange>,
 }
 
 impl DatabaseInfo {
@@ -1,5 +1,5 @@
 use std::cmp::{self, max, min, Ordering};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::hash::Hash;
 use std::ops::{Bound as StdBound, RangeBounds};
@@ -24,7 +24,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use super::transformation::RowExpressionToDomainsVisitor;
 use super::utils::filter_to_time_ranges;
 use super::PlacedSplit;
-use crate::schema::{ColumnType, TableColumn, TskvTableSchemaRef};
+use crate::schema::{ColumnType, TskvTableSchemaRef};
 use crate::{Error, Result, Timestamp};
 
 pub type PredicateRef = Arc<Predicate>;
@@ -1097,10 +1097,112 @@ impl Display for EqutableValueSet {
     }
 }
 
+/// A set containing string values matching all of the regular expressions.
+///
+/// It can not be merged with other value sets, and is only used to filter tag values
+/// in the inverted index, the values are scanned by the literal prefix of the patterns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternValueSet {
+    patterns: BTreeSet<String>,
+}
+
+impl PatternValueSet {
+    pub fn patterns(&self) -> impl IntoIterator<Item = &String> {
+        &self.patterns
+    }
+
+    /// Returns the longest literal prefix of the patterns, all matched values start with it.
+    pub fn literal_prefix(&self) -> String {
+        self.patterns
+            .iter()
+            .map(|p| regex_literal_prefix(p))
+            .max_by_key(|p| p.len())
+            .unwrap_or_default()
+    }
+}
+
+impl Display for PatternValueSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[")?;
+        for (i, pattern) in self.patterns.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "/{pattern}/")?;
+        }
+        write!(f, "]")
+    }
+}
+
+fn is_regex_meta_character(c: char) -> bool {
+    matches!(
+        c,
+        '\\' | '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$'
+    )
+}
+
+/// Push the character to the regular expression as a literal.
+pub(crate) fn push_regex_escaped(regex: &mut String, c: char) {
+    // Characters of class set operations are escaped too.
+    if is_regex_meta_character(c) || matches!(c, '#' | '&' | '-' | '~') {
+        regex.push('\\');
+    }
+    regex.push(c);
+}
+
+/// Returns the literal prefix of a regular expression anchored by '^', e.g. 'cpu' of '^cpu[0-9]+',
+/// empty if there is no such prefix.
+fn regex_literal_prefix(pattern: &str) -> String {
+    let mut prefix = String::new();
+    // An alternation may not be anchored, such as '^a|b'.
+    if !pattern.starts_with('^') || pattern.contains('|') {
+        return prefix;
+    }
+
+    let mut chars = pattern[1..].chars().peekable();
+    while let Some(c) = chars.next() {
+        let literal = match c {
+            '\\' => match chars.next() {
+                Some(e) if e.is_ascii_punctuation() => e,
+                // Character classes like '\d', '\w'.
+                _ => break,
+            },
+            c if is_regex_meta_character(c) => break,
+            c => c,
+        };
+        match chars.peek() {
+            // The last literal may be absent or repeated.
+            Some('*' | '?' | '{') => break,
+            Some('+') => {
+                prefix.push(literal);
+                break;
+            }
+            _ => prefix.push(literal),
+        }
+    }
+
+    prefix
+}
+
+/// Returns the smallest string greater than all strings starting with the prefix,
+/// `None` if there is no such string.
+fn prefix_upper_bound(prefix: &str) -> Option<String> {
+    let mut chars = prefix.chars().collect::<Vec<_>>();
+    while let Some(c) = chars.pop() {
+        let next = (c as u32 + 1..=char::MAX as u32).find_map(char::from_u32);
+        if let Some(next) = next {
+            chars.push(next);
+            return Some(chars.into_iter().collect());
+        }
+    }
+    None
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Domain {
     Range(RangeValueSet),
     Equtable(EqutableValueSet),
+    Pattern(PatternValueSet),
     None,
     All,
 }
@@ -1168,6 +1270,27 @@ impl Domain {
             entries,
         })
     }
+    /// Construct a range of strings starting with the prefix.
+    pub fn of_prefix(prefix: &str) -> Domain {
+        if prefix.is_empty() {
+            return Domain::All;
+        }
+
+        let low = ScalarValue::Utf8(Some(prefix.to_string()));
+        let range = match prefix_upper_bound(prefix) {
+            Some(high) => Range::gelt(&DataType::Utf8, &low, &ScalarValue::Utf8(Some(high))),
+            None => Range::ge(&DataType::Utf8, &low),
+        };
+        Domain::Range(RangeValueSet {
+            low_indexed_ranges: BTreeMap::from([(range.low.clone(), range)]),
+        })
+    }
+    /// Construct a set of strings matching the regular expression.
+    pub fn of_pattern(pattern: &str) -> Domain {
+        Domain::Pattern(PatternValueSet {
+            patterns: BTreeSet::from([pattern.to_string()]),
+        })
+    }
     /// Calculates the intersection of two ranges, and returns None if the intersection does not exist
     ///
     /// This method returns the new value without changing the old value
@@ -1182,6 +1305,19 @@ impl Domain {
             (Self::None, _) | (_, Self::None) => Ok(Self::None),
             (Self::All, _) => Ok(other.clone()),
             (_, Self::All) => Ok(self.clone()),
+            (Self::Pattern(ref self_val_set), Self::Pattern(ref other_val_set)) => {
+                Ok(Self::Pattern(PatternValueSet {
+                    patterns: self_val_set
+                        .patterns
+                        .union(&other_val_set.patterns)
+                        .cloned()
+                        .collect(),
+                }))
+            }
+            // Patterns can not be intersected with other value sets, the other one is kept
+            // since it's usually more selective, matched values are filtered again later.
+            (Self::Pattern(_), _) => Ok(other.clone()),
+            (_, Self::Pattern(_)) => Ok(self.clone()),
             _ => Err(Error::Internal {
                 err: "mismatched ValueSet type".to_string(),
             }),
@@ -1205,6 +1341,19 @@ impl Domain {
             (Self::None, _) => Ok(other.clone()),
             (_, Self::None) => Ok(self.clone()),
             (Self::All, _) | (_, Self::All) => Ok(Self::All),
+            (Self::Pattern(ref self_val_set), Self::Pattern(ref other_val_set))
+                if self_val_set.patterns.len() == 1 && other_val_set.patterns.len() == 1 =>
+            {
+                let pattern = self_val_set
+                    .patterns
+                    .iter()
+                    .chain(other_val_set.patterns.iter())
+                    .map(|p| format!("(?:{p})"))
+                    .collect::<Vec<_>>()
+                    .join("|");
+                Ok(Self::of_pattern(&pattern))
+            }
+            (Self::Pattern(_), _) | (_, Self::Pattern(_)) => Ok(Self::All),
             _ => Err(Error::Internal {
                 err: "mismatched ValueSet type".to_string(),
             }),
@@ -1401,6 +1550,7 @@ impl Display for Domain {
         match self {
             Domain::Range(s) => write!(f, "range({s})"),
             Domain::Equtable(s) => write!(f, "equtable({s})"),
+            Domain::Pattern(s) => write!(f, "pattern({s})"),
             Domain::None => write!(f, "none"),
             Domain::All => write!(f, "all"),
         }
@@ -1806,7 +1956,7 @@ impl QueryExpr {
     }
 }
 
-pub fn encode_agg(agg: &Option<Vec<TableColumn>>) -> Result<Vec<u8>> {
+pub fn encode_agg(agg: &Option<PushedAggregate>) -> Result<Vec<u8>> {
     let d = bincode::serialize(agg).map_err(|err| Error::InvalidSerdeMessage {
         err: err.to_string(),
     })?;
@@ -1814,8 +1964,8 @@ pub fn encode_agg(agg: &Option<Vec<TableColumn>>) -> Result<Vec<u8>> {
     Ok(d)
 }
 
-pub fn decode_agg(buf: &[u8]) -> Result<Option<Vec<TableColumn>>> {
-    let args = bincode::deserialize::<Option<Vec<TableColumn>>>(buf).map_err(|err| {
+pub fn decode_agg(buf: &[u8]) -> Result<Option<PushedAggregate>> {
+    let args = bincode::deserialize::<Option<PushedAggregate>>(buf).map_err(|err| {
         Error::InvalidSerdeMessage {
             err: err.to_string(),
         }
@@ -1824,9 +1974,81 @@ pub fn decode_agg(buf: &[u8]) -> Result<Option<Vec<TableColumn>>> {
     Ok(args)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Aggregate function pushed down to tskv, the parameter is the name of a field column.
+///
+/// Only `count`, `min` and `max` may be answered from the statistics of pages, the others
+/// are always calculated from the decoded values, page statistics have no sum or the values
+/// at the min and max time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PushedAggregateFunction {
     Count(String),
+    Min(String),
+    Max(String),
+    Sum(String),
+    /// Outputs two columns: the time and the value of the first non-null value.
+    First(String),
+    /// Outputs two columns: the time and the value of the last non-null value.
+    Last(String),
+}
+
+impl PushedAggregateFunction {
+    pub fn column(&self) -> &str {
+        match self {
+            Self::Count(c)
+            | Self::Min(c)
+            | Self::Max(c)
+            | Self::Sum(c)
+            | Self::First(c)
+            | Self::Last(c) => c,
+        }
+    }
+
+    /// Number of the columns of the partial aggregation result.
+    pub fn output_columns(&self) -> usize {
+        match self {
+            Self::First(_) | Self::Last(_) => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// Tumbling window of `time_window(time, duration[, duration[, start]])`,
+/// in the time unit of the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PushedTimeWindow {
+    pub duration: i64,
+    /// `start % duration`
+    pub offset: i64,
+}
+
+impl PushedTimeWindow {
+    pub fn new(duration: i64, start: i64) -> Self {
+        Self {
+            duration,
+            offset: start % duration,
+        }
+    }
+
+    /// Start of the window containing the timestamp, the same as `time_window`.
+    pub fn window_start(&self, ts: Timestamp) -> Timestamp {
+        ts - (ts - self.offset + self.duration) % self.duration
+    }
+}
+
+/// Aggregations pushed down to the scan of tskv.
+///
+/// The scan outputs partial aggregations for each series (and window if any),
+/// the columns are the group keys (time and tags) followed by the results of `functions`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PushedAggregate {
+    pub functions: Vec<PushedAggregateFunction>,
+    pub window: Option<PushedTimeWindow>,
+}
+
+impl PushedAggregate {
+    pub fn output_columns(&self) -> usize {
+        self.functions.iter().map(|f| f.output_columns()).sum()
+    }
 }
 
 #[cfg(test)]
@@ -2151,4 +2373,33 @@ mod tests {
 
         assert_eq!(wrap.0.expr_type, wrap1.0.expr_type);
     }
+
+    #[test]
+    fn test_pushed_time_window() {
+        let window = PushedTimeWindow::new(10, 0);
+        assert_eq!(window.window_start(0), 0);
+        assert_eq!(window.window_start(9), 0);
+        assert_eq!(window.window_start(10), 10);
+        assert_eq!(window.window_start(-1), -10);
+
+        let window = PushedTimeWindow::new(10, 23);
+        assert_eq!(window.offset, 3);
+        assert_eq!(window.window_start(3), 3);
+        assert_eq!(window.window_start(12), 3);
+        assert_eq!(window.window_start(13), 13);
+        assert_eq!(window.window_start(2), -7);
+
+        let agg = PushedAggregate {
+            functions: vec![
+                PushedAggregateFunction::Min("a".to_string()),
+                PushedAggregateFunction::First("b".to_string()),
+            ],
+            window: Some(window),
+        };
+        assert_eq!(agg.output_columns(), 3);
+        assert_eq!(
+            decode_agg(&encode_agg(&Some(agg.clone())).unwrap()).unwrap(),
+            Some(agg)
+        );
+    }
 }
@@ -5,11 +5,12 @@ use datafusion::arrow::datatypes::DataType;
 use datafusion::common::tree_node::{TreeNode, TreeNodeVisitor, VisitRecursion};
 use datafusion::common::Result as DFResult;
 use datafusion::error::DataFusionError;
-use datafusion::logical_expr::{BinaryExpr, Operator};
+use datafusion::logical_expr::expr::ScalarFunction;
+use datafusion::logical_expr::{BinaryExpr, BuiltinScalarFunction, Like, Operator};
 use datafusion::prelude::{Column, Expr};
 use datafusion::scalar::ScalarValue;
 
-use super::domain::{ColumnDomains, Domain, Range};
+use super::domain::{push_regex_escaped, ColumnDomains, Domain, Range};
 use crate::schema::TIME_FIELD_NAME;
 
 type Result<T> = result::Result<T, DataFusionError>;
@@ -169,11 +170,7 @@ impl TreeNodeVisitor for RowExpressionToDomainsVisitor<'_> {
             // | Expr::QualifiedWildcard { .. }
             // | Expr::GetIndexedField { .. } => {}
             Expr::Column(_) | Expr::Literal(_) => Ok(VisitRecursion::Continue),
-            Expr::BinaryExpr(BinaryExpr {
-                left: _,
-                op,
-                right: _,
-            }) => {
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
                 match op {
                     Operator::Eq
                     | Operator::NotEq
@@ -186,6 +183,15 @@ impl TreeNodeVisitor for RowExpressionToDomainsVisitor<'_> {
                         // support
                         Ok(VisitRecursion::Continue)
                     }
+                    Operator::RegexMatch | Operator::RegexIMatch => {
+                        let domains = Self::regex_match_to_column_domains(
+                            left,
+                            right,
+                            *op == Operator::RegexIMatch,
+                        );
+                        self.ctx.current_domain_stack.push_back(domains);
+                        Ok(VisitRecursion::Skip)
+                    }
                     _ => {
                         // not support
                         self.ctx
@@ -195,9 +201,20 @@ impl TreeNodeVisitor for RowExpressionToDomainsVisitor<'_> {
                     }
                 }
             }
+            Expr::Like(like) => {
+                let domains = Self::like_to_column_domains(like);
+                self.ctx.current_domain_stack.push_back(domains);
+                Ok(VisitRecursion::Skip)
+            }
+            Expr::ScalarFunction(ScalarFunction { fun, args })
+                if *fun == BuiltinScalarFunction::StartsWith =>
+            {
+                let domains = Self::starts_with_to_column_domains(args);
+                self.ctx.current_domain_stack.push_back(domains);
+                Ok(VisitRecursion::Skip)
+            }
             // TODO Currently not supported, follow-up support needs to implement the corresponding expression in post_visit
-            Expr::Like(_)
-            | Expr::ILike(_)
+            Expr::ILike(_)
             | Expr::SimilarTo(_)
             | Expr::Not(_)
             | Expr::IsNotNull(_)
@@ -330,6 +347,89 @@ impl RowExpressionToDomainsVisitor<'_> {
             ctx.current_domain_stack.push_back(domains);
         }
     }
+    /// Convert `column LIKE 'pattern'` to the range of strings starting with the literal prefix
+    /// if there is only a trailing '%', otherwise to the regular expression of the pattern.
+    fn like_to_column_domains(like: &Like) -> ColumnDomains<Column> {
+        match (like.negated, like.expr.as_ref(), like.pattern.as_ref()) {
+            (false, Expr::Column(column), Expr::Literal(ScalarValue::Utf8(Some(pattern)))) => {
+                let domain = like_pattern_to_domain(pattern, like.escape_char);
+                ColumnDomains::of(column.to_owned(), &domain)
+            }
+            _ => ColumnDomains::all(),
+        }
+    }
+    /// Convert `starts_with(column, 'prefix')` to the range of strings starting with the prefix.
+    fn starts_with_to_column_domains(args: &[Expr]) -> ColumnDomains<Column> {
+        match args {
+            [Expr::Column(column), Expr::Literal(ScalarValue::Utf8(Some(prefix)))] => {
+                ColumnDomains::of(column.to_owned(), &Domain::of_prefix(prefix))
+            }
+            _ => ColumnDomains::all(),
+        }
+    }
+    /// Convert `column ~ 'pattern'` and `column ~* 'pattern'` to the regular expression.
+    fn regex_match_to_column_domains(
+        left: &Expr,
+        right: &Expr,
+        case_insensitive: bool,
+    ) -> ColumnDomains<Column> {
+        match (left, right) {
+            (Expr::Column(column), Expr::Literal(ScalarValue::Utf8(Some(pattern)))) => {
+                let domain = if case_insensitive {
+                    Domain::of_pattern(&format!("(?i){pattern}"))
+                } else {
+                    Domain::of_pattern(pattern)
+                };
+                ColumnDomains::of(column.to_owned(), &domain)
+            }
+            _ => ColumnDomains::all(),
+        }
+    }
+}
+
+/// Convert the pattern of LIKE to a domain:
+///
+/// - 'abc' to the value 'abc'
+/// - 'abc%' to the range of strings starting with 'abc'
+/// - others like 'a%b_c' to the regular expression '^a(?s:.*)b(?s:.)c$'
+fn like_pattern_to_domain(pattern: &str, escape_char: Option<char>) -> Domain {
+    // Backslash is the default escape character of LIKE.
+    let escape_char = escape_char.unwrap_or('\\');
+    let mut prefix = String::new();
+    let mut regex = String::from("^");
+    let mut wildcards = 0;
+    let mut ends_with_percent = false;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            c if c == escape_char => match chars.next() {
+                Some(c) => c,
+                None => return Domain::All,
+            },
+            '%' | '_' => {
+                wildcards += 1;
+                ends_with_percent = c == '%';
+                regex.push_str(if c == '%' { "(?s:.*)" } else { "(?s:.)" });
+                continue;
+            }
+            c => c,
+        };
+        if wildcards == 0 {
+            prefix.push(c);
+        }
+        ends_with_percent = false;
+        push_regex_escaped(&mut regex, c);
+    }
+    regex.push('$');
+
+    match wildcards {
+        0 => {
+            let value = ScalarValue::Utf8(Some(prefix));
+            Domain::of_ranges(&[Range::eq(&DataType::Utf8, &value)]).unwrap_or(Domain::All)
+        }
+        1 if ends_with_percent => Domain::of_prefix(&prefix),
+        _ => Domain::of_pattern(&regex),
+    }
 }
 
 #[derive(Clone, Default)]
@@ -560,7 +660,7 @@ mod tests {
     use std::ops::Add;
 
     use chrono::{Duration, NaiveDate};
-    use datafusion::logical_expr::expr_fn::col;
+    use datafusion::logical_expr::expr_fn::{col, starts_with};
     use datafusion::logical_expr::{binary_expr, Like};
     use datafusion::prelude::{and, in_list, lit, or, random};
 
@@ -749,6 +849,7 @@ mod tests {
     /// eg.
     ///   s1 like '%上证180' and time >= '2022-10-10 00:00:00'
     ///   ===>
+    ///   s1: /^(?s:.*)上证180$/
     ///   time: ['2022-10-10 00:00:00', _)
     #[test]
     fn test_simple_and_to_domain_0() {
@@ -771,7 +872,11 @@ mod tests {
 
         let i1_domain = Domain::of_ranges(&[i1]).unwrap();
 
-        let except_column_domains = ColumnDomains::of(Column::from_name("time"), &i1_domain);
+        let mut except_column_domains = ColumnDomains::of(Column::from_name("time"), &i1_domain);
+        except_column_domains.insert_or_intersect(
+            Column::from_name("s1"),
+            &Domain::of_pattern("^(?s:.*)上证180$"),
+        );
 
         let result = get_domains(&and);
 
@@ -792,6 +897,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pattern_to_domain() {
+        let like = |pattern: &str, negated: bool| {
+            Expr::Like(Like::new(
+                negated,
+                Box::new(col("host")),
+                Box::new(lit(pattern)),
+                None,
+            ))
+        };
+        let eq = |value: &str| {
+            let value = ScalarValue::Utf8(Some(value.to_string()));
+            Domain::of_ranges(&[Range::eq(&DataType::Utf8, &value)]).unwrap()
+        };
+        let host = |domain: &Domain| ColumnDomains::of(Column::from_name("host"), domain);
+
+        for (expr, except_domain) in [
+            (like("web", false), eq("web")),
+            (like("web\\%", false), eq("web%")),
+            (like("web%", false), Domain::of_prefix("web")),
+            (
+                like("web_0%", false),
+                Domain::of_pattern("^web(?s:.)0(?s:.*)$"),
+            ),
+            (
+                starts_with(col("host"), lit("web")),
+                Domain::of_prefix("web"),
+            ),
+            (
+                binary_expr(col("host"), Operator::RegexMatch, lit("^web")),
+                Domain::of_pattern("^web"),
+            ),
+            (
+                binary_expr(col("host"), Operator::RegexIMatch, lit("^web")),
+                Domain::of_pattern("(?i)^web"),
+            ),
+            (like("web%", true), Domain::All),
+        ] {
+            let column_domain = get_domains(&expr).unwrap();
+            let except_column_domains = if except_domain == Domain::All {
+                ColumnDomains::all()
+            } else {
+                host(&except_domain)
+            };
+            assert_eq!(column_domain, except_column_domains, "{expr}");
+        }
+
+        // 'web' <= host < 'wec'
+        let except_range = Range::gelt(
+            &DataType::Utf8,
+            &ScalarValue::Utf8(Some("web".to_string())),
+            &ScalarValue::Utf8(Some("wec".to_string())),
+        );
+        assert_eq!(
+            Domain::of_prefix("web"),
+            Domain::of_ranges(&[except_range]).unwrap()
+        );
+
+        match Domain::of_pattern("^web\\-0[1-9]+") {
+            Domain::Pattern(set) => assert_eq!(set.literal_prefix(), "web-0"),
+            other => panic!("excepted pattern, found {other}"),
+        }
+
+        // Patterns are kept only if intersected with patterns.
+        let expr = and(
+            binary_expr(col("host"), Operator::RegexMatch, lit("^web")),
+            like("web_1", false),
+        );
+        let column_domain = get_domains(&expr).unwrap();
+        match column_domain
+            .domains()
+            .unwrap()
+            .get(&Column::from_name("host"))
+        {
+            Some(Domain::Pattern(set)) => assert_eq!(set.patterns().into_iter().count(), 2),
+            other => panic!("excepted pattern, found {other:?}"),
+        }
+        let expr = and(
+            binary_expr(col("host"), Operator::RegexMatch, lit("^web")),
+            like("web%", false),
+        );
+        assert_eq!(get_domains(&expr).unwrap(), host(&Domain::of_prefix("web")));
+        let expr = or(
+            binary_expr(col("host"), Operator::RegexMatch, lit("^web")),
+            like("web%", false),
+        );
+        assert_eq!(get_domains(&expr).unwrap(), host(&Domain::All));
+    }
+
     /// simple and test - 1
     /// eg.
     ///   i1 < -1000000 and i2 = 2147483647 and i3 > 3333333333333333
@@ -9,7 +9,7 @@
 
 use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::{self, Display};
 use std::mem::size_of_val;
 use std::str::FromStr;
@@ -85,6 +85,9 @@ pub enum ResourceOperator {
         Vec<Vec<u8>>,
         Vec<ReplicationSet>,
     ),
+
+    // scheduled task
+    ScheduledTask(ScheduledTask),
 }
 
 impl fmt::Display for ResourceOperator {
@@ -97,6 +100,7 @@ impl fmt::Display for ResourceOperator {
             ResourceOperator::AddColumn(..) => write!(f, "AddColumn"),
             ResourceOperator::AlterColumn(..) => write!(f, "AlterColumn"),
             ResourceOperator::UpdateTagValue(..) => write!(f, "UpdateTagValue"),
+            ResourceOperator::ScheduledTask(..) => write!(f, "ScheduledTask"),
         }
     }
 }
@@ -226,6 +230,31 @@ impl ResourceInfo {
     }
 }
 
+/// A statement run periodically by the resource manager, created by `CREATE TASK`.
+///
+/// Only `COPY INTO <location>` is supported, each run only exports the rows whose
+/// time is not less than `high_water_mark`, up to the maximum time of the data.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledTask {
+    pub tenant: String,
+    pub database: String,
+    pub name: String,
+    // the user who runs the statement
+    pub owner: String,
+    pub schedule: Duration,
+    pub statement: String,
+    // the maximum time of the exported rows plus 1ns, None means nothing is exported yet
+    pub high_water_mark: Option<Timestamp>,
+    pub last_run_time: Option<Timestamp>,
+}
+
+impl ScheduledTask {
+    /// The name of the `ResourceInfo` of a task.
+    pub fn resource_name(tenant: &str, name: &str) -> String {
+        format!("{}-task-{}", tenant, name)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum TableSchema {
     TsKvTableSchema(TskvTableSchemaRef),
@@ -326,6 +355,112 @@ pub struct TskvTableSchema {
     columns: Vec<TableColumn>,
     //ColumnName -> ColumnsIndex
     columns_index: HashMap<String, usize>,
+
+    #[serde(default)]
+    options: TableOptions,
+}
+
+/// Layout of [`TskvTableSchema`] before [`TableOptions`] was added,
+/// only used to decode the schemas persisted in old files by bincode.
+#[derive(Deserialize, Debug, Clone)]
+pub struct LegacyTskvTableSchema {
+    tenant: String,
+    db: String,
+    name: String,
+    schema_id: SchemaId,
+    next_column_id: ColumnId,
+    columns: Vec<TableColumn>,
+    columns_index: HashMap<String, usize>,
+}
+
+impl From<LegacyTskvTableSchema> for TskvTableSchema {
+    fn from(schema: LegacyTskvTableSchema) -> Self {
+        Self {
+            tenant: schema.tenant,
+            db: schema.db,
+            name: schema.name,
+            schema_id: schema.schema_id,
+            next_column_id: schema.next_column_id,
+            columns: schema.columns,
+            columns_index: schema.columns_index,
+            options: TableOptions::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableOptions {
+    // tag or string field columns with bloom filters in tsm files
+    #[serde(default)]
+    bloom_filter_columns: BTreeSet<ColumnId>,
+    #[serde(default)]
+    dedup_policy: DedupPolicy,
+}
+
+impl TableOptions {
+    pub fn bloom_filter_columns(&self) -> &BTreeSet<ColumnId> {
+        &self.bloom_filter_columns
+    }
+
+    pub fn with_bloom_filter_columns(&mut self, columns: BTreeSet<ColumnId>) {
+        self.bloom_filter_columns = columns;
+    }
+
+    pub fn dedup_policy(&self) -> DedupPolicy {
+        self.dedup_policy
+    }
+
+    pub fn with_dedup_policy(&mut self, policy: DedupPolicy) {
+        self.dedup_policy = policy;
+    }
+}
+
+/// How the rows of a series with the same timestamp are deduplicated,
+/// in memcache, flush, compaction and the merging of query.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DedupPolicy {
+    /// The later row replaces the whole earlier row.
+    LastWriteWins,
+    /// The earlier row is kept, the later rows are discarded.
+    KeepFirst,
+    /// Non-null fields of the later row replace the fields of the earlier row.
+    #[default]
+    Merge,
+}
+
+impl DedupPolicy {
+    pub fn new(text: &str) -> Option<Self> {
+        match text.to_uppercase().as_str() {
+            "LAST_WRITE_WINS" => Some(Self::LastWriteWins),
+            "KEEP_FIRST" => Some(Self::KeepFirst),
+            "MERGE" => Some(Self::Merge),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::LastWriteWins => "LAST_WRITE_WINS",
+            Self::KeepFirst => "KEEP_FIRST",
+            Self::Merge => "MERGE",
+        }
+    }
+
+    /// The value of a field of two rows with the same timestamp,
+    /// `earlier` is written before `later`.
+    pub fn dedup_value<T>(&self, earlier: Option<T>, later: Option<T>) -> Option<T> {
+        match self {
+            Self::LastWriteWins => later,
+            Self::KeepFirst => earlier,
+            Self::Merge => later.or(earlier),
+        }
+    }
+}
+
+impl Display for DedupPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 impl PartialOrd for TskvTableSchema {
@@ -350,6 +485,7 @@ impl Default for TskvTableSchema {
             next_column_id: 0,
             columns: Default::default(),
             columns_index: Default::default(),
+            options: Default::default(),
         }
     }
 }
@@ -388,9 +524,27 @@ impl TskvTableSchema {
             next_column_id: columns.len() as ColumnId,
             columns,
             columns_index,
+            options: TableOptions::default(),
         }
     }
 
+    pub fn options(&self) -> &TableOptions {
+        &self.options
+    }
+
+    pub fn set_options(&mut self, options: TableOptions) {
+        self.options = options;
+    }
+
+    /// Whether values of the column are indexed by bloom filters in tsm files.
+    pub fn has_bloom_filter(&self, column_id: ColumnId) -> bool {
+        self.options.bloom_filter_columns.contains(&column_id)
+    }
+
+    pub fn dedup_policy(&self) -> DedupPolicy {
+        self.options.dedup_policy
+    }
+
     /// only for mock!!!
     pub fn new_test() -> Self {
         TskvTableSchema::new(
@@ -416,7 +570,8 @@ impl TskvTableSchema {
     /// drop column if exists
     pub fn drop_column(&mut self, col_name: &str) {
         if let Some(id) = self.columns_index.get(col_name) {
-            self.columns.remove(*id);
+            let column = self.columns.remove(*id);
+            self.options.bloom_filter_columns.remove(&column.id);
         }
         let columns_index = self
             .columns
@@ -931,6 +1086,16 @@ pub struct DatabaseOptions {
     precision: Option<Precision>,
 
     db_is_hidden: bool,
+
+    #[serde(default)]
+    rollups: Vec<RollupPolicy>,
+
+    // max number of series of the database
+    #[serde(default)]
+    max_series: Option<u64>,
+    // max number of series of each table in the database
+    #[serde(default)]
+    max_series_per_table: Option<u64>,
 }
 
 impl DatabaseOptions {
@@ -960,6 +1125,9 @@ impl DatabaseOptions {
             replica,
             precision,
             db_is_hidden: false,
+            rollups: vec![],
+            max_series: None,
+            max_series_per_table: None,
         }
     }
 
@@ -1027,6 +1195,22 @@ impl DatabaseOptions {
         self.precision = Some(precision)
     }
 
+    pub fn max_series(&self) -> &Option<u64> {
+        &self.max_series
+    }
+
+    pub fn with_max_series(&mut self, max_series: u64) {
+        self.max_series = Some(max_series);
+    }
+
+    pub fn max_series_per_table(&self) -> &Option<u64> {
+        &self.max_series_per_table
+    }
+
+    pub fn with_max_series_per_table(&mut self, max_series_per_table: u64) {
+        self.max_series_per_table = Some(max_series_per_table);
+    }
+
     pub fn get_db_is_hidden(&self) -> bool {
         self.db_is_hidden
     }
@@ -1034,6 +1218,211 @@ impl DatabaseOptions {
     pub fn set_db_is_hidden(&mut self, db_is_hidden: bool) {
         self.db_is_hidden = db_is_hidden;
     }
+
+    pub fn rollups(&self) -> &[RollupPolicy] {
+        &self.rollups
+    }
+
+    /// Add a rollup policy, returns false if there is already a policy with the same interval.
+    pub fn add_rollup(&mut self, rollup: RollupPolicy) -> bool {
+        if self.rollups.iter().any(|r| r.interval == rollup.interval) {
+            return false;
+        }
+        self.rollups.push(rollup);
+        self.rollups.sort_by_key(|r| r.interval.to_nanoseconds());
+        true
+    }
+
+    /// Drop the rollup policy of the interval, returns false if it does not exist.
+    pub fn drop_rollup(&mut self, interval: &Duration) -> bool {
+        let len = self.rollups.len();
+        self.rollups.retain(|r| &r.interval != interval);
+        self.rollups.len() != len
+    }
+
+    /// Whether the table is written by a rollup policy of this database.
+    pub fn is_rollup_table(&self, table: &str) -> bool {
+        self.rollups
+            .iter()
+            .any(|r| table.ends_with(&format!("{}{}", ROLLUP_TABLE_INFIX, r.interval_suffix())))
+    }
+}
+
+pub const ROLLUP_TABLE_INFIX: &str = "_rollup_";
+
+/// Aggregations that a [`RollupPolicy`] materializes for each field.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RollupAgg {
+    Min,
+    Max,
+    Sum,
+    Count,
+    Mean,
+    First,
+    Last,
+}
+
+impl RollupAgg {
+    pub fn new(text: &str) -> Option<Self> {
+        match text.to_uppercase().as_str() {
+            "MIN" => Some(Self::Min),
+            "MAX" => Some(Self::Max),
+            "SUM" => Some(Self::Sum),
+            "COUNT" => Some(Self::Count),
+            "MEAN" | "AVG" => Some(Self::Mean),
+            "FIRST" => Some(Self::First),
+            "LAST" => Some(Self::Last),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Sum => "sum",
+            Self::Count => "count",
+            Self::Mean => "mean",
+            Self::First => "first",
+            Self::Last => "last",
+        }
+    }
+
+    /// Only `count`, `first` and `last` are available for non-numeric fields.
+    pub fn support_type(&self, value_type: &ValueType) -> bool {
+        match self {
+            Self::Count | Self::First | Self::Last => matches!(
+                value_type,
+                ValueType::Float
+                    | ValueType::Integer
+                    | ValueType::Unsigned
+                    | ValueType::Boolean
+                    | ValueType::String
+            ),
+            Self::Min | Self::Max | Self::Sum | Self::Mean => matches!(
+                value_type,
+                ValueType::Float | ValueType::Integer | ValueType::Unsigned
+            ),
+        }
+    }
+
+    /// The type of the rollup column of a field.
+    pub fn value_type(&self, field_type: &ValueType) -> ValueType {
+        match self {
+            Self::Count => ValueType::Integer,
+            Self::Mean => ValueType::Float,
+            _ => field_type.clone(),
+        }
+    }
+
+    /// Column name of the aggregated field in the rollup table, such as `max_usage`.
+    pub fn column_name(&self, field: &str) -> String {
+        format!("{}_{}", self.as_str(), field)
+    }
+}
+
+impl Display for RollupAgg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Downsample each table of a database into a sibling table `<table>_rollup_<interval>`,
+/// which has the tags of the source table and a column `<agg>_<field>` for each aggregation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RollupPolicy {
+    interval: Duration,
+    // data keep time of the rollup table
+    keep: Duration,
+    aggs: Vec<RollupAgg>,
+}
+
+impl RollupPolicy {
+    pub fn new(interval: Duration, keep: Duration, mut aggs: Vec<RollupAgg>) -> Self {
+        aggs.sort();
+        aggs.dedup();
+        Self {
+            interval,
+            keep,
+            aggs,
+        }
+    }
+
+    pub fn interval(&self) -> &Duration {
+        &self.interval
+    }
+
+    pub fn keep(&self) -> &Duration {
+        &self.keep
+    }
+
+    pub fn aggs(&self) -> &[RollupAgg] {
+        &self.aggs
+    }
+
+    /// Aggregations stored in the rollup table, `mean` is stored with `count`
+    /// so that it can be merged into coarser windows.
+    pub fn materialized_aggs(&self) -> Vec<RollupAgg> {
+        let mut aggs = self.aggs.clone();
+        if aggs.contains(&RollupAgg::Mean) && !aggs.contains(&RollupAgg::Count) {
+            aggs.push(RollupAgg::Count);
+            aggs.sort();
+        }
+        aggs
+    }
+
+    pub fn has_agg(&self, agg: RollupAgg) -> bool {
+        self.materialized_aggs().contains(&agg)
+    }
+
+    /// Interval in the short form, such as `1h`.
+    pub fn interval_suffix(&self) -> String {
+        let unit = match self.interval.unit {
+            DurationUnit::Minutes => "m",
+            DurationUnit::Hour => "h",
+            DurationUnit::Day => "d",
+            DurationUnit::Inf => "inf",
+        };
+        format!("{}{}", self.interval.time_num, unit)
+    }
+
+    pub fn rollup_table_name(&self, table: &str) -> String {
+        format!("{}{}{}", table, ROLLUP_TABLE_INFIX, self.interval_suffix())
+    }
+}
+
+impl Display for RollupPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ROLLUP {} KEEP {} AGG ({})",
+            self.interval,
+            self.keep,
+            self.aggs
+                .iter()
+                .map(|a| a.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+}
+
+/// Time range in nanoseconds `[start, end)` which is completely aggregated into a rollup table,
+/// it's stored in meta so that all query nodes read the rollup table by the same range.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RollupRange {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl RollupRange {
+    /// The range never goes back, the job of each query node may update it with a stale view.
+    pub fn merge(&self, other: &RollupRange) -> RollupRange {
+        RollupRange {
+            start: self.start.max(other.start),
+            end: self.end.max(other.end),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -1167,10 +1556,11 @@ impl Duration {
                 return None;
             }
         };
-        let time_unit = match unit.to_uppercase().as_str() {
-            "D" => DurationUnit::Day,
-            "H" => DurationUnit::Hour,
-            "M" => DurationUnit::Minutes,
+        let (time_num, time_unit) = match unit.to_uppercase().as_str() {
+            "Y" => (time_num.saturating_mul(365), DurationUnit::Day),
+            "D" => (time_num, DurationUnit::Day),
+            "H" => (time_num, DurationUnit::Hour),
+            "M" => (time_num, DurationUnit::Minutes),
             _ => return None,
         };
         Some(Duration {
@@ -1261,6 +1651,9 @@ pub struct TenantOptions {
     drop_after: Option<Duration>,
     // None means now
     tenant_is_hidden: bool,
+    /// Max memory in bytes of the cached query results of the tenant on each node,
+    /// None means the `query.query_cache_max_memory` of the config.
+    query_cache_max_memory: Option<u64>,
 }
 
 impl From<TenantOptions> for TenantOptionsBuilder {
@@ -1275,6 +1668,9 @@ impl From<TenantOptions> for TenantOptionsBuilder {
         if let Some(drop_after) = value.get_drop_after() {
             builder.drop_after(drop_after);
         }
+        if let Some(max_memory) = value.get_query_cache_max_memory() {
+            builder.query_cache_max_memory(max_memory);
+        }
         builder.tenant_is_hidden(false);
         builder
     }
@@ -1290,6 +1686,9 @@ impl TenantOptionsBuilder {
     pub fn unset_drop_after(&mut self) {
         self.drop_after = None;
     }
+    pub fn unset_query_cache_max_memory(&mut self) {
+        self.query_cache_max_memory = None;
+    }
 }
 
 impl TenantOptions {
@@ -1318,6 +1717,10 @@ impl TenantOptions {
     pub fn get_drop_after(&self) -> Option<Duration> {
         self.drop_after.clone()
     }
+
+    pub fn get_query_cache_max_memory(&self) -> Option<u64> {
+        self.query_cache_max_memory
+    }
 }
 
 impl Display for TenantOptions {
@@ -1,5 +1,6 @@
 use std::str::FromStr;
 
+use chrono::{SecondsFormat, TimeZone, Utc};
 use datafusion::datasource::file_format::file_type::{FileCompressionType, FileType};
 
 use crate::arrow::arrow_data_type_to_sql_data_type;
@@ -8,8 +9,8 @@ use crate::auth::user::UserDesc;
 use crate::datafusion::SqlParserValue;
 use crate::oid::{Identifier, Oid};
 use crate::schema::{
-    ColumnType, DatabaseSchema, DurationUnit, ExternalTableSchema, StreamTable, TableSchema,
-    Tenant, TskvTableSchema,
+    ColumnType, DatabaseSchema, DedupPolicy, DurationUnit, ExternalTableSchema, StreamTable,
+    TableSchema, Tenant, TskvTableSchema,
 };
 use crate::Error;
 
@@ -55,8 +56,15 @@ impl ToDDLSql for Tenant {
                     .map(|config| ("_limiter", SqlParserValue::SingleQuotedString(config)))
             })
             .transpose()?;
+        let query_cache_max_memory = option.get_query_cache_max_memory().map(|a| {
+            (
+                "query_cache_max_memory",
+                SqlParserValue::Number(a.to_string(), false),
+            )
+        });
         sql_opts.push(comment);
         sql_opts.push(limit);
+        sql_opts.push(query_cache_max_memory);
         let str = sql_option_to_sql_str(sql_opts);
         if !str.is_empty() {
             res.push_str("with ");
@@ -99,6 +107,15 @@ impl ToDDLSql for UserDesc {
         let granted_admin = option
             .granted_admin()
             .map(|v| ("granted_admin", SqlParserValue::Boolean(v)));
+        let password_expire_at = option.password_expire_at().map(|v| {
+            (
+                "password_expire_at",
+                SqlParserValue::SingleQuotedString(
+                    Utc.timestamp_nanos(v)
+                        .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+                ),
+            )
+        });
 
         let sql_opts = vec![
             hash_password,
@@ -106,6 +123,7 @@ impl ToDDLSql for UserDesc {
             must_change_password,
             rsa_public_key,
             granted_admin,
+            password_expire_at,
         ];
         let opt_sql = sql_option_to_sql_str(sql_opts);
         if !opt_sql.is_empty() {
@@ -210,6 +228,28 @@ pub fn role_to_sql(role: &CustomTenantRole<Oid>) -> Result<Vec<String>> {
 // GRANT privilege
 pub fn privilege_to_sql(role: &CustomTenantRole<Oid>) -> Vec<String> {
     let privileges = role.additional_privileges();
+    let table_privileges = role.table_privileges().iter().map(|p| {
+        let columns = p
+            .columns
+            .as_ref()
+            .map(|columns| {
+                let columns = columns
+                    .iter()
+                    .map(|c| format!("\"{}\"", c))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(" ({})", columns)
+            })
+            .unwrap_or_default();
+        format!(
+            "grant {} on table \"{}\".\"{}\"{} to \"{}\";",
+            p.privilege.as_str(),
+            p.database,
+            p.table,
+            columns,
+            role.name()
+        )
+    });
     privileges
         .iter()
         .map(|(d, p)| {
@@ -220,6 +260,24 @@ pub fn privilege_to_sql(role: &CustomTenantRole<Oid>) -> Vec<String> {
                 role.name()
             )
         })
+        .chain(table_privileges)
+        .collect()
+}
+
+// CREATE POLICY, must be after the tables are created
+pub fn row_policy_to_sql(role: &CustomTenantRole<Oid>) -> Vec<String> {
+    role.row_policies()
+        .iter()
+        .map(|p| {
+            format!(
+                "create policy \"{}\" on \"{}\".\"{}\" for role \"{}\" using ({});",
+                p.name,
+                p.database,
+                p.table,
+                role.name(),
+                p.predicate
+            )
+        })
         .collect()
 }
 
@@ -299,7 +357,30 @@ impl ToDDLSql for TskvTableSchema {
             .collect::<Vec<_>>()
             .join(", ");
         res.push_str(format!("tags ({})", tags).as_str());
-        res.push_str(");");
+        res.push(')');
+
+        let bloom_filter_columns = self
+            .options()
+            .bloom_filter_columns()
+            .iter()
+            .filter_map(|id| self.column_name(*id))
+            .map(|name| format!("\"{}\"", name))
+            .collect::<Vec<_>>();
+        let mut options = vec![];
+        if !bloom_filter_columns.is_empty() {
+            options.push(format!("bloom_filter({})", bloom_filter_columns.join(", ")));
+        }
+        let dedup_policy = self.dedup_policy();
+        if dedup_policy != DedupPolicy::default() {
+            options.push(format!(
+                "dedup policy {}",
+                dedup_policy.as_str().to_lowercase()
+            ));
+        }
+        if !options.is_empty() {
+            res.push_str(format!(" with {}", options.join(" ")).as_str());
+        }
+        res.push(';');
         Ok(res)
     }
 }
@@ -567,6 +648,19 @@ remote_bucket = {max = 100, initial = 0, refill = 100, interval = 100}
         assert_eq!(
             sql,
             r#"create user "test" with hash_password='123', comment='test', must_change_password=true, rsa_public_key='aaa', granted_admin=true;"#
+        );
+
+        let user_option = UserOptionsBuilder::default()
+            .hash_password("123")
+            .password_expire_at(1_700_000_000_000_000_000_i64)
+            .build()
+            .unwrap();
+        let desc = UserDesc::new(0_u128, "test".to_string(), user_option, false);
+
+        let sql = desc.to_ddl_sql(false).unwrap();
+        assert_eq!(
+            sql,
+            r#"create user "test" with hash_password='123', password_expire_at='2023-11-14T22:13:20Z';"#
         )
     }
 
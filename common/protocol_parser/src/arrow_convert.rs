@@ -0,0 +1,190 @@
+use std::io::Cursor;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{Field, Schema};
+use datafusion::arrow::ipc::reader::StreamReader;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use models::schema::{ColumnType, TskvTableSchema, TIME_FIELD_NAME};
+
+use crate::{Error, Result};
+
+/// Decode record batches from the arrow ipc streaming format.
+pub fn arrow_stream_to_batches(data: Bytes) -> Result<Vec<RecordBatch>> {
+    let reader = StreamReader::try_new(Cursor::new(data), None).map_err(|e| Error::Common {
+        content: format!("Invalid arrow stream: {}", e),
+    })?;
+    reader
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| Error::Common {
+            content: format!("Invalid arrow stream: {}", e),
+        })
+}
+
+/// Decode record batches from a parquet file.
+pub fn parquet_to_batches(data: Bytes) -> Result<Vec<RecordBatch>> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(data)
+        .and_then(|builder| builder.build())
+        .map_err(|e| Error::Common {
+            content: format!("Invalid parquet file: {}", e),
+        })?;
+    reader
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| Error::Common {
+            content: format!("Invalid parquet file: {}", e),
+        })
+}
+
+/// Map the columns of a record batch to the columns of the table by name,
+/// and cast them to the data types of the table columns.
+///
+/// The time column and at least one field column are required, tag columns are optional.
+pub fn record_batch_to_table_schema(
+    batch: &RecordBatch,
+    table_schema: &TskvTableSchema,
+) -> Result<RecordBatch> {
+    let mut has_time = false;
+    let mut has_field = false;
+    let mut fields = Vec::with_capacity(batch.num_columns());
+    let mut columns = Vec::with_capacity(batch.num_columns());
+
+    for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+        let table_column = table_schema
+            .column(field.name())
+            .ok_or_else(|| Error::Common {
+                content: format!(
+                    "column {} not found in table {}",
+                    field.name(),
+                    table_schema.name
+                ),
+            })?;
+        match table_column.column_type {
+            ColumnType::Time(_) => has_time = true,
+            ColumnType::Field(_) => has_field = true,
+            ColumnType::Tag => {}
+        }
+
+        let target = Field::from(table_column);
+        let column = cast(column, target.data_type()).map_err(|e| Error::Common {
+            content: format!(
+                "column {} can't be cast from {} to {}: {}",
+                field.name(),
+                field.data_type(),
+                target.data_type(),
+                e
+            ),
+        })?;
+        // The nullability of the table columns is checked when writing.
+        fields.push(target.with_nullable(true));
+        columns.push(column);
+    }
+
+    if !has_time {
+        return Err(Error::Common {
+            content: format!(
+                "column {} not found in record batch of table {}",
+                TIME_FIELD_NAME, table_schema.name
+            ),
+        });
+    }
+    if !has_field {
+        return Err(Error::Common {
+            content: format!(
+                "no field column found in record batch of table {}",
+                table_schema.name
+            ),
+        });
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).map_err(|e| Error::Common {
+        content: format!("Invalid record batch: {}", e),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::{
+        Array, Float64Array, Int32Array, Int64Array, StringArray, TimestampNanosecondArray,
+    };
+    use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use datafusion::arrow::ipc::writer::StreamWriter;
+    use datafusion::arrow::record_batch::RecordBatch;
+    use models::schema::{ColumnType, TableColumn, TskvTableSchema};
+    use models::ValueType;
+
+    use super::{arrow_stream_to_batches, record_batch_to_table_schema};
+
+    fn table_schema() -> TskvTableSchema {
+        TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "air".to_string(),
+            vec![
+                TableColumn::new_time_column(0, TimeUnit::Nanosecond),
+                TableColumn::new_tag_column(1, "station".to_string()),
+                TableColumn::new(
+                    2,
+                    "temperature".to_string(),
+                    ColumnType::Field(ValueType::Float),
+                    Default::default(),
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_record_batch_to_table_schema() {
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("time", DataType::Int64, false),
+                Field::new("station", DataType::Int32, true),
+                Field::new("temperature", DataType::Int64, true),
+            ])),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(Int32Array::from(vec![Some(1), None])),
+                Arc::new(Int64Array::from(vec![10, 20])),
+            ],
+        )
+        .unwrap();
+
+        let mut data = vec![];
+        {
+            let mut writer = StreamWriter::try_new(&mut data, &batch.schema()).unwrap();
+            writer.write(&batch).unwrap();
+            writer.finish().unwrap();
+        }
+        let batches = arrow_stream_to_batches(data.into()).unwrap();
+        assert_eq!(batches, vec![batch.clone()]);
+
+        let batch = record_batch_to_table_schema(&batch, &table_schema()).unwrap();
+        let time = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<TimestampNanosecondArray>()
+            .unwrap();
+        assert_eq!(&time.values()[..], &[1, 2]);
+        let station = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(station.value(0), "1");
+        assert!(station.is_null(1));
+        let temperature = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(&temperature.values()[..], &[10.0, 20.0]);
+
+        let no_time = batch.project(&[1, 2]).unwrap();
+        assert!(record_batch_to_table_schema(&no_time, &table_schema()).is_err());
+        let no_field = batch.project(&[0, 1]).unwrap();
+        assert!(record_batch_to_table_schema(&no_field, &table_schema()).is_err());
+    }
+}
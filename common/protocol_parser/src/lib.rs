@@ -16,6 +16,7 @@ pub use line_protocol::parser::Error as LineProtocolError;
 
 type NextTagRes<'a> = Result<Option<(Vec<(Cow<'a, str>, Cow<'a, str>)>, usize)>>;
 
+pub mod arrow_convert;
 pub mod line_protocol;
 pub mod lines_convert;
 pub mod open_tsdb;
@@ -33,7 +34,7 @@ pub enum Error {
     Common { content: String },
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Line<'a> {
     pub hash_id: u64,
     pub table: Cow<'a, str>,
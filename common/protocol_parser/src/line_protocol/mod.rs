@@ -1,4 +1,4 @@
-use self::parser::{Parser, Result};
+use self::parser::{Error, Parser, Result};
 use crate::Line;
 
 pub mod parser;
@@ -7,3 +7,12 @@ pub fn line_protocol_to_lines(lines: &str, default_time: i64) -> Result<Vec<Line
     let parser = Parser::new(default_time);
     parser.parse(lines)
 }
+
+/// Parse lines leniently, see [`Parser::parse_lenient`].
+pub fn line_protocol_to_lines_lenient(
+    lines: &str,
+    default_time: i64,
+) -> (Vec<(usize, Line)>, Vec<(usize, Error)>) {
+    let parser = Parser::new(default_time);
+    parser.parse_lenient(lines)
+}
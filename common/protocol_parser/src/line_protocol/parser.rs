@@ -47,6 +47,50 @@ impl Parser {
         Ok(res)
     }
 
+    /// Parses lines one by one, malformed lines are skipped instead of failing the whole input.
+    ///
+    /// Returns the valid lines and the malformed lines with their line numbers, starting from 1.
+    /// Positions in errors are relative to the start of the line.
+    pub fn parse_lenient<'a>(
+        &self,
+        lines: &'a str,
+    ) -> (Vec<(usize, Line<'a>)>, Vec<(usize, Error)>) {
+        let buf = lines.as_bytes();
+        let mut res = vec![];
+        let mut errors = vec![];
+        let mut line_number = 1;
+        let mut spos = 0;
+        while spos < buf.len() {
+            let epos = search_line_end(buf, spos);
+            let line = &buf[spos..epos];
+            if line.iter().any(|c| !c.is_ascii_whitespace()) {
+                match self.parse_line(line, 0) {
+                    Ok(Some((parsed, newpos))) => {
+                        // Content after the timestamp.
+                        match line[newpos.min(line.len())..]
+                            .iter()
+                            .position(|c| !c.is_ascii_whitespace())
+                        {
+                            Some(i) => errors.push((
+                                line_number,
+                                Error::UnexpectedToken {
+                                    pos: newpos + i,
+                                    token: line[newpos + i] as char,
+                                },
+                            )),
+                            None => res.push((line_number, parsed)),
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => errors.push((line_number, e)),
+                }
+            }
+            line_number += line.iter().filter(|&&c| c == b'\n').count() + 1;
+            spos = epos + 1;
+        }
+        (res, errors)
+    }
+
     fn parse_line<'a>(&self, lines: &'a [u8], spos: usize) -> Result<Option<(Line<'a>, usize)>> {
         let len = lines.len();
         let mut spos = spos;
@@ -146,6 +190,55 @@ impl Parser {
     }
 }
 
+/// Returns the position of the `\n` ending the line starting at `spos`,
+/// newlines in quoted field values don't end the line. If a quoted string is
+/// not closed, the line ends at the first `\n` after the quote, so that the
+/// following lines are still parsed.
+fn search_line_end(lines: &[u8], spos: usize) -> usize {
+    let mut i = spos;
+    let mut escaped = false;
+    while i < lines.len() {
+        match lines[i] {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            b'"' => match search_quote_end(lines, i + 1) {
+                Some(epos) => i = epos,
+                None => {
+                    return match lines[i..].iter().position(|&c| c == b'\n') {
+                        Some(pos) => i + pos,
+                        None => lines.len(),
+                    }
+                }
+            },
+            b'\n' => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    lines.len()
+}
+
+/// Returns the position of the `"` closing the quoted string starting at `spos`,
+/// a closing `"` must be followed by the end of the field value.
+fn search_quote_end(lines: &[u8], spos: usize) -> Option<usize> {
+    let mut escaped = false;
+    for (i, &c) in lines[spos..].iter().enumerate() {
+        match c {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            b'"' => {
+                let epos = spos + i;
+                return match lines.get(epos + 1) {
+                    None | Some(b',' | b' ' | b'\n' | b'\r') => Some(epos),
+                    Some(_) => None,
+                };
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 fn get_unescaped_measurement(s: &[u8], need_unescape: bool) -> Result<Cow<str>> {
     if !need_unescape {
         return Ok(Cow::Borrowed(u8_slice_to_str_unchecked(s)));
@@ -585,4 +678,56 @@ comma\,1,equals\==e\,x,two=val2 field\=x="fir\"
         let res = parser.parse(line);
         assert_eq!(res, Err(LineProtocolError::InvaildSyntax));
     }
+
+    #[test]
+    fn test_parse_lenient() {
+        let parser = Parser::new(-1);
+        let lines = "cpu,host=a value=1 1\n\
+                     cpu,host=b,c d=1\n\
+                     \n\
+                     cpu,host=c value=\"x\ny\" 3\n\
+                     cpu,host=d value=1 4x\n\
+                     cpu,host=e value=1 5";
+        let (lines, errors) = parser.parse_lenient(lines);
+
+        let parsed = lines
+            .iter()
+            .map(|(n, line)| (*n, line.timestamp))
+            .collect::<Vec<_>>();
+        assert_eq!(parsed, vec![(1, 1), (4, 3), (7, 5)]);
+        assert_eq!(
+            errors,
+            vec![
+                (2, LineProtocolError::InvaildSyntax),
+                (
+                    6,
+                    LineProtocolError::UnexpectedToken {
+                        pos: 20,
+                        token: 'x'
+                    }
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_lenient_unclosed_quote() {
+        let parser = Parser::new(-1);
+        let lines = "cpu,host=a value=\"x 1\n\
+                     cpu,host=b value=1 2\n\
+                     cpu,host=c value=\"y\" 3\n\
+                     cpu,host=d value=\"z\"w 4\n\
+                     cpu,host=e value=\"multi\nline\" 5";
+        let (lines, errors) = parser.parse_lenient(lines);
+
+        let parsed = lines
+            .iter()
+            .map(|(n, line)| (*n, line.timestamp))
+            .collect::<Vec<_>>();
+        assert_eq!(parsed, vec![(2, 2), (3, 3), (5, 5)]);
+        assert_eq!(
+            errors.iter().map(|(n, _)| *n).collect::<Vec<_>>(),
+            vec![1, 4]
+        );
+    }
 }
@@ -124,6 +124,15 @@ impl Parser {
         Ok((ret, pos))
     }
 
+    /// Parse a single telnet style `put` command, the trailing line break should be removed.
+    pub fn parse_tcp_line<'a>(&self, buf: &'a str) -> Result<Option<Line<'a>>> {
+        let mut line = self.next_tcp_line(buf)?;
+        if let Some(line) = line.as_mut() {
+            line.sort_dedup_and_hash();
+        }
+        Ok(line)
+    }
+
     fn next_tcp_line<'a>(&self, buf: &'a str) -> Result<Option<Line<'a>>> {
         if buf.is_empty() {
             return Ok(None);
@@ -166,7 +175,7 @@ impl Parser {
             let tag = token.split('=').collect::<Vec<&str>>();
             if tag.len() != 2 || tag[0].is_empty() || tag[1].is_empty() {
                 return Err(Common {
-                    content: format!("put: invalid tag: {}", tokens[3]),
+                    content: format!("put: invalid tag: {}", token),
                 });
             }
             tags.push((Cow::Borrowed(tag[0]), Cow::Borrowed(tag[1])));
@@ -283,6 +283,40 @@ write_timeout_ms = 3000
 stream_trigger_cpu = 1
 stream_executor_cpu = 2
 
+## Cache the results of repeated queries, the time range of a query is split into
+## buckets of $query_cache_bucket, buckets older than $query_cache_live_tail are cached
+## for at most $query_cache_bucket.
+# query_cache_enabled = false
+## The maximum memory of the cached results of each tenant, the tenant option
+## `query_cache_max_memory` overrides it.
+# query_cache_max_memory = "64M" # 67,108,864 bytes
+# query_cache_bucket = "1h"
+# query_cache_live_tail = "5m"
+
+## Record the statements of the classes in $audit_statements (ddl, dcl, dml, query)
+## to rotated files in $audit_path and the table `cluster_schema.audit_log`.
+# audit_enabled = false
+# audit_path = '/var/log/cnosdb/audit'
+# audit_statements = ["ddl", "dcl"]
+# audit_max_file_size = "128M" # 134,217,728 bytes
+# audit_max_files = 10
+## The maximum number of the latest records kept in `cluster_schema.audit_log`,
+## which only shows the records of the statements executed by the node serving the query.
+# audit_table_max_records = 10000
+
+## Password policy checked when a password is set by CREATE USER or ALTER USER,
+## passwords expire after $password_lifetime, "0" means never.
+# password_min_length = 0
+# password_require_uppercase = false
+# password_require_lowercase = false
+# password_require_digit = false
+# password_require_special = false
+# password_lifetime = "0"
+## Lock the user for $login_lock_time after $login_max_failed_attempts consecutive
+## failed logins, 0 means never lock. Only takes effect when auth_enabled is true.
+# login_max_failed_attempts = 0
+# login_lock_time = "10m"
+
 [storage]
 
 ## The directory where database files stored.
@@ -320,6 +354,18 @@ path = '/var/lib/cnosdb/data'
 ## The maximum concurrent compactions.
 # max_concurrent_compaction = 4
 
+## The directory of the cold tier, fully compacted tsm files are moved here once
+## their data is older than $compact_trigger_cold_duration, only their index is
+## kept in $path. Cold tier is disabled if it's empty.
+# cold_tier_path = ''
+
+## The size of the cache of data read from the cold tier.
+# cold_tier_read_cache_size = "256M" # 268,435,456 bytes
+
+## The engine storing the series index, one of [radix, sled]. The index of
+## existing vnodes must be migrated by `cnosdb migrate-index` after changing it.
+# index_engine = "radix"
+
 ## If true, write request will not be checked in detail.
 strict_write = false
 
@@ -370,6 +416,16 @@ path = '/var/log/cnosdb'
 # [security.tls_config]
 # certificate = "./config/tls/server.crt"
 # private_key = "./config/tls/server.key"
+## Authenticate the requests with JWT bearer tokens signed by RS256 or ES256 keys in
+## the JWKS file, the claim $user_claim is the user name, the claim $tenant_claim (if
+## present) must be the tenant of the request.
+# [security.jwt_config]
+# jwks_path = "./config/jwt/jwks.json"
+# issuer = ""
+# audience = ""
+# user_claim = "sub"
+# tenant_claim = "tenant"
+# leeway = "1m"
 
 [service]
 http_listen_port = 8902
@@ -1,9 +1,16 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
 use crate::check::{CheckConfig, CheckConfigItemResult, CheckConfigResult};
-use crate::override_by_env::{entry_override, OverrideByEnv};
+use crate::codec::{bytes_num, duration};
+use crate::override_by_env::{
+    entry_override, entry_override_to_duration, entry_override_to_vec_string, OverrideByEnv,
+};
+
+/// The statement classes that can be audited.
+pub const AUDIT_STATEMENT_CLASSES: [&str; 4] = ["ddl", "dcl", "dml", "query"];
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct QueryConfig {
@@ -23,6 +30,51 @@ pub struct QueryConfig {
     pub stream_trigger_cpu: usize,
     #[serde(default = "QueryConfig::default_stream_executor_cpu")]
     pub stream_executor_cpu: usize,
+    #[serde(default = "QueryConfig::default_query_cache_enabled")]
+    pub query_cache_enabled: bool,
+    #[serde(
+        with = "bytes_num",
+        default = "QueryConfig::default_query_cache_max_memory"
+    )]
+    pub query_cache_max_memory: u64,
+    #[serde(with = "duration", default = "QueryConfig::default_query_cache_bucket")]
+    pub query_cache_bucket: Duration,
+    #[serde(
+        with = "duration",
+        default = "QueryConfig::default_query_cache_live_tail"
+    )]
+    pub query_cache_live_tail: Duration,
+    #[serde(default = "QueryConfig::default_audit_enabled")]
+    pub audit_enabled: bool,
+    #[serde(default = "QueryConfig::default_audit_path")]
+    pub audit_path: String,
+    #[serde(default = "QueryConfig::default_audit_statements")]
+    pub audit_statements: Vec<String>,
+    #[serde(
+        with = "bytes_num",
+        default = "QueryConfig::default_audit_max_file_size"
+    )]
+    pub audit_max_file_size: u64,
+    #[serde(default = "QueryConfig::default_audit_max_files")]
+    pub audit_max_files: usize,
+    #[serde(default = "QueryConfig::default_audit_table_max_records")]
+    pub audit_table_max_records: usize,
+    #[serde(default = "QueryConfig::default_password_min_length")]
+    pub password_min_length: usize,
+    #[serde(default = "QueryConfig::default_password_require_uppercase")]
+    pub password_require_uppercase: bool,
+    #[serde(default = "QueryConfig::default_password_require_lowercase")]
+    pub password_require_lowercase: bool,
+    #[serde(default = "QueryConfig::default_password_require_digit")]
+    pub password_require_digit: bool,
+    #[serde(default = "QueryConfig::default_password_require_special")]
+    pub password_require_special: bool,
+    #[serde(with = "duration", default = "QueryConfig::default_password_lifetime")]
+    pub password_lifetime: Duration,
+    #[serde(default = "QueryConfig::default_login_max_failed_attempts")]
+    pub login_max_failed_attempts: u32,
+    #[serde(with = "duration", default = "QueryConfig::default_login_lock_time")]
+    pub login_lock_time: Duration,
 }
 
 impl QueryConfig {
@@ -55,6 +107,78 @@ impl QueryConfig {
     fn default_stream_executor_cpu() -> usize {
         2
     }
+
+    fn default_query_cache_enabled() -> bool {
+        false
+    }
+
+    fn default_query_cache_max_memory() -> u64 {
+        64 * 1024 * 1024 // 64M
+    }
+
+    fn default_query_cache_bucket() -> Duration {
+        Duration::from_secs(60 * 60)
+    }
+
+    fn default_query_cache_live_tail() -> Duration {
+        Duration::from_secs(5 * 60)
+    }
+
+    fn default_audit_enabled() -> bool {
+        false
+    }
+
+    fn default_audit_path() -> String {
+        "/var/log/cnosdb/audit".to_string()
+    }
+
+    fn default_audit_statements() -> Vec<String> {
+        vec!["ddl".to_string(), "dcl".to_string()]
+    }
+
+    fn default_audit_max_file_size() -> u64 {
+        128 * 1024 * 1024 // 128M
+    }
+
+    fn default_audit_max_files() -> usize {
+        10
+    }
+
+    fn default_audit_table_max_records() -> usize {
+        10000
+    }
+
+    fn default_password_min_length() -> usize {
+        0
+    }
+
+    fn default_password_require_uppercase() -> bool {
+        false
+    }
+
+    fn default_password_require_lowercase() -> bool {
+        false
+    }
+
+    fn default_password_require_digit() -> bool {
+        false
+    }
+
+    fn default_password_require_special() -> bool {
+        false
+    }
+
+    fn default_password_lifetime() -> Duration {
+        Duration::ZERO
+    }
+
+    fn default_login_max_failed_attempts() -> u32 {
+        0
+    }
+
+    fn default_login_lock_time() -> Duration {
+        Duration::from_secs(10 * 60)
+    }
 }
 
 impl OverrideByEnv for QueryConfig {
@@ -76,6 +200,63 @@ impl OverrideByEnv for QueryConfig {
             &mut self.stream_executor_cpu,
             "CNOSDB_QUERY_STREAM_EXECUTOR_CPU",
         );
+        entry_override(
+            &mut self.query_cache_enabled,
+            "CNOSDB_QUERY_QUERY_CACHE_ENABLED",
+        );
+        entry_override(
+            &mut self.query_cache_max_memory,
+            "CNOSDB_QUERY_QUERY_CACHE_MAX_MEMORY",
+        );
+        entry_override_to_duration(
+            &mut self.query_cache_bucket,
+            "CNOSDB_QUERY_QUERY_CACHE_BUCKET",
+        );
+        entry_override_to_duration(
+            &mut self.query_cache_live_tail,
+            "CNOSDB_QUERY_QUERY_CACHE_LIVE_TAIL",
+        );
+        entry_override(&mut self.audit_enabled, "CNOSDB_QUERY_AUDIT_ENABLED");
+        entry_override(&mut self.audit_path, "CNOSDB_QUERY_AUDIT_PATH");
+        entry_override_to_vec_string(&mut self.audit_statements, "CNOSDB_QUERY_AUDIT_STATEMENTS");
+        entry_override(
+            &mut self.audit_max_file_size,
+            "CNOSDB_QUERY_AUDIT_MAX_FILE_SIZE",
+        );
+        entry_override(&mut self.audit_max_files, "CNOSDB_QUERY_AUDIT_MAX_FILES");
+        entry_override(
+            &mut self.audit_table_max_records,
+            "CNOSDB_QUERY_AUDIT_TABLE_MAX_RECORDS",
+        );
+        entry_override(
+            &mut self.password_min_length,
+            "CNOSDB_QUERY_PASSWORD_MIN_LENGTH",
+        );
+        entry_override(
+            &mut self.password_require_uppercase,
+            "CNOSDB_QUERY_PASSWORD_REQUIRE_UPPERCASE",
+        );
+        entry_override(
+            &mut self.password_require_lowercase,
+            "CNOSDB_QUERY_PASSWORD_REQUIRE_LOWERCASE",
+        );
+        entry_override(
+            &mut self.password_require_digit,
+            "CNOSDB_QUERY_PASSWORD_REQUIRE_DIGIT",
+        );
+        entry_override(
+            &mut self.password_require_special,
+            "CNOSDB_QUERY_PASSWORD_REQUIRE_SPECIAL",
+        );
+        entry_override_to_duration(
+            &mut self.password_lifetime,
+            "CNOSDB_QUERY_PASSWORD_LIFETIME",
+        );
+        entry_override(
+            &mut self.login_max_failed_attempts,
+            "CNOSDB_QUERY_LOGIN_MAX_FAILED_ATTEMPTS",
+        );
+        entry_override_to_duration(&mut self.login_lock_time, "CNOSDB_QUERY_LOGIN_LOCK_TIME");
     }
 }
 
@@ -90,6 +271,24 @@ impl Default for QueryConfig {
             write_timeout_ms: Self::default_write_timeout_ms(),
             stream_trigger_cpu: Self::default_stream_trigger_cpu(),
             stream_executor_cpu: Self::default_stream_executor_cpu(),
+            query_cache_enabled: Self::default_query_cache_enabled(),
+            query_cache_max_memory: Self::default_query_cache_max_memory(),
+            query_cache_bucket: Self::default_query_cache_bucket(),
+            query_cache_live_tail: Self::default_query_cache_live_tail(),
+            audit_enabled: Self::default_audit_enabled(),
+            audit_path: Self::default_audit_path(),
+            audit_statements: Self::default_audit_statements(),
+            audit_max_file_size: Self::default_audit_max_file_size(),
+            audit_max_files: Self::default_audit_max_files(),
+            audit_table_max_records: Self::default_audit_table_max_records(),
+            password_min_length: Self::default_password_min_length(),
+            password_require_uppercase: Self::default_password_require_uppercase(),
+            password_require_lowercase: Self::default_password_require_lowercase(),
+            password_require_digit: Self::default_password_require_digit(),
+            password_require_special: Self::default_password_require_special(),
+            password_lifetime: Self::default_password_lifetime(),
+            login_max_failed_attempts: Self::default_login_max_failed_attempts(),
+            login_lock_time: Self::default_login_lock_time(),
         }
     }
 }
@@ -146,11 +345,45 @@ impl CheckConfig for QueryConfig {
         }
         if self.stream_trigger_cpu > 1024 {
             ret.add_warn(CheckConfigItemResult {
-                config: config_name,
+                config: config_name.clone(),
                 item: "stream_trigger_cpu".to_string(),
                 message: "'stream_trigger_cpu' maybe too big(more than 1024)".to_string(),
             })
         }
+        if self.query_cache_enabled && self.query_cache_bucket.as_secs() == 0 {
+            ret.add_error(CheckConfigItemResult {
+                config: config_name.clone(),
+                item: "query_cache_bucket".to_string(),
+                message: "'query_cache_bucket' must be at least 1 second".to_string(),
+            })
+        }
+        for class in self.audit_statements.iter() {
+            if !AUDIT_STATEMENT_CLASSES.contains(&class.to_ascii_lowercase().as_str()) {
+                ret.add_error(CheckConfigItemResult {
+                    config: config_name.clone(),
+                    item: "audit_statements".to_string(),
+                    message: format!(
+                        "'audit_statements' contains unknown statement class '{class}', expected one of {AUDIT_STATEMENT_CLASSES:?}"
+                    ),
+                })
+            }
+        }
+        if self.audit_enabled && self.audit_max_files == 0 {
+            ret.add_error(CheckConfigItemResult {
+                config: config_name.clone(),
+                item: "audit_max_files".to_string(),
+                message: "'audit_max_files' must be at least 1".to_string(),
+            })
+        }
+        if self.login_max_failed_attempts > 0 && self.login_lock_time.is_zero() {
+            ret.add_warn(CheckConfigItemResult {
+                config: config_name,
+                item: "login_lock_time".to_string(),
+                message:
+                    "'login_lock_time' is 0, users will not be locked after too many failed logins"
+                        .to_string(),
+            })
+        }
 
         if ret.is_empty() {
             None
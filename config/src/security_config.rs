@@ -1,13 +1,16 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
 use crate::check::{CheckConfig, CheckConfigItemResult, CheckConfigResult};
-use crate::override_by_env::{entry_override, OverrideByEnv};
+use crate::codec::duration;
+use crate::override_by_env::{entry_override, entry_override_to_duration, OverrideByEnv};
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SecurityConfig {
     pub tls_config: Option<TLSConfig>,
+    pub jwt_config: Option<JwtConfig>,
 }
 
 impl CheckConfig for SecurityConfig {
@@ -19,6 +22,11 @@ impl CheckConfig for SecurityConfig {
                 ret.add_all(r);
             }
         }
+        if let Some(ref jwt_config) = self.jwt_config {
+            if let Some(r) = jwt_config.check(all_config) {
+                ret.add_all(r);
+            }
+        }
 
         if ret.is_empty() {
             Some(ret)
@@ -72,6 +80,7 @@ impl OverrideByEnv for Option<TLSConfig> {
 impl OverrideByEnv for SecurityConfig {
     fn override_by_env(&mut self) {
         self.tls_config.override_by_env();
+        self.jwt_config.override_by_env();
     }
 }
 
@@ -111,3 +120,116 @@ impl CheckConfig for TLSConfig {
         }
     }
 }
+
+/// Verifies the JWT bearer tokens by the keys in a static JWKS file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JwtConfig {
+    #[serde(default = "JwtConfig::default_jwks_path")]
+    pub jwks_path: String,
+    /// Expected `iss` claim, not checked if empty
+    #[serde(default)]
+    pub issuer: String,
+    /// Expected `aud` claim, not checked if empty
+    #[serde(default)]
+    pub audience: String,
+    /// The claim holding the user name
+    #[serde(default = "JwtConfig::default_user_claim")]
+    pub user_claim: String,
+    /// The claim holding the tenant name, the tenant of the request must match it if present
+    #[serde(default = "JwtConfig::default_tenant_claim")]
+    pub tenant_claim: String,
+    /// Allowed clock skew when checking `exp` and `nbf`
+    #[serde(with = "duration", default = "JwtConfig::default_leeway")]
+    pub leeway: Duration,
+}
+
+impl JwtConfig {
+    fn default_jwks_path() -> String {
+        "/etc/cnosdb/jwt/jwks.json".to_string()
+    }
+
+    fn default_user_claim() -> String {
+        "sub".to_string()
+    }
+
+    fn default_tenant_claim() -> String {
+        "tenant".to_string()
+    }
+
+    fn default_leeway() -> Duration {
+        Duration::from_secs(60)
+    }
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            jwks_path: Self::default_jwks_path(),
+            issuer: Default::default(),
+            audience: Default::default(),
+            user_claim: Self::default_user_claim(),
+            tenant_claim: Self::default_tenant_claim(),
+            leeway: Self::default_leeway(),
+        }
+    }
+}
+
+impl OverrideByEnv for Option<JwtConfig> {
+    fn override_by_env(&mut self) {
+        let is_some = self.is_some();
+        let mut jwt_config = self.take().unwrap_or_default();
+        let overridden = [
+            entry_override(
+                &mut jwt_config.jwks_path,
+                "CNOSDB_SECURITY_JWT_CONFIG_JWKS_PATH",
+            ),
+            entry_override(&mut jwt_config.issuer, "CNOSDB_SECURITY_JWT_CONFIG_ISSUER"),
+            entry_override(
+                &mut jwt_config.audience,
+                "CNOSDB_SECURITY_JWT_CONFIG_AUDIENCE",
+            ),
+            entry_override(
+                &mut jwt_config.user_claim,
+                "CNOSDB_SECURITY_JWT_CONFIG_USER_CLAIM",
+            ),
+            entry_override(
+                &mut jwt_config.tenant_claim,
+                "CNOSDB_SECURITY_JWT_CONFIG_TENANT_CLAIM",
+            ),
+            entry_override_to_duration(&mut jwt_config.leeway, "CNOSDB_SECURITY_JWT_CONFIG_LEEWAY"),
+        ];
+        *self = if is_some || overridden.contains(&true) {
+            Some(jwt_config)
+        } else {
+            None
+        };
+    }
+}
+
+impl CheckConfig for JwtConfig {
+    fn check(&self, _: &crate::Config) -> Option<CheckConfigResult> {
+        let config_name = Arc::new("security.jwt".to_string());
+        let mut ret = CheckConfigResult::default();
+
+        if self.jwks_path.is_empty() {
+            ret.add_error(CheckConfigItemResult {
+                config: config_name.clone(),
+                item: "jwks_path".to_string(),
+                message: "'jwks_path' is empty".to_string(),
+            });
+        }
+        if self.user_claim.is_empty() {
+            ret.add_error(CheckConfigItemResult {
+                config: config_name,
+                item: "user_claim".to_string(),
+                message: "'user_claim' is empty".to_string(),
+            });
+        }
+
+        if ret.is_empty() {
+            None
+        } else {
+            Some(ret)
+        }
+    }
+}
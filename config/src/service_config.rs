@@ -18,6 +18,16 @@ pub struct ServiceConfig {
     pub flight_rpc_listen_port: Option<u16>,
     #[serde(default = "ServiceConfig::default_tcp_listen_port")]
     pub tcp_listen_port: Option<u16>,
+    /// Tenant that OpenTSDB telnet points are written into, unless a connection sends `use`.
+    #[serde(default = "ServiceConfig::default_tcp_tenant")]
+    pub tcp_tenant: String,
+    /// Database that OpenTSDB telnet points are written into, unless a connection sends `use`.
+    #[serde(default = "ServiceConfig::default_tcp_database")]
+    pub tcp_database: String,
+    /// Precision of timestamps of OpenTSDB telnet points, one of `ms`, `us`, `ns`.
+    /// Seconds or milliseconds are detected by the number of digits if it's not set.
+    #[serde(default = "ServiceConfig::default_tcp_precision")]
+    pub tcp_precision: Option<String>,
     #[serde(default = "ServiceConfig::default_vector_listen_port")]
     pub vector_listen_port: Option<u16>,
     #[serde(default = "ServiceConfig::default_enable_report")]
@@ -45,6 +55,18 @@ impl ServiceConfig {
         None
     }
 
+    fn default_tcp_tenant() -> String {
+        "cnosdb".to_string()
+    }
+
+    fn default_tcp_database() -> String {
+        "public".to_string()
+    }
+
+    fn default_tcp_precision() -> Option<String> {
+        None
+    }
+
     fn default_vector_listen_port() -> Option<u16> {
         None
     }
@@ -62,6 +84,9 @@ impl Default for ServiceConfig {
             grpc_enable_gzip: ServiceConfig::default_grpc_enable_gzip(),
             flight_rpc_listen_port: ServiceConfig::default_flight_rpc_listen_port(),
             tcp_listen_port: ServiceConfig::default_tcp_listen_port(),
+            tcp_tenant: ServiceConfig::default_tcp_tenant(),
+            tcp_database: ServiceConfig::default_tcp_database(),
+            tcp_precision: ServiceConfig::default_tcp_precision(),
             vector_listen_port: ServiceConfig::default_vector_listen_port(),
             enable_report: ServiceConfig::default_enable_report(),
         }
@@ -87,6 +112,9 @@ impl OverrideByEnv for ServiceConfig {
             "CNOSDB_SERVICE_FLIGHT_RPC_LISTEN_PORT",
         );
         entry_override_option(&mut self.tcp_listen_port, "CNOSDB_SERVICE_TCP_LISTEN_PORT");
+        entry_override(&mut self.tcp_tenant, "CNOSDB_SERVICE_TCP_TENANT");
+        entry_override(&mut self.tcp_database, "CNOSDB_SERVICE_TCP_DATABASE");
+        entry_override_option(&mut self.tcp_precision, "CNOSDB_SERVICE_TCP_PRECISION");
         entry_override_option(
             &mut self.vector_listen_port,
             "CNOSDB_SERVICE_VECTOR_LISTEN_PORT",
@@ -174,6 +202,19 @@ impl CheckConfig for ServiceConfig {
             }
         }
 
+        if let Some(precision) = &self.tcp_precision {
+            if !matches!(precision.to_lowercase().as_str(), "ms" | "us" | "ns") {
+                ret.add_error(CheckConfigItemResult {
+                    config: config_name.clone(),
+                    item: "tcp_precision".to_string(),
+                    message: format!(
+                        "Invalid 'tcp_precision': {}, expected one of 'ms', 'us', 'ns'",
+                        precision
+                    ),
+                });
+            }
+        }
+
         if let Some(port) = self.vector_listen_port {
             let default_vector_addr = format!("{}:{}", &config.global.host, port);
             if let Err(e) = default_vector_addr.to_socket_addrs() {
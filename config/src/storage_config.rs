@@ -1,3 +1,5 @@
+use std::fmt::Display;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -7,6 +9,36 @@ use crate::check::{CheckConfig, CheckConfigItemResult, CheckConfigResult};
 use crate::codec::{bytes_num, duration};
 use crate::override_by_env::{entry_override, entry_override_to_duration, OverrideByEnv};
 
+/// Key-value engine storing the series index of vnodes.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexEngineType {
+    #[default]
+    Radix,
+    Sled,
+}
+
+impl FromStr for IndexEngineType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "radix" => Ok(Self::Radix),
+            "sled" => Ok(Self::Sled),
+            _ => Err("index engine must be one of [radix, sled]".to_string()),
+        }
+    }
+}
+
+impl Display for IndexEngineType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Radix => write!(f, "radix"),
+            Self::Sled => write!(f, "sled"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct StorageConfig {
     #[serde(default = "StorageConfig::default_path")]
@@ -59,6 +91,18 @@ pub struct StorageConfig {
         default = "StorageConfig::default_copyinto_trigger_flush_size"
     )]
     pub copyinto_trigger_flush_size: u64,
+
+    #[serde(default = "StorageConfig::default_cold_tier_path")]
+    pub cold_tier_path: String,
+
+    #[serde(
+        with = "bytes_num",
+        default = "StorageConfig::default_cold_tier_read_cache_size"
+    )]
+    pub cold_tier_read_cache_size: u64,
+
+    #[serde(default = "StorageConfig::default_index_engine")]
+    pub index_engine: IndexEngineType,
 }
 
 impl StorageConfig {
@@ -115,11 +159,27 @@ impl StorageConfig {
         128 * 1024 * 1024 // 128M
     }
 
+    fn default_cold_tier_path() -> String {
+        String::new()
+    }
+
+    fn default_cold_tier_read_cache_size() -> u64 {
+        256 * 1024 * 1024 // 256M
+    }
+
+    fn default_index_engine() -> IndexEngineType {
+        IndexEngineType::Radix
+    }
+
     pub fn introspect(&mut self) {
         // Unit of storage.compact_trigger_cold_duration is seconds
         self.compact_trigger_cold_duration =
             Duration::from_secs(self.compact_trigger_cold_duration.as_secs());
     }
+
+    pub fn cold_tier_enabled(&self) -> bool {
+        !self.cold_tier_path.is_empty()
+    }
 }
 
 impl OverrideByEnv for StorageConfig {
@@ -160,6 +220,12 @@ impl OverrideByEnv for StorageConfig {
             &mut self.copyinto_trigger_flush_size,
             "CNOSDB_COPYINTO_TRIGGER_FLUSH_SIZE",
         );
+        entry_override(&mut self.cold_tier_path, "CNOSDB_STORAGE_COLD_TIER_PATH");
+        entry_override(
+            &mut self.cold_tier_read_cache_size,
+            "CNOSDB_STORAGE_COLD_TIER_READ_CACHE_SIZE",
+        );
+        entry_override(&mut self.index_engine, "CNOSDB_STORAGE_INDEX_ENGINE");
     }
 }
 
@@ -179,6 +245,9 @@ impl Default for StorageConfig {
             strict_write: Self::default_strict_write(),
             reserve_space: Self::default_reserve_space(),
             copyinto_trigger_flush_size: Self::default_copyinto_trigger_flush_size(),
+            cold_tier_path: Self::default_cold_tier_path(),
+            cold_tier_read_cache_size: Self::default_cold_tier_read_cache_size(),
+            index_engine: Self::default_index_engine(),
         }
     }
 }
@@ -233,11 +302,18 @@ impl CheckConfig for StorageConfig {
         }
         if self.max_compact_size < 1024 * 1024 {
             ret.add_warn(CheckConfigItemResult {
-                config: config_name,
+                config: config_name.clone(),
                 item: "max_compact_size".to_string(),
                 message: "'max_compact_size' maybe too small(less than 1M)".to_string(),
             });
         }
+        if self.cold_tier_enabled() && self.cold_tier_path == self.path {
+            ret.add_error(CheckConfigItemResult {
+                config: config_name,
+                item: "cold_tier_path".to_string(),
+                message: "'cold_tier_path' must be different from 'path'".to_string(),
+            });
+        }
 
         if ret.is_empty() {
             None
@@ -0,0 +1,90 @@
+//! Notify the changes of the data written or deleted through this node, or applied to the
+//! vnodes on this node from the raft log, so the caches of the query results of the changed
+//! time ranges can be invalidated and the changed windows of rollup tables can be aggregated
+//! again, see [`DataChangeListener`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use models::predicate::domain::TimeRange;
+use models::schema::{timestamp_convert, Precision};
+use models::Timestamp;
+
+/// The time range of a table changed by writes or deletes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataChange {
+    pub tenant: String,
+    pub database: String,
+    /// All tables of the database are changed if `None`.
+    pub table: Option<String>,
+    /// Closed interval in nanoseconds.
+    pub time_range: TimeRange,
+}
+
+/// Receives the changes after the data was written or deleted.
+pub trait DataChangeListener: Send + Sync {
+    fn on_data_change(&self, change: DataChange);
+}
+
+static DATA_CHANGE_LISTENERS: RwLock<Vec<Arc<dyn DataChangeListener>>> = RwLock::new(Vec::new());
+
+/// Add a listener of data changes, such as the query cache and the rollup job.
+pub fn register_data_change_listener(listener: Arc<dyn DataChangeListener>) {
+    match DATA_CHANGE_LISTENERS.write() {
+        Ok(mut listeners) => listeners.push(listener),
+        Err(poisoned) => poisoned.into_inner().push(listener),
+    }
+}
+
+pub(crate) fn notify_data_change(change: DataChange) {
+    let listeners = match DATA_CHANGE_LISTENERS.read() {
+        Ok(listeners) => listeners.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    };
+    for listener in listeners {
+        listener.on_data_change(change.clone());
+    }
+}
+
+/// Convert a timestamp to nanoseconds, saturated if overflow.
+pub(crate) fn to_nanos(precision: Precision, ts: Timestamp) -> Timestamp {
+    timestamp_convert(precision, Precision::NS, ts).unwrap_or(if ts < 0 {
+        Timestamp::MIN
+    } else {
+        Timestamp::MAX
+    })
+}
+
+/// Collects the time range of each table written by a request.
+#[derive(Debug, Default)]
+pub(crate) struct WrittenTimeRanges {
+    tables: HashMap<String, TimeRange>,
+}
+
+impl WrittenTimeRanges {
+    pub fn add(&mut self, table: &str, precision: Precision, ts: Timestamp) {
+        let ts = to_nanos(precision, ts);
+        self.add_range(table, TimeRange::new(ts, ts));
+    }
+
+    /// Add a time range in nanoseconds.
+    pub fn add_range(&mut self, table: &str, time_range: TimeRange) {
+        match self.tables.get_mut(table) {
+            Some(range) => range.merge(&time_range),
+            None => {
+                self.tables.insert(table.to_string(), time_range);
+            }
+        }
+    }
+
+    pub fn notify(self, tenant: &str, database: &str) {
+        for (table, time_range) in self.tables {
+            notify_data_change(DataChange {
+                tenant: tenant.to_string(),
+                database: database.to_string(),
+                table: Some(table),
+                time_range,
+            });
+        }
+    }
+}
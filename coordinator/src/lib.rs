@@ -25,10 +25,12 @@ use tskv::EngineRef;
 use crate::errors::CoordinatorResult;
 use crate::service::CoordServiceMetrics;
 
+pub mod data_change;
 pub mod errors;
 pub mod metrics;
 pub mod raft;
 pub mod reader;
+pub mod replica_check;
 pub mod resource_manager;
 pub mod service;
 pub mod service_mock;
@@ -51,12 +53,16 @@ pub enum VnodeManagerCmdType {
     RemoveRaftNode(u32),
     /// replica set id
     DestoryRaftGroup(u32),
+    /// replica set id
+    RepairReplicationSet(u32),
 }
 
 #[derive(Debug, Clone)]
 pub enum VnodeSummarizerCmdType {
     /// replication set id
     Checksum(u32),
+    /// database name, number of the top tag values of each tag key
+    SeriesCardinality(String, u32),
 }
 
 #[async_trait::async_trait]
@@ -105,6 +111,17 @@ pub trait Coordinator: Send + Sync {
         span_ctx: Option<&SpanContext>,
     ) -> CoordinatorResult<usize>;
 
+    /// Write record batches of arbitrary arrow schema to an existing table,
+    /// columns are mapped to the table columns by name.
+    async fn write_table_record_batches(
+        &self,
+        tenant: &str,
+        db: &str,
+        table: &str,
+        record_batches: Vec<RecordBatch>,
+        span_ctx: Option<&SpanContext>,
+    ) -> CoordinatorResult<usize>;
+
     fn table_scan(
         &self,
         option: QueryOption,
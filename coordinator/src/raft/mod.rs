@@ -3,8 +3,11 @@ use std::time::Duration;
 
 use meta::model::MetaRef;
 use models::meta_data::VnodeId;
+use models::predicate::domain::{ResolvedPredicate, TimeRange};
+use models::schema::Precision;
+use models::SeriesKey;
 use protos::kv_service::tskv_service_client::TskvServiceClient;
-use protos::kv_service::{DownloadFileRequest, RaftWriteCommand};
+use protos::kv_service::{raft_write_command, DownloadFileRequest, RaftWriteCommand};
 use protos::models_helper::parse_prost_bytes;
 use protos::{tskv_service_time_out_client, DEFAULT_GRPC_SERVER_MESSAGE_LEN};
 use replication::errors::{ReplicationError, ReplicationResult};
@@ -19,6 +22,7 @@ use tskv::kv_option::StorageOptions;
 use tskv::vnode_store::VnodeStorage;
 use tskv::VnodeSnapshot;
 
+use crate::data_change::{notify_data_change, DataChange, WrittenTimeRanges};
 use crate::errors::{CoordinatorError, CoordinatorResult};
 
 pub mod manager;
@@ -166,15 +170,107 @@ impl TskvEngineStorage {
     ) -> ReplicationResult<replication::Response> {
         let request = parse_prost_bytes::<RaftWriteCommand>(req)?;
         if let Some(command) = request.command {
+            // Nothing is cached from before the restart when replaying the wal.
+            let changed = if ctx.apply_type == replication::APPLY_TYPE_WAL {
+                None
+            } else {
+                Some(self.changed_time_ranges(&command).await)
+            };
+
             self.vnode.apply(ctx, command).await.map_err(|err| {
                 ReplicationError::ApplyEngineErr {
                     msg: err.to_string(),
                 }
             })?;
+
+            if let Some(changed) = changed {
+                changed.notify(&self.tenant, &self.db_name);
+            }
         }
 
         Ok(vec![])
     }
+
+    /// The time ranges of the tables changed by the command, they are notified after the
+    /// command is applied, so the query caches on every node holding a replica are
+    /// invalidated, no matter which node the command is proposed by.
+    async fn changed_time_ranges(
+        &self,
+        command: &raft_write_command::Command,
+    ) -> WrittenTimeRanges {
+        let mut changed = WrittenTimeRanges::default();
+        match command {
+            raft_write_command::Command::WriteData(cmd) => {
+                let precision = Precision::from(cmd.precision as u8);
+                let tables = flatbuffers::root::<protos::models::Points>(&cmd.data)
+                    .ok()
+                    .and_then(|points| points.tables());
+                for table in tables.iter().flat_map(|tables| tables.iter()) {
+                    let name = match table.tab() {
+                        Some(name) => name,
+                        None => continue,
+                    };
+                    let time_values = table
+                        .columns()
+                        .and_then(|columns| {
+                            columns
+                                .iter()
+                                .find(|c| c.column_type() == protos::models::ColumnType::Time)
+                        })
+                        .and_then(|c| c.col_values())
+                        .and_then(|v| v.int_value());
+                    for ts in time_values.iter().flat_map(|values| values.iter()) {
+                        changed.add(name, precision, ts);
+                    }
+                }
+            }
+            raft_write_command::Command::DropTable(cmd) => {
+                changed.add_range(&cmd.table, TimeRange::all());
+            }
+            raft_write_command::Command::DropColumn(cmd) => {
+                changed.add_range(&cmd.table, TimeRange::all());
+            }
+            raft_write_command::Command::UpdateTags(cmd) => {
+                for key in cmd.matched_series.iter() {
+                    if let Ok(series_key) = SeriesKey::decode(key) {
+                        changed.add_range(series_key.table(), TimeRange::all());
+                    }
+                }
+            }
+            raft_write_command::Command::DeleteFromTable(cmd) => {
+                let predicate = bincode::deserialize::<ResolvedPredicate>(&cmd.predicate).ok();
+                match (predicate, self.db_precision().await) {
+                    (Some(predicate), Some(precision)) => {
+                        for r in predicate.time_ranges().time_ranges() {
+                            changed.add(&cmd.table, precision, r.min_ts);
+                            changed.add(&cmd.table, precision, r.max_ts);
+                        }
+                    }
+                    _ => changed.add_range(&cmd.table, TimeRange::all()),
+                }
+            }
+            raft_write_command::Command::RepairVnode(cmd) => {
+                let precision = Precision::from(cmd.precision as u8);
+                match bincode::deserialize::<Vec<(SeriesKey, TimeRange)>>(&cmd.series_time_ranges) {
+                    Ok(series_time_ranges) => {
+                        for (_, r) in series_time_ranges {
+                            changed.add(&cmd.table, precision, r.min_ts);
+                            changed.add(&cmd.table, precision, r.max_ts);
+                        }
+                    }
+                    Err(_) => changed.add_range(&cmd.table, TimeRange::all()),
+                }
+            }
+        }
+
+        changed
+    }
+
+    async fn db_precision(&self) -> Option<Precision> {
+        let client = self.meta.tenant_meta(&self.tenant).await?;
+        let db_schema = client.get_db_schema(&self.db_name).ok().flatten()?;
+        Some(*db_schema.config.precision_or_default())
+    }
 }
 
 #[async_trait::async_trait]
@@ -224,6 +320,13 @@ impl ApplyStorage for TskvEngineStorage {
                 msg: err.to_string(),
             })?;
 
+        notify_data_change(DataChange {
+            tenant: self.tenant.clone(),
+            database: self.db_name.clone(),
+            table: None,
+            time_range: TimeRange::all(),
+        });
+
         Ok(())
     }
 
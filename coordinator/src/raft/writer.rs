@@ -105,7 +105,7 @@ impl RaftWriter {
             .get_node_or_build(&request.tenant, &request.db_name, replica)
             .await?;
 
-        self.pre_check_write_to_raft(&request).await?;
+        self.pre_check_write_to_raft(replica, &request).await?;
         let raft_data = to_prost_bytes(request.clone());
         let result = self.write_to_raft(raft, raft_data).await;
         if let Err(CoordinatorError::ForwardToLeader {
@@ -120,32 +120,53 @@ impl RaftWriter {
         }
     }
 
-    async fn pre_check_write_to_raft(&self, request: &RaftWriteCommand) -> CoordinatorResult<()> {
+    /// Checks done once by the leader before proposing, applying the command must not fail
+    /// on them, otherwise replicas may diverge.
+    async fn pre_check_write_to_raft(
+        &self,
+        replica: &ReplicationSet,
+        request: &RaftWriteCommand,
+    ) -> CoordinatorResult<()> {
         if let Some(command) = &request.command {
             match command {
-                raft_write_command::Command::WriteData(request) => {
-                    let fb_points = flatbuffers::root::<protos::models::Points>(&request.data)
+                raft_write_command::Command::WriteData(write_data) => {
+                    let fb_points = flatbuffers::root::<protos::models::Points>(&write_data.data)
                         .map_err(|err| CoordinatorError::TskvError {
-                            source: tskv::Error::InvalidFlatbuffer { source: err },
-                        })?;
+                        source: tskv::Error::InvalidFlatbuffer { source: err },
+                    })?;
 
                     let _ = fb_points.tables().ok_or(CoordinatorError::TskvError {
                         source: tskv::Error::InvalidPointTable,
                     })?;
 
                     let total_memory = self.config.deployment.memory * 1024 * 1024 * 1024;
-                    if request.data.len() > total_memory.saturating_sub(self.memory_pool.reserved())
+                    if write_data.data.len()
+                        > total_memory.saturating_sub(self.memory_pool.reserved())
                     {
                         return Err(CoordinatorError::TskvError {
                             source: tskv::Error::MemoryExhausted,
                         });
                     }
+
+                    let node_id = self.config.global.node_id;
+                    let local_vnode = replica.vnodes.iter().find(|v| v.node_id == node_id);
+                    if let (Some(kv_inst), Some(vnode)) = (&self.kv_inst, local_vnode) {
+                        kv_inst
+                            .check_series_limit(
+                                &request.tenant,
+                                &request.db_name,
+                                vnode.id,
+                                &write_data.data,
+                            )
+                            .await?;
+                    }
                 }
 
                 raft_write_command::Command::DropTable(_request) => {}
                 raft_write_command::Command::DropColumn(_request) => {}
                 raft_write_command::Command::UpdateTags(_request) => {}
                 raft_write_command::Command::DeleteFromTable(_request) => {}
+                raft_write_command::Command::RepairVnode(_request) => {}
             }
         }
 
@@ -0,0 +1,554 @@
+//! Compare data of the vnodes in a replication set.
+//!
+//! Checksums of vnodes are calculated by tskv from tsm files. For each series and time range,
+//! the checksum held by the majority of the vnodes is treated as the healthy data, the leader
+//! vnode decides when there is no majority.
+//!
+//! Time ranges not ended yet are not compared, points are still written to them and followers
+//! which have not applied the latest writes would be treated as divergent. Repairing is an
+//! upsert of the divergent rows, see [`plan_series_upsert`].
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+use datafusion::arrow::array::{
+    BinaryArray, BooleanBuilder, Int64Array, StringArray, StringBuilder, UInt32Array, UInt32Builder,
+};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::scalar::ScalarValue;
+use models::meta_data::VnodeId;
+use models::predicate::domain::TimeRange;
+use models::schema::{Precision, TskvTableSchema};
+use models::{SeriesKey, Timestamp};
+
+use crate::data_change::to_nanos;
+use crate::errors::{CoordinatorError, CoordinatorResult};
+
+pub type SeriesTimeRange = (SeriesKey, TimeRange);
+
+/// Schema of the result of `CHECKSUM GROUP`.
+pub fn replica_checksum_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("VNODE_ID", DataType::UInt32, false),
+        Field::new("CHECK_SUM", DataType::Utf8, false),
+        Field::new("CONSISTENT", DataType::Boolean, false),
+    ]))
+}
+
+/// Read `(vnode_id, checksum)` from record batches of `tskv::vnode_table_checksum_schema`.
+pub fn vnode_checksums(batch: &RecordBatch) -> CoordinatorResult<Vec<(VnodeId, String)>> {
+    let vnode_ids = downcast_column::<UInt32Array>(batch, "vnode_id")?;
+    let checksums = downcast_column::<StringArray>(batch, "checksum")?;
+    Ok(vnode_ids
+        .iter()
+        .zip(checksums.iter())
+        .filter_map(|(id, checksum)| Some((id?, checksum?.to_string())))
+        .collect())
+}
+
+/// Build the result of `CHECKSUM GROUP`, a vnode is consistent if it's checksum equals to
+/// the checksum of the leader vnode.
+pub fn replica_checksum_batch(
+    leader_vnode_id: VnodeId,
+    checksums: &[(VnodeId, String)],
+) -> CoordinatorResult<RecordBatch> {
+    let leader_checksum = checksums
+        .iter()
+        .find(|(id, _)| *id == leader_vnode_id)
+        .map(|(_, checksum)| checksum);
+
+    let mut vnode_id_array = UInt32Builder::with_capacity(checksums.len());
+    let mut checksum_array = StringBuilder::with_capacity(checksums.len(), 64 * checksums.len());
+    let mut consistent_array = BooleanBuilder::with_capacity(checksums.len());
+    for (vnode_id, checksum) in checksums {
+        vnode_id_array.append_value(*vnode_id);
+        checksum_array.append_value(checksum);
+        consistent_array.append_value(leader_checksum == Some(checksum));
+    }
+
+    Ok(RecordBatch::try_new(
+        replica_checksum_schema(),
+        vec![
+            Arc::new(vnode_id_array.finish()),
+            Arc::new(checksum_array.finish()),
+            Arc::new(consistent_array.finish()),
+        ],
+    )?)
+}
+
+/// Read checksums of each series and time range from record batches of
+/// `tskv::vnode_series_checksum_schema`.
+pub fn series_checksums(
+    batch: &RecordBatch,
+) -> CoordinatorResult<HashMap<SeriesTimeRange, String>> {
+    let series_keys = downcast_column::<BinaryArray>(batch, "series_key")?;
+    let min_ts = downcast_column::<Int64Array>(batch, "min_ts")?;
+    let max_ts = downcast_column::<Int64Array>(batch, "max_ts")?;
+    let checksums = downcast_column::<StringArray>(batch, "checksum")?;
+
+    let mut series_checksums = HashMap::with_capacity(batch.num_rows());
+    for i in 0..batch.num_rows() {
+        let series_key =
+            SeriesKey::decode(series_keys.value(i)).map_err(|e| CoordinatorError::CommonError {
+                msg: format!("invalid series key in checksums: {}", e),
+            })?;
+        let time_range = TimeRange::new(min_ts.value(i), max_ts.value(i));
+        series_checksums.insert((series_key, time_range), checksums.value(i).to_string());
+    }
+    Ok(series_checksums)
+}
+
+/// Removes checksums of time ranges not ended before `now_nanos`, `table_precisions` is the
+/// precision of the time column of each table.
+pub fn remove_live_time_ranges(
+    checksums: &mut HashMap<SeriesTimeRange, String>,
+    now_nanos: Timestamp,
+    table_precisions: &HashMap<String, Precision>,
+) {
+    checksums.retain(|(series_key, time_range), _| {
+        let precision = table_precisions
+            .get(series_key.table())
+            .copied()
+            .unwrap_or(Precision::NS);
+        to_nanos(precision, time_range.max_ts) < now_nanos
+    });
+}
+
+/// Series and time ranges to repair, grouped by table and the vnode to read the healthy data
+/// from, the vnode is `None` if the healthy data is absent (only deletion is needed).
+pub type RepairPlan = BTreeMap<(String, Option<VnodeId>), Vec<SeriesTimeRange>>;
+
+/// Returns series and time ranges whose checksums are not equal in all vnodes, along with
+/// the vnode holding the healthy data of them.
+///
+/// The healthy data of a series and time range is the checksum (or absence of data) shared by
+/// more than half of the vnodes; if there is no such majority, the leader vnode is trusted.
+pub fn plan_repair(
+    leader_vnode_id: VnodeId,
+    vnode_checksums: &BTreeMap<VnodeId, HashMap<SeriesTimeRange, String>>,
+) -> RepairPlan {
+    let mut keys: Vec<&SeriesTimeRange> = vnode_checksums
+        .values()
+        .flat_map(|checksums| checksums.keys())
+        .collect();
+    keys.sort_by(|a, b| (a.0.string(), a.1.min_ts).cmp(&(b.0.string(), b.1.min_ts)));
+    keys.dedup();
+
+    let mut plan = RepairPlan::new();
+    for key in keys {
+        let mut votes: Vec<(Option<&String>, Vec<VnodeId>)> = vec![];
+        for (vnode_id, checksums) in vnode_checksums {
+            let checksum = checksums.get(key);
+            match votes.iter_mut().find(|(c, _)| *c == checksum) {
+                Some((_, vnode_ids)) => vnode_ids.push(*vnode_id),
+                None => votes.push((checksum, vec![*vnode_id])),
+            }
+        }
+        if votes.len() <= 1 {
+            continue;
+        }
+
+        let healthy = votes
+            .iter()
+            .find(|(_, vnode_ids)| vnode_ids.len() * 2 > vnode_checksums.len())
+            .or_else(|| {
+                votes
+                    .iter()
+                    .find(|(_, vnode_ids)| vnode_ids.contains(&leader_vnode_id))
+            });
+        let (checksum, vnode_ids) = match healthy {
+            Some(healthy) => healthy,
+            None => continue,
+        };
+        let source = checksum.map(|_| {
+            if vnode_ids.contains(&leader_vnode_id) {
+                leader_vnode_id
+            } else {
+                vnode_ids[0]
+            }
+        });
+        plan.entry((key.0.table().clone(), source))
+            .or_default()
+            .push(key.clone());
+    }
+    plan
+}
+
+/// Rows to write and points to delete to make the vnodes converge to the healthy data.
+#[derive(Debug, Default, PartialEq)]
+pub struct SeriesUpsert {
+    /// Indices of rows in the healthy data to write.
+    pub rows: Vec<u32>,
+    /// Points only found on divergent vnodes, the time range of each point is `[ts, ts]`.
+    pub deletes: Vec<SeriesTimeRange>,
+}
+
+/// Compares rows of the healthy vnode with rows of the other vnodes read by
+/// `tskv::vnode_series_data`, rows are identified by series and timestamp.
+///
+/// Only divergent rows are repaired, so points written to other timestamps after the checksums
+/// were taken are kept:
+/// - rows of the healthy data which are absent or different on a vnode are written;
+/// - rows absent in the healthy data are deleted.
+///
+/// The leader vnode applies writes no later than followers, a row of the leader may be newer than
+/// the healthy data read from a follower, so rows held by the leader are never deleted or
+/// overwritten by different values. Data of the other vnodes should be read before the healthy data, a row absent in
+/// the healthy data is then not a write the healthy vnode has not applied yet.
+pub fn plan_series_upsert(
+    table_schema: &TskvTableSchema,
+    series_keys: &[&SeriesKey],
+    leader_vnode_id: VnodeId,
+    healthy: Option<&RecordBatch>,
+    others: &[(VnodeId, RecordBatch)],
+) -> CoordinatorResult<SeriesUpsert> {
+    let healthy_rows = match healthy {
+        Some(batch) => series_rows(table_schema, series_keys, batch)?,
+        None => HashMap::new(),
+    };
+    let mut other_rows = Vec::with_capacity(others.len());
+    for (vnode_id, batch) in others {
+        other_rows.push((*vnode_id, series_rows(table_schema, series_keys, batch)?));
+    }
+    let leader_rows = other_rows
+        .iter()
+        .find(|(vnode_id, _)| *vnode_id == leader_vnode_id)
+        .map(|(_, rows)| rows);
+
+    let mut writes = BTreeSet::new();
+    let mut deletes = BTreeSet::new();
+    for (_, rows) in other_rows.iter() {
+        for (key, (row, values)) in healthy_rows.iter() {
+            if rows.get(key).map(|(_, v)| v) == Some(values) {
+                continue;
+            }
+            let leader_value = leader_rows
+                .and_then(|leader| leader.get(key))
+                .map(|(_, v)| v);
+            if leader_value.map_or(false, |v| v != values) {
+                continue;
+            }
+            writes.insert(*row);
+        }
+        for key in rows.keys() {
+            if healthy_rows.contains_key(key) {
+                continue;
+            }
+            if leader_rows.map_or(false, |leader| leader.contains_key(key)) {
+                continue;
+            }
+            deletes.insert(*key);
+        }
+    }
+
+    Ok(SeriesUpsert {
+        rows: writes.into_iter().collect(),
+        deletes: deletes
+            .into_iter()
+            .map(|(series, ts)| (series_keys[series].clone(), TimeRange::new(ts, ts)))
+            .collect(),
+    })
+}
+
+type SeriesRows = HashMap<(usize, Timestamp), (u32, Vec<ScalarValue>)>;
+
+/// Read rows of a record batch of `tskv::vnode_series_data`, returns a map from
+/// `(index of series key, timestamp)` to `(row index, field values)`.
+fn series_rows(
+    table_schema: &TskvTableSchema,
+    series_keys: &[&SeriesKey],
+    batch: &RecordBatch,
+) -> CoordinatorResult<SeriesRows> {
+    let columns = table_schema.columns();
+    if batch.num_columns() != columns.len() {
+        return Err(CoordinatorError::CommonError {
+            msg: format!(
+                "series data has {} columns, table {} has {} columns",
+                batch.num_columns(),
+                table_schema.name,
+                columns.len()
+            ),
+        });
+    }
+
+    let mut tag_arrays = vec![];
+    let mut field_arrays = vec![];
+    let mut time_array = None;
+    for (column, array) in columns.iter().zip(batch.columns()) {
+        if column.column_type.is_tag() {
+            let array = array
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| CoordinatorError::CommonError {
+                    msg: format!("tag column {} of series data is not string", column.name),
+                })?;
+            tag_arrays.push((column.name.as_str(), array));
+        } else if column.column_type.is_field() {
+            field_arrays.push(array);
+        } else if column.column_type.is_time() {
+            time_array = Some(cast(array, &DataType::Int64)?);
+        }
+    }
+    let time_array = time_array.ok_or_else(|| CoordinatorError::CommonError {
+        msg: format!("time column of table {} not found", table_schema.name),
+    })?;
+    let time_array = time_array
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .ok_or_else(|| CoordinatorError::CommonError {
+            msg: "time column of series data is not timestamp".to_string(),
+        })?;
+
+    let mut series = HashMap::with_capacity(series_keys.len());
+    for (i, series_key) in series_keys.iter().enumerate() {
+        let tags = tag_arrays
+            .iter()
+            .map(|(name, _)| series_key.tag_val(name))
+            .collect::<Vec<_>>();
+        series.insert(tags, i);
+    }
+
+    let mut rows = HashMap::with_capacity(batch.num_rows());
+    for row in 0..batch.num_rows() {
+        let tags = tag_arrays
+            .iter()
+            .map(|(_, array)| (!array.is_null(row)).then(|| array.value(row).as_bytes().to_vec()))
+            .collect::<Vec<_>>();
+        let series = match series.get(&tags) {
+            Some(series) => *series,
+            None => continue,
+        };
+        let values = field_arrays
+            .iter()
+            .map(|array| ScalarValue::try_from_array(array, row))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CoordinatorError::CommonError {
+                msg: format!("read series data error: {}", e),
+            })?;
+        rows.insert((series, time_array.value(row)), (row as u32, values));
+    }
+    Ok(rows)
+}
+
+fn downcast_column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> CoordinatorResult<&'a T> {
+    batch
+        .column_by_name(name)
+        .and_then(|c| c.as_any().downcast_ref::<T>())
+        .ok_or_else(|| CoordinatorError::CommonError {
+            msg: format!("column {} not found in checksums", name),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, HashMap};
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::{
+        BooleanArray, Int64Array, StringArray, TimestampNanosecondArray,
+    };
+    use datafusion::arrow::datatypes::TimeUnit;
+    use datafusion::arrow::record_batch::RecordBatch;
+    use models::codec::Encoding;
+    use models::predicate::domain::TimeRange;
+    use models::schema::{ColumnType, Precision, TableColumn, TskvTableSchema};
+    use models::{SeriesKey, Tag, ValueType};
+
+    use super::{
+        plan_repair, plan_series_upsert, remove_live_time_ranges, replica_checksum_batch,
+        SeriesUpsert,
+    };
+
+    fn series_key(table: &str, host: &str) -> SeriesKey {
+        SeriesKey {
+            tags: vec![Tag::new(b"host".to_vec(), host.as_bytes().to_vec())],
+            table: table.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_plan_repair() {
+        let tr_1 = TimeRange::new(0, 99);
+        let tr_2 = TimeRange::new(100, 199);
+        let leader = HashMap::from([
+            ((series_key("air", "a"), tr_1), "1".to_string()),
+            ((series_key("air", "a"), tr_2), "0".to_string()),
+            ((series_key("sea", "b"), tr_1), "3".to_string()),
+            ((series_key("wind", "c"), tr_1), "4".to_string()),
+        ]);
+        let follower = HashMap::from([
+            ((series_key("air", "a"), tr_1), "1".to_string()),
+            ((series_key("air", "a"), tr_2), "2".to_string()),
+            ((series_key("sea", "b"), tr_1), "3".to_string()),
+        ]);
+
+        // The leader is in the minority, data is repaired from followers.
+        let vnode_checksums = BTreeMap::from([
+            (1, leader.clone()),
+            (2, follower.clone()),
+            (3, follower.clone()),
+        ]);
+        let plan = plan_repair(1, &vnode_checksums);
+        assert_eq!(plan.len(), 2);
+        assert_eq!(
+            plan[&("air".to_string(), Some(2))],
+            vec![(series_key("air", "a"), tr_2)]
+        );
+        assert_eq!(
+            plan[&("wind".to_string(), None)],
+            vec![(series_key("wind", "c"), tr_1)]
+        );
+
+        // No majority, the leader is trusted.
+        let vnode_checksums = BTreeMap::from([(1, leader.clone()), (2, follower.clone())]);
+        let plan = plan_repair(1, &vnode_checksums);
+        assert_eq!(plan.len(), 2);
+        assert_eq!(
+            plan[&("air".to_string(), Some(1))],
+            vec![(series_key("air", "a"), tr_2)]
+        );
+        assert_eq!(
+            plan[&("wind".to_string(), Some(1))],
+            vec![(series_key("wind", "c"), tr_1)]
+        );
+
+        let vnode_checksums = BTreeMap::from([(1, leader.clone()), (2, leader)]);
+        assert!(plan_repair(1, &vnode_checksums).is_empty());
+    }
+
+    #[test]
+    fn test_replica_checksum_batch() {
+        let checksums = vec![
+            (1, "a".to_string()),
+            (2, "a".to_string()),
+            (3, "b".to_string()),
+        ];
+        let batch = replica_checksum_batch(1, &checksums).unwrap();
+        let consistent = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<BooleanArray>()
+            .unwrap();
+        assert_eq!(
+            consistent.iter().collect::<Vec<_>>(),
+            vec![Some(true), Some(true), Some(false)]
+        );
+    }
+
+    #[test]
+    fn test_remove_live_time_ranges() {
+        let mut checksums = HashMap::from([
+            (
+                (series_key("air", "a"), TimeRange::new(0, 99)),
+                "1".to_string(),
+            ),
+            (
+                (series_key("air", "a"), TimeRange::new(100, 199)),
+                "2".to_string(),
+            ),
+            (
+                (series_key("sea", "b"), TimeRange::new(0, 9)),
+                "3".to_string(),
+            ),
+        ]);
+        let table_precisions = HashMap::from([("sea".to_string(), Precision::US)]);
+        remove_live_time_ranges(&mut checksums, 150, &table_precisions);
+        assert_eq!(
+            checksums,
+            HashMap::from([(
+                (series_key("air", "a"), TimeRange::new(0, 99)),
+                "1".to_string()
+            )])
+        );
+    }
+
+    fn table_schema() -> TskvTableSchema {
+        TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "air".to_string(),
+            vec![
+                TableColumn::new_time_column(0, TimeUnit::Nanosecond),
+                TableColumn::new_tag_column(1, "host".to_string()),
+                TableColumn::new(
+                    2,
+                    "value".to_string(),
+                    ColumnType::Field(ValueType::Integer),
+                    Encoding::default(),
+                ),
+            ],
+        )
+    }
+
+    fn series_data(rows: &[(&str, i64, i64)]) -> RecordBatch {
+        let schema = table_schema().to_arrow_schema();
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(TimestampNanosecondArray::from(
+                    rows.iter().map(|r| r.1).collect::<Vec<_>>(),
+                )),
+                Arc::new(StringArray::from(
+                    rows.iter().map(|r| r.0).collect::<Vec<_>>(),
+                )),
+                Arc::new(Int64Array::from(
+                    rows.iter().map(|r| r.2).collect::<Vec<_>>(),
+                )),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_plan_series_upsert() {
+        let schema = table_schema();
+        let key_a = series_key("air", "a");
+        let key_b = series_key("air", "b");
+        let series_keys = vec![&key_a, &key_b];
+
+        // Vnode 2 and 3 are followers, the healthy data is read from vnode 2.
+        let healthy = series_data(&[("a", 1, 1), ("a", 2, 2), ("b", 1, 1)]);
+        // Vnode 3 lost `a@2` and `b@1`, and holds a stale point `a@5`.
+        let divergent = series_data(&[("a", 1, 1), ("a", 5, 5)]);
+        // Points `a@3` and `b@1 = 10` are written concurrently after the checksums were taken,
+        // the leader has applied them but the followers not yet.
+        let leader = series_data(&[("a", 1, 1), ("a", 2, 2), ("a", 3, 3), ("b", 1, 10)]);
+
+        let upsert = plan_series_upsert(
+            &schema,
+            &series_keys,
+            1,
+            Some(&healthy),
+            &[(1, leader), (3, divergent)],
+        )
+        .unwrap();
+        assert_eq!(
+            upsert,
+            SeriesUpsert {
+                rows: vec![1],
+                deletes: vec![(key_a.clone(), TimeRange::new(5, 5))],
+            }
+        );
+
+        // The healthy data is absent, only points not held by the leader are deleted.
+        let leader = series_data(&[("a", 3, 3)]);
+        let divergent = series_data(&[("a", 3, 3), ("b", 4, 4)]);
+        let upsert = plan_series_upsert(
+            &schema,
+            &series_keys,
+            1,
+            None,
+            &[(1, leader), (2, divergent)],
+        )
+        .unwrap();
+        assert_eq!(
+            upsert,
+            SeriesUpsert {
+                rows: vec![],
+                deletes: vec![(key_b.clone(), TimeRange::new(4, 4))],
+            }
+        );
+    }
+}
@@ -1,8 +1,10 @@
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
+use async_trait::async_trait;
 use models::meta_data::ReplicationSet;
-use models::schema::{ResourceInfo, ResourceOperator, ResourceStatus, TableSchema};
+use models::predicate::domain::TimeRange;
+use models::schema::{ResourceInfo, ResourceOperator, ResourceStatus, ScheduledTask, TableSchema};
 use protos::kv_service::{
     raft_write_command, DropColumnRequest, DropTableRequest, RaftWriteCommand, UpdateSetValue,
     UpdateTagsRequest,
@@ -10,9 +12,24 @@ use protos::kv_service::{
 use tokio::time::sleep;
 use tracing::{debug, error, info};
 
+use crate::data_change::{notify_data_change, DataChange};
 use crate::errors::*;
 use crate::{Coordinator, VnodeManagerCmdType};
 
+/// Runs the statement of a [`ScheduledTask`], the coordinator can't execute sql by itself.
+#[async_trait]
+pub trait ScheduledTaskExecutor: Send + Sync {
+    /// Run the task once, returns the task with the new high water mark.
+    async fn execute(&self, task: &ScheduledTask) -> CoordinatorResult<ScheduledTask>;
+}
+
+static TASK_EXECUTOR: OnceLock<Arc<dyn ScheduledTaskExecutor>> = OnceLock::new();
+
+/// Set the executor of scheduled tasks, only the first call takes effect.
+pub fn register_task_executor(executor: Arc<dyn ScheduledTaskExecutor>) {
+    let _ = TASK_EXECUTOR.set(executor);
+}
+
 #[derive(Clone)]
 pub struct ResourceManager {}
 
@@ -21,6 +38,7 @@ impl ResourceManager {
         coord: Arc<dyn Coordinator>,
         mut resourceinfo: ResourceInfo,
     ) -> CoordinatorResult<bool> {
+        let mut next_task = None;
         let operator_result = match resourceinfo.get_operator() {
             ResourceOperator::DropTenant(tenant_name) => {
                 ResourceManager::drop_tenant(coord.clone(), tenant_name).await
@@ -54,7 +72,24 @@ impl ResourceManager {
                 )
                 .await
             }
+            ResourceOperator::ScheduledTask(task) => {
+                ResourceManager::run_scheduled_task(task).await.map(|task| {
+                    next_task = Some(task);
+                    true
+                })
+            }
         };
+        if matches!(
+            resourceinfo.get_operator(),
+            ResourceOperator::ScheduledTask(_)
+        ) && ResourceManager::is_dropped_task(coord.clone(), resourceinfo.get_name()).await?
+        {
+            debug!("Scheduled task {} is dropped", resourceinfo.get_name());
+            return operator_result;
+        }
+        if let Some(task) = next_task {
+            return ResourceManager::schedule_next_run(coord, &resourceinfo, task).await;
+        }
         resourceinfo.set_is_new_add(false);
         let mut status_comment = (ResourceStatus::Successed, String::default());
         if let Err(coord_err) = &operator_result {
@@ -128,6 +163,12 @@ impl ResourceManager {
             .drop_db(db_name)
             .await
             .map_err(|err| CoordinatorError::Meta { source: err })?;
+        notify_data_change(DataChange {
+            tenant: tenant_name.to_string(),
+            database: db_name.to_string(),
+            table: None,
+            time_range: TimeRange::all(),
+        });
 
         Ok(true)
     }
@@ -180,6 +221,12 @@ impl ResourceManager {
             .drop_table(db_name, table_name)
             .await
             .map_err(|err| CoordinatorError::Meta { source: err })?;
+        notify_data_change(DataChange {
+            tenant: tenant_name.to_string(),
+            database: db_name.to_string(),
+            table: Some(table_name.to_string()),
+            time_range: TimeRange::all(),
+        });
 
         Ok(true)
     }
@@ -295,13 +342,59 @@ impl ResourceManager {
             requests.push(request);
         }
 
-        for result in futures::future::join_all(requests).await {
+        let results = futures::future::join_all(requests).await;
+        // The tables of the series are encoded in the keys, notify the whole database.
+        notify_data_change(DataChange {
+            tenant: tenant_name.to_string(),
+            database: db_name.to_string(),
+            table: None,
+            time_range: TimeRange::all(),
+        });
+        for result in results {
             result?
         }
 
         Ok(true)
     }
 
+    async fn run_scheduled_task(task: &ScheduledTask) -> CoordinatorResult<ScheduledTask> {
+        let executor = TASK_EXECUTOR
+            .get()
+            .ok_or_else(|| CoordinatorError::CommonError {
+                msg: "scheduled task executor is not registered".to_string(),
+            })?;
+        info!("Run scheduled task {} of tenant {}", task.name, task.tenant);
+        executor.execute(task).await
+    }
+
+    /// A task is dropped by setting it's status to `Cancel` while it may be running.
+    async fn is_dropped_task(coord: Arc<dyn Coordinator>, name: &str) -> CoordinatorResult<bool> {
+        let current = coord.meta_manager().read_resourceinfo_by_name(name).await?;
+        Ok(current.map_or(true, |info| *info.get_status() == ResourceStatus::Cancel))
+    }
+
+    /// Replace the finished run of a task by the next run.
+    async fn schedule_next_run(
+        coord: Arc<dyn Coordinator>,
+        resourceinfo: &ResourceInfo,
+        task: ScheduledTask,
+    ) -> CoordinatorResult<bool> {
+        let schedule = Some(task.schedule.clone());
+        let next_run = ResourceInfo::new(
+            resourceinfo.get_tenant_id_and_db().clone(),
+            resourceinfo.get_name().to_string(),
+            ResourceOperator::ScheduledTask(task),
+            &schedule,
+            coord.node_id(),
+        );
+        coord
+            .meta_manager()
+            .write_resourceinfo(next_run.get_name(), next_run)
+            .await?;
+
+        Ok(true)
+    }
+
     pub async fn add_resource_task(
         coord: Arc<dyn Coordinator>,
         mut resourceinfo: ResourceInfo,
@@ -366,6 +459,9 @@ impl ResourceManager {
                 .await;
             match res {
                 Ok(Some(res)) => {
+                    if *res.get_status() == ResourceStatus::Cancel {
+                        break;
+                    }
                     resourceinfo = res;
                 }
                 Ok(None) => {
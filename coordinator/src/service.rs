@@ -2,7 +2,7 @@
 #![allow(unused)]
 #![allow(clippy::type_complexity)]
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Debug;
 use std::future::Future;
 use std::pin::Pin;
@@ -31,7 +31,8 @@ use metrics::label::Labels;
 use metrics::metric::Metric;
 use metrics::metric_register::MetricsRegister;
 use models::meta_data::{
-    ExpiredBucketInfo, MetaModifyType, ReplicationSet, ReplicationSetId, VnodeInfo, VnodeStatus,
+    ExpiredBucketInfo, MetaModifyType, ReplicaAllInfo, ReplicationSet, ReplicationSetId, VnodeId,
+    VnodeInfo, VnodeStatus,
 };
 use models::object_reference::ResolvedTable;
 use models::oid::Identifier;
@@ -42,6 +43,7 @@ use models::schema::{
 };
 use models::utils::now_timestamp_nanos;
 use models::{record_batch_decode, ColumnId, SeriesKey, Tag};
+use protocol_parser::arrow_convert::record_batch_to_table_schema;
 use protocol_parser::lines_convert::{
     arrow_array_to_points, line_to_batches, mutable_batches_to_point,
 };
@@ -61,6 +63,7 @@ use trace::{debug, error, info, SpanContext, SpanExt, SpanRecorder};
 use tskv::{EngineRef, Error};
 use utils::BkdrHasher;
 
+use crate::data_change::{notify_data_change, to_nanos, DataChange, WrittenTimeRanges};
 use crate::errors::*;
 use crate::metrics::LPReporter;
 use crate::raft::manager::RaftNodesManager;
@@ -70,8 +73,9 @@ use crate::reader::tag_scan::opener::TemporaryTagScanOpener;
 use crate::reader::{CheckFuture, CheckedCoordinatorRecordBatchStream};
 use crate::resource_manager::ResourceManager;
 use crate::{
-    get_replica_all_info, get_vnode_all_info, status_response_to_result, Coordinator, QueryOption,
-    SendableCoordinatorRecordBatchStream, VnodeManagerCmdType, VnodeSummarizerCmdType,
+    get_replica_all_info, get_vnode_all_info, replica_check, status_response_to_result,
+    Coordinator, QueryOption, SendableCoordinatorRecordBatchStream, VnodeManagerCmdType,
+    VnodeSummarizerCmdType,
 };
 
 pub type CoordinatorRef = Arc<dyn Coordinator>;
@@ -79,6 +83,9 @@ pub type CoordinatorRef = Arc<dyn Coordinator>;
 use models::schema::USAGE_SCHEMA;
 use protos::{tskv_service_time_out_client, DEFAULT_GRPC_SERVER_MESSAGE_LEN};
 
+/// Max number of series time ranges repaired in a request.
+const REPAIR_SERIES_BATCH_SIZE: usize = 1000;
+
 #[derive(Clone)]
 pub struct CoordService {
     node_id: u64,
@@ -274,7 +281,10 @@ impl CoordService {
                         }
                         ResourceStatus::Failed => {
                             if let Ok(mut joinhandle_map) = coord.failed_task_joinhandle.lock() {
-                                if joinhandle_map.contains_key(resourceinfo.get_name()) {
+                                if joinhandle_map
+                                    .get(resourceinfo.get_name())
+                                    .is_some_and(|handle| !handle.is_finished())
+                                {
                                     return Ok(()); // ignore repetition failed task
                                 }
                                 let coord = coord.clone();
@@ -294,6 +304,12 @@ impl CoordService {
                                 }
                                 joinhandle_map.remove(resourceinfo.get_name()); // remove task
                             }
+                            if let Ok(mut joinhandle_map) = coord.failed_task_joinhandle.lock() {
+                                if let Some(handle) = joinhandle_map.remove(resourceinfo.get_name())
+                                {
+                                    handle.abort(); // stop retrying
+                                }
+                            }
                         }
                         _ => {}
                     }
@@ -467,6 +483,23 @@ impl CoordService {
 
         meta.delete_bucket(&info.database, info.bucket.id).await?;
 
+        let time_range = match meta.get_db_schema(&info.database).ok().flatten() {
+            Some(db_schema) => {
+                let precision = *db_schema.config.precision_or_default();
+                TimeRange::new(
+                    to_nanos(precision, info.bucket.start_time),
+                    to_nanos(precision, info.bucket.end_time),
+                )
+            }
+            None => TimeRange::all(),
+        };
+        notify_data_change(DataChange {
+            tenant: info.tenant.clone(),
+            database: info.database.clone(),
+            table: None,
+            time_range,
+        });
+
         Ok(())
     }
 
@@ -550,6 +583,226 @@ impl CoordService {
         }
     }
 
+    /// Compare checksums of each series and time range of vnodes, the data held by the majority
+    /// of vnodes (or the leader vnode if there is no majority) is treated as healthy, then
+    /// upsert the divergent rows of the healthy data into vnodes through raft.
+    ///
+    /// Time ranges not ended yet are skipped, see [`replica_check::remove_live_time_ranges`].
+    async fn repair_replication_set(
+        &self,
+        tenant: &str,
+        all_info: ReplicaAllInfo,
+    ) -> CoordinatorResult<()> {
+        let replica = all_info.replica_set;
+        let db = all_info.db_name.as_str();
+        let meta_client =
+            self.meta
+                .tenant_meta(tenant)
+                .await
+                .ok_or(CoordinatorError::TenantNotFound {
+                    name: tenant.to_string(),
+                })?;
+        let db_precision = *meta_client
+            .get_db_schema(db)?
+            .ok_or_else(|| MetaError::DatabaseNotFound {
+                database: db.to_string(),
+            })?
+            .config
+            .precision_or_default();
+
+        let now = now_timestamp_nanos();
+        let mut table_precisions = HashMap::new();
+        let mut vnode_checksums = BTreeMap::new();
+        for vnode in replica.vnodes.iter() {
+            let cmd = AdminFetchCommandRequest {
+                tenant: tenant.to_string(),
+                command: Some(
+                    admin_fetch_command_request::Command::FetchVnodeSeriesChecksum(
+                        FetchVnodeSeriesChecksumRequest { vnode_id: vnode.id },
+                    ),
+                ),
+            };
+            let record_batch = self
+                .exec_admin_fetch_command_on_node(vnode.node_id, cmd)
+                .await?;
+            let mut checksums = replica_check::series_checksums(&record_batch)?;
+            for (series_key, _) in checksums.keys() {
+                if !table_precisions.contains_key(series_key.table()) {
+                    if let Some(schema) =
+                        meta_client.get_tskv_table_schema(db, series_key.table())?
+                    {
+                        table_precisions
+                            .insert(series_key.table().clone(), schema.time_column_precision());
+                    }
+                }
+            }
+            replica_check::remove_live_time_ranges(&mut checksums, now, &table_precisions);
+            vnode_checksums.insert(vnode.id, checksums);
+        }
+
+        let plan = replica_check::plan_repair(replica.leader_vnode_id, &vnode_checksums);
+        for ((table, source), series_time_ranges) in plan {
+            info!(
+                "Repair: {} series time ranges of table '{}' in replication set {} diverge, healthy vnode: {:?}",
+                series_time_ranges.len(),
+                table,
+                replica.id,
+                source
+            );
+            let table_schema = meta_client
+                .get_tskv_table_schema(db, &table)?
+                .ok_or_else(|| MetaError::TableNotFound {
+                    table: table.clone(),
+                })?;
+            for series_time_ranges in series_time_ranges.chunks(REPAIR_SERIES_BATCH_SIZE) {
+                self.repair_vnode_series(
+                    tenant,
+                    db_precision,
+                    table_schema.clone(),
+                    &replica,
+                    source,
+                    series_time_ranges,
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read data of the series in time ranges from vnodes, then propose a raft command to upsert
+    /// the divergent rows of the healthy vnode and delete the points absent in it.
+    ///
+    /// Data of the other vnodes is read before the healthy vnode, see
+    /// [`replica_check::plan_series_upsert`].
+    async fn repair_vnode_series(
+        &self,
+        tenant: &str,
+        db_precision: Precision,
+        table_schema: TskvTableSchemaRef,
+        replica: &ReplicationSet,
+        healthy: Option<VnodeId>,
+        series_time_ranges: &[replica_check::SeriesTimeRange],
+    ) -> CoordinatorResult<()> {
+        let series_time_ranges_bytes =
+            bincode::serialize(series_time_ranges).map_err(|e| CoordinatorError::CommonError {
+                msg: format!("serialize series time ranges error: {}", e),
+            })?;
+        let fetch_series_data = |vnode: &VnodeInfo| {
+            let cmd = AdminFetchCommandRequest {
+                tenant: tenant.to_string(),
+                command: Some(admin_fetch_command_request::Command::FetchVnodeSeriesData(
+                    FetchVnodeSeriesDataRequest {
+                        vnode_id: vnode.id,
+                        table: table_schema.name.clone(),
+                        series_time_ranges: series_time_ranges_bytes.clone(),
+                    },
+                )),
+            };
+            self.exec_admin_fetch_command_on_node(vnode.node_id, cmd)
+        };
+
+        let mut others = Vec::with_capacity(replica.vnodes.len());
+        for vnode in replica.vnodes.iter().filter(|v| Some(v.id) != healthy) {
+            others.push((vnode.id, fetch_series_data(vnode).await?));
+        }
+        let healthy = match healthy {
+            Some(id) => {
+                let vnode = replica
+                    .vnode(id)
+                    .ok_or(CoordinatorError::VnodeNotFound { id })?;
+                Some(fetch_series_data(&vnode).await?)
+            }
+            None => None,
+        };
+
+        let mut series_keys: Vec<&SeriesKey> = series_time_ranges.iter().map(|(k, _)| k).collect();
+        series_keys.dedup();
+        let upsert = replica_check::plan_series_upsert(
+            &table_schema,
+            &series_keys,
+            replica.leader_vnode_id,
+            healthy.as_ref(),
+            &others,
+        )?;
+        if upsert.rows.is_empty() && upsert.deletes.is_empty() {
+            return Ok(());
+        }
+
+        let points = match healthy {
+            Some(record_batch) if !upsert.rows.is_empty() => {
+                let indices = UInt32Array::from(upsert.rows);
+                let columns = record_batch
+                    .columns()
+                    .iter()
+                    .map(|c| take(c, &indices, None))
+                    .collect::<Result<Vec<_>, _>>()?;
+                arrow_array_to_points(
+                    columns,
+                    record_batch.schema(),
+                    table_schema.clone(),
+                    indices.len(),
+                )
+                .map_err(|e| CoordinatorError::CommonError {
+                    msg: format!("arrow array to points error: {}", e),
+                })?
+            }
+            _ => vec![],
+        };
+        let deletes =
+            bincode::serialize(&upsert.deletes).map_err(|e| CoordinatorError::CommonError {
+                msg: format!("serialize series time ranges error: {}", e),
+            })?;
+
+        let command = RaftWriteCommand {
+            replica_id: replica.id,
+            tenant: tenant.to_string(),
+            db_name: table_schema.db.clone(),
+            command: Some(raft_write_command::Command::RepairVnode(
+                RepairVnodeRequest {
+                    table: table_schema.name.clone(),
+                    series_time_ranges: deletes,
+                    points,
+                    precision: db_precision as u32,
+                },
+            )),
+        };
+        self.write_replica_by_raft(replica.clone(), command, None)
+            .await
+    }
+
+    /// Notify the time ranges of the table deleted by the predicate, all time ranges
+    /// are notified if the precision of the database is unknown.
+    async fn notify_deleted(&self, table: &ResolvedTable, predicate: &ResolvedPredicate) {
+        let precision = match self.meta.tenant_meta(table.tenant()).await {
+            Some(client) => client
+                .get_db_schema(table.database())
+                .ok()
+                .flatten()
+                .map(|db_schema| *db_schema.config.precision_or_default()),
+            None => None,
+        };
+        let time_ranges = predicate.time_ranges();
+        let ranges = match precision {
+            Some(precision) => time_ranges
+                .time_ranges()
+                .iter()
+                .map(|r| {
+                    TimeRange::new(to_nanos(precision, r.min_ts), to_nanos(precision, r.max_ts))
+                })
+                .collect::<Vec<_>>(),
+            None => vec![TimeRange::all()],
+        };
+        for time_range in ranges {
+            notify_data_change(DataChange {
+                tenant: table.tenant().to_string(),
+                database: table.database().to_string(),
+                table: Some(table.table().to_string()),
+                time_range,
+            });
+        }
+    }
+
     async fn push_points_to_requests<'a>(
         &'a self,
         tenant: &'a str,
@@ -723,12 +976,14 @@ impl Coordinator for CoordService {
         }
 
         let db_precision = db_schema.config.precision_or_default();
+        let mut written_ranges = WrittenTimeRanges::default();
         for line in lines {
             let ts = timestamp_convert(precision, *db_precision, line.timestamp).ok_or(
                 CoordinatorError::CommonError {
                     msg: "timestamp overflow".to_string(),
                 },
             )?;
+            written_ranges.add(&line.table, *db_precision, ts);
             let info = meta_client
                 .locate_replication_set_for_write(db, line.hash_id, ts)
                 .await?;
@@ -750,7 +1005,10 @@ impl Coordinator for CoordService {
             );
         }
         let now = tokio::time::Instant::now();
-        for res in futures::future::join_all(requests).await {
+        let results = futures::future::join_all(requests).await;
+        // Some of the requests may be written even if others failed.
+        written_ranges.notify(tenant, db);
+        for res in results {
             debug!(
                 "Parallel write points on vnode over, start at: {:?}, elapsed: {} millis, result: {:?}",
                 now,
@@ -782,6 +1040,7 @@ impl Coordinator for CoordService {
                 })?;
 
         let mut repl_idx: HashMap<ReplicationSet, Vec<u32>> = HashMap::new();
+        let mut written_ranges = WrittenTimeRanges::default();
         let schema = record_batch.schema().fields.clone();
         let table_name = table_schema.name.as_str();
         let columns = record_batch.columns();
@@ -841,6 +1100,7 @@ impl Coordinator for CoordService {
                 });
             }
 
+            written_ranges.add(table_name, db_precision, ts);
             let hash = hasher.number();
             let info = meta_client
                 .locate_replication_set_for_write(db, hash, ts)
@@ -874,7 +1134,10 @@ impl Coordinator for CoordService {
             );
         }
         let now = tokio::time::Instant::now();
-        for res in futures::future::join_all(requests).await {
+        let results = futures::future::join_all(requests).await;
+        // Some of the requests may be written even if others failed.
+        written_ranges.notify(tenant, db);
+        for res in results {
             debug!(
                 "Parallel write points on vnode over, start at: {:?}, elapsed: {} millis, result: {:?}",
                 now,
@@ -886,6 +1149,55 @@ impl Coordinator for CoordService {
         Ok(write_bytes)
     }
 
+    async fn write_table_record_batches(
+        &self,
+        tenant: &str,
+        db: &str,
+        table: &str,
+        record_batches: Vec<RecordBatch>,
+        span_ctx: Option<&SpanContext>,
+    ) -> CoordinatorResult<usize> {
+        let meta_client =
+            self.meta
+                .tenant_meta(tenant)
+                .await
+                .ok_or(CoordinatorError::TenantNotFound {
+                    name: tenant.to_string(),
+                })?;
+        let db_schema =
+            meta_client
+                .get_db_schema(db)?
+                .ok_or_else(|| MetaError::DatabaseNotFound {
+                    database: db.to_string(),
+                })?;
+        if db_schema.options().get_db_is_hidden() {
+            return Err(CoordinatorError::Meta {
+                source: MetaError::DatabaseNotFound {
+                    database: db.to_string(),
+                },
+            });
+        }
+        let table_schema = meta_client
+            .get_tskv_table_schema(db, table)?
+            .ok_or_else(|| MetaError::TableNotFound {
+                table: table.to_string(),
+            })?;
+
+        let db_precision = db_schema.config.precision_or_default();
+        let mut write_bytes = 0;
+        for record_batch in record_batches {
+            if record_batch.num_rows() == 0 {
+                continue;
+            }
+            let record_batch = record_batch_to_table_schema(&record_batch, &table_schema)
+                .map_err(|e| CoordinatorError::CommonError { msg: e.to_string() })?;
+            write_bytes += self
+                .write_record_batch(table_schema.clone(), record_batch, *db_precision, span_ctx)
+                .await?;
+        }
+        Ok(write_bytes)
+    }
+
     fn table_scan(
         &self,
         option: QueryOption,
@@ -972,7 +1284,9 @@ impl Coordinator for CoordService {
             requests.push(request);
         }
 
-        for result in futures::future::join_all(requests).await {
+        let results = futures::future::join_all(requests).await;
+        self.notify_deleted(table, predicate).await;
+        for result in results {
             debug!("exec delete from {table} WHERE {predicate:?}, now:{now:?}, elapsed:{}ms, result:{result:?}", now.elapsed().as_millis());
             result?
         }
@@ -1091,6 +1405,11 @@ impl Coordinator for CoordService {
                 )
             }
 
+            VnodeManagerCmdType::RepairReplicationSet(replica_id) => {
+                let all_info = get_replica_all_info(self.meta.clone(), tenant, replica_id).await?;
+                return self.repair_replication_set(tenant, all_info).await;
+            }
+
             VnodeManagerCmdType::Compact(vnode_ids) => {
                 // Group vnode ids by node id.
                 let mut node_vnode_ids_map: HashMap<u64, Vec<u32>> = HashMap::new();
@@ -1137,37 +1456,58 @@ impl Coordinator for CoordService {
                     .await?
                     .replica_set;
 
-                // Group vnode ids by node id.
-                let mut node_vnode_ids_map: HashMap<u64, Vec<u32>> = HashMap::new();
-                for vnode in replica.vnodes {
-                    node_vnode_ids_map
-                        .entry(vnode.node_id)
-                        .or_default()
-                        .push(vnode.id);
+                let req_futures = replica.vnodes.iter().map(|vnode| {
+                    let cmd = AdminFetchCommandRequest {
+                        tenant: tenant.to_string(),
+                        command: Some(admin_fetch_command_request::Command::FetchVnodeChecksum(
+                            FetchVnodeChecksumRequest { vnode_id: vnode.id },
+                        )),
+                    };
+                    self.exec_admin_fetch_command_on_node(vnode.node_id, cmd)
+                });
+                let mut checksums = vec![];
+                for record_batch in futures::future::try_join_all(req_futures).await? {
+                    checksums.append(&mut replica_check::vnode_checksums(&record_batch)?);
                 }
 
-                let nodes = self.meta.data_nodes().await;
-
-                // Send grouped vnode ids to nodes.
-                let mut req_futures = vec![];
-                for node in nodes {
-                    if let Some(vnode_ids) = node_vnode_ids_map.remove(&node.id) {
-                        for vnode_id in vnode_ids {
-                            let cmd = AdminFetchCommandRequest {
-                                tenant: tenant.to_string(),
-                                command: Some(
-                                    admin_fetch_command_request::Command::FetchVnodeChecksum(
-                                        FetchVnodeChecksumRequest { vnode_id },
-                                    ),
-                                ),
-                            };
-                            req_futures.push(self.exec_admin_fetch_command_on_node(node.id, cmd));
-                        }
+                // Compare checksums of all vnodes with the leader vnode.
+                let record_batch =
+                    replica_check::replica_checksum_batch(replica.leader_vnode_id, &checksums)?;
+                return Ok(vec![record_batch]);
+            }
+            VnodeSummarizerCmdType::SeriesCardinality(database, top_n) => {
+                let meta_client = self.meta.tenant_meta(tenant).await.ok_or(
+                    CoordinatorError::TenantNotFound {
+                        name: tenant.to_string(),
+                    },
+                )?;
+                let db_info = meta_client.get_db_info(&database)?.ok_or_else(|| {
+                    MetaError::DatabaseNotFound {
+                        database: database.clone(),
                     }
-                }
-                let record_batches = futures::future::try_join_all(req_futures).await?;
+                })?;
 
-                return Ok(record_batches);
+                // Replicas have the same series, so only the leader vnodes are fetched.
+                let req_futures = db_info
+                    .buckets
+                    .iter()
+                    .flat_map(|bucket| bucket.shard_group.iter())
+                    .filter_map(|replica| replica.vnode(replica.leader_vnode_id))
+                    .map(|vnode| {
+                        let cmd = AdminFetchCommandRequest {
+                            tenant: tenant.to_string(),
+                            command: Some(
+                                admin_fetch_command_request::Command::FetchVnodeSeriesCardinality(
+                                    FetchVnodeSeriesCardinalityRequest {
+                                        vnode_id: vnode.id,
+                                        top_n,
+                                    },
+                                ),
+                            ),
+                        };
+                        self.exec_admin_fetch_command_on_node(vnode.node_id, cmd)
+                    });
+                return futures::future::try_join_all(req_futures).await;
             }
         }
     }
@@ -185,6 +185,17 @@ impl Coordinator for MockCoordinator {
         todo!()
     }
 
+    async fn write_table_record_batches(
+        &self,
+        tenant: &str,
+        db: &str,
+        table: &str,
+        record_batches: Vec<RecordBatch>,
+        span_ctx: Option<&SpanContext>,
+    ) -> CoordinatorResult<usize> {
+        todo!()
+    }
+
     fn table_scan(
         &self,
         option: QueryOption,
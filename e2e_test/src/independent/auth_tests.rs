@@ -1,6 +1,7 @@
 #![cfg(test)]
 
 use std::path::Path;
+use std::time::Duration;
 
 use http_protocol::status_code;
 use serial_test::serial;
@@ -158,3 +159,150 @@ fn test1() {
         )
     }
 }
+
+#[test]
+#[serial]
+fn test_password_policy_and_login_lock() {
+    println!("Test begin auth_test password policy and login lock");
+
+    let test_dir = "/tmp/e2e_test/auth_tests/test_password_policy_and_login_lock";
+    let _ = std::fs::remove_dir_all(test_dir);
+    std::fs::create_dir_all(test_dir).unwrap();
+
+    kill_all();
+
+    let data_node_def = &cluster_def::one_data(1);
+
+    {
+        // Start cnosdb singleton with `auth_enabled = false`, alter password for root.
+        let data = run_singleton(test_dir, data_node_def, false, true);
+        let resp = data
+            .client
+            .post(
+                "http://127.0.0.1:8902/api/v1/sql?db=public",
+                "alter user root set password='abcde1'",
+            )
+            .unwrap();
+        assert_response_is_ok!(resp);
+    }
+
+    // Start cnosdb singleton with `auth_enabled = true` and the password policy
+    let mut config = build_data_node_config(test_dir, &data_node_def.config_file_name);
+    data_node_def.update_config(&mut config);
+    config.query.auth_enabled = true;
+    config.query.password_min_length = 6;
+    config.query.password_require_digit = true;
+    config.query.login_max_failed_attempts = 2;
+    config.query.login_lock_time = Duration::from_secs(60 * 60);
+    let config_dir = Path::new(test_dir).join("data").join("config");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    let config_file_path = config_dir.join(&data_node_def.config_file_name);
+    std::fs::write(config_file_path, config.to_string_pretty()).unwrap();
+
+    let _data = run_singleton(test_dir, data_node_def, false, false);
+
+    let url = "http://127.0.0.1:8902/api/v1/sql?db=public";
+    {
+        let client = Client::with_auth("root".to_string(), Some("abcde1".to_string()));
+
+        let resp = client
+            .post(url, "create user u1 with password='abc'")
+            .unwrap();
+        assert_eq!(resp.status(), status_code::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            resp.text().unwrap(),
+            "{\"error_code\":\"010016\",\"error_message\":\"Auth error: Password does not satisfy the password policy: the password must contain at least 6 characters, a digit\"}"
+        );
+
+        let resp = client
+            .post(
+                url,
+                "create user u1 with password='abcde1', must_change_password=true",
+            )
+            .unwrap();
+        assert_response_is_ok!(resp);
+
+        let resp = client
+            .post(url, "alter tenant cnosdb add user u1 as member")
+            .unwrap();
+        assert_response_is_ok!(resp);
+    }
+    {
+        // u1 must change the password at first.
+        let client = Client::with_auth("u1".to_string(), Some("abcde1".to_string()));
+
+        let resp = client.post(url, "select 1").unwrap();
+        assert_eq!(resp.status(), status_code::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            resp.text().unwrap(),
+            "{\"error_code\":\"010016\",\"error_message\":\"Auth error: The password of user 'u1' has expired or must be changed, please change it by ALTER USER u1 SET PASSWORD\"}"
+        );
+
+        let resp = client.post(url, "alter user u1 set comment='u1'").unwrap();
+        assert_eq!(resp.status(), status_code::UNPROCESSABLE_ENTITY);
+
+        let resp = client
+            .post(url, "alter user u1 set password='abcde2'")
+            .unwrap();
+        assert_response_is_ok!(resp);
+        std::thread::sleep(Duration::from_secs(1));
+
+        let client = Client::with_auth("u1".to_string(), Some("abcde2".to_string()));
+        let resp = client.post(url, "select 1").unwrap();
+        assert_response_is_ok!(resp);
+        assert_eq!(resp.text().unwrap(), "Int64(1)\n1\n");
+    }
+    {
+        // u1 is locked after 2 failed logins.
+        let client = Client::with_auth("u1".to_string(), Some("abc".to_string()));
+        for _ in 0..2 {
+            let resp = client.post(url, "select 1").unwrap();
+            assert_eq!(resp.status(), status_code::UNPROCESSABLE_ENTITY);
+        }
+        std::thread::sleep(Duration::from_secs(1));
+
+        let client = Client::with_auth("u1".to_string(), Some("abcde2".to_string()));
+        let resp = client.post(url, "select 1").unwrap();
+        assert_eq!(resp.status(), status_code::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            resp.text().unwrap(),
+            "{\"error_code\":\"010016\",\"error_message\":\"Auth error: Access denied for user 'u1' (using xxx) the user is locked due to too many failed login attempts\"}"
+        );
+    }
+    {
+        let client = Client::with_auth("root".to_string(), Some("abcde1".to_string()));
+        let resp = client.post(url, "alter user u1 unlock").unwrap();
+        assert_response_is_ok!(resp);
+        std::thread::sleep(Duration::from_secs(1));
+
+        let client = Client::with_auth("u1".to_string(), Some("abcde2".to_string()));
+        let resp = client.post(url, "select 1").unwrap();
+        assert_response_is_ok!(resp);
+    }
+    {
+        // The expired password must be changed.
+        let client = Client::with_auth("root".to_string(), Some("abcde1".to_string()));
+        let resp = client
+            .post(
+                url,
+                "alter user u1 set password_expire_at='2000-01-01 00:00:00'",
+            )
+            .unwrap();
+        assert_response_is_ok!(resp);
+        std::thread::sleep(Duration::from_secs(1));
+
+        let client = Client::with_auth("u1".to_string(), Some("abcde2".to_string()));
+        let resp = client.post(url, "select 1").unwrap();
+        assert_eq!(resp.status(), status_code::UNPROCESSABLE_ENTITY);
+
+        let resp = client
+            .post(url, "alter user u1 set password='abcde3'")
+            .unwrap();
+        assert_response_is_ok!(resp);
+        std::thread::sleep(Duration::from_secs(1));
+
+        let client = Client::with_auth("u1".to_string(), Some("abcde3".to_string()));
+        let resp = client.post(url, "select 1").unwrap();
+        assert_response_is_ok!(resp);
+    }
+}
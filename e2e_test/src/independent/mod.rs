@@ -12,3 +12,4 @@ mod chaos_tests;
 mod auth_tests;
 mod client_tests;
 mod flush_tests;
+mod query_cache_tests;
@@ -0,0 +1,95 @@
+#![cfg(test)]
+
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use http_protocol::status_code;
+use serial_test::serial;
+
+use crate::utils::{build_data_node_config, kill_all, run_cluster, Client};
+use crate::{assert_response_is_ok, cluster_def};
+
+const QUERY: &str = "select date_bin(interval '1 hour', time) as t, count(usage) as c from cpu \
+    where time >= '2001-09-09T00:00:00' and time < '2001-09-10T00:00:00' group by t order by t";
+
+fn query_count(client: &Client, url: &str) -> String {
+    let resp = client.post(url, QUERY).unwrap();
+    assert_eq!(resp.status(), status_code::OK);
+    let text = resp.text().unwrap();
+    let line = text.lines().nth(1).unwrap_or_default();
+    line.rsplit(',').next().unwrap_or_default().to_string()
+}
+
+/// Late writes proposed by one node are applied to the replica on the other node,
+/// which invalidates the cached results on the other node.
+#[test]
+#[serial]
+fn test_query_cache_invalidated_by_remote_write() {
+    println!("Test begin query_cache_tests remote write");
+
+    let test_dir = "/tmp/e2e_test/query_cache_tests/test_query_cache_invalidated_by_remote_write";
+    let _ = std::fs::remove_dir_all(test_dir);
+    std::fs::create_dir_all(test_dir).unwrap();
+
+    kill_all();
+
+    let cluster_def = cluster_def::one_meta_two_data_bundled();
+    let config_dir = Path::new(test_dir).join("data").join("config");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    for data_node_def in cluster_def.data_cluster_def.iter() {
+        let mut config = build_data_node_config(test_dir, &data_node_def.config_file_name);
+        data_node_def.update_config(&mut config);
+        config.query.query_cache_enabled = true;
+        std::fs::create_dir_all(&config.storage.path).unwrap();
+        let config_file_path = config_dir.join(&data_node_def.config_file_name);
+        std::fs::write(config_file_path, config.to_string_pretty()).unwrap();
+    }
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .worker_threads(4)
+        .build()
+        .unwrap();
+    let (_meta, data) = run_cluster(test_dir, Arc::new(runtime), &cluster_def, true, false);
+    let data = data.unwrap();
+
+    let sql_url_1 = "http://127.0.0.1:8902/api/v1/sql?db=public";
+    let write_url_1 = "http://127.0.0.1:8902/api/v1/write?db=db_cache";
+    let sql_url_2 = "http://127.0.0.1:8912/api/v1/sql?db=db_cache";
+
+    let resp = data
+        .client
+        .post(sql_url_1, "create database db_cache with replica 2")
+        .unwrap();
+    assert_response_is_ok!(resp);
+    let resp = data
+        .client
+        .post(write_url_1, "cpu,host=a usage=1 1000000000000000000")
+        .unwrap();
+    assert_response_is_ok!(resp);
+
+    // Cached by the node 2.
+    assert_eq!(query_count(&data.client, sql_url_2), "1");
+    assert_eq!(query_count(&data.client, sql_url_2), "1");
+
+    // A late write to the cached bucket through the node 1.
+    let resp = data
+        .client
+        .post(write_url_1, "cpu,host=b usage=2 1000000000000000001")
+        .unwrap();
+    assert_response_is_ok!(resp);
+
+    let mut count = String::new();
+    for _ in 0..10 {
+        count = query_count(&data.client, sql_url_2);
+        if count == "2" {
+            break;
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+    assert_eq!(count, "2");
+
+    kill_all();
+}
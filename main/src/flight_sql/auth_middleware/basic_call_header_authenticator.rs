@@ -31,19 +31,22 @@ impl CallHeaderAuthenticator for BasicCallHeaderAuthenticator {
             .ok_or_else(|| Status::unauthenticated("authorization field not present"))?;
         let private_key = utils::get_value_from_header(req_headers, PRIVATE_KEY, "");
 
-        let user_info = Header::with_private_key(None, None, None, authorization, private_key)
-            .try_get_basic_auth()
-            .map_err(|e| Status::invalid_argument(e.to_string()))?;
-
+        let header = Header::with_private_key(None, None, None, authorization, private_key);
         let tenant = utils::get_value_from_header(req_headers, header::TENANT, "");
+        let tenant = tenant.as_deref().unwrap_or(DEFAULT_CATALOG);
 
-        let user = self
-            .instance
-            .authenticate(&user_info, tenant.as_deref().unwrap_or(DEFAULT_CATALOG))
-            .await
-            .map_err(|e| Status::unauthenticated(e.to_string()))?;
+        let user = match header.try_get_bearer_token() {
+            Some(token) => self.instance.authenticate_token(token, tenant).await,
+            None => {
+                let user_info = header
+                    .try_get_basic_auth()
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?;
+                self.instance.authenticate(&user_info, tenant).await
+            }
+        }
+        .map_err(|e| Status::unauthenticated(e.to_string()))?;
 
-        debug!("authenticate success, user: {}", user_info.user);
+        debug!("authenticate success, user: {}", user.desc().name());
 
         Ok(CommonAuthResult { user })
     }
@@ -82,6 +85,12 @@ mod test {
             .append_to_outgoing_headers(&mut req_headers)
             .expect("append_to_outgoing_headers");
 
-        assert_eq!(req_headers.len(), 1)
+        assert_eq!(req_headers.len(), 1);
+
+        // the mock does not support bearer token
+        let mut req_headers = MetadataMap::default();
+        let val = AsciiMetadataValue::from_static("Bearer a.b.c");
+        req_headers.insert(AUTHORIZATION.as_str(), val);
+        assert!(authenticator.authenticate(&req_headers).await.is_err());
     }
 }
@@ -57,16 +57,27 @@ where
         // Check if headers contain a bearer token and if so, validate the token.
         if let Some(bearer_token) = utils::get_value_from_auth_header(req_headers, BEARER_PREFIX) {
             // get user_info from cache by token
-            let user = self
-                .bearer_to_identifier
-                .get(&bearer_token)
-                .ok_or_else(|| Status::unauthenticated("token has expired or not exist"))?;
+            if let Some(user) = self.bearer_to_identifier.get(&bearer_token) {
+                debug!("authenticate success, bearer_token exists");
 
-            debug!("authenticate success, bearer_token exists");
+                return Ok(GeneratedBearerTokenAuthResult {
+                    user,
+                    bearer_token: Some(bearer_token),
+                });
+            }
 
+            // The token issued by an external identity provider (JWT) is verified on every call,
+            // so that its expiry is honored, and no token is generated for it.
+            if !is_jwt(&bearer_token) {
+                return Err(Status::unauthenticated("token has expired or not exist"));
+            }
+
+            debug!("bearer_token is a jwt, delegate to initial_authenticator");
+
+            let auth_result = self.initial_authenticator.authenticate(req_headers).await?;
             return Ok(GeneratedBearerTokenAuthResult {
-                user,
-                bearer_token: Some(bearer_token),
+                user: auth_result.identity(),
+                bearer_token: None,
             });
         }
 
@@ -103,6 +114,11 @@ where
     }
 }
 
+/// The generated bearer tokens are uuids, a JWT consists of three dot separated parts.
+fn is_jwt(token: &str) -> bool {
+    token.split('.').count() == 3
+}
+
 pub struct GeneratedBearerTokenAuthResult {
     user: User,
     bearer_token: Option<String>,
@@ -178,5 +194,24 @@ mod test {
             .expect("append_to_outgoing_headers");
 
         assert!(utils::get_value_from_auth_header(&req_headers, BEARER_PREFIX).is_some());
+
+        // unknown generated token
+        let mut req_headers = MetadataMap::default();
+        let val = AsciiMetadataValue::from_static("Bearer 1234");
+        req_headers.insert(AUTHORIZATION.as_str(), val);
+        assert!(authenticator.authenticate(&req_headers).await.is_err());
+
+        // jwt is delegated to the initial authenticator and not cached
+        let mut req_headers = MetadataMap::default();
+        let val = AsciiMetadataValue::from_static("Bearer a.b.c");
+        req_headers.insert(AUTHORIZATION.as_str(), val);
+        let mut resp_headers = MetadataMap::default();
+        authenticator
+            .authenticate(&req_headers)
+            .await
+            .expect("authenticate")
+            .append_to_outgoing_headers(&mut resp_headers)
+            .expect("append_to_outgoing_headers");
+        assert!(resp_headers.is_empty());
     }
 }
@@ -0,0 +1,279 @@
+use std::pin::Pin;
+use std::sync::Arc;
+
+use arrow_flight::flight_descriptor::DescriptorType;
+use arrow_flight::flight_service_server::FlightService;
+use arrow_flight::sql::server::FlightSqlService;
+use arrow_flight::sql::{Any, Command, DoPutUpdateResult};
+use arrow_flight::{
+    Action, Criteria, Empty, FlightData, FlightDescriptor, FlightInfo, HandshakeRequest, PutResult,
+    SchemaResult, Ticket,
+};
+use coordinator::service::CoordinatorRef;
+use datafusion::arrow::datatypes::Schema;
+use futures::Stream;
+use models::auth::privilege::{DatabasePrivilege, Privilege, TenantObjectPrivilege};
+use models::oid::Identifier;
+use prost::Message;
+use spi::service::protocol::Context;
+use tonic::{Request, Response, Status, Streaming};
+use trace::{debug, SpanContext, SpanExt, SpanRecorder};
+
+use super::auth_middleware::CallHeaderAuthenticator;
+use super::flight_sql_server::FlightSqlServiceImpl;
+use crate::flight_sql::utils;
+
+type PutResultStream = Pin<Box<dyn Stream<Item = Result<PutResult, Status>> + Send + 'static>>;
+
+/// The flight service of CnosDB.
+///
+/// All requests are handled by [`FlightSqlServiceImpl`], except `DoPut` with a path descriptor,
+/// which writes the record batches to the table `[<database>, ]<table>` directly.
+pub struct CnosFlightService<T> {
+    flight_sql: FlightSqlServiceImpl<T>,
+    coord: CoordinatorRef,
+}
+
+impl<T> CnosFlightService<T>
+where
+    T: CallHeaderAuthenticator + Send + Sync + 'static,
+{
+    pub fn new(flight_sql: FlightSqlServiceImpl<T>, coord: CoordinatorRef) -> Self {
+        Self { flight_sql, coord }
+    }
+
+    async fn do_put_flight_sql(
+        &self,
+        descriptor: FlightDescriptor,
+        request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<PutResultStream>, Status> {
+        let message = Any::decode(&*descriptor.cmd)
+            .map_err(|e| Status::invalid_argument(format!("Invalid flight sql command: {}", e)))?;
+        let command = Command::try_from(message)
+            .map_err(|e| Status::invalid_argument(format!("Invalid flight sql command: {}", e)))?;
+
+        let record_count = match command {
+            Command::CommandStatementUpdate(cmd) => {
+                self.flight_sql
+                    .do_put_statement_update(cmd, request)
+                    .await?
+            }
+            Command::CommandPreparedStatementQuery(cmd) => {
+                return self
+                    .flight_sql
+                    .do_put_prepared_statement_query(cmd, request)
+                    .await
+            }
+            Command::CommandPreparedStatementUpdate(cmd) => {
+                self.flight_sql
+                    .do_put_prepared_statement_update(cmd, request)
+                    .await?
+            }
+            cmd => {
+                return Err(Status::invalid_argument(format!(
+                    "do_put: The defined request is invalid: {}",
+                    cmd.type_url()
+                )))
+            }
+        };
+
+        Ok(Response::new(put_update_result(record_count)))
+    }
+
+    /// Write record batches to the table of the path descriptor,
+    /// the path is `[<table>]` or `[<database>, <table>]`.
+    async fn do_put_table(
+        &self,
+        descriptor: FlightDescriptor,
+        schema_message: FlightData,
+        request: Request<Streaming<FlightData>>,
+        span_ctx: Option<&SpanContext>,
+    ) -> Result<Response<PutResultStream>, Status> {
+        let ctx = self
+            .flight_sql
+            .authenticate_and_construct_context(request.metadata(), request.remote_addr())
+            .await?;
+        let (db, table) = match descriptor.path.as_slice() {
+            [table] => (ctx.database().to_string(), table.clone()),
+            [db, table] => (db.clone(), table.clone()),
+            path => {
+                return Err(Status::invalid_argument(format!(
+                    "Invalid table path: {:?}, expect [<table>] or [<database>, <table>]",
+                    path
+                )))
+            }
+        };
+        self.check_write_privilege(&ctx, &db).await?;
+
+        // The same tenant limits as the http write api.
+        let limiter = self
+            .coord
+            .meta_manager()
+            .limiter(ctx.tenant())
+            .await
+            .map_err(|e| Status::internal(format!("Get limiter: {}", e)))?;
+        limiter
+            .check_http_writes()
+            .await
+            .map_err(|e| Status::resource_exhausted(e.to_string()))?;
+
+        let schema = Schema::try_from(&schema_message)
+            .map_err(|e| Status::invalid_argument(format!("Invalid schema message: {}", e)))?;
+        let record_batches =
+            utils::read_record_batches(request.into_inner(), Arc::new(schema)).await?;
+        let record_count: i64 = record_batches.iter().map(|e| e.num_rows() as i64).sum();
+        let data_len: usize = record_batches
+            .iter()
+            .map(|e| e.get_array_memory_size())
+            .sum();
+        limiter
+            .check_http_data_in(data_len)
+            .await
+            .map_err(|e| Status::resource_exhausted(e.to_string()))?;
+
+        let mut span_recorder = SpanRecorder::new(span_ctx.child_span("write record batches"));
+        span_recorder.set_metadata("rows", record_count);
+        self.coord
+            .write_table_record_batches(
+                ctx.tenant(),
+                &db,
+                &table,
+                record_batches,
+                span_recorder.span_ctx(),
+            )
+            .await
+            .map_err(|e| {
+                span_recorder.error(e.to_string());
+                Status::internal(format!("Write record batches: {}", e))
+            })?;
+
+        Ok(Response::new(put_update_result(record_count)))
+    }
+
+    async fn check_write_privilege(&self, ctx: &Context, db: &str) -> Result<(), Status> {
+        let tenant_id = *self
+            .coord
+            .tenant_meta(ctx.tenant())
+            .await
+            .ok_or_else(|| Status::not_found(format!("Tenant {} not found", ctx.tenant())))?
+            .tenant()
+            .id();
+        let privilege = Privilege::TenantObject(
+            TenantObjectPrivilege::Database(DatabasePrivilege::Write, Some(db.to_string())),
+            Some(tenant_id),
+        );
+        if !ctx.user().check_privilege(&privilege) {
+            return Err(Status::permission_denied(format!(
+                "Insufficient privileges, expected [{}]",
+                privilege
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn put_update_result(record_count: i64) -> PutResultStream {
+    let result = DoPutUpdateResult { record_count };
+    let output = futures::stream::iter(vec![Ok(PutResult {
+        app_metadata: result.encode_to_vec().into(),
+    })]);
+    Box::pin(output)
+}
+
+#[tonic::async_trait]
+impl<T> FlightService for CnosFlightService<T>
+where
+    T: CallHeaderAuthenticator + Send + Sync + 'static,
+{
+    type HandshakeStream = <FlightSqlServiceImpl<T> as FlightService>::HandshakeStream;
+    type ListFlightsStream = <FlightSqlServiceImpl<T> as FlightService>::ListFlightsStream;
+    type DoGetStream = <FlightSqlServiceImpl<T> as FlightService>::DoGetStream;
+    type DoPutStream = PutResultStream;
+    type DoActionStream = <FlightSqlServiceImpl<T> as FlightService>::DoActionStream;
+    type ListActionsStream = <FlightSqlServiceImpl<T> as FlightService>::ListActionsStream;
+    type DoExchangeStream = <FlightSqlServiceImpl<T> as FlightService>::DoExchangeStream;
+
+    async fn handshake(
+        &self,
+        request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        FlightService::handshake(&self.flight_sql, request).await
+    }
+
+    async fn list_flights(
+        &self,
+        request: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        FlightService::list_flights(&self.flight_sql, request).await
+    }
+
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        FlightService::get_flight_info(&self.flight_sql, request).await
+    }
+
+    async fn get_schema(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        FlightService::get_schema(&self.flight_sql, request).await
+    }
+
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        FlightService::do_get(&self.flight_sql, request).await
+    }
+
+    async fn do_put(
+        &self,
+        mut request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        let span_ctx = request.extensions().get::<SpanContext>().cloned();
+        let span_recorder = SpanRecorder::new(span_ctx.child_span("flight do_put"));
+
+        // The first message carries the flight descriptor.
+        let first_message = request
+            .get_mut()
+            .message()
+            .await?
+            .ok_or_else(|| Status::invalid_argument("do_put: Empty request"))?;
+        let descriptor = first_message
+            .flight_descriptor
+            .clone()
+            .ok_or_else(|| Status::invalid_argument("do_put: Missing flight descriptor"))?;
+        debug!("do_put: descriptor: {:?}", descriptor);
+
+        match descriptor.r#type() {
+            DescriptorType::Path => {
+                self.do_put_table(descriptor, first_message, request, span_recorder.span_ctx())
+                    .await
+            }
+            _ => self.do_put_flight_sql(descriptor, request).await,
+        }
+    }
+
+    async fn do_action(
+        &self,
+        request: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        FlightService::do_action(&self.flight_sql, request).await
+    }
+
+    async fn list_actions(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        FlightService::list_actions(&self.flight_sql, request).await
+    }
+
+    async fn do_exchange(
+        &self,
+        request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        FlightService::do_exchange(&self.flight_sql, request).await
+    }
+}
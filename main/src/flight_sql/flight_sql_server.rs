@@ -1,3 +1,4 @@
+use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
@@ -18,7 +19,7 @@ use arrow_flight::sql::{
 };
 use arrow_flight::{
     utils as flight_utils, Action, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
-    HandshakeRequest, HandshakeResponse, IpcMessage, Ticket,
+    HandshakeRequest, HandshakeResponse, IpcMessage, PutResult, Ticket,
 };
 use datafusion::arrow::datatypes::{Schema, SchemaRef, ToByteSlice};
 use futures::Stream;
@@ -39,6 +40,7 @@ use trace::{debug, SpanContext, SpanExt, SpanRecorder};
 
 use super::auth_middleware::CallHeaderAuthenticator;
 use crate::flight_sql::auth_middleware::AuthResult;
+use crate::flight_sql::prepared_statement::{read_parameters, PreparedStatement};
 use crate::flight_sql::utils;
 use crate::status;
 
@@ -49,6 +51,7 @@ pub struct FlightSqlServiceImpl<T> {
     authenticator: T,
     id_generator: UuidGenerator,
     result_cache: Cache<Vec<u8>, (Option<Plan>, QueryStateMachineRef)>,
+    prepared_statements: Cache<Vec<u8>, PreparedStatement>,
 }
 
 impl<T> FlightSqlServiceImpl<T> {
@@ -59,12 +62,19 @@ impl<T> FlightSqlServiceImpl<T> {
             // The query results are only cached for 2 minutes and expire after 2 minutes
             .time_to_live(Duration::from_secs(2 * 60))
             .build();
+        let prepared_statements = Cache::builder()
+            .thread_pool_enabled(false)
+            // Time to idle (TTI): 30 minutes
+            // The prepared statements are closed by client, or expire after 30 minutes of inactivity
+            .time_to_idle(Duration::from_secs(30 * 60))
+            .build();
 
         Self {
             instance,
             authenticator,
             id_generator: Default::default(),
             result_cache,
+            prepared_statements,
         }
     }
 }
@@ -77,6 +87,7 @@ where
         &self,
         sql: impl Into<String>,
         req_headers: &MetadataMap,
+        client_addr: Option<SocketAddr>,
         span_ctx: Option<&SpanContext>,
     ) -> Result<(Option<Plan>, QueryStateMachineRef), Status> {
         // auth request
@@ -89,7 +100,7 @@ where
         // construct context by user_info and headers(parse tenant & default database)
         let ctx = {
             let _span_recorder = SpanRecorder::new(span_ctx.child_span("construct context"));
-            self.construct_context(user, req_headers)?
+            self.construct_context(user, req_headers, client_addr)?
         };
 
         // build query state machine
@@ -106,14 +117,25 @@ where
         Ok((logical_plan, query_state_machine))
     }
 
+    /// Authenticate the request and construct the context by the user and headers.
+    pub(crate) async fn authenticate_and_construct_context(
+        &self,
+        req_headers: &MetadataMap,
+        client_addr: Option<SocketAddr>,
+    ) -> Result<Context, Status> {
+        let auth_result = self.authenticator.authenticate(req_headers).await?;
+        self.construct_context(auth_result.identity(), req_headers, client_addr)
+    }
+
     async fn pre_precess_statement_query_req_and_save(
         &self,
         sql: impl Into<String>,
         req_headers: &MetadataMap,
+        client_addr: Option<SocketAddr>,
         span_ctx: Option<&SpanContext>,
     ) -> Result<(Vec<u8>, SchemaRef), Status> {
         let (logical_plan, query_state_machine) = self
-            .pre_precess_statement_query_req(sql, req_headers, span_ctx)
+            .pre_precess_statement_query_req(sql, req_headers, client_addr, span_ctx)
             .await?;
 
         let schema = logical_plan
@@ -138,7 +160,12 @@ where
         span_ctx: Option<&SpanContext>,
     ) -> Result<Response<FlightInfo>, Status> {
         let (result_ident, schema) = self
-            .pre_precess_statement_query_req_and_save(sql, request.metadata(), span_ctx)
+            .pre_precess_statement_query_req_and_save(
+                sql,
+                request.metadata(),
+                request.remote_addr(),
+                span_ctx,
+            )
             .await?;
 
         let ticket = TicketStatementQuery {
@@ -179,7 +206,12 @@ where
         Ok(flight_info)
     }
 
-    fn construct_context(&self, user: User, metadata: &MetadataMap) -> Result<Context, Status> {
+    fn construct_context(
+        &self,
+        user: User,
+        metadata: &MetadataMap,
+        client_addr: Option<SocketAddr>,
+    ) -> Result<Context, Status> {
         // parse tenant & default database
         let tenant = utils::get_value_from_header(metadata, TENANT, "");
         let db = utils::get_value_from_header(metadata, DB, "");
@@ -207,6 +239,7 @@ where
             .with_database(db)
             .with_target_partitions(target_partitions)
             .with_stream_trigger_interval(stream_trigger_interval)
+            .with_client_addr(client_addr.map(|addr| addr.to_string()))
             .build();
 
         Ok(ctx)
@@ -289,6 +322,14 @@ where
         let query_result = self
             .execute_logical_plan(logical_plan, query_state_machine)
             .await?;
+
+        self.fetch_result_set(query_result).await
+    }
+
+    async fn fetch_result_set(
+        &self,
+        query_result: QueryHandle,
+    ) -> Result<<Self as FlightService>::DoGetStream, Status> {
         let output = query_result.result();
 
         let schema = (*output.schema()).clone();
@@ -305,6 +346,49 @@ where
             Box::pin(futures::stream::iter(flight_data));
         Ok(stream)
     }
+
+    /// Get the prepared statement created by the user of the request.
+    async fn get_prepared_statement(
+        &self,
+        prepared_statement_handle: &[u8],
+        req_headers: &MetadataMap,
+    ) -> Result<PreparedStatement, Status> {
+        let auth_result = self.authenticator.authenticate(req_headers).await?;
+        let user = auth_result.identity();
+
+        self.prepared_statements
+            .get(prepared_statement_handle)
+            .filter(|e| e.is_owned_by(&user))
+            .ok_or_else(|| {
+                Status::not_found(format!(
+                    "The prepared statement({:?}) does not exist or has expired",
+                    prepared_statement_handle
+                ))
+            })
+    }
+
+    /// Execute the prepared statement once for each row of the bound parameters.
+    async fn execute_prepared_statement(
+        &self,
+        prepared_statement: &PreparedStatement,
+        span_ctx: Option<&SpanContext>,
+    ) -> Result<Vec<QueryHandle>, Status> {
+        let query = prepared_statement.query();
+        let mut query_results = vec![];
+        for logical_plan in prepared_statement.bound_plans()? {
+            let query_state_machine = self
+                .build_query_state_machine(query.content(), query.context().clone(), span_ctx)
+                .await?;
+            if let Some(statement_type) = prepared_statement.statement_type() {
+                query_state_machine.set_statement_type(statement_type);
+            }
+            let query_result = self
+                .execute_logical_plan(logical_plan, query_state_machine)
+                .await?;
+            query_results.push(query_result);
+        }
+        Ok(query_results)
+    }
 }
 
 /// use jdbc to execute statement query:
@@ -342,10 +426,11 @@ where
 /// .   }
 /// ```
 /// 1. do_handshake: basic auth -> baerar token
-/// 2. do_action_create_prepared_statement: sql(baerar token) -> sql
-/// 3. do_put_prepared_statement_update: not use
-/// 4. get_flight_info_prepared_statement: sql(baerar token) -> address of resut set
-/// 5. do_get_statement: address of resut set(baerar token) -> resut set stream
+/// 2. do_action_create_prepared_statement: sql(baerar token) -> handle of prepared statement
+/// 3. do_put_prepared_statement_query: parameters(baerar token) -> bind parameters
+/// 4. get_flight_info_prepared_statement: handle(baerar token) -> address of resut set
+/// 5. do_get_prepared_statement: address of resut set(baerar token) -> resut set stream
+/// 6. do_action_close_prepared_statement: handle(baerar token) -> close prepared statement
 /// ```
 ///
 /// use flight sql to execute statement query:
@@ -425,17 +510,16 @@ where
             query, request
         );
 
-        let span_recorder = get_span_recorder(
+        let _span_recorder = get_span_recorder(
             request.extensions(),
             "flight sql get_flight_info_prepared_statement",
         );
 
         let statement_handle = query.prepared_statement_handle.to_byte_slice();
-        let (plan, _) =
-            self.get_plan_and_qsm(statement_handle, span_recorder.span_ctx().cloned())?;
-        let schema = plan
-            .map(|e| e.schema())
-            .unwrap_or(Arc::new(Schema::empty()));
+        let prepared_statement = self
+            .get_prepared_statement(statement_handle, request.metadata())
+            .await?;
+        let schema = prepared_statement.dataset_schema();
 
         // construct response start
         let flight_info = self.construct_flight_info(
@@ -728,14 +812,23 @@ where
             get_span_recorder(request.extensions(), "flight sql do_get_prepared_statement");
 
         let prepared_statement_handle = query.prepared_statement_handle.to_byte_slice();
+        let prepared_statement = self
+            .get_prepared_statement(prepared_statement_handle, request.metadata())
+            .await?;
 
-        let output = self
-            .execute_and_fetch_result_set(prepared_statement_handle, span_recorder.span_ctx())
+        let mut query_results = self
+            .execute_prepared_statement(&prepared_statement, span_recorder.span_ctx())
             .await?;
+        let query_result = match (query_results.pop(), query_results.is_empty()) {
+            (Some(query_result), true) => query_result,
+            _ => {
+                return Err(Status::invalid_argument(
+                    "Prepared query must be bound with exactly one row of parameters",
+                ))
+            }
+        };
 
-        // clear cache of this query
-        self.result_cache
-            .invalidate(prepared_statement_handle.to_byte_slice());
+        let output = self.fetch_result_set(query_result).await?;
 
         Ok(Response::new(output))
     }
@@ -879,7 +972,7 @@ where
         let req_headers = request.metadata();
 
         let (logical_plan, query_state_machine) = self
-            .pre_precess_statement_query_req(query, req_headers, span_ctx)
+            .pre_precess_statement_query_req(query, req_headers, request.remote_addr(), span_ctx)
             .await?;
 
         // execute plan
@@ -892,7 +985,10 @@ where
         Ok(affected_rows)
     }
 
-    /// not support
+    /// Bind parameters to the prepared statement.
+    ///
+    /// The parameters are an arrow batch matching the parameter schema of the prepared statement,
+    /// they are used by the following [`Self::do_get_prepared_statement`].
     async fn do_put_prepared_statement_query(
         &self,
         query: CommandPreparedStatementQuery,
@@ -903,22 +999,39 @@ where
             query, request
         );
 
-        Err(Status::unimplemented(
-            "do_put_prepared_statement_query not implemented",
-        ))
+        let _span_recorder = get_span_recorder(
+            request.extensions(),
+            "flight sql do_put_prepared_statement_query",
+        );
+
+        let prepared_statement_handle = query.prepared_statement_handle.to_vec();
+        let prepared_statement = self
+            .get_prepared_statement(&prepared_statement_handle, request.metadata())
+            .await?;
+
+        let parameters =
+            read_parameters(request.into_inner(), prepared_statement.parameter_schema()).await?;
+        if let Some(parameters) = parameters {
+            let prepared_statement = prepared_statement.with_parameters(parameters)?;
+            self.prepared_statements
+                .insert(prepared_statement_handle, prepared_statement);
+        }
+
+        let output: Pin<Box<dyn Stream<Item = Result<PutResult, Status>> + Send>> =
+            Box::pin(futures::stream::empty());
+        Ok(Response::new(output))
     }
 
-    /// Execute the query and return the number of affected rows.
+    /// Execute the prepared statement and return the number of affected rows.
     /// The prepared statement can be reused afterwards.
     ///
-    /// Prepared statement is not supported,
-    /// because ad-hoc statement of flight jdbc needs to call this interface, so it is simple to implement
+    /// If parameters are sent with the request, the statement is executed once for each row of them.
     async fn do_put_prepared_statement_update(
         &self,
         query: CommandPreparedStatementUpdate,
         request: Request<Streaming<FlightData>>,
     ) -> Result<i64, Status> {
-        let prepared_statement_ident = query.prepared_statement_handle.to_byte_slice();
+        let prepared_statement_ident = query.prepared_statement_handle.to_vec();
         debug!(
             "do_put_prepared_statement_update query: {:?}",
             prepared_statement_ident
@@ -927,17 +1040,32 @@ where
             request.extensions(),
             "flight sql do_put_prepared_statement_update",
         );
-        let (plan, query_machine) =
-            self.get_plan_and_qsm(prepared_statement_ident, span_recorder.span_ctx().cloned())?;
+        let mut prepared_statement = self
+            .get_prepared_statement(&prepared_statement_ident, request.metadata())
+            .await?;
+
+        let parameters =
+            read_parameters(request.into_inner(), prepared_statement.parameter_schema()).await?;
+        if let Some(parameters) = parameters {
+            prepared_statement = prepared_statement.with_parameters(parameters)?;
+            self.prepared_statements
+                .insert(prepared_statement_ident, prepared_statement.clone());
+        }
+
         // execute plan
-        let query_result = self.execute_logical_plan(plan, query_machine).await?;
-        let output = query_result.result();
-        Ok(output.affected_rows().await)
+        let query_results = self
+            .execute_prepared_statement(&prepared_statement, span_recorder.span_ctx())
+            .await?;
+        let mut affected_rows = 0;
+        for query_result in query_results {
+            affected_rows += query_result.result().affected_rows().await;
+        }
+        Ok(affected_rows)
     }
 
-    /// Prepared statement is not supported,
-    /// because ad-hoc statement of flight jdbc needs to call this interface,
-    /// so directly return the sql as the result.
+    /// Create a prepared statement, the logical plan of the sql is built and cached.
+    ///
+    /// Return the dataset schema and the parameter schema of the prepared statement.
     async fn do_action_create_prepared_statement(
         &self,
         query: ActionCreatePreparedStatementRequest,
@@ -955,31 +1083,44 @@ where
         // ignore transaction_id
         let ActionCreatePreparedStatementRequest { query: sql, .. } = query;
 
-        let (result_ident, schema) = self
-            .pre_precess_statement_query_req_and_save(
+        let (logical_plan, query_state_machine) = self
+            .pre_precess_statement_query_req(
                 sql,
                 request.metadata(),
+                request.remote_addr(),
                 span_recorder.span_ctx(),
             )
             .await?;
+        let prepared_statement = PreparedStatement::try_new(
+            query_state_machine.query.clone(),
+            query_state_machine.statement_type(),
+            logical_plan,
+        )?;
+
+        let IpcMessage(dataset_schema) =
+            utils::schema_to_ipc_message(prepared_statement.dataset_schema().as_ref())
+                .map_err(|e| status!("Schema to ipc message", e))?;
+        let IpcMessage(parameter_schema) =
+            utils::schema_to_ipc_message(prepared_statement.parameter_schema().as_ref())
+                .map_err(|e| status!("Schema to ipc message", e))?;
 
-        let IpcMessage(dataset_schema) = utils::schema_to_ipc_message(schema.as_ref())
-            .map_err(|e| status!("Schema to ipc message", e))?;
+        // generate prepared statement identifier
+        let result_ident = self.id_generator.next_id().to_le_bytes().to_vec();
+        self.prepared_statements
+            .insert(result_ident.clone(), prepared_statement);
         // JDBC:
         //    - schema.getFields().isEmpty() ? StatementType.UPDATE : StatementType.SELECT;
         //    - long updateCount = statementType.equals(StatementType.UPDATE) ? preparedStatement.executeUpdate() : -1L;
         let result = ActionCreatePreparedStatementResult {
             prepared_statement_handle: result_ident.into(),
             dataset_schema,
-            ..Default::default()
+            parameter_schema,
         };
 
         Ok(result)
     }
 
     /// Close a previously created prepared statement.
-    ///
-    /// Empty logic, because we not save created prepared statement.
     async fn do_action_close_prepared_statement(
         &self,
         query: ActionClosePreparedStatementRequest,
@@ -990,6 +1131,12 @@ where
             query, request
         );
 
+        let prepared_statement_handle = query.prepared_statement_handle.to_byte_slice();
+        self.get_prepared_statement(prepared_statement_handle, request.metadata())
+            .await?;
+        self.prepared_statements
+            .invalidate(prepared_statement_handle);
+
         Ok(())
     }
 
@@ -0,0 +1,204 @@
+//! Prepared statements of flight sql.
+//!
+//! The logical plan of a prepared statement is built once when it is created,
+//! placeholders(`$1`, `$2`, ...) in the plan are replaced by the bound parameters before each execution.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_flight::FlightData;
+use datafusion::arrow::compute::concat_batches;
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::scalar::ScalarValue;
+use models::auth::user::User;
+use spi::query::execution::StatementType;
+use spi::query::logical_planner::{Plan, QueryPlan};
+use spi::service::protocol::Query;
+use tonic::{Status, Streaming};
+
+use crate::flight_sql::utils;
+use crate::status;
+
+const PLACEHOLDER_PREFIX: &str = "$";
+
+#[derive(Clone)]
+pub struct PreparedStatement {
+    query: Query,
+    statement_type: Option<StatementType>,
+    plan: Option<Plan>,
+    parameter_schema: SchemaRef,
+    parameters: Option<RecordBatch>,
+}
+
+impl PreparedStatement {
+    pub fn try_new(
+        query: Query,
+        statement_type: Option<StatementType>,
+        plan: Option<Plan>,
+    ) -> Result<Self, Status> {
+        let parameter_schema = match &plan {
+            Some(Plan::Query(QueryPlan { df_plan })) => {
+                let parameter_types = df_plan
+                    .get_parameter_types()
+                    .map_err(|e| status!("Get parameter types", e))?;
+                parameter_schema(parameter_types)?
+            }
+            _ => Arc::new(Schema::empty()),
+        };
+
+        Ok(Self {
+            query,
+            statement_type,
+            plan,
+            parameter_schema,
+            parameters: None,
+        })
+    }
+
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// The type of the statement parsed when the prepared statement was created.
+    pub fn statement_type(&self) -> Option<StatementType> {
+        self.statement_type
+    }
+
+    /// Only the user who created the prepared statement can use it.
+    pub fn is_owned_by(&self, user: &User) -> bool {
+        self.query.context().user().desc().name() == user.desc().name()
+    }
+
+    pub fn dataset_schema(&self) -> SchemaRef {
+        self.plan
+            .as_ref()
+            .map(|e| e.schema())
+            .unwrap_or(Arc::new(Schema::empty()))
+    }
+
+    pub fn parameter_schema(&self) -> SchemaRef {
+        self.parameter_schema.clone()
+    }
+
+    /// Bind parameters, each row of the batch is the parameters of one execution.
+    pub fn with_parameters(self, parameters: RecordBatch) -> Result<Self, Status> {
+        let expected = self.parameter_schema.fields().len();
+        if parameters.num_columns() != expected {
+            return Err(Status::invalid_argument(format!(
+                "Prepared statement expects {} parameters, but {} are bound",
+                expected,
+                parameters.num_columns()
+            )));
+        }
+
+        Ok(Self {
+            parameters: Some(parameters),
+            ..self
+        })
+    }
+
+    /// Returns the plans to execute, one for each row of the bound parameters.
+    pub fn bound_plans(&self) -> Result<Vec<Option<Plan>>, Status> {
+        if self.parameter_schema.fields().is_empty() {
+            return Ok(vec![self.plan.clone()]);
+        }
+
+        let (parameters, df_plan) = match (&self.parameters, &self.plan) {
+            (Some(parameters), Some(Plan::Query(QueryPlan { df_plan }))) => (parameters, df_plan),
+            _ => {
+                return Err(Status::invalid_argument(
+                    "No parameters are bound to the prepared statement",
+                ))
+            }
+        };
+
+        (0..parameters.num_rows())
+            .map(|row| {
+                let values = parameters
+                    .columns()
+                    .iter()
+                    .map(|column| ScalarValue::try_from_array(column, row))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| status!("Read parameters", e))?;
+                let df_plan = df_plan
+                    .clone()
+                    .with_param_values(values)
+                    .map_err(|e| Status::invalid_argument(format!("Bind parameters: {}", e)))?;
+                Ok(Some(Plan::Query(QueryPlan { df_plan })))
+            })
+            .collect()
+    }
+}
+
+/// Sort placeholders by their index, the type of a placeholder that can't be inferred is [`DataType::Null`].
+fn parameter_schema(
+    parameter_types: HashMap<String, Option<DataType>>,
+) -> Result<SchemaRef, Status> {
+    let mut parameters = parameter_types
+        .into_iter()
+        .map(|(name, data_type)| {
+            let idx = name
+                .strip_prefix(PLACEHOLDER_PREFIX)
+                .and_then(|e| e.parse::<usize>().ok())
+                .ok_or_else(|| {
+                    Status::invalid_argument(format!("Invalid placeholder: {}", name))
+                })?;
+            Ok((idx, name, data_type))
+        })
+        .collect::<Result<Vec<_>, Status>>()?;
+    parameters.sort_by_key(|(idx, _, _)| *idx);
+
+    let fields = parameters
+        .into_iter()
+        .map(|(_, name, data_type)| Field::new(name, data_type.unwrap_or(DataType::Null), true))
+        .collect::<Vec<_>>();
+
+    Ok(Arc::new(Schema::new(fields)))
+}
+
+/// Read the parameters sent by `DoPut`.
+pub async fn read_parameters(
+    stream: Streaming<FlightData>,
+    parameter_schema: SchemaRef,
+) -> Result<Option<RecordBatch>, Status> {
+    let batches = utils::read_record_batches(stream, parameter_schema).await?;
+    let schema = match batches.first() {
+        Some(batch) => batch.schema(),
+        None => return Ok(None),
+    };
+
+    let parameters =
+        concat_batches(&schema, &batches).map_err(|e| status!("Concat parameters", e))?;
+    Ok(Some(parameters))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+
+    use super::parameter_schema;
+
+    #[test]
+    fn test_parameter_schema() {
+        let parameter_types = HashMap::from([
+            ("$10".to_string(), None),
+            ("$2".to_string(), Some(DataType::Utf8)),
+            ("$1".to_string(), Some(DataType::Int64)),
+        ]);
+        let schema = parameter_schema(parameter_types).unwrap();
+        assert_eq!(
+            schema.as_ref(),
+            &Schema::new(vec![
+                Field::new("$1", DataType::Int64, true),
+                Field::new("$2", DataType::Utf8, true),
+                Field::new("$10", DataType::Null, true),
+            ])
+        );
+
+        let parameter_types = HashMap::from([("?".to_string(), None)]);
+        assert!(parameter_schema(parameter_types).is_err());
+    }
+}
@@ -1,8 +1,11 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
 
 use arrow_flight::sql::{Any, ProstMessageExt};
-use arrow_flight::{FlightDescriptor, FlightEndpoint, IpcMessage, Location, SchemaAsIpc, Ticket};
+use arrow_flight::{
+    FlightData, FlightDescriptor, FlightEndpoint, IpcMessage, Location, SchemaAsIpc, Ticket,
+};
 use datafusion::arrow::array::ArrayRef;
 use datafusion::arrow::buffer::Buffer;
 use datafusion::arrow::datatypes::{Schema, SchemaRef};
@@ -13,7 +16,7 @@ use datafusion::arrow::record_batch::RecordBatch;
 use http_protocol::header::AUTHORIZATION;
 use prost::Message;
 use tonic::metadata::{AsciiMetadataValue, MetadataMap};
-use tonic::{Request, Status};
+use tonic::{Request, Status, Streaming};
 
 #[macro_export]
 macro_rules! status_with_location {
@@ -167,6 +170,60 @@ pub fn dictionary_from_message(
         .map_err(|e| Status::internal(format!("Could not convert to Dictionary: {:?}", e)))
 }
 
+/// Read the record batches of a `DoPut` request.
+///
+/// The first message(with the flight descriptor) has been consumed by the flight server,
+/// so the record batches are decoded with the given schema unless the client sends the schema again.
+pub async fn read_record_batches(
+    mut stream: Streaming<FlightData>,
+    schema: SchemaRef,
+) -> Result<Vec<RecordBatch>, Status> {
+    let mut schema = schema;
+    let mut dictionaries_by_id = HashMap::new();
+    let mut batches = vec![];
+
+    while let Some(data) = stream.message().await? {
+        if data.data_header.is_empty() {
+            continue;
+        }
+        let message = ipc::root_as_message(&data.data_header[..])
+            .map_err(|e| Status::invalid_argument(format!("Invalid ipc message: {}", e)))?;
+
+        match message.header_type() {
+            ipc::MessageHeader::Schema => {
+                schema = Arc::new(Schema::try_from(&data).map_err(|e| {
+                    Status::invalid_argument(format!("Invalid schema message: {}", e))
+                })?);
+            }
+            ipc::MessageHeader::RecordBatch => {
+                let batch = record_batch_from_message(
+                    message,
+                    &Buffer::from(data.data_body.as_ref()),
+                    schema.clone(),
+                    &dictionaries_by_id,
+                )?;
+                batches.push(batch);
+            }
+            ipc::MessageHeader::DictionaryBatch => {
+                dictionary_from_message(
+                    message,
+                    &Buffer::from(data.data_body.as_ref()),
+                    schema.clone(),
+                    &mut dictionaries_by_id,
+                )?;
+            }
+            t => {
+                return Err(Status::invalid_argument(format!(
+                    "Unexpected ipc message: {:?}",
+                    t
+                )))
+            }
+        }
+    }
+
+    Ok(batches)
+}
+
 pub fn schema_to_ipc_message(schema: &Schema) -> Result<IpcMessage, ArrowError> {
     let options = IpcWriteOptions::default();
     SchemaAsIpc::new(schema, &options).try_into()
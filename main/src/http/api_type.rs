@@ -7,9 +7,14 @@ pub enum HttpApiType {
     ApiV1OpenTsDBWrite,
     ApiV1OpenTsDBPut,
     ApiV1PromWrite,
+    InfluxDBWrite,
+    InfluxDBV2Write,
 
     ApiV1Sql,
     ApiV1PromRead,
+    ApiV1PromQuery,
+    ApiV1PromQueryRange,
+    InfluxDBQuery,
 }
 
 impl Display for HttpApiType {
@@ -27,12 +32,27 @@ impl Display for HttpApiType {
             HttpApiType::ApiV1PromWrite => {
                 write!(f, "api/v1/prom/write")
             }
+            HttpApiType::InfluxDBWrite => {
+                write!(f, "write")
+            }
+            HttpApiType::InfluxDBV2Write => {
+                write!(f, "api/v2/write")
+            }
             HttpApiType::ApiV1Sql => {
                 write!(f, "api/v1/sql")
             }
             HttpApiType::ApiV1PromRead => {
                 write!(f, "api/v1/prom/read")
             }
+            HttpApiType::ApiV1PromQuery => {
+                write!(f, "api/v1/query")
+            }
+            HttpApiType::ApiV1PromQueryRange => {
+                write!(f, "api/v1/query_range")
+            }
+            HttpApiType::InfluxDBQuery => {
+                write!(f, "query")
+            }
         }
     }
 }
@@ -44,7 +64,12 @@ pub fn metrics_record_db(api: &HttpApiType) -> bool {
         | HttpApiType::ApiV1OpenTsDBPut
         | HttpApiType::ApiV1OpenTsDBWrite
         | HttpApiType::ApiV1PromWrite
-        | HttpApiType::ApiV1PromRead => true,
-        HttpApiType::ApiV1Sql => false,
+        | HttpApiType::InfluxDBWrite
+        | HttpApiType::InfluxDBV2Write
+        | HttpApiType::ApiV1PromRead
+        | HttpApiType::ApiV1PromQuery
+        | HttpApiType::ApiV1PromQueryRange => true,
+        // InfluxQL statements may query other databases
+        HttpApiType::ApiV1Sql | HttpApiType::InfluxDBQuery => false,
     }
 }
@@ -1,4 +1,4 @@
-use http_protocol::header::{APPLICATION_CSV, BASIC_PREFIX};
+use http_protocol::header::{APPLICATION_CSV, BASIC_PREFIX, BEARER_PREFIX};
 use models::auth::user::UserInfo;
 use warp::http::header::{HeaderName, HeaderValue};
 
@@ -57,6 +57,14 @@ impl Header {
         self.content_encoding.as_deref()
     }
 
+    /// Returns the token if the authorization is `Bearer <token>`.
+    pub fn try_get_bearer_token(&self) -> Option<&str> {
+        self.authorization
+            .strip_prefix(BEARER_PREFIX)
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+    }
+
     pub fn try_get_basic_auth(&self) -> Result<UserInfo, HttpError> {
         let private_key = self
             .private_key
@@ -195,4 +203,18 @@ mod tests {
         let header = Header::with(None, None, None, auth);
         assert!(header.try_get_basic_auth().is_err());
     }
+
+    #[test]
+    fn test_header_bearer_token() {
+        let header = Header::with(None, None, None, format!("{}{}", BEARER_PREFIX, "a.b.c"));
+        assert_eq!(header.try_get_bearer_token(), Some("a.b.c"));
+        assert!(header.try_get_basic_auth().is_err());
+
+        let header = Header::with(None, None, None, BEARER_PREFIX.to_string());
+        assert_eq!(header.try_get_bearer_token(), None);
+
+        let auth = format!("{}{}", BASIC_PREFIX, base64::encode("xx:xx"));
+        let header = Header::with(None, None, None, auth);
+        assert_eq!(header.try_get_bearer_token(), None);
+    }
 }
@@ -10,11 +10,21 @@ use std::time::Instant;
 
 use config::TLSConfig;
 use coordinator::service::CoordinatorRef;
+use datafusion::arrow::record_batch::RecordBatch;
 use fly_accept_encoding::Encoding;
+use futures::TryStreamExt;
 use http_protocol::encoding::EncodingExt;
-use http_protocol::header::{ACCEPT, APPLICATION_JSON, AUTHORIZATION, PRIVATE_KEY};
-use http_protocol::parameter::{DebugParam, DumpParam, SqlParam, WriteParam};
+use http_protocol::header::{
+    ACCEPT, APPLICATION_ARROW_STREAM, APPLICATION_JSON, APPLICATION_PARQUET, APPLICATION_PROTOBUF,
+    APPLICATION_STREAMED_PROTOBUF_CHUNKED_READ_RESPONSE, AUTHORIZATION, CONTENT_TYPE,
+    LENIENT_WRITE, PRIVATE_KEY, SNAPPY,
+};
+use http_protocol::parameter::{
+    DebugParam, DumpParam, InfluxQueryParam, InfluxV2WriteParam, InfluxWriteParam, PromQueryParam,
+    SqlParam, WriteParam,
+};
 use http_protocol::response::ErrorResponse;
+use http_protocol::status_code::{BAD_REQUEST, NO_CONTENT, OK, UNPROCESSABLE_ENTITY};
 use meta::error::{MetaError, MetaResult};
 use meta::limiter::RequestLimiter;
 use meta::model::MetaRef;
@@ -23,25 +33,31 @@ use metrics::gather_metrics;
 use metrics::metric_register::MetricsRegister;
 use metrics::prom_reporter::PromReporter;
 use models::auth::privilege::{DatabasePrivilege, Privilege, TenantObjectPrivilege};
+use models::auth::user::{User, UserInfo};
 use models::error_code::UnknownCodeWithMessage;
 use models::oid::{Identifier, Oid};
-use models::schema::{Precision, DEFAULT_CATALOG, DEFAULT_DATABASE};
-use models::utils::now_timestamp_nanos;
+use models::schema::{Precision, DEFAULT_CATALOG};
+use models::utils::{now_timestamp_millis, now_timestamp_nanos};
+use protocol_parser::arrow_convert::{arrow_stream_to_batches, parquet_to_batches};
 use protocol_parser::line_protocol::line_protocol_to_lines;
 use protocol_parser::open_tsdb::open_tsdb_to_lines;
 use protocol_parser::{DataPoint, Line};
+use query::influxql;
+use query::influxql::response::Epoch;
+use query::prom::promql;
 use query::prom::remote_server::PromRemoteSqlServer;
 use reqwest::header::{ACCEPT_ENCODING, CONTENT_ENCODING};
 use snafu::ResultExt;
 use spi::query::config::StreamTriggerInterval;
 use spi::server::dbms::DBMSRef;
-use spi::server::prom::PromRemoteServerRef;
+use spi::server::prom::{PromReadResponse, PromRemoteServerRef};
 use spi::service::protocol::{Context, ContextBuilder, Query};
 use spi::QueryError;
 use tokio::sync::oneshot;
 use trace::{debug, error, info, SpanContext, SpanExt, SpanRecorder};
 use trace_http::ctx::{SpanContextExtractor, DEFAULT_TRACE_HEADER_NAME};
 use utils::backtrace;
+use warp::http::header::HeaderName;
 use warp::hyper::body::Bytes;
 use warp::hyper::Body;
 use warp::reject::{MethodNotAllowed, MissingHeader, PayloadTooLarge};
@@ -52,6 +68,8 @@ use super::header::Header;
 use super::Error as HttpError;
 use crate::http::api_type::{metrics_record_db, HttpApiType};
 use crate::http::encoding::{get_accept_encoding_from_header, get_content_encoding_from_header};
+use crate::http::influxdb::{self, InfluxApi, InfluxPrecision, X_INFLUXDB_VERSION};
+use crate::http::lenient_write::{lenient_write_response, write_lines_leniently};
 use crate::http::metrics::HttpMetrics;
 use crate::http::response::{HttpResponse, ResponseBuilder};
 use crate::http::result_format::{get_result_format_from_header, ResultFormat};
@@ -216,13 +234,17 @@ impl HttpService {
     ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
         self.ping()
             .or(self.query())
-            .or(self.mock_influxdb_write())
+            .or(self.influxdb_ping())
+            .or(self.influxdb_write_v1())
+            .or(self.influxdb_write_v2())
+            .or(self.influxdb_query())
             .or(self.metrics())
             .or(self.print_meta())
             .or(self.meta_leader_addr())
             .or(self.debug_pprof())
             .or(self.debug_jeprof())
             .or(self.prom_remote_read())
+            .or(self.prom_query())
             .or(self.backtrace())
             .or(self.print_raft())
             .or(self.dump_ddl_sql())
@@ -281,6 +303,7 @@ impl HttpService {
             .and(self.with_meta())
             .and(self.with_http_metrics())
             .and(self.with_hostaddr())
+            .and(warp::addr::remote())
             .and(self.handle_span_header())
             // construct_query
             .and_then(
@@ -291,6 +314,7 @@ impl HttpService {
                  meta: MetaRef,
                  metrics: Arc<HttpMetrics>,
                  addr: String,
+                 client_addr: Option<SocketAddr>,
                  parent_span_ctx: Option<SpanContext>| async move {
                     let start = Instant::now();
                     debug!(
@@ -313,7 +337,7 @@ impl HttpService {
                             SpanRecorder::new(span_context.child_span("authenticate"));
 
                         // Parse req、header and param to construct query request
-                        let query = construct_query(req, &header, param, dbms.clone())
+                        let query = construct_query(req, &header, param, client_addr, dbms.clone())
                             .await
                             .map_err(reject::custom)?;
 
@@ -383,6 +407,8 @@ impl HttpService {
             .and(warp::body::content_length_limit(self.write_body_limit))
             .and(warp::body::bytes())
             .and(self.handle_header())
+            .and(header::optional::<String>(CONTENT_TYPE.as_str()))
+            .and(header::optional::<String>(LENIENT_WRITE))
             .and(warp::query::<WriteParam>())
             .and(self.with_dbms())
             .and(self.with_coord())
@@ -392,6 +418,8 @@ impl HttpService {
             .and_then(
                 |mut req: Bytes,
                  header: Header,
+                 content_type: Option<String>,
+                 lenient: Option<String>,
                  param: WriteParam,
                  dbms: DBMSRef,
                  coord: CoordinatorRef,
@@ -412,6 +440,12 @@ impl HttpService {
                         })?;
                     }
 
+                    let table = param.table.clone();
+                    // The parameter takes precedence over the header.
+                    let lenient = param
+                        .lenient
+                        .or_else(|| lenient.map(|v| v.eq_ignore_ascii_case("true")))
+                        .unwrap_or(false);
                     let ctx = {
                         let mut span_recorder =
                             SpanRecorder::new(span_context.child_span("construct write context"));
@@ -430,22 +464,75 @@ impl HttpService {
 
                     let precision = Precision::new(ctx.precision()).unwrap_or(Precision::NS);
 
-                    let write_points_lines = {
-                        let mut span_recorder =
-                            SpanRecorder::new(span_context.child_span("try parse req to lines"));
+                    let record_batches = {
+                        let mut span_recorder = SpanRecorder::new(
+                            span_context.child_span("try parse req to record batches"),
+                        );
                         span_recorder.set_metadata("bytes", req.len());
-                        try_parse_req_to_lines(&req).map_err(reject::custom)?
+                        try_parse_req_to_record_batches(&req, content_type.as_deref())
+                            .map_err(reject::custom)?
                     };
 
-                    let resp = coord_write_points_with_span_recorder(
-                        &coord,
-                        ctx.tenant(),
-                        ctx.database(),
-                        precision,
-                        write_points_lines,
-                        span_context,
-                    )
-                    .await;
+                    if lenient && record_batches.is_none() {
+                        let resp = write_lines_leniently(
+                            &coord,
+                            ctx.tenant(),
+                            ctx.database(),
+                            precision,
+                            &req,
+                            span_context,
+                        )
+                        .await;
+                        http_record_write_metrics(
+                            &metrics,
+                            &ctx,
+                            &addr,
+                            req_len,
+                            start,
+                            HttpApiType::ApiV1Write,
+                        );
+                        return resp
+                            .map(|resp| lenient_write_response(&resp))
+                            .map_err(reject::custom);
+                    }
+
+                    let resp = match record_batches {
+                        Some(record_batches) => {
+                            let table = table.ok_or_else(|| {
+                                reject::custom(HttpError::InvalidWriteParam {
+                                    reason: "table is required for arrow data".to_string(),
+                                })
+                            })?;
+                            coord_write_record_batches_with_span_recorder(
+                                &coord,
+                                ctx.tenant(),
+                                ctx.database(),
+                                &table,
+                                record_batches,
+                                span_context,
+                            )
+                            .await
+                        }
+                        None => {
+                            let write_points_lines = {
+                                let mut span_recorder = SpanRecorder::new(
+                                    span_context.child_span("try parse req to lines"),
+                                );
+                                span_recorder.set_metadata("bytes", req.len());
+                                try_parse_req_to_lines(&req).map_err(reject::custom)?
+                            };
+
+                            coord_write_points_with_span_recorder(
+                                &coord,
+                                ctx.tenant(),
+                                ctx.database(),
+                                precision,
+                                write_points_lines,
+                                span_context,
+                            )
+                            .await
+                        }
+                    };
 
                     http_record_write_metrics(
                         &metrics,
@@ -460,56 +547,177 @@ impl HttpService {
             )
     }
 
-    fn mock_influxdb_write(
+    fn influxdb_ping(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("ping")
+            .and(warp::get().or(warp::head()))
+            .map(|_| {
+                ResponseBuilder::new(NO_CONTENT)
+                    .insert_header((
+                        HeaderName::from_static(X_INFLUXDB_VERSION),
+                        influxdb::INFLUXDB_VERSION,
+                    ))
+                    .build(vec![])
+            })
+    }
+
+    fn influxdb_write_v1(
         &self,
     ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
         warp::path!("write")
             .and(warp::post())
+            .and(warp::body::content_length_limit(self.write_body_limit))
             .and(warp::body::bytes())
-            .and(warp::query::query())
+            .and(header::optional::<String>(AUTHORIZATION.as_str()))
+            .and(header::optional::<String>(CONTENT_ENCODING.as_str()))
+            .and(warp::query::<InfluxWriteParam>())
             .and(self.with_dbms())
             .and(self.with_coord())
+            .and(self.with_http_metrics())
+            .and(self.with_hostaddr())
+            .and(self.handle_span_header())
             .and_then(
                 |req: Bytes,
-                 mut query: HashMap<String, String>,
+                 authorization: Option<String>,
+                 content_encoding: Option<String>,
+                 param: InfluxWriteParam,
                  dbms: DBMSRef,
-                 coord: CoordinatorRef| async move {
-                    let db = query.remove("db").unwrap_or(DEFAULT_DATABASE.to_string());
-                    let header = Header::with(
-                        Some(APPLICATION_JSON.to_string()),
-                        None,
-                        None,
-                        "Basic cm9vdDo=".to_string(),
-                    );
-                    let param = WriteParam {
-                        db: Some(db),
-                        precision: None,
-                        tenant: None,
+                 coord: CoordinatorRef,
+                 metrics: Arc<HttpMetrics>,
+                 addr: String,
+                 parent_span_ctx: Option<SpanContext>| async move {
+                    debug!("Receive influxdb write request, param: {:?}", param);
+                    let span_recorder =
+                        SpanRecorder::new(parent_span_ctx.child_span("influxdb write"));
+                    let InfluxWriteParam {
+                        db,
+                        rp: _,
+                        precision,
+                        u,
+                        p,
+                        tenant,
+                    } = param;
+                    let request = InfluxWriteRequest {
+                        api: InfluxApi::V1,
+                        api_type: HttpApiType::InfluxDBWrite,
+                        user_info: influxdb::user_info(authorization.as_deref(), u, p),
+                        tenant,
+                        db,
+                        precision,
+                        content_encoding,
                     };
-                    let precision = Precision::NS;
+                    let resp = influxdb_write(
+                        request,
+                        req,
+                        dbms,
+                        coord,
+                        &metrics,
+                        &addr,
+                        span_recorder.span_ctx(),
+                    )
+                    .await;
+                    Ok::<_, Rejection>(resp)
+                },
+            )
+    }
 
-                    let ctx = construct_write_context_and_check_privilege(
-                        header,
-                        param,
+    fn influxdb_write_v2(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("api" / "v2" / "write")
+            .and(warp::post())
+            .and(warp::body::content_length_limit(self.write_body_limit))
+            .and(warp::body::bytes())
+            .and(header::optional::<String>(AUTHORIZATION.as_str()))
+            .and(header::optional::<String>(CONTENT_ENCODING.as_str()))
+            .and(warp::query::<InfluxV2WriteParam>())
+            .and(self.with_dbms())
+            .and(self.with_coord())
+            .and(self.with_http_metrics())
+            .and(self.with_hostaddr())
+            .and(self.handle_span_header())
+            .and_then(
+                |req: Bytes,
+                 authorization: Option<String>,
+                 content_encoding: Option<String>,
+                 param: InfluxV2WriteParam,
+                 dbms: DBMSRef,
+                 coord: CoordinatorRef,
+                 metrics: Arc<HttpMetrics>,
+                 addr: String,
+                 parent_span_ctx: Option<SpanContext>| async move {
+                    debug!("Receive influxdb v2 write request, param: {:?}", param);
+                    let span_recorder =
+                        SpanRecorder::new(parent_span_ctx.child_span("influxdb v2 write"));
+                    let request = InfluxWriteRequest {
+                        api: InfluxApi::V2,
+                        api_type: HttpApiType::InfluxDBV2Write,
+                        user_info: influxdb::user_info(authorization.as_deref(), None, None),
+                        tenant: param.org,
+                        db: param
+                            .bucket
+                            .as_deref()
+                            .map(|bucket| influxdb::bucket_to_database(bucket).to_string()),
+                        precision: param.precision,
+                        content_encoding,
+                    };
+                    let resp = influxdb_write(
+                        request,
+                        req,
                         dbms,
-                        coord.clone(),
+                        coord,
+                        &metrics,
+                        &addr,
+                        span_recorder.span_ctx(),
                     )
-                    .await
-                    .map_err(reject::custom)?;
+                    .await;
+                    Ok::<_, Rejection>(resp)
+                },
+            )
+    }
 
-                    let lines = try_parse_req_to_lines(&req).map_err(reject::custom)?;
+    fn influxdb_query(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        // Parameters are sent in the url, or in the form body of a POST request.
+        let get_param = warp::get().and(warp::query::<InfluxQueryParam>());
+        let post_param = warp::post()
+            .and(warp::query::<InfluxQueryParam>())
+            .and(warp::body::content_length_limit(self.query_body_limit))
+            .and(warp::body::form::<InfluxQueryParam>())
+            .map(|query: InfluxQueryParam, form: InfluxQueryParam| query.merge(form));
 
-                    let resp = coord_write_points_with_span_recorder(
-                        &coord,
-                        ctx.tenant(),
-                        ctx.database(),
-                        precision,
-                        lines,
-                        None,
+        warp::path!("query")
+            .and(get_param.or(post_param).unify())
+            .and(header::optional::<String>(AUTHORIZATION.as_str()))
+            .and(self.with_dbms())
+            .and(self.with_meta())
+            .and(self.with_http_metrics())
+            .and(self.with_hostaddr())
+            .and(self.handle_span_header())
+            .and_then(
+                |param: InfluxQueryParam,
+                 authorization: Option<String>,
+                 dbms: DBMSRef,
+                 meta: MetaRef,
+                 metrics: Arc<HttpMetrics>,
+                 addr: String,
+                 parent_span_ctx: Option<SpanContext>| async move {
+                    debug!("Receive influxdb query request, param: {:?}", param);
+                    let span_recorder =
+                        SpanRecorder::new(parent_span_ctx.child_span("influxdb query"));
+                    let resp = influxdb_query(
+                        param,
+                        authorization,
+                        dbms,
+                        meta,
+                        &metrics,
+                        &addr,
+                        span_recorder.span_ctx(),
                     )
                     .await;
-
-                    resp.map(|_| ResponseBuilder::ok()).map_err(reject::custom)
+                    Ok::<_, Rejection>(resp)
                 },
             )
     }
@@ -807,6 +1015,7 @@ impl HttpService {
             .and(self.with_http_metrics())
             .and(self.with_prom_remote_server())
             .and(self.with_hostaddr())
+            .and(warp::addr::remote())
             .and(self.handle_span_header())
             .and_then(
                 |req: Bytes,
@@ -817,6 +1026,7 @@ impl HttpService {
                  metrics: Arc<HttpMetrics>,
                  prs: PromRemoteServerRef,
                  addr: String,
+                 client_addr: Option<SocketAddr>,
                  parent_span_ctx: Option<SpanContext>| async move {
                     let start = Instant::now();
                     debug!(
@@ -832,7 +1042,7 @@ impl HttpService {
                         let mut span_recorder =
                             SpanRecorder::new(span_context.child_span("construct context"));
                         span_recorder.set_metadata("bytes", req.len());
-                        let ctx = construct_read_context(&header, param, dbms)
+                        let ctx = construct_read_context(&header, param, client_addr, dbms)
                             .await
                             .map_err(reject::custom)?;
                         span_recorder.record(ctx)
@@ -868,10 +1078,7 @@ impl HttpService {
                                 );
                                 reject::custom(HttpError::from(e))
                             })
-                            .map(|b| {
-                                http_query_data_out.inc(b.len() as u64);
-                                b
-                            })
+                            .map(|resp| prom_read_response_to_reply(resp, http_query_data_out))
                     };
 
                     http_record_query_metrics(
@@ -887,6 +1094,137 @@ impl HttpService {
             )
     }
 
+    fn prom_query(
+        &self,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        let instant = warp::path!("api" / "v1" / "query").map(|| HttpApiType::ApiV1PromQuery);
+        let range =
+            warp::path!("api" / "v1" / "query_range").map(|| HttpApiType::ApiV1PromQueryRange);
+        // Parameters are sent in the url, or in the form body of a POST request.
+        let get_param = warp::get().and(warp::query::<PromQueryParam>());
+        let post_param = warp::post()
+            .and(warp::query::<PromQueryParam>())
+            .and(warp::body::content_length_limit(self.query_body_limit))
+            .and(warp::body::form::<PromQueryParam>())
+            .map(|query: PromQueryParam, form: PromQueryParam| query.merge(form));
+
+        instant
+            .or(range)
+            .unify()
+            .and(get_param.or(post_param).unify())
+            .and(self.handle_header())
+            .and(self.with_dbms())
+            .and(self.with_meta())
+            .and(self.with_http_metrics())
+            .and(self.with_prom_remote_server())
+            .and(self.with_hostaddr())
+            .and(warp::addr::remote())
+            .and(self.handle_span_header())
+            .and_then(
+                |api_type: HttpApiType,
+                 param: PromQueryParam,
+                 header: Header,
+                 dbms: DBMSRef,
+                 meta: MetaRef,
+                 metrics: Arc<HttpMetrics>,
+                 prs: PromRemoteServerRef,
+                 addr: String,
+                 client_addr: Option<SocketAddr>,
+                 parent_span_ctx: Option<SpanContext>| async move {
+                    let start = Instant::now();
+                    debug!(
+                        "Receive rest prom query request, header: {:?}, param: {:?}",
+                        header, param
+                    );
+                    let span_recorder = SpanRecorder::new(
+                        parent_span_ctx.child_span(format!("rest prom {api_type}")),
+                    );
+                    let span_context = span_recorder.span_ctx();
+
+                    let PromQueryParam {
+                        tenant,
+                        db,
+                        query,
+                        time,
+                        start: start_time,
+                        end: end_time,
+                        step,
+                    } = param;
+                    let context = {
+                        let mut span_recorder =
+                            SpanRecorder::new(span_context.child_span("construct context"));
+                        let param = SqlParam {
+                            tenant,
+                            db,
+                            chunked: None,
+                            target_partitions: None,
+                            stream_trigger_interval: None,
+                        };
+                        let ctx = construct_read_context(&header, param, client_addr, dbms)
+                            .await
+                            .map_err(reject::custom)?;
+                        span_recorder.record(ctx)
+                    };
+                    let query = query.unwrap_or_default();
+                    let req_len = query.len();
+
+                    http_limiter_check_query(&meta, context.tenant(), req_len)
+                        .await
+                        .map_err(reject::custom)?;
+
+                    let http_query_data_out = metrics.http_data_out(
+                        context.tenant(),
+                        context.user().desc().name(),
+                        Some(context.database()),
+                        addr.as_str(),
+                        api_type,
+                    );
+
+                    let request = match api_type {
+                        HttpApiType::ApiV1PromQueryRange => promql::range_query_request(
+                            query,
+                            start_time.as_deref(),
+                            end_time.as_deref(),
+                            step.as_deref(),
+                        ),
+                        _ => promql::instant_query_request(
+                            query,
+                            time.as_deref(),
+                            now_timestamp_millis(),
+                        ),
+                    };
+                    let result = {
+                        let mut span_recorder =
+                            SpanRecorder::new(span_context.child_span("promql query"));
+                        let result = match request {
+                            Ok(request) => {
+                                prs.query(&context, request, span_recorder.span_ctx()).await
+                            }
+                            Err(e) => Err(e),
+                        };
+                        if let Err(e) = &result {
+                            span_recorder.error(e.to_string());
+                            trace::error!("Failed to handle prom query request, err: {}", e);
+                        }
+                        result
+                    };
+
+                    http_record_query_metrics(&metrics, &context, &addr, req_len, start, api_type);
+
+                    let resp = match result {
+                        Ok(body) => {
+                            http_query_data_out.inc(body.len() as u64);
+                            ResponseBuilder::new(OK)
+                                .insert_header((CONTENT_TYPE, APPLICATION_JSON))
+                                .build(body)
+                        }
+                        Err(e) => prom_query_error_to_reply(e),
+                    };
+                    Ok::<_, Rejection>(resp)
+                },
+            )
+    }
+
     fn prom_remote_write(
         &self,
     ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
@@ -1111,9 +1449,10 @@ async fn construct_query(
     req: Bytes,
     header: &Header,
     param: SqlParam,
+    client_addr: Option<SocketAddr>,
     dbms: DBMSRef,
 ) -> Result<Query, HttpError> {
-    let context = construct_read_context(header, param, dbms).await?;
+    let context = construct_read_context(header, param, client_addr, dbms).await?;
 
     Ok(Query::new(
         context,
@@ -1121,24 +1460,39 @@ async fn construct_query(
     ))
 }
 
+/// Authenticate by the bearer token if present, otherwise by the basic auth.
+async fn authenticate(
+    header: &Header,
+    tenant_name: &str,
+    dbms: &DBMSRef,
+) -> Result<User, HttpError> {
+    let user = match header.try_get_bearer_token() {
+        Some(token) => dbms.authenticate_token(token, tenant_name).await,
+        None => {
+            let user_info = header.try_get_basic_auth()?;
+            dbms.authenticate(&user_info, tenant_name).await
+        }
+    }
+    .context(QuerySnafu)?;
+
+    Ok(user)
+}
+
 async fn construct_read_context(
     header: &Header,
     param: SqlParam,
+    client_addr: Option<SocketAddr>,
     dbms: DBMSRef,
 ) -> Result<Context, HttpError> {
-    let user_info = header.try_get_basic_auth()?;
-
     let tenant = param.tenant;
-    let user = dbms
-        .authenticate(&user_info, tenant.as_deref().unwrap_or(DEFAULT_CATALOG))
-        .await
-        .context(QuerySnafu)?;
+    let user = authenticate(header, tenant.as_deref().unwrap_or(DEFAULT_CATALOG), &dbms).await?;
 
     let context = ContextBuilder::new(user)
         .with_tenant(tenant)
         .with_database(param.db)
         .with_target_partitions(param.target_partitions)
         .with_chunked(param.chunked)
+        .with_client_addr(client_addr.map(|addr| addr.to_string()))
         .with_stream_trigger_interval(
             param
                 .stream_trigger_interval
@@ -1158,14 +1512,11 @@ async fn construct_write_context(
     param: WriteParam,
     dbms: DBMSRef,
 ) -> Result<Context, HttpError> {
-    let user_info = header.try_get_basic_auth()?;
     let tenant = param.tenant;
     let db = param.db;
     let precision = param.precision;
 
-    let user = dbms
-        .authenticate(&user_info, tenant.as_deref().unwrap_or(DEFAULT_CATALOG))
-        .await?;
+    let user = authenticate(header, tenant.as_deref().unwrap_or(DEFAULT_CATALOG), &dbms).await?;
 
     let context = ContextBuilder::new(user)
         .with_tenant(tenant)
@@ -1191,7 +1542,11 @@ async fn construct_write_context_and_check_privilege(
     coord: CoordinatorRef,
 ) -> Result<Context, HttpError> {
     let context = construct_write_context(&header, param, dbms).await?;
+    check_write_privilege(&context, &coord).await?;
+    Ok(context)
+}
 
+async fn check_write_privilege(context: &Context, coord: &CoordinatorRef) -> Result<(), HttpError> {
     let tenant_id = *coord
         .tenant_meta(context.tenant())
         .await
@@ -1215,7 +1570,26 @@ async fn construct_write_context_and_check_privilege(
             },
         });
     }
-    Ok(context)
+    Ok(())
+}
+
+/// Decode the body as arrow record batches according to the content type,
+/// returns None if the body is line protocol.
+fn try_parse_req_to_record_batches(
+    req: &Bytes,
+    content_type: Option<&str>,
+) -> Result<Option<Vec<RecordBatch>>, HttpError> {
+    let media_type = content_type
+        .and_then(|e| e.split(';').next())
+        .map(str::trim);
+    let record_batches = match media_type {
+        Some(APPLICATION_ARROW_STREAM) => arrow_stream_to_batches(req.clone()),
+        Some(APPLICATION_PARQUET) => parquet_to_batches(req.clone()),
+        _ => return Ok(None),
+    };
+    record_batches
+        .map(Some)
+        .map_err(|e| HttpError::ParseArrowData { source: e })
 }
 
 fn try_parse_req_to_lines(req: &Bytes) -> Result<Vec<Line>, HttpError> {
@@ -1281,6 +1655,24 @@ async fn coord_write_points_with_span_recorder(
         })
 }
 
+async fn coord_write_record_batches_with_span_recorder(
+    coord: &CoordinatorRef,
+    tenant: &str,
+    db: &str,
+    table: &str,
+    record_batches: Vec<RecordBatch>,
+    span_context: Option<&SpanContext>,
+) -> Result<usize, HttpError> {
+    let mut span_recorder = SpanRecorder::new(span_context.child_span("write record batches"));
+    coord
+        .write_table_record_batches(tenant, db, table, record_batches, span_recorder.span_ctx())
+        .await
+        .map_err(|e| {
+            span_recorder.error(e.to_string());
+            e.into()
+        })
+}
+
 async fn sql_handle(
     query: &Query,
     dbms: &DBMSRef,
@@ -1345,6 +1737,241 @@ async fn sql_handle(
     }
 }
 
+fn prom_read_response_to_reply(
+    resp: PromReadResponse,
+    http_query_data_out: U64Counter,
+) -> Response {
+    match resp {
+        PromReadResponse::Samples(body) => {
+            http_query_data_out.inc(body.len() as u64);
+            ResponseBuilder::new(OK)
+                .insert_header((CONTENT_TYPE, APPLICATION_PROTOBUF))
+                .insert_header((CONTENT_ENCODING, SNAPPY))
+                .build(body)
+        }
+        PromReadResponse::StreamedXorChunks(frames) => {
+            // The frames already sent can not be revoked, the response is aborted on error.
+            let frames = frames
+                .inspect_ok(move |frame| http_query_data_out.inc(frame.len() as u64))
+                .map_err(|e| {
+                    trace::error!("Failed to stream prom remote read response, err: {}", e);
+                    HttpError::from(e)
+                });
+            let body = Body::wrap_stream(frames);
+            ResponseBuilder::new(OK)
+                .insert_header((
+                    CONTENT_TYPE,
+                    APPLICATION_STREAMED_PROTOBUF_CHUNKED_READ_RESPONSE,
+                ))
+                .build_stream_response(Response::new(body))
+        }
+    }
+}
+
+/// Errors of PromQL queries are replied in the format of prometheus HTTP API.
+fn prom_query_error_to_reply(err: QueryError) -> Response {
+    let (status_code, error_type) = match err {
+        QueryError::InvalidPromQL { .. } => (BAD_REQUEST, "bad_data"),
+        _ => (UNPROCESSABLE_ENTITY, "execution"),
+    };
+    ResponseBuilder::new(status_code).json(&serde_json::json!({
+        "status": "error",
+        "errorType": error_type,
+        "error": err.to_string(),
+    }))
+}
+
+/// A write request of the InfluxDB API, whose parameters are mapped to CnosDB.
+struct InfluxWriteRequest {
+    api: InfluxApi,
+    api_type: HttpApiType,
+    user_info: Result<UserInfo, HttpError>,
+    tenant: Option<String>,
+    db: Option<String>,
+    precision: Option<String>,
+    content_encoding: Option<String>,
+}
+
+async fn influxdb_write(
+    request: InfluxWriteRequest,
+    mut req: Bytes,
+    dbms: DBMSRef,
+    coord: CoordinatorRef,
+    metrics: &HttpMetrics,
+    addr: &str,
+    span_context: Option<&SpanContext>,
+) -> Response {
+    let start = Instant::now();
+    let req_len = req.len();
+    let InfluxWriteRequest {
+        api,
+        api_type,
+        user_info,
+        tenant,
+        db,
+        precision,
+        content_encoding,
+    } = request;
+
+    let mut lines_len = None;
+    let result = async {
+        let db = db.ok_or_else(|| HttpError::InvalidWriteParam {
+            reason: match api {
+                InfluxApi::V1 => "database is required".to_string(),
+                InfluxApi::V2 => "bucket is required".to_string(),
+            },
+        })?;
+        let precision = InfluxPrecision::parse(api, precision.as_deref())?;
+        let ctx = {
+            let mut span_recorder =
+                SpanRecorder::new(span_context.child_span("construct write context"));
+            let user = dbms
+                .authenticate(&user_info?, tenant.as_deref().unwrap_or(DEFAULT_CATALOG))
+                .await?;
+            let ctx = ContextBuilder::new(user)
+                .with_tenant(tenant)
+                .with_database(Some(db))
+                .build();
+            check_write_privilege(&ctx, &coord).await?;
+            span_recorder.record(ctx)
+        };
+
+        http_limiter_check_write(&coord.meta_manager(), ctx.tenant(), req_len).await?;
+
+        if let Some(encoding) = content_encoding {
+            let encoding =
+                Encoding::from_str(&encoding).ok_or_else(|| HttpError::InvalidHeader {
+                    reason: format!("content encoding not support: {}", encoding),
+                })?;
+            req = encoding
+                .decode(req)
+                .map_err(|e| HttpError::DecodeRequest { source: e })?;
+        }
+
+        let (precision, lines) = {
+            let mut span_recorder =
+                SpanRecorder::new(span_context.child_span("try parse req to lines"));
+            span_recorder.set_metadata("bytes", req.len());
+            influxdb::parse_lines(&req, precision)?
+        };
+        lines_len = Some(lines.len());
+
+        coord_write_points_with_span_recorder(
+            &coord,
+            ctx.tenant(),
+            ctx.database(),
+            precision,
+            lines,
+            span_context,
+        )
+        .await?;
+        Ok::<_, HttpError>(ctx)
+    }
+    .await;
+
+    match result {
+        Ok(ctx) => {
+            http_record_write_metrics(metrics, &ctx, addr, req_len, start, api_type);
+            ResponseBuilder::new(NO_CONTENT).build(vec![])
+        }
+        Err(e) => {
+            trace::error!("Failed to handle influxdb write request, err: {}", e);
+            influxdb::error_response(api, &e, lines_len)
+        }
+    }
+}
+
+async fn influxdb_query(
+    param: InfluxQueryParam,
+    authorization: Option<String>,
+    dbms: DBMSRef,
+    meta: MetaRef,
+    metrics: &HttpMetrics,
+    addr: &str,
+    span_context: Option<&SpanContext>,
+) -> Response {
+    let start = Instant::now();
+    let InfluxQueryParam {
+        q,
+        db,
+        epoch,
+        u,
+        p,
+        tenant,
+    } = param;
+
+    let result = async {
+        let query = q.ok_or_else(|| HttpError::InvalidQueryParam {
+            reason: "missing required parameter \"q\"".to_string(),
+        })?;
+        let epoch = epoch
+            .map(|e| {
+                Epoch::parse(&e).ok_or_else(|| HttpError::InvalidQueryParam {
+                    reason: format!("invalid epoch {e:?}"),
+                })
+            })
+            .transpose()?;
+
+        let ctx = {
+            let mut span_recorder = SpanRecorder::new(span_context.child_span("construct context"));
+            let user_info = influxdb::user_info(authorization.as_deref(), u, p)?;
+            let user = dbms
+                .authenticate(&user_info, tenant.as_deref().unwrap_or(DEFAULT_CATALOG))
+                .await?;
+            let ctx = ContextBuilder::new(user)
+                .with_tenant(tenant)
+                .with_database(db)
+                .build();
+            span_recorder.record(ctx)
+        };
+
+        http_limiter_check_query(&meta, ctx.tenant(), query.len()).await?;
+
+        let response = {
+            let mut span_recorder = SpanRecorder::new(span_context.child_span("influxql query"));
+            influxql::execute(&dbms, &ctx, &query, epoch, span_recorder.span_ctx())
+                .await
+                .map_err(|e| {
+                    span_recorder.error(e.to_string());
+                    e
+                })?
+        };
+        http_record_query_metrics(
+            metrics,
+            &ctx,
+            addr,
+            query.len(),
+            start,
+            HttpApiType::InfluxDBQuery,
+        );
+
+        let body = serde_json::to_vec(&response).map_err(|e| HttpError::FetchResult {
+            reason: e.to_string(),
+        })?;
+        metrics
+            .http_data_out(
+                ctx.tenant(),
+                ctx.user().desc().name(),
+                None,
+                addr,
+                HttpApiType::InfluxDBQuery,
+            )
+            .inc(body.len() as u64);
+        Ok::<_, HttpError>(body)
+    }
+    .await;
+
+    match result {
+        Ok(body) => ResponseBuilder::new(OK)
+            .insert_header((CONTENT_TYPE, APPLICATION_JSON))
+            .build(body),
+        Err(e) => {
+            trace::error!("Failed to handle influxdb query request, err: {}", e);
+            influxdb::error_response(InfluxApi::V1, &e, None)
+        }
+    }
+}
+
 async fn http_limiter_check_query(
     meta: &MetaRef,
     tenant: &str,
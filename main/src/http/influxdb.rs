@@ -0,0 +1,370 @@
+//! Compatibility layer of the InfluxDB v1 and v2 HTTP APIs.
+
+use coordinator::errors::CoordinatorError;
+use http_protocol::header::BASIC_PREFIX;
+use meta::error::MetaError;
+use models::auth::user::UserInfo;
+use models::auth::AuthError;
+use models::error_code::ErrorCode;
+use models::schema::Precision;
+use models::utils::now_timestamp_nanos;
+use protocol_parser::line_protocol::line_protocol_to_lines;
+use protocol_parser::Line;
+use serde_json::json;
+use spi::QueryError;
+use warp::http::StatusCode;
+use warp::hyper::body::Bytes;
+use warp::reply::Response;
+
+use super::header::Header;
+use super::response::ResponseBuilder;
+use super::Error as HttpError;
+
+pub const TOKEN_PREFIX: &str = "Token ";
+pub const X_INFLUXDB_VERSION: &str = "x-influxdb-version";
+/// The InfluxDB version reported to clients, which is checked by some of them.
+pub const INFLUXDB_VERSION: &str = "1.8.10";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfluxApi {
+    V1,
+    V2,
+}
+
+/// Timestamp precision of InfluxDB line protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfluxPrecision {
+    Nanosecond,
+    Microsecond,
+    Millisecond,
+    Second,
+    Minute,
+    Hour,
+}
+
+impl InfluxPrecision {
+    /// Nanosecond if absent, v1 accepts `n`, `ns`, `u`, `us`, `µ`, `ms`, `s`, `m`, `h`,
+    /// v2 accepts `ns`, `us`, `ms`, `s`.
+    pub fn parse(api: InfluxApi, precision: Option<&str>) -> Result<Self, HttpError> {
+        let precision = match (api, precision.unwrap_or("ns")) {
+            (_, "ns") | (InfluxApi::V1, "n") => Self::Nanosecond,
+            (_, "us") | (InfluxApi::V1, "u" | "µ") => Self::Microsecond,
+            (_, "ms") => Self::Millisecond,
+            (_, "s") => Self::Second,
+            (InfluxApi::V1, "m") => Self::Minute,
+            (InfluxApi::V1, "h") => Self::Hour,
+            (_, other) => {
+                return Err(HttpError::InvalidWriteParam {
+                    reason: format!("invalid precision {other:?}"),
+                })
+            }
+        };
+        Ok(precision)
+    }
+
+    /// The precision to write with, and the multiplier of timestamps to it.
+    fn to_precision(self) -> (Precision, i64) {
+        match self {
+            Self::Nanosecond => (Precision::NS, 1),
+            Self::Microsecond => (Precision::US, 1),
+            Self::Millisecond => (Precision::MS, 1),
+            Self::Second => (Precision::MS, 1_000),
+            Self::Minute => (Precision::MS, 60_000),
+            Self::Hour => (Precision::MS, 3_600_000),
+        }
+    }
+
+    fn nanos(self) -> i64 {
+        match self {
+            Self::Nanosecond => 1,
+            Self::Microsecond => 1_000,
+            Self::Millisecond => 1_000_000,
+            Self::Second => 1_000_000_000,
+            Self::Minute => 60_000_000_000,
+            Self::Hour => 3_600_000_000_000,
+        }
+    }
+}
+
+/// Parse line protocol with timestamps in `precision`, lines without timestamp
+/// are written at now truncated to `precision`, as InfluxDB does.
+pub fn parse_lines(
+    req: &Bytes,
+    precision: InfluxPrecision,
+) -> Result<(Precision, Vec<Line>), HttpError> {
+    let lines = simdutf8::basic::from_utf8(req.as_ref())
+        .map_err(|e| HttpError::InvalidUTF8 { source: e })?;
+    let default_time = now_timestamp_nanos() / precision.nanos();
+    let mut lines = line_protocol_to_lines(lines, default_time)
+        .map_err(|e| HttpError::ParseLineProtocol { source: e })?;
+
+    let (precision, multiplier) = precision.to_precision();
+    if multiplier != 1 {
+        for line in lines.iter_mut() {
+            line.timestamp = line.timestamp.saturating_mul(multiplier);
+        }
+    }
+
+    Ok((precision, lines))
+}
+
+/// Credentials are taken from the `u` and `p` parameters, or the `Authorization` header
+/// of `Basic <base64(user:password)>` or `Token <user>:<password>`.
+pub fn user_info(
+    authorization: Option<&str>,
+    user: Option<String>,
+    password: Option<String>,
+) -> Result<UserInfo, HttpError> {
+    if let Some(user) = user {
+        return Ok(UserInfo {
+            user,
+            password: password.unwrap_or_default(),
+            private_key: None,
+        });
+    }
+
+    match authorization {
+        Some(auth) if auth.starts_with(BASIC_PREFIX) => {
+            Header::with(None, None, None, auth.to_string()).try_get_basic_auth()
+        }
+        Some(auth) if auth.starts_with(TOKEN_PREFIX) => {
+            match auth[TOKEN_PREFIX.len()..].trim().split_once(':') {
+                Some((user, password)) => Ok(UserInfo {
+                    user: user.to_string(),
+                    password: password.to_string(),
+                    private_key: None,
+                }),
+                None => Err(HttpError::ParseAuth {
+                    reason: "token must be in the form of <user>:<password>".to_string(),
+                }),
+            }
+        }
+        Some(auth) => Err(HttpError::ParseAuth {
+            reason: auth.to_string(),
+        }),
+        None => Err(HttpError::ParseAuth {
+            reason: "missing credentials".to_string(),
+        }),
+    }
+}
+
+/// The database of a v2 bucket, which may be in the form of `<database>/<retention policy>`.
+pub fn bucket_to_database(bucket: &str) -> &str {
+    bucket.split_once('/').map(|(db, _)| db).unwrap_or(bucket)
+}
+
+fn is_column_type_conflict(err: &HttpError) -> bool {
+    match err {
+        HttpError::Tskv { source }
+        | HttpError::Coordinator {
+            source: CoordinatorError::TskvError { source },
+        } => source.is_column_type_conflict(),
+        _ => false,
+    }
+}
+
+fn is_not_found(err: &HttpError) -> bool {
+    let meta_not_found = |e: &MetaError| {
+        matches!(
+            e,
+            MetaError::TenantNotFound { .. } | MetaError::DatabaseNotFound { .. }
+        )
+    };
+    match err {
+        HttpError::NotFoundTenant { .. }
+        | HttpError::Query {
+            source: QueryError::DatabaseNotFound { .. },
+        }
+        | HttpError::Coordinator {
+            source: CoordinatorError::TenantNotFound { .. },
+        } => true,
+        HttpError::Meta { source }
+        | HttpError::Query {
+            source: QueryError::Meta { source },
+        }
+        | HttpError::Coordinator {
+            source: CoordinatorError::Meta { source },
+        } => meta_not_found(source),
+        _ => false,
+    }
+}
+
+/// Convert the error to an InfluxDB error response,
+/// `lines` is the number of lines dropped if the error is caused by writing.
+pub fn error_response(api: InfluxApi, err: &HttpError, lines: Option<usize>) -> Response {
+    let (status, code, message) = match err {
+        err if is_column_type_conflict(err) => (
+            StatusCode::BAD_REQUEST,
+            "invalid",
+            format!(
+                "partial write: field type conflict: {} dropped={}",
+                err.error_code().message(),
+                lines.unwrap_or_default()
+            ),
+        ),
+        HttpError::ParseAuth { .. }
+        | HttpError::Query {
+            source:
+                QueryError::Auth {
+                    source: AuthError::AccessDenied { .. } | AuthError::PasswordNotSet,
+                },
+        } => (
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+            err.error_code().message(),
+        ),
+        HttpError::Query {
+            source: QueryError::InsufficientPrivileges { .. },
+        } => (
+            StatusCode::FORBIDDEN,
+            "forbidden",
+            err.error_code().message(),
+        ),
+        err if is_not_found(err) => (
+            StatusCode::NOT_FOUND,
+            "not found",
+            err.error_code().message(),
+        ),
+        HttpError::ParseLineProtocol { .. }
+        | HttpError::InvalidUTF8 { .. }
+        | HttpError::InvalidWriteParam { .. }
+        | HttpError::InvalidHeader { .. }
+        | HttpError::DecodeRequest { .. } => (
+            StatusCode::BAD_REQUEST,
+            "invalid",
+            match api {
+                InfluxApi::V1 => format!("unable to parse: {}", err.error_code().message()),
+                InfluxApi::V2 => err.error_code().message(),
+            },
+        ),
+        HttpError::Query {
+            source: QueryError::InvalidInfluxQL { .. },
+        } => (
+            StatusCode::BAD_REQUEST,
+            "invalid",
+            format!("error parsing query: {}", err.error_code().message()),
+        ),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal error",
+            err.error_code().message(),
+        ),
+    };
+
+    let body = match api {
+        InfluxApi::V1 => json!({ "error": message }),
+        InfluxApi::V2 => json!({ "code": code, "message": message }),
+    };
+    ResponseBuilder::new(status).json(&body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_precision() {
+        assert_eq!(
+            InfluxPrecision::parse(InfluxApi::V1, None).unwrap(),
+            InfluxPrecision::Nanosecond
+        );
+        assert_eq!(
+            InfluxPrecision::parse(InfluxApi::V1, Some("u")).unwrap(),
+            InfluxPrecision::Microsecond
+        );
+        assert_eq!(
+            InfluxPrecision::parse(InfluxApi::V1, Some("h")).unwrap(),
+            InfluxPrecision::Hour
+        );
+        assert!(InfluxPrecision::parse(InfluxApi::V2, Some("h")).is_err());
+        assert!(InfluxPrecision::parse(InfluxApi::V2, Some("u")).is_err());
+        assert!(InfluxPrecision::parse(InfluxApi::V1, Some("d")).is_err());
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let req = Bytes::from("cpu,host=a value=1 1700000000\ncpu,host=b value=2 1700000001");
+        let (precision, lines) = parse_lines(&req, InfluxPrecision::Second).unwrap();
+        assert_eq!(precision, Precision::MS);
+        assert_eq!(lines[0].timestamp, 1_700_000_000_000);
+        assert_eq!(lines[1].timestamp, 1_700_000_001_000);
+
+        let req = Bytes::from("cpu,host=a value=1 1700000000000000000");
+        let (precision, lines) = parse_lines(&req, InfluxPrecision::Nanosecond).unwrap();
+        assert_eq!(precision, Precision::NS);
+        assert_eq!(lines[0].timestamp, 1_700_000_000_000_000_000);
+    }
+
+    #[test]
+    fn test_user_info() {
+        let user = user_info(
+            Some("Basic cm9vdDo="),
+            Some("u".to_string()),
+            Some("p".to_string()),
+        )
+        .unwrap();
+        assert_eq!((user.user.as_str(), user.password.as_str()), ("u", "p"));
+
+        let user = user_info(Some("Basic cm9vdDo="), None, None).unwrap();
+        assert_eq!((user.user.as_str(), user.password.as_str()), ("root", ""));
+
+        let user = user_info(Some("Token telegraf:secret"), None, None).unwrap();
+        assert_eq!(
+            (user.user.as_str(), user.password.as_str()),
+            ("telegraf", "secret")
+        );
+
+        assert!(user_info(Some("Token secret"), None, None).is_err());
+        assert!(user_info(Some("Bearer secret"), None, None).is_err());
+        assert!(user_info(None, None, None).is_err());
+    }
+
+    #[test]
+    fn test_bucket_to_database() {
+        assert_eq!(bucket_to_database("telegraf/autogen"), "telegraf");
+        assert_eq!(bucket_to_database("telegraf"), "telegraf");
+    }
+
+    #[test]
+    fn test_error_response() {
+        let resp = error_response(
+            InfluxApi::V2,
+            &HttpError::ParseAuth {
+                reason: "missing credentials".to_string(),
+            },
+            None,
+        );
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let resp = error_response(
+            InfluxApi::V1,
+            &HttpError::Query {
+                source: QueryError::InsufficientPrivileges {
+                    privilege: "write".to_string(),
+                },
+            },
+            None,
+        );
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        let resp = error_response(
+            InfluxApi::V1,
+            &HttpError::Meta {
+                source: MetaError::DatabaseNotFound {
+                    database: "telegraf".to_string(),
+                },
+            },
+            None,
+        );
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let resp = error_response(
+            InfluxApi::V1,
+            &HttpError::InvalidWriteParam {
+                reason: "database is required".to_string(),
+            },
+            None,
+        );
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
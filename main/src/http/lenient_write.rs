@@ -0,0 +1,271 @@
+//! Lenient line protocol writes.
+//!
+//! Malformed lines and lines conflicting with the types of columns, in the stored
+//! table schemas or in the earlier lines, are rejected one by one, the other lines
+//! are written at once.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use coordinator::errors::CoordinatorError;
+use coordinator::service::CoordinatorRef;
+use http_protocol::response::{LenientWriteResponse, RejectedLine};
+use http_protocol::status_code::{BAD_REQUEST, OK};
+use models::schema::{ColumnType, Precision};
+use models::utils::now_timestamp_nanos;
+use models::ValueType;
+use protocol_parser::line_protocol::line_protocol_to_lines_lenient;
+use protocol_parser::Line;
+use protos::FieldValue;
+use trace::{SpanContext, SpanExt, SpanRecorder};
+use warp::hyper::body::Bytes;
+use warp::reply::Response;
+
+use super::response::ResponseBuilder;
+use super::Error as HttpError;
+
+/// Parse the request leniently and write the valid lines.
+pub async fn write_lines_leniently(
+    coord: &CoordinatorRef,
+    tenant: &str,
+    db: &str,
+    precision: Precision,
+    req: &Bytes,
+    span_context: Option<&SpanContext>,
+) -> Result<LenientWriteResponse, HttpError> {
+    let lines = simdutf8::basic::from_utf8(req.as_ref())
+        .map_err(|e| HttpError::InvalidUTF8 { source: e })?;
+
+    let (lines, errors) = {
+        let mut span_recorder =
+            SpanRecorder::new(span_context.child_span("try parse req to lines leniently"));
+        span_recorder.set_metadata("bytes", req.len());
+        line_protocol_to_lines_lenient(lines, now_timestamp_nanos())
+    };
+    let mut rejected = errors
+        .into_iter()
+        .map(|(line, e)| RejectedLine {
+            line,
+            error: e.to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    let column_types = stored_column_types(coord, tenant, db, &lines).await?;
+    let lines = reject_conflicting_lines(lines, column_types, &mut rejected);
+    if !lines.is_empty() {
+        write(coord, tenant, db, precision, &lines, span_context).await?;
+    }
+    let written = lines.len();
+
+    rejected.sort_by_key(|r| r.line);
+    Ok(LenientWriteResponse { written, rejected })
+}
+
+/// `200 OK` if all lines are written, otherwise `400 Bad Request`.
+pub fn lenient_write_response(resp: &LenientWriteResponse) -> Response {
+    let status = if resp.rejected.is_empty() {
+        OK
+    } else {
+        BAD_REQUEST
+    };
+    ResponseBuilder::new(status).json(resp)
+}
+
+fn column_type(value: Option<&FieldValue>) -> &'static str {
+    match value {
+        None => "tag",
+        Some(FieldValue::U64(_)) => "unsigned",
+        Some(FieldValue::I64(_)) => "integer",
+        Some(FieldValue::Str(_)) => "string",
+        Some(FieldValue::F64(_)) => "float",
+        Some(FieldValue::Bool(_)) => "boolean",
+    }
+}
+
+fn stored_column_type(column_type: &ColumnType) -> &'static str {
+    match column_type {
+        ColumnType::Tag => "tag",
+        ColumnType::Time(_) => "timestamp",
+        ColumnType::Field(ValueType::Unsigned) => "unsigned",
+        ColumnType::Field(ValueType::Integer) => "integer",
+        ColumnType::Field(ValueType::String) => "string",
+        // Strings are written into geometry columns.
+        ColumnType::Field(ValueType::Geometry(_)) => "string",
+        ColumnType::Field(ValueType::Float) => "float",
+        ColumnType::Field(ValueType::Boolean) => "boolean",
+        ColumnType::Field(ValueType::Unknown) => "unknown",
+    }
+}
+
+type ColumnTypes<'a> = HashMap<(Cow<'a, str>, Cow<'a, str>), &'static str>;
+
+/// Types of the columns in the stored schemas of the tables written by the lines.
+async fn stored_column_types<'a>(
+    coord: &CoordinatorRef,
+    tenant: &str,
+    db: &str,
+    lines: &[(usize, Line<'a>)],
+) -> Result<ColumnTypes<'a>, HttpError> {
+    let mut column_types = ColumnTypes::new();
+    let meta = match coord.meta_manager().tenant_meta(tenant).await {
+        Some(meta) => meta,
+        None => return Ok(column_types),
+    };
+
+    let mut tables = lines
+        .iter()
+        .map(|(_, line)| &line.table)
+        .collect::<Vec<_>>();
+    tables.sort();
+    tables.dedup();
+    for table in tables {
+        let schema = meta
+            .get_tskv_table_schema(db, table)
+            .map_err(CoordinatorError::from)?;
+        if let Some(schema) = schema {
+            for column in schema.columns() {
+                column_types.insert(
+                    (table.clone(), Cow::Owned(column.name.clone())),
+                    stored_column_type(&column.column_type),
+                );
+            }
+        }
+    }
+
+    Ok(column_types)
+}
+
+/// Lines of the same table are converted to one batch, a column of them must have one type,
+/// so lines conflicting with the types of columns in the stored schemas (`column_types`)
+/// or in the earlier lines are rejected.
+fn reject_conflicting_lines<'a>(
+    lines: Vec<(usize, Line<'a>)>,
+    mut column_types: ColumnTypes<'a>,
+    rejected: &mut Vec<RejectedLine>,
+) -> Vec<(usize, Line<'a>)> {
+    let mut valid_lines = Vec::with_capacity(lines.len());
+
+    for (line_number, line) in lines {
+        let columns = line
+            .tags
+            .iter()
+            .map(|(k, _)| (k, column_type(None)))
+            .chain(line.fields.iter().map(|(k, v)| (k, column_type(Some(v)))))
+            .collect::<Vec<_>>();
+
+        let conflict = columns.iter().find_map(|(column, found)| {
+            column_types
+                .get(&(line.table.clone(), (*column).clone()))
+                .filter(|expected| *expected != found)
+                .map(|expected| {
+                    format!("column '{column}' type conflict, found {found} expected {expected}")
+                })
+        });
+        match conflict {
+            Some(error) => rejected.push(RejectedLine {
+                line: line_number,
+                error,
+            }),
+            None => {
+                for (column, found) in columns {
+                    column_types
+                        .entry((line.table.clone(), column.clone()))
+                        .or_insert(found);
+                }
+                valid_lines.push((line_number, line));
+            }
+        }
+    }
+
+    valid_lines
+}
+
+async fn write(
+    coord: &CoordinatorRef,
+    tenant: &str,
+    db: &str,
+    precision: Precision,
+    lines: &[(usize, Line<'_>)],
+    span_context: Option<&SpanContext>,
+) -> Result<(), CoordinatorError> {
+    let mut span_recorder = SpanRecorder::new(span_context.child_span("write points"));
+    span_recorder.set_metadata("lines", lines.len());
+    let lines = lines.iter().map(|(_, line)| line.clone()).collect();
+    coord
+        .write_lines(tenant, db, precision, lines, span_recorder.span_ctx())
+        .await
+        .map_err(|e| {
+            span_recorder.error(e.to_string());
+            e
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+
+    use protocol_parser::line_protocol::line_protocol_to_lines_lenient;
+
+    use super::{reject_conflicting_lines, ColumnTypes};
+
+    #[test]
+    fn test_reject_conflicting_lines() {
+        let (lines, _) = line_protocol_to_lines_lenient(
+            "cpu,host=a value=1\n\
+             cpu,host=b value=\"x\"\n\
+             cpu,value=c usage=1i\n\
+             mem,host=a value=\"x\"\n\
+             cpu,host=c value=2,usage=1i\n\
+             cpu,host=d usage=2",
+            0,
+        );
+
+        let mut rejected = vec![];
+        let lines = reject_conflicting_lines(lines, ColumnTypes::new(), &mut rejected);
+        assert_eq!(
+            lines.iter().map(|(n, _)| *n).collect::<Vec<_>>(),
+            vec![1, 4, 5]
+        );
+        assert_eq!(
+            rejected.iter().map(|r| r.line).collect::<Vec<_>>(),
+            vec![2, 3, 6]
+        );
+        assert_eq!(
+            rejected[0].error,
+            "column 'value' type conflict, found string expected float"
+        );
+    }
+
+    #[test]
+    fn test_reject_lines_conflicting_with_stored_schema() {
+        let (lines, _) = line_protocol_to_lines_lenient(
+            "cpu,host=a value=1i\n\
+             cpu,host=b value=2\n\
+             cpu,host=c value=3i,usage=1\n\
+             mem,value=a usage=1",
+            0,
+        );
+
+        let column_types = ColumnTypes::from([
+            ((Cow::from("cpu"), Cow::from("host")), "tag"),
+            ((Cow::from("cpu"), Cow::from("value")), "float"),
+            ((Cow::from("mem"), Cow::from("value")), "integer"),
+        ]);
+        let mut rejected = vec![];
+        let lines = reject_conflicting_lines(lines, column_types, &mut rejected);
+        assert_eq!(lines.iter().map(|(n, _)| *n).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(
+            rejected.iter().map(|r| r.line).collect::<Vec<_>>(),
+            vec![1, 3, 4]
+        );
+        assert_eq!(
+            rejected[0].error,
+            "column 'value' type conflict, found integer expected float"
+        );
+        assert_eq!(
+            rejected[2].error,
+            "column 'value' type conflict, found tag expected integer"
+        );
+    }
+}
@@ -14,6 +14,8 @@ mod api_type;
 mod encoding;
 pub mod header;
 pub mod http_service;
+mod influxdb;
+mod lenient_write;
 mod metrics;
 mod response;
 mod result_format;
@@ -109,6 +111,24 @@ pub enum Error {
     InvalidUTF8 {
         source: simdutf8::basic::Utf8Error,
     },
+
+    #[snafu(display("Error parsing arrow data: {}", source))]
+    #[error_code(code = 16)]
+    ParseArrowData {
+        source: protocol_parser::Error,
+    },
+
+    #[snafu(display("Invalid write parameter: {}", reason))]
+    #[error_code(code = 17)]
+    InvalidWriteParam {
+        reason: String,
+    },
+
+    #[snafu(display("Invalid query parameter: {}", reason))]
+    #[error_code(code = 18)]
+    InvalidQueryParam {
+        reason: String,
+    },
 }
 
 impl From<tskv::Error> for Error {
@@ -182,7 +202,10 @@ impl From<&Error> for Response {
             | Error::TraceHttp { .. }
             | Error::DecodeRequest { .. }
             | Error::ParseOpentsdbProtocol { .. }
-            | Error::ParseOpentsdbJsonProtocol { .. } => ResponseBuilder::bad_request(&error_resp),
+            | Error::ParseOpentsdbJsonProtocol { .. }
+            | Error::ParseArrowData { .. }
+            | Error::InvalidWriteParam { .. }
+            | Error::InvalidQueryParam { .. } => ResponseBuilder::bad_request(&error_resp),
             _ => ResponseBuilder::internal_server_error(),
         }
     }
@@ -6,7 +6,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use clap::{command, Args, Parser, Subcommand, ValueEnum};
-use config::{Config, OverrideByEnv, VERSION};
+use config::{Config, IndexEngineType, OverrideByEnv, VERSION};
 use memory_pool::GreedyMemoryPool;
 use metrics::init_tskv_metrics_recorder;
 use metrics::metric_register::MetricsRegister;
@@ -43,7 +43,9 @@ Examples:
     # Run the CnosDB:
     cnosdb run
     # Check configuration file:
-    cnosdb check server-config ./config/config.toml"#)]
+    cnosdb check server-config ./config/config.toml
+    # Migrate the series index to the sled engine:
+    cnosdb migrate-index --config ./config/config.toml --to sled"#)]
 struct Cli {
     #[command(subcommand)]
     subcmd: CliCommand,
@@ -60,6 +62,19 @@ enum CliCommand {
         #[command(subcommand)]
         subcmd: CheckCommand,
     },
+    /// Migrate the series index of all vnodes to another index engine, the node must be stopped.
+    MigrateIndex(MigrateIndexArgs),
+}
+
+#[derive(Debug, Args)]
+struct MigrateIndexArgs {
+    /// Path to configuration file.
+    #[arg(long, default_value = "/etc/cnosdb/cnosdb.conf")]
+    config: String,
+
+    /// The index engine to migrate to, one of [radix, sled].
+    #[arg(long)]
+    to: IndexEngineType,
 }
 
 #[derive(Debug, Args)]
@@ -166,6 +181,7 @@ fn main() -> Result<(), std::io::Error> {
                 return Ok(());
             }
         },
+        CliCommand::MigrateIndex(args) => return migrate_index(&args),
     };
 
     let mut config = parse_config(&run_args.config);
@@ -235,6 +251,46 @@ fn parse_config(config_file: &str) -> config::Config {
     config
 }
 
+/// Migrate the index of all vnodes in the storage path of the config to the engine,
+/// the engine in the config should be changed afterwards.
+fn migrate_index(args: &MigrateIndexArgs) -> Result<(), std::io::Error> {
+    let mut config = config::get_config(&args.config).unwrap();
+    config.override_by_env();
+    let opt = tskv::kv_option::StorageOptions::from(&config);
+
+    let data_dir = opt.data_dir();
+    if !data_dir.exists() {
+        println!("No data in {}", data_dir.display());
+        return Ok(());
+    }
+    for database in std::fs::read_dir(&data_dir)? {
+        let database = database?.path();
+        if !database.is_dir() {
+            continue;
+        }
+        for vnode in std::fs::read_dir(&database)? {
+            let vnode = vnode?.path();
+            let is_vnode_dir = vnode
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.parse::<u32>().is_ok());
+            let index_dir = vnode.join(tskv::kv_option::INDEX_PATH);
+            if !is_vnode_dir || !index_dir.is_dir() {
+                continue;
+            }
+            let count = tskv::index::migrate_index_engine(&index_dir, args.to)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+            println!("Migrated {count} keys of index '{}'", index_dir.display());
+        }
+    }
+    println!(
+        "Index migrated to {}, set 'storage.index_engine' to \"{}\" before starting CnosDB.",
+        args.to, args.to
+    );
+
+    Ok(())
+}
+
 fn init_runtime(cores: Option<usize>) -> Result<Runtime, std::io::Error> {
     use tokio::runtime::Builder;
     match cores {
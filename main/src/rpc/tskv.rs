@@ -7,9 +7,8 @@ use futures::{Stream, TryStreamExt};
 use meta::model::MetaRef;
 use metrics::metric_register::MetricsRegister;
 use models::meta_data::VnodeInfo;
-use models::predicate::domain::{self, QueryArgs, QueryExpr};
-use models::record_batch_encode;
-use models::schema::TableColumn;
+use models::predicate::domain::{self, PushedAggregate, QueryArgs, QueryExpr, TimeRange};
+use models::{record_batch_encode, SeriesKey};
 use protos::kv_service::tskv_service_server::TskvService;
 use protos::kv_service::*;
 use protos::models::{PingBody, PingBodyBuilder};
@@ -83,6 +82,66 @@ impl TskvServiceImpl {
             Err(_) => self.bytes_response(FAILED_RESPONSE_CODE, vec![]),
         }
     }
+    async fn admin_fetch_vnode_series_checksum(
+        &self,
+        _tenant: &str,
+        request: &FetchVnodeSeriesChecksumRequest,
+    ) -> Result<tonic::Response<BatchBytesResponse>, tonic::Status> {
+        match self
+            .kv_inst
+            .get_vnode_series_checksum(request.vnode_id)
+            .await
+        {
+            Ok(record) => match record_batch_encode(&record) {
+                Ok(bytes) => self.bytes_response(SUCCESS_RESPONSE_CODE, bytes),
+                Err(_) => self.bytes_response(FAILED_RESPONSE_CODE, vec![]),
+            },
+            Err(_) => self.bytes_response(FAILED_RESPONSE_CODE, vec![]),
+        }
+    }
+
+    async fn admin_fetch_vnode_series_data(
+        &self,
+        _tenant: &str,
+        request: &FetchVnodeSeriesDataRequest,
+    ) -> Result<tonic::Response<BatchBytesResponse>, tonic::Status> {
+        let series_time_ranges = match bincode::deserialize::<Vec<(SeriesKey, TimeRange)>>(
+            &request.series_time_ranges,
+        ) {
+            Ok(v) => v,
+            Err(_) => return self.bytes_response(FAILED_RESPONSE_CODE, vec![]),
+        };
+        match self
+            .kv_inst
+            .get_vnode_series_data(request.vnode_id, &request.table, &series_time_ranges)
+            .await
+        {
+            Ok(record) => match record_batch_encode(&record) {
+                Ok(bytes) => self.bytes_response(SUCCESS_RESPONSE_CODE, bytes),
+                Err(_) => self.bytes_response(FAILED_RESPONSE_CODE, vec![]),
+            },
+            Err(_) => self.bytes_response(FAILED_RESPONSE_CODE, vec![]),
+        }
+    }
+
+    async fn admin_fetch_vnode_series_cardinality(
+        &self,
+        _tenant: &str,
+        request: &FetchVnodeSeriesCardinalityRequest,
+    ) -> Result<tonic::Response<BatchBytesResponse>, tonic::Status> {
+        match self
+            .kv_inst
+            .get_vnode_series_cardinality(request.vnode_id, request.top_n as usize)
+            .await
+        {
+            Ok(record) => match record_batch_encode(&record) {
+                Ok(bytes) => self.bytes_response(SUCCESS_RESPONSE_CODE, bytes),
+                Err(_) => self.bytes_response(FAILED_RESPONSE_CODE, vec![]),
+            },
+            Err(_) => self.bytes_response(FAILED_RESPONSE_CODE, vec![]),
+        }
+    }
+
     async fn admin_add_raft_follower(
         &self,
         tenant: &str,
@@ -145,7 +204,7 @@ impl TskvServiceImpl {
         self,
         args: QueryArgs,
         expr: QueryExpr,
-        aggs: Option<Vec<TableColumn>>,
+        aggs: Option<PushedAggregate>,
         span_ctx: Option<&SpanContext>,
     ) -> TskvResult<SendableTskvRecordBatchStream> {
         let option = QueryOption::new(
@@ -348,6 +407,18 @@ impl TskvService for TskvServiceImpl {
                     self.admin_fetch_vnode_checksum(&inner.tenant, command)
                         .await
                 }
+                admin_fetch_command_request::Command::FetchVnodeSeriesChecksum(command) => {
+                    self.admin_fetch_vnode_series_checksum(&inner.tenant, command)
+                        .await
+                }
+                admin_fetch_command_request::Command::FetchVnodeSeriesData(command) => {
+                    self.admin_fetch_vnode_series_data(&inner.tenant, command)
+                        .await
+                }
+                admin_fetch_command_request::Command::FetchVnodeSeriesCardinality(command) => {
+                    self.admin_fetch_vnode_series_cardinality(&inner.tenant, command)
+                        .await
+                }
             }
         } else {
             self.bytes_response(FAILED_RESPONSE_CODE, vec![])
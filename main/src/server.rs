@@ -7,8 +7,11 @@ use memory_pool::MemoryPoolRef;
 use meta::model::meta_admin::AdminMeta;
 use meta::model::MetaRef;
 use metrics::metric_register::MetricsRegister;
+use models::schema::Precision;
 use models::utils::build_address;
 use query::instance::make_cnosdbms;
+use query::rollup::RollupJob;
+use query::scheduled_task::ScheduledTaskRunner;
 use snafu::{Backtrace, Snafu};
 use spi::server::dbms::DBMSRef;
 use tokio::runtime::Runtime;
@@ -23,6 +26,7 @@ use crate::flight_sql::FlightSqlServiceAdapter;
 use crate::http::http_service::{HttpService, ServerMode};
 use crate::rpc::grpc_service::GrpcService;
 use crate::spi::service::ServiceRef;
+use crate::tcp::connection::TcpWriteOptions;
 use crate::tcp::tcp_service::TcpService;
 use crate::vector::vector_grpc_service::VectorGrpcService;
 
@@ -160,7 +164,7 @@ impl ServiceBuilder {
             server.add_service(Box::new(grpc_service));
         }
 
-        if let Some(tcp_service) = self.create_tcp_if_enabled(coord.clone()) {
+        if let Some(tcp_service) = self.create_tcp_if_enabled(coord.clone(), dbms.clone()) {
             server.add_service(Box::new(tcp_service));
         }
 
@@ -188,7 +192,9 @@ impl ServiceBuilder {
             server.add_service(Box::new(http_service));
         }
 
-        if let Some(flight_sql_service) = self.create_flight_sql_if_enabled(dbms.clone()) {
+        if let Some(flight_sql_service) =
+            self.create_flight_sql_if_enabled(dbms.clone(), coord.clone())
+        {
             server.add_service(Box::new(flight_sql_service));
         }
 
@@ -223,11 +229,13 @@ impl ServiceBuilder {
             server.add_service(Box::new(grpc_service));
         }
 
-        if let Some(flight_sql_service) = self.create_flight_sql_if_enabled(dbms.clone()) {
+        if let Some(flight_sql_service) =
+            self.create_flight_sql_if_enabled(dbms.clone(), coord.clone())
+        {
             server.add_service(Box::new(flight_sql_service));
         }
 
-        if let Some(tcp_service) = self.create_tcp_if_enabled(coord.clone()) {
+        if let Some(tcp_service) = self.create_tcp_if_enabled(coord.clone(), dbms.clone()) {
             server.add_service(Box::new(tcp_service));
         }
 
@@ -285,11 +293,13 @@ impl ServiceBuilder {
 
     async fn create_dbms(&self, coord: CoordinatorRef, memory_pool: MemoryPoolRef) -> DBMSRef {
         let options = tskv::Options::from(&self.config);
-        let dbms = make_cnosdbms(coord, options.clone(), memory_pool)
+        let dbms = make_cnosdbms(coord.clone(), options.clone(), memory_pool)
             .await
             .expect("make dbms");
 
         let dbms: DBMSRef = Arc::new(dbms);
+        RollupJob::start(coord.clone(), dbms.clone());
+        ScheduledTaskRunner::start(coord, dbms.clone());
 
         dbms
     }
@@ -419,16 +429,36 @@ impl ServiceBuilder {
         ))
     }
 
-    fn create_tcp_if_enabled(&self, coord: CoordinatorRef) -> Option<TcpService> {
+    fn create_tcp_if_enabled(&self, coord: CoordinatorRef, dbms: DBMSRef) -> Option<TcpService> {
         let default_tcp_addr = match self.config.service.tcp_listen_port {
             Some(port) => build_default_address(port),
             None => return None,
         };
 
-        Some(TcpService::new(coord, default_tcp_addr))
+        let write_options = TcpWriteOptions {
+            tenant: self.config.service.tcp_tenant.clone(),
+            database: self.config.service.tcp_database.clone(),
+            precision: self
+                .config
+                .service
+                .tcp_precision
+                .as_deref()
+                .and_then(Precision::new),
+        };
+
+        Some(TcpService::new(
+            coord,
+            dbms,
+            default_tcp_addr,
+            write_options,
+        ))
     }
 
-    fn create_flight_sql_if_enabled(&self, dbms: DBMSRef) -> Option<FlightSqlServiceAdapter> {
+    fn create_flight_sql_if_enabled(
+        &self,
+        dbms: DBMSRef,
+        coord: CoordinatorRef,
+    ) -> Option<FlightSqlServiceAdapter> {
         let default_flight_sql_addr = match self.config.service.flight_rpc_listen_port {
             Some(port) => build_default_address(port),
             None => return None,
@@ -451,6 +481,7 @@ impl ServiceBuilder {
 
         Some(FlightSqlServiceAdapter::new(
             dbms,
+            coord,
             addr,
             tls_config,
             self.span_context_extractor.clone(),
@@ -0,0 +1,348 @@
+use coordinator::errors::CoordinatorError;
+use coordinator::service::CoordinatorRef;
+use meta::error::MetaError;
+use models::auth::privilege::{DatabasePrivilege, Privilege, TenantObjectPrivilege};
+use models::auth::user::{User, UserInfo, ROOT, ROOT_PWD};
+use models::oid::Identifier;
+use models::schema::Precision;
+use models::utils::now_timestamp_millis;
+use protocol_parser::open_tsdb::parser::Parser;
+use protocol_parser::Line;
+use snafu::{ResultExt, Snafu};
+use spi::server::dbms::DBMSRef;
+use spi::QueryError;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use trace::debug;
+
+const MILLISECOND_TIMESTAMP: i64 = 1_000_000_000_000;
+/// Max length of a line, the connection is closed if a longer line is received.
+const MAX_LINE_LENGTH: usize = 1024 * 1024;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum TcpError {
+    #[snafu(display("{}", source))]
+    Parse { source: protocol_parser::Error },
+
+    #[snafu(display("illegal argument: {}", reason))]
+    IllegalArgument { reason: String },
+
+    #[snafu(display("authentication failed: {}", source))]
+    Authenticate { source: QueryError },
+
+    #[snafu(display("tenant not found: {}", tenant))]
+    TenantNotFound { tenant: String },
+
+    #[snafu(display("permission denied: user {} has no privilege {}", user, privilege))]
+    PermissionDenied { user: String, privilege: String },
+
+    #[snafu(display("request limited: {}", source))]
+    RequestLimit { source: MetaError },
+
+    #[snafu(display("write failed: {}", source))]
+    Write { source: CoordinatorError },
+
+    #[snafu(display("io error: {}", source))]
+    Io { source: std::io::Error },
+}
+
+pub type TcpResult<T> = Result<T, TcpError>;
+
+/// Where and how the points of a connection are written.
+#[derive(Debug, Clone)]
+pub struct TcpWriteOptions {
+    pub tenant: String,
+    pub database: String,
+    /// Precision of timestamps, detected by the number of digits if it's `None`.
+    pub precision: Option<Precision>,
+}
+
+/// A telnet style OpenTSDB connection.
+///
+/// Besides `put`, the following commands are accepted, points are written with
+/// the options set by the commands before them:
+/// - `auth <user> [<password>]`: authenticate as `user`, `root` is used if not sent.
+/// - `use [tenant=<tenant>] [database=<database>] [precision=<ms|us|ns|auto>]`.
+/// - `version`.
+///
+/// Successful commands get no reply, an error line is replied for every rejected command.
+pub struct TcpConnection {
+    coord: CoordinatorRef,
+    dbms: DBMSRef,
+    options: TcpWriteOptions,
+    user_info: UserInfo,
+    /// User authenticated in the current tenant.
+    user: Option<User>,
+}
+
+impl TcpConnection {
+    pub fn new(coord: CoordinatorRef, dbms: DBMSRef, options: TcpWriteOptions) -> Self {
+        Self {
+            coord,
+            dbms,
+            options,
+            user_info: UserInfo {
+                user: ROOT.to_string(),
+                password: ROOT_PWD.to_string(),
+                private_key: None,
+            },
+            user: None,
+        }
+    }
+
+    pub async fn run<S>(mut self, mut stream: S) -> TcpResult<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut buffer = Vec::with_capacity(1024);
+        loop {
+            if stream.read_buf(&mut buffer).await.context(IoSnafu)? == 0 {
+                break;
+            }
+            let end = match buffer.iter().rposition(|b| *b == b'\n') {
+                Some(pos) => pos + 1,
+                None if buffer.len() > MAX_LINE_LENGTH => {
+                    let reply = format!("error: line is longer than {MAX_LINE_LENGTH} bytes\n");
+                    stream.write_all(reply.as_bytes()).await.context(IoSnafu)?;
+                    break;
+                }
+                None => continue,
+            };
+
+            let reply = self.process(&buffer[..end]).await;
+            buffer.drain(..end);
+            if !reply.is_empty() {
+                stream.write_all(reply.as_bytes()).await.context(IoSnafu)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Process complete lines, returns the error lines to reply.
+    async fn process(&mut self, buf: &[u8]) -> String {
+        let mut reply = String::new();
+        let text = match std::str::from_utf8(buf) {
+            Ok(text) => text,
+            Err(e) => {
+                reply.push_str(&format!("error: invalid utf-8 sequence: {e}\n"));
+                return reply;
+            }
+        };
+
+        let parser = Parser::new(now_timestamp_millis());
+        let mut points = Vec::new();
+        let mut points_len = 0;
+        for raw in text.split('\n') {
+            let raw = raw.trim_end_matches('\r');
+            let command = raw.split_whitespace().next().unwrap_or_default();
+            match command {
+                "" => continue,
+                "put" => match parser.parse_tcp_line(raw).context(ParseSnafu) {
+                    Ok(Some(line)) => {
+                        points.push(line);
+                        points_len += raw.len();
+                    }
+                    Ok(None) => {}
+                    Err(e) => reply.push_str(&format!("{e}\n")),
+                },
+                "auth" | "use" => {
+                    // Points before this command are written with the current options.
+                    if let Err(e) = self
+                        .write_points(std::mem::take(&mut points), points_len)
+                        .await
+                    {
+                        reply.push_str(&format!("put: {e}\n"));
+                    }
+                    points_len = 0;
+
+                    let result = if command == "auth" {
+                        self.auth(raw).await
+                    } else {
+                        self.use_options(raw)
+                    };
+                    if let Err(e) = result {
+                        reply.push_str(&format!("{command}: {e}\n"));
+                    }
+                }
+                "version" => {
+                    reply.push_str(&format!("cnosdb {}\n", config::VERSION.as_str()));
+                }
+                _ => reply.push_str(&format!("unknown command: {command}.  Try `help'.\n")),
+            }
+        }
+
+        if let Err(e) = self.write_points(points, points_len).await {
+            reply.push_str(&format!("put: {e}\n"));
+        }
+
+        reply
+    }
+
+    async fn auth(&mut self, raw: &str) -> TcpResult<()> {
+        let args = raw.split_whitespace().skip(1).collect::<Vec<_>>();
+        if args.is_empty() || args.len() > 2 {
+            return Err(TcpError::IllegalArgument {
+                reason: "usage: auth <user> [<password>]".to_string(),
+            });
+        }
+
+        self.user_info = UserInfo {
+            user: args[0].to_string(),
+            password: args.get(1).unwrap_or(&ROOT_PWD).to_string(),
+            private_key: None,
+        };
+        self.user = None;
+        self.authenticate().await?;
+
+        Ok(())
+    }
+
+    fn use_options(&mut self, raw: &str) -> TcpResult<()> {
+        let mut options = self.options.clone();
+        for arg in raw.split_whitespace().skip(1) {
+            match arg.split_once('=') {
+                Some(("tenant", tenant)) if !tenant.is_empty() => {
+                    options.tenant = tenant.to_string();
+                }
+                Some(("database", database)) if !database.is_empty() => {
+                    options.database = database.to_string();
+                }
+                Some(("precision", "auto")) => options.precision = None,
+                Some(("precision", precision)) => {
+                    options.precision = Some(Precision::new(precision).ok_or_else(|| {
+                        TcpError::IllegalArgument {
+                            reason: format!("invalid precision: {precision}"),
+                        }
+                    })?);
+                }
+                _ => {
+                    return Err(TcpError::IllegalArgument {
+                        reason: format!("invalid option: {arg}"),
+                    })
+                }
+            }
+        }
+
+        if options.tenant != self.options.tenant {
+            // The user must be authenticated in the new tenant.
+            self.user = None;
+        }
+        debug!("OpenTSDB tcp connection use options: {:?}", options);
+        self.options = options;
+
+        Ok(())
+    }
+
+    async fn authenticate(&mut self) -> TcpResult<&User> {
+        if self.user.is_none() {
+            let user = self
+                .dbms
+                .authenticate(&self.user_info, &self.options.tenant)
+                .await
+                .context(AuthenticateSnafu)?;
+            self.user = Some(user);
+        }
+
+        Ok(self.user.as_ref().expect("user has been authenticated"))
+    }
+
+    async fn write_points(
+        &mut self,
+        mut points: Vec<Line<'_>>,
+        points_len: usize,
+    ) -> TcpResult<()> {
+        if points.is_empty() {
+            return Ok(());
+        }
+
+        let tenant = self.options.tenant.clone();
+        let database = self.options.database.clone();
+        let tenant_id = *self
+            .coord
+            .tenant_meta(&tenant)
+            .await
+            .ok_or_else(|| TcpError::TenantNotFound {
+                tenant: tenant.clone(),
+            })?
+            .tenant()
+            .id();
+
+        let privilege = Privilege::TenantObject(
+            TenantObjectPrivilege::Database(DatabasePrivilege::Write, Some(database.clone())),
+            Some(tenant_id),
+        );
+        if !self.authenticate().await?.check_privilege(&privilege) {
+            return Err(TcpError::PermissionDenied {
+                user: self.user_info.user.clone(),
+                privilege: format!("{privilege}"),
+            });
+        }
+
+        let limiter = self
+            .coord
+            .meta_manager()
+            .limiter(&tenant)
+            .await
+            .context(RequestLimitSnafu)?;
+        limiter
+            .check_http_writes()
+            .await
+            .context(RequestLimitSnafu)?;
+        limiter
+            .check_http_data_in(points_len)
+            .await
+            .context(RequestLimitSnafu)?;
+
+        let precision = match self.options.precision {
+            Some(precision) => precision,
+            None => {
+                points
+                    .iter_mut()
+                    .for_each(|line| line.timestamp = normalize_timestamp_ms(line.timestamp));
+                Precision::MS
+            }
+        };
+
+        self.coord
+            .write_lines(&tenant, &database, precision, points, None)
+            .await
+            .context(WriteSnafu)?;
+
+        Ok(())
+    }
+}
+
+/// OpenTSDB accepts timestamps in seconds or milliseconds,
+/// scale the timestamp to milliseconds by the number of digits.
+fn normalize_timestamp_ms(mut timestamp: i64) -> i64 {
+    if timestamp <= 0 {
+        return timestamp;
+    }
+    let mut bit = timestamp / MILLISECOND_TIMESTAMP;
+    while bit > 10 {
+        timestamp /= 10;
+        bit = timestamp / MILLISECOND_TIMESTAMP;
+    }
+    while bit == 0 {
+        timestamp *= 10;
+        bit = timestamp / MILLISECOND_TIMESTAMP;
+    }
+    timestamp
+}
+
+#[cfg(test)]
+mod test {
+    use super::normalize_timestamp_ms;
+
+    #[test]
+    fn test_normalize_timestamp_ms() {
+        assert_eq!(normalize_timestamp_ms(1_700_000_000), 1_700_000_000_000);
+        assert_eq!(normalize_timestamp_ms(1_700_000_000_123), 1_700_000_000_123);
+        assert_eq!(
+            normalize_timestamp_ms(1_700_000_000_123_456),
+            1_700_000_000_123
+        );
+        assert_eq!(normalize_timestamp_ms(0), 0);
+    }
+}
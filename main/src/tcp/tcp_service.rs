@@ -1,31 +1,36 @@
 use async_trait::async_trait;
 use coordinator::service::CoordinatorRef;
-use models::schema::{Precision, DEFAULT_CATALOG, DEFAULT_DATABASE};
-use models::utils::now_timestamp_millis;
-use protocol_parser::open_tsdb::parser::Parser;
-use tokio::io::AsyncReadExt;
+use spi::server::dbms::DBMSRef;
 use tokio::net::TcpListener;
 use tokio::sync::oneshot;
-use trace::info;
+use trace::{debug, info};
 
 use crate::server;
 use crate::server::{Error, ServiceHandle};
 use crate::spi::service::Service;
-
-const MILLISECOND_TIMESTAMP: i64 = 1_000_000_000_000;
+use crate::tcp::connection::{TcpConnection, TcpWriteOptions};
 
 pub struct TcpService {
     handle: Option<ServiceHandle<server::Result<()>>>,
     coord: CoordinatorRef,
+    dbms: DBMSRef,
     addr: String,
+    write_options: TcpWriteOptions,
 }
 
 impl TcpService {
-    pub fn new(coord: CoordinatorRef, addr: String) -> Self {
+    pub fn new(
+        coord: CoordinatorRef,
+        dbms: DBMSRef,
+        addr: String,
+        write_options: TcpWriteOptions,
+    ) -> Self {
         Self {
             handle: None,
             coord,
+            dbms,
             addr,
+            write_options,
         }
     }
 }
@@ -35,60 +40,21 @@ impl Service for TcpService {
     fn start(&mut self) -> server::Result<()> {
         let (shutdown, _rx) = oneshot::channel();
         let coord = self.coord.clone();
+        let dbms = self.dbms.clone();
         let addr = self.addr.clone();
+        let write_options = self.write_options.clone();
         let join_handle = tokio::spawn(async move {
             let listener = TcpListener::bind(&addr).await.unwrap();
             loop {
-                let (mut stream, _) = listener.accept().await.map_err(|e| Error::Common {
+                let (stream, peer_addr) = listener.accept().await.map_err(|e| Error::Common {
                     reason: format!("{:?}", e),
                 })?;
-                let coord = coord.clone();
+                let connection =
+                    TcpConnection::new(coord.clone(), dbms.clone(), write_options.clone());
                 tokio::spawn(async move {
-                    let parser = Parser::new(now_timestamp_millis());
-                    let mut buffer = Vec::with_capacity(1024);
-                    'inner: loop {
-                        if stream
-                            .read_buf(&mut buffer)
-                            .await
-                            .map_err(|e| Error::Common {
-                                reason: format!("{:?}", e),
-                            })?
-                            == 0
-                        {
-                            break 'inner;
-                        }
-                        if let Ok((mut lines, pos)) = parser.parse_tcp_opentsdb(&buffer) {
-                            if lines.is_empty() {
-                                continue;
-                            }
-
-                            lines.iter_mut().for_each(|line| {
-                                let mut bit = line.timestamp / MILLISECOND_TIMESTAMP;
-                                while bit > 10 {
-                                    line.timestamp /= 10;
-                                    bit = line.timestamp / MILLISECOND_TIMESTAMP;
-                                }
-                                while bit == 0 {
-                                    line.timestamp *= 10;
-                                    bit = line.timestamp / MILLISECOND_TIMESTAMP;
-                                }
-                            });
-                            coord
-                                .write_lines(
-                                    DEFAULT_CATALOG,
-                                    DEFAULT_DATABASE,
-                                    Precision::NS,
-                                    lines,
-                                    None,
-                                )
-                                .await
-                                .map_err(|e| Error::Common {
-                                    reason: format!("open opentsdb write point failed: {:?}", e),
-                                })?;
-                            buffer.drain(..pos);
-                        }
+                    if let Err(e) = connection.run(stream).await {
+                        debug!("OpenTSDB tcp connection from {peer_addr} closed: {e}");
                     }
-                    Ok::<(), Error>(())
                 });
             }
         });
@@ -109,12 +75,3 @@ impl Service for TcpService {
         };
     }
 }
-
-#[cfg(test)]
-pub mod test {
-
-    #[tokio::test]
-    async fn test() {
-        println!("{}", 10_u64.pow(10));
-    }
-}
@@ -577,6 +577,30 @@ impl AdminMeta {
         self.client.write::<()>(&req).await
     }
 
+    /// 记录一次登录失败, 连续失败 max_failed_attempts 次后锁定用户到 locked_until(纳秒)
+    pub async fn record_login_failure(
+        &self,
+        name: &str,
+        max_failed_attempts: u32,
+        locked_until: i64,
+    ) -> MetaResult<()> {
+        let req = command::WriteCommand::RecordLoginFailure(
+            self.cluster(),
+            name.to_string(),
+            max_failed_attempts,
+            locked_until,
+        );
+
+        self.client.write::<()>(&req).await
+    }
+
+    /// 清除登录失败次数并解锁用户
+    pub async fn reset_login_status(&self, name: &str) -> MetaResult<()> {
+        let req = command::WriteCommand::ResetLoginStatus(self.cluster(), name.to_string());
+
+        self.client.write::<()>(&req).await
+    }
+
     pub async fn user_with_privileges(
         &self,
         user_name: &str,
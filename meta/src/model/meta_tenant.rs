@@ -6,13 +6,14 @@ use std::sync::Arc;
 
 use client::MetaHttpClient;
 use config::TenantObjectLimiterConfig;
-use models::auth::privilege::{DatabasePrivilege, Privilege};
-use models::auth::role::{CustomTenantRole, SystemTenantRole, TenantRoleIdentifier};
+use models::auth::privilege::{DatabasePrivilege, Privilege, TablePrivilege};
+use models::auth::role::{CustomTenantRole, RowPolicy, SystemTenantRole, TenantRoleIdentifier};
 use models::auth::user::UserDesc;
 use models::meta_data::*;
 use models::oid::{Identifier, Oid};
 use models::schema::{
-    DatabaseSchema, ExternalTableSchema, ResourceInfo, TableSchema, Tenant, TskvTableSchemaRef,
+    DatabaseSchema, ExternalTableSchema, ResourceInfo, RollupRange, TableSchema, Tenant,
+    TskvTableSchemaRef,
 };
 use parking_lot::RwLock;
 use store::command;
@@ -344,6 +345,83 @@ impl TenantMeta {
         self.client.write::<()>(&req).await
     }
 
+    pub async fn grant_table_privilege_to_custom_role(
+        &self,
+        table_privileges: Vec<TablePrivilege>,
+        role_name: &str,
+    ) -> MetaResult<()> {
+        let req = command::WriteCommand::GrantTablePrivileges(
+            self.cluster.clone(),
+            table_privileges,
+            role_name.to_string(),
+            self.tenant_name(),
+        );
+
+        self.client.write::<()>(&req).await
+    }
+
+    pub async fn revoke_table_privilege_from_custom_role(
+        &self,
+        table_privileges: Vec<TablePrivilege>,
+        role_name: &str,
+    ) -> MetaResult<()> {
+        let req = command::WriteCommand::RevokeTablePrivileges(
+            self.cluster.clone(),
+            table_privileges,
+            role_name.to_string(),
+            self.tenant_name(),
+        );
+
+        self.client.write::<()>(&req).await
+    }
+
+    pub async fn create_row_policy(&self, policy: RowPolicy, role_name: &str) -> MetaResult<()> {
+        let req = command::WriteCommand::CreateRowPolicy(
+            self.cluster.clone(),
+            policy,
+            role_name.to_string(),
+            self.tenant_name(),
+        );
+
+        self.client.write::<()>(&req).await
+    }
+
+    pub async fn drop_row_policy(
+        &self,
+        name: &str,
+        database_name: &str,
+        table_name: &str,
+    ) -> MetaResult<bool> {
+        let req = command::WriteCommand::DropRowPolicy(
+            self.cluster.clone(),
+            name.to_string(),
+            database_name.to_string(),
+            table_name.to_string(),
+            self.tenant_name(),
+        );
+
+        self.client.write::<bool>(&req).await
+    }
+
+    /// The row policies of the role on the table from the cache
+    pub fn row_policies(
+        &self,
+        role_name: &str,
+        database_name: &str,
+        table_name: &str,
+    ) -> Vec<RowPolicy> {
+        self.data
+            .read()
+            .roles
+            .get(role_name)
+            .map(|role| {
+                role.table_row_policies(database_name, table_name)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     pub async fn drop_custom_role(&self, role_name: &str) -> MetaResult<bool> {
         let req = command::WriteCommand::DropRole(
             self.cluster.clone(),
@@ -472,6 +550,33 @@ impl TenantMeta {
         self.client.write::<()>(&req).await
     }
 
+    /// Extend the range aggregated into a rollup table, the range never goes back.
+    pub async fn update_rollup_range(
+        &self,
+        db: &str,
+        rollup_table: &str,
+        range: RollupRange,
+    ) -> MetaResult<()> {
+        let req = command::WriteCommand::UpdateRollupRange(
+            self.cluster.clone(),
+            self.tenant_name(),
+            db.to_string(),
+            rollup_table.to_string(),
+            range,
+        );
+
+        self.client.write::<()>(&req).await
+    }
+
+    pub fn get_rollup_range(&self, db: &str, rollup_table: &str) -> Option<RollupRange> {
+        self.data
+            .read()
+            .dbs
+            .get(db)
+            .and_then(|info| info.rollups.get(rollup_table))
+            .copied()
+    }
+
     pub fn get_table_schema(&self, db: &str, table: &str) -> MetaResult<Option<TableSchema>> {
         return Ok(self.data.read().table_schema(db, table));
     }
@@ -816,6 +921,22 @@ impl TenantMeta {
                     db.tables.remove(tab_name);
                 }
             }
+        } else if len == 8
+            && strs[6] == key_path::ROLLUPS
+            && strs[4] == key_path::DBS
+            && strs[2] == key_path::TENANTS
+        {
+            let db_name = strs[5];
+            let rollup_table = strs[7];
+            if let Some(db) = cache.dbs.get_mut(db_name) {
+                if entry.tye == command::ENTRY_LOG_TYPE_SET {
+                    if let Ok(range) = serde_json::from_str::<RollupRange>(&entry.val) {
+                        db.rollups.insert(rollup_table.to_string(), range);
+                    }
+                } else if entry.tye == command::ENTRY_LOG_TYPE_DEL {
+                    db.rollups.remove(rollup_table);
+                }
+            }
         } else if len == 8
             && strs[6] == key_path::BUCKETS
             && strs[4] == key_path::DBS
@@ -2,12 +2,14 @@
 
 use std::collections::{HashMap, HashSet};
 
-use models::auth::privilege::DatabasePrivilege;
-use models::auth::role::{SystemTenantRole, TenantRoleIdentifier};
+use models::auth::privilege::{DatabasePrivilege, TablePrivilege};
+use models::auth::role::{RowPolicy, SystemTenantRole, TenantRoleIdentifier};
 use models::auth::user::{UserDesc, UserOptions};
 use models::meta_data::*;
 use models::oid::Oid;
-use models::schema::{DatabaseSchema, ResourceInfo, TableSchema, Tenant, TenantOptions};
+use models::schema::{
+    DatabaseSchema, ResourceInfo, RollupRange, TableSchema, Tenant, TenantOptions,
+};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
@@ -83,6 +85,8 @@ pub enum WriteCommand {
     UpdateTable(String, String, TableSchema),
     // cluster, tenant, db name, table name
     DropTable(String, String, String, String),
+    // cluster, tenant, db name, rollup table name, aggregated range
+    UpdateRollupRange(String, String, String, String, RollupRange),
 
     // cluster, user_name, user_options, is_admin
     CreateUser(String, UserDesc),
@@ -92,6 +96,10 @@ pub enum WriteCommand {
     RenameUser(String, String, String),
     // cluster, user_name
     DropUser(String, String),
+    // cluster, user_name, max_failed_attempts, locked_until
+    RecordLoginFailure(String, String, u32, i64),
+    // cluster, user_name
+    ResetLoginStatus(String, String),
 
     // cluster, tenant_name, tenant_options
     CreateTenant(String, Tenant),
@@ -125,6 +133,14 @@ pub enum WriteCommand {
     GrantPrivileges(String, Vec<(DatabasePrivilege, String)>, String, String),
     // cluster, privileges, role_name, tenant_name
     RevokePrivileges(String, Vec<(DatabasePrivilege, String)>, String, String),
+    // cluster, table privileges, role_name, tenant_name
+    GrantTablePrivileges(String, Vec<TablePrivilege>, String, String),
+    // cluster, table privileges, role_name, tenant_name
+    RevokeTablePrivileges(String, Vec<TablePrivilege>, String, String),
+    // cluster, row policy, role_name, tenant_name
+    CreateRowPolicy(String, RowPolicy, String, String),
+    // cluster, policy_name, database_name, table_name, tenant_name
+    DropRowPolicy(String, String, String, String, String),
 
     Set {
         key: String,
@@ -6,7 +6,7 @@ use models::oid::{Identifier, Oid};
 use models::schema::{
     DatabaseSchema, TableSchema, Tenant, DEFAULT_CATALOG, DEFAULT_DATABASE, USAGE_SCHEMA,
 };
-use models::sql::{add_member_to_sql, create_table_sqls, role_to_sql, ToDDLSql};
+use models::sql::{add_member_to_sql, create_table_sqls, role_to_sql, row_policy_to_sql, ToDDLSql};
 
 use crate::error::MetaResult;
 use crate::store::key_path::KeyPath;
@@ -156,5 +156,10 @@ pub fn dump_tenant(
         }
     }
 
+    // dump row policy, the tables of the policies are required
+    for (_, role) in roles.iter() {
+        res.append(&mut row_policy_to_sql(role))
+    }
+
     Ok(res)
 }
@@ -12,12 +12,14 @@ use models::oid::Oid;
 // **    /cluster_name/tenant_name/dbs/db_name -> [DatabaseInfo] db相关信息、保留策略等
 // **    /cluster_name/tenant_name/dbs/db_name/buckets/id -> [BucketInfo] bucket相关信息
 // **    /cluster_name/tenant_name/dbs/db_name/schemas/name -> [TskvTableSchema] schema相关信息
+// **    /cluster_name/tenant_name/dbs/db_name/rollups/name -> [RollupRange] rollup表已聚合的时间范围
 
 pub const DBS: &str = "dbs";
 pub const USERS: &str = "users";
 pub const ROLES: &str = "roles";
 pub const BUCKETS: &str = "buckets";
 pub const SCHEMAS: &str = "schemas";
+pub const ROLLUPS: &str = "rollups";
 pub const TENANTS: &str = "tenants";
 pub const MEMBERS: &str = "members";
 pub const LIMITER: &str = "limiter";
@@ -104,6 +106,17 @@ impl KeyPath {
         )
     }
 
+    pub fn tenant_rollups(cluster: &str, tenant: &str, db: &str) -> String {
+        format!("/{}/tenants/{}/dbs/{}/rollups", cluster, tenant, db)
+    }
+
+    pub fn tenant_rollup_name(cluster: &str, tenant: &str, db: &str, name: &str) -> String {
+        format!(
+            "/{}/tenants/{}/dbs/{}/rollups/{}",
+            cluster, tenant, db, name
+        )
+    }
+
     pub fn tenants(cluster: &str) -> String {
         format!("/{}/tenants/", cluster)
     }
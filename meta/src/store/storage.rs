@@ -4,12 +4,14 @@ use std::fs;
 use std::path::Path;
 use std::sync::Arc;
 
-use models::auth::privilege::DatabasePrivilege;
-use models::auth::role::{CustomTenantRole, SystemTenantRole, TenantRoleIdentifier};
-use models::auth::user::{UserDesc, UserOptions};
+use models::auth::privilege::{DatabasePrivilege, TablePrivilege};
+use models::auth::role::{CustomTenantRole, RowPolicy, SystemTenantRole, TenantRoleIdentifier};
+use models::auth::user::{LoginStatus, UserDesc, UserOptions};
 use models::meta_data::*;
 use models::oid::{Identifier, Oid, UuidGenerator};
-use models::schema::{DatabaseSchema, ResourceInfo, TableSchema, Tenant, TenantOptions};
+use models::schema::{
+    DatabaseSchema, ResourceInfo, RollupRange, TableSchema, Tenant, TenantOptions,
+};
 use replication::errors::ReplicationResult;
 use replication::{ApplyContext, ApplyStorage, Request, Response};
 use serde::{Deserialize, Serialize};
@@ -352,11 +354,14 @@ impl StateMachine {
                 .children_data::<BucketInfo>(&KeyPath::tenant_db_buckets(cluster, tenant, key))?;
             let tables =
                 self.children_data::<TableSchema>(&KeyPath::tenant_schemas(cluster, tenant, key))?;
+            let rollups =
+                self.children_data::<RollupRange>(&KeyPath::tenant_rollups(cluster, tenant, key))?;
 
             let info = DatabaseInfo {
                 tables,
                 schema: schema.clone(),
                 buckets: buckets.into_values().collect(),
+                rollups,
             };
 
             meta.dbs.insert(key.clone(), info);
@@ -569,6 +574,15 @@ impl StateMachine {
             WriteCommand::UpdateTable(cluster, tenant, schema) => {
                 response_encode(self.process_update_table(cluster, tenant, schema))
             }
+            WriteCommand::UpdateRollupRange(cluster, tenant, db_name, rollup_table, range) => {
+                response_encode(self.process_update_rollup_range(
+                    cluster,
+                    tenant,
+                    db_name,
+                    rollup_table,
+                    range,
+                ))
+            }
             WriteCommand::CreateBucket(cluster, tenant, db, ts) => {
                 response_encode(self.process_create_bucket(cluster, tenant, db, ts))
             }
@@ -587,6 +601,17 @@ impl StateMachine {
             WriteCommand::DropUser(cluster, name) => {
                 response_encode(self.process_drop_user(cluster, name))
             }
+            WriteCommand::RecordLoginFailure(cluster, name, max_failed_attempts, locked_until) => {
+                response_encode(self.process_record_login_failure(
+                    cluster,
+                    name,
+                    *max_failed_attempts,
+                    *locked_until,
+                ))
+            }
+            WriteCommand::ResetLoginStatus(cluster, name) => {
+                response_encode(self.process_reset_login_status(cluster, name))
+            }
             WriteCommand::CreateTenant(cluster, tenant) => {
                 response_encode(self.process_create_tenant(cluster, tenant))
             }
@@ -650,6 +675,39 @@ impl StateMachine {
                     tenant_name,
                 ))
             }
+            WriteCommand::GrantTablePrivileges(cluster, privileges, role_name, tenant_name) => {
+                response_encode(self.process_grant_table_privileges(
+                    cluster,
+                    privileges,
+                    role_name,
+                    tenant_name,
+                ))
+            }
+            WriteCommand::RevokeTablePrivileges(cluster, privileges, role_name, tenant_name) => {
+                response_encode(self.process_revoke_table_privileges(
+                    cluster,
+                    privileges,
+                    role_name,
+                    tenant_name,
+                ))
+            }
+            WriteCommand::CreateRowPolicy(cluster, policy, role_name, tenant_name) => {
+                response_encode(self.process_create_row_policy(
+                    cluster,
+                    policy,
+                    role_name,
+                    tenant_name,
+                ))
+            }
+            WriteCommand::DropRowPolicy(cluster, name, database, table, tenant_name) => {
+                response_encode(self.process_drop_row_policy(
+                    cluster,
+                    name,
+                    database,
+                    table,
+                    tenant_name,
+                ))
+            }
             WriteCommand::RetainID(cluster, count) => {
                 response_encode(self.process_retain_id(cluster, *count))
             }
@@ -839,6 +897,11 @@ impl StateMachine {
             let _ = self.remove(it);
         }
 
+        let rollups_path = KeyPath::tenant_rollups(cluster, tenant, db_name);
+        for it in self.children_fullpath(&rollups_path)?.iter() {
+            let _ = self.remove(it);
+        }
+
         Ok(())
     }
 
@@ -856,9 +919,43 @@ impl StateMachine {
             });
         }
 
+        // The range of a dropped rollup table is aggregated again if it's created again.
+        let rollup_key = KeyPath::tenant_rollup_name(cluster, tenant, db_name, table_name);
+        if self.contains_key(&rollup_key)? {
+            self.remove(&rollup_key)?;
+        }
+
         self.remove(&key)
     }
 
+    fn process_update_rollup_range(
+        &self,
+        cluster: &str,
+        tenant: &str,
+        db_name: &str,
+        rollup_table: &str,
+        range: &RollupRange,
+    ) -> MetaResult<()> {
+        if !self.contains_key(&KeyPath::tenant_schema_name(
+            cluster,
+            tenant,
+            db_name,
+            rollup_table,
+        ))? {
+            return Err(MetaError::TableNotFound {
+                table: rollup_table.to_owned(),
+            });
+        }
+
+        let key = KeyPath::tenant_rollup_name(cluster, tenant, db_name, rollup_table);
+        let range = match self.get_struct::<RollupRange>(&key)? {
+            Some(current) => current.merge(range),
+            None => *range,
+        };
+
+        self.insert(&key, &value_encode(&range)?)
+    }
+
     fn process_create_db(
         &self,
         cluster: &str,
@@ -1100,12 +1197,7 @@ impl StateMachine {
             let old_options = old_user_desc.options().to_owned();
             let new_options = user_options.clone().merge(old_options);
 
-            let new_user_desc = UserDesc::new(
-                *old_user_desc.id(),
-                user_name.to_string(),
-                new_options,
-                old_user_desc.is_root_admin(),
-            );
+            let new_user_desc = old_user_desc.with_options(new_options);
 
             Ok(self.insert(&key, &value_encode(&new_user_desc)?)?)
         } else {
@@ -1115,6 +1207,39 @@ impl StateMachine {
         }
     }
 
+    fn process_record_login_failure(
+        &self,
+        cluster: &str,
+        user_name: &str,
+        max_failed_attempts: u32,
+        locked_until: i64,
+    ) -> MetaResult<()> {
+        self.update_login_status(cluster, user_name, |status| {
+            status.record_failure(max_failed_attempts, locked_until)
+        })
+    }
+
+    fn process_reset_login_status(&self, cluster: &str, user_name: &str) -> MetaResult<()> {
+        self.update_login_status(cluster, user_name, |status| status.reset())
+    }
+
+    fn update_login_status(
+        &self,
+        cluster: &str,
+        user_name: &str,
+        f: impl FnOnce(&mut LoginStatus),
+    ) -> MetaResult<()> {
+        let key = KeyPath::user(cluster, user_name);
+        let mut user_desc =
+            self.get_struct::<UserDesc>(&key)?
+                .ok_or_else(|| MetaError::UserNotFound {
+                    user: user_name.to_string(),
+                })?;
+        f(user_desc.login_status_mut());
+
+        self.insert(&key, &value_encode(&user_desc)?)
+    }
+
     fn process_rename_user(
         &self,
         _cluster: &str,
@@ -1423,6 +1548,92 @@ impl StateMachine {
         }
     }
 
+    fn process_grant_table_privileges(
+        &self,
+        cluster: &str,
+        privileges: &[TablePrivilege],
+        role_name: &str,
+        tenant_name: &str,
+    ) -> MetaResult<()> {
+        let key = KeyPath::role(cluster, tenant_name, role_name);
+        if let Some(mut role) = self.get_struct::<CustomTenantRole<Oid>>(&key)? {
+            for privilege in privileges {
+                let _ = role.grant_table_privilege(privilege.clone());
+            }
+
+            Ok(self.insert(&key, &value_encode(&role)?)?)
+        } else {
+            Err(MetaError::RoleNotFound {
+                role: role_name.to_string(),
+            })
+        }
+    }
+
+    fn process_revoke_table_privileges(
+        &self,
+        cluster: &str,
+        privileges: &[TablePrivilege],
+        role_name: &str,
+        tenant_name: &str,
+    ) -> MetaResult<()> {
+        let key = KeyPath::role(cluster, tenant_name, role_name);
+        if let Some(mut role) = self.get_struct::<CustomTenantRole<Oid>>(&key)? {
+            for privilege in privileges {
+                let _ = role.revoke_table_privilege(
+                    &privilege.database,
+                    &privilege.table,
+                    &privilege.privilege,
+                );
+            }
+
+            Ok(self.insert(&key, &value_encode(&role)?)?)
+        } else {
+            Err(MetaError::RoleNotFound {
+                role: role_name.to_string(),
+            })
+        }
+    }
+
+    fn process_create_row_policy(
+        &self,
+        cluster: &str,
+        policy: &RowPolicy,
+        role_name: &str,
+        tenant_name: &str,
+    ) -> MetaResult<()> {
+        let key = KeyPath::role(cluster, tenant_name, role_name);
+        if let Some(mut role) = self.get_struct::<CustomTenantRole<Oid>>(&key)? {
+            role.add_row_policy(policy.clone());
+
+            Ok(self.insert(&key, &value_encode(&role)?)?)
+        } else {
+            Err(MetaError::RoleNotFound {
+                role: role_name.to_string(),
+            })
+        }
+    }
+
+    /// Remove the policy from all roles of the tenant, return false if not found
+    fn process_drop_row_policy(
+        &self,
+        cluster: &str,
+        name: &str,
+        database: &str,
+        table: &str,
+        tenant_name: &str,
+    ) -> MetaResult<bool> {
+        let mut found = false;
+        for mut role in self.process_read_roles(cluster, tenant_name)? {
+            if role.remove_row_policy(name, database, table) {
+                let key = KeyPath::role(cluster, tenant_name, role.name());
+                self.insert(&key, &value_encode(&role)?)?;
+                found = true;
+            }
+        }
+
+        Ok(found)
+    }
+
     fn process_limiter_request(
         &self,
         cluster: &str,
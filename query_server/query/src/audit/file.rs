@@ -0,0 +1,193 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use spi::Result;
+use trace::error;
+
+use super::{AuditRecord, AuditSink};
+
+pub const AUDIT_FILE_NAME: &str = "audit.log";
+
+/// Writes the records as json lines to `$dir/audit.log`.
+///
+/// When the file would exceed `max_file_size`, it is renamed to `audit.log.1`, the older
+/// `audit.log.N` to `audit.log.N+1`, and only the latest `max_files` files are kept.
+///
+/// The records are sent to a dedicated thread writing the files, so the statements are
+/// not blocked by the file system; the thread writes the remaining records and exits
+/// when the sink is dropped.
+pub struct FileAuditSink {
+    dir: PathBuf,
+    sender: Option<Sender<Vec<u8>>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl FileAuditSink {
+    pub fn try_new(dir: impl AsRef<Path>, max_file_size: u64, max_files: usize) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let writer = FileWriter::try_new(&dir, max_file_size, max_files)?;
+        let (sender, receiver) = mpsc::channel();
+        let writer = thread::Builder::new()
+            .name("audit-log-writer".to_string())
+            .spawn(move || writer.run(receiver))?;
+
+        Ok(Self {
+            dir,
+            sender: Some(sender),
+            writer: Some(writer),
+        })
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn write(&self, record: &AuditRecord) {
+        let mut line = match serde_json::to_vec(record) {
+            Ok(line) => line,
+            Err(err) => {
+                error!("Failed to serialize audit record: {}", err);
+                return;
+            }
+        };
+        line.push(b'\n');
+
+        if let Some(sender) = self.sender.as_ref() {
+            if sender.send(line).is_err() {
+                error!(
+                    "Failed to write audit record to {}: the writer exited",
+                    self.dir.display()
+                );
+            }
+        }
+    }
+}
+
+impl Drop for FileAuditSink {
+    fn drop(&mut self) {
+        // Close the channel, then wait for the remaining records written.
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+struct FileWriter {
+    dir: PathBuf,
+    max_file_size: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl FileWriter {
+    fn try_new(dir: &Path, max_file_size: u64, max_files: usize) -> std::io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let file = open_append(&dir.join(AUDIT_FILE_NAME))?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            max_file_size,
+            max_files: max_files.max(1),
+            file,
+            size,
+        })
+    }
+
+    fn run(mut self, receiver: Receiver<Vec<u8>>) {
+        while let Ok(line) = receiver.recv() {
+            if let Err(err) = self.write_line(&line) {
+                error!(
+                    "Failed to write audit record to {}: {}",
+                    self.dir.display(),
+                    err
+                );
+            }
+        }
+    }
+
+    fn file_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join(AUDIT_FILE_NAME)
+        } else {
+            self.dir.join(format!("{AUDIT_FILE_NAME}.{index}"))
+        }
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let oldest = self.max_files - 1;
+        if oldest == 0 {
+            fs::remove_file(self.file_path(0))?;
+        } else {
+            let _ = fs::remove_file(self.file_path(oldest));
+            for index in (0..oldest).rev() {
+                let from = self.file_path(index);
+                if from.exists() {
+                    fs::rename(from, self.file_path(index + 1))?;
+                }
+            }
+        }
+
+        self.file = open_append(&self.file_path(0))?;
+        self.size = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> std::io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_file_size {
+            self.rotate()?;
+        }
+        self.file.write_all(line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rotate_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = FileWriter::try_new(dir.path(), 10, 3).unwrap();
+
+        for line in ["0123456\n", "abcdefg\n", "ABCDEFG\n", "hijklmn\n"] {
+            writer.write_line(line.as_bytes()).unwrap();
+        }
+
+        let read = |index: usize| fs::read_to_string(writer.file_path(index)).unwrap();
+        assert_eq!(read(0), "hijklmn\n");
+        assert_eq!(read(1), "ABCDEFG\n");
+        assert_eq!(read(2), "abcdefg\n");
+        assert!(!writer.file_path(3).exists());
+    }
+
+    #[test]
+    fn test_append_to_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let mut writer = FileWriter::try_new(dir.path(), 10, 2).unwrap();
+            writer.write_line(b"0123\n").unwrap();
+        }
+        let mut writer = FileWriter::try_new(dir.path(), 10, 2).unwrap();
+        writer.write_line(b"4567\n").unwrap();
+        writer.write_line(b"89\n").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(writer.file_path(0)).unwrap(),
+            "89\n".to_string()
+        );
+        assert_eq!(
+            fs::read_to_string(writer.file_path(1)).unwrap(),
+            "0123\n4567\n".to_string()
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use std::collections::VecDeque;
+
+use parking_lot::Mutex;
+
+use super::{AuditRecord, AuditSink};
+
+/// Keeps the latest `capacity` records of this node in memory, the records of the other
+/// nodes are not collected.
+pub struct MemoryAuditSink {
+    capacity: usize,
+    records: Mutex<VecDeque<AuditRecord>>,
+}
+
+impl MemoryAuditSink {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: Mutex::new(VecDeque::with_capacity(capacity.min(1024))),
+        }
+    }
+
+    /// Oldest first
+    pub fn records(&self) -> Vec<AuditRecord> {
+        self.records.lock().iter().cloned().collect()
+    }
+}
+
+impl AuditSink for MemoryAuditSink {
+    fn write(&self, record: &AuditRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut records = self.records.lock();
+        while records.len() >= self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record.clone());
+    }
+}
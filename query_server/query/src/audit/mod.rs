@@ -0,0 +1,492 @@
+//! Audit trail of the statements executed by the authenticated users.
+//!
+//! Each audited statement produces an [`AuditRecord`], which is written to all the
+//! [`AuditSink`]s of the [`AuditLogger`]: the rotated local files and the latest records
+//! kept in memory for the table `cluster_schema.audit_log`.
+//!
+//! Both sinks are local to the node executing the statements, `cluster_schema.audit_log`
+//! only shows the records of the node serving the query, and the files of every node
+//! should be collected for the audit trail of the whole cluster.
+//!
+//! The record of a statement returning a stream is written when the stream ends or is
+//! dropped, see [`AuditedRecordBatchStream`].
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use datafusion::common::tree_node::{TreeNode, VisitRecursion};
+use datafusion::logical_expr::{LogicalPlan, TableScan};
+use datafusion::sql::sqlparser::ast::Statement;
+use lazy_static::lazy_static;
+use md5::{Digest, Md5};
+use models::object_reference::ResolvedTable;
+use models::utils::now_timestamp_nanos;
+use regex::Regex;
+use serde::{Serialize, Serializer};
+use spi::query::ast::{AlterTenantOperation, ExtStatement};
+use spi::query::execution::QueryStateMachine;
+pub use spi::query::execution::StatementType;
+use spi::query::logical_planner::{
+    AlterTenantAction, DDLPlan, DMLPlan, GlobalObjectType, Plan, TenantObjectType,
+};
+use spi::Result;
+use trace::warn;
+use tskv::kv_option::QueryOptions;
+
+pub use self::file::FileAuditSink;
+pub use self::memory::MemoryAuditSink;
+pub use self::stream::AuditedRecordBatchStream;
+use crate::data_source::table_source::TableSourceAdapter;
+use crate::extension::logical::plan_node::table_writer::TableWriterPlanNode;
+
+mod file;
+mod memory;
+mod stream;
+
+lazy_static! {
+    /// The values of the options of the users which must not be written to the audit log
+    static ref SECRET_OPTION: Regex = Regex::new(
+        r#"(?i)\b(password|hash_password|rsa_public_key)(\s*=\s*)('(?:[^']|'')*'?|"(?:[^"]|"")*"?|[^\s,;)]+)"#
+    )
+    .unwrap();
+}
+
+pub type AuditLoggerRef = Arc<AuditLogger>;
+pub type AuditSinkRef = Arc<dyn AuditSink>;
+
+/// Returns the class of the statement
+pub fn statement_type(stmt: &ExtStatement) -> StatementType {
+    match stmt {
+        ExtStatement::SqlStatement(stmt) => match stmt.as_ref() {
+            Statement::Insert { .. } | Statement::Update { .. } | Statement::Delete { .. } => {
+                StatementType::Dml
+            }
+            Statement::Kill { .. } => StatementType::Ddl,
+            _ => StatementType::Query,
+        },
+        ExtStatement::Copy(_) => StatementType::Dml,
+
+        ExtStatement::CreateUser(_)
+        | ExtStatement::CreateRole(_)
+        | ExtStatement::AlterUser(_)
+        | ExtStatement::GrantRevoke(_)
+        | ExtStatement::CreatePolicy(_)
+        | ExtStatement::DropPolicy(_) => StatementType::Dcl,
+        ExtStatement::DropGlobalObject(stmt) => match stmt.obj_type {
+            GlobalObjectType::User => StatementType::Dcl,
+            GlobalObjectType::Tenant => StatementType::Ddl,
+        },
+        ExtStatement::DropTenantObject(stmt) => match stmt.obj_type {
+            TenantObjectType::Role => StatementType::Dcl,
+            TenantObjectType::Database | TenantObjectType::Task => StatementType::Ddl,
+        },
+        ExtStatement::AlterTenant(stmt) => match stmt.operation {
+            AlterTenantOperation::AddUser(..)
+            | AlterTenantOperation::SetUser(..)
+            | AlterTenantOperation::RemoveUser(_) => StatementType::Dcl,
+            AlterTenantOperation::Set(_) | AlterTenantOperation::UnSet(_) => StatementType::Ddl,
+        },
+
+        ExtStatement::DescribeTable(_)
+        | ExtStatement::DescribeDatabase(_)
+        | ExtStatement::ShowDatabases()
+        | ExtStatement::ShowTables(_)
+        | ExtStatement::ShowSeries(_)
+        | ExtStatement::ShowTagValues(_)
+        | ExtStatement::ShowStreams(_)
+        | ExtStatement::ShowQueries
+        | ExtStatement::ShowQueryCache
+        | ExtStatement::ShowTasks
+        | ExtStatement::Explain(_) => StatementType::Query,
+
+        ExtStatement::CreateExternalTable(_)
+        | ExtStatement::CreateTable(_)
+        | ExtStatement::CreateStreamTable(_)
+        | ExtStatement::CreateDatabase(_)
+        | ExtStatement::CreateTenant(_)
+        | ExtStatement::CreateStream(_)
+        | ExtStatement::DropStream(_)
+        | ExtStatement::DropDatabaseObject(_)
+        | ExtStatement::ClearQueryCache
+        | ExtStatement::AlterDatabase(_)
+        | ExtStatement::AlterTable(_)
+        | ExtStatement::DropVnode(_)
+        | ExtStatement::CopyVnode(_)
+        | ExtStatement::MoveVnode(_)
+        | ExtStatement::CompactVnode(_)
+        | ExtStatement::ChecksumGroup(_)
+        | ExtStatement::RepairGroup(_)
+        | ExtStatement::RecoverTenant(_)
+        | ExtStatement::RecoverDatabase(_)
+        | ExtStatement::CreateTask(_) => StatementType::Ddl,
+    }
+}
+
+/// Returns the objects touched by the plan, e.g. the tables read or written by a query,
+/// the database created by a ddl.
+pub fn plan_objects(plan: &Plan) -> Vec<String> {
+    let mut objects = vec![];
+    match plan {
+        Plan::Query(plan) => {
+            let _ = plan.df_plan.apply(&mut |plan| {
+                match plan {
+                    LogicalPlan::TableScan(TableScan {
+                        table_name, source, ..
+                    }) => {
+                        let object = match source.as_any().downcast_ref::<TableSourceAdapter>() {
+                            Some(adapter) => {
+                                format!("{}.{}", adapter.database_name(), adapter.table_name())
+                            }
+                            None => table_name.to_string(),
+                        };
+                        objects.push(object);
+                    }
+                    LogicalPlan::Extension(extension) => {
+                        if let Some(writer) = extension
+                            .node
+                            .as_any()
+                            .downcast_ref::<TableWriterPlanNode>()
+                        {
+                            objects.push(writer.target_table_name.clone());
+                        }
+                    }
+                    _ => {}
+                }
+                Ok(VisitRecursion::Continue)
+            });
+        }
+        Plan::DDL(plan) => objects.extend(ddl_plan_objects(plan)),
+        Plan::DML(DMLPlan::DeleteFromTable(plan)) => objects.push(table_object(&plan.table_name)),
+        Plan::SYSTEM(_) => {}
+    }
+
+    objects.sort();
+    objects.dedup();
+    objects
+}
+
+fn table_object(table: &ResolvedTable) -> String {
+    format!("{}.{}", table.database(), table.table())
+}
+
+fn ddl_plan_objects(plan: &DDLPlan) -> Vec<String> {
+    match plan {
+        DDLPlan::DropDatabaseObject(p) => vec![table_object(&p.object_name)],
+        DDLPlan::DropTenantObject(p) => vec![p.name.clone()],
+        DDLPlan::DropGlobalObject(p) => vec![p.name.clone()],
+        DDLPlan::CreateExternalTable(p) => match p.name.schema() {
+            Some(database) => vec![format!("{database}.{}", p.name.table())],
+            None => vec![p.name.table().to_string()],
+        },
+        DDLPlan::CreateTable(p) => vec![table_object(&p.name)],
+        DDLPlan::CreateStreamTable(p) => vec![table_object(&p.name)],
+        DDLPlan::CreateDatabase(p) => vec![p.name.clone()],
+        DDLPlan::CreateTenant(p) => vec![p.name.clone()],
+        DDLPlan::CreateUser(p) => vec![p.name.clone()],
+        DDLPlan::CreateRole(p) => vec![p.name.clone()],
+        DDLPlan::AlterDatabase(p) => vec![p.database_name.clone()],
+        DDLPlan::AlterTable(p) => vec![table_object(&p.table_name)],
+        DDLPlan::AlterTenant(p) => match p.alter_tenant_action {
+            AlterTenantAction::SetOption(_) => vec![p.tenant_name.clone()],
+            _ => vec![],
+        },
+        DDLPlan::AlterUser(p) => vec![p.user_name.clone()],
+        DDLPlan::GrantRevoke(p) => {
+            let mut objects = vec![p.role_name.clone()];
+            objects.extend(p.database_privileges.iter().map(|(_, db)| db.clone()));
+            objects.extend(
+                p.table_privileges
+                    .iter()
+                    .map(|e| format!("{}.{}", e.database, e.table)),
+            );
+            objects
+        }
+        DDLPlan::RecoverDatabase(p) => vec![p.db_name.clone()],
+        DDLPlan::RecoverTenant(p) => vec![p.tenant_name.clone()],
+        DDLPlan::CreateTask(p) => vec![p.task.name.clone()],
+        DDLPlan::CreatePolicy(p) => vec![
+            p.role_name.clone(),
+            format!("{}.{}", p.policy.database, p.policy.table),
+        ],
+        DDLPlan::DropPolicy(p) => vec![format!("{}.{}", p.database_name, p.table_name)],
+        DDLPlan::DropVnode(_)
+        | DDLPlan::CopyVnode(_)
+        | DDLPlan::MoveVnode(_)
+        | DDLPlan::CompactVnode(_)
+        | DDLPlan::ChecksumGroup(_)
+        | DDLPlan::RepairGroup(_) => vec![],
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditStatus {
+    Success,
+    Failure,
+}
+
+impl AuditStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "failure",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditRecord {
+    /// Nanoseconds since the epoch
+    #[serde(serialize_with = "serialize_time")]
+    pub time: i64,
+    pub query_id: String,
+    pub user: String,
+    pub tenant: String,
+    pub database: String,
+    pub client_addr: Option<String>,
+    pub statement_type: StatementType,
+    pub statement: String,
+    /// The md5 of the statement text, used to group the same statements
+    pub digest: String,
+    pub objects: Vec<String>,
+    pub status: AuditStatus,
+    pub error: Option<String>,
+    /// The number of the rows returned
+    pub rows: u64,
+    /// Seconds
+    pub duration: f64,
+}
+
+impl AuditRecord {
+    pub fn new(
+        statement_type: StatementType,
+        query_state_machine: &QueryStateMachine,
+        objects: Vec<String>,
+        rows: u64,
+        error: Option<String>,
+    ) -> Self {
+        let query = &query_state_machine.query;
+        let context = query.context();
+        let statement = redact_secrets(query.content());
+        Self {
+            time: now_timestamp_nanos(),
+            query_id: query_state_machine.query_id.to_string(),
+            user: context.user().desc().name().to_string(),
+            tenant: context.tenant().to_string(),
+            database: context.database().to_string(),
+            client_addr: context.client_addr().map(|e| e.to_string()),
+            statement_type,
+            digest: statement_digest(&statement),
+            statement,
+            objects,
+            status: match &error {
+                Some(_) => AuditStatus::Failure,
+                None => AuditStatus::Success,
+            },
+            error,
+            rows,
+            duration: query_state_machine.duration().as_secs_f64(),
+        }
+    }
+}
+
+fn serialize_time<S: Serializer>(
+    time: &i64,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    let time = Utc
+        .timestamp_nanos(*time)
+        .to_rfc3339_opts(SecondsFormat::Nanos, true);
+    serializer.serialize_str(&time)
+}
+
+/// Replace the values of the password, hash_password and rsa_public_key options with `'***'`.
+fn redact_secrets(statement: &str) -> String {
+    SECRET_OPTION
+        .replace_all(statement, "$1$2'***'")
+        .into_owned()
+}
+
+fn statement_digest(statement: &str) -> String {
+    format!("{:x}", Md5::digest(statement.trim().as_bytes()))
+}
+
+/// Where the audit records go
+pub trait AuditSink: Send + Sync {
+    /// Write the record, failures should not fail the audited statement.
+    fn write(&self, record: &AuditRecord);
+}
+
+/// Writes the records of the configured statement types to all the sinks.
+pub struct AuditLogger {
+    statement_types: HashSet<StatementType>,
+    sinks: Vec<AuditSinkRef>,
+    /// The latest records, shown by `cluster_schema.audit_log`
+    records: Arc<MemoryAuditSink>,
+}
+
+impl AuditLogger {
+    pub fn try_new(options: &QueryOptions) -> Result<Self> {
+        if !options.audit_enabled {
+            return Ok(Self {
+                statement_types: HashSet::new(),
+                sinks: vec![],
+                records: Arc::new(MemoryAuditSink::new(0)),
+            });
+        }
+
+        let mut statement_types = HashSet::new();
+        for e in options.audit_statements.iter() {
+            match e.parse::<StatementType>() {
+                Ok(statement_type) => {
+                    statement_types.insert(statement_type);
+                }
+                Err(err) => warn!("Ignore the audited statement type: {}", err),
+            }
+        }
+
+        let file_sink = FileAuditSink::try_new(
+            &options.audit_path,
+            options.audit_max_file_size,
+            options.audit_max_files,
+        )?;
+        let records = Arc::new(MemoryAuditSink::new(options.audit_table_max_records));
+
+        Ok(Self {
+            statement_types,
+            sinks: vec![Arc::new(file_sink), records.clone()],
+            records,
+        })
+    }
+
+    /// Add another sink, e.g. forwarding the records to an external system.
+    pub fn with_sink(mut self, sink: AuditSinkRef) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    pub fn is_audited(&self, statement_type: StatementType) -> bool {
+        self.statement_types.contains(&statement_type)
+    }
+
+    /// Whether any statement is audited
+    pub fn enabled(&self) -> bool {
+        !self.statement_types.is_empty()
+    }
+
+    pub fn log(&self, record: AuditRecord) {
+        if !self.is_audited(record.statement_type) {
+            return;
+        }
+        for sink in self.sinks.iter() {
+            sink.write(&record);
+        }
+    }
+
+    /// The latest records in memory, oldest first
+    pub fn records(&self) -> Vec<AuditRecord> {
+        self.records.records()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use config::get_config_for_test;
+
+    use super::*;
+
+    fn record(statement_type: StatementType, statement: &str) -> AuditRecord {
+        AuditRecord {
+            time: 0,
+            query_id: "1".to_string(),
+            user: "root".to_string(),
+            tenant: "cnosdb".to_string(),
+            database: "public".to_string(),
+            client_addr: Some("127.0.0.1:12345".to_string()),
+            statement_type,
+            statement: statement.to_string(),
+            digest: statement_digest(statement),
+            objects: vec![],
+            status: AuditStatus::Success,
+            error: None,
+            rows: 0,
+            duration: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_statement_type_from_str() {
+        assert_eq!("DDL".parse::<StatementType>(), Ok(StatementType::Ddl));
+        assert_eq!("query".parse::<StatementType>(), Ok(StatementType::Query));
+        assert!("select".parse::<StatementType>().is_err());
+    }
+
+    #[test]
+    fn test_redact_secrets() {
+        assert_eq!(
+            redact_secrets("CREATE USER u1 WITH PASSWORD='abc''d', COMMENT = 'c1'"),
+            "CREATE USER u1 WITH PASSWORD='***', COMMENT = 'c1'"
+        );
+        assert_eq!(
+            redact_secrets("alter user u1 set hash_password = \"$2b$12$x\""),
+            "alter user u1 set hash_password = '***'"
+        );
+        assert_eq!(
+            redact_secrets("alter user u1 set rsa_public_key='-----BEGIN PUBLIC KEY-----\nMII'"),
+            "alter user u1 set rsa_public_key='***'"
+        );
+        assert_eq!(
+            redact_secrets("alter user u1 set password=abc; select 1"),
+            "alter user u1 set password='***'; select 1"
+        );
+        // Unterminated
+        assert_eq!(
+            redact_secrets("create user u1 with password='abc"),
+            "create user u1 with password='***'"
+        );
+        assert_eq!(
+            redact_secrets("select password_expire_at, must_change_password from users"),
+            "select password_expire_at, must_change_password from users"
+        );
+    }
+
+    #[test]
+    fn test_logger_filter_statement_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = get_config_for_test();
+        let mut options = QueryOptions::from(&config);
+        options.audit_enabled = true;
+        options.audit_path = dir.path().to_string_lossy().to_string();
+        options.audit_statements = vec!["ddl".to_string(), "DCL".to_string()];
+
+        let logger = AuditLogger::try_new(&options).unwrap();
+        logger.log(record(StatementType::Ddl, "drop database db1"));
+        logger.log(record(StatementType::Query, "select * from t1"));
+        logger.log(record(StatementType::Dcl, "create user u1"));
+
+        let statements = logger
+            .records()
+            .into_iter()
+            .map(|e| e.statement)
+            .collect::<Vec<_>>();
+        assert_eq!(statements, vec!["drop database db1", "create user u1"]);
+
+        // Wait for the records written to the file.
+        drop(logger);
+        let content = std::fs::read_to_string(dir.path().join(file::AUDIT_FILE_NAME)).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(content.contains("\"statement_type\":\"dcl\""));
+        assert!(content.contains("\"time\":\"1970-01-01T00:00:00.000000000Z\""));
+    }
+
+    #[test]
+    fn test_disabled_logger() {
+        let config = get_config_for_test();
+        let options = QueryOptions::from(&config);
+        let logger = AuditLogger::try_new(&options).unwrap();
+        assert!(!logger.enabled());
+        logger.log(record(StatementType::Ddl, "drop database db1"));
+        assert!(logger.records().is_empty());
+    }
+}
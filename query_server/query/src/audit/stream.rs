@@ -0,0 +1,215 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::Result as DFResult;
+use datafusion::physical_plan::{RecordBatchStream, SendableRecordBatchStream};
+use futures::{Stream, StreamExt};
+use spi::query::execution::QueryStateMachine;
+
+use super::{AuditLoggerRef, AuditRecord, StatementType};
+
+const DROPPED_ERROR: &str = "the result stream was dropped before the end";
+
+/// Writes the audit record of a statement when its result stream ends or is dropped,
+/// so the record carries the rows returned, the error raised while streaming and the
+/// duration of the whole execution.
+pub struct AuditedRecordBatchStream {
+    inner: SendableRecordBatchStream,
+    audit_logger: AuditLoggerRef,
+    statement_type: StatementType,
+    query_state_machine: Arc<QueryStateMachine>,
+    objects: Vec<String>,
+    rows: u64,
+    error: Option<String>,
+    logged: bool,
+}
+
+impl AuditedRecordBatchStream {
+    pub fn new(
+        inner: SendableRecordBatchStream,
+        audit_logger: AuditLoggerRef,
+        statement_type: StatementType,
+        query_state_machine: Arc<QueryStateMachine>,
+        objects: Vec<String>,
+    ) -> Self {
+        Self {
+            inner,
+            audit_logger,
+            statement_type,
+            query_state_machine,
+            objects,
+            rows: 0,
+            error: None,
+            logged: false,
+        }
+    }
+
+    fn log(&mut self) {
+        if self.logged {
+            return;
+        }
+        self.logged = true;
+        self.audit_logger.log(AuditRecord::new(
+            self.statement_type,
+            &self.query_state_machine,
+            std::mem::take(&mut self.objects),
+            self.rows,
+            self.error.take(),
+        ));
+    }
+}
+
+impl RecordBatchStream for AuditedRecordBatchStream {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
+
+impl Stream for AuditedRecordBatchStream {
+    type Item = DFResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = self.inner.poll_next_unpin(cx);
+        match &poll {
+            Poll::Ready(Some(Ok(batch))) => self.rows += batch.num_rows() as u64,
+            Poll::Ready(Some(Err(err))) => {
+                if self.error.is_none() {
+                    self.error = Some(err.to_string());
+                }
+            }
+            Poll::Ready(None) => self.log(),
+            Poll::Pending => {}
+        }
+        poll
+    }
+}
+
+impl Drop for AuditedRecordBatchStream {
+    fn drop(&mut self) {
+        if !self.logged && self.error.is_none() {
+            self.error = Some(DROPPED_ERROR.to_string());
+        }
+        self.log();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use config::get_config_for_test;
+    use datafusion::arrow::array::Int64Array;
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::arrow::record_batch::RecordBatch;
+    use datafusion::error::DataFusionError;
+    use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+    use futures::StreamExt;
+    use models::auth::user::{User, UserDesc, UserOptions};
+    use spi::query::execution::QueryStateMachine;
+    use spi::service::protocol::{ContextBuilder, Query};
+    use tskv::kv_option::QueryOptions;
+
+    use super::AuditedRecordBatchStream;
+    use crate::audit::{AuditLogger, AuditLoggerRef, AuditStatus, StatementType};
+
+    fn audit_logger(dir: &std::path::Path) -> AuditLoggerRef {
+        let config = get_config_for_test();
+        let mut options = QueryOptions::from(&config);
+        options.audit_enabled = true;
+        options.audit_path = dir.to_string_lossy().to_string();
+        options.audit_statements = vec!["query".to_string()];
+        Arc::new(AuditLogger::try_new(&options).unwrap())
+    }
+
+    fn audited_stream(
+        audit_logger: AuditLoggerRef,
+        results: Vec<Result<RecordBatch, DataFusionError>>,
+    ) -> AuditedRecordBatchStream {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let inner = RecordBatchStreamAdapter::new(schema, futures::stream::iter(results));
+        let desc = UserDesc::new(0_u128, "user".to_string(), UserOptions::default(), true);
+        let query = Query::new(
+            ContextBuilder::new(User::new(desc, Default::default(), None)).build(),
+            "select a from t".to_string(),
+        );
+        AuditedRecordBatchStream::new(
+            Box::pin(inner),
+            audit_logger,
+            StatementType::Query,
+            Arc::new(QueryStateMachine::test(query, None)),
+            vec!["public.t".to_string()],
+        )
+    }
+
+    fn batch(num_rows: i64) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Int64Array::from_iter_values(0..num_rows))],
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_audit_at_the_end_of_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit_logger = audit_logger(dir.path());
+
+        let mut stream = audited_stream(audit_logger.clone(), vec![Ok(batch(2)), Ok(batch(3))]);
+        while let Some(batch) = stream.next().await {
+            batch.unwrap();
+            // Not logged before the end.
+            assert!(audit_logger.records().is_empty());
+        }
+        let records = audit_logger.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].rows, 5);
+        assert_eq!(records[0].status, AuditStatus::Success);
+        assert_eq!(records[0].objects, vec!["public.t".to_string()]);
+        // Logged once.
+        drop(stream);
+        assert_eq!(audit_logger.records().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_audit_error_of_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit_logger = audit_logger(dir.path());
+
+        let mut stream = audited_stream(
+            audit_logger.clone(),
+            vec![
+                Ok(batch(2)),
+                Err(DataFusionError::Execution("read failed".to_string())),
+            ],
+        );
+        assert!(stream.next().await.unwrap().is_ok());
+        assert!(stream.next().await.unwrap().is_err());
+        drop(stream);
+
+        let records = audit_logger.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].rows, 2);
+        assert_eq!(records[0].status, AuditStatus::Failure);
+        assert!(records[0].error.as_ref().unwrap().contains("read failed"));
+    }
+
+    #[tokio::test]
+    async fn test_audit_dropped_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit_logger = audit_logger(dir.path());
+
+        let mut stream = audited_stream(audit_logger.clone(), vec![Ok(batch(2)), Ok(batch(3))]);
+        assert!(stream.next().await.unwrap().is_ok());
+        drop(stream);
+
+        let records = audit_logger.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].rows, 2);
+        assert_eq!(records[0].status, AuditStatus::Failure);
+        assert_eq!(records[0].error.as_deref(), Some(super::DROPPED_ERROR));
+    }
+}
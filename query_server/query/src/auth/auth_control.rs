@@ -1,20 +1,55 @@
+use std::time::Duration;
+
 use meta::model::MetaRef;
 use models::auth::user::{AuthType, User, UserInfo};
 use models::auth::AuthError;
 use models::oid::{Identifier, Oid};
+use models::utils::now_timestamp_nanos;
 use spi::query::auth::AccessControl;
 use trace::warn;
 
 pub type Result<T> = std::result::Result<T, AuthError>;
 
+/// Lock the user for `lock_time` after `max_failed_attempts` consecutive failed logins,
+/// 0 means never lock.
+#[derive(Debug, Clone, Default)]
+pub struct LoginPolicy {
+    pub max_failed_attempts: u32,
+    pub lock_time: Duration,
+}
+
 #[derive(Clone)]
 pub struct AccessControlImpl {
     inner: AccessControlNoCheck,
+    login_policy: LoginPolicy,
 }
 
 impl AccessControlImpl {
-    pub fn new(inner: AccessControlNoCheck) -> Self {
-        Self { inner }
+    pub fn new(inner: AccessControlNoCheck, login_policy: LoginPolicy) -> Self {
+        Self {
+            inner,
+            login_policy,
+        }
+    }
+
+    async fn record_login_failure(&self, user_name: &str, now: i64) {
+        if self.login_policy.max_failed_attempts == 0 {
+            return;
+        }
+
+        let locked_until = now.saturating_add(self.login_policy.lock_time.as_nanos() as i64);
+        if let Err(err) = self
+            .inner
+            .meta_manager
+            .record_login_failure(
+                user_name,
+                self.login_policy.max_failed_attempts,
+                locked_until,
+            )
+            .await
+        {
+            warn!("record login failure of user {}, error: {}", user_name, err);
+        }
     }
 }
 
@@ -31,16 +66,71 @@ impl AccessControl for AccessControlImpl {
                 err: "username or password invalid".to_owned(),
             })?;
 
-        let user_options = user.desc().options();
+        let now = now_timestamp_nanos();
+        let user_desc = user.desc();
+        if user_desc.login_status().is_locked(now) {
+            return Err(AuthError::AccessDenied {
+                user_name: user_info.user.clone(),
+                auth_type: "xxx".to_owned(),
+                err: "the user is locked due to too many failed login attempts".to_owned(),
+            });
+        }
+
+        let auth_type = AuthType::from(user_desc.options());
         // access check
-        AuthType::from(user_options)
-            .access_check(user_info)
-            .map_err(|_err| AuthError::AccessDenied {
+        if auth_type.access_check(user_info).is_err() {
+            self.record_login_failure(&user_info.user, now).await;
+            return Err(AuthError::AccessDenied {
                 user_name: user_info.user.clone(),
                 auth_type: "xxx".to_owned(),
                 err: "username or password invalid".to_owned(),
+            });
+        }
+
+        if !user_desc.login_status().is_clean() {
+            if let Err(err) = self
+                .inner
+                .meta_manager
+                .reset_login_status(&user_info.user)
+                .await
+            {
+                warn!(
+                    "reset login status of user {}, error: {}",
+                    user_info.user, err
+                );
+            }
+        }
+
+        // 使用 RSA 认证的用户不受密码有效期的限制
+        let password_change_required = matches!(auth_type, AuthType::HashPassword(_))
+            && user_desc.password_change_required(now);
+        if password_change_required {
+            return Ok(user.with_password_change_required());
+        }
+
+        Ok(user)
+    }
+
+    async fn token_access_check(&self, user_name: &str, tenant_name: &str) -> Result<User> {
+        let user = self
+            .inner
+            .token_access_check(user_name, tenant_name)
+            .await
+            .map_err(|_err| AuthError::AccessDenied {
+                user_name: user_name.to_owned(),
+                auth_type: "bearer token".to_owned(),
+                err: "user not found".to_owned(),
             })?;
 
+        // 令牌由外部签发，不记录登录失败，也不受密码有效期的限制
+        if user.desc().login_status().is_locked(now_timestamp_nanos()) {
+            return Err(AuthError::AccessDenied {
+                user_name: user_name.to_owned(),
+                auth_type: "bearer token".to_owned(),
+                err: "the user is locked due to too many failed login attempts".to_owned(),
+            });
+        }
+
         Ok(user)
     }
 
@@ -66,7 +156,10 @@ impl AccessControlNoCheck {
 #[async_trait::async_trait]
 impl AccessControl for AccessControlNoCheck {
     async fn access_check(&self, user_info: &UserInfo, tenant_name: &str) -> Result<User> {
-        let user_name = user_info.user.as_str();
+        self.token_access_check(&user_info.user, tenant_name).await
+    }
+
+    async fn token_access_check(&self, user_name: &str, tenant_name: &str) -> Result<User> {
         // only get user info with privileges
         self.meta_manager
             .user_with_privileges(user_name, tenant_name)
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use config::JwtConfig;
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use models::auth::AuthError;
+use serde_json::Value;
+
+use super::auth_control::Result;
+
+/// The identity carried by a verified bearer token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JwtIdentity {
+    pub user: String,
+    pub tenant: Option<String>,
+}
+
+struct VerifyingKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+/// Verifies bearer tokens (JWT) issued by an external OIDC provider
+/// against the public keys of a local JWKS file.
+///
+/// Only RS256 and ES256 signed tokens are accepted.
+pub struct JwtVerifier {
+    keys: Vec<VerifyingKey>,
+    config: JwtConfig,
+}
+
+impl JwtVerifier {
+    pub fn try_new(config: &JwtConfig) -> Result<Self> {
+        let jwks = std::fs::read_to_string(&config.jwks_path).map_err(|err| AuthError::Jwks {
+            reason: format!("read {}: {}", config.jwks_path, err),
+        })?;
+
+        Self::from_jwks(&jwks, config)
+    }
+
+    pub fn from_jwks(jwks: &str, config: &JwtConfig) -> Result<Self> {
+        let jwk_set: JwkSet = serde_json::from_str(jwks).map_err(|err| AuthError::Jwks {
+            reason: err.to_string(),
+        })?;
+
+        let mut keys = Vec::with_capacity(jwk_set.keys.len());
+        for jwk in &jwk_set.keys {
+            let algorithm = jwk_algorithm(jwk)?;
+            let key = DecodingKey::from_jwk(jwk).map_err(|err| AuthError::Jwks {
+                reason: err.to_string(),
+            })?;
+            keys.push(VerifyingKey {
+                kid: jwk.common.key_id.clone(),
+                algorithm,
+                key,
+            });
+        }
+
+        if keys.is_empty() {
+            return Err(AuthError::Jwks {
+                reason: "no keys found".to_string(),
+            });
+        }
+
+        Ok(Self {
+            keys,
+            config: config.clone(),
+        })
+    }
+
+    pub fn verify(&self, token: &str) -> Result<JwtIdentity> {
+        let header = decode_header(token).map_err(invalid_token)?;
+
+        let key = self
+            .keys
+            .iter()
+            .find(|k| match header.kid.as_ref() {
+                Some(kid) => k.kid.as_ref() == Some(kid),
+                None => k.algorithm == header.alg,
+            })
+            .ok_or_else(|| AuthError::InvalidToken {
+                reason: "no matching key found".to_string(),
+            })?;
+
+        if key.algorithm != header.alg {
+            return Err(AuthError::InvalidToken {
+                reason: format!("unexpected algorithm {:?}", header.alg),
+            });
+        }
+
+        let mut validation = Validation::new(key.algorithm);
+        validation.leeway = self.config.leeway.as_secs();
+        validation.validate_nbf = true;
+        validation.set_required_spec_claims(&["exp"]);
+        if !self.config.issuer.is_empty() {
+            validation.set_issuer(&[&self.config.issuer]);
+        }
+        if self.config.audience.is_empty() {
+            validation.validate_aud = false;
+        } else {
+            validation.set_audience(&[&self.config.audience]);
+        }
+
+        let claims = decode::<HashMap<String, Value>>(token, &key.key, &validation)
+            .map_err(invalid_token)?
+            .claims;
+
+        let user = claims
+            .get(&self.config.user_claim)
+            .and_then(Value::as_str)
+            .filter(|u| !u.is_empty())
+            .ok_or_else(|| AuthError::InvalidToken {
+                reason: format!("missing claim '{}'", self.config.user_claim),
+            })?
+            .to_string();
+        let tenant = claims
+            .get(&self.config.tenant_claim)
+            .and_then(Value::as_str)
+            .map(ToString::to_string);
+
+        Ok(JwtIdentity { user, tenant })
+    }
+}
+
+fn jwk_algorithm(jwk: &Jwk) -> Result<Algorithm> {
+    let algorithm = match (&jwk.common.key_algorithm, &jwk.algorithm) {
+        (Some(KeyAlgorithm::RS256), AlgorithmParameters::RSA(_)) => Algorithm::RS256,
+        (Some(KeyAlgorithm::ES256), AlgorithmParameters::EllipticCurve(p))
+            if p.curve == EllipticCurve::P256 =>
+        {
+            Algorithm::ES256
+        }
+        (None, AlgorithmParameters::RSA(_)) => Algorithm::RS256,
+        (None, AlgorithmParameters::EllipticCurve(p)) if p.curve == EllipticCurve::P256 => {
+            Algorithm::ES256
+        }
+        _ => {
+            return Err(AuthError::Jwks {
+                reason: format!(
+                    "unsupported key {}, only RS256 and ES256 are supported",
+                    jwk.common.key_id.as_deref().unwrap_or_default()
+                ),
+            })
+        }
+    };
+
+    Ok(algorithm)
+}
+
+fn invalid_token(err: jsonwebtoken::errors::Error) -> AuthError {
+    AuthError::InvalidToken {
+        reason: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    use super::*;
+
+    const JWKS: &str = include_str!("../../../../config/jwt/jwks.json");
+    const RSA_KEY: &str = include_str!("../../../../config/jwt/rsa.key");
+    const EC_KEY: &str = include_str!("../../../../config/jwt/ec.key");
+
+    fn config() -> JwtConfig {
+        JwtConfig {
+            jwks_path: String::new(),
+            issuer: "https://idp.example.com".to_string(),
+            audience: "cnosdb".to_string(),
+            user_claim: "sub".to_string(),
+            tenant_claim: "tenant".to_string(),
+            leeway: Duration::from_secs(0),
+        }
+    }
+
+    fn now() -> i64 {
+        chrono::Utc::now().timestamp()
+    }
+
+    fn sign_rs256(claims: &Value) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some("rsa-key".to_string());
+        let key = EncodingKey::from_rsa_pem(RSA_KEY.as_bytes()).unwrap();
+        encode(&header, claims, &key).unwrap()
+    }
+
+    fn sign_es256(claims: &Value) -> String {
+        let header = Header::new(Algorithm::ES256);
+        let key = EncodingKey::from_ec_pem(EC_KEY.as_bytes()).unwrap();
+        encode(&header, claims, &key).unwrap()
+    }
+
+    #[test]
+    fn test_verify() {
+        let verifier = JwtVerifier::from_jwks(JWKS, &config()).unwrap();
+
+        let token = sign_rs256(&json!({
+            "sub": "alice",
+            "tenant": "cnosdb",
+            "iss": "https://idp.example.com",
+            "aud": "cnosdb",
+            "exp": now() + 60,
+        }));
+        assert_eq!(
+            verifier.verify(&token).unwrap(),
+            JwtIdentity {
+                user: "alice".to_string(),
+                tenant: Some("cnosdb".to_string()),
+            }
+        );
+
+        let token = sign_es256(&json!({
+            "sub": "bob",
+            "iss": "https://idp.example.com",
+            "aud": ["cnosdb", "other"],
+            "exp": now() + 60,
+        }));
+        assert_eq!(
+            verifier.verify(&token).unwrap(),
+            JwtIdentity {
+                user: "bob".to_string(),
+                tenant: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_rejected() {
+        let verifier = JwtVerifier::from_jwks(JWKS, &config()).unwrap();
+        let claims = json!({
+            "sub": "alice",
+            "iss": "https://idp.example.com",
+            "aud": "cnosdb",
+            "exp": now() + 60,
+        });
+
+        // expired
+        let mut expired = claims.clone();
+        expired["exp"] = json!(now() - 60);
+        assert!(matches!(
+            verifier.verify(&sign_rs256(&expired)),
+            Err(AuthError::InvalidToken { .. })
+        ));
+
+        // not yet valid
+        let mut not_before = claims.clone();
+        not_before["nbf"] = json!(now() + 60);
+        assert!(matches!(
+            verifier.verify(&sign_rs256(&not_before)),
+            Err(AuthError::InvalidToken { .. })
+        ));
+
+        // no exp
+        let mut no_exp = claims.clone();
+        no_exp.as_object_mut().unwrap().remove("exp");
+        assert!(verifier.verify(&sign_rs256(&no_exp)).is_err());
+
+        // wrong issuer
+        let mut wrong_iss = claims.clone();
+        wrong_iss["iss"] = json!("https://evil.example.com");
+        assert!(verifier.verify(&sign_rs256(&wrong_iss)).is_err());
+
+        // wrong audience
+        let mut wrong_aud = claims.clone();
+        wrong_aud["aud"] = json!("other");
+        assert!(verifier.verify(&sign_rs256(&wrong_aud)).is_err());
+
+        // missing user claim
+        let mut no_user = claims.clone();
+        no_user.as_object_mut().unwrap().remove("sub");
+        assert!(verifier.verify(&sign_rs256(&no_user)).is_err());
+
+        // signed by the ec key but claims to be the rsa key
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some("rsa-key".to_string());
+        let key = EncodingKey::from_ec_pem(EC_KEY.as_bytes()).unwrap();
+        let token = encode(&header, &claims, &key).unwrap();
+        assert!(verifier.verify(&token).is_err());
+
+        // tampered payload
+        let token = sign_rs256(&claims);
+        let mut parts = token.split('.').collect::<Vec<_>>();
+        let forged = sign_rs256(&json!({
+            "sub": "root",
+            "iss": "https://idp.example.com",
+            "aud": "cnosdb",
+            "exp": now() + 60,
+        }));
+        parts[1] = forged.split('.').nth(1).unwrap();
+        assert!(verifier.verify(&parts.join(".")).is_err());
+
+        assert!(verifier.verify("not a token").is_err());
+    }
+
+    #[test]
+    fn test_invalid_jwks() {
+        assert!(matches!(
+            JwtVerifier::from_jwks("{}", &config()),
+            Err(AuthError::Jwks { .. })
+        ));
+        assert!(matches!(
+            JwtVerifier::from_jwks(r#"{"keys": []}"#, &config()),
+            Err(AuthError::Jwks { .. })
+        ));
+
+        let mut config = config();
+        config.jwks_path = "/not/exists/jwks.json".to_string();
+        assert!(matches!(
+            JwtVerifier::try_new(&config),
+            Err(AuthError::Jwks { .. })
+        ));
+    }
+}
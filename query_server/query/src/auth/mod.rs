@@ -1 +1,2 @@
 pub mod auth_control;
+pub mod jwt;
@@ -21,7 +21,9 @@ use datafusion::physical_plan::{project_schema, ExecutionPlan};
 use datafusion::prelude::Column;
 use meta::error::MetaError;
 use meta::model::MetaClientRef;
-use models::predicate::domain::{Predicate, PredicateRef, PushedAggregateFunction};
+use models::predicate::domain::{
+    Predicate, PredicateRef, PushedAggregate, PushedAggregateFunction,
+};
 use models::schema::{TskvTableSchema, TskvTableSchemaRef, TIME_FIELD_NAME};
 use trace::debug;
 
@@ -41,7 +43,7 @@ use crate::extension::physical::plan_node::update_tag::UpdateTagExec;
 pub struct ClusterTable {
     coord: CoordinatorRef,
     split_manager: SplitManagerRef,
-    _meta: MetaClientRef,
+    meta: MetaClientRef,
     schema: TskvTableSchemaRef,
 }
 
@@ -163,7 +165,10 @@ impl ClusterTable {
             self.coord.clone(),
             proj_schema,
             self.schema.clone(),
-            pushed_aggs,
+            PushedAggregate {
+                functions: pushed_aggs,
+                window: None,
+            },
             filter,
             splits,
         )))
@@ -220,6 +225,48 @@ impl ClusterTable {
         )))
     }
 
+    /// Scan the partial aggregations of each series (and window), see [`PushedAggregate`].
+    pub async fn create_aggregate_scan_physical_plan(
+        &self,
+        ctx: &SessionState,
+        schema: SchemaRef,
+        filters: &[Expr],
+        aggregate: PushedAggregate,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let filter = conjunction(filters.iter().cloned());
+        let predicate = Arc::new(
+            Predicate::push_down_filter(
+                filter,
+                &self.schema.to_df_schema()?,
+                &self.schema.to_arrow_schema(),
+                None,
+            )
+            .map_err(|e| DataFusionError::External(Box::new(e)))?,
+        );
+
+        let table_layout = TableLayoutHandle {
+            table: self.schema.clone(),
+            predicate: predicate.clone(),
+        };
+        let splits = self
+            .split_manager
+            .splits(ctx, table_layout)
+            .await
+            .map_err(|err| DataFusionError::External(Box::new(err)))?;
+        if splits.is_empty() {
+            return Ok(Arc::new(EmptyExec::new(false, schema)));
+        }
+
+        Ok(Arc::new(AggregateFilterTskvExec::new(
+            self.coord.clone(),
+            schema,
+            self.schema.clone(),
+            aggregate,
+            predicate,
+            splits,
+        )))
+    }
+
     pub fn new(
         coord: CoordinatorRef,
         split_manager: SplitManagerRef,
@@ -229,7 +276,7 @@ impl ClusterTable {
         ClusterTable {
             coord,
             split_manager,
-            _meta: meta,
+            meta,
             schema,
         }
     }
@@ -238,6 +285,20 @@ impl ClusterTable {
         self.schema.clone()
     }
 
+    pub fn meta(&self) -> &MetaClientRef {
+        &self.meta
+    }
+
+    /// Another table of the same tenant.
+    pub fn with_table_schema(&self, schema: TskvTableSchemaRef) -> Self {
+        Self {
+            coord: self.coord.clone(),
+            split_manager: self.split_manager.clone(),
+            meta: self.meta.clone(),
+            schema,
+        }
+    }
+
     // Check and return the projected schema
     fn project_schema(&self, projection: Option<&Vec<usize>>) -> Result<SchemaRef> {
         valid_project(&self.schema, projection)
@@ -1,4 +1,5 @@
 use std::any::Any;
+use std::collections::BTreeSet;
 use std::fmt::Display;
 use std::sync::Arc;
 use std::write;
@@ -32,6 +33,8 @@ pub struct TableSourceAdapter {
     database_name: String,
     table_name: String,
     table_handle: TableHandle,
+    // Only a part of the columns of the table are visible if set
+    projected_schema: Option<SchemaRef>,
 
     plan: LogicalPlan,
 }
@@ -85,10 +88,50 @@ impl TableSourceAdapter {
             database_name,
             table_name,
             table_handle,
+            projected_schema: None,
             plan,
         })
     }
 
+    /// Only the rows matching `predicate` are visible
+    pub fn filter(mut self, predicate: Expr) -> Result<Self, DataFusionError> {
+        self.plan = LogicalPlanBuilder::from(self.plan)
+            .filter(predicate)?
+            .build()?;
+
+        debug!(
+            "Filtered table source logical plan node of {}:\n{}",
+            self.table_name,
+            self.plan.display_indent_schema()
+        );
+
+        Ok(self)
+    }
+
+    /// Hide the columns of the table not in `columns`
+    pub fn project_columns(mut self, columns: &BTreeSet<String>) -> Result<Self, DataFusionError> {
+        let exprs = self
+            .plan
+            .schema()
+            .fields()
+            .iter()
+            .filter(|field| columns.contains(field.name()))
+            .map(|field| Expr::Column(field.qualified_column()))
+            .collect::<Vec<_>>();
+        self.plan = LogicalPlanBuilder::from(self.plan)
+            .project(exprs)?
+            .build()?;
+        self.projected_schema = Some(SchemaRef::from(self.plan.schema().as_ref()));
+
+        debug!(
+            "Projected table source logical plan node of {}:\n{}",
+            self.table_name,
+            self.plan.display_indent_schema()
+        );
+
+        Ok(self)
+    }
+
     pub fn database_name(&self) -> &str {
         &self.database_name
     }
@@ -113,7 +156,10 @@ impl TableSource for TableSourceAdapter {
     }
 
     fn schema(&self) -> SchemaRef {
-        self.table_handle.schema()
+        match &self.projected_schema {
+            Some(schema) => schema.clone(),
+            None => self.table_handle.schema(),
+        }
     }
 
     fn supports_filters_pushdown(
@@ -2,9 +2,13 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use coordinator::service::CoordinatorRef;
+use datafusion::common::OwnedTableReference;
+use datafusion::logical_expr::TableSource;
 use memory_pool::MemoryPoolRef;
 use meta::error::MetaError;
 use meta::model::MetaClientRef;
+use models::auth::privilege::DatabasePrivilege;
+use models::auth::PasswordPolicy;
 use models::oid::Oid;
 use spi::query::ast::ExtStatement;
 use spi::query::datasource::stream::StreamProviderManagerRef;
@@ -19,12 +23,14 @@ use spi::{QueryError, Result};
 use trace::{info, SpanContext, SpanExt, SpanRecorder, TraceExporter};
 
 use super::query_tracker::QueryTracker;
+use crate::audit::{self, AuditLoggerRef, AuditRecord, AuditedRecordBatchStream, StatementType};
 use crate::data_source::split::SplitManagerRef;
 use crate::execution::factory::QueryExecutionFactoryRef;
 use crate::metadata::{
     BaseTableProvider, ContextProviderExtension, MetadataProvider, TableHandleProviderRef,
 };
 use crate::sql::logical::planner::DefaultLogicalPlanner;
+use crate::sql::planner::{check_privilege, tables_privileges};
 
 #[derive(Clone)]
 pub struct SimpleQueryDispatcher {
@@ -44,6 +50,8 @@ pub struct SimpleQueryDispatcher {
     func_manager: FuncMetaManagerRef,
     stream_provider_manager: StreamProviderManagerRef,
     trace_collector: Option<Arc<dyn TraceExporter>>,
+    audit_logger: AuditLoggerRef,
+    password_policy: PasswordPolicy,
 }
 
 #[async_trait]
@@ -126,7 +134,8 @@ impl QueryDispatcher for SimpleQueryDispatcher {
 
         let scheme_provider = self.build_scheme_provider(session).await?;
 
-        let logical_planner = DefaultLogicalPlanner::new(&scheme_provider);
+        let logical_planner = DefaultLogicalPlanner::new(&scheme_provider)
+            .with_password_policy(self.password_policy.clone());
 
         let span_recorder = session.get_child_span_recorder("parse sql");
         let statements = self.parser.parse(query.content())?;
@@ -146,12 +155,39 @@ impl QueryDispatcher for SimpleQueryDispatcher {
 
         drop(span_recorder);
 
+        let statement_type = audit::statement_type(&stmt);
+        query_state_machine.set_statement_type(statement_type);
         let logical_plan = self
-            .statement_to_logical_plan(stmt, &logical_planner, query_state_machine)
-            .await?;
+            .statement_to_logical_plan(stmt, &logical_planner, query_state_machine.clone())
+            .await
+            .map_err(|err| {
+                // the statements rejected by the planner, e.g. without privileges, are audited too
+                self.audit(statement_type, &query_state_machine, vec![], Some(&err));
+                err
+            })?;
         Ok(Some(logical_plan))
     }
 
+    async fn get_table_source(
+        &self,
+        table: OwnedTableReference,
+        query_state_machine: Arc<QueryStateMachine>,
+    ) -> Result<Arc<dyn TableSource>> {
+        let session = &query_state_machine.session;
+        let scheme_provider = self.build_scheme_provider(session).await?;
+        let table_source = scheme_provider.get_table_source(table)?;
+
+        // the same privileges as reading the table by sql
+        let privileges = tables_privileges(
+            DatabasePrivilege::Read,
+            *session.tenant_id(),
+            scheme_provider.reset_access_databases(),
+        );
+        check_privilege(session.user(), privileges)?;
+
+        Ok(table_source)
+    }
+
     async fn execute_logical_plan(
         &self,
         logical_plan: Plan,
@@ -229,16 +265,79 @@ impl SimpleQueryDispatcher {
         logical_plan: Plan,
         query_state_machine: Arc<QueryStateMachine>,
     ) -> Result<Output> {
-        let execution = self
-            .query_execution_factory
-            .create_query_execution(logical_plan, query_state_machine.clone())?;
+        let audit = self
+            .audited_statement_type(&query_state_machine)
+            .map(|statement_type| (statement_type, audit::plan_objects(&logical_plan)));
+
+        let result: Result<Output> = async {
+            let execution = self
+                .query_execution_factory
+                .create_query_execution(logical_plan, query_state_machine.clone())?;
+
+            // TrackedQuery.drop() is called implicitly when the value goes out of scope,
+            self.query_tracker
+                .try_track_query(query_state_machine.query_id, execution)
+                .await?
+                .start()
+                .await
+        }
+        .await;
 
-        // TrackedQuery.drop() is called implicitly when the value goes out of scope,
-        self.query_tracker
-            .try_track_query(query_state_machine.query_id, execution)
-            .await?
-            .start()
-            .await
+        let (statement_type, objects) = match audit {
+            Some(audit) => audit,
+            None => return result,
+        };
+        match result {
+            // The statement is finished when the stream ends.
+            Ok(Output::StreamData(stream)) => {
+                Ok(Output::StreamData(Box::pin(AuditedRecordBatchStream::new(
+                    stream,
+                    self.audit_logger.clone(),
+                    statement_type,
+                    query_state_machine,
+                    objects,
+                ))))
+            }
+            result => {
+                self.audit(
+                    statement_type,
+                    &query_state_machine,
+                    objects,
+                    result.as_ref().err(),
+                );
+                result
+            }
+        }
+    }
+
+    /// Returns the type of the statement if it's audited, the type is set on the
+    /// query state machine when the statement is parsed.
+    fn audited_statement_type(
+        &self,
+        query_state_machine: &QueryStateMachine,
+    ) -> Option<StatementType> {
+        let statement_type = query_state_machine.statement_type()?;
+        self.audit_logger
+            .is_audited(statement_type)
+            .then_some(statement_type)
+    }
+
+    fn audit(
+        &self,
+        statement_type: StatementType,
+        query_state_machine: &QueryStateMachine,
+        objects: Vec<String>,
+        error: Option<&QueryError>,
+    ) {
+        if self.audit_logger.is_audited(statement_type) {
+            self.audit_logger.log(AuditRecord::new(
+                statement_type,
+                query_state_machine,
+                objects,
+                0,
+                error.map(|e| e.to_string()),
+            ));
+        }
     }
 
     async fn build_scheme_provider(&self, session: &SessionCtx) -> Result<MetadataProvider> {
@@ -252,6 +351,7 @@ impl SimpleQueryDispatcher {
             self.default_table_provider.clone(),
             self.func_manager.clone(),
             self.query_tracker.clone(),
+            self.audit_logger.clone(),
             session.clone(),
         );
 
@@ -304,6 +404,8 @@ pub struct SimpleQueryDispatcherBuilder {
     func_manager: Option<FuncMetaManagerRef>,
     stream_provider_manager: Option<StreamProviderManagerRef>,
     trace_collector: Option<Arc<dyn TraceExporter>>,
+    audit_logger: Option<AuditLoggerRef>,
+    password_policy: Option<PasswordPolicy>,
 }
 
 impl SimpleQueryDispatcherBuilder {
@@ -371,6 +473,16 @@ impl SimpleQueryDispatcherBuilder {
         self
     }
 
+    pub fn with_audit_logger(mut self, audit_logger: AuditLoggerRef) -> Self {
+        self.audit_logger = Some(audit_logger);
+        self
+    }
+
+    pub fn with_password_policy(mut self, password_policy: PasswordPolicy) -> Self {
+        self.password_policy = Some(password_policy);
+        self
+    }
+
     pub fn build(self) -> Result<SimpleQueryDispatcher> {
         let coord = self.coord.ok_or_else(|| QueryError::BuildQueryDispatcher {
             err: "lost of coord".to_string(),
@@ -430,7 +542,14 @@ impl SimpleQueryDispatcherBuilder {
                     err: "lost of default_table_provider".to_string(),
                 })?;
 
+        let audit_logger = self
+            .audit_logger
+            .ok_or_else(|| QueryError::BuildQueryDispatcher {
+                err: "lost of audit_logger".to_string(),
+            })?;
+
         let trace_collector = self.trace_collector;
+        let password_policy = self.password_policy.unwrap_or_default();
 
         Ok(SimpleQueryDispatcher {
             coord,
@@ -444,6 +563,8 @@ impl SimpleQueryDispatcherBuilder {
             func_manager,
             stream_provider_manager,
             trace_collector,
+            audit_logger,
+            password_policy,
         })
     }
 }
@@ -2,8 +2,8 @@ use async_trait::async_trait;
 use meta::error::MetaError;
 use models::schema::DatabaseOptions;
 use spi::query::execution::{Output, QueryStateMachineRef};
-use spi::query::logical_planner::AlterDatabase;
-use spi::Result;
+use spi::query::logical_planner::{AlterDatabase, AlterDatabaseOperation};
+use spi::{QueryError, Result};
 
 use crate::execution::ddl::DDLDefinitionTask;
 
@@ -42,7 +42,32 @@ impl DDLDefinitionTask for AlterDatabaseTask {
             });
         }
         // .context(spi::MetaSnafu)?;
-        build_database_schema(&self.stmt.database_options, &mut schema.config);
+        match &self.stmt.operation {
+            AlterDatabaseOperation::Set(options) => {
+                build_database_schema(options, &mut schema.config);
+            }
+            AlterDatabaseOperation::AddRollup(rollup) => {
+                if !schema.config.add_rollup(rollup.clone()) {
+                    return Err(QueryError::Semantic {
+                        err: format!(
+                            "Rollup with interval {} already exists in database {}",
+                            rollup.interval(),
+                            self.stmt.database_name
+                        ),
+                    });
+                }
+            }
+            AlterDatabaseOperation::DropRollup(interval) => {
+                if !schema.config.drop_rollup(interval) {
+                    return Err(QueryError::Semantic {
+                        err: format!(
+                            "Rollup with interval {} not found in database {}",
+                            interval, self.stmt.database_name
+                        ),
+                    });
+                }
+            }
+        }
         // client
         //     .alter_database(schema)
         //     .context(spi::MetaSnafu)?;
@@ -69,4 +94,10 @@ fn build_database_schema(database_options: &DatabaseOptions, config: &mut Databa
     if let Some(precision) = database_options.precision() {
         config.with_precision(*precision);
     }
+    if let Some(max_series) = database_options.max_series() {
+        config.with_max_series(*max_series);
+    }
+    if let Some(max_series_per_table) = database_options.max_series_per_table() {
+        config.with_max_series_per_table(*max_series_per_table);
+    }
 }
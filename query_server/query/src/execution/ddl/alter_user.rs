@@ -60,6 +60,13 @@ impl DDLDefinitionTask for AlterUserTask {
                     .await?;
                 // .context(MetaSnafu)?;
             }
+            AlterUserAction::Unlock => {
+                debug!("Unlock user {}", user_name);
+                query_state_machine
+                    .meta
+                    .reset_login_status(user_name)
+                    .await?;
+            }
         }
 
         return Ok(Output::Nil(()));
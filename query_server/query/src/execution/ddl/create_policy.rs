@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use meta::error::MetaError;
+use models::oid::Identifier;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::query::logical_planner::CreatePolicy;
+use spi::{QueryError, Result};
+use trace::debug;
+
+use super::DDLDefinitionTask;
+
+pub struct CreatePolicyTask {
+    stmt: CreatePolicy,
+}
+
+impl CreatePolicyTask {
+    #[inline(always)]
+    pub fn new(stmt: CreatePolicy) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for CreatePolicyTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        let CreatePolicy {
+            ref tenant_name,
+            ref role_name,
+            ref if_not_exists,
+            ref policy,
+        } = self.stmt;
+
+        let meta = query_state_machine
+            .meta
+            .tenant_meta(tenant_name)
+            .await
+            .ok_or_else(|| QueryError::Meta {
+                source: MetaError::TenantNotFound {
+                    tenant: tenant_name.to_string(),
+                },
+            })?;
+
+        // the name of the policy is unique on the table
+        let roles = meta.custom_roles().await?;
+        if !roles.iter().any(|role| role.name() == role_name) {
+            return Err(QueryError::Meta {
+                source: MetaError::RoleNotFound {
+                    role: role_name.to_string(),
+                },
+            });
+        }
+        let exists = roles.iter().any(|role| {
+            role.get_row_policy(&policy.name, &policy.database, &policy.table)
+                .is_some()
+        });
+        match (if_not_exists, exists) {
+            (true, true) => return Ok(Output::Nil(())),
+            (false, true) => {
+                return Err(QueryError::PolicyAlreadyExists {
+                    name: policy.name.clone(),
+                    table: format!("{}.{}", policy.database, policy.table),
+                })
+            }
+            _ => {}
+        }
+
+        debug!(
+            "Create policy {} on {}.{} for role {} of tenant {}",
+            policy.name, policy.database, policy.table, role_name, tenant_name
+        );
+        meta.create_row_policy(policy.clone(), role_name).await?;
+
+        Ok(Output::Nil(()))
+    }
+}
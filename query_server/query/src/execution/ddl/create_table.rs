@@ -58,12 +58,19 @@ async fn create_table(stmt: &CreateTable, machine: QueryStateMachineRef) -> Resu
 }
 
 fn build_schema(stmt: &CreateTable) -> TskvTableSchema {
-    let CreateTable { schema, name, .. } = stmt;
+    let CreateTable {
+        schema,
+        name,
+        options,
+        ..
+    } = stmt;
 
-    TskvTableSchema::new(
+    let mut table_schema = TskvTableSchema::new(
         name.tenant().to_string(),
         name.database().to_string(),
         name.table().to_string(),
         schema.to_owned(),
-    )
+    );
+    table_schema.set_options(options.clone());
+    table_schema
 }
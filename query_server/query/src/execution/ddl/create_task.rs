@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use coordinator::resource_manager::ResourceManager;
+use meta::error::MetaError;
+use models::oid::Identifier;
+use models::schema::{ResourceInfo, ResourceOperator, ResourceStatus, ScheduledTask};
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::query::logical_planner::CreateTask;
+use spi::{QueryError, Result};
+use trace::debug;
+
+use super::DDLDefinitionTask;
+
+pub struct CreateTaskTask {
+    stmt: CreateTask,
+}
+
+impl CreateTaskTask {
+    #[inline(always)]
+    pub fn new(stmt: CreateTask) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for CreateTaskTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        let CreateTask {
+            ref if_not_exists,
+            ref task,
+        } = self.stmt;
+
+        let tenant = query_state_machine
+            .meta
+            .tenant_meta(&task.tenant)
+            .await
+            .ok_or_else(|| QueryError::Meta {
+                source: MetaError::TenantNotFound {
+                    tenant: task.tenant.clone(),
+                },
+            })?;
+
+        // A dropped task is kept with the status `Cancel`
+        let resource_name = ScheduledTask::resource_name(&task.tenant, &task.name);
+        let exists = query_state_machine
+            .meta
+            .read_resourceinfo_by_name(&resource_name)
+            .await?
+            .is_some_and(|info| *info.get_status() != ResourceStatus::Cancel);
+        match (if_not_exists, exists) {
+            (true, true) => return Ok(Output::Nil(())),
+            (false, true) => {
+                return Err(QueryError::TaskAlreadyExists {
+                    name: task.name.clone(),
+                })
+            }
+            _ => {}
+        }
+
+        debug!("Create task {} of tenant {}", task.name, task.tenant);
+        // the first run is after a schedule interval
+        let resourceinfo = ResourceInfo::new(
+            (*tenant.tenant().id(), task.database.clone()),
+            resource_name,
+            ResourceOperator::ScheduledTask(task.clone()),
+            &Some(task.schedule.clone()),
+            query_state_machine.coord.node_id(),
+        );
+        let added =
+            ResourceManager::add_resource_task(query_state_machine.coord.clone(), resourceinfo)
+                .await?;
+        if !added {
+            return Err(QueryError::TaskAlreadyExists {
+                name: task.name.clone(),
+            });
+        }
+
+        Ok(Output::Nil(()))
+    }
+}
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use meta::error::MetaError;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::query::logical_planner::DropPolicy;
+use spi::{QueryError, Result};
+use trace::debug;
+
+use super::DDLDefinitionTask;
+
+pub struct DropPolicyTask {
+    stmt: DropPolicy,
+}
+
+impl DropPolicyTask {
+    #[inline(always)]
+    pub fn new(stmt: DropPolicy) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for DropPolicyTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        let DropPolicy {
+            ref tenant_name,
+            ref name,
+            ref database_name,
+            ref table_name,
+            ref if_exist,
+        } = self.stmt;
+
+        let meta = query_state_machine
+            .meta
+            .tenant_meta(tenant_name)
+            .await
+            .ok_or_else(|| QueryError::Meta {
+                source: MetaError::TenantNotFound {
+                    tenant: tenant_name.to_string(),
+                },
+            })?;
+
+        debug!(
+            "Drop policy {} on {}.{} of tenant {}",
+            name, database_name, table_name, tenant_name
+        );
+        let success = meta
+            .drop_row_policy(name, database_name, table_name)
+            .await?;
+
+        if let (false, false) = (if_exist, success) {
+            return Err(QueryError::PolicyNotFound {
+                name: name.clone(),
+                table: format!("{database_name}.{table_name}"),
+            });
+        }
+
+        Ok(Output::Nil(()))
+    }
+}
@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use coordinator::resource_manager::ResourceManager;
 use meta::error::MetaError;
 use models::oid::Identifier;
-use models::schema::{ResourceInfo, ResourceOperator};
+use models::schema::{ResourceInfo, ResourceOperator, ResourceStatus, ScheduledTask};
 use spi::query::execution::{Output, QueryStateMachineRef};
 use spi::query::logical_planner::{DropTenantObject, TenantObjectType};
 use spi::{QueryError, Result};
@@ -101,6 +101,33 @@ impl DDLDefinitionTask for DropTenantObjectTask {
 
                 Ok(Output::Nil(()))
             }
+
+            TenantObjectType::Task => {
+                // set the status to Cancel, the resource manager stops scheduling it
+                let resource_name = ScheduledTask::resource_name(tenant_name, name);
+                let resourceinfo = query_state_machine
+                    .meta
+                    .read_resourceinfo_by_name(&resource_name)
+                    .await?
+                    .filter(|info| *info.get_status() != ResourceStatus::Cancel);
+
+                match resourceinfo {
+                    Some(mut resourceinfo) => {
+                        debug!("Drop task {} of tenant {}", name, tenant_name);
+                        resourceinfo.set_status(ResourceStatus::Cancel);
+                        resourceinfo.set_is_new_add(true);
+                        query_state_machine
+                            .meta
+                            .write_resourceinfo(&resource_name, resourceinfo)
+                            .await?;
+                        Ok(Output::Nil(()))
+                    }
+                    None if *if_exist => Ok(Output::Nil(())),
+                    None => Err(QueryError::TaskNotFound {
+                        name: name.to_string(),
+                    }),
+                }
+            }
         }
     }
 }
@@ -23,6 +23,7 @@ impl DDLDefinitionTask for GrantRevokeTask {
         let GrantRevoke {
             is_grant,
             ref database_privileges,
+            ref table_privileges,
             ref tenant_name,
             ref role_name,
         } = self.stmt;
@@ -54,8 +55,29 @@ impl DDLDefinitionTask for GrantRevokeTask {
                 role_name, tenant_name
             );
 
-            meta.grant_privilege_to_custom_role(database_privileges.clone(), role_name)
-                .await?;
+            let role = meta
+                .custom_role(role_name)
+                .await?
+                .ok_or_else(|| QueryError::Meta {
+                    source: MetaError::RoleNotFound {
+                        role: role_name.to_string(),
+                    },
+                })?;
+            for (privilege, database_name) in database_privileges {
+                role.check_grant_privilege(database_name, privilege)?;
+            }
+            for privilege in table_privileges {
+                role.check_grant_table_privilege(privilege)?;
+            }
+
+            if !database_privileges.is_empty() {
+                meta.grant_privilege_to_custom_role(database_privileges.clone(), role_name)
+                    .await?;
+            }
+            if !table_privileges.is_empty() {
+                meta.grant_table_privilege_to_custom_role(table_privileges.clone(), role_name)
+                    .await?;
+            }
         } else {
             // 给租户下的自定义角色撤销若干权限
             // fn revoke_privilege_from_custom_role_of_tenant(
@@ -70,8 +92,14 @@ impl DDLDefinitionTask for GrantRevokeTask {
                 role_name, tenant_name
             );
 
-            meta.revoke_privilege_from_custom_role(database_privileges.clone(), role_name)
-                .await?;
+            if !database_privileges.is_empty() {
+                meta.revoke_privilege_from_custom_role(database_privileges.clone(), role_name)
+                    .await?;
+            }
+            if !table_privileges.is_empty() {
+                meta.revoke_table_privilege_from_custom_role(table_privileges.clone(), role_name)
+                    .await?;
+            }
         }
 
         return Ok(Output::Nil(()));
@@ -8,13 +8,16 @@ use spi::Result;
 use self::alter_tenant::AlterTenantTask;
 use self::alter_user::AlterUserTask;
 use self::create_external_table::CreateExternalTableTask;
+use self::create_policy::CreatePolicyTask;
 use self::create_role::CreateRoleTask;
 use self::create_stream_table::CreateStreamTableTask;
 use self::create_table::CreateTableTask;
+use self::create_task::CreateTaskTask;
 use self::create_tenant::CreateTenantTask;
 use self::create_user::CreateUserTask;
 use self::drop_database_object::DropDatabaseObjectTask;
 use self::drop_global_object::DropGlobalObjectTask;
+use self::drop_policy::DropPolicyTask;
 use self::drop_tenant_object::DropTenantObjectTask;
 use self::grant_revoke::GrantRevokeTask;
 use self::recover_database::RecoverDatabaseTask;
@@ -27,6 +30,7 @@ use crate::execution::ddl::copy_vnode::CopyVnodeTask;
 use crate::execution::ddl::create_database::CreateDatabaseTask;
 use crate::execution::ddl::drop_vnode::DropVnodeTask;
 use crate::execution::ddl::move_node::MoveVnodeTask;
+use crate::execution::ddl::repair_group::RepairGroupTask;
 
 mod alter_database;
 mod alter_table;
@@ -37,19 +41,23 @@ mod compact_vnode;
 mod copy_vnode;
 mod create_database;
 mod create_external_table;
+mod create_policy;
 mod create_role;
 mod create_stream_table;
 mod create_table;
+mod create_task;
 mod create_tenant;
 mod create_user;
 mod drop_database_object;
 mod drop_global_object;
+mod drop_policy;
 mod drop_tenant_object;
 mod drop_vnode;
 mod grant_revoke;
 mod move_node;
 mod recover_database;
 mod recover_tenant;
+mod repair_group;
 
 /// Traits that DDL tasks should implement
 #[async_trait]
@@ -169,6 +177,7 @@ impl DDLDefinitionTaskFactory {
             DDLPlan::ChecksumGroup(sub_plan) => {
                 Box::new(ChecksumGroupTask::new(sub_plan.clone(), self.plan.schema()))
             }
+            DDLPlan::RepairGroup(sub_plan) => Box::new(RepairGroupTask::new(sub_plan.clone())),
             DDLPlan::CreateStreamTable(sub_plan) => {
                 let checker = self.stream_checker_manager.checker(&sub_plan.stream_type);
 
@@ -178,6 +187,9 @@ impl DDLDefinitionTaskFactory {
                 Box::new(RecoverDatabaseTask::new(sub_plan.clone()))
             }
             DDLPlan::RecoverTenant(sub_plan) => Box::new(RecoverTenantTask::new(sub_plan.clone())),
+            DDLPlan::CreateTask(sub_plan) => Box::new(CreateTaskTask::new(sub_plan.clone())),
+            DDLPlan::CreatePolicy(sub_plan) => Box::new(CreatePolicyTask::new(sub_plan.clone())),
+            DDLPlan::DropPolicy(sub_plan) => Box::new(DropPolicyTask::new(sub_plan.clone())),
         }
     }
 }
@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use coordinator::VnodeManagerCmdType;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::query::logical_planner::RepairGroup;
+use spi::Result;
+
+use super::DDLDefinitionTask;
+
+pub struct RepairGroupTask {
+    stmt: RepairGroup,
+}
+
+impl RepairGroupTask {
+    #[inline(always)]
+    pub fn new(stmt: RepairGroup) -> Self {
+        Self { stmt }
+    }
+}
+
+#[async_trait]
+impl DDLDefinitionTask for RepairGroupTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        let replication_set_id = self.stmt.replication_set_id;
+        let tenant = query_state_machine.session.tenant();
+
+        let coord = query_state_machine.coord.clone();
+        let cmd_type = VnodeManagerCmdType::RepairReplicationSet(replication_set_id);
+        coord.vnode_manager(tenant, cmd_type).await?;
+
+        Ok(Output::Nil(()))
+    }
+}
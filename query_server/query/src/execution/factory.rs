@@ -12,6 +12,7 @@ use tskv::kv_option::QueryOptions;
 
 use super::dml::DMLExecution;
 use super::query::SqlQueryExecution;
+use super::query_cache::QueryCache;
 use super::stream::trigger::executor::{TriggerExecutorFactory, TriggerExecutorFactoryRef};
 use super::stream::{MicroBatchStreamExecutionBuilder, MicroBatchStreamExecutionDesc};
 use super::sys::SystemExecution;
@@ -28,6 +29,7 @@ pub struct SqlQueryExecutionFactory {
     trigger_executor_factory: TriggerExecutorFactoryRef,
     runtime: Arc<DedicatedExecutor>,
     stream_checker_manager: StreamCheckerManagerRef,
+    query_cache: Arc<QueryCache>,
 }
 
 impl SqlQueryExecutionFactory {
@@ -37,6 +39,7 @@ impl SqlQueryExecutionFactory {
         scheduler: SchedulerRef,
         query_tracker: Arc<QueryTracker>,
         stream_checker_manager: StreamCheckerManagerRef,
+        query_cache: Arc<QueryCache>,
         config: Arc<QueryOptions>,
     ) -> Self {
         // Only do periodic scheduling, no need for many threads
@@ -59,6 +62,7 @@ impl SqlQueryExecutionFactory {
             trigger_executor_factory,
             runtime,
             stream_checker_manager,
+            query_cache,
         }
     }
 }
@@ -87,6 +91,7 @@ impl QueryExecutionFactory for SqlQueryExecutionFactory {
                         query_plan,
                         self.optimizer.clone(),
                         self.scheduler.clone(),
+                        self.query_cache.clone(),
                     ))),
                     (true, false, true) => {
                         // 流操作
@@ -125,6 +130,7 @@ impl QueryExecutionFactory for SqlQueryExecutionFactory {
                 state_machine,
                 sys_plan,
                 self.query_tracker.clone(),
+                self.query_cache.clone(),
             ))),
         }
     }
@@ -11,11 +11,14 @@ use spi::query::scheduler::SchedulerRef;
 use spi::{QueryError, Result};
 use trace::debug;
 
+use super::query_cache::QueryCache;
+
 pub struct SqlQueryExecution {
     query_state_machine: QueryStateMachineRef,
     plan: QueryPlan,
     optimizer: Arc<dyn Optimizer + Send + Sync>,
     scheduler: SchedulerRef,
+    query_cache: Arc<QueryCache>,
 
     abort_handle: Mutex<Option<AbortHandle>>,
 }
@@ -26,12 +29,14 @@ impl SqlQueryExecution {
         plan: QueryPlan,
         optimizer: Arc<dyn Optimizer + Send + Sync>,
         scheduler: SchedulerRef,
+        query_cache: Arc<QueryCache>,
     ) -> Self {
         Self {
             query_state_machine,
             plan,
             optimizer,
             scheduler,
+            query_cache,
             abort_handle: Mutex::new(None),
         }
     }
@@ -39,9 +44,20 @@ impl SqlQueryExecution {
     async fn start(&self) -> Result<Output> {
         // begin optimize
         self.query_state_machine.begin_optimize();
+        // read the cached results of the aggregation if possible
+        let cached_plan = self
+            .query_cache
+            .rewrite_plan(
+                &self.plan.df_plan,
+                &self.query_state_machine,
+                &self.optimizer,
+                &self.scheduler,
+            )
+            .await?;
+        let plan = cached_plan.as_ref().unwrap_or(&self.plan.df_plan);
         let physical_plan = self
             .optimizer
-            .optimize(&self.plan.df_plan, &self.query_state_machine.session)
+            .optimize(plan, &self.query_state_machine.session)
             .await?;
         self.query_state_machine.end_optimize();
 
@@ -0,0 +1,596 @@
+//! Cache of the results of the aggregations by a tumbling window of time, which are
+//! repeated by dashboards over the history that rarely changes.
+//!
+//! The time range of a query is split into buckets, the results of the buckets that ended
+//! before the live tail are cached, and the rest of the range is recomputed each time.
+//! The cached buckets of a table are invalidated by the deletes and late writes to them,
+//! which are notified by the coordinator of this node and by the vnodes of this node when
+//! they apply the raft log, so the cache is local to each node. The changes of the vnodes
+//! on the other nodes are not notified to a node without a replica of them, so only the
+//! buckets whose vnodes all have a replica on this node are cached.
+
+mod plan;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use cache::{ShardedSyncCache, SyncCache};
+use coordinator::data_change::{DataChange, DataChangeListener};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::logical_expr::LogicalPlan;
+use futures::{StreamExt, TryStreamExt};
+use meta::model::MetaRef;
+use models::meta_data::{BucketInfo, NodeId};
+use models::predicate::domain::TimeRange;
+use models::schema::{timestamp_convert, Precision};
+use models::utils::now_timestamp_nanos;
+use parking_lot::{Mutex, RwLock};
+use spi::query::execution::QueryStateMachineRef;
+use spi::query::optimizer::Optimizer;
+use spi::query::scheduler::SchedulerRef;
+use spi::query::session::SessionCtx;
+use spi::Result;
+use trace::debug;
+use tskv::kv_option::QueryOptions;
+
+use self::plan::CacheablePlan;
+use crate::extension::logical::plan_node::cached_result::CachedResultPlanNode;
+
+/// Max number of the buckets of a query, the query is not cached if it has more.
+const MAX_BUCKETS: i64 = 1024;
+/// Max number of the ranges of the missing buckets computed concurrently.
+const MAX_CONCURRENT_RANGES: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QueryCacheKey {
+    database: String,
+    table: String,
+    plan: String,
+    /// Start of the bucket in nanoseconds
+    bucket: i64,
+}
+
+#[derive(Debug)]
+pub struct CachedBucket {
+    batches: Vec<RecordBatch>,
+    /// Estimated memory in bytes
+    size: usize,
+    cached_at: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct QueryCacheStats {
+    pub entries: u64,
+    pub memory_bytes: u64,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+#[derive(Default)]
+struct TenantCacheState {
+    /// Keys of the cached buckets of each table by the start of bucket
+    index: HashMap<(String, String), BTreeMap<i64, HashSet<QueryCacheKey>>>,
+    memory: usize,
+}
+
+struct TenantCache {
+    entries: ShardedSyncCache<QueryCacheKey, Arc<CachedBucket>>,
+    state: Mutex<TenantCacheState>,
+    /// Increased when the data older than the live tail is changed,
+    /// the results computed before that must not be cached.
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl TenantCache {
+    fn new() -> Self {
+        Self {
+            entries: ShardedSyncCache::create_lru_sharded_cache_unbounded(),
+            state: Mutex::new(TenantCacheState::default()),
+            generation: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Get the cached bucket, it's removed if it has been cached longer than the ttl.
+    fn get(&self, key: &QueryCacheKey, ttl: Duration) -> Option<Arc<CachedBucket>> {
+        let value = match self.entries.get(key) {
+            Some(value) if value.cached_at.elapsed() >= ttl => {
+                let mut state = self.state.lock();
+                self.remove(&mut state, key);
+                None
+            }
+            value => value,
+        };
+        match value {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        value
+    }
+
+    fn insert(
+        &self,
+        key: QueryCacheKey,
+        batches: Vec<RecordBatch>,
+        generation: u64,
+        max_memory: usize,
+    ) {
+        let size = key.plan.len()
+            + batches
+                .iter()
+                .map(|b| b.get_array_memory_size())
+                .sum::<usize>();
+        if size > max_memory {
+            return;
+        }
+
+        let mut state = self.state.lock();
+        if self.generation.load(Ordering::Acquire) != generation {
+            return;
+        }
+        state
+            .index
+            .entry((key.database.clone(), key.table.clone()))
+            .or_default()
+            .entry(key.bucket)
+            .or_default()
+            .insert(key.clone());
+        if let Some(old) = self.entries.insert(
+            key,
+            Arc::new(CachedBucket {
+                batches,
+                size,
+                cached_at: Instant::now(),
+            }),
+        ) {
+            state.memory -= old.size;
+        }
+        state.memory += size;
+
+        while state.memory > max_memory {
+            match self.entries.pop() {
+                Some((key, value)) => {
+                    state.memory -= value.size;
+                    remove_from_index(&mut state.index, &key);
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn invalidate(&self, database: &str, table: Option<&str>, time_range: &TimeRange, bucket: i64) {
+        let mut state = self.state.lock();
+        let lower = time_range.min_ts.saturating_sub(bucket - 1);
+        let keys = state
+            .index
+            .iter()
+            .filter(|((db, tbl), _)| db == database && table.map_or(true, |t| t == tbl))
+            .flat_map(|(_, buckets)| {
+                buckets
+                    .range(lower..=time_range.max_ts)
+                    .flat_map(|(_, keys)| keys.iter().cloned())
+            })
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.remove(&mut state, &key);
+        }
+    }
+
+    fn remove(&self, state: &mut TenantCacheState, key: &QueryCacheKey) {
+        if let Some(value) = self.entries.remove(key) {
+            state.memory -= value.size;
+        }
+        remove_from_index(&mut state.index, key);
+    }
+
+    fn clear(&self) {
+        let mut state = self.state.lock();
+        self.entries.clear();
+        state.index.clear();
+        state.memory = 0;
+    }
+}
+
+fn remove_from_index(
+    index: &mut HashMap<(String, String), BTreeMap<i64, HashSet<QueryCacheKey>>>,
+    key: &QueryCacheKey,
+) {
+    let table = (key.database.clone(), key.table.clone());
+    if let Some(buckets) = index.get_mut(&table) {
+        if let Some(keys) = buckets.get_mut(&key.bucket) {
+            keys.remove(key);
+            if keys.is_empty() {
+                buckets.remove(&key.bucket);
+            }
+        }
+        if buckets.is_empty() {
+            index.remove(&table);
+        }
+    }
+}
+
+/// The query result cache of this node, see the [module documentation](self).
+pub struct QueryCache {
+    enabled: bool,
+    /// Default max memory in bytes of each tenant
+    max_memory: u64,
+    /// Length of the buckets in nanoseconds
+    bucket: i64,
+    /// Length of the live tail in nanoseconds
+    live_tail: i64,
+    /// How long a bucket is cached
+    ttl: Duration,
+    tenants: RwLock<HashMap<String, Arc<TenantCache>>>,
+}
+
+impl QueryCache {
+    pub fn new(options: &QueryOptions) -> Self {
+        let nanos = |d: std::time::Duration| i64::try_from(d.as_nanos()).unwrap_or(i64::MAX);
+        Self {
+            enabled: options.query_cache_enabled,
+            max_memory: options.query_cache_max_memory,
+            bucket: nanos(options.query_cache_bucket).max(1),
+            live_tail: nanos(options.query_cache_live_tail),
+            ttl: options.query_cache_bucket,
+            tenants: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn tenant(&self, tenant: &str) -> Arc<TenantCache> {
+        if let Some(cache) = self.tenants.read().get(tenant) {
+            return cache.clone();
+        }
+        self.tenants
+            .write()
+            .entry(tenant.to_string())
+            .or_insert_with(|| Arc::new(TenantCache::new()))
+            .clone()
+    }
+
+    /// Max memory in bytes of the cached results of the tenant, set by the tenant option
+    /// `query_cache_max_memory` or the config.
+    pub async fn max_memory(&self, meta: &MetaRef, tenant: &str) -> u64 {
+        meta.tenant_meta(tenant)
+            .await
+            .and_then(|client| client.tenant().options().get_query_cache_max_memory())
+            .unwrap_or(self.max_memory)
+    }
+
+    pub fn stats(&self, tenant: &str) -> QueryCacheStats {
+        match self.tenants.read().get(tenant) {
+            Some(cache) => QueryCacheStats {
+                entries: cache.entries.get_usage() as u64,
+                memory_bytes: cache.state.lock().memory as u64,
+                hits: cache.hits.load(Ordering::Relaxed),
+                misses: cache.misses.load(Ordering::Relaxed),
+            },
+            None => QueryCacheStats::default(),
+        }
+    }
+
+    pub fn clear(&self, tenant: &str) {
+        if let Some(cache) = self.tenants.read().get(tenant) {
+            cache.clear();
+        }
+    }
+
+    /// Replace the aggregation in the plan with the cached results of the buckets and the
+    /// aggregations of the rest of the time range, the missing buckets are computed and
+    /// cached. Returns None if the plan can't be cached.
+    pub async fn rewrite_plan(
+        &self,
+        plan: &LogicalPlan,
+        query_state_machine: &QueryStateMachineRef,
+        optimizer: &Arc<dyn Optimizer + Send + Sync>,
+        scheduler: &SchedulerRef,
+    ) -> Result<Option<LogicalPlan>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let cacheable = match CacheablePlan::try_new(plan, self.bucket)? {
+            Some(cacheable) => cacheable,
+            None => return Ok(None),
+        };
+        let cutoff = now_timestamp_nanos().saturating_sub(self.live_tail);
+        let (start, end) = match cacheable.bucket_range(self.bucket, cutoff) {
+            Some((start, end)) if (end - start) / self.bucket <= MAX_BUCKETS => (start, end),
+            _ => return Ok(None),
+        };
+
+        let session = &query_state_machine.session;
+        let max_memory = self
+            .max_memory(&query_state_machine.meta, session.tenant())
+            .await;
+        let tenant = self.tenant(session.tenant());
+        let generation = tenant.generation.load(Ordering::Acquire);
+        let key_of = |bucket: i64| QueryCacheKey {
+            database: cacheable.database.clone(),
+            table: cacheable.table.clone(),
+            plan: cacheable.key.clone(),
+            bucket,
+        };
+
+        // Read the cached buckets, and merge the adjacent missing buckets into ranges.
+        let mut batches = vec![];
+        let mut missing_ranges: Vec<(i64, i64)> = vec![];
+        for bucket in (start..end).step_by(self.bucket as usize) {
+            match tenant.get(&key_of(bucket), self.ttl) {
+                Some(cached) => batches.extend(cached.batches.iter().cloned()),
+                None => match missing_ranges.last_mut() {
+                    Some((_, range_end)) if *range_end == bucket => *range_end += self.bucket,
+                    _ => missing_ranges.push((bucket, bucket + self.bucket)),
+                },
+            }
+        }
+        debug!(
+            "Query cache of {} buckets, missing ranges: {:?}",
+            (end - start) / self.bucket,
+            missing_ranges
+        );
+
+        let computed = futures::stream::iter(missing_ranges)
+            .map(|(range_start, range_end)| {
+                let cacheable = &cacheable;
+                async move {
+                    let plan = cacheable.range_plan(Some(range_start), Some(range_end), false)?;
+                    let batches = execute(&plan, session, optimizer, scheduler).await?;
+                    Ok::<_, spi::QueryError>((range_start, range_end, batches))
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_RANGES)
+            .try_collect::<Vec<_>>()
+            .await?;
+        let (precision, local_buckets) = self
+            .meta_buckets(query_state_machine, &cacheable.database, start, end)
+            .await;
+        let node_id = query_state_machine.coord.node_id();
+        for (range_start, range_end, range_batches) in computed {
+            if let Some(buckets) =
+                cacheable.split_by_bucket(&range_batches, self.bucket, range_start, range_end)
+            {
+                for (bucket, bucket_batches) in buckets {
+                    let bucket_start = timestamp_convert(Precision::NS, precision, bucket);
+                    let bucket_end =
+                        timestamp_convert(Precision::NS, precision, bucket + self.bucket - 1);
+                    let is_local = match (bucket_start, bucket_end) {
+                        (Some(start), Some(end)) => {
+                            all_vnodes_local(&local_buckets, node_id, start, end)
+                        }
+                        _ => false,
+                    };
+                    if !is_local {
+                        continue;
+                    }
+                    tenant.insert(
+                        key_of(bucket),
+                        bucket_batches,
+                        generation,
+                        max_memory as usize,
+                    );
+                }
+            }
+            batches.extend(range_batches);
+        }
+
+        let cached = CachedResultPlanNode {
+            schema: cacheable.schema(),
+            buckets: ((end - start) / self.bucket) as usize,
+            batches,
+        };
+        Ok(Some(cacheable.rewrite(plan, cached, start, end)?))
+    }
+
+    /// The precision of the database and the buckets of it overlapping the time range
+    /// in nanoseconds, no bucket is returned if the database is not found.
+    async fn meta_buckets(
+        &self,
+        query_state_machine: &QueryStateMachineRef,
+        database: &str,
+        start: i64,
+        end: i64,
+    ) -> (Precision, Vec<BucketInfo>) {
+        let client = match query_state_machine
+            .meta
+            .tenant_meta(query_state_machine.session.tenant())
+            .await
+        {
+            Some(client) => client,
+            None => return (Precision::NS, vec![]),
+        };
+        let precision = match client.get_db_schema(database) {
+            Ok(Some(schema)) => *schema.config.precision_or_default(),
+            _ => return (Precision::NS, vec![]),
+        };
+        let range = (
+            timestamp_convert(Precision::NS, precision, start),
+            timestamp_convert(Precision::NS, precision, end - 1),
+        );
+        match range {
+            (Some(start), Some(end)) => (
+                precision,
+                client
+                    .mapping_bucket(database, start, end)
+                    .unwrap_or_default(),
+            ),
+            _ => (precision, vec![]),
+        }
+    }
+}
+
+/// Whether the time range `[start, end]` is covered by the buckets and all vnodes of them
+/// have a replica on the node, so the changes of the range are notified to the node.
+/// A range not covered may be written to a new bucket on the other nodes.
+fn all_vnodes_local(buckets: &[BucketInfo], node_id: NodeId, start: i64, end: i64) -> bool {
+    let mut overlapped = buckets
+        .iter()
+        .filter(|b| b.start_time <= end && b.end_time > start)
+        .collect::<Vec<_>>();
+    overlapped.sort_by_key(|b| b.start_time);
+
+    let mut covered_to = start;
+    for bucket in overlapped {
+        if bucket.start_time > covered_to {
+            return false;
+        }
+        let is_local = bucket
+            .shard_group
+            .iter()
+            .all(|set| set.vnodes.iter().any(|vnode| vnode.node_id == node_id));
+        if !is_local {
+            return false;
+        }
+        covered_to = covered_to.max(bucket.end_time);
+    }
+    covered_to > end
+}
+
+async fn execute(
+    plan: &LogicalPlan,
+    session: &SessionCtx,
+    optimizer: &Arc<dyn Optimizer + Send + Sync>,
+    scheduler: &SchedulerRef,
+) -> Result<Vec<RecordBatch>> {
+    let physical_plan = optimizer.optimize(plan, session).await?;
+    let stream = scheduler
+        .schedule(physical_plan, session.inner().task_ctx())
+        .await?
+        .stream();
+    Ok(stream.try_collect::<Vec<_>>().await?)
+}
+
+impl DataChangeListener for QueryCache {
+    fn on_data_change(&self, change: DataChange) {
+        if !self.enabled {
+            return;
+        }
+        let tenant = match self.tenants.read().get(&change.tenant) {
+            Some(tenant) => tenant.clone(),
+            None => return,
+        };
+        // The buckets being computed may be changed.
+        let cutoff = now_timestamp_nanos().saturating_sub(self.live_tail);
+        if change.time_range.min_ts < cutoff {
+            tenant.generation.fetch_add(1, Ordering::AcqRel);
+        }
+        tenant.invalidate(
+            &change.database,
+            change.table.as_deref(),
+            &change.time_range,
+            self.bucket,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use models::meta_data::{BucketInfo, ReplicationSet, VnodeInfo};
+    use models::predicate::domain::TimeRange;
+
+    use super::{all_vnodes_local, QueryCacheKey, SyncCache, TenantCache};
+
+    const HOUR: i64 = 3_600_000_000_000;
+    const TTL: Duration = Duration::from_secs(3600);
+
+    fn key(table: &str, bucket: i64) -> QueryCacheKey {
+        QueryCacheKey {
+            database: "public".to_string(),
+            table: table.to_string(),
+            plan: "plan".to_string(),
+            bucket,
+        }
+    }
+
+    #[test]
+    fn test_tenant_cache() {
+        let cache = TenantCache::new();
+        for bucket in 0..4 {
+            cache.insert(key("cpu", bucket * HOUR), vec![], 0, 1024);
+        }
+        cache.insert(key("mem", 0), vec![], 0, 1024);
+        assert_eq!(cache.entries.get_usage(), 5);
+        assert_eq!(cache.state.lock().memory, 5 * "plan".len());
+
+        // The buckets of [1h, 2h) and [2h, 3h) are changed.
+        cache.invalidate("public", Some("cpu"), &TimeRange::new(HOUR, 2 * HOUR), HOUR);
+        assert!(cache.get(&key("cpu", 0), TTL).is_some());
+        assert!(cache.get(&key("cpu", HOUR), TTL).is_none());
+        assert!(cache.get(&key("cpu", 2 * HOUR), TTL).is_none());
+        assert!(cache.get(&key("cpu", 3 * HOUR), TTL).is_some());
+        assert!(cache.get(&key("mem", 0), TTL).is_some());
+        assert_eq!(cache.hits.load(std::sync::atomic::Ordering::Relaxed), 3);
+
+        cache.invalidate("public", None, &TimeRange::all(), HOUR);
+        assert_eq!(cache.entries.get_usage(), 0);
+        assert_eq!(cache.state.lock().memory, 0);
+        assert!(cache.state.lock().index.is_empty());
+
+        // Computed before the data was changed.
+        cache.insert(key("cpu", 0), vec![], 1, 1024);
+        assert_eq!(cache.entries.get_usage(), 0);
+
+        // Evicted to the max memory.
+        for bucket in 0..4 {
+            cache.insert(key("cpu", bucket * HOUR), vec![], 0, 2 * "plan".len());
+        }
+        assert_eq!(cache.entries.get_usage(), 2);
+        assert_eq!(cache.state.lock().memory, 2 * "plan".len());
+    }
+
+    #[test]
+    fn test_tenant_cache_ttl() {
+        let cache = TenantCache::new();
+        cache.insert(key("cpu", 0), vec![], 0, 1024);
+        cache.insert(key("cpu", HOUR), vec![], 0, 1024);
+        assert!(cache.get(&key("cpu", 0), TTL).is_some());
+
+        // Expired and removed.
+        assert!(cache.get(&key("cpu", 0), Duration::ZERO).is_none());
+        assert!(cache.get(&key("cpu", 0), TTL).is_none());
+        assert_eq!(cache.entries.get_usage(), 1);
+        assert_eq!(cache.state.lock().memory, "plan".len());
+        assert!(cache.get(&key("cpu", HOUR), TTL).is_some());
+    }
+
+    #[test]
+    fn test_all_vnodes_local() {
+        let bucket = |id: u32, start_time: i64, end_time: i64, nodes: &[u64]| BucketInfo {
+            id,
+            start_time,
+            end_time,
+            shard_group: vec![ReplicationSet::new(
+                id,
+                nodes[0],
+                id,
+                nodes
+                    .iter()
+                    .map(|node_id| VnodeInfo::new(id, *node_id))
+                    .collect(),
+            )],
+        };
+        let buckets = vec![
+            bucket(1, 0, 100, &[1, 2]),
+            bucket(2, 100, 200, &[1]),
+            bucket(3, 300, 400, &[1, 2]),
+        ];
+
+        assert!(all_vnodes_local(&buckets, 1, 50, 150));
+        assert!(all_vnodes_local(&buckets, 2, 0, 99));
+        // A vnode of the bucket 2 is not on the node 2.
+        assert!(!all_vnodes_local(&buckets, 2, 50, 150));
+        // No bucket of [200, 300) yet.
+        assert!(!all_vnodes_local(&buckets, 1, 150, 350));
+        assert!(!all_vnodes_local(&buckets, 1, 350, 450));
+        assert!(!all_vnodes_local(&[], 1, 0, 99));
+    }
+}
@@ -0,0 +1,556 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, ArrayRef, Int64Array, StructArray, UInt32Array};
+use datafusion::arrow::compute::kernels::cast_utils::string_to_timestamp_nanos;
+use datafusion::arrow::compute::{cast, take};
+use datafusion::arrow::datatypes::{DataType, TimeUnit};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::tree_node::{Transformed, TreeNode, VisitRecursion};
+use datafusion::common::{Column, DFSchemaRef};
+use datafusion::datasource::source_as_provider;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_expr::expr::{ScalarFunction, ScalarUDF};
+use datafusion::logical_expr::{
+    binary_expr, Aggregate, Between, BinaryExpr, Extension, Filter, Limit, LogicalPlan, Operator,
+    Projection, Sort, SubqueryAlias, Union, Volatility,
+};
+use datafusion::optimizer::utils::{conjunction, split_conjunction};
+use datafusion::prelude::Expr;
+use datafusion::scalar::ScalarValue;
+use models::schema::TskvTableSchema;
+
+use crate::data_source::batch::tskv::ClusterTable;
+use crate::extension::analyse::transform_rollup::{
+    find_source, is_time, time_window_of, timestamp_nanos,
+};
+use crate::extension::analyse::transform_time_window::simplify_expr;
+use crate::extension::logical::plan_node::cached_result::CachedResultPlanNode;
+
+/// An aggregation of a table grouped by a tumbling window of time.
+///
+/// The window is aligned to the buckets of time, so the results of each bucket can be
+/// computed and cached separately, and the aggregation of a time range is the union of
+/// the results of the buckets in the range and the aggregations of the remaining parts.
+pub(crate) struct CacheablePlan {
+    /// The aggregation to be replaced
+    aggregate: Aggregate,
+    pub(crate) database: String,
+    pub(crate) table: String,
+    /// Identifies the aggregation regardless of the time range
+    pub(crate) key: String,
+    /// The input of the filter of the aggregation
+    source: Arc<LogicalPlan>,
+    /// Conjuncts of the filter except the time bounds
+    conjuncts: Vec<Expr>,
+    /// Time bounds of the filter with the constants folded, e.g. `now()`
+    time_bounds: Vec<Expr>,
+    time_column: Column,
+    time_type: DataType,
+    /// Index of the window in the output of the aggregation
+    window_index: usize,
+    /// The time range of the filter, closed interval in nanoseconds
+    lower: i64,
+    upper: i64,
+}
+
+impl CacheablePlan {
+    /// Returns None if the plan is not an aggregation by a window that is aligned
+    /// to the bucket, or it has no lower bound of time or volatile expressions.
+    pub(crate) fn try_new(plan: &LogicalPlan, bucket: i64) -> Result<Option<Self>> {
+        let aggregate = match find_aggregate(plan) {
+            Some(aggregate) => aggregate,
+            None => return Ok(None),
+        };
+        let (filter, scan) = match find_source(aggregate.input.as_ref()) {
+            Some(source) => match source.filter {
+                Some(filter) => (filter, source.scan),
+                None => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+        let provider = source_as_provider(&scan.source)?;
+        let table_schema = match provider.as_any().downcast_ref::<ClusterTable>() {
+            Some(cluster_table) => cluster_table.table_schema(),
+            None => return Ok(None),
+        };
+        let input_schema = aggregate.input.schema();
+
+        let window_index = aggregate.group_expr.iter().position(|expr| {
+            let expr = match expr {
+                Expr::Alias(expr, _) => expr.as_ref(),
+                expr => expr,
+            };
+            time_window_of(expr, input_schema, &table_schema).map_or(false, |(window, origin)| {
+                bucket % window == 0 && origin.rem_euclid(window) == 0
+            })
+        });
+        let window_index = match window_index {
+            Some(window_index) => window_index,
+            None => return Ok(None),
+        };
+        if !aggregate
+            .group_expr
+            .iter()
+            .chain(aggregate.aggr_expr.iter())
+            .all(is_deterministic)
+        {
+            return Ok(None);
+        }
+
+        let mut lower: Option<i64> = None;
+        let mut upper = i64::MAX;
+        let mut time_column = None;
+        let mut conjuncts = vec![];
+        let mut time_bounds = vec![];
+        for expr in split_conjunction(&filter.predicate) {
+            match time_bound(expr, input_schema, &table_schema) {
+                Some(bound) => {
+                    if let Some(l) = bound.lower {
+                        lower = Some(lower.map_or(l, |lower| lower.max(l)));
+                    }
+                    if let Some(u) = bound.upper {
+                        upper = upper.min(u);
+                    }
+                    time_column.get_or_insert(bound.column);
+                    time_bounds.push(bound.expr);
+                }
+                None if is_deterministic(expr) => conjuncts.push(expr.clone()),
+                None => return Ok(None),
+            }
+        }
+        let (lower, time_column) = match (lower, time_column) {
+            (Some(lower), Some(time_column)) => (lower, time_column),
+            _ => return Ok(None),
+        };
+        let time_type = input_schema
+            .field_from_column(&time_column)?
+            .data_type()
+            .clone();
+        if !matches!(time_type, DataType::Timestamp(_, _)) {
+            return Ok(None);
+        }
+
+        let mut cacheable = Self {
+            aggregate: aggregate.clone(),
+            database: table_schema.db.clone(),
+            table: table_schema.name.clone(),
+            key: String::new(),
+            source: filter.input.clone(),
+            conjuncts,
+            time_bounds,
+            time_column,
+            time_type,
+            window_index,
+            lower,
+            upper,
+        };
+        cacheable.key = cacheable
+            .range_plan(None, None, false)?
+            .display_indent()
+            .to_string();
+
+        Ok(Some(cacheable))
+    }
+
+    /// The output schema of the aggregation
+    pub(crate) fn schema(&self) -> DFSchemaRef {
+        self.aggregate.schema.clone()
+    }
+
+    /// Returns the range `[start, end)` of the buckets in the time range which end before
+    /// the cutoff, None if there is no such bucket.
+    pub(crate) fn bucket_range(&self, bucket: i64, cutoff: i64) -> Option<(i64, i64)> {
+        let start = self
+            .lower
+            .div_euclid(bucket)
+            .checked_add((self.lower.rem_euclid(bucket) != 0) as i64)?
+            .checked_mul(bucket)?;
+        let end = self
+            .upper
+            .saturating_add(1)
+            .min(cutoff)
+            .div_euclid(bucket)
+            .checked_mul(bucket)?;
+        (start < end).then_some((start, end))
+    }
+
+    /// Build the aggregation of the time range `[start, end)`, with the time bounds of
+    /// the filter or not.
+    pub(crate) fn range_plan(
+        &self,
+        start: Option<i64>,
+        end: Option<i64>,
+        with_time_bounds: bool,
+    ) -> Result<LogicalPlan> {
+        let time = Expr::Column(self.time_column.clone());
+        let mut predicates = self.conjuncts.clone();
+        if with_time_bounds {
+            predicates.extend(self.time_bounds.iter().cloned());
+        }
+        if let Some(start) = start {
+            predicates.push(time.clone().gt_eq(self.time_literal(start)?));
+        }
+        if let Some(end) = end {
+            predicates.push(time.lt(self.time_literal(end)?));
+        }
+        let input = match conjunction(predicates) {
+            Some(predicate) => Arc::new(LogicalPlan::Filter(Filter::try_new(
+                predicate,
+                self.source.clone(),
+            )?)),
+            None => self.source.clone(),
+        };
+
+        Ok(LogicalPlan::Aggregate(Aggregate::try_new(
+            input,
+            self.aggregate.group_expr.clone(),
+            self.aggregate.aggr_expr.clone(),
+        )?))
+    }
+
+    /// Replace the aggregation in the plan with the union of the cached results of the
+    /// buckets `[start, end)` and the aggregations of the time range before and after.
+    pub(crate) fn rewrite(
+        &self,
+        plan: &LogicalPlan,
+        cached: CachedResultPlanNode,
+        start: i64,
+        end: i64,
+    ) -> Result<LogicalPlan> {
+        let mut inputs = vec![Arc::new(LogicalPlan::Extension(Extension {
+            node: Arc::new(cached),
+        }))];
+        if self.lower < start {
+            inputs.push(Arc::new(self.range_plan(None, Some(start), true)?));
+        }
+        if end <= self.upper {
+            inputs.push(Arc::new(self.range_plan(Some(end), None, true)?));
+        }
+        let replacement = if inputs.len() == 1 {
+            inputs[0].as_ref().clone()
+        } else {
+            LogicalPlan::Union(Union {
+                inputs,
+                schema: self.aggregate.schema.clone(),
+            })
+        };
+
+        let aggregate = LogicalPlan::Aggregate(self.aggregate.clone());
+        plan.clone().transform_up(&|plan| {
+            if plan == aggregate {
+                Ok(Transformed::Yes(replacement.clone()))
+            } else {
+                Ok(Transformed::No(plan))
+            }
+        })
+    }
+
+    /// Split the results of the buckets `[start, end)` by the windows, the buckets
+    /// without results are empty. Returns None if the windows can't be read.
+    pub(crate) fn split_by_bucket(
+        &self,
+        batches: &[RecordBatch],
+        bucket: i64,
+        start: i64,
+        end: i64,
+    ) -> Option<BTreeMap<i64, Vec<RecordBatch>>> {
+        split_by_bucket(batches, self.window_index, bucket, start, end)
+    }
+
+    fn time_literal(&self, nanos: i64) -> Result<Expr> {
+        let value = match &self.time_type {
+            DataType::Timestamp(TimeUnit::Second, tz) => {
+                ScalarValue::TimestampSecond(Some(nanos / 1_000_000_000), tz.clone())
+            }
+            DataType::Timestamp(TimeUnit::Millisecond, tz) => {
+                ScalarValue::TimestampMillisecond(Some(nanos / 1_000_000), tz.clone())
+            }
+            DataType::Timestamp(TimeUnit::Microsecond, tz) => {
+                ScalarValue::TimestampMicrosecond(Some(nanos / 1_000), tz.clone())
+            }
+            DataType::Timestamp(TimeUnit::Nanosecond, tz) => {
+                ScalarValue::TimestampNanosecond(Some(nanos), tz.clone())
+            }
+            other => {
+                return Err(DataFusionError::Internal(format!(
+                    "Expected timestamp, but found {other}"
+                )))
+            }
+        };
+        Ok(Expr::Literal(value))
+    }
+}
+
+/// The aggregation under the projections, filters, sorts and limits.
+fn find_aggregate(plan: &LogicalPlan) -> Option<&Aggregate> {
+    match plan {
+        LogicalPlan::Aggregate(aggregate) => Some(aggregate),
+        LogicalPlan::Projection(Projection { input, .. })
+        | LogicalPlan::Filter(Filter { input, .. })
+        | LogicalPlan::Sort(Sort { input, .. })
+        | LogicalPlan::Limit(Limit { input, .. })
+        | LogicalPlan::SubqueryAlias(SubqueryAlias { input, .. }) => find_aggregate(input),
+        _ => None,
+    }
+}
+
+/// Returns false if the expression has volatile or stable functions or subqueries,
+/// whose results may change between queries.
+fn is_deterministic(expr: &Expr) -> bool {
+    let mut deterministic = true;
+    let _ = expr.apply(&mut |expr| {
+        deterministic = match expr {
+            Expr::ScalarFunction(ScalarFunction { fun, .. }) => {
+                fun.volatility() == Volatility::Immutable
+            }
+            Expr::ScalarUDF(ScalarUDF { fun, .. }) => {
+                fun.signature.volatility == Volatility::Immutable
+            }
+            Expr::ScalarSubquery(_)
+            | Expr::Exists { .. }
+            | Expr::InSubquery { .. }
+            | Expr::OuterReferenceColumn(_, _)
+            | Expr::Placeholder(_) => false,
+            _ => true,
+        };
+        Ok(if deterministic {
+            VisitRecursion::Continue
+        } else {
+            VisitRecursion::Stop
+        })
+    });
+    deterministic
+}
+
+/// A bound of time in the filter, e.g. `time >= now() - interval '1 hour'`.
+struct TimeBound {
+    column: Column,
+    /// The bound with the value folded
+    expr: Expr,
+    lower: Option<i64>,
+    upper: Option<i64>,
+}
+
+fn time_bound(
+    expr: &Expr,
+    schema: &DFSchemaRef,
+    table_schema: &TskvTableSchema,
+) -> Option<TimeBound> {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            let (column, op, value) = match (left.as_ref(), right.as_ref()) {
+                (Expr::Column(column), value) if is_time(table_schema, left.as_ref()) => {
+                    (column, *op, value)
+                }
+                (value, Expr::Column(column)) if is_time(table_schema, right.as_ref()) => {
+                    (column, op.swap()?, value)
+                }
+                _ => return None,
+            };
+            let value = simplify_expr(value.clone(), schema.clone()).ok()?;
+            let ts = literal_nanos(&value)?;
+            let (lower, upper) = match op {
+                Operator::Gt => (Some(ts.saturating_add(1)), None),
+                Operator::GtEq => (Some(ts), None),
+                Operator::Lt => (None, Some(ts.saturating_sub(1))),
+                Operator::LtEq => (None, Some(ts)),
+                Operator::Eq => (Some(ts), Some(ts)),
+                _ => return None,
+            };
+            Some(TimeBound {
+                column: column.clone(),
+                expr: binary_expr(Expr::Column(column.clone()), op, value),
+                lower,
+                upper,
+            })
+        }
+        Expr::Between(Between {
+            expr,
+            negated: false,
+            low,
+            high,
+        }) if is_time(table_schema, expr.as_ref()) => {
+            let column = match expr.as_ref() {
+                Expr::Column(column) => column.clone(),
+                _ => return None,
+            };
+            let low = simplify_expr(low.as_ref().clone(), schema.clone()).ok()?;
+            let high = simplify_expr(high.as_ref().clone(), schema.clone()).ok()?;
+            Some(TimeBound {
+                column,
+                lower: Some(literal_nanos(&low)?),
+                upper: Some(literal_nanos(&high)?),
+                expr: Expr::Between(Between::new(
+                    expr.clone(),
+                    false,
+                    Box::new(low),
+                    Box::new(high),
+                )),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn literal_nanos(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Literal(ScalarValue::Utf8(Some(s))) => string_to_timestamp_nanos(s).ok(),
+        expr => timestamp_nanos(expr),
+    }
+}
+
+fn split_by_bucket(
+    batches: &[RecordBatch],
+    window_index: usize,
+    bucket: i64,
+    start: i64,
+    end: i64,
+) -> Option<BTreeMap<i64, Vec<RecordBatch>>> {
+    let mut buckets = (start..end)
+        .step_by(bucket as usize)
+        .map(|b| (b, vec![]))
+        .collect::<BTreeMap<_, _>>();
+    for batch in batches {
+        let windows = window_nanos(batch.column(window_index))?;
+        let mut rows_of_bucket = BTreeMap::<i64, Vec<u32>>::new();
+        for (row, window) in windows.iter().enumerate() {
+            let b = window?.div_euclid(bucket) * bucket;
+            rows_of_bucket.entry(b).or_default().push(row as u32);
+        }
+        for (b, rows) in rows_of_bucket {
+            let rows = UInt32Array::from(rows);
+            let columns = batch
+                .columns()
+                .iter()
+                .map(|c| take(c.as_ref(), &rows, None))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .ok()?;
+            let batch = RecordBatch::try_new(batch.schema(), columns).ok()?;
+            buckets.get_mut(&b)?.push(batch);
+        }
+    }
+    Some(buckets)
+}
+
+/// The start of the windows in nanoseconds, `time_window` returns the struct of start and end.
+fn window_nanos(array: &ArrayRef) -> Option<Int64Array> {
+    let array = match array.data_type() {
+        DataType::Struct(_) => array
+            .as_any()
+            .downcast_ref::<StructArray>()?
+            .column(0)
+            .clone(),
+        _ => array.clone(),
+    };
+    let factor = match array.data_type() {
+        DataType::Timestamp(TimeUnit::Second, _) => 1_000_000_000,
+        DataType::Timestamp(TimeUnit::Millisecond, _) => 1_000_000,
+        DataType::Timestamp(TimeUnit::Microsecond, _) => 1_000,
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => 1,
+        _ => return None,
+    };
+    let values = cast(&array, &DataType::Int64).ok()?;
+    let values = values.as_any().downcast_ref::<Int64Array>()?;
+    Some(
+        values
+            .iter()
+            .map(|v| v.and_then(|v| v.checked_mul(factor)))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::{Int64Array, TimestampNanosecondArray};
+    use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use datafusion::arrow::record_batch::RecordBatch;
+    use datafusion::common::DFSchema;
+    use datafusion::prelude::{col, lit};
+    use datafusion::scalar::ScalarValue;
+    use models::schema::{TableColumn, TskvTableSchema};
+
+    use super::{split_by_bucket, time_bound};
+
+    const HOUR: i64 = 3_600_000_000_000;
+
+    #[test]
+    fn test_time_bound() {
+        let table_schema = TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "cpu".to_string(),
+            vec![TableColumn::new_time_column(0, TimeUnit::Nanosecond)],
+        );
+        let schema = Arc::new(
+            DFSchema::try_from(Schema::new(vec![Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            )]))
+            .unwrap(),
+        );
+        let ts = |v: i64| lit(ScalarValue::TimestampNanosecond(Some(v), None));
+        let bound = |expr| {
+            time_bound(&expr, &schema, &table_schema).map(|bound| (bound.lower, bound.upper))
+        };
+
+        assert_eq!(
+            bound(col("time").gt(ts(HOUR))),
+            Some((Some(HOUR + 1), None))
+        );
+        assert_eq!(
+            bound(ts(HOUR).gt(col("time"))),
+            Some((None, Some(HOUR - 1)))
+        );
+        assert_eq!(
+            bound(col("time").lt_eq(lit("1970-01-01T01:00:00Z"))),
+            Some((None, Some(HOUR)))
+        );
+        assert_eq!(
+            bound(col("time").between(ts(0), ts(HOUR))),
+            Some((Some(0), Some(HOUR)))
+        );
+        assert_eq!(bound(col("time").not_eq(ts(HOUR))), None);
+    }
+
+    #[test]
+    fn test_split_by_bucket() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "window",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("count", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(TimestampNanosecondArray::from(vec![0, HOUR, HOUR / 2])),
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+            ],
+        )
+        .unwrap();
+
+        let buckets = split_by_bucket(&[batch.clone()], 0, HOUR, 0, 3 * HOUR).unwrap();
+        assert_eq!(buckets.len(), 3);
+        let counts = |b: i64| {
+            buckets[&b]
+                .iter()
+                .flat_map(|batch| {
+                    let counts = batch.column(1).as_any().downcast_ref::<Int64Array>();
+                    counts.unwrap().values().to_vec()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(counts(0), vec![1, 3]);
+        assert_eq!(counts(HOUR), vec![2]);
+        assert!(counts(2 * HOUR).is_empty());
+
+        // Windows out of the buckets.
+        assert!(split_by_bucket(&[batch], 0, HOUR, 0, HOUR).is_none());
+    }
+}
@@ -35,8 +35,8 @@ use crate::sql::logical::optimizer::{DefaultLogicalOptimizer, LogicalOptimizer};
 use crate::sql::physical::optimizer::PhysicalOptimizer;
 use crate::sql::physical::planner::DefaultPhysicalPlanner;
 use crate::stream::offset_tracker::{OffsetTracker, OffsetTrackerRef};
-use crate::stream::state_store::memory::MemoryStateStoreFactory;
-use crate::stream::state_store::StateStoreFactory;
+use crate::stream::state_store::disk::DiskStateStoreFactory;
+use crate::stream::state_store::{create_disk_state_store_factory, StateStoreFactory};
 use crate::stream::watermark_tracker::{WatermarkTracker, WatermarkTrackerRef};
 
 #[derive(Debug, Clone)]
@@ -109,6 +109,13 @@ impl MicroBatchStreamExecutionBuilder {
             query_state_machine.query_id,
             query_state_machine.session.dedicated_hidden_dir(),
         )?);
+        // States are checkpointed next to the watermark, so both can be recovered after restart
+        let state_store_factory = create_disk_state_store_factory(
+            query_state_machine
+                .session
+                .dedicated_hidden_dir()
+                .join(format!("{}", query_state_machine.query_id)),
+        );
 
         Ok(MicroBatchStreamExecution {
             query_state_machine,
@@ -118,7 +125,7 @@ impl MicroBatchStreamExecutionBuilder {
             trigger_executor,
             watermark_tracker,
             offset_tracker: Arc::new(OffsetTracker::new()),
-            state_store_factory: Arc::new(MemoryStateStoreFactory::default()),
+            state_store_factory,
             runtime,
             abort_handle: Mutex::new(None),
         })
@@ -131,7 +138,7 @@ pub struct MicroBatchStreamExecution {
     stream_providers: Vec<StreamProviderRef>,
     scheduler: SchedulerRef,
     trigger_executor: TriggerExecutorRef,
-    state_store_factory: Arc<MemoryStateStoreFactory>,
+    state_store_factory: Arc<DiskStateStoreFactory>,
     watermark_tracker: WatermarkTrackerRef,
     offset_tracker: OffsetTrackerRef,
     runtime: Arc<DedicatedExecutor>,
@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::Result;
+
+use super::SystemTask;
+use crate::execution::query_cache::QueryCache;
+
+pub struct ClearQueryCacheTask {
+    query_cache: Arc<QueryCache>,
+}
+
+impl ClearQueryCacheTask {
+    pub fn new(query_cache: Arc<QueryCache>) -> Self {
+        Self { query_cache }
+    }
+}
+
+#[async_trait]
+impl SystemTask for ClearQueryCacheTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        self.query_cache.clear(query_state_machine.session.tenant());
+
+        Ok(Output::Nil(()))
+    }
+}
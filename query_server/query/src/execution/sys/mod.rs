@@ -1,4 +1,6 @@
+mod clear_query_cache;
 mod kill_query;
+mod show_query_cache;
 
 use std::sync::Arc;
 
@@ -8,8 +10,11 @@ use spi::query::execution::{Output, QueryExecution, QueryStateMachineRef};
 use spi::query::logical_planner::SYSPlan;
 use spi::Result;
 
+use self::clear_query_cache::ClearQueryCacheTask;
 use self::kill_query::KillQueryTask;
+use self::show_query_cache::ShowQueryCacheTask;
 use crate::dispatcher::query_tracker::QueryTracker;
+use crate::execution::query_cache::QueryCache;
 
 pub struct SystemExecution {
     task_factory: SystemTaskFactory,
@@ -21,11 +26,13 @@ impl SystemExecution {
         state_machine: QueryStateMachineRef,
         plan: SYSPlan,
         query_tracker: Arc<QueryTracker>,
+        query_cache: Arc<QueryCache>,
     ) -> Self {
         Self {
             task_factory: SystemTaskFactory {
                 plan,
                 query_tracker,
+                query_cache,
             },
             state_machine,
         }
@@ -84,6 +91,7 @@ trait SystemTask: Send + Sync {
 struct SystemTaskFactory {
     plan: SYSPlan,
     query_tracker: Arc<QueryTracker>,
+    query_cache: Arc<QueryCache>,
 }
 
 impl SystemTaskFactory {
@@ -92,6 +100,10 @@ impl SystemTaskFactory {
             SYSPlan::KillQuery(query_id) => {
                 Box::new(KillQueryTask::new(self.query_tracker.clone(), *query_id))
             }
+            SYSPlan::ShowQueryCache => Box::new(ShowQueryCacheTask::new(self.query_cache.clone())),
+            SYSPlan::ClearQueryCache => {
+                Box::new(ClearQueryCacheTask::new(self.query_cache.clone()))
+            }
         }
     }
 }
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::arrow::array::{StringArray, UInt64Array};
+use datafusion::arrow::record_batch::RecordBatch;
+use spi::query::execution::{Output, QueryStateMachineRef};
+use spi::query::logical_planner::SYSPlan;
+use spi::query::recordbatch::RecordBatchStreamWrapper;
+use spi::Result;
+
+use super::SystemTask;
+use crate::execution::query_cache::QueryCache;
+
+pub struct ShowQueryCacheTask {
+    query_cache: Arc<QueryCache>,
+}
+
+impl ShowQueryCacheTask {
+    pub fn new(query_cache: Arc<QueryCache>) -> Self {
+        Self { query_cache }
+    }
+}
+
+#[async_trait]
+impl SystemTask for ShowQueryCacheTask {
+    async fn execute(&self, query_state_machine: QueryStateMachineRef) -> Result<Output> {
+        let tenant = query_state_machine.session.tenant();
+        let stats = self.query_cache.stats(tenant);
+        let max_memory = self
+            .query_cache
+            .max_memory(&query_state_machine.meta, tenant)
+            .await;
+
+        let schema = SYSPlan::ShowQueryCache.schema();
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![tenant])),
+                Arc::new(UInt64Array::from(vec![stats.entries])),
+                Arc::new(UInt64Array::from(vec![stats.memory_bytes])),
+                Arc::new(UInt64Array::from(vec![max_memory])),
+                Arc::new(UInt64Array::from(vec![stats.hits])),
+                Arc::new(UInt64Array::from(vec![stats.misses])),
+            ],
+        )?;
+
+        let stream = RecordBatchStreamWrapper::new(schema, vec![batch]);
+        Ok(Output::StreamData(Box::pin(stream)))
+    }
+}
@@ -3,8 +3,10 @@ use datafusion::logical_expr::LogicalPlan;
 
 pub mod initial_plan_checker;
 pub mod stream_checker;
+pub mod transform_aggregate_scan;
 pub mod transform_bottom_func_to_topk_node;
 pub mod transform_gapfill;
+pub mod transform_rollup;
 pub mod transform_time_window;
 pub mod transform_topk_func_to_topk_node;
 pub mod transform_update;
@@ -0,0 +1,471 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::{DataType, Field, TimeUnit};
+use datafusion::common::tree_node::{Transformed, TreeNode};
+use datafusion::common::{Column, DFField, DFSchema, DFSchemaRef};
+use datafusion::config::ConfigOptions;
+use datafusion::datasource::source_as_provider;
+use datafusion::error::Result;
+use datafusion::logical_expr::expr::{AggregateFunction, AggregateUDF, InList, ScalarUDF};
+use datafusion::logical_expr::expr_rewriter::unnormalize_col;
+use datafusion::logical_expr::{
+    aggregate_function, Aggregate, BinaryExpr, Extension, LogicalPlan, Operator, Projection,
+};
+use datafusion::optimizer::analyzer::AnalyzerRule;
+use datafusion::optimizer::utils::{disjunction, split_conjunction};
+use datafusion::prelude::{cast, coalesce, lit, max, min, sum, Expr};
+use datafusion::scalar::ScalarValue;
+use models::predicate::domain::{PushedAggregate, PushedAggregateFunction, PushedTimeWindow};
+use models::schema::{ColumnType, TableColumn, TskvTableSchema};
+use models::ValueType;
+use trace::debug;
+
+use crate::data_source::batch::tskv::ClusterTable;
+use crate::extension::analyse::transform_rollup::{find_source, is_tag, is_time, timestamp_nanos};
+use crate::extension::analyse::transform_time_window::{parse_duration_arg, simplify_expr};
+use crate::extension::expr::{FIRST_UDAF_NAME, LAST_UDAF_NAME, TIME_WINDOW};
+use crate::extension::logical::plan_node::aggregate_scan::AggregateScanPlanNode;
+
+/// Push down the aggregations of a table to the scan of tskv, see [`PushedAggregate`].
+///
+/// Triggering conditions:
+/// 1. Group by tags and at most one tumbling `time_window`
+/// 2. Aggregations are `count`/`min`/`max`/`sum` of fields, and `first`/`last` of time and a field
+/// 3. Filters are comparisons of time with constants, and `=`/`IN` of tags with constants
+///
+/// The scan outputs partial aggregations of each series (and window),
+/// the aggregate is rewritten to merge the partial aggregations.
+pub struct TransformAggregateScanRule;
+
+impl AnalyzerRule for TransformAggregateScanRule {
+    fn analyze(&self, plan: LogicalPlan, _config: &ConfigOptions) -> Result<LogicalPlan> {
+        plan.transform_up(&analyze_internal)
+    }
+
+    fn name(&self) -> &str {
+        "transform_aggregate_scan"
+    }
+}
+
+fn analyze_internal(plan: LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+    if let LogicalPlan::Aggregate(aggregate) = &plan {
+        if let Some(new_plan) = try_rewrite_aggregate(aggregate)? {
+            debug!("Push down aggregate to scan: {}", new_plan.display_indent());
+            return Ok(Transformed::Yes(new_plan));
+        }
+    }
+
+    Ok(Transformed::No(plan))
+}
+
+fn try_rewrite_aggregate(aggregate: &Aggregate) -> Result<Option<LogicalPlan>> {
+    if aggregate.aggr_expr.is_empty() {
+        return Ok(None);
+    }
+    let source = match find_source(aggregate.input.as_ref()) {
+        Some(source) => source,
+        None => return Ok(None),
+    };
+    let provider = source_as_provider(&source.scan.source)?;
+    let cluster_table = match provider.as_any().downcast_ref::<ClusterTable>() {
+        Some(cluster_table) => cluster_table,
+        None => return Ok(None),
+    };
+    let table_schema = cluster_table.table_schema();
+    let input_schema = aggregate.input.schema();
+
+    let filters = match &source.filter {
+        Some(filter) => match pushed_filters(&filter.predicate, input_schema, &table_schema) {
+            Some(filters) => filters,
+            None => return Ok(None),
+        },
+        None => vec![],
+    };
+    let (window, group_tags) =
+        match pushed_group_keys(&aggregate.group_expr, input_schema, &table_schema) {
+            Some(keys) => keys,
+            None => return Ok(None),
+        };
+
+    let mut functions = vec![];
+    let mut partial_fields = vec![];
+    let mut final_exprs = vec![];
+    for expr in aggregate.aggr_expr.iter() {
+        match push_down_aggr_expr(expr, &table_schema, &mut functions, &mut partial_fields) {
+            Some(final_expr) => final_exprs.push(final_expr),
+            None => return Ok(None),
+        }
+    }
+
+    // Group keys keep the qualifier of the columns referenced by the aggregate.
+    let time_column = table_schema.time_column();
+    let mut fields = vec![];
+    if window.is_some() {
+        let field = Field::from(&time_column);
+        fields.push(DFField::new(
+            Some(source.alias.clone()),
+            field.name(),
+            field.data_type().clone(),
+            field.is_nullable(),
+        ));
+    }
+    for tag in group_tags.iter() {
+        fields.push(DFField::new(
+            Some(source.alias.clone()),
+            &tag.name,
+            DataType::Utf8,
+            true,
+        ));
+    }
+    fields.extend(partial_fields);
+    let schema = Arc::new(DFSchema::new_with_metadata(fields, HashMap::new())?);
+
+    let scan = LogicalPlan::Extension(Extension {
+        node: Arc::new(AggregateScanPlanNode {
+            table_name: source.scan.table_name.to_string(),
+            source: Arc::new(cluster_table.clone()),
+            schema,
+            filters,
+            aggregate: PushedAggregate { functions, window },
+        }),
+    });
+    let final_aggr_exprs = final_exprs
+        .into_iter()
+        .enumerate()
+        .map(|(i, e)| e.alias(format!("__final_agg_{}", i)))
+        .collect::<Vec<_>>();
+    let final_aggregate = LogicalPlan::Aggregate(Aggregate::try_new(
+        Arc::new(scan),
+        aggregate.group_expr.clone(),
+        final_aggr_exprs,
+    )?);
+
+    // Output the same schema as the aggregate.
+    let group_num = aggregate.group_expr.len();
+    let exprs = aggregate
+        .schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            if i < group_num {
+                Expr::Column(field.qualified_column())
+            } else {
+                let final_column =
+                    Expr::Column(Column::from_name(format!("__final_agg_{}", i - group_num)));
+                cast(final_column, field.data_type().clone()).alias(field.name())
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Some(LogicalPlan::Projection(Projection::try_new(
+        exprs,
+        Arc::new(final_aggregate),
+    )?)))
+}
+
+/// Returns the conjuncts of the filter to push down if all of them are exact for tskv:
+/// comparisons of time with constants, and `=`/`IN` of tags with constants.
+fn pushed_filters(
+    predicate: &Expr,
+    schema: &DFSchemaRef,
+    table_schema: &TskvTableSchema,
+) -> Option<Vec<Expr>> {
+    split_conjunction(predicate)
+        .into_iter()
+        .map(|expr| {
+            let expr = simplify_expr(expr.clone(), schema.clone()).ok()?;
+            let pushed = match &expr {
+                Expr::BinaryExpr(_)
+                    if is_tag_disjunction(&expr, table_schema)
+                        || is_time_comparison(&expr, table_schema) =>
+                {
+                    Some(expr.clone())
+                }
+                // The domain of `IN` is not resolved, push down `tag = a OR tag = b` instead.
+                Expr::InList(InList {
+                    expr: column,
+                    list,
+                    negated: false,
+                }) => match column.as_ref() {
+                    Expr::Column(c)
+                        if is_tag(table_schema, c)
+                            && list.iter().all(|e| {
+                                matches!(e, Expr::Literal(ScalarValue::Utf8(Some(_))))
+                            }) =>
+                    {
+                        disjunction(list.iter().map(|e| column.as_ref().clone().eq(e.clone())))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+            pushed.map(unnormalize_col)
+        })
+        .collect()
+}
+
+/// `tag = 'a' [OR tag = 'b' ...]`
+fn is_tag_disjunction(expr: &Expr, table_schema: &TskvTableSchema) -> bool {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::Or,
+            right,
+        }) => is_tag_disjunction(left, table_schema) && is_tag_disjunction(right, table_schema),
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::Eq,
+            right,
+        }) => matches!(
+            (left.as_ref(), right.as_ref()),
+            (Expr::Column(c), Expr::Literal(ScalarValue::Utf8(Some(_))))
+                | (Expr::Literal(ScalarValue::Utf8(Some(_))), Expr::Column(c))
+                if is_tag(table_schema, c)
+        ),
+        _ => false,
+    }
+}
+
+/// `time <op> <timestamp>`
+fn is_time_comparison(expr: &Expr, table_schema: &TskvTableSchema) -> bool {
+    let (op, value) = match expr {
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) if is_time(table_schema, left) => {
+            (*op, right.as_ref())
+        }
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) if is_time(table_schema, right) => {
+            match op.swap() {
+                Some(op) => (op, left.as_ref()),
+                None => return false,
+            }
+        }
+        _ => return false,
+    };
+    timestamp_nanos(value).is_some()
+        && matches!(
+            op,
+            Operator::Eq | Operator::Lt | Operator::LtEq | Operator::Gt | Operator::GtEq
+        )
+}
+
+/// Returns the window and the grouped tags
+/// if the aggregate is grouped by tags and at most one tumbling `time_window`.
+fn pushed_group_keys(
+    group_expr: &[Expr],
+    schema: &DFSchemaRef,
+    table_schema: &TskvTableSchema,
+) -> Option<(Option<PushedTimeWindow>, Vec<TableColumn>)> {
+    let unit_nanos = match table_schema.time_column().column_type {
+        ColumnType::Time(TimeUnit::Second) => 1_000_000_000,
+        ColumnType::Time(TimeUnit::Millisecond) => 1_000_000,
+        ColumnType::Time(TimeUnit::Microsecond) => 1_000,
+        ColumnType::Time(TimeUnit::Nanosecond) => 1,
+        _ => return None,
+    };
+
+    let mut window = None;
+    let mut tags: Vec<TableColumn> = vec![];
+    for expr in group_expr {
+        let expr = match expr {
+            Expr::Alias(expr, _) => expr.as_ref(),
+            expr => expr,
+        };
+        match expr {
+            Expr::Column(column) if is_tag(table_schema, column) => {
+                if !tags.iter().any(|t| t.name == column.name) {
+                    tags.push(table_schema.column(&column.name)?.clone());
+                }
+            }
+            // time_window(time, window[, slide[, start]]), only tumbling windows
+            Expr::ScalarUDF(ScalarUDF { fun, args })
+                if window.is_none()
+                    && fun.name == TIME_WINDOW
+                    && (2..=4).contains(&args.len())
+                    && is_time(table_schema, &args[0])
+                    && args.get(2).map_or(true, |slide| slide == &args[1]) =>
+            {
+                let duration = simplify_expr(args[1].clone(), schema.clone()).ok()?;
+                let duration =
+                    i64::try_from(parse_duration_arg(&duration).ok()?.as_nanos()).ok()?;
+                let start = match args.get(3) {
+                    Some(start) => {
+                        timestamp_nanos(&simplify_expr(start.clone(), schema.clone()).ok()?)?
+                    }
+                    None => 0,
+                };
+                if duration == 0 || duration % unit_nanos != 0 || start % unit_nanos != 0 {
+                    return None;
+                }
+                window = Some(PushedTimeWindow::new(
+                    duration / unit_nanos,
+                    start / unit_nanos,
+                ));
+            }
+            _ => return None,
+        }
+    }
+
+    Some((window, tags))
+}
+
+fn partial_column_name(i: usize) -> String {
+    format!("__partial_agg_{}", i)
+}
+
+fn partial_time_column_name(i: usize) -> String {
+    format!("__partial_agg_{}_time", i)
+}
+
+/// Push down an aggregation, returns the expression which merges the partial aggregations.
+fn push_down_aggr_expr(
+    expr: &Expr,
+    table_schema: &TskvTableSchema,
+    functions: &mut Vec<PushedAggregateFunction>,
+    partial_fields: &mut Vec<DFField>,
+) -> Option<Expr> {
+    let field = |arg: &Expr| match arg {
+        Expr::Column(column) => table_schema
+            .column(&column.name)
+            .filter(|c| c.column_type.is_field())
+            .cloned(),
+        _ => None,
+    };
+    let name = partial_column_name(functions.len());
+    let partial = Expr::Column(Column::from_name(&name));
+
+    match expr {
+        Expr::AggregateFunction(AggregateFunction {
+            fun,
+            args,
+            distinct: false,
+            filter: None,
+            order_by: None,
+        }) if args.len() == 1 => {
+            let column = field(&args[0])?;
+            let data_type = Field::from(&column).data_type().clone();
+            let (function, data_type, final_expr) = match fun {
+                aggregate_function::AggregateFunction::Min => (
+                    PushedAggregateFunction::Min(column.name),
+                    data_type,
+                    min(partial),
+                ),
+                aggregate_function::AggregateFunction::Max => (
+                    PushedAggregateFunction::Max(column.name),
+                    data_type,
+                    max(partial),
+                ),
+                aggregate_function::AggregateFunction::Sum
+                    if matches!(
+                        column.column_type,
+                        ColumnType::Field(
+                            ValueType::Float | ValueType::Integer | ValueType::Unsigned
+                        )
+                    ) =>
+                {
+                    (
+                        PushedAggregateFunction::Sum(column.name),
+                        data_type,
+                        sum(partial),
+                    )
+                }
+                // Groups without rows are not scanned, count of them is 0.
+                aggregate_function::AggregateFunction::Count => (
+                    PushedAggregateFunction::Count(column.name),
+                    DataType::Int64,
+                    coalesce(vec![sum(partial), lit(0_i64)]),
+                ),
+                _ => return None,
+            };
+            functions.push(function);
+            partial_fields.push(DFField::new_unqualified(&name, data_type, true));
+            Some(final_expr)
+        }
+        // first(time, field), last(time, field)
+        Expr::AggregateUDF(AggregateUDF {
+            fun,
+            args,
+            filter: None,
+            order_by: None,
+        }) if args.len() == 2 && is_time(table_schema, &args[0]) => {
+            let column = field(&args[1])?;
+            let function = match fun.name.as_str() {
+                FIRST_UDAF_NAME => PushedAggregateFunction::First(column.name.clone()),
+                LAST_UDAF_NAME => PushedAggregateFunction::Last(column.name.clone()),
+                _ => return None,
+            };
+            let time_name = partial_time_column_name(functions.len());
+            let time_type = Field::from(&table_schema.time_column()).data_type().clone();
+            functions.push(function);
+            partial_fields.push(DFField::new_unqualified(&time_name, time_type, true));
+            partial_fields.push(DFField::new_unqualified(
+                &name,
+                Field::from(&column).data_type().clone(),
+                true,
+            ));
+            Some(fun.call(vec![Expr::Column(Column::from_name(time_name)), partial]))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use datafusion::arrow::datatypes::TimeUnit;
+    use datafusion::common::{DFSchema, ToDFSchema};
+    use datafusion::prelude::{col, lit, Expr};
+    use datafusion::scalar::ScalarValue;
+    use models::schema::{ColumnType, TableColumn, TskvTableSchema};
+    use models::ValueType;
+
+    use super::pushed_filters;
+
+    fn table_schema() -> TskvTableSchema {
+        TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "air".to_string(),
+            vec![
+                TableColumn::new_time_column(0, TimeUnit::Nanosecond),
+                TableColumn::new_tag_column(1, "station".to_string()),
+                TableColumn::new(
+                    2,
+                    "temperature".to_string(),
+                    ColumnType::Field(ValueType::Float),
+                    Default::default(),
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_pushed_filters() {
+        let table_schema = table_schema();
+        let schema: Arc<DFSchema> = table_schema.to_arrow_schema().to_dfschema_ref().unwrap();
+        let ts = |v| Expr::Literal(ScalarValue::TimestampNanosecond(Some(v), None));
+
+        let predicate = col("time")
+            .gt_eq(ts(0))
+            .and(col("time").lt(ts(10)))
+            .and(col("station").in_list(vec![lit("a"), lit("b")], false));
+        let filters = pushed_filters(&predicate, &schema, &table_schema).unwrap();
+        assert_eq!(
+            filters,
+            vec![
+                col("time").gt_eq(ts(0)),
+                col("time").lt(ts(10)),
+                col("station").eq(lit("a")).or(col("station").eq(lit("b"))),
+            ]
+        );
+
+        let predicate = col("time")
+            .gt_eq(ts(0))
+            .and(col("temperature").gt(lit(1.0)));
+        assert!(pushed_filters(&predicate, &schema, &table_schema).is_none());
+
+        let predicate = col("station").in_list(vec![lit("a")], true);
+        assert!(pushed_filters(&predicate, &schema, &table_schema).is_none());
+    }
+}
@@ -0,0 +1,441 @@
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::DataType;
+use datafusion::common::tree_node::{Transformed, TreeNode};
+use datafusion::common::{Column, DFSchemaRef, OwnedTableReference};
+use datafusion::config::ConfigOptions;
+use datafusion::datasource::{provider_as_source, source_as_provider};
+use datafusion::error::Result;
+use datafusion::logical_expr::expr::{AggregateFunction, AggregateUDF, ScalarFunction, ScalarUDF};
+use datafusion::logical_expr::{
+    aggregate_function, Aggregate, BinaryExpr, BuiltinScalarFunction, Filter, LogicalPlan,
+    LogicalPlanBuilder, Operator, Projection, SubqueryAlias, TableScan,
+};
+use datafusion::optimizer::analyzer::AnalyzerRule;
+use datafusion::optimizer::utils::split_conjunction;
+use datafusion::prelude::{cast, lit, max, min, nullif, sum, Expr};
+use datafusion::scalar::ScalarValue;
+use models::schema::{RollupAgg, RollupPolicy, TskvTableSchema};
+use trace::debug;
+
+use crate::data_source::batch::tskv::ClusterTable;
+use crate::extension::analyse::transform_time_window::{parse_duration_arg, simplify_expr};
+use crate::extension::expr::{FIRST_UDAF_NAME, LAST_UDAF_NAME, TIME_WINDOW};
+
+/// Read the coarsest rollup table instead of the table for a
+/// `date_bin`/`time_window` aggregation, see [`crate::rollup`].
+///
+/// Triggering conditions:
+/// 1. Group by a window whose interval is a multiple of the rollup interval, and tags
+/// 2. Aggregations of fields that the rollup table has, `first` and `last` require all tags are grouped
+/// 3. Filters of tags and a time range `time >= x AND time < y` aligned to the rollup interval,
+///    and in the range that the rollup table covers
+pub struct TransformRollupRule;
+
+impl AnalyzerRule for TransformRollupRule {
+    fn analyze(&self, plan: LogicalPlan, _config: &ConfigOptions) -> Result<LogicalPlan> {
+        plan.transform_up(&analyze_internal)
+    }
+
+    fn name(&self) -> &str {
+        "transform_rollup"
+    }
+}
+
+fn analyze_internal(plan: LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+    if let LogicalPlan::Aggregate(aggregate) = &plan {
+        if let Some(rollup_plan) = try_rewrite_aggregate(aggregate)? {
+            debug!(
+                "Rewrite aggregate to rollup: {}",
+                rollup_plan.display_indent()
+            );
+            return Ok(Transformed::Yes(rollup_plan));
+        }
+    }
+
+    Ok(Transformed::No(plan))
+}
+
+/// The source of an aggregate: Filter? -> SubqueryAlias? -> Projection of columns? -> TableScan
+pub(crate) struct AggregateSource<'a> {
+    pub(crate) filter: Option<&'a Filter>,
+    pub(crate) alias: OwnedTableReference,
+    pub(crate) scan: &'a TableScan,
+}
+
+pub(crate) fn find_source(input: &LogicalPlan) -> Option<AggregateSource<'_>> {
+    let (filter, input) = match input {
+        LogicalPlan::Filter(filter) => (Some(filter), filter.input.as_ref()),
+        other => (None, other),
+    };
+    let (alias, input) = match input {
+        LogicalPlan::SubqueryAlias(alias) => (Some(alias.alias.clone()), alias.input.as_ref()),
+        other => (None, other),
+    };
+    let input = match input {
+        LogicalPlan::Projection(Projection { expr, input, .. })
+            if expr.iter().all(|e| matches!(e, Expr::Column(_))) =>
+        {
+            input.as_ref()
+        }
+        other => other,
+    };
+    match input {
+        LogicalPlan::TableScan(scan)
+            if scan.projection.is_none() && scan.filters.is_empty() && scan.fetch.is_none() =>
+        {
+            Some(AggregateSource {
+                filter,
+                alias: alias.unwrap_or_else(|| scan.table_name.clone()),
+                scan,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn try_rewrite_aggregate(aggregate: &Aggregate) -> Result<Option<LogicalPlan>> {
+    let source = match find_source(aggregate.input.as_ref()) {
+        Some(source) => source,
+        None => return Ok(None),
+    };
+    let provider = source_as_provider(&source.scan.source)?;
+    let cluster_table = match provider.as_any().downcast_ref::<ClusterTable>() {
+        Some(cluster_table) => cluster_table,
+        None => return Ok(None),
+    };
+    let table_schema = cluster_table.table_schema();
+    let meta = cluster_table.meta();
+    let db_schema = match meta.get_db_schema(&table_schema.db) {
+        Ok(Some(db_schema)) => db_schema,
+        _ => return Ok(None),
+    };
+    let input_schema = aggregate.input.schema();
+
+    // Coarsest rollup first.
+    for policy in db_schema.options().rollups().iter().rev() {
+        let rollup_table = policy.rollup_table_name(&table_schema.name);
+        let rollup_schema = match meta.get_tskv_table_schema(&table_schema.db, &rollup_table) {
+            Ok(Some(rollup_schema)) => rollup_schema,
+            _ => continue,
+        };
+        let range = match meta.get_rollup_range(&table_schema.db, &rollup_table) {
+            Some(range) => range,
+            None => continue,
+        };
+        let interval = policy.interval().to_nanoseconds();
+        let aligned = |ts: i64| ts.rem_euclid(interval) == 0;
+
+        // Time range of the query must be aligned and covered by the rollup table.
+        let time_range = match &source.filter {
+            Some(filter) => filter_time_range(
+                &filter.predicate,
+                input_schema,
+                &table_schema,
+                &rollup_schema,
+            ),
+            None => None,
+        };
+        match time_range {
+            Some((lower, upper))
+                if aligned(lower)
+                    && aligned(upper)
+                    && lower >= range.start
+                    && upper <= range.end => {}
+            _ => continue,
+        }
+
+        let group_tags = match group_by_window(
+            &aggregate.group_expr,
+            input_schema,
+            &table_schema,
+            &rollup_schema,
+            interval,
+        ) {
+            Some(group_tags) => group_tags,
+            None => continue,
+        };
+        let all_tags_grouped = group_tags == table_schema.tag_num();
+
+        let mut rollup_aggr_exprs = vec![];
+        let mut final_exprs = vec![];
+        for expr in aggregate.aggr_expr.iter() {
+            match rewrite_aggr_expr(
+                expr,
+                &table_schema,
+                &rollup_schema,
+                policy,
+                all_tags_grouped,
+                &mut rollup_aggr_exprs,
+            ) {
+                Some(final_expr) => final_exprs.push(final_expr),
+                None => break,
+            }
+        }
+        if final_exprs.len() != aggregate.aggr_expr.len() {
+            continue;
+        }
+
+        let rollup_cluster_table = cluster_table.with_table_schema(rollup_schema);
+        let scan = LogicalPlanBuilder::scan(
+            rollup_table,
+            provider_as_source(Arc::new(rollup_cluster_table)),
+            None,
+        )?
+        .build()?;
+        // Keep the qualifier of the columns referenced by the aggregate.
+        let input = LogicalPlan::SubqueryAlias(SubqueryAlias::try_new(scan, source.alias.clone())?);
+        let input = match source.filter {
+            Some(filter) => {
+                LogicalPlan::Filter(Filter::try_new(filter.predicate.clone(), Arc::new(input))?)
+            }
+            None => input,
+        };
+        let rollup_aggregate = LogicalPlan::Aggregate(Aggregate::try_new(
+            Arc::new(input),
+            aggregate.group_expr.clone(),
+            rollup_aggr_exprs,
+        )?);
+
+        // Output the same schema as the aggregate.
+        let group_num = aggregate.group_expr.len();
+        let exprs = aggregate
+            .schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                if i < group_num {
+                    Expr::Column(field.qualified_column())
+                } else {
+                    cast(
+                        final_exprs[i - group_num].clone(),
+                        field.data_type().clone(),
+                    )
+                    .alias(field.name())
+                }
+            })
+            .collect::<Vec<_>>();
+
+        return Ok(Some(LogicalPlan::Projection(Projection::try_new(
+            exprs,
+            Arc::new(rollup_aggregate),
+        )?)));
+    }
+
+    Ok(None)
+}
+
+pub(crate) fn is_tag(table_schema: &TskvTableSchema, column: &Column) -> bool {
+    table_schema
+        .column(&column.name)
+        .map_or(false, |c| c.column_type.is_tag())
+}
+
+pub(crate) fn is_time(table_schema: &TskvTableSchema, expr: &Expr) -> bool {
+    match expr {
+        Expr::Column(column) => table_schema
+            .column(&column.name)
+            .map_or(false, |c| c.column_type.is_time()),
+        _ => false,
+    }
+}
+
+/// Returns the time range `[lower, upper)` in nanoseconds of the filter,
+/// None if the filter has other conditions than tags and time.
+fn filter_time_range(
+    predicate: &Expr,
+    schema: &DFSchemaRef,
+    table_schema: &TskvTableSchema,
+    rollup_schema: &TskvTableSchema,
+) -> Option<(i64, i64)> {
+    let mut lower: Option<i64> = None;
+    let mut upper: Option<i64> = None;
+    for expr in split_conjunction(predicate) {
+        let columns = expr.to_columns().ok()?;
+        if columns
+            .iter()
+            .all(|c| is_tag(table_schema, c) && rollup_schema.contains_column(&c.name))
+        {
+            continue;
+        }
+        let (op, value) = match expr {
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) if is_time(table_schema, left) => {
+                (*op, right.as_ref())
+            }
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) if is_time(table_schema, right) => {
+                (op.swap()?, left.as_ref())
+            }
+            _ => return None,
+        };
+        let value = timestamp_nanos(&simplify_expr(value.clone(), schema.clone()).ok()?)?;
+        match op {
+            Operator::GtEq => lower = Some(lower.map_or(value, |l| l.max(value))),
+            Operator::Lt => upper = Some(upper.map_or(value, |u| u.min(value))),
+            _ => return None,
+        }
+    }
+    Some((lower?, upper?))
+}
+
+pub(crate) fn timestamp_nanos(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Literal(ScalarValue::TimestampSecond(Some(v), _)) => v.checked_mul(1_000_000_000),
+        Expr::Literal(ScalarValue::TimestampMillisecond(Some(v), _)) => v.checked_mul(1_000_000),
+        Expr::Literal(ScalarValue::TimestampMicrosecond(Some(v), _)) => v.checked_mul(1_000),
+        Expr::Literal(ScalarValue::TimestampNanosecond(Some(v), _)) => Some(*v),
+        _ => None,
+    }
+}
+
+/// Check the group by is exactly one window of time and tags,
+/// returns the number of the grouped tags.
+fn group_by_window(
+    group_expr: &[Expr],
+    schema: &DFSchemaRef,
+    table_schema: &TskvTableSchema,
+    rollup_schema: &TskvTableSchema,
+    interval: i64,
+) -> Option<usize> {
+    let mut windows = 0;
+    let mut tags = 0;
+    for expr in group_expr {
+        let expr = match expr {
+            Expr::Alias(expr, _) => expr.as_ref(),
+            expr => expr,
+        };
+        if let Expr::Column(column) = expr {
+            if is_tag(table_schema, column) && rollup_schema.contains_column(&column.name) {
+                tags += 1;
+                continue;
+            }
+        }
+        let (window, origin) = time_window_of(expr, schema, table_schema)?;
+        if window % interval != 0 || origin.rem_euclid(interval) != 0 {
+            return None;
+        }
+        windows += 1;
+    }
+
+    (windows == 1).then_some(tags)
+}
+
+/// Returns the interval and origin in nanoseconds of a tumbling window of time,
+/// `date_bin(interval, time[, origin])` or `time_window(time, interval[, interval[, start]])`.
+pub(crate) fn time_window_of(
+    expr: &Expr,
+    schema: &DFSchemaRef,
+    table_schema: &TskvTableSchema,
+) -> Option<(i64, i64)> {
+    let (window, origin) = match expr {
+        // date_bin(interval, time[, origin])
+        Expr::ScalarFunction(ScalarFunction { fun, args })
+            if *fun == BuiltinScalarFunction::DateBin
+                && (args.len() == 2 || args.len() == 3)
+                && is_time(table_schema, &args[1]) =>
+        {
+            (&args[0], args.get(2))
+        }
+        // time_window(time, window[, slide[, start]]), only tumbling windows
+        Expr::ScalarUDF(ScalarUDF { fun, args })
+            if fun.name == TIME_WINDOW
+                && (2..=4).contains(&args.len())
+                && is_time(table_schema, &args[0])
+                && args.get(2).map_or(true, |slide| slide == &args[1]) =>
+        {
+            (&args[1], args.get(3))
+        }
+        _ => return None,
+    };
+    let window = simplify_expr(window.clone(), schema.clone()).ok()?;
+    let window = i64::try_from(parse_duration_arg(&window).ok()?.as_nanos()).ok()?;
+    let origin = match origin {
+        Some(origin) => timestamp_nanos(&simplify_expr(origin.clone(), schema.clone()).ok()?)?,
+        None => 0,
+    };
+    (window > 0).then_some((window, origin))
+}
+
+/// Rewrite an aggregation of the table to the aggregations of the rollup table,
+/// returns the expression which computes the result from the rollup aggregations.
+fn rewrite_aggr_expr(
+    expr: &Expr,
+    table_schema: &TskvTableSchema,
+    rollup_schema: &TskvTableSchema,
+    policy: &RollupPolicy,
+    all_tags_grouped: bool,
+    rollup_aggr_exprs: &mut Vec<Expr>,
+) -> Option<Expr> {
+    let field = |arg: &Expr| match arg {
+        Expr::Column(column)
+            if table_schema
+                .column(&column.name)
+                .map_or(false, |c| c.column_type.is_field()) =>
+        {
+            Some(column.name.clone())
+        }
+        _ => None,
+    };
+    let rollup_column = |agg: RollupAgg, field: &str| {
+        let name = agg.column_name(field);
+        (policy.has_agg(agg) && rollup_schema.contains_column(&name))
+            .then(|| Expr::Column(Column::from_name(name)))
+    };
+    let mut push = |expr: Expr| {
+        let name = format!("__rollup_agg_{}", rollup_aggr_exprs.len());
+        rollup_aggr_exprs.push(expr.alias(&name));
+        Expr::Column(Column::from_name(name))
+    };
+
+    match expr {
+        Expr::AggregateFunction(AggregateFunction {
+            fun,
+            args,
+            distinct: false,
+            filter: None,
+            order_by: None,
+        }) if args.len() == 1 => {
+            let field = field(&args[0])?;
+            match fun {
+                aggregate_function::AggregateFunction::Min => {
+                    Some(push(min(rollup_column(RollupAgg::Min, &field)?)))
+                }
+                aggregate_function::AggregateFunction::Max => {
+                    Some(push(max(rollup_column(RollupAgg::Max, &field)?)))
+                }
+                aggregate_function::AggregateFunction::Sum => {
+                    Some(push(sum(rollup_column(RollupAgg::Sum, &field)?)))
+                }
+                aggregate_function::AggregateFunction::Count => {
+                    Some(push(sum(rollup_column(RollupAgg::Count, &field)?)))
+                }
+                // Mean of the windows weighted by the counts.
+                aggregate_function::AggregateFunction::Avg => {
+                    let mean = rollup_column(RollupAgg::Mean, &field)?;
+                    let count = rollup_column(RollupAgg::Count, &field)?;
+                    let weighted_sum = push(sum(mean * cast(count.clone(), DataType::Float64)));
+                    let count = push(sum(count));
+                    Some(weighted_sum / cast(nullif(count, lit(0_i64)), DataType::Float64))
+                }
+                _ => None,
+            }
+        }
+        // first(time, field), last(time, field)
+        Expr::AggregateUDF(AggregateUDF {
+            fun,
+            args,
+            filter: None,
+            order_by: None,
+        }) if args.len() == 2 && is_time(table_schema, &args[0]) && all_tags_grouped => {
+            let agg = match fun.name.as_str() {
+                FIRST_UDAF_NAME => RollupAgg::First,
+                LAST_UDAF_NAME => RollupAgg::Last,
+                _ => return None,
+            };
+            let field = field(&args[1])?;
+            let column = rollup_column(agg, &field)?;
+            Some(push(fun.call(vec![args[0].clone(), column])))
+        }
+        _ => None,
+    }
+}
@@ -152,7 +152,7 @@ fn valid_duration(dur: Duration) -> Result<Duration, QueryError> {
 
 /// Convert string time duration to [`Duration`] \
 /// Only support [`ScalarValue::IntervalYearMonth`] | [`ScalarValue::IntervalMonthDayNano`] | [`ScalarValue::IntervalDayTime`]
-fn parse_duration_arg(expr: &Expr) -> Result<Duration, QueryError> {
+pub(crate) fn parse_duration_arg(expr: &Expr) -> Result<Duration, QueryError> {
     let nano = match expr {
         Expr::Literal(ScalarValue::IntervalYearMonth(val)) => ym_to_nano(val),
         Expr::Literal(ScalarValue::IntervalMonthDayNano(val)) => mdn_to_nano(val),
@@ -407,7 +407,7 @@ fn replace_window_expr(new_expr: Expr, plan: &LogicalPlan) -> Result<LogicalPlan
     })
 }
 
-fn simplify_expr(expr: Expr, schema: DFSchemaRef) -> Result<Expr> {
+pub(crate) fn simplify_expr(expr: Expr, schema: DFSchemaRef) -> Result<Expr> {
     let mut execution_props = ExecutionProps::new();
     let ctx = OptimizerContext::new();
     execution_props.query_execution_start_time = ctx.query_execution_start_time();
@@ -15,6 +15,7 @@ use datafusion::logical_expr::{
 };
 use datafusion::physical_plan::Accumulator;
 use datafusion::scalar::ScalarValue;
+use once_cell::sync::Lazy;
 use spi::query::function::FunctionMetadataManager;
 use spi::{QueryError, Result};
 
@@ -22,6 +23,8 @@ use super::TSPoint;
 use crate::extension::expr::aggregate_function::FIRST_UDAF_NAME;
 use crate::extension::expr::BINARYS;
 
+pub static FIRST_UDAF: Lazy<Arc<AggregateUDF>> = Lazy::new(|| Arc::new(new()));
+
 pub fn register_udaf(func_manager: &mut dyn FunctionMetadataManager) -> Result<AggregateUDF> {
     let udf = new();
     func_manager.register_udaf(udf.clone())?;
@@ -31,7 +31,9 @@ pub const COMPLETENESS_UDF_NAME: &str = "completeness";
 pub const CONSISTENCY_UDF_NAME: &str = "consistency";
 pub const TIMELINESS_UDF_NAME: &str = "timeliness";
 pub const VALIDITY_UDF_NAME: &str = "validity";
+pub use first::FIRST_UDAF;
 pub use gauge::GaugeData;
+pub use last::LAST_UDAF;
 pub use state_agg::StateAggData;
 
 pub fn register_udafs(func_manager: &mut dyn FunctionMetadataManager) -> Result<()> {
@@ -9,6 +9,7 @@ mod selector_function;
 mod session_function;
 mod window;
 
+pub use aggregate_function::{FIRST_UDAF, FIRST_UDAF_NAME, LAST_UDAF, LAST_UDAF_NAME};
 use datafusion::arrow::datatypes::{DataType, IntervalUnit};
 pub use scalar_function::{INTERPOLATE, LOCF, TIME_WINDOW_GAPFILL};
 pub use selector_function::{BOTTOM, TOPK};
@@ -0,0 +1,90 @@
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use datafusion::common::DFSchemaRef;
+use datafusion::logical_expr::{LogicalPlan, UserDefinedLogicalNodeCore};
+use datafusion::prelude::Expr;
+use models::predicate::domain::PushedAggregate;
+
+use crate::data_source::batch::tskv::ClusterTable;
+
+/// Scan the partial aggregations of each series (and window) from tskv.
+#[derive(Clone)]
+pub struct AggregateScanPlanNode {
+    /// The name of the table
+    pub table_name: String,
+    /// The source of the table
+    pub source: Arc<ClusterTable>,
+    /// The schema description of the output, group keys followed by the partial aggregations
+    pub schema: DFSchemaRef,
+    /// Expressions to be used as filters by the table provider
+    pub filters: Vec<Expr>,
+    /// The aggregations pushed down to tskv
+    pub aggregate: PushedAggregate,
+}
+
+impl Debug for AggregateScanPlanNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_for_explain(f)
+    }
+}
+
+impl Hash for AggregateScanPlanNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.table_name.hash(state);
+        self.schema.hash(state);
+        self.filters.hash(state);
+        self.aggregate.hash(state);
+    }
+}
+
+impl PartialEq for AggregateScanPlanNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.table_name == other.table_name
+            && self.schema == other.schema
+            && self.filters == other.filters
+            && self.aggregate == other.aggregate
+    }
+}
+
+impl Eq for AggregateScanPlanNode {}
+
+impl UserDefinedLogicalNodeCore for AggregateScanPlanNode {
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "AggregateScan: table={}, agg={:?}, window={:?}, filter=[{}]",
+            self.table_name,
+            self.aggregate.functions,
+            self.aggregate.window,
+            self.filters
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
+    fn from_template(&self, exprs: &[Expr], inputs: &[LogicalPlan]) -> Self {
+        assert_eq!(inputs.len(), 0, "input size inconsistent");
+        assert_eq!(exprs.len(), 0, "expr size inconsistent");
+        self.clone()
+    }
+
+    fn name(&self) -> &str {
+        "AggregateScan"
+    }
+}
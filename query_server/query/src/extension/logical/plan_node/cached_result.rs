@@ -0,0 +1,80 @@
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::DFSchemaRef;
+use datafusion::logical_expr::{LogicalPlan, UserDefinedLogicalNodeCore};
+use datafusion::prelude::Expr;
+
+/// The results of a plan read from the query cache, see [`crate::execution::query_cache`].
+#[derive(Clone)]
+pub struct CachedResultPlanNode {
+    /// The schema description of the output, same as the cached plan
+    pub schema: DFSchemaRef,
+    /// The number of the cached buckets
+    pub buckets: usize,
+    pub batches: Vec<RecordBatch>,
+}
+
+impl CachedResultPlanNode {
+    fn num_rows(&self) -> usize {
+        self.batches.iter().map(|b| b.num_rows()).sum()
+    }
+}
+
+impl Debug for CachedResultPlanNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_for_explain(f)
+    }
+}
+
+impl Hash for CachedResultPlanNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.schema.hash(state);
+        self.buckets.hash(state);
+        self.num_rows().hash(state);
+    }
+}
+
+impl PartialEq for CachedResultPlanNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.schema == other.schema
+            && self.buckets == other.buckets
+            && self.batches == other.batches
+    }
+}
+
+impl Eq for CachedResultPlanNode {}
+
+impl UserDefinedLogicalNodeCore for CachedResultPlanNode {
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "CachedResult: buckets={}, rows={}",
+            self.buckets,
+            self.num_rows()
+        )
+    }
+
+    fn from_template(&self, exprs: &[Expr], inputs: &[LogicalPlan]) -> Self {
+        assert_eq!(inputs.len(), 0, "input size inconsistent");
+        assert_eq!(exprs.len(), 0, "expr size inconsistent");
+        self.clone()
+    }
+
+    fn name(&self) -> &str {
+        "CachedResult"
+    }
+}
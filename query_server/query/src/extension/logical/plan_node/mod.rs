@@ -6,6 +6,8 @@ use datafusion::prelude::Expr;
 
 use crate::extension::expr::expr_rewriter::ExprReplacer;
 
+pub mod aggregate_scan;
+pub mod cached_result;
 pub mod expand;
 pub mod gapfill;
 pub mod stream_scan;
@@ -13,7 +13,7 @@ use datafusion::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
 use datafusion::physical_plan::{
     DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
 };
-use models::predicate::domain::{PredicateRef, PushedAggregateFunction};
+use models::predicate::domain::{PredicateRef, PushedAggregate};
 use models::predicate::PlacedSplit;
 use models::schema::TskvTableSchemaRef;
 use trace::{debug, SpanContext, SpanExt, SpanRecorder};
@@ -27,7 +27,7 @@ pub struct AggregateFilterTskvExec {
     coord: CoordinatorRef,
     schema: SchemaRef,
     table_schema: TskvTableSchemaRef,
-    pushed_aggs: Vec<PushedAggregateFunction>,
+    pushed_aggs: PushedAggregate,
     filter: PredicateRef,
     splits: Vec<PlacedSplit>,
     metrics: ExecutionPlanMetricsSet,
@@ -38,7 +38,7 @@ impl AggregateFilterTskvExec {
         coord: CoordinatorRef,
         schema: SchemaRef,
         table_schema: TskvTableSchemaRef,
-        pushed_aggs: Vec<PushedAggregateFunction>,
+        pushed_aggs: PushedAggregate,
         filter: PredicateRef,
         splits: Vec<PlacedSplit>,
     ) -> Self {
@@ -87,17 +87,6 @@ impl ExecutionPlan for AggregateFilterTskvExec {
         partition: usize,
         context: Arc<TaskContext>,
     ) -> Result<SendableRecordBatchStream> {
-        let mut agg_columns = Vec::with_capacity(self.pushed_aggs.len());
-        for agg in self.pushed_aggs.iter() {
-            match agg {
-                PushedAggregateFunction::Count(column) => {
-                    if let Some(col) = self.table_schema.column(column) {
-                        agg_columns.push(col.clone());
-                    }
-                }
-            }
-        }
-
         let split = unsafe {
             debug_assert!(partition < self.splits.len(), "Partition not exists");
             self.splits.get_unchecked(partition).clone()
@@ -108,7 +97,7 @@ impl ExecutionPlan for AggregateFilterTskvExec {
         let query_opt = QueryOption::new(
             100_usize,
             split,
-            Some(agg_columns),
+            Some(self.pushed_aggs.clone()),
             self.schema.clone(),
             self.table_schema.clone(),
         );
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::{LogicalPlan, UserDefinedLogicalNode};
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::physical_planner::{ExtensionPlanner, PhysicalPlanner};
+
+use crate::extension::logical::plan_node::aggregate_scan::AggregateScanPlanNode;
+
+/// Physical planner for AggregateScan nodes
+pub struct AggregateScanPlanner {}
+
+#[async_trait]
+impl ExtensionPlanner for AggregateScanPlanner {
+    /// Create a physical plan for an extension node
+    async fn plan_extension(
+        &self,
+        _planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        _logical_inputs: &[&LogicalPlan],
+        _physical_inputs: &[Arc<dyn ExecutionPlan>],
+        session_state: &SessionState,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        let res = if let Some(AggregateScanPlanNode {
+            table_name: _,
+            source,
+            schema,
+            filters,
+            aggregate,
+        }) = as_aggregate_scan_plan_node(node)
+        {
+            let aggregate_scan = source
+                .create_aggregate_scan_physical_plan(
+                    session_state,
+                    schema.as_ref().into(),
+                    filters,
+                    aggregate.clone(),
+                )
+                .await?;
+
+            Some(aggregate_scan)
+        } else {
+            None
+        };
+        Ok(res)
+    }
+}
+
+fn as_aggregate_scan_plan_node(
+    node: &dyn UserDefinedLogicalNode,
+) -> Option<&AggregateScanPlanNode> {
+    node.as_any().downcast_ref::<AggregateScanPlanNode>()
+}
@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::arrow::datatypes::Schema;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::{LogicalPlan, UserDefinedLogicalNode};
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::physical_planner::{ExtensionPlanner, PhysicalPlanner};
+
+use crate::extension::logical::plan_node::cached_result::CachedResultPlanNode;
+
+/// Physical planner for CachedResult nodes
+pub struct CachedResultPlanner {}
+
+#[async_trait]
+impl ExtensionPlanner for CachedResultPlanner {
+    /// Create a physical plan for an extension node
+    async fn plan_extension(
+        &self,
+        _planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        _logical_inputs: &[&LogicalPlan],
+        _physical_inputs: &[Arc<dyn ExecutionPlan>],
+        _session_state: &SessionState,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        let res = if let Some(CachedResultPlanNode {
+            schema, batches, ..
+        }) = as_cached_result_plan_node(node)
+        {
+            let schema = Arc::new(Schema::from(schema.as_ref()));
+            let exec: Arc<dyn ExecutionPlan> =
+                Arc::new(MemoryExec::try_new(&[batches.clone()], schema, None)?);
+            Some(exec)
+        } else {
+            None
+        };
+        Ok(res)
+    }
+}
+
+fn as_cached_result_plan_node(node: &dyn UserDefinedLogicalNode) -> Option<&CachedResultPlanNode> {
+    node.as_any().downcast_ref::<CachedResultPlanNode>()
+}
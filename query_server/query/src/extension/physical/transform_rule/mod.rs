@@ -1,4 +1,6 @@
 //! logical paln to physical plan transform rule
+pub mod aggregate_scan;
+pub mod cached_result;
 pub mod expand;
 pub mod gapfill;
 pub mod stream_scan;
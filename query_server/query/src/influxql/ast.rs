@@ -0,0 +1,189 @@
+//! AST of the supported subset of InfluxQL.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Select(SelectStatement),
+    /// `CREATE DATABASE <name>`
+    CreateDatabase(String),
+    /// `SHOW DATABASES`
+    ShowDatabases,
+    /// `SHOW MEASUREMENTS [LIMIT <n>]`
+    ShowMeasurements {
+        limit: Option<u64>,
+    },
+    /// `SHOW TAG KEYS [FROM <measurement>]`
+    ShowTagKeys {
+        from: Option<Measurement>,
+    },
+    /// `SHOW TAG VALUES FROM <measurement> WITH KEY = <tag>`
+    ShowTagValues {
+        from: Measurement,
+        key: String,
+    },
+    /// `SHOW FIELD KEYS [FROM <measurement>]`
+    ShowFieldKeys {
+        from: Option<Measurement>,
+    },
+}
+
+/// `[<database>.[<retention policy>].]<measurement>`, retention policies are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    pub database: Option<String>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectStatement {
+    pub fields: Vec<Field>,
+    pub from: Measurement,
+    pub condition: Option<Expr>,
+    pub group_by: GroupBy,
+    pub fill: Fill,
+    /// `ORDER BY time DESC`
+    pub order_desc: bool,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub expr: Expr,
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroupBy {
+    pub time: Option<TimeDimension>,
+    pub tags: Vec<String>,
+}
+
+/// `time(<interval>[, <offset>])`, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeDimension {
+    pub interval: i64,
+    pub offset: i64,
+}
+
+/// How to fill the windows of `GROUP BY time(...)` without data.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Fill {
+    #[default]
+    Null,
+    None,
+    Previous,
+    Linear,
+    Value(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// `*`
+    Wildcard,
+    Ident(String),
+    Str(String),
+    Integer(i64),
+    Number(f64),
+    Bool(bool),
+    /// Duration in nanoseconds.
+    Duration(i64),
+    /// `/<pattern>/`
+    Regex(String),
+    Call {
+        name: String,
+        args: Vec<Expr>,
+    },
+    Binary {
+        left: Box<Expr>,
+        op: BinaryOp,
+        right: Box<Expr>,
+    },
+}
+
+impl Expr {
+    pub fn binary(left: Expr, op: BinaryOp, right: Expr) -> Self {
+        Self::Binary {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        }
+    }
+
+    pub fn is_time(&self) -> bool {
+        matches!(self, Self::Ident(name) if name.eq_ignore_ascii_case("time"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    RegexMatch,
+    RegexNotMatch,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Self::Or => 1,
+            Self::And => 2,
+            Self::Eq
+            | Self::NotEq
+            | Self::Lt
+            | Self::LtEq
+            | Self::Gt
+            | Self::GtEq
+            | Self::RegexMatch
+            | Self::RegexNotMatch => 3,
+            Self::Add | Self::Sub => 4,
+            Self::Mul | Self::Div => 5,
+        }
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        self.precedence() == 3
+    }
+
+    /// The operator if the operands are swapped.
+    pub fn swap(&self) -> Option<Self> {
+        let op = match self {
+            Self::Eq => Self::Eq,
+            Self::NotEq => Self::NotEq,
+            Self::Lt => Self::Gt,
+            Self::LtEq => Self::GtEq,
+            Self::Gt => Self::Lt,
+            Self::GtEq => Self::LtEq,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Eq => "=",
+            Self::NotEq => "!=",
+            Self::Lt => "<",
+            Self::LtEq => "<=",
+            Self::Gt => ">",
+            Self::GtEq => ">=",
+            Self::RegexMatch => "~",
+            Self::RegexNotMatch => "!~",
+            Self::And => "AND",
+            Self::Or => "OR",
+        }
+    }
+}
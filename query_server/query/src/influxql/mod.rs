@@ -0,0 +1,67 @@
+//! Compatibility layer of the InfluxDB `/query` API.
+//!
+//! Only a core subset of InfluxQL is supported, statements are translated to SQL
+//! and the results are converted to the InfluxDB JSON format.
+
+use spi::server::dbms::DBMSRef;
+use spi::service::protocol::{Context, Query};
+use spi::Result;
+use trace::SpanContext;
+
+use self::ast::Statement;
+use self::response::{build_series, Epoch, QueryResponse, Series, StatementResult};
+
+pub mod ast;
+pub mod parser;
+pub mod planner;
+pub mod response;
+
+/// Executes all statements of the query in order.
+///
+/// Returns an error if the query can't be parsed, errors of each statement are
+/// returned in the result of the statement as InfluxDB does.
+pub async fn execute(
+    dbms: &DBMSRef,
+    ctx: &Context,
+    query: &str,
+    epoch: Option<Epoch>,
+    span_ctx: Option<&SpanContext>,
+) -> Result<QueryResponse> {
+    let statements = parser::parse(query)?;
+
+    let mut response = QueryResponse::default();
+    for (statement_id, statement) in statements.iter().enumerate() {
+        let result = match execute_statement(dbms, ctx, statement, epoch, span_ctx).await {
+            Ok(series) => StatementResult {
+                statement_id,
+                series,
+                error: None,
+            },
+            Err(err) => StatementResult {
+                statement_id,
+                series: vec![],
+                error: Some(err.to_string()),
+            },
+        };
+        response.results.push(result);
+    }
+
+    Ok(response)
+}
+
+async fn execute_statement(
+    dbms: &DBMSRef,
+    ctx: &Context,
+    statement: &Statement,
+    epoch: Option<Epoch>,
+    span_ctx: Option<&SpanContext>,
+) -> Result<Vec<Series>> {
+    let planned = planner::plan(statement, ctx.database())?;
+
+    let query = Query::new(ctx.clone(), planned.sql);
+    let output = dbms.execute(&query, span_ctx).await?.result();
+    let schema = output.schema();
+    let batches = output.chunk_result().await?;
+
+    build_series(&planned.kind, schema, &batches, epoch)
+}
@@ -0,0 +1,863 @@
+//! A hand-written parser of the subset of InfluxQL that is supported by [`super::planner`].
+
+use spi::{QueryError, Result};
+
+use super::ast::{
+    BinaryOp, Expr, Field, Fill, GroupBy, Measurement, SelectStatement, Statement, TimeDimension,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Unquoted identifier or keyword.
+    Ident(String),
+    /// Double quoted identifier, never a keyword.
+    QuotedIdent(String),
+    Str(String),
+    Integer(i64),
+    Number(f64),
+    /// Duration in nanoseconds.
+    Duration(i64),
+    Regex(String),
+    LParen,
+    RParen,
+    Comma,
+    Dot,
+    Semicolon,
+    Mul,
+    Add,
+    Sub,
+    Div,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    RegexMatch,
+    RegexNotMatch,
+}
+
+impl Token {
+    fn binary_op(&self) -> Option<BinaryOp> {
+        let op = match self {
+            Self::Add => BinaryOp::Add,
+            Self::Sub => BinaryOp::Sub,
+            Self::Mul => BinaryOp::Mul,
+            Self::Div => BinaryOp::Div,
+            Self::Eq => BinaryOp::Eq,
+            Self::NotEq => BinaryOp::NotEq,
+            Self::Lt => BinaryOp::Lt,
+            Self::LtEq => BinaryOp::LtEq,
+            Self::Gt => BinaryOp::Gt,
+            Self::GtEq => BinaryOp::GtEq,
+            Self::RegexMatch => BinaryOp::RegexMatch,
+            Self::RegexNotMatch => BinaryOp::RegexNotMatch,
+            Self::Ident(s) if s.eq_ignore_ascii_case("AND") => BinaryOp::And,
+            Self::Ident(s) if s.eq_ignore_ascii_case("OR") => BinaryOp::Or,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Self::Ident(s) if s.eq_ignore_ascii_case(keyword))
+    }
+}
+
+fn syntax_error(reason: impl Into<String>) -> QueryError {
+    QueryError::InvalidInfluxQL {
+        reason: reason.into(),
+    }
+}
+
+/// Parse statements separated by `;`.
+pub fn parse(input: &str) -> Result<Vec<Statement>> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, pos: 0 };
+
+    let mut statements = vec![];
+    loop {
+        while parser.consume(&Token::Semicolon) {}
+        if parser.peek().is_none() {
+            break;
+        }
+        statements.push(parser.parse_statement()?);
+        match parser.peek() {
+            None | Some(Token::Semicolon) => {}
+            Some(token) => return Err(syntax_error(format!("unexpected {token:?}"))),
+        }
+    }
+    if statements.is_empty() {
+        return Err(syntax_error("missing query"));
+    }
+
+    Ok(statements)
+}
+
+/// Parse a duration like `1h30m`, returns nanoseconds.
+pub fn parse_duration(input: &str) -> Result<i64> {
+    match tokenize(input)?.as_slice() {
+        [Token::Duration(d)] => Ok(*d),
+        _ => Err(syntax_error(format!("invalid duration: {input}"))),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            '.' if !next.map_or(false, |n| n.is_ascii_digit()) => Token::Dot,
+            ';' => Token::Semicolon,
+            // Skip the type cast of variables, e.g. `"value"::field`.
+            ':' if next == Some(':') => {
+                i += 2;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                continue;
+            }
+            '*' => Token::Mul,
+            '+' => Token::Add,
+            '-' => Token::Sub,
+            // A regular expression is only expected after `=~` or `!~`.
+            '/' if matches!(
+                tokens.last(),
+                Some(Token::RegexMatch | Token::RegexNotMatch)
+            ) =>
+            {
+                let (s, end) = lex_regex(&chars, i)?;
+                tokens.push(Token::Regex(s));
+                i = end;
+                continue;
+            }
+            '/' => Token::Div,
+            '=' => match next {
+                Some('~') => {
+                    i += 1;
+                    Token::RegexMatch
+                }
+                _ => Token::Eq,
+            },
+            '!' => match next {
+                Some('=') => {
+                    i += 1;
+                    Token::NotEq
+                }
+                Some('~') => {
+                    i += 1;
+                    Token::RegexNotMatch
+                }
+                _ => return Err(syntax_error("unexpected character after '!'")),
+            },
+            '<' => match next {
+                Some('=') => {
+                    i += 1;
+                    Token::LtEq
+                }
+                Some('>') => {
+                    i += 1;
+                    Token::NotEq
+                }
+                _ => Token::Lt,
+            },
+            '>' => match next {
+                Some('=') => {
+                    i += 1;
+                    Token::GtEq
+                }
+                _ => Token::Gt,
+            },
+            '"' | '\'' => {
+                let (s, end) = lex_string(&chars, i)?;
+                tokens.push(if c == '"' {
+                    Token::QuotedIdent(s)
+                } else {
+                    Token::Str(s)
+                });
+                i = end;
+                continue;
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let (token, end) = lex_number_or_duration(&chars, i)?;
+                tokens.push(token);
+                i = end;
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+                continue;
+            }
+            c => return Err(syntax_error(format!("unexpected character: {c:?}"))),
+        };
+        tokens.push(token);
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+/// Returns the unescaped string and the position after the closing quote.
+fn lex_string(chars: &[char], start: usize) -> Result<(String, usize)> {
+    let quote = chars[start];
+    let mut s = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == quote {
+            return Ok((s, i + 1));
+        }
+        if c == '\\' {
+            i += 1;
+            let escaped = chars
+                .get(i)
+                .ok_or_else(|| syntax_error("unterminated quoted string"))?;
+            match escaped {
+                'n' => s.push('\n'),
+                't' => s.push('\t'),
+                '\\' | '"' | '\'' => s.push(*escaped),
+                other => {
+                    s.push('\\');
+                    s.push(*other);
+                }
+            }
+        } else {
+            s.push(c);
+        }
+        i += 1;
+    }
+
+    Err(syntax_error("unterminated quoted string"))
+}
+
+/// Returns the pattern between `/` and the position after the closing `/`,
+/// only `\/` is unescaped.
+fn lex_regex(chars: &[char], start: usize) -> Result<(String, usize)> {
+    let mut s = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        match (chars[i], chars.get(i + 1)) {
+            ('/', _) => return Ok((s, i + 1)),
+            ('\\', Some('/')) => {
+                s.push('/');
+                i += 1;
+            }
+            (c, _) => s.push(c),
+        }
+        i += 1;
+    }
+
+    Err(syntax_error("unterminated regular expression"))
+}
+
+/// Returns a number or a duration like `1h30m`, and the position after it.
+fn lex_number_or_duration(chars: &[char], start: usize) -> Result<(Token, usize)> {
+    let digits_end = |mut i: usize| {
+        while i < chars.len() && chars[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+    // Returns the nanoseconds of the unit and the length of it.
+    let unit_at = |i: usize| -> Option<(i64, usize)> {
+        let (unit, len) = match (chars.get(i), chars.get(i + 1)) {
+            (Some('n'), Some('s')) => (1, 2),
+            (Some('u' | 'µ'), _) => (1_000, 1),
+            (Some('m'), Some('s')) => (1_000_000, 2),
+            (Some('s'), _) => (1_000_000_000, 1),
+            (Some('m'), _) => (60_000_000_000, 1),
+            (Some('h'), _) => (3_600_000_000_000, 1),
+            (Some('d'), _) => (86_400_000_000_000, 1),
+            (Some('w'), _) => (7 * 86_400_000_000_000, 1),
+            _ => return None,
+        };
+        // The unit must not be followed by other letters, e.g. `1mo`.
+        match chars.get(i + len) {
+            Some(c) if c.is_alphabetic() => None,
+            _ => Some((unit, len)),
+        }
+    };
+
+    let mut i = digits_end(start);
+    if i > start && unit_at(i).is_some() {
+        let mut duration = 0_i64;
+        let mut component_start = start;
+        loop {
+            let (unit, len) = match unit_at(i) {
+                Some(unit) => unit,
+                None => break,
+            };
+            let value = chars[component_start..i]
+                .iter()
+                .collect::<String>()
+                .parse::<i64>()
+                .map_err(|e| syntax_error(format!("invalid duration: {e}")))?;
+            duration = value
+                .checked_mul(unit)
+                .and_then(|v| duration.checked_add(v))
+                .ok_or_else(|| syntax_error("overflowed duration"))?;
+            i += len;
+            let end = digits_end(i);
+            if end == i || unit_at(end).is_none() {
+                break;
+            }
+            component_start = i;
+            i = end;
+        }
+        return Ok((Token::Duration(duration), i));
+    }
+
+    let mut is_float = false;
+    if chars.get(i) == Some(&'.') {
+        is_float = true;
+        i = digits_end(i + 1);
+    }
+    if matches!(chars.get(i), Some('e' | 'E')) {
+        let exp_start = match chars.get(i + 1) {
+            Some('+' | '-') => i + 2,
+            _ => i + 1,
+        };
+        let exp_end = digits_end(exp_start);
+        if exp_end > exp_start {
+            is_float = true;
+            i = exp_end;
+        }
+    }
+    let literal = chars[start..i].iter().collect::<String>();
+    let token = if is_float {
+        Token::Number(
+            literal
+                .parse::<f64>()
+                .map_err(|e| syntax_error(format!("invalid number {literal}: {e}")))?,
+        )
+    } else {
+        Token::Integer(
+            literal
+                .parse::<i64>()
+                .map_err(|e| syntax_error(format!("invalid integer {literal}: {e}")))?,
+        )
+    };
+
+    Ok((token, i))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn consume(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn consume_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek().map_or(false, |t| t.is_keyword(keyword)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<()> {
+        match self.next() {
+            Some(t) if t == token => Ok(()),
+            other => Err(syntax_error(format!("expected {token:?}, found {other:?}"))),
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.consume_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(syntax_error(format!(
+                "expected {keyword}, found {:?}",
+                self.peek()
+            )))
+        }
+    }
+
+    fn parse_ident(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Token::Ident(s) | Token::QuotedIdent(s)) => Ok(s),
+            other => Err(syntax_error(format!(
+                "expected identifier, found {other:?}"
+            ))),
+        }
+    }
+
+    fn parse_unsigned(&mut self) -> Result<u64> {
+        match self.next() {
+            Some(Token::Integer(n)) if n >= 0 => Ok(n as u64),
+            other => Err(syntax_error(format!("expected integer, found {other:?}"))),
+        }
+    }
+
+    fn parse_statement(&mut self) -> Result<Statement> {
+        if self.consume_keyword("SELECT") {
+            return self.parse_select().map(Statement::Select);
+        }
+        if self.consume_keyword("CREATE") {
+            self.expect_keyword("DATABASE")?;
+            return self.parse_ident().map(Statement::CreateDatabase);
+        }
+        if self.consume_keyword("SHOW") {
+            return self.parse_show();
+        }
+
+        Err(syntax_error(format!(
+            "unsupported statement starting with {:?}",
+            self.peek()
+        )))
+    }
+
+    fn parse_show(&mut self) -> Result<Statement> {
+        if self.consume_keyword("DATABASES") {
+            return Ok(Statement::ShowDatabases);
+        }
+        if self.consume_keyword("MEASUREMENTS") {
+            let limit = if self.consume_keyword("LIMIT") {
+                Some(self.parse_unsigned()?)
+            } else {
+                None
+            };
+            return Ok(Statement::ShowMeasurements { limit });
+        }
+        if self.consume_keyword("FIELD") {
+            self.expect_keyword("KEYS")?;
+            let from = self.parse_optional_from()?;
+            return Ok(Statement::ShowFieldKeys { from });
+        }
+        if self.consume_keyword("TAG") {
+            if self.consume_keyword("KEYS") {
+                let from = self.parse_optional_from()?;
+                return Ok(Statement::ShowTagKeys { from });
+            }
+            self.expect_keyword("VALUES")?;
+            let from = self
+                .parse_optional_from()?
+                .ok_or_else(|| syntax_error("SHOW TAG VALUES without FROM is not supported"))?;
+            self.expect_keyword("WITH")?;
+            self.expect_keyword("KEY")?;
+            self.expect(Token::Eq)?;
+            let key = self.parse_ident()?;
+            return Ok(Statement::ShowTagValues { from, key });
+        }
+
+        Err(syntax_error(format!(
+            "unsupported SHOW statement: {:?}",
+            self.peek()
+        )))
+    }
+
+    fn parse_optional_from(&mut self) -> Result<Option<Measurement>> {
+        if self.consume_keyword("FROM") {
+            self.parse_measurement().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// `<measurement>`, `<retention policy>.<measurement>`,
+    /// `<database>.<retention policy>.<measurement>` or `<database>..<measurement>`
+    fn parse_measurement(&mut self) -> Result<Measurement> {
+        if let Some(Token::Div) = self.peek() {
+            return Err(syntax_error(
+                "regular expressions of measurements are not supported",
+            ));
+        }
+        let mut parts = vec![Some(self.parse_ident()?)];
+        while self.consume(&Token::Dot) {
+            if self.peek() == Some(&Token::Dot) {
+                parts.push(None);
+                continue;
+            }
+            parts.push(Some(self.parse_ident()?));
+        }
+
+        match parts.as_slice() {
+            [Some(name)] | [Some(_), Some(name)] => Ok(Measurement {
+                database: None,
+                name: name.clone(),
+            }),
+            [Some(database), _, Some(name)] => Ok(Measurement {
+                database: Some(database.clone()),
+                name: name.clone(),
+            }),
+            _ => Err(syntax_error("invalid measurement")),
+        }
+    }
+
+    fn parse_select(&mut self) -> Result<SelectStatement> {
+        let mut fields = vec![];
+        loop {
+            let expr = self.parse_expr(0)?;
+            let alias = if self.consume_keyword("AS") {
+                Some(self.parse_ident()?)
+            } else {
+                None
+            };
+            fields.push(Field { expr, alias });
+            if !self.consume(&Token::Comma) {
+                break;
+            }
+        }
+
+        self.expect_keyword("FROM")?;
+        let from = self.parse_measurement()?;
+        if self.peek() == Some(&Token::Comma) {
+            return Err(syntax_error(
+                "selecting from multiple measurements is not supported",
+            ));
+        }
+
+        let condition = if self.consume_keyword("WHERE") {
+            Some(self.parse_expr(0)?)
+        } else {
+            None
+        };
+
+        let mut group_by = GroupBy::default();
+        if self.consume_keyword("GROUP") {
+            self.expect_keyword("BY")?;
+            loop {
+                if self.peek().map_or(false, |t| t.is_keyword("time"))
+                    && self.peek_nth(1) == Some(&Token::LParen)
+                {
+                    self.pos += 2;
+                    if group_by.time.is_some() {
+                        return Err(syntax_error("multiple time dimensions"));
+                    }
+                    group_by.time = Some(self.parse_time_dimension()?);
+                } else if self.peek() == Some(&Token::Mul) {
+                    return Err(syntax_error("GROUP BY * is not supported"));
+                } else {
+                    group_by.tags.push(self.parse_ident()?);
+                }
+                if !self.consume(&Token::Comma) {
+                    break;
+                }
+            }
+        }
+
+        let mut fill = Fill::default();
+        if self.consume_keyword("FILL") {
+            self.expect(Token::LParen)?;
+            fill = match self.next() {
+                Some(t) if t.is_keyword("null") => Fill::Null,
+                Some(t) if t.is_keyword("none") => Fill::None,
+                Some(t) if t.is_keyword("previous") => Fill::Previous,
+                Some(t) if t.is_keyword("linear") => Fill::Linear,
+                Some(Token::Integer(n)) => Fill::Value(n as f64),
+                Some(Token::Number(n)) => Fill::Value(n),
+                Some(Token::Sub) => match self.next() {
+                    Some(Token::Integer(n)) => Fill::Value(-n as f64),
+                    Some(Token::Number(n)) => Fill::Value(-n),
+                    other => return Err(syntax_error(format!("invalid fill: {other:?}"))),
+                },
+                other => return Err(syntax_error(format!("invalid fill: {other:?}"))),
+            };
+            self.expect(Token::RParen)?;
+        }
+
+        let mut order_desc = false;
+        if self.consume_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            match self.next() {
+                Some(Token::Ident(s) | Token::QuotedIdent(s)) if s.eq_ignore_ascii_case("time") => {
+                }
+                other => {
+                    return Err(syntax_error(format!(
+                        "only ORDER BY time is supported, found {other:?}"
+                    )))
+                }
+            }
+            if self.consume_keyword("DESC") {
+                order_desc = true;
+            } else {
+                self.consume_keyword("ASC");
+            }
+        }
+
+        let mut limit = None;
+        let mut offset = None;
+        loop {
+            if self.consume_keyword("LIMIT") {
+                limit = Some(self.parse_unsigned()?);
+            } else if self.consume_keyword("OFFSET") {
+                offset = Some(self.parse_unsigned()?);
+            } else if self.peek().map_or(false, |t| {
+                t.is_keyword("SLIMIT") || t.is_keyword("SOFFSET") || t.is_keyword("tz")
+            }) {
+                return Err(syntax_error(format!("{:?} is not supported", self.peek())));
+            } else {
+                break;
+            }
+        }
+
+        Ok(SelectStatement {
+            fields,
+            from,
+            condition,
+            group_by,
+            fill,
+            order_desc,
+            limit,
+            offset,
+        })
+    }
+
+    /// After `time(`: `<interval>[, <offset>])`
+    fn parse_time_dimension(&mut self) -> Result<TimeDimension> {
+        let interval = match self.next() {
+            Some(Token::Duration(d)) if d > 0 => d,
+            other => {
+                return Err(syntax_error(format!(
+                    "time dimension expects a positive duration, found {other:?}"
+                )))
+            }
+        };
+        let mut offset = 0;
+        if self.consume(&Token::Comma) {
+            let negative = self.consume(&Token::Sub);
+            offset = match self.next() {
+                Some(Token::Duration(d)) => d,
+                other => {
+                    return Err(syntax_error(format!(
+                        "time dimension offset expects a duration, found {other:?}"
+                    )))
+                }
+            };
+            if negative {
+                offset = -offset;
+            }
+        }
+        self.expect(Token::RParen)?;
+
+        Ok(TimeDimension { interval, offset })
+    }
+
+    /// Precedence climbing, operators with precedence lower than `min_precedence` are left.
+    fn parse_expr(&mut self, min_precedence: u8) -> Result<Expr> {
+        let mut left = self.parse_primary()?;
+        loop {
+            let op = match self.peek().and_then(Token::binary_op) {
+                Some(op) if op.precedence() > min_precedence => op,
+                _ => break,
+            };
+            self.pos += 1;
+            let right = self.parse_expr(op.precedence())?;
+            left = Expr::binary(left, op, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        let expr = match self.next() {
+            Some(Token::Mul) => Expr::Wildcard,
+            Some(Token::Str(s)) => Expr::Str(s),
+            Some(Token::Integer(n)) => Expr::Integer(n),
+            Some(Token::Number(n)) => Expr::Number(n),
+            Some(Token::Duration(d)) => Expr::Duration(d),
+            Some(Token::Regex(r)) => Expr::Regex(r),
+            Some(Token::Sub) => match self.parse_primary()? {
+                Expr::Integer(n) => Expr::Integer(-n),
+                Expr::Number(n) => Expr::Number(-n),
+                Expr::Duration(d) => Expr::Duration(-d),
+                other => Expr::binary(Expr::Integer(0), BinaryOp::Sub, other),
+            },
+            Some(Token::LParen) => {
+                let expr = self.parse_expr(0)?;
+                self.expect(Token::RParen)?;
+                expr
+            }
+            Some(Token::QuotedIdent(s)) => Expr::Ident(s),
+            Some(Token::Ident(s)) if self.peek() == Some(&Token::LParen) => {
+                self.pos += 1;
+                let mut args = vec![];
+                if !self.consume(&Token::RParen) {
+                    loop {
+                        args.push(self.parse_expr(0)?);
+                        if !self.consume(&Token::RParen) {
+                            self.expect(Token::Comma)?;
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                Expr::Call {
+                    name: s.to_ascii_lowercase(),
+                    args,
+                }
+            }
+            Some(Token::Ident(s)) if s.eq_ignore_ascii_case("true") => Expr::Bool(true),
+            Some(Token::Ident(s)) if s.eq_ignore_ascii_case("false") => Expr::Bool(false),
+            Some(Token::Ident(s)) => Expr::Ident(s),
+            other => return Err(syntax_error(format!("unexpected {other:?}"))),
+        };
+        Ok(expr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, parse_duration};
+    use crate::influxql::ast::{
+        BinaryOp, Expr, Field, Fill, GroupBy, Measurement, SelectStatement, Statement,
+        TimeDimension,
+    };
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("10s").unwrap(), 10_000_000_000);
+        assert_eq!(parse_duration("1h30m").unwrap(), 5_400_000_000_000);
+        assert_eq!(parse_duration("100ms").unwrap(), 100_000_000);
+        assert_eq!(parse_duration("5u").unwrap(), 5_000);
+        assert_eq!(parse_duration("1ns").unwrap(), 1);
+        assert!(parse_duration("10").is_err());
+    }
+
+    #[test]
+    fn test_parse_select() {
+        let statements = parse(
+            "SELECT mean(\"value\") AS avg FROM \"telegraf\".\"autogen\".\"cpu\" \
+             WHERE time >= now() - 1h AND \"host\" =~ /^server\\/[0-9]+$/ \
+             GROUP BY time(10s), \"host\" fill(none) ORDER BY time DESC LIMIT 10",
+        )
+        .unwrap();
+        let time = Expr::Ident("time".to_string());
+        let now = Expr::Call {
+            name: "now".to_string(),
+            args: vec![],
+        };
+        let condition = Expr::binary(
+            Expr::binary(
+                time,
+                BinaryOp::GtEq,
+                Expr::binary(now, BinaryOp::Sub, Expr::Duration(3_600_000_000_000)),
+            ),
+            BinaryOp::And,
+            Expr::binary(
+                Expr::Ident("host".to_string()),
+                BinaryOp::RegexMatch,
+                Expr::Regex("^server/[0-9]+$".to_string()),
+            ),
+        );
+        assert_eq!(
+            statements,
+            vec![Statement::Select(SelectStatement {
+                fields: vec![Field {
+                    expr: Expr::Call {
+                        name: "mean".to_string(),
+                        args: vec![Expr::Ident("value".to_string())],
+                    },
+                    alias: Some("avg".to_string()),
+                }],
+                from: Measurement {
+                    database: Some("telegraf".to_string()),
+                    name: "cpu".to_string(),
+                },
+                condition: Some(condition),
+                group_by: GroupBy {
+                    time: Some(TimeDimension {
+                        interval: 10_000_000_000,
+                        offset: 0,
+                    }),
+                    tags: vec!["host".to_string()],
+                },
+                fill: Fill::None,
+                order_desc: true,
+                limit: Some(10),
+                offset: None,
+            })]
+        );
+    }
+
+    #[test]
+    fn test_parse_show() {
+        let statements = parse(
+            "SHOW DATABASES; SHOW MEASUREMENTS LIMIT 1; SHOW TAG KEYS FROM cpu; \
+             SHOW TAG VALUES FROM \"cpu\" WITH KEY = \"host\"; SHOW FIELD KEYS; \
+             CREATE DATABASE \"telegraf\"",
+        )
+        .unwrap();
+        let cpu = Measurement {
+            database: None,
+            name: "cpu".to_string(),
+        };
+        assert_eq!(
+            statements,
+            vec![
+                Statement::ShowDatabases,
+                Statement::ShowMeasurements { limit: Some(1) },
+                Statement::ShowTagKeys {
+                    from: Some(cpu.clone())
+                },
+                Statement::ShowTagValues {
+                    from: cpu,
+                    key: "host".to_string()
+                },
+                Statement::ShowFieldKeys { from: None },
+                Statement::CreateDatabase("telegraf".to_string()),
+            ]
+        );
+
+        let statements =
+            parse("SELECT \"value\"::field FROM cpu WHERE \"host\"::tag = 'a'").unwrap();
+        match &statements[0] {
+            Statement::Select(select) => {
+                assert_eq!(select.fields[0].expr, Expr::Ident("value".to_string()));
+                assert_eq!(
+                    select.condition,
+                    Some(Expr::binary(
+                        Expr::Ident("host".to_string()),
+                        BinaryOp::Eq,
+                        Expr::Str("a".to_string())
+                    ))
+                );
+            }
+            other => panic!("unexpected statement: {other:?}"),
+        }
+
+        assert!(parse("").is_err());
+        assert!(parse("DROP DATABASE telegraf").is_err());
+        assert!(parse("SELECT * FROM cpu GROUP BY *").is_err());
+    }
+}
@@ -0,0 +1,528 @@
+//! Translate InfluxQL statements to SQL of CnosDB.
+
+use spi::{QueryError, Result};
+
+use super::ast::{BinaryOp, Expr, Fill, Measurement, SelectStatement, Statement};
+
+/// The SQL of a statement and how to build the InfluxDB series from the result of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedStatement {
+    pub sql: String,
+    pub kind: ResultKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResultKind {
+    /// The statement returns no series.
+    Empty,
+    /// All columns are returned as one series.
+    Series { name: String },
+    /// The first column is the name of series, the other columns are returned.
+    SeriesByFirstColumn,
+    /// Rows of `SELECT`, series are split by the grouped tags, which are not returned as columns.
+    Select {
+        measurement: String,
+        tags: Vec<String>,
+    },
+}
+
+fn unsupported(reason: impl Into<String>) -> QueryError {
+    QueryError::InvalidInfluxQL {
+        reason: reason.into(),
+    }
+}
+
+/// `database` is the default database of statements.
+pub fn plan(statement: &Statement, database: &str) -> Result<PlannedStatement> {
+    let planned = match statement {
+        Statement::Select(select) => plan_select(select)?,
+        Statement::CreateDatabase(name) => PlannedStatement {
+            sql: format!("CREATE DATABASE IF NOT EXISTS {}", quote_identifier(name)),
+            kind: ResultKind::Empty,
+        },
+        Statement::ShowDatabases => PlannedStatement {
+            sql: "SELECT database_name AS name FROM information_schema.databases \
+                  ORDER BY database_name"
+                .to_string(),
+            kind: ResultKind::Series {
+                name: "databases".to_string(),
+            },
+        },
+        Statement::ShowMeasurements { limit } => PlannedStatement {
+            sql: format!(
+                "SELECT table_name AS name FROM information_schema.tables \
+                 WHERE table_database = {} AND table_engine = 'TSKV' ORDER BY table_name{}",
+                quote_literal(database),
+                limit.map(|n| format!(" LIMIT {n}")).unwrap_or_default()
+            ),
+            kind: ResultKind::Series {
+                name: "measurements".to_string(),
+            },
+        },
+        Statement::ShowTagKeys { from } => PlannedStatement {
+            sql: format!(
+                "SELECT table_name, column_name AS \"tagKey\" FROM information_schema.columns \
+                 WHERE {} ORDER BY table_name, column_name",
+                columns_filter(from.as_ref(), database, "TAG")
+            ),
+            kind: ResultKind::SeriesByFirstColumn,
+        },
+        Statement::ShowFieldKeys { from } => PlannedStatement {
+            sql: format!(
+                "SELECT table_name, column_name AS \"fieldKey\", \
+                 CASE data_type WHEN 'DOUBLE' THEN 'float' WHEN 'BIGINT' THEN 'integer' \
+                 WHEN 'BIGINT UNSIGNED' THEN 'unsigned' WHEN 'BOOLEAN' THEN 'boolean' \
+                 ELSE 'string' END AS \"fieldType\" FROM information_schema.columns \
+                 WHERE {} ORDER BY table_name, column_name",
+                columns_filter(from.as_ref(), database, "FIELD")
+            ),
+            kind: ResultKind::SeriesByFirstColumn,
+        },
+        Statement::ShowTagValues { from, key } => PlannedStatement {
+            sql: format!(
+                "SELECT DISTINCT {} AS key, {} AS value FROM {} WHERE {} IS NOT NULL ORDER BY value",
+                quote_literal(key),
+                quote_identifier(key),
+                table_reference(from),
+                quote_identifier(key)
+            ),
+            kind: ResultKind::Series {
+                name: from.name.clone(),
+            },
+        },
+    };
+
+    Ok(planned)
+}
+
+fn columns_filter(from: Option<&Measurement>, database: &str, column_type: &str) -> String {
+    let database = from.and_then(|m| m.database.as_deref()).unwrap_or(database);
+    let mut filter = format!(
+        "database_name = {} AND column_type = '{column_type}'",
+        quote_literal(database)
+    );
+    if let Some(from) = from {
+        filter.push_str(&format!(" AND table_name = {}", quote_literal(&from.name)));
+    }
+    filter
+}
+
+fn plan_select(select: &SelectStatement) -> Result<PlannedStatement> {
+    let aggregated = select.fields.iter().any(|f| has_aggregate(&f.expr));
+    if aggregated && !select.fields.iter().all(|f| is_aggregated(&f.expr)) {
+        return Err(unsupported(
+            "mixing aggregate and non-aggregate queries is not supported",
+        ));
+    }
+    if select.group_by.time.is_some() && !aggregated {
+        return Err(unsupported("GROUP BY time requires an aggregate function"));
+    }
+
+    let tags = select
+        .group_by
+        .tags
+        .iter()
+        .map(|t| quote_identifier(t))
+        .collect::<Vec<_>>();
+    let condition = select
+        .condition
+        .as_ref()
+        .map(|c| expr_to_sql(c, Fill::None))
+        .transpose()?;
+
+    // Empty windows are only filled if the time range is bounded.
+    let (lower_bound, has_upper_bound) = select
+        .condition
+        .as_ref()
+        .map(time_bounds)
+        .unwrap_or((None, false));
+    let gap_fill = select.fill != Fill::None && lower_bound.is_some() && has_upper_bound;
+    let fill = match select.fill {
+        Fill::Previous | Fill::Linear if !gap_fill => Fill::None,
+        fill => fill,
+    };
+
+    let time = match (&select.group_by.time, aggregated) {
+        (Some(dimension), _) => {
+            let interval = duration_to_sql(dimension.interval);
+            let origin = format!("CAST({} AS TIMESTAMP)", dimension.offset);
+            if gap_fill {
+                format!("time_window_gapfill(time, {interval}, {interval}, {origin})")
+            } else {
+                format!("date_bin({interval}, time, {origin})")
+            }
+        }
+        // The time of an aggregation is the start of the time range.
+        (None, true) => match lower_bound {
+            Some(lower_bound) => time_literal_to_sql(lower_bound, Fill::None)?,
+            None => "CAST(0 AS TIMESTAMP)".to_string(),
+        },
+        (None, false) => "time".to_string(),
+    };
+
+    let mut projection = vec![format!("{time} AS time")];
+    projection.extend(tags.iter().cloned());
+    if select.fields.len() == 1 && select.fields[0].expr == Expr::Wildcard {
+        if aggregated || !tags.is_empty() {
+            return Err(unsupported(
+                "SELECT * only supports raw queries without GROUP BY",
+            ));
+        }
+        projection = vec!["*".to_string()];
+    } else {
+        let mut names: Vec<String> = vec![];
+        for field in select.fields.iter() {
+            let base_name = field
+                .alias
+                .clone()
+                .unwrap_or_else(|| field_name(&field.expr));
+            // Duplicated names are suffixed by `_1`, `_2`, ...
+            let mut name = base_name.clone();
+            let mut i = 0;
+            while names.contains(&name) || name == "time" {
+                i += 1;
+                name = format!("{base_name}_{i}");
+            }
+            projection.push(format!(
+                "{} AS {}",
+                expr_to_sql(&field.expr, fill)?,
+                quote_identifier(&name)
+            ));
+            names.push(name);
+        }
+    }
+
+    let mut sql = format!(
+        "SELECT {} FROM {}",
+        projection.join(", "),
+        table_reference(&select.from)
+    );
+    if let Some(condition) = condition {
+        sql.push_str(&format!(" WHERE {condition}"));
+    }
+    let mut group_by = tags.clone();
+    if select.group_by.time.is_some() {
+        group_by.insert(0, time.clone());
+    }
+    if aggregated && !group_by.is_empty() {
+        sql.push_str(&format!(" GROUP BY {}", group_by.join(", ")));
+    }
+    let mut order_by = tags;
+    if !aggregated || select.group_by.time.is_some() {
+        order_by.push(format!(
+            "{time}{}",
+            if select.order_desc { " DESC" } else { "" }
+        ));
+    }
+    if !order_by.is_empty() {
+        sql.push_str(&format!(" ORDER BY {}", order_by.join(", ")));
+    }
+    if let Some(limit) = select.limit {
+        sql.push_str(&format!(" LIMIT {limit}"));
+    }
+    if let Some(offset) = select.offset {
+        sql.push_str(&format!(" OFFSET {offset}"));
+    }
+
+    Ok(PlannedStatement {
+        sql,
+        kind: ResultKind::Select {
+            measurement: select.from.name.clone(),
+            tags: select.group_by.tags.clone(),
+        },
+    })
+}
+
+/// Returns the lower bound of `time >= x`/`time > x` and whether `time <= y`/`time < y` exists
+/// in the top level conjunction of the condition.
+fn time_bounds(condition: &Expr) -> (Option<&Expr>, bool) {
+    match condition {
+        Expr::Binary {
+            left,
+            op: BinaryOp::And,
+            right,
+        } => {
+            let (left_lower, left_upper) = time_bounds(left);
+            let (right_lower, right_upper) = time_bounds(right);
+            (left_lower.or(right_lower), left_upper || right_upper)
+        }
+        Expr::Binary { left, op, right } => {
+            let (op, value) = match (left.as_ref(), right.as_ref()) {
+                (l, r) if l.is_time() => (*op, r),
+                (l, r) if r.is_time() => match op.swap() {
+                    Some(op) => (op, l),
+                    None => return (None, false),
+                },
+                _ => return (None, false),
+            };
+            match op {
+                BinaryOp::Gt | BinaryOp::GtEq => (Some(value), false),
+                BinaryOp::Lt | BinaryOp::LtEq => (None, true),
+                _ => (None, false),
+            }
+        }
+        _ => (None, false),
+    }
+}
+
+fn aggregate_function(name: &str) -> Option<&'static str> {
+    let function = match name {
+        "mean" => "avg",
+        "median" => "median",
+        "count" => "count",
+        "sum" => "sum",
+        "min" => "min",
+        "max" => "max",
+        "first" => "first",
+        "last" => "last",
+        "spread" => "spread",
+        "stddev" => "stddev",
+        "mode" => "mode",
+        "percentile" => "approx_percentile_cont",
+        _ => return None,
+    };
+    Some(function)
+}
+
+fn has_aggregate(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call { name, args } => {
+            aggregate_function(name).is_some() || args.iter().any(has_aggregate)
+        }
+        Expr::Binary { left, right, .. } => has_aggregate(left) || has_aggregate(right),
+        _ => false,
+    }
+}
+
+/// Whether all variables in the expression are arguments of aggregate functions.
+fn is_aggregated(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call { name, .. } if aggregate_function(name).is_some() => true,
+        Expr::Call { args, .. } => args.iter().all(is_aggregated),
+        Expr::Binary { left, right, .. } => is_aggregated(left) && is_aggregated(right),
+        Expr::Ident(_) | Expr::Wildcard => false,
+        _ => true,
+    }
+}
+
+/// The column name of a field without alias, the same as InfluxDB:
+/// the name of the first function, or the first variable.
+fn field_name(expr: &Expr) -> String {
+    fn find(expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Call { name, .. } => Some(name.clone()),
+            Expr::Ident(name) => Some(name.clone()),
+            Expr::Binary { left, right, .. } => find(left).or_else(|| find(right)),
+            _ => None,
+        }
+    }
+    find(expr).unwrap_or_else(|| "value".to_string())
+}
+
+fn expr_to_sql(expr: &Expr, fill: Fill) -> Result<String> {
+    let sql = match expr {
+        Expr::Wildcard => return Err(unsupported("unexpected wildcard")),
+        Expr::Ident(name) => quote_identifier(name),
+        Expr::Str(s) | Expr::Regex(s) => quote_literal(s),
+        Expr::Integer(n) => n.to_string(),
+        Expr::Number(n) => format!("{n:?}"),
+        Expr::Bool(b) => b.to_string(),
+        Expr::Duration(d) => duration_to_sql(*d),
+        Expr::Call { name, args } if name == "now" && args.is_empty() => "now()".to_string(),
+        Expr::Call { name, args } => match aggregate_function(name) {
+            Some(function) => aggregate_to_sql(name, function, args, fill)?,
+            None => format!(
+                "{}({})",
+                name,
+                args.iter()
+                    .map(|a| expr_to_sql(a, fill))
+                    .collect::<Result<Vec<_>>>()?
+                    .join(", ")
+            ),
+        },
+        // Time is compared with strings in RFC3339 and integers in nanoseconds.
+        Expr::Binary { left, op, right } if op.is_comparison() && left.is_time() => {
+            format!(
+                "(time {} {})",
+                op.as_sql(),
+                time_literal_to_sql(right, fill)?
+            )
+        }
+        Expr::Binary { left, op, right } if op.is_comparison() && right.is_time() => {
+            format!(
+                "({} {} time)",
+                time_literal_to_sql(left, fill)?,
+                op.as_sql()
+            )
+        }
+        Expr::Binary { left, op, right } => format!(
+            "({} {} {})",
+            expr_to_sql(left, fill)?,
+            op.as_sql(),
+            expr_to_sql(right, fill)?
+        ),
+    };
+
+    Ok(sql)
+}
+
+fn time_literal_to_sql(expr: &Expr, fill: Fill) -> Result<String> {
+    let sql = match expr {
+        Expr::Str(s) => format!("CAST({} AS TIMESTAMP)", quote_literal(s)),
+        Expr::Integer(n) | Expr::Duration(n) => format!("CAST({n} AS TIMESTAMP)"),
+        other => expr_to_sql(other, fill)?,
+    };
+    Ok(sql)
+}
+
+fn aggregate_to_sql(name: &str, function: &str, args: &[Expr], fill: Fill) -> Result<String> {
+    let field = match args.first() {
+        Some(Expr::Ident(field)) => quote_identifier(field),
+        _ => {
+            return Err(unsupported(format!(
+                "{name}() expects a field as the first argument"
+            )))
+        }
+    };
+    let aggregate = match (name, &args[1..]) {
+        ("first" | "last", []) => format!("{function}(time, {field})"),
+        ("spread", []) => format!("(max({field}) - min({field}))"),
+        ("percentile", [Expr::Integer(n)]) => format!("{function}({field}, {})", *n as f64 / 100.0),
+        ("percentile", [Expr::Number(n)]) => format!("{function}({field}, {})", n / 100.0),
+        (_, []) => format!("{function}({field})"),
+        _ => return Err(unsupported(format!("invalid arguments of {name}()"))),
+    };
+
+    let filled = match fill {
+        Fill::Previous => format!("locf({aggregate})"),
+        Fill::Linear => format!("interpolate({aggregate})"),
+        Fill::Value(v) => format!("coalesce({aggregate}, {v:?})"),
+        Fill::Null | Fill::None => aggregate,
+    };
+    Ok(filled)
+}
+
+/// `INTERVAL '<n> <unit>'` of the largest unit that the duration is a multiple of.
+fn duration_to_sql(nanos: i64) -> String {
+    const UNITS: [(i64, &str); 7] = [
+        (86_400_000_000_000, "day"),
+        (3_600_000_000_000, "hour"),
+        (60_000_000_000, "minute"),
+        (1_000_000_000, "second"),
+        (1_000_000, "millisecond"),
+        (1_000, "microsecond"),
+        (1, "nanosecond"),
+    ];
+    let (unit_nanos, unit) = UNITS
+        .iter()
+        .find(|(unit_nanos, _)| nanos % unit_nanos == 0)
+        .copied()
+        .unwrap_or((1, "nanosecond"));
+    format!("INTERVAL '{} {}'", nanos / unit_nanos, unit)
+}
+
+fn table_reference(measurement: &Measurement) -> String {
+    match &measurement.database {
+        Some(database) => format!(
+            "{}.{}",
+            quote_identifier(database),
+            quote_identifier(&measurement.name)
+        ),
+        None => quote_identifier(&measurement.name),
+    }
+}
+
+fn quote_identifier(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{plan, ResultKind};
+    use crate::influxql::parser::parse;
+
+    fn plan_sql(query: &str) -> String {
+        let statements = parse(query).unwrap();
+        plan(&statements[0], "public").unwrap().sql
+    }
+
+    #[test]
+    fn test_plan_select() {
+        assert_eq!(
+            plan_sql("SELECT value FROM cpu WHERE host = 'a' AND time > 1000 LIMIT 10"),
+            "SELECT time AS time, \"value\" AS \"value\" FROM \"cpu\" \
+             WHERE ((\"host\" = 'a') AND (time > CAST(1000 AS TIMESTAMP))) \
+             ORDER BY time LIMIT 10"
+        );
+
+        assert_eq!(
+            plan_sql(
+                "SELECT mean(value), max(value) * 2 AS m FROM cpu \
+                 WHERE time >= now() - 1h GROUP BY time(1m), host fill(none)"
+            ),
+            "SELECT date_bin(INTERVAL '1 minute', time, CAST(0 AS TIMESTAMP)) AS time, \"host\", \
+             avg(\"value\") AS \"mean\", (max(\"value\") * 2) AS \"m\" FROM \"cpu\" \
+             WHERE (time >= (now() - INTERVAL '1 hour')) \
+             GROUP BY date_bin(INTERVAL '1 minute', time, CAST(0 AS TIMESTAMP)), \"host\" \
+             ORDER BY \"host\", date_bin(INTERVAL '1 minute', time, CAST(0 AS TIMESTAMP))"
+        );
+
+        // Gaps are filled if the time range is bounded.
+        assert_eq!(
+            plan_sql(
+                "SELECT last(value) FROM cpu WHERE time >= '2023-01-01T00:00:00Z' \
+                 AND time < '2023-01-02T00:00:00Z' GROUP BY time(1h) fill(previous)"
+            ),
+            "SELECT time_window_gapfill(time, INTERVAL '1 hour', INTERVAL '1 hour', \
+             CAST(0 AS TIMESTAMP)) AS time, locf(last(time, \"value\")) AS \"last\" FROM \"cpu\" \
+             WHERE ((time >= CAST('2023-01-01T00:00:00Z' AS TIMESTAMP)) \
+             AND (time < CAST('2023-01-02T00:00:00Z' AS TIMESTAMP))) \
+             GROUP BY time_window_gapfill(time, INTERVAL '1 hour', INTERVAL '1 hour', \
+             CAST(0 AS TIMESTAMP)) ORDER BY time_window_gapfill(time, INTERVAL '1 hour', \
+             INTERVAL '1 hour', CAST(0 AS TIMESTAMP))"
+        );
+
+        // The time of an aggregation without GROUP BY time is the lower bound.
+        assert_eq!(
+            plan_sql("SELECT count(value), count(value) FROM cpu WHERE time > 0"),
+            "SELECT CAST(0 AS TIMESTAMP) AS time, count(\"value\") AS \"count\", \
+             count(\"value\") AS \"count_1\" FROM \"cpu\" WHERE (time > CAST(0 AS TIMESTAMP))"
+        );
+
+        let statements = parse("SELECT mean(value), value FROM cpu").unwrap();
+        assert!(plan(&statements[0], "public").is_err());
+        let statements = parse("SELECT value FROM cpu GROUP BY time(1m)").unwrap();
+        assert!(plan(&statements[0], "public").is_err());
+    }
+
+    #[test]
+    fn test_plan_show() {
+        let statements = parse("SHOW TAG VALUES FROM cpu WITH KEY = host").unwrap();
+        let planned = plan(&statements[0], "public").unwrap();
+        assert_eq!(
+            planned.sql,
+            "SELECT DISTINCT 'host' AS key, \"host\" AS value FROM \"cpu\" \
+             WHERE \"host\" IS NOT NULL ORDER BY value"
+        );
+        assert_eq!(
+            planned.kind,
+            ResultKind::Series {
+                name: "cpu".to_string()
+            }
+        );
+
+        let statements = parse("SHOW TAG KEYS FROM telegraf..cpu").unwrap();
+        let planned = plan(&statements[0], "public").unwrap();
+        assert_eq!(
+            planned.sql,
+            "SELECT table_name, column_name AS \"tagKey\" FROM information_schema.columns \
+             WHERE database_name = 'telegraf' AND column_type = 'TAG' AND table_name = 'cpu' \
+             ORDER BY table_name, column_name"
+        );
+        assert_eq!(planned.kind, ResultKind::SeriesByFirstColumn);
+    }
+}
@@ -0,0 +1,293 @@
+//! The JSON response of InfluxDB `/query`.
+
+use std::collections::BTreeMap;
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, SchemaRef, TimeUnit};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::scalar::ScalarValue;
+use models::schema::TIME_FIELD_NAME;
+use serde::Serialize;
+use serde_json::Value;
+use spi::{QueryError, Result};
+
+use super::planner::ResultKind;
+
+#[derive(Debug, Default, Serialize)]
+pub struct QueryResponse {
+    pub results: Vec<StatementResult>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct StatementResult {
+    pub statement_id: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub series: Vec<Series>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Series {
+    pub name: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+    pub columns: Vec<String>,
+    pub values: Vec<Vec<Value>>,
+}
+
+/// The `epoch` parameter of `/query`, timestamps are returned in RFC3339 if absent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Epoch {
+    Nanosecond,
+    Microsecond,
+    Millisecond,
+    Second,
+    Minute,
+    Hour,
+}
+
+impl Epoch {
+    pub fn parse(epoch: &str) -> Option<Self> {
+        let epoch = match epoch {
+            "ns" | "n" => Self::Nanosecond,
+            "u" | "us" | "µ" => Self::Microsecond,
+            "ms" => Self::Millisecond,
+            "s" => Self::Second,
+            "m" => Self::Minute,
+            "h" => Self::Hour,
+            _ => return None,
+        };
+        Some(epoch)
+    }
+
+    fn nanos(&self) -> i64 {
+        match self {
+            Self::Nanosecond => 1,
+            Self::Microsecond => 1_000,
+            Self::Millisecond => 1_000_000,
+            Self::Second => 1_000_000_000,
+            Self::Minute => 60_000_000_000,
+            Self::Hour => 3_600_000_000_000,
+        }
+    }
+}
+
+fn time_value(nanos: i64, epoch: Option<Epoch>) -> Value {
+    match epoch {
+        Some(epoch) => Value::from(nanos / epoch.nanos()),
+        None => Value::from(
+            Utc.timestamp_nanos(nanos)
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        ),
+    }
+}
+
+fn scalar_value(value: ScalarValue) -> Value {
+    match value {
+        ScalarValue::Boolean(Some(v)) => Value::from(v),
+        ScalarValue::Float32(Some(v)) => serde_json::Number::from_f64(v as f64)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        ScalarValue::Float64(Some(v)) => serde_json::Number::from_f64(v)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        ScalarValue::Int8(Some(v)) => Value::from(v),
+        ScalarValue::Int16(Some(v)) => Value::from(v),
+        ScalarValue::Int32(Some(v)) => Value::from(v),
+        ScalarValue::Int64(Some(v)) => Value::from(v),
+        ScalarValue::UInt8(Some(v)) => Value::from(v),
+        ScalarValue::UInt16(Some(v)) => Value::from(v),
+        ScalarValue::UInt32(Some(v)) => Value::from(v),
+        ScalarValue::UInt64(Some(v)) => Value::from(v),
+        ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => Value::from(v),
+        v if v.is_null() => Value::Null,
+        v => Value::from(v.to_string()),
+    }
+}
+
+/// Convert the result of a planned statement to InfluxDB series.
+pub fn build_series(
+    kind: &ResultKind,
+    schema: SchemaRef,
+    batches: &[RecordBatch],
+    epoch: Option<Epoch>,
+) -> Result<Vec<Series>> {
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|f| f.name().clone())
+        .collect::<Vec<_>>();
+
+    let (skip, tags, name) = match kind {
+        ResultKind::Empty => return Ok(vec![]),
+        ResultKind::Series { name } => (vec![], vec![], Some(name.clone())),
+        ResultKind::SeriesByFirstColumn => (vec![0], vec![], None),
+        ResultKind::Select { measurement, tags } => {
+            let indices =
+                tags.iter()
+                    .map(|t| {
+                        columns.iter().position(|c| c == t).ok_or_else(|| {
+                            QueryError::InvalidInfluxQL {
+                                reason: format!("tag {t} not found in result"),
+                            }
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+            (indices.clone(), indices, Some(measurement.clone()))
+        }
+    };
+    let time_index = columns.iter().position(|c| c == TIME_FIELD_NAME);
+    let output_columns = columns
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !skip.contains(i))
+        .map(|(_, c)| c.clone())
+        .collect::<Vec<_>>();
+
+    let mut series: Vec<Series> = vec![];
+    for batch in batches {
+        let mut arrays = batch.columns().to_vec();
+        if let Some(i) = time_index {
+            arrays[i] = cast(&arrays[i], &DataType::Timestamp(TimeUnit::Nanosecond, None))?;
+        }
+
+        for row in 0..batch.num_rows() {
+            let series_name = match &name {
+                Some(name) => name.clone(),
+                None => ScalarValue::try_from_array(&arrays[0], row)?.to_string(),
+            };
+            let mut series_tags = BTreeMap::new();
+            for &i in tags.iter() {
+                let value = ScalarValue::try_from_array(&arrays[i], row)?;
+                let value = if value.is_null() {
+                    String::new()
+                } else {
+                    value.to_string()
+                };
+                series_tags.insert(columns[i].clone(), value);
+            }
+
+            let mut values = Vec::with_capacity(output_columns.len());
+            for (i, array) in arrays.iter().enumerate() {
+                if skip.contains(&i) {
+                    continue;
+                }
+                let value = ScalarValue::try_from_array(array, row)?;
+                let value = match (Some(i) == time_index, value) {
+                    (true, ScalarValue::TimestampNanosecond(Some(nanos), _)) => {
+                        time_value(nanos, epoch)
+                    }
+                    (_, value) => scalar_value(value),
+                };
+                values.push(value);
+            }
+
+            // Rows are ordered by the series, so only the last series needs to be checked.
+            match series.last_mut() {
+                Some(last) if last.name == series_name && last.tags == series_tags => {
+                    last.values.push(values)
+                }
+                _ => series.push(Series {
+                    name: series_name,
+                    tags: series_tags,
+                    columns: output_columns.clone(),
+                    values: vec![values],
+                }),
+            }
+        }
+    }
+
+    Ok(series)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::{Float64Array, StringArray, TimestampNanosecondArray};
+    use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use datafusion::arrow::record_batch::RecordBatch;
+    use serde_json::json;
+
+    use super::{build_series, Epoch};
+    use crate::influxql::planner::ResultKind;
+
+    #[test]
+    fn test_build_series() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("host", DataType::Utf8, true),
+            Field::new("mean", DataType::Float64, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(TimestampNanosecondArray::from(vec![0, 60_000_000_000, 0])),
+                Arc::new(StringArray::from(vec!["a", "a", "b"])),
+                Arc::new(Float64Array::from(vec![Some(1.5), None, Some(f64::NAN)])),
+            ],
+        )
+        .unwrap();
+        let kind = ResultKind::Select {
+            measurement: "cpu".to_string(),
+            tags: vec!["host".to_string()],
+        };
+
+        let series = build_series(&kind, schema.clone(), &[batch.clone()], None).unwrap();
+        assert_eq!(
+            serde_json::to_value(&series).unwrap(),
+            json!([
+                {
+                    "name": "cpu",
+                    "tags": {"host": "a"},
+                    "columns": ["time", "mean"],
+                    "values": [["1970-01-01T00:00:00Z", 1.5], ["1970-01-01T00:01:00Z", null]]
+                },
+                {
+                    "name": "cpu",
+                    "tags": {"host": "b"},
+                    "columns": ["time", "mean"],
+                    "values": [["1970-01-01T00:00:00Z", null]]
+                }
+            ])
+        );
+
+        let series = build_series(&kind, schema, &[batch], Some(Epoch::Second)).unwrap();
+        assert_eq!(
+            serde_json::to_value(&series[0].values).unwrap(),
+            json!([[0, 1.5], [60, null]])
+        );
+    }
+
+    #[test]
+    fn test_build_series_by_first_column() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("table_name", DataType::Utf8, false),
+            Field::new("tagKey", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["cpu", "cpu", "mem"])),
+                Arc::new(StringArray::from(vec!["host", "region", "host"])),
+            ],
+        )
+        .unwrap();
+
+        let series =
+            build_series(&ResultKind::SeriesByFirstColumn, schema, &[batch], None).unwrap();
+        assert_eq!(
+            serde_json::to_value(&series).unwrap(),
+            json!([
+                {"name": "cpu", "columns": ["tagKey"], "values": [["host"], ["region"]]},
+                {"name": "mem", "columns": ["tagKey"], "values": [["host"]]}
+            ])
+        );
+    }
+}
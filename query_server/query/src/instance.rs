@@ -2,11 +2,13 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use coordinator::service::CoordinatorRef;
+use datafusion::common::OwnedTableReference;
+use datafusion::logical_expr::TableSource;
 use derive_builder::Builder;
 use memory_pool::MemoryPoolRef;
 use meta::error::MetaError;
 use models::auth::user::{User, UserInfo};
-use models::auth::AuthError;
+use models::auth::{AuthError, PasswordPolicy};
 use models::oid::Oid;
 use models::schema::DEFAULT_CATALOG;
 use snafu::ResultExt;
@@ -21,15 +23,18 @@ use spi::server::dbms::DatabaseManagerSystem;
 use spi::service::protocol::{Query, QueryHandle, QueryId};
 use spi::{AuthSnafu, Result};
 use trace::{debug, SpanContext};
-use tskv::kv_option::Options;
+use tskv::kv_option::{Options, QueryOptions};
 
-use crate::auth::auth_control::{AccessControlImpl, AccessControlNoCheck};
+use crate::audit::AuditLogger;
+use crate::auth::auth_control::{AccessControlImpl, AccessControlNoCheck, LoginPolicy};
+use crate::auth::jwt::JwtVerifier;
 use crate::data_source::split::SplitManager;
 use crate::data_source::stream::tskv::factory::{TskvStreamProviderFactory, TSKV_STREAM_PROVIDER};
 use crate::dispatcher::manager::SimpleQueryDispatcherBuilder;
 use crate::dispatcher::persister::LocalQueryPersister;
 use crate::dispatcher::query_tracker::QueryTracker;
 use crate::execution::factory::SqlQueryExecutionFactory;
+use crate::execution::query_cache::QueryCache;
 use crate::execution::scheduler::local::LocalScheduler;
 use crate::extension::expr::{load_all_functions, register_session_udfs};
 use crate::extension::variable::load_all_system_vars;
@@ -46,6 +51,9 @@ pub const DEFAULT_CNOSDB_QUERY_DIRECTORY_NAME: &str = "query";
 pub struct Cnosdbms<D: QueryDispatcher> {
     // TODO access control
     access_control: AccessControlRef,
+    // verify the bearer tokens, disabled if none
+    #[builder(default)]
+    jwt_verifier: Option<Arc<JwtVerifier>>,
     // query dispatcher & query execution
     query_dispatcher: D,
 }
@@ -66,6 +74,33 @@ where
             .context(AuthSnafu)
     }
 
+    async fn authenticate_token(&self, token: &str, tenant_name: &str) -> Result<User> {
+        let verifier = self
+            .jwt_verifier
+            .as_ref()
+            .ok_or_else(|| AuthError::InvalidToken {
+                reason: "bearer token authentication is not enabled".to_string(),
+            })
+            .context(AuthSnafu)?;
+
+        let identity = verifier.verify(token).context(AuthSnafu)?;
+        if let Some(tenant) = identity.tenant.as_ref() {
+            if tenant != tenant_name {
+                return Err(AuthError::AccessDenied {
+                    user_name: identity.user,
+                    auth_type: "bearer token".to_string(),
+                    err: format!("the token is not issued for tenant {}", tenant_name),
+                })
+                .context(AuthSnafu);
+            }
+        }
+
+        self.access_control
+            .token_access_check(&identity.user, tenant_name)
+            .await
+            .context(AuthSnafu)
+    }
+
     async fn execute(
         &self,
         query: &Query,
@@ -110,6 +145,16 @@ where
         Ok(logical_plan)
     }
 
+    async fn get_table_source(
+        &self,
+        table: OwnedTableReference,
+        query_state_machine: QueryStateMachineRef,
+    ) -> Result<Arc<dyn TableSource>> {
+        self.query_dispatcher
+            .get_table_source(table, query_state_machine)
+            .await
+    }
+
     async fn execute_logical_plan(
         &self,
         logical_plan: Plan,
@@ -157,6 +202,17 @@ impl<D: QueryDispatcher> Cnosdbms<D> {
         self.access_control.tenant_id(tenant_name).await
     }
 }
+fn password_policy(options: &QueryOptions) -> PasswordPolicy {
+    PasswordPolicy {
+        min_length: options.password_min_length,
+        require_uppercase: options.password_require_uppercase,
+        require_lowercase: options.password_require_lowercase,
+        require_digit: options.password_require_digit,
+        require_special: options.password_require_special,
+        lifetime: (!options.password_lifetime.is_zero()).then_some(options.password_lifetime),
+    }
+}
+
 pub async fn make_cnosdbms(
     coord: CoordinatorRef,
     options: Options,
@@ -212,11 +268,18 @@ pub async fn make_cnosdbms(
         query_persister,
     ));
 
+    // the cached query results are invalidated by the data changes of this node
+    let query_cache = Arc::new(QueryCache::new(&options.query));
+    coordinator::data_change::register_data_change_listener(query_cache.clone());
+
+    let audit_logger = Arc::new(AuditLogger::try_new(&options.query)?);
+
     let query_execution_factory = Arc::new(SqlQueryExecutionFactory::new(
         optimizer,
         scheduler,
         query_tracker.clone(),
         Arc::new(stream_checker_manager),
+        query_cache,
         options.query.clone(),
     ));
 
@@ -250,6 +313,8 @@ pub async fn make_cnosdbms(
         .with_query_tracker(query_tracker)
         .with_func_manager(Arc::new(func_manager))
         .with_stream_provider_manager(stream_provider_manager)
+        .with_audit_logger(audit_logger)
+        .with_password_policy(password_policy(&options.query))
         .build()?;
 
     let mut builder = CnosdbmsBuilder::default();
@@ -257,12 +322,25 @@ pub async fn make_cnosdbms(
     let access_control_no_check = AccessControlNoCheck::new(meta_manager);
     if options.query.auth_enabled {
         debug!("build access control");
-        builder.access_control(Arc::new(AccessControlImpl::new(access_control_no_check)))
+        let login_policy = LoginPolicy {
+            max_failed_attempts: options.query.login_max_failed_attempts,
+            lock_time: options.query.login_lock_time,
+        };
+        builder.access_control(Arc::new(AccessControlImpl::new(
+            access_control_no_check,
+            login_policy,
+        )))
     } else {
         debug!("build access control without check");
         builder.access_control(Arc::new(access_control_no_check))
     };
 
+    if let Some(jwt_config) = options.query.jwt_config.as_ref() {
+        debug!("build jwt verifier");
+        let verifier = JwtVerifier::try_new(jwt_config).context(AuthSnafu)?;
+        builder.jwt_verifier(Some(Arc::new(verifier)));
+    }
+
     let db_server = builder
         .query_dispatcher(query_dispatcher)
         .build()
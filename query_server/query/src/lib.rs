@@ -1,15 +1,19 @@
 #![feature(stmt_expr_attributes)]
 extern crate core;
 
+pub mod audit;
 pub mod auth;
 mod data_source;
 pub mod dispatcher;
 mod execution;
 pub mod extension;
 pub mod function;
+pub mod influxql;
 pub mod instance;
 pub mod metadata;
 pub mod prom;
+pub mod rollup;
+pub mod scheduled_task;
 pub mod sql;
 pub mod stream;
 mod utils;
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::{
+    Float64Builder, StringBuilder, TimestampNanosecondBuilder, UInt64Builder,
+};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::DataFusionError;
+use lazy_static::lazy_static;
+
+use crate::audit::AuditRecord;
+
+lazy_static! {
+    pub static ref AUDIT_LOG_SCHEMA: SchemaRef = Arc::new(Schema::new(vec![
+        Field::new(
+            "time",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            false
+        ),
+        Field::new("query_id", DataType::Utf8, false),
+        Field::new("user_name", DataType::Utf8, false),
+        Field::new("tenant_name", DataType::Utf8, false),
+        Field::new("database_name", DataType::Utf8, false),
+        Field::new("client_addr", DataType::Utf8, true),
+        Field::new("statement_type", DataType::Utf8, false),
+        Field::new("statement", DataType::Utf8, false),
+        Field::new("digest", DataType::Utf8, false),
+        Field::new("objects", DataType::Utf8, false),
+        Field::new("status", DataType::Utf8, false),
+        Field::new("error", DataType::Utf8, true),
+        Field::new("rows", DataType::UInt64, false),
+        Field::new("duration", DataType::Float64, false),
+    ]));
+}
+
+/// Builds the `cluster_schema.audit_log` table row by row
+#[derive(Default)]
+pub struct ClusterSchemaAuditLogBuilder {
+    times: TimestampNanosecondBuilder,
+    query_ids: StringBuilder,
+    user_names: StringBuilder,
+    tenant_names: StringBuilder,
+    database_names: StringBuilder,
+    client_addrs: StringBuilder,
+    statement_types: StringBuilder,
+    statements: StringBuilder,
+    digests: StringBuilder,
+    objects: StringBuilder,
+    statuses: StringBuilder,
+    errors: StringBuilder,
+    rows: UInt64Builder,
+    durations: Float64Builder,
+}
+
+impl ClusterSchemaAuditLogBuilder {
+    pub fn append_row(&mut self, record: &AuditRecord) {
+        // Note: append_value is actually infallable.
+        self.times.append_value(record.time);
+        self.query_ids.append_value(&record.query_id);
+        self.user_names.append_value(&record.user);
+        self.tenant_names.append_value(&record.tenant);
+        self.database_names.append_value(&record.database);
+        self.client_addrs.append_option(record.client_addr.as_ref());
+        self.statement_types
+            .append_value(record.statement_type.as_str());
+        self.statements.append_value(&record.statement);
+        self.digests.append_value(&record.digest);
+        self.objects.append_value(record.objects.join(","));
+        self.statuses.append_value(record.status.as_str());
+        self.errors.append_option(record.error.as_ref());
+        self.rows.append_value(record.rows);
+        self.durations.append_value(record.duration);
+    }
+}
+
+impl TryFrom<ClusterSchemaAuditLogBuilder> for RecordBatch {
+    type Error = DataFusionError;
+
+    fn try_from(value: ClusterSchemaAuditLogBuilder) -> Result<Self, Self::Error> {
+        let ClusterSchemaAuditLogBuilder {
+            mut times,
+            mut query_ids,
+            mut user_names,
+            mut tenant_names,
+            mut database_names,
+            mut client_addrs,
+            mut statement_types,
+            mut statements,
+            mut digests,
+            mut objects,
+            mut statuses,
+            mut errors,
+            mut rows,
+            mut durations,
+        } = value;
+
+        let batch = RecordBatch::try_new(
+            AUDIT_LOG_SCHEMA.clone(),
+            vec![
+                Arc::new(times.finish()),
+                Arc::new(query_ids.finish()),
+                Arc::new(user_names.finish()),
+                Arc::new(tenant_names.finish()),
+                Arc::new(database_names.finish()),
+                Arc::new(client_addrs.finish()),
+                Arc::new(statement_types.finish()),
+                Arc::new(statements.finish()),
+                Arc::new(digests.finish()),
+                Arc::new(objects.finish()),
+                Arc::new(statuses.finish()),
+                Arc::new(errors.finish()),
+                Arc::new(rows.finish()),
+                Arc::new(durations.finish()),
+            ],
+        )?;
+
+        Ok(batch)
+    }
+}
@@ -0,0 +1,98 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::Result as DFResult;
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::logical_plan::AggWithGrouping;
+use datafusion::logical_expr::Expr;
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::ExecutionPlan;
+use meta::model::MetaRef;
+use models::auth::user::User;
+
+use crate::audit::AuditLoggerRef;
+use crate::metadata::cluster_schema_provider::builder::audit_log::{
+    ClusterSchemaAuditLogBuilder, AUDIT_LOG_SCHEMA,
+};
+use crate::metadata::cluster_schema_provider::ClusterSchemaTableFactory;
+
+const CLUSTER_SCHEMA_AUDIT_LOG: &str = "AUDIT_LOG";
+
+/// The latest audit records of the statements executed by this node,
+/// the number of the records is limited by the config `query.audit_table_max_records`.
+pub struct ClusterSchemaAuditLogFactory {
+    audit_logger: AuditLoggerRef,
+}
+
+impl ClusterSchemaAuditLogFactory {
+    pub fn new(audit_logger: AuditLoggerRef) -> Self {
+        Self { audit_logger }
+    }
+}
+
+impl ClusterSchemaTableFactory for ClusterSchemaAuditLogFactory {
+    fn table_name(&self) -> &str {
+        CLUSTER_SCHEMA_AUDIT_LOG
+    }
+
+    fn create(&self, user: &User, _metadata: MetaRef) -> Arc<dyn TableProvider> {
+        Arc::new(ClusterSchemaAuditLogTable::new(
+            self.audit_logger.clone(),
+            user.clone(),
+        ))
+    }
+}
+
+pub struct ClusterSchemaAuditLogTable {
+    user: User,
+    audit_logger: AuditLoggerRef,
+}
+
+impl ClusterSchemaAuditLogTable {
+    pub fn new(audit_logger: AuditLoggerRef, user: User) -> Self {
+        Self { user, audit_logger }
+    }
+}
+
+#[async_trait::async_trait]
+impl TableProvider for ClusterSchemaAuditLogTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        AUDIT_LOG_SCHEMA.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _agg_with_grouping: Option<&AggWithGrouping>,
+        _limit: Option<usize>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        let mut builder = ClusterSchemaAuditLogBuilder::default();
+
+        // Only visible to admin
+        if self.user.desc().is_admin() {
+            for record in self.audit_logger.records().iter() {
+                builder.append_row(record);
+            }
+        }
+
+        let rb: RecordBatch = builder.try_into()?;
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![rb]],
+            self.schema(),
+            projection.cloned(),
+        )?))
+    }
+}
@@ -7,9 +7,11 @@ use meta::error::MetaError;
 use meta::model::MetaRef;
 use models::auth::user::User;
 
+use self::factory::audit_log::ClusterSchemaAuditLogFactory;
 use self::factory::tenants::ClusterSchemaTenantsFactory;
 use self::factory::users::ClusterSchemaUsersFactory;
 use super::CLUSTER_SCHEMA;
+use crate::audit::AuditLoggerRef;
 
 mod builder;
 mod factory;
@@ -19,13 +21,14 @@ pub struct ClusterSchemaProvider {
 }
 
 impl ClusterSchemaProvider {
-    pub fn new() -> Self {
+    pub fn new(audit_logger: AuditLoggerRef) -> Self {
         let mut provider = Self {
             table_factories: Default::default(),
         };
 
         provider.register_table_factory(Box::new(ClusterSchemaTenantsFactory {}));
         provider.register_table_factory(Box::new(ClusterSchemaUsersFactory {}));
+        provider.register_table_factory(Box::new(ClusterSchemaAuditLogFactory::new(audit_logger)));
 
         provider
     }
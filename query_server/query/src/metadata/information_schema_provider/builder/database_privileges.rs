@@ -5,6 +5,7 @@ use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::error::DataFusionError;
 use lazy_static::lazy_static;
+use models::auth::privilege::TablePrivilege;
 
 lazy_static! {
     pub static ref DATABASE_PRIVILEGE_SCHEMA: SchemaRef = Arc::new(Schema::new(vec![
@@ -12,6 +13,8 @@ lazy_static! {
         Field::new("database_name", DataType::Utf8, false),
         Field::new("privilege_type", DataType::Utf8, false),
         Field::new("role_name", DataType::Utf8, false),
+        Field::new("table_name", DataType::Utf8, true),
+        Field::new("column_names", DataType::Utf8, true),
     ]));
 }
 
@@ -22,6 +25,8 @@ pub struct InformationSchemaDatabasePrivilegesBuilder {
     database_names: StringBuilder,
     privilege_types: StringBuilder,
     role_names: StringBuilder,
+    table_names: StringBuilder,
+    column_names: StringBuilder,
 }
 
 impl InformationSchemaDatabasePrivilegesBuilder {
@@ -37,6 +42,29 @@ impl InformationSchemaDatabasePrivilegesBuilder {
         self.database_names.append_value(database_name.as_ref());
         self.privilege_types.append_value(privilege_type.as_ref());
         self.role_names.append_value(role_name);
+        self.table_names.append_null();
+        self.column_names.append_null();
+    }
+
+    /// Append the privilege on the table, the columns are joined by ','
+    pub fn append_table_row(
+        &mut self,
+        tenant_name: impl AsRef<str>,
+        privilege: &TablePrivilege,
+        role_name: impl AsRef<str>,
+    ) {
+        self.tenant_names.append_value(tenant_name.as_ref());
+        self.database_names.append_value(&privilege.database);
+        self.privilege_types
+            .append_value(privilege.privilege.as_str());
+        self.role_names.append_value(role_name);
+        self.table_names.append_value(&privilege.table);
+        self.column_names.append_option(
+            privilege
+                .columns
+                .as_ref()
+                .map(|columns| columns.iter().cloned().collect::<Vec<_>>().join(",")),
+        );
     }
 }
 
@@ -49,6 +77,8 @@ impl TryFrom<InformationSchemaDatabasePrivilegesBuilder> for RecordBatch {
             mut database_names,
             mut privilege_types,
             mut role_names,
+            mut table_names,
+            mut column_names,
         } = value;
 
         let batch = RecordBatch::try_new(
@@ -58,6 +88,8 @@ impl TryFrom<InformationSchemaDatabasePrivilegesBuilder> for RecordBatch {
                 Arc::new(database_names.finish()),
                 Arc::new(privilege_types.finish()),
                 Arc::new(role_names.finish()),
+                Arc::new(table_names.finish()),
+                Arc::new(column_names.finish()),
             ],
         )?;
 
@@ -6,4 +6,6 @@ pub mod members;
 pub mod queries;
 pub mod resource_status;
 pub mod roles;
+pub mod series_cardinality;
 pub mod tables;
+pub mod tasks;
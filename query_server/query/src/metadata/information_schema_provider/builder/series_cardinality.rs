@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, StringArray};
+use datafusion::arrow::compute::concat_batches;
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::DataFusionError;
+use lazy_static::lazy_static;
+use tskv::index::cardinality::{
+    CARDINALITY_SERIES_COUNT, CARDINALITY_TABLE_NAME, CARDINALITY_TAG_KEY, CARDINALITY_TAG_VALUE,
+    CARDINALITY_TAG_VALUE_COUNT, CARDINALITY_VNODE_ID,
+};
+
+pub const SERIES_CARDINALITY_DATABASE_NAME: &str = "database_name";
+
+lazy_static! {
+    pub static ref SERIES_CARDINALITY_SCHEMA: SchemaRef = Arc::new(Schema::new(vec![
+        Field::new(SERIES_CARDINALITY_DATABASE_NAME, DataType::Utf8, false),
+        Field::new(CARDINALITY_VNODE_ID, DataType::UInt32, false),
+        Field::new(CARDINALITY_TABLE_NAME, DataType::Utf8, false),
+        Field::new(CARDINALITY_TAG_KEY, DataType::Utf8, true),
+        Field::new(CARDINALITY_TAG_VALUE, DataType::Utf8, true),
+        Field::new(CARDINALITY_SERIES_COUNT, DataType::UInt64, false),
+        Field::new(CARDINALITY_TAG_VALUE_COUNT, DataType::UInt64, true),
+    ]));
+}
+
+/// Builds the `information_schema.SERIES_CARDINALITY` table from the series cardinality of vnodes
+#[derive(Default)]
+pub struct InformationSchemaSeriesCardinalityBuilder {
+    batches: Vec<RecordBatch>,
+}
+
+impl InformationSchemaSeriesCardinalityBuilder {
+    pub fn append_vnode_batch(
+        &mut self,
+        database_name: &str,
+        batch: &RecordBatch,
+    ) -> Result<(), DataFusionError> {
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+
+        let database_names: ArrayRef =
+            Arc::new(StringArray::from(vec![database_name; batch.num_rows()]));
+        let columns = std::iter::once(database_names)
+            .chain(batch.columns().iter().cloned())
+            .collect();
+        self.batches.push(RecordBatch::try_new(
+            SERIES_CARDINALITY_SCHEMA.clone(),
+            columns,
+        )?);
+
+        Ok(())
+    }
+}
+
+impl TryFrom<InformationSchemaSeriesCardinalityBuilder> for RecordBatch {
+    type Error = DataFusionError;
+
+    fn try_from(value: InformationSchemaSeriesCardinalityBuilder) -> Result<Self, Self::Error> {
+        Ok(concat_batches(&SERIES_CARDINALITY_SCHEMA, &value.batches)?)
+    }
+}
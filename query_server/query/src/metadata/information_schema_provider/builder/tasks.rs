@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::StringBuilder;
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::error::DataFusionError;
+use lazy_static::lazy_static;
+
+pub const TASKS_TASK_NAME: &str = "task_name";
+pub const TASKS_DATABASE_NAME: &str = "database_name";
+pub const TASKS_OWNER: &str = "owner";
+pub const TASKS_SCHEDULE: &str = "schedule";
+pub const TASKS_STATEMENT: &str = "statement";
+pub const TASKS_HIGH_WATER_MARK: &str = "high_water_mark";
+pub const TASKS_LAST_RUN_TIME: &str = "last_run_time";
+pub const TASKS_NEXT_RUN_TIME: &str = "next_run_time";
+pub const TASKS_STATUS: &str = "status";
+pub const TASKS_COMMENT: &str = "comment";
+
+lazy_static! {
+    pub static ref TASKS_SCHEMA: SchemaRef = Arc::new(Schema::new(vec![
+        Field::new(TASKS_TASK_NAME, DataType::Utf8, false),
+        Field::new(TASKS_DATABASE_NAME, DataType::Utf8, false),
+        Field::new(TASKS_OWNER, DataType::Utf8, false),
+        Field::new(TASKS_SCHEDULE, DataType::Utf8, false),
+        Field::new(TASKS_STATEMENT, DataType::Utf8, false),
+        Field::new(TASKS_HIGH_WATER_MARK, DataType::Utf8, true),
+        Field::new(TASKS_LAST_RUN_TIME, DataType::Utf8, true),
+        Field::new(TASKS_NEXT_RUN_TIME, DataType::Utf8, true),
+        Field::new(TASKS_STATUS, DataType::Utf8, false),
+        Field::new(TASKS_COMMENT, DataType::Utf8, true),
+    ]));
+}
+
+/// Builds the `information_schema.TASKS` table row by row
+#[derive(Default)]
+pub struct InformationSchemaTasksBuilder {
+    task_names: StringBuilder,
+    database_names: StringBuilder,
+    owners: StringBuilder,
+    schedules: StringBuilder,
+    statements: StringBuilder,
+    high_water_marks: StringBuilder,
+    last_run_times: StringBuilder,
+    next_run_times: StringBuilder,
+    statuses: StringBuilder,
+    comments: StringBuilder,
+}
+
+impl InformationSchemaTasksBuilder {
+    #[allow(clippy::too_many_arguments)]
+    pub fn append_row(
+        &mut self,
+        task_name: impl AsRef<str>,
+        database_name: impl AsRef<str>,
+        owner: impl AsRef<str>,
+        schedule: impl AsRef<str>,
+        statement: impl AsRef<str>,
+        high_water_mark: Option<impl AsRef<str>>,
+        last_run_time: Option<impl AsRef<str>>,
+        next_run_time: Option<impl AsRef<str>>,
+        status: impl AsRef<str>,
+        comment: impl AsRef<str>,
+    ) {
+        self.task_names.append_value(task_name);
+        self.database_names.append_value(database_name);
+        self.owners.append_value(owner);
+        self.schedules.append_value(schedule);
+        self.statements.append_value(statement);
+        self.high_water_marks.append_option(high_water_mark);
+        self.last_run_times.append_option(last_run_time);
+        self.next_run_times.append_option(next_run_time);
+        self.statuses.append_value(status);
+        self.comments.append_value(comment);
+    }
+}
+
+impl TryFrom<InformationSchemaTasksBuilder> for RecordBatch {
+    type Error = DataFusionError;
+
+    fn try_from(value: InformationSchemaTasksBuilder) -> Result<Self, Self::Error> {
+        let InformationSchemaTasksBuilder {
+            mut task_names,
+            mut database_names,
+            mut owners,
+            mut schedules,
+            mut statements,
+            mut high_water_marks,
+            mut last_run_times,
+            mut next_run_times,
+            mut statuses,
+            mut comments,
+        } = value;
+
+        let batch = RecordBatch::try_new(
+            TASKS_SCHEMA.clone(),
+            vec![
+                Arc::new(task_names.finish()),
+                Arc::new(database_names.finish()),
+                Arc::new(owners.finish()),
+                Arc::new(schedules.finish()),
+                Arc::new(statements.finish()),
+                Arc::new(high_water_marks.finish()),
+                Arc::new(last_run_times.finish()),
+                Arc::new(next_run_times.finish()),
+                Arc::new(statuses.finish()),
+                Arc::new(comments.finish()),
+            ],
+        )?;
+
+        Ok(batch)
+    }
+}
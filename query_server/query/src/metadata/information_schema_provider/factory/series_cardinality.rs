@@ -0,0 +1,200 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use coordinator::service::CoordinatorRef;
+use coordinator::VnodeSummarizerCmdType;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::{DataFusionError, Result as DFResult};
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::logical_plan::AggWithGrouping;
+use datafusion::logical_expr::{BinaryExpr, Operator, TableProviderFilterPushDown};
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::prelude::Expr;
+use datafusion::scalar::ScalarValue;
+use meta::model::MetaClientRef;
+use models::auth::user::User;
+use models::oid::Identifier;
+
+use crate::dispatcher::query_tracker::QueryTracker;
+use crate::metadata::information_schema_provider::builder::series_cardinality::{
+    InformationSchemaSeriesCardinalityBuilder, SERIES_CARDINALITY_DATABASE_NAME,
+    SERIES_CARDINALITY_SCHEMA,
+};
+use crate::metadata::information_schema_provider::InformationSchemaTableFactory;
+
+pub const INFORMATION_SCHEMA_SERIES_CARDINALITY: &str = "SERIES_CARDINALITY";
+
+/// Number of the top tag values with the most series of each tag key.
+const TOP_TAG_VALUES: u32 = 10;
+
+/// This view shows the number of series of each table and tag key in each vnode,
+/// computed from the inverted index, and the tag values with the most series.
+///
+/// Only the databases the user can read are displayed, it is recommended to
+/// filter by `database_name` since the indexes of all vnodes are scanned.
+pub struct SeriesCardinalityFactory {
+    coord: CoordinatorRef,
+}
+
+impl SeriesCardinalityFactory {
+    pub fn new(coord: CoordinatorRef) -> Self {
+        Self { coord }
+    }
+}
+
+impl InformationSchemaTableFactory for SeriesCardinalityFactory {
+    fn table_name(&self) -> &'static str {
+        INFORMATION_SCHEMA_SERIES_CARDINALITY
+    }
+
+    fn create(
+        &self,
+        user: &User,
+        metadata: MetaClientRef,
+        _query_tracker: Arc<QueryTracker>,
+    ) -> Arc<dyn TableProvider> {
+        Arc::new(InformationSchemaSeriesCardinalityTable::new(
+            self.coord.clone(),
+            metadata,
+            user.clone(),
+        ))
+    }
+}
+
+pub struct InformationSchemaSeriesCardinalityTable {
+    coord: CoordinatorRef,
+    user: User,
+    metadata: MetaClientRef,
+}
+
+impl InformationSchemaSeriesCardinalityTable {
+    pub fn new(coord: CoordinatorRef, metadata: MetaClientRef, user: User) -> Self {
+        Self {
+            coord,
+            user,
+            metadata,
+        }
+    }
+}
+
+/// Returns the database of filter `database_name = '<database>'`.
+fn database_filter(filter: &Expr) -> Option<&str> {
+    match filter {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::Eq,
+            right,
+        }) => match (left.as_ref(), right.as_ref()) {
+            (Expr::Column(c), Expr::Literal(ScalarValue::Utf8(Some(database))))
+            | (Expr::Literal(ScalarValue::Utf8(Some(database))), Expr::Column(c))
+                if c.name == SERIES_CARDINALITY_DATABASE_NAME =>
+            {
+                Some(database.as_str())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl TableProvider for InformationSchemaSeriesCardinalityTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        SERIES_CARDINALITY_SCHEMA.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filter_pushdown(&self, filter: &Expr) -> DFResult<TableProviderFilterPushDown> {
+        if database_filter(filter).is_some() {
+            Ok(TableProviderFilterPushDown::Inexact)
+        } else {
+            Ok(TableProviderFilterPushDown::Unsupported)
+        }
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        _agg_with_grouping: Option<&AggWithGrouping>,
+        _limit: Option<usize>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        let mut builder = InformationSchemaSeriesCardinalityBuilder::default();
+
+        let dbs = self
+            .metadata
+            .list_databases()
+            .map_err(|e| DataFusionError::Internal(format!("Failed to list databases: {}", e)))?;
+        let tenant = self.metadata.tenant();
+        let tenant_id = tenant.id();
+        let tenant_name = tenant.name();
+        let filter_dbs = filters
+            .iter()
+            .filter_map(database_filter)
+            .collect::<Vec<_>>();
+
+        for (db, info) in dbs {
+            // Check if the current user has at least read permission on this db, skip if not
+            if !self.user.can_read_database(*tenant_id, &db) {
+                continue;
+            }
+
+            if info.is_hidden() || filter_dbs.iter().any(|f| *f != db) {
+                continue;
+            }
+
+            let cmd = VnodeSummarizerCmdType::SeriesCardinality(db.clone(), TOP_TAG_VALUES);
+            let batches = self
+                .coord
+                .vnode_summarizer(tenant_name, cmd)
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))?;
+            for batch in batches.iter() {
+                builder.append_vnode_batch(&db, batch)?;
+            }
+        }
+        let rb: RecordBatch = builder.try_into()?;
+
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![rb]],
+            self.schema(),
+            projection.cloned(),
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::prelude::{col, lit};
+
+    use super::database_filter;
+
+    #[test]
+    fn test_database_filter() {
+        assert_eq!(
+            database_filter(&col("database_name").eq(lit("public"))),
+            Some("public")
+        );
+        assert_eq!(
+            database_filter(&lit("public").eq(col("database_name"))),
+            Some("public")
+        );
+        assert_eq!(database_filter(&col("table_name").eq(lit("cpu"))), None);
+        assert_eq!(
+            database_filter(&col("database_name").not_eq(lit("public"))),
+            None
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use std::any::Any;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use async_trait::async_trait;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::{DataFusionError, Result as DFResult};
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::logical_plan::AggWithGrouping;
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::prelude::Expr;
+use meta::model::MetaClientRef;
+use models::auth::user::User;
+use models::oid::Identifier;
+use models::schema::{ResourceOperator, ResourceStatus};
+
+use crate::dispatcher::query_tracker::QueryTracker;
+use crate::metadata::information_schema_provider::builder::tasks::{
+    InformationSchemaTasksBuilder, TASKS_SCHEMA,
+};
+use crate::metadata::information_schema_provider::InformationSchemaTableFactory;
+
+pub const INFORMATION_SCHEMA_TASKS: &str = "TASKS";
+
+/// This view shows the scheduled tasks of the current tenant, created by `CREATE TASK`.
+///
+/// Only the tasks of the databases the user can read are displayed.
+pub struct TasksFactory {}
+
+impl InformationSchemaTableFactory for TasksFactory {
+    fn table_name(&self) -> &'static str {
+        INFORMATION_SCHEMA_TASKS
+    }
+
+    fn create(
+        &self,
+        user: &User,
+        metadata: MetaClientRef,
+        _query_tracker: Arc<QueryTracker>,
+    ) -> Arc<dyn TableProvider> {
+        Arc::new(InformationSchemaTasksTable::new(metadata, user.clone()))
+    }
+}
+
+pub struct InformationSchemaTasksTable {
+    user: User,
+    metadata: MetaClientRef,
+}
+
+impl InformationSchemaTasksTable {
+    pub fn new(metadata: MetaClientRef, user: User) -> Self {
+        Self { user, metadata }
+    }
+}
+
+#[async_trait]
+impl TableProvider for InformationSchemaTasksTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        TASKS_SCHEMA.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _agg_with_grouping: Option<&AggWithGrouping>,
+        _limit: Option<usize>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        let mut builder = InformationSchemaTasksBuilder::default();
+
+        let tenant_id = *self.metadata.tenant().id();
+        let tenant_name = self.metadata.tenant_name();
+        let resourceinfos = self.metadata.read_resourceinfos().await.map_err(|e| {
+            DataFusionError::Internal(format!("Failed to read resourceinfo: {}", e))
+        })?;
+
+        for resourceinfo in resourceinfos {
+            let task = match resourceinfo.get_operator() {
+                ResourceOperator::ScheduledTask(task) => task,
+                _ => continue,
+            };
+            let status = resourceinfo.get_status();
+            if task.tenant != tenant_name
+                || *status == ResourceStatus::Cancel
+                || !self.user.can_read_database(tenant_id, &task.database)
+            {
+                continue;
+            }
+
+            let next_run_time = if *status == ResourceStatus::Schedule {
+                Some(format_time(resourceinfo.get_time()))
+            } else {
+                None
+            };
+
+            builder.append_row(
+                &task.name,
+                &task.database,
+                &task.owner,
+                task.schedule.to_string(),
+                &task.statement,
+                task.high_water_mark.map(format_time),
+                task.last_run_time.map(format_time),
+                next_run_time,
+                status.to_string(),
+                resourceinfo.get_comment(),
+            );
+        }
+
+        let rb: RecordBatch = builder.try_into()?;
+
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![rb]],
+            self.schema(),
+            projection.cloned(),
+        )?))
+    }
+}
+
+fn format_time(nanos: i64) -> String {
+    let datetime = UNIX_EPOCH + std::time::Duration::from_nanos(nanos.max(0) as u64);
+    chrono::DateTime::<chrono::Utc>::from(datetime)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
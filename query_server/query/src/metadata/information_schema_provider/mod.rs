@@ -14,11 +14,14 @@ pub use builder::tables::{
     TABLES_TABLE_DATABASE, TABLES_TABLE_ENGINE, TABLES_TABLE_NAME, TABLES_TABLE_OPTIONS,
     TABLES_TABLE_TENANT, TABLES_TABLE_TYPE,
 };
+use coordinator::service::CoordinatorRef;
 use datafusion::datasource::TableProvider;
 pub use factory::columns::INFORMATION_SCHEMA_COLUMNS;
 pub use factory::databases::INFORMATION_SCHEMA_DATABASES;
 pub use factory::queries::INFORMATION_SCHEMA_QUERIES;
+pub use factory::series_cardinality::INFORMATION_SCHEMA_SERIES_CARDINALITY;
 pub use factory::tables::INFORMATION_SCHEMA_TABLES;
+pub use factory::tasks::INFORMATION_SCHEMA_TASKS;
 use meta::error::MetaError;
 use meta::model::MetaClientRef;
 use models::auth::user::User;
@@ -31,6 +34,8 @@ use self::factory::members::MembersFactory;
 use self::factory::queries::QueriesFactory;
 use self::factory::resource_status::InformationSchemaResourceStatusFactory;
 use self::factory::roles::RolesFactory;
+use self::factory::series_cardinality::SeriesCardinalityFactory;
+use self::factory::tasks::TasksFactory;
 use super::INFORMATION_SCHEMA;
 use crate::dispatcher::query_tracker::QueryTracker;
 use crate::metadata::information_schema_provider::factory::tables::TablesFactory;
@@ -45,7 +50,7 @@ pub struct InformationSchemaProvider {
 }
 
 impl InformationSchemaProvider {
-    pub fn new(query_tracker: Arc<QueryTracker>) -> Self {
+    pub fn new(coord: CoordinatorRef, query_tracker: Arc<QueryTracker>) -> Self {
         let mut provider = Self {
             query_tracker,
             table_factories: Default::default(),
@@ -60,6 +65,8 @@ impl InformationSchemaProvider {
         provider.register_table_factory(Box::new(MembersFactory {}));
         provider.register_table_factory(Box::new(QueriesFactory {}));
         provider.register_table_factory(Box::new(InformationSchemaResourceStatusFactory {}));
+        provider.register_table_factory(Box::new(TasksFactory {}));
+        provider.register_table_factory(Box::new(SeriesCardinalityFactory::new(coord)));
 
         provider
     }
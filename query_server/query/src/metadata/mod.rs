@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -8,9 +8,14 @@ use datafusion::common::Result as DFResult;
 use datafusion::config::ConfigOptions;
 use datafusion::datasource::TableProvider;
 use datafusion::error::DataFusionError;
-use datafusion::logical_expr::{AggregateUDF, ScalarUDF, TableSource, WindowUDF};
+use datafusion::logical_expr::{
+    create_udf, or, AggregateUDF, ColumnarValue, Expr, ScalarFunctionImplementation, ScalarUDF,
+    TableSource, Volatility, WindowUDF,
+};
 use datafusion::physical_expr::var_provider::is_system_variables;
-use datafusion::sql::planner::ContextProvider;
+use datafusion::scalar::ScalarValue;
+use datafusion::sql::planner::{ContextProvider, PlannerContext, SqlToRel};
+use datafusion::sql::sqlparser::parser::Parser;
 use datafusion::sql::TableReference;
 use datafusion::variable::{VarProvider, VarType};
 pub use information_schema_provider::{
@@ -18,12 +23,13 @@ pub use information_schema_provider::{
     COLUMNS_DATA_TYPE, COLUMNS_TABLE_NAME, DATABASES_DATABASE_NAME, DATABASES_PRECISION,
     DATABASES_REPLICA, DATABASES_SHARD, DATABASES_TENANT_NAME, DATABASES_TTL,
     DATABASES_VNODE_DURATION, INFORMATION_SCHEMA_COLUMNS, INFORMATION_SCHEMA_DATABASES,
-    INFORMATION_SCHEMA_QUERIES, INFORMATION_SCHEMA_TABLES, TABLES_TABLE_DATABASE,
-    TABLES_TABLE_ENGINE, TABLES_TABLE_NAME, TABLES_TABLE_OPTIONS, TABLES_TABLE_TENANT,
-    TABLES_TABLE_TYPE,
+    INFORMATION_SCHEMA_QUERIES, INFORMATION_SCHEMA_TABLES, INFORMATION_SCHEMA_TASKS,
+    TABLES_TABLE_DATABASE, TABLES_TABLE_ENGINE, TABLES_TABLE_NAME, TABLES_TABLE_OPTIONS,
+    TABLES_TABLE_TENANT, TABLES_TABLE_TYPE,
 };
 use meta::error::MetaError;
 use meta::model::MetaClientRef;
+use models::auth::role::TenantRoleIdentifier;
 use models::auth::user::UserDesc;
 use models::object_reference::{Resolve, ResolvedTable};
 use models::schema::{Precision, Tenant, DEFAULT_CATALOG, DEFAULT_DATABASE};
@@ -34,9 +40,11 @@ use spi::query::session::SessionCtx;
 pub use self::base_table::BaseTableProvider;
 use self::cluster_schema_provider::ClusterSchemaProvider;
 use self::information_schema_provider::InformationSchemaProvider;
+use crate::audit::AuditLoggerRef;
 use crate::data_source::table_source::{TableHandle, TableSourceAdapter};
 use crate::dispatcher::query_tracker::QueryTracker;
 use crate::metadata::usage_schema_provider::UsageSchemaProvider;
+use crate::sql::dialect::CnosDBDialect;
 
 mod base_table;
 mod cluster_schema_provider;
@@ -46,6 +54,8 @@ mod usage_schema_provider;
 pub const CLUSTER_SCHEMA: &str = "CLUSTER_SCHEMA";
 pub const INFORMATION_SCHEMA: &str = "INFORMATION_SCHEMA";
 pub const USAGE_SCHEMA: &str = "usage_schema";
+/// The function returning the name of the session user, e.g. used by the row policies
+pub const CURRENT_USER: &str = "current_user";
 
 /// remote meta
 pub struct RemoteCatalogMeta {}
@@ -100,6 +110,7 @@ impl MetadataProvider {
         default_table_provider: TableHandleProviderRef,
         func_manager: FuncMetaManagerRef,
         query_tracker: Arc<QueryTracker>,
+        audit_logger: AuditLoggerRef,
         session: SessionCtx,
     ) -> Self {
         Self {
@@ -110,13 +121,73 @@ impl MetadataProvider {
             session,
             meta_client,
             func_manager,
-            information_schema_provider: InformationSchemaProvider::new(query_tracker),
-            cluster_schema_provider: ClusterSchemaProvider::new(),
+            information_schema_provider: InformationSchemaProvider::new(
+                coord.clone(),
+                query_tracker,
+            ),
+            cluster_schema_provider: ClusterSchemaProvider::new(audit_logger),
             usage_schema_provider: UsageSchemaProvider::new(default_table_provider),
             access_databases: Default::default(),
         }
     }
 
+    /// The disjunction of the row policies on the table of the user's role,
+    /// None if the user can see all rows.
+    fn row_policy_predicate(
+        &self,
+        database_name: &str,
+        table_name: &str,
+        table_source: &TableSourceAdapter,
+    ) -> DFResult<Option<Expr>> {
+        let role_name = match self.session.user().role() {
+            Some(TenantRoleIdentifier::Custom(role_name)) => role_name,
+            _ => return Ok(None),
+        };
+        let policies = self
+            .meta_client
+            .row_policies(role_name, database_name, table_name);
+        if policies.is_empty() {
+            return Ok(None);
+        }
+
+        let schema = match table_source.get_logical_plan() {
+            Some(plan) => plan.schema().clone(),
+            None => return Ok(None),
+        };
+        let sql_to_rel = SqlToRel::new(self);
+        let mut predicate: Option<Expr> = None;
+        for policy in policies {
+            let expr = Parser::new(&CnosDBDialect)
+                .try_with_sql(&policy.predicate)?
+                .parse_expr()?;
+            let expr = sql_to_rel.sql_to_expr(expr, &schema, &mut PlannerContext::new())?;
+            predicate = Some(match predicate {
+                Some(predicate) => or(predicate, expr),
+                None => expr,
+            });
+        }
+
+        Ok(predicate)
+    }
+
+    /// `current_user()` is evaluated to the name of the session user
+    fn current_user_udf(&self) -> Arc<ScalarUDF> {
+        let user_name = self.session.user().desc().name().to_string();
+        let fun: ScalarFunctionImplementation = Arc::new(move |_| {
+            Ok(ColumnarValue::Scalar(ScalarValue::Utf8(Some(
+                user_name.clone(),
+            ))))
+        });
+
+        Arc::new(create_udf(
+            CURRENT_USER,
+            vec![],
+            Arc::new(DataType::Utf8),
+            Volatility::Stable,
+            fun,
+        ))
+    }
+
     fn process_system_table_source(
         &self,
         tenant_name: &str,
@@ -247,19 +318,38 @@ impl ContextProviderExtension for MetadataProvider {
             )));
         }
 
+        // the user may only be granted some columns of the table
+        let columns = self.session.user().readable_columns(
+            *self.session.tenant_id(),
+            database_name,
+            table_name,
+        );
+
         // save access table
         self.access_databases
             .write()
-            .push_table(database_name, table_name);
+            .push_table(database_name, table_name, columns.clone());
 
         let table_handle = self.build_table_handle(&name)?;
 
-        Ok(Arc::new(TableSourceAdapter::try_new(
+        let table_source = TableSourceAdapter::try_new(
             table_ref.to_owned_reference(),
             database_name,
             table_name,
             table_handle,
-        )?))
+        )?;
+
+        // the user may only see the rows matching the policies of the role
+        let table_source =
+            match self.row_policy_predicate(database_name, table_name, &table_source)? {
+                Some(predicate) => table_source.filter(predicate)?,
+                None => table_source,
+            };
+
+        match columns {
+            Some(columns) => Ok(Arc::new(table_source.project_columns(&columns)?)),
+            None => Ok(Arc::new(table_source)),
+        }
     }
 
     fn database_table_exist(
@@ -302,6 +392,10 @@ impl ContextProvider for MetadataProvider {
     }
 
     fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>> {
+        if name.eq_ignore_ascii_case(CURRENT_USER) {
+            return Some(self.current_user_udf());
+        }
+
         self.func_manager.udf(name).ok().or(self
             .session
             .inner()
@@ -353,8 +447,16 @@ impl DatabaseSet {
         self.dbs.clear();
     }
 
-    pub fn push_table(&mut self, db: impl Into<String>, tbl: impl Into<String>) {
-        self.dbs.entry(db.into()).or_default().push_table(tbl);
+    pub fn push_table(
+        &mut self,
+        db: impl Into<String>,
+        tbl: impl Into<String>,
+        columns: Option<BTreeSet<String>>,
+    ) {
+        self.dbs
+            .entry(db.into())
+            .or_default()
+            .push_table(tbl, columns);
     }
 
     pub fn dbs(&self) -> Vec<&String> {
@@ -368,12 +470,17 @@ impl DatabaseSet {
 
 #[derive(Default, Clone)]
 pub struct TableSet {
-    tables: HashSet<String>,
+    // table name -> the accessed columns, None if all columns are accessible
+    tables: HashMap<String, Option<BTreeSet<String>>>,
 }
 
 impl TableSet {
-    pub fn push_table(&mut self, tbl: impl Into<String>) {
-        self.tables.insert(tbl.into());
+    pub fn push_table(&mut self, tbl: impl Into<String>, columns: Option<BTreeSet<String>>) {
+        self.tables.insert(tbl.into(), columns);
+    }
+
+    pub fn tables(&self) -> impl Iterator<Item = (&String, &Option<BTreeSet<String>>)> {
+        self.tables.iter()
     }
 }
 
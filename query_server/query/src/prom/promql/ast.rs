@@ -0,0 +1,288 @@
+use std::fmt::{Display, Formatter};
+
+use protos::prompb::types::label_matcher::Type;
+
+/// Expression of PromQL, durations and timestamps are in milliseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    VectorSelector(VectorSelector),
+    MatrixSelector {
+        selector: VectorSelector,
+        range: i64,
+    },
+    Call {
+        func: Function,
+        args: Vec<Expr>,
+    },
+    Aggregate {
+        op: AggregateOp,
+        grouping: Grouping,
+        expr: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    Neg(Box<Expr>),
+}
+
+impl Expr {
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Self::Number(_) => ValueType::Scalar,
+            Self::MatrixSelector { .. } => ValueType::Matrix,
+            Self::Neg(expr) => expr.value_type(),
+            Self::Binary { lhs, rhs, .. } => {
+                if lhs.value_type() == ValueType::Scalar && rhs.value_type() == ValueType::Scalar {
+                    ValueType::Scalar
+                } else {
+                    ValueType::Vector
+                }
+            }
+            Self::VectorSelector(_) | Self::Call { .. } | Self::Aggregate { .. } => {
+                ValueType::Vector
+            }
+        }
+    }
+
+    /// Visit all selectors of the expression, with the range of matrix selectors.
+    pub fn selectors(&self, f: &mut impl FnMut(&VectorSelector, i64)) {
+        match self {
+            Self::Number(_) => {}
+            Self::VectorSelector(selector) => f(selector, 0),
+            Self::MatrixSelector { selector, range } => f(selector, *range),
+            Self::Call { args, .. } => args.iter().for_each(|e| e.selectors(f)),
+            Self::Aggregate { expr, .. } | Self::Neg(expr) => expr.selectors(f),
+            Self::Binary { lhs, rhs, .. } => {
+                lhs.selectors(f);
+                rhs.selectors(f);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Scalar,
+    Vector,
+    Matrix,
+}
+
+impl Display for ValueType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Scalar => write!(f, "scalar"),
+            Self::Vector => write!(f, "instant vector"),
+            Self::Matrix => write!(f, "range vector"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorSelector {
+    /// Index of the selector in the expression, used to look up the selected series.
+    pub id: usize,
+    pub matchers: Vec<Matcher>,
+    pub offset: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matcher {
+    pub name: String,
+    pub value: String,
+    pub type_: Type,
+}
+
+impl Matcher {
+    pub fn new(name: impl Into<String>, value: impl Into<String>, type_: Type) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            type_,
+        }
+    }
+}
+
+impl From<Matcher> for protos::prompb::types::LabelMatcher {
+    fn from(m: Matcher) -> Self {
+        Self {
+            name: m.name,
+            value: m.value,
+            type_: m.type_.into(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Rate,
+    Increase,
+    Irate,
+    Delta,
+    Idelta,
+    AvgOverTime,
+    MinOverTime,
+    MaxOverTime,
+    SumOverTime,
+    CountOverTime,
+    HistogramQuantile,
+    Abs,
+    Ceil,
+    Floor,
+    Round,
+}
+
+impl Function {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let func = match name {
+            "rate" => Self::Rate,
+            "increase" => Self::Increase,
+            "irate" => Self::Irate,
+            "delta" => Self::Delta,
+            "idelta" => Self::Idelta,
+            "avg_over_time" => Self::AvgOverTime,
+            "min_over_time" => Self::MinOverTime,
+            "max_over_time" => Self::MaxOverTime,
+            "sum_over_time" => Self::SumOverTime,
+            "count_over_time" => Self::CountOverTime,
+            "histogram_quantile" => Self::HistogramQuantile,
+            "abs" => Self::Abs,
+            "ceil" => Self::Ceil,
+            "floor" => Self::Floor,
+            "round" => Self::Round,
+            _ => return None,
+        };
+        Some(func)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rate => "rate",
+            Self::Increase => "increase",
+            Self::Irate => "irate",
+            Self::Delta => "delta",
+            Self::Idelta => "idelta",
+            Self::AvgOverTime => "avg_over_time",
+            Self::MinOverTime => "min_over_time",
+            Self::MaxOverTime => "max_over_time",
+            Self::SumOverTime => "sum_over_time",
+            Self::CountOverTime => "count_over_time",
+            Self::HistogramQuantile => "histogram_quantile",
+            Self::Abs => "abs",
+            Self::Ceil => "ceil",
+            Self::Floor => "floor",
+            Self::Round => "round",
+        }
+    }
+
+    /// Types of the arguments.
+    pub fn arg_types(&self) -> &'static [ValueType] {
+        match self {
+            Self::Rate
+            | Self::Increase
+            | Self::Irate
+            | Self::Delta
+            | Self::Idelta
+            | Self::AvgOverTime
+            | Self::MinOverTime
+            | Self::MaxOverTime
+            | Self::SumOverTime
+            | Self::CountOverTime => &[ValueType::Matrix],
+            Self::HistogramQuantile => &[ValueType::Scalar, ValueType::Vector],
+            Self::Abs | Self::Ceil | Self::Floor | Self::Round => &[ValueType::Vector],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateOp {
+    Sum,
+    Avg,
+    Min,
+    Max,
+    Count,
+}
+
+impl AggregateOp {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let op = match name {
+            "sum" => Self::Sum,
+            "avg" => Self::Avg,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "count" => Self::Count,
+            _ => return None,
+        };
+        Some(op)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Grouping {
+    By(Vec<String>),
+    Without(Vec<String>),
+}
+
+impl Default for Grouping {
+    fn default() -> Self {
+        Self::By(vec![])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    Eq,
+    Ne,
+    Gt,
+    Lt,
+    Ge,
+    Le,
+}
+
+impl BinaryOp {
+    /// Operators with higher precedence bind tighter.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Self::Eq | Self::Ne | Self::Gt | Self::Lt | Self::Ge | Self::Le => 1,
+            Self::Add | Self::Sub => 2,
+            Self::Mul | Self::Div | Self::Mod => 3,
+            Self::Pow => 4,
+        }
+    }
+
+    pub fn is_right_associative(&self) -> bool {
+        matches!(self, Self::Pow)
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        self.precedence() == 1
+    }
+
+    /// Apply the operator, comparisons return 1 for true and 0 for false.
+    pub fn apply(&self, lhs: f64, rhs: f64) -> f64 {
+        let bool_to_f64 = |b: bool| if b { 1.0 } else { 0.0 };
+        match self {
+            Self::Add => lhs + rhs,
+            Self::Sub => lhs - rhs,
+            Self::Mul => lhs * rhs,
+            Self::Div => lhs / rhs,
+            Self::Mod => lhs % rhs,
+            Self::Pow => lhs.powf(rhs),
+            Self::Eq => bool_to_f64(lhs == rhs),
+            Self::Ne => bool_to_f64(lhs != rhs),
+            Self::Gt => bool_to_f64(lhs > rhs),
+            Self::Lt => bool_to_f64(lhs < rhs),
+            Self::Ge => bool_to_f64(lhs >= rhs),
+            Self::Le => bool_to_f64(lhs <= rhs),
+        }
+    }
+}
@@ -0,0 +1,1000 @@
+//! Evaluation of PromQL expressions over the buckets selected by the selectors.
+//!
+//! The samples of each selector are summarized into buckets by the lowered queries,
+//! see [`super::lower`], then the expression is evaluated at every step like prometheus
+//! does, a lookback window or a range is the union of the buckets in it.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{json, Value as JsonValue};
+use spi::{QueryError, Result};
+
+use super::ast::{AggregateOp, BinaryOp, Expr, Function, Grouping, ValueType, VectorSelector};
+use super::LOOKBACK_DELTA_MS;
+use crate::prom::METRIC_NAME_LABEL;
+
+const BUCKET_LABEL: &str = "le";
+
+pub type Labels = BTreeMap<String, String>;
+
+/// Samples of a series, in ascending order of timestamp.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub labels: Labels,
+    pub samples: Vec<(i64, f64)>,
+}
+
+/// Summary of the samples of a series in `(end - width, end]`, timestamps are in milliseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bucket {
+    pub end: i64,
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub first: (i64, f64),
+    pub last: (i64, f64),
+    /// Sum of the increase of each sample from the previous one, a decrease is a counter reset.
+    pub increase: f64,
+    /// Increase of the first sample from the previous one.
+    pub first_increase: f64,
+    /// The sample before the last one.
+    pub prev: Option<(i64, f64)>,
+}
+
+impl Bucket {
+    /// A bucket of a raw sample.
+    pub fn from_sample((t, v): (i64, f64)) -> Self {
+        Self {
+            end: t,
+            count: 1,
+            sum: v,
+            min: v,
+            max: v,
+            first: (t, v),
+            last: (t, v),
+            increase: 0.0,
+            first_increase: 0.0,
+            prev: None,
+        }
+    }
+}
+
+/// Buckets of a series, in ascending order of the end.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectedSeries {
+    pub labels: Labels,
+    pub buckets: Vec<Bucket>,
+}
+
+/// Summary of the samples of a series in a range.
+#[derive(Debug, Clone, PartialEq)]
+struct Window {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    first: (i64, f64),
+    last: (i64, f64),
+    increase: f64,
+    prev: Option<(i64, f64)>,
+}
+
+impl Window {
+    /// Merge the buckets in the range `(start, ...]`.
+    fn merge(buckets: &[Bucket], start: i64) -> Option<Self> {
+        let (first, last) = (buckets.first()?, buckets.last()?);
+        let mut window = Self {
+            count: 0,
+            sum: 0.0,
+            min: f64::NAN,
+            max: f64::NAN,
+            first: first.first,
+            last: last.last,
+            // The previous sample of the first one is out of the range.
+            increase: -first.first_increase,
+            prev: last.prev.filter(|(t, _)| *t > start),
+        };
+        for b in buckets {
+            window.count += b.count;
+            window.sum += b.sum;
+            window.increase += b.increase;
+            if window.min.is_nan() || b.min < window.min {
+                window.min = b.min;
+            }
+            if window.max.is_nan() || b.max > window.max {
+                window.max = b.max;
+            }
+        }
+        Some(window)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub labels: Labels,
+    pub value: f64,
+}
+
+/// Result of an expression at a step.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Scalar(f64),
+    Vector(Vec<Sample>),
+}
+
+/// Result of a query, timestamps are in milliseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryResult {
+    Scalar(i64, f64),
+    Vector(i64, Vec<Sample>),
+    Matrix(Vec<Series>),
+}
+
+impl QueryResult {
+    /// The `data` of a prometheus HTTP API response.
+    pub fn to_json(&self) -> JsonValue {
+        let point = |t: i64, v: f64| json!([t as f64 / 1000.0, format_value(v)]);
+        match self {
+            Self::Scalar(t, v) => json!({
+                "resultType": "scalar",
+                "result": point(*t, *v),
+            }),
+            Self::Vector(t, samples) => json!({
+                "resultType": "vector",
+                "result": samples
+                    .iter()
+                    .map(|s| json!({"metric": s.labels, "value": point(*t, s.value)}))
+                    .collect::<Vec<_>>(),
+            }),
+            Self::Matrix(series) => json!({
+                "resultType": "matrix",
+                "result": series
+                    .iter()
+                    .map(|s| json!({
+                        "metric": s.labels,
+                        "values": s.samples.iter().map(|(t, v)| point(*t, *v)).collect::<Vec<_>>(),
+                    }))
+                    .collect::<Vec<_>>(),
+            }),
+        }
+    }
+}
+
+/// Format a sample value the same way as prometheus.
+fn format_value(v: f64) -> String {
+    if v.is_nan() {
+        "NaN".to_string()
+    } else if v == f64::INFINITY {
+        "+Inf".to_string()
+    } else if v == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        v.to_string()
+    }
+}
+
+fn execution_error(reason: impl Into<String>) -> QueryError {
+    QueryError::InvalidPromQL {
+        reason: reason.into(),
+    }
+}
+
+/// Evaluate `expr` at `time`, `series` are the selected series of each selector.
+pub fn instant_query(
+    expr: &Expr,
+    series: &HashMap<usize, Vec<SelectedSeries>>,
+    time: i64,
+) -> Result<QueryResult> {
+    let evaluator = Evaluator { series };
+    if let Expr::MatrixSelector { selector, range } = expr {
+        let matrix = evaluator
+            .matrix(selector, *range, time)
+            .into_iter()
+            .filter(|(_, buckets)| !buckets.is_empty())
+            .map(|(labels, buckets)| Series {
+                labels: labels.clone(),
+                samples: buckets.iter().map(|b| b.last).collect(),
+            })
+            .collect();
+        return Ok(QueryResult::Matrix(matrix));
+    }
+
+    match evaluator.eval(expr, time)? {
+        Value::Scalar(v) => Ok(QueryResult::Scalar(time, v)),
+        Value::Vector(mut samples) => {
+            check_duplicated_labels(&samples)?;
+            samples.sort_by(|a, b| a.labels.cmp(&b.labels));
+            Ok(QueryResult::Vector(time, samples))
+        }
+    }
+}
+
+/// Evaluate `expr` at every step from `start` to `end`.
+pub fn range_query(
+    expr: &Expr,
+    series: &HashMap<usize, Vec<SelectedSeries>>,
+    start: i64,
+    end: i64,
+    step: i64,
+) -> Result<QueryResult> {
+    match expr.value_type() {
+        ValueType::Scalar | ValueType::Vector => {}
+        ValueType::Matrix => {
+            return Err(execution_error(
+                "invalid expression type \"range vector\" for range query, must be Scalar or instant Vector",
+            ))
+        }
+    }
+
+    let evaluator = Evaluator { series };
+    let mut result: BTreeMap<Labels, Vec<(i64, f64)>> = BTreeMap::new();
+    let mut t = start;
+    while t <= end {
+        match evaluator.eval(expr, t)? {
+            Value::Scalar(v) => result.entry(Labels::new()).or_default().push((t, v)),
+            Value::Vector(samples) => {
+                check_duplicated_labels(&samples)?;
+                for s in samples {
+                    result.entry(s.labels).or_default().push((t, s.value));
+                }
+            }
+        }
+        t += step;
+    }
+
+    Ok(QueryResult::Matrix(
+        result
+            .into_iter()
+            .map(|(labels, samples)| Series { labels, samples })
+            .collect(),
+    ))
+}
+
+fn check_duplicated_labels(samples: &[Sample]) -> Result<()> {
+    let mut seen = std::collections::HashSet::with_capacity(samples.len());
+    for s in samples {
+        if !seen.insert(&s.labels) {
+            return Err(execution_error(
+                "vector cannot contain metrics with the same labelset",
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn drop_metric_name(mut labels: Labels) -> Labels {
+    labels.remove(METRIC_NAME_LABEL);
+    labels
+}
+
+struct Evaluator<'a> {
+    series: &'a HashMap<usize, Vec<SelectedSeries>>,
+}
+
+impl Evaluator<'_> {
+    fn selected(&self, selector: &VectorSelector) -> &[SelectedSeries] {
+        self.series
+            .get(&selector.id)
+            .map(|s| s.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The latest sample of each series in the lookback window.
+    fn vector(&self, selector: &VectorSelector, t: i64) -> Vec<Sample> {
+        let t = t - selector.offset;
+        self.selected(selector)
+            .iter()
+            .filter_map(|series| {
+                let idx = series.buckets.partition_point(|b| b.end <= t);
+                match idx.checked_sub(1).map(|i| series.buckets[i].last) {
+                    Some((ts, value)) if ts > t - LOOKBACK_DELTA_MS => Some(Sample {
+                        labels: series.labels.clone(),
+                        value,
+                    }),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Buckets of each series in the window `(t - offset - range, t - offset]`.
+    fn matrix(&self, selector: &VectorSelector, range: i64, t: i64) -> Vec<(&Labels, &[Bucket])> {
+        let end = t - selector.offset;
+        let start = end - range;
+        self.selected(selector)
+            .iter()
+            .map(|series| {
+                let lo = series.buckets.partition_point(|b| b.end <= start);
+                let hi = series.buckets.partition_point(|b| b.end <= end);
+                (&series.labels, &series.buckets[lo..hi.max(lo)])
+            })
+            .collect()
+    }
+
+    fn eval(&self, expr: &Expr, t: i64) -> Result<Value> {
+        match expr {
+            Expr::Number(n) => Ok(Value::Scalar(*n)),
+            Expr::VectorSelector(selector) => Ok(Value::Vector(self.vector(selector, t))),
+            Expr::MatrixSelector { .. } => Err(execution_error(
+                "range vector is only allowed as function argument",
+            )),
+            Expr::Neg(expr) => match self.eval(expr, t)? {
+                Value::Scalar(v) => Ok(Value::Scalar(-v)),
+                Value::Vector(samples) => Ok(Value::Vector(
+                    samples
+                        .into_iter()
+                        .map(|s| Sample {
+                            labels: drop_metric_name(s.labels),
+                            value: -s.value,
+                        })
+                        .collect(),
+                )),
+            },
+            Expr::Call { func, args } => self.eval_call(*func, args, t),
+            Expr::Aggregate { op, grouping, expr } => {
+                let samples = self.eval_vector(expr, t)?;
+                Ok(Value::Vector(aggregate(*op, grouping, samples)))
+            }
+            Expr::Binary { op, lhs, rhs } => {
+                let lhs = self.eval(lhs, t)?;
+                let rhs = self.eval(rhs, t)?;
+                binary(*op, lhs, rhs)
+            }
+        }
+    }
+
+    fn eval_vector(&self, expr: &Expr, t: i64) -> Result<Vec<Sample>> {
+        match self.eval(expr, t)? {
+            Value::Vector(samples) => Ok(samples),
+            Value::Scalar(_) => Err(execution_error("expected instant vector, got scalar")),
+        }
+    }
+
+    fn eval_call(&self, func: Function, args: &[Expr], t: i64) -> Result<Value> {
+        let arg_types = func.arg_types();
+        if args.len() != arg_types.len() {
+            return Err(execution_error(format!(
+                "expected {} argument(s) in call to function {:?}, got {}",
+                arg_types.len(),
+                func.name(),
+                args.len()
+            )));
+        }
+        for (arg, expected) in args.iter().zip(arg_types) {
+            if arg.value_type() != *expected {
+                return Err(execution_error(format!(
+                    "expected type {expected} in call to function {:?}, got {}",
+                    func.name(),
+                    arg.value_type()
+                )));
+            }
+        }
+
+        let samples = match (func, args) {
+            (Function::HistogramQuantile, [phi, expr]) => {
+                let phi = match self.eval(phi, t)? {
+                    Value::Scalar(phi) => phi,
+                    Value::Vector(_) => {
+                        return Err(execution_error("expected scalar, got instant vector"))
+                    }
+                };
+                histogram_quantile(phi, self.eval_vector(expr, t)?)
+            }
+            (Function::Abs | Function::Ceil | Function::Floor | Function::Round, [expr]) => self
+                .eval_vector(expr, t)?
+                .into_iter()
+                .map(|s| Sample {
+                    labels: drop_metric_name(s.labels),
+                    value: match func {
+                        Function::Abs => s.value.abs(),
+                        Function::Ceil => s.value.ceil(),
+                        Function::Floor => s.value.floor(),
+                        _ => s.value.round(),
+                    },
+                })
+                .collect(),
+            (_, [Expr::MatrixSelector { selector, range }]) => {
+                let end = t - selector.offset;
+                let start = end - range;
+                self.matrix(selector, *range, t)
+                    .into_iter()
+                    .filter_map(|(labels, buckets)| {
+                        let window = Window::merge(buckets, start)?;
+                        let value = match func {
+                            Function::Rate => extrapolated_rate(&window, start, end, true, true),
+                            Function::Increase => {
+                                extrapolated_rate(&window, start, end, true, false)
+                            }
+                            Function::Delta => extrapolated_rate(&window, start, end, false, false),
+                            Function::Irate => instant_value(&window, true),
+                            Function::Idelta => instant_value(&window, false),
+                            _ => over_time(func, &window),
+                        }?;
+                        Some(Sample {
+                            labels: drop_metric_name(labels.clone()),
+                            value,
+                        })
+                    })
+                    .collect()
+            }
+            _ => {
+                return Err(execution_error(format!(
+                    "invalid arguments of function {:?}",
+                    func.name()
+                )))
+            }
+        };
+
+        Ok(Value::Vector(samples))
+    }
+}
+
+/// Calculate rate, increase and delta, the result is extrapolated to the
+/// boundaries of the range like prometheus.
+fn extrapolated_rate(
+    window: &Window,
+    range_start: i64,
+    range_end: i64,
+    is_counter: bool,
+    is_rate: bool,
+) -> Option<f64> {
+    if window.count < 2 {
+        return None;
+    }
+    let (first_t, first_v) = window.first;
+    let (last_t, last_v) = window.last;
+
+    // The increase of a counter takes the counter resets into account.
+    let mut result = if is_counter {
+        window.increase
+    } else {
+        last_v - first_v
+    };
+
+    let mut duration_to_start = (first_t - range_start) as f64 / 1000.0;
+    let duration_to_end = (range_end - last_t) as f64 / 1000.0;
+    let sampled_interval = (last_t - first_t) as f64 / 1000.0;
+    let average_duration_between_samples = sampled_interval / (window.count - 1) as f64;
+
+    // Counters can not be negative, so do not extrapolate below zero.
+    if is_counter && result > 0.0 && first_v >= 0.0 {
+        let duration_to_zero = sampled_interval * (first_v / result);
+        if duration_to_zero < duration_to_start {
+            duration_to_start = duration_to_zero;
+        }
+    }
+
+    let extrapolation_threshold = average_duration_between_samples * 1.1;
+    let mut extrapolate_to_interval = sampled_interval;
+    extrapolate_to_interval += if duration_to_start < extrapolation_threshold {
+        duration_to_start
+    } else {
+        average_duration_between_samples / 2.0
+    };
+    extrapolate_to_interval += if duration_to_end < extrapolation_threshold {
+        duration_to_end
+    } else {
+        average_duration_between_samples / 2.0
+    };
+
+    result *= extrapolate_to_interval / sampled_interval;
+    if is_rate {
+        result /= (range_end - range_start) as f64 / 1000.0;
+    }
+
+    Some(result)
+}
+
+/// Calculate irate and idelta with the last two samples.
+fn instant_value(window: &Window, is_rate: bool) -> Option<f64> {
+    let (prev_t, prev_v) = window.prev?;
+    let (last_t, last_v) = window.last;
+
+    if !is_rate {
+        return Some(last_v - prev_v);
+    }
+
+    let interval = last_t - prev_t;
+    if interval == 0 {
+        return None;
+    }
+    let result = if last_v < prev_v {
+        // Counter reset
+        last_v
+    } else {
+        last_v - prev_v
+    };
+
+    Some(result / (interval as f64 / 1000.0))
+}
+
+fn over_time(func: Function, window: &Window) -> Option<f64> {
+    let value = match func {
+        Function::SumOverTime => window.sum,
+        Function::AvgOverTime => window.sum / window.count as f64,
+        Function::CountOverTime => window.count as f64,
+        Function::MinOverTime => window.min,
+        Function::MaxOverTime => window.max,
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn aggregate(op: AggregateOp, grouping: &Grouping, samples: Vec<Sample>) -> Vec<Sample> {
+    struct Group {
+        sum: f64,
+        count: usize,
+        min: f64,
+        max: f64,
+    }
+
+    let mut groups: BTreeMap<Labels, Group> = BTreeMap::new();
+    for s in samples {
+        let labels = match grouping {
+            Grouping::By(names) => s
+                .labels
+                .into_iter()
+                .filter(|(name, _)| names.contains(name))
+                .collect(),
+            Grouping::Without(names) => s
+                .labels
+                .into_iter()
+                .filter(|(name, _)| name != METRIC_NAME_LABEL && !names.contains(name))
+                .collect(),
+        };
+        let group = groups.entry(labels).or_insert(Group {
+            sum: 0.0,
+            count: 0,
+            min: f64::NAN,
+            max: f64::NAN,
+        });
+        group.sum += s.value;
+        group.count += 1;
+        if group.min.is_nan() || s.value < group.min {
+            group.min = s.value;
+        }
+        if group.max.is_nan() || s.value > group.max {
+            group.max = s.value;
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(labels, group)| Sample {
+            labels,
+            value: match op {
+                AggregateOp::Sum => group.sum,
+                AggregateOp::Avg => group.sum / group.count as f64,
+                AggregateOp::Min => group.min,
+                AggregateOp::Max => group.max,
+                AggregateOp::Count => group.count as f64,
+            },
+        })
+        .collect()
+}
+
+fn binary(op: BinaryOp, lhs: Value, rhs: Value) -> Result<Value> {
+    // Comparisons filter samples, the value of the vector side is kept.
+    let apply = |vector_side: f64, l: f64, r: f64| -> Option<f64> {
+        let value = op.apply(l, r);
+        if !op.is_comparison() {
+            Some(value)
+        } else if value == 1.0 {
+            Some(vector_side)
+        } else {
+            None
+        }
+    };
+    let result_labels = |labels: Labels| {
+        if op.is_comparison() {
+            labels
+        } else {
+            drop_metric_name(labels)
+        }
+    };
+
+    let samples = match (lhs, rhs) {
+        (Value::Scalar(l), Value::Scalar(r)) => return Ok(Value::Scalar(op.apply(l, r))),
+        (Value::Vector(lhs), Value::Scalar(r)) => lhs
+            .into_iter()
+            .filter_map(|s| {
+                apply(s.value, s.value, r).map(|value| Sample {
+                    labels: result_labels(s.labels),
+                    value,
+                })
+            })
+            .collect(),
+        (Value::Scalar(l), Value::Vector(rhs)) => rhs
+            .into_iter()
+            .filter_map(|s| {
+                apply(s.value, l, s.value).map(|value| Sample {
+                    labels: result_labels(s.labels),
+                    value,
+                })
+            })
+            .collect(),
+        (Value::Vector(lhs), Value::Vector(rhs)) => {
+            // One-to-one matching on all labels except the metric name.
+            let mut rhs_by_signature = HashMap::with_capacity(rhs.len());
+            for s in rhs {
+                let signature = drop_metric_name(s.labels);
+                if rhs_by_signature.insert(signature, s.value).is_some() {
+                    return Err(execution_error(
+                        "found duplicate series for the match group on the right hand-side of the operation, many-to-many matching not allowed",
+                    ));
+                }
+            }
+            let mut matched = std::collections::HashSet::with_capacity(lhs.len());
+            let mut samples = Vec::with_capacity(lhs.len());
+            for s in lhs {
+                let signature = drop_metric_name(s.labels.clone());
+                let r = match rhs_by_signature.get(&signature) {
+                    Some(r) => *r,
+                    None => continue,
+                };
+                if !matched.insert(signature) {
+                    return Err(execution_error(
+                        "found duplicate series for the match group on the left hand-side of the operation, many-to-many matching not allowed",
+                    ));
+                }
+                if let Some(value) = apply(s.value, s.value, r) {
+                    samples.push(Sample {
+                        labels: result_labels(s.labels),
+                        value,
+                    });
+                }
+            }
+            samples
+        }
+    };
+
+    Ok(Value::Vector(samples))
+}
+
+/// Calculate the quantile from the buckets of histograms, grouped by all labels except `le`.
+fn histogram_quantile(phi: f64, samples: Vec<Sample>) -> Vec<Sample> {
+    let mut histograms: BTreeMap<Labels, Vec<(f64, f64)>> = BTreeMap::new();
+    for s in samples {
+        let mut labels = drop_metric_name(s.labels);
+        let upper_bound = match labels.remove(BUCKET_LABEL).map(|le| le.parse::<f64>()) {
+            Some(Ok(upper_bound)) => upper_bound,
+            // Series without a valid `le` label are ignored.
+            _ => continue,
+        };
+        histograms
+            .entry(labels)
+            .or_default()
+            .push((upper_bound, s.value));
+    }
+
+    histograms
+        .into_iter()
+        .map(|(labels, buckets)| Sample {
+            labels,
+            value: bucket_quantile(phi, buckets),
+        })
+        .collect()
+}
+
+/// `buckets` are pairs of upper bound and cumulative count.
+fn bucket_quantile(phi: f64, mut buckets: Vec<(f64, f64)>) -> f64 {
+    if phi.is_nan() {
+        return f64::NAN;
+    }
+    if phi < 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if phi > 1.0 {
+        return f64::INFINITY;
+    }
+
+    buckets.sort_by(|a, b| a.0.total_cmp(&b.0));
+    match buckets.last() {
+        Some((upper_bound, _)) if *upper_bound == f64::INFINITY => {}
+        _ => return f64::NAN,
+    }
+    // Merge buckets with the same upper bound, and make the counts monotonic.
+    buckets.dedup_by(|b, a| {
+        if a.0 == b.0 {
+            a.1 += b.1;
+            true
+        } else {
+            false
+        }
+    });
+    for i in 1..buckets.len() {
+        if buckets[i].1 < buckets[i - 1].1 {
+            buckets[i].1 = buckets[i - 1].1;
+        }
+    }
+    if buckets.len() < 2 {
+        return f64::NAN;
+    }
+    let observations = buckets[buckets.len() - 1].1;
+    if observations == 0.0 {
+        return f64::NAN;
+    }
+
+    let mut rank = phi * observations;
+    let b = buckets.partition_point(|(_, count)| *count < rank);
+    if b == buckets.len() - 1 {
+        return buckets[buckets.len() - 2].0;
+    }
+    if b == 0 && buckets[0].0 <= 0.0 {
+        return buckets[0].0;
+    }
+
+    let (bucket_end, mut count) = buckets[b];
+    let mut bucket_start = 0.0;
+    if b > 0 {
+        bucket_start = buckets[b - 1].0;
+        count -= buckets[b - 1].1;
+        rank -= buckets[b - 1].1;
+    }
+
+    bucket_start + (bucket_end - bucket_start) * (rank / count)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+    use spi::server::prom::PromQueryRequest;
+
+    use super::{
+        bucket_quantile, extrapolated_rate, instant_query, range_query, Bucket, Labels,
+        SelectedSeries, Series, Window,
+    };
+    use crate::prom::promql::ast::{Expr, Function, VectorSelector};
+    use crate::prom::promql::lower::lower;
+    use crate::prom::promql::parser::parse;
+
+    fn labels(pairs: &[(&str, &str)]) -> Labels {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    /// A counter increases by 1 every 10 seconds from 0s to 600s.
+    fn counter(instance: &str) -> Series {
+        Series {
+            labels: labels(&[("__name__", "requests"), ("instance", instance)]),
+            samples: (0..=60).map(|i| (i * 10_000, i as f64)).collect(),
+        }
+    }
+
+    /// Summarize the samples into the buckets `(origin + n * width, origin + (n + 1) * width]`
+    /// like the lowered query does, the samples are not summarized if `width` is 0.
+    fn bucketize(samples: &[(i64, f64)], width: i64, origin: i64) -> Vec<Bucket> {
+        let mut buckets: Vec<Bucket> = vec![];
+        let mut prev: Option<(i64, f64)> = None;
+        for &(t, v) in samples {
+            if width == 0 {
+                buckets.push(Bucket::from_sample((t, v)));
+                continue;
+            }
+            let end = origin + (t - origin + width - 1) / width * width;
+            let increase = match prev {
+                Some((_, pv)) if v < pv => v,
+                Some((_, pv)) => v - pv,
+                None => 0.0,
+            };
+            match buckets.last_mut() {
+                Some(b) if b.end == end => {
+                    b.count += 1;
+                    b.sum += v;
+                    b.min = b.min.min(v);
+                    b.max = b.max.max(v);
+                    b.last = (t, v);
+                    b.increase += increase;
+                    b.prev = prev;
+                }
+                _ => buckets.push(Bucket {
+                    end,
+                    increase,
+                    first_increase: increase,
+                    prev,
+                    ..Bucket::from_sample((t, v))
+                }),
+            }
+            prev = Some((t, v));
+        }
+        buckets
+    }
+
+    /// Select the buckets of each selector of `query` from `series`.
+    fn select(
+        query: &str,
+        start: i64,
+        end: i64,
+        step: i64,
+        series: &[Series],
+    ) -> (Expr, HashMap<usize, Vec<SelectedSeries>>) {
+        let expr = parse(query).unwrap();
+        let req = PromQueryRequest {
+            query: query.to_string(),
+            start,
+            end,
+            step,
+        };
+        let selected = lower(&expr, &req)
+            .into_iter()
+            .map(|s| {
+                let selected = series
+                    .iter()
+                    .map(|series| {
+                        let samples = series
+                            .samples
+                            .iter()
+                            .filter(|(t, _)| *t > s.start && *t <= s.end)
+                            .copied()
+                            .collect::<Vec<_>>();
+                        SelectedSeries {
+                            labels: series.labels.clone(),
+                            buckets: bucketize(&samples, s.width, s.origin),
+                        }
+                    })
+                    .collect();
+                (s.selector.id, selected)
+            })
+            .collect();
+        (expr, selected)
+    }
+
+    #[test]
+    fn test_extrapolated_rate() {
+        let window = |samples: &[(i64, f64)]| {
+            Window::merge(&bucketize(samples, 10_000, 0), i64::MIN).unwrap()
+        };
+
+        let samples = window(&[(10_000, 1.0), (20_000, 2.0), (30_000, 3.0)]);
+        // Extrapolated to the boundaries of the range
+        let increase = extrapolated_rate(&samples, 0, 40_000, true, false).unwrap();
+        assert!((increase - 4.0).abs() < 1e-9);
+        // Counter reset
+        let samples = window(&[(10_000, 1.0), (20_000, 2.0), (30_000, 1.0)]);
+        let increase = extrapolated_rate(&samples, 10_000, 30_000, true, false).unwrap();
+        assert!((increase - 2.0).abs() < 1e-9);
+        let delta = extrapolated_rate(&samples, 10_000, 30_000, false, false).unwrap();
+        assert!(delta.abs() < 1e-9);
+        let samples = window(&[(10_000, 1.0)]);
+        assert!(extrapolated_rate(&samples, 0, 30_000, true, false).is_none());
+    }
+
+    #[test]
+    fn test_bucket_quantile() {
+        let buckets = vec![
+            (0.1, 10.0),
+            (0.5, 60.0),
+            (1.0, 100.0),
+            (f64::INFINITY, 100.0),
+        ];
+        assert!((bucket_quantile(0.5, buckets.clone()) - 0.42).abs() < 1e-9);
+        assert_eq!(bucket_quantile(1.0, buckets.clone()), 1.0);
+        assert!(bucket_quantile(0.5, vec![(0.1, 10.0)]).is_nan());
+        assert_eq!(bucket_quantile(-1.0, buckets), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_instant_query() {
+        let series = [counter("a"), counter("b")];
+
+        let (expr, selected) = select("sum(rate(requests[1m]))", 600_000, 600_000, 0, &series);
+        let result = instant_query(&expr, &selected, 600_000).unwrap();
+        assert_eq!(
+            result.to_json(),
+            json!({"resultType": "vector", "result": [{"metric": {}, "value": [600.0, "0.2"]}]})
+        );
+
+        let query = "requests{instance=\"a\"} offset 1m";
+        let (expr, selected) = select(query, 600_000, 600_000, 0, &series[..1]);
+        let result = instant_query(&expr, &selected, 600_000).unwrap();
+        assert_eq!(
+            result.to_json(),
+            json!({"resultType": "vector", "result": [
+                {"metric": {"__name__": "requests", "instance": "a"}, "value": [600.0, "54"]}
+            ]})
+        );
+
+        // Out of the lookback window
+        let (expr, selected) = select(query, 1_000_000, 1_000_000, 0, &series[..1]);
+        let result = instant_query(&expr, &selected, 1_000_000).unwrap();
+        assert_eq!(
+            result.to_json(),
+            json!({"resultType": "vector", "result": []})
+        );
+
+        // Raw samples of a range vector
+        let (expr, selected) = select("requests[30s]", 600_000, 600_000, 0, &series[..1]);
+        let result = instant_query(&expr, &selected, 600_000).unwrap();
+        assert_eq!(
+            result.to_json(),
+            json!({"resultType": "matrix", "result": [{
+                "metric": {"__name__": "requests", "instance": "a"},
+                "values": [[580.0, "58"], [590.0, "59"], [600.0, "60"]],
+            }]})
+        );
+
+        let (expr, selected) = select("1 + 2", 1_000, 1_000, 0, &series);
+        let result = instant_query(&expr, &selected, 1_000).unwrap();
+        assert_eq!(
+            result.to_json(),
+            json!({"resultType": "scalar", "result": [1.0, "3"]})
+        );
+    }
+
+    #[test]
+    fn test_range_query() {
+        let series = [counter("a"), counter("b")];
+
+        let query = "sum by (instance) (increase(requests[1m])) > 5";
+        let (expr, selected) = select(query, 300_000, 420_000, 60_000, &series);
+        let result = range_query(&expr, &selected, 300_000, 420_000, 60_000).unwrap();
+        let values = json!([[300.0, "6"], [360.0, "6"], [420.0, "6"]]);
+        assert_eq!(
+            result.to_json(),
+            json!({"resultType": "matrix", "result": [
+                {"metric": {"instance": "a"}, "values": values},
+                {"metric": {"instance": "b"}, "values": values},
+            ]})
+        );
+
+        // The range is not a multiple of the step, the buckets are 15s wide.
+        for (query, value) in [
+            ("rate(requests{instance=\"a\"}[45s])", "0.1"),
+            ("irate(requests{instance=\"a\"}[45s])", "0.1"),
+            ("delta(requests{instance=\"a\"}[45s])", "4.5"),
+            ("count_over_time(requests{instance=\"a\"}[45s])", "5"),
+            (
+                "max_over_time(requests{instance=\"a\"}[45s]) - requests",
+                "0",
+            ),
+        ] {
+            let (expr, selected) = select(query, 300_000, 390_000, 30_000, &series[..1]);
+            let result = range_query(&expr, &selected, 300_000, 390_000, 30_000).unwrap();
+            let values = [300.0, 330.0, 360.0, 390.0]
+                .into_iter()
+                .map(|t| json!([t, value]))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                result.to_json(),
+                json!({"resultType": "matrix", "result": [
+                    {"metric": {"instance": "a"}, "values": values},
+                ]}),
+                "{query}"
+            );
+        }
+
+        // The metric name is dropped, so the results have the same labels.
+        let mut duplicated = counter("a");
+        duplicated
+            .labels
+            .insert("__name__".to_string(), "requests_2".to_string());
+        let query = "rate({__name__=~\"req.*\"}[1m])";
+        let (expr, selected) = select(query, 300_000, 420_000, 60_000, &[counter("a"), duplicated]);
+        assert!(range_query(&expr, &selected, 300_000, 420_000, 60_000).is_err());
+    }
+
+    #[test]
+    fn test_range_vector_argument() {
+        // abs(requests[5m]) is rejected by the parser as well.
+        let expr = Expr::Call {
+            func: Function::Abs,
+            args: vec![Expr::MatrixSelector {
+                selector: VectorSelector {
+                    id: 0,
+                    matchers: vec![],
+                    offset: 0,
+                },
+                range: 300_000,
+            }],
+        };
+        let err = instant_query(&expr, &HashMap::new(), 600_000).unwrap_err();
+        assert!(err.to_string().contains(
+            "expected type instant vector in call to function \"abs\", got range vector"
+        ));
+    }
+}
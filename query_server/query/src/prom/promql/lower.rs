@@ -0,0 +1,692 @@
+//! Lowering of the selectors of a PromQL expression to aggregate queries.
+//!
+//! The samples of a selector are summarized into buckets by `date_bin` and the tags
+//! of the table, the buckets are aligned to the evaluation steps, so every lookback
+//! window and range of the expression is a union of buckets. Only the functions that
+//! can not be lowered (extrapolation, aggregation, binary operators and quantiles) are
+//! evaluated by [`super::engine`] over the buckets.
+//!
+//! The plan of a selector is built directly, it is like:
+//!
+//! ```text
+//! Projection: tag1, tag2, __bucket, COUNT(__value) AS __count, ..., last(time, __value) AS __last_value
+//!   Aggregate: groupBy=[[tag1, tag2, __bucket]], aggr=[[COUNT(__value), ..., last(time, __value)]]
+//!     Projection: tag1, tag2, time, CAST(value AS Float64) AS __value, date_bin(...) AS __bucket
+//!       Filter: <label matchers> AND value IS NOT NULL AND time > <start> AND time <= <end>
+//!         TableScan: metric
+//! ```
+
+use std::collections::BTreeMap;
+
+use datafusion::arrow::array::{as_primitive_array, as_string_array, Array, ArrayRef};
+use datafusion::arrow::compute;
+use datafusion::arrow::datatypes::{
+    DataType, Float64Type, Int64Type, IntervalMonthDayNanoType, TimeUnit,
+};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::Column;
+use datafusion::logical_expr::expr::{ScalarFunction, WindowFunction};
+use datafusion::logical_expr::{
+    window_function, BuiltInWindowFunction, BuiltinScalarFunction, LogicalPlan, LogicalPlanBuilder,
+    WindowFrame,
+};
+use datafusion::prelude::{cast, coalesce, count, lit, max, min, sum, when, Expr};
+use datafusion::scalar::ScalarValue;
+use models::schema::{TskvTableSchema, TIME_FIELD_NAME};
+use spi::server::prom::PromQueryRequest;
+use spi::{QueryError, Result};
+
+use super::ast::{self, Function, VectorSelector};
+use super::engine::{Bucket, Labels};
+use super::LOOKBACK_DELTA_MS;
+use crate::extension::expr::{FIRST_UDAF, LAST_UDAF};
+use crate::prom::{METRIC_NAME_LABEL, METRIC_SAMPLE_COLUMN_NAME};
+
+const NANOS_PER_MILLI: i64 = 1_000_000;
+
+const BUCKET: &str = "__bucket";
+const TIME: &str = "__time";
+const VALUE: &str = "__value";
+const COUNT: &str = "__count";
+const SUM: &str = "__sum";
+const MIN: &str = "__min";
+const MAX: &str = "__max";
+const FIRST_TIME: &str = "__first_time";
+const FIRST_VALUE: &str = "__first_value";
+const LAST_TIME: &str = "__last_time";
+const LAST_VALUE: &str = "__last_value";
+const INCREASE: &str = "__increase";
+const FIRST_INCREASE: &str = "__first_increase";
+const PREV_TIME: &str = "__prev_time";
+const PREV_VALUE: &str = "__prev_value";
+
+/// How the samples of a selector are used by the expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Usage {
+    /// An instant vector selector, the latest sample in the lookback window is used.
+    Instant,
+    /// Argument of `delta` and `*_over_time`, only the summary of the range is used.
+    Window,
+    /// Argument of `rate`, `increase`, `irate` and `idelta`, the increase and
+    /// the previous sample of each sample are needed as well.
+    WindowWithPrev,
+    /// A range vector returned by an instant query, the raw samples are returned.
+    Raw,
+}
+
+/// The query of a selector, all timestamps are in milliseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoweredSelector {
+    pub selector: VectorSelector,
+    pub usage: Usage,
+    /// Width of the buckets, the samples are not summarized if it is 0.
+    pub width: i64,
+    /// Buckets are `(origin + n * width, origin + (n + 1) * width]`.
+    pub origin: i64,
+    /// The samples in `(start, end]` are selected.
+    pub start: i64,
+    pub end: i64,
+}
+
+/// Lower the selectors of `expr` evaluated by `req`.
+pub fn lower(expr: &ast::Expr, req: &PromQueryRequest) -> Vec<LoweredSelector> {
+    let mut selectors = Vec::new();
+    collect_selectors(expr, None, &mut selectors);
+
+    selectors
+        .into_iter()
+        .map(|(selector, usage, range)| {
+            let window = if usage == Usage::Instant {
+                LOOKBACK_DELTA_MS
+            } else {
+                range
+            };
+            let width = match usage {
+                Usage::Raw => 0,
+                _ if req.is_instant() => window,
+                Usage::Instant => req.step,
+                Usage::Window | Usage::WindowWithPrev => gcd(range, req.step),
+            };
+            let start = req.start - selector.offset - window;
+            let end = req.end - selector.offset;
+            // The first evaluation step is the end of a bucket, and no sample is before the
+            // origin, `date_bin` does not round down the timestamps before the origin correctly.
+            let origin = if width > 0 {
+                req.start - selector.offset - (window + width - 1) / width * width
+            } else {
+                start
+            };
+
+            LoweredSelector {
+                selector,
+                usage,
+                width,
+                origin,
+                start,
+                end,
+            }
+        })
+        .collect()
+}
+
+fn collect_selectors(
+    expr: &ast::Expr,
+    func: Option<Function>,
+    selectors: &mut Vec<(VectorSelector, Usage, i64)>,
+) {
+    match expr {
+        ast::Expr::Number(_) => {}
+        ast::Expr::VectorSelector(selector) => {
+            selectors.push((selector.clone(), Usage::Instant, 0))
+        }
+        ast::Expr::MatrixSelector { selector, range } => {
+            let usage = match func {
+                Some(Function::Rate | Function::Increase | Function::Irate | Function::Idelta) => {
+                    Usage::WindowWithPrev
+                }
+                Some(_) => Usage::Window,
+                None => Usage::Raw,
+            };
+            selectors.push((selector.clone(), usage, *range));
+        }
+        ast::Expr::Call { func, args } => args
+            .iter()
+            .for_each(|e| collect_selectors(e, Some(*func), selectors)),
+        ast::Expr::Aggregate { expr, .. } | ast::Expr::Neg(expr) => {
+            collect_selectors(expr, None, selectors)
+        }
+        ast::Expr::Binary { lhs, rhs, .. } => {
+            collect_selectors(lhs, None, selectors);
+            collect_selectors(rhs, None, selectors);
+        }
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Convert a timestamp column to nanoseconds, whatever the precision of the table is.
+fn to_nanos(expr: Expr) -> Expr {
+    cast(
+        cast(expr, DataType::Timestamp(TimeUnit::Nanosecond, None)),
+        DataType::Int64,
+    )
+}
+
+/// The timestamp literal is coerced to the precision of the table.
+fn timestamp(nanos: i64) -> Expr {
+    lit(ScalarValue::TimestampNanosecond(Some(nanos), None))
+}
+
+/// The column named `name`, the name is not parsed as a qualified name.
+fn ident(name: &str) -> Expr {
+    Expr::Column(Column::from_name(name))
+}
+
+impl LoweredSelector {
+    /// The plan reading the samples of `table` from `scan`, `filters` are the filters
+    /// of the label matchers.
+    pub fn to_plan(
+        &self,
+        scan: LogicalPlanBuilder,
+        table: &TskvTableSchema,
+        filters: Vec<Expr>,
+    ) -> Result<LogicalPlan> {
+        let tags = tag_names(table)
+            .iter()
+            .map(|name| ident(name))
+            .collect::<Vec<_>>();
+        let time = ident(TIME_FIELD_NAME);
+        let value = cast(ident(METRIC_SAMPLE_COLUMN_NAME), DataType::Float64);
+
+        let predicate = filters.into_iter().fold(
+            ident(METRIC_SAMPLE_COLUMN_NAME)
+                .is_not_null()
+                .and(time.clone().gt(timestamp(self.start * NANOS_PER_MILLI)))
+                .and(time.clone().lt_eq(timestamp(self.end * NANOS_PER_MILLI))),
+            Expr::and,
+        );
+        let source = scan.filter(predicate)?;
+
+        if self.usage == Usage::Raw {
+            let mut projection = tags;
+            projection.extend([to_nanos(time).alias(TIME), value.alias(VALUE)]);
+            return Ok(source.project(projection)?.build()?);
+        }
+
+        let bucket = Expr::ScalarFunction(ScalarFunction {
+            fun: BuiltinScalarFunction::DateBin,
+            args: vec![
+                lit(ScalarValue::IntervalMonthDayNano(Some(
+                    IntervalMonthDayNanoType::make_value(0, 0, self.width * NANOS_PER_MILLI),
+                ))),
+                time.clone(),
+                // Plus 1ns to make the buckets left-open and right-closed.
+                timestamp(self.origin * NANOS_PER_MILLI + 1),
+            ],
+        });
+        let mut inner = tags.clone();
+        inner.extend([
+            time.clone(),
+            to_nanos(time.clone()).alias(TIME),
+            value.clone().alias(VALUE),
+            to_nanos(bucket).alias(BUCKET),
+        ]);
+        let source = if self.usage == Usage::WindowWithPrev {
+            let lag = |arg: Expr| {
+                Expr::WindowFunction(WindowFunction {
+                    fun: window_function::WindowFunction::BuiltInWindowFunction(
+                        BuiltInWindowFunction::Lag,
+                    ),
+                    args: vec![arg],
+                    partition_by: tags.clone(),
+                    order_by: vec![time.clone().sort(true, false)],
+                    window_frame: WindowFrame::new(true),
+                })
+            };
+            let prev_time = lag(time.clone());
+            let prev_value = lag(value);
+            inner.extend([
+                to_nanos(ident(&prev_time.display_name()?)).alias(PREV_TIME),
+                ident(&prev_value.display_name()?).alias(PREV_VALUE),
+            ]);
+            source.window(vec![prev_time, prev_value])?
+        } else {
+            source
+        };
+
+        let value = ident(VALUE);
+        let mut aggregates = vec![
+            (count(value.clone()), COUNT),
+            (sum(value.clone()), SUM),
+            (min(value.clone()), MIN),
+            (max(value.clone()), MAX),
+            (min(ident(TIME)), FIRST_TIME),
+            (
+                FIRST_UDAF.call(vec![time.clone(), value.clone()]),
+                FIRST_VALUE,
+            ),
+            (max(ident(TIME)), LAST_TIME),
+            (
+                LAST_UDAF.call(vec![time.clone(), value.clone()]),
+                LAST_VALUE,
+            ),
+        ];
+        if self.usage == Usage::WindowWithPrev {
+            let prev_value = ident(PREV_VALUE);
+            // Increase from the previous sample, a decrease is a counter reset.
+            let increase = when(prev_value.clone().is_null(), lit(0.0))
+                .when(value.clone().lt(prev_value.clone()), value.clone())
+                .otherwise(value - prev_value.clone())?;
+            // `first` and `last` skip null values, so the missing previous sample
+            // of the first sample is replaced with one out of the range.
+            let prev_time = coalesce(vec![ident(PREV_TIME), lit(self.start * NANOS_PER_MILLI)]);
+            aggregates.extend([
+                (sum(increase.clone()), INCREASE),
+                (
+                    FIRST_UDAF.call(vec![time.clone(), increase]),
+                    FIRST_INCREASE,
+                ),
+                (LAST_UDAF.call(vec![time.clone(), prev_time]), PREV_TIME),
+                (
+                    LAST_UDAF.call(vec![time, coalesce(vec![prev_value, lit(0.0)])]),
+                    PREV_VALUE,
+                ),
+            ]);
+        }
+
+        let mut group_by = tags;
+        group_by.push(ident(BUCKET));
+        // The results of the aggregates are renamed to the names expected by `decode`.
+        let mut projection = group_by.clone();
+        let mut aggregate_exprs = Vec::with_capacity(aggregates.len());
+        for (aggregate, name) in aggregates {
+            projection.push(ident(&aggregate.display_name()?).alias(name));
+            aggregate_exprs.push(aggregate);
+        }
+
+        Ok(source
+            .project(inner)?
+            .aggregate(group_by, aggregate_exprs)?
+            .project(projection)?
+            .build()?)
+    }
+
+    /// Decode the result of the query of `table`, the buckets are appended to the
+    /// series with the same labels, the metric name is added to the labels if the
+    /// table is not written by prometheus remote write.
+    pub fn decode(
+        &self,
+        table: &TskvTableSchema,
+        batch: &RecordBatch,
+        series: &mut BTreeMap<Labels, Vec<Bucket>>,
+    ) -> Result<()> {
+        let tag_names = tag_names(table);
+        let mut tags = Vec::with_capacity(tag_names.len());
+        for name in tag_names.iter() {
+            let col = column(batch, name)?;
+            if col.data_type() != &DataType::Utf8 {
+                return Err(QueryError::CommonError {
+                    msg: "Tag noly support string type".to_string(),
+                });
+            }
+            tags.push(col.clone());
+        }
+
+        let labels = |row: usize| {
+            let mut labels = tag_names
+                .iter()
+                .zip(tags.iter())
+                .filter(|(_, col)| col.is_valid(row))
+                .map(|(name, col)| (name.to_string(), as_string_array(col).value(row)))
+                .filter(|(_, value)| !value.is_empty())
+                .map(|(name, value)| (name, value.to_string()))
+                .collect::<Labels>();
+            labels
+                .entry(METRIC_NAME_LABEL.to_string())
+                .or_insert_with(|| table.name.to_string());
+            labels
+        };
+
+        if self.usage == Usage::Raw {
+            let times = i64_column(batch, TIME)?;
+            let values = f64_column(batch, VALUE)?;
+            let times = as_primitive_array::<Int64Type>(&times);
+            let values = as_primitive_array::<Float64Type>(&values);
+            for row in 0..batch.num_rows() {
+                let sample = (to_millis(times.value(row)), values.value(row));
+                series
+                    .entry(labels(row))
+                    .or_default()
+                    .push(Bucket::from_sample(sample));
+            }
+            return Ok(());
+        }
+
+        let bucket = i64_column(batch, BUCKET)?;
+        let count = i64_column(batch, COUNT)?;
+        let sum = f64_column(batch, SUM)?;
+        let min = f64_column(batch, MIN)?;
+        let max = f64_column(batch, MAX)?;
+        let first_time = i64_column(batch, FIRST_TIME)?;
+        let first_value = f64_column(batch, FIRST_VALUE)?;
+        let last_time = i64_column(batch, LAST_TIME)?;
+        let last_value = f64_column(batch, LAST_VALUE)?;
+        let with_prev = if self.usage == Usage::WindowWithPrev {
+            Some((
+                f64_column(batch, INCREASE)?,
+                f64_column(batch, FIRST_INCREASE)?,
+                i64_column(batch, PREV_TIME)?,
+                f64_column(batch, PREV_VALUE)?,
+            ))
+        } else {
+            None
+        };
+
+        let int = |col: &ArrayRef, row: usize| as_primitive_array::<Int64Type>(col).value(row);
+        let float = |col: &ArrayRef, row: usize| as_primitive_array::<Float64Type>(col).value(row);
+        for row in 0..batch.num_rows() {
+            // `date_bin` returns the start of the bucket, which is `end - width + 1ns`.
+            let end = to_millis(int(&bucket, row) - 1) + self.width;
+            let mut b = Bucket {
+                end,
+                count: int(&count, row) as u64,
+                sum: float(&sum, row),
+                min: float(&min, row),
+                max: float(&max, row),
+                first: (to_millis(int(&first_time, row)), float(&first_value, row)),
+                last: (to_millis(int(&last_time, row)), float(&last_value, row)),
+                increase: 0.0,
+                first_increase: 0.0,
+                prev: None,
+            };
+            if let Some((increase, first_increase, prev_time, prev_value)) = &with_prev {
+                b.increase = float(increase, row);
+                b.first_increase = float(first_increase, row);
+                b.prev = Some((to_millis(int(prev_time, row)), float(prev_value, row)))
+                    .filter(|(t, _)| *t > self.start);
+            }
+            series.entry(labels(row)).or_default().push(b);
+        }
+
+        Ok(())
+    }
+}
+
+fn tag_names(table: &TskvTableSchema) -> Vec<String> {
+    let columns = table.columns();
+    table
+        .tag_indices()
+        .into_iter()
+        .map(|i| columns[i].name.clone())
+        .collect()
+}
+
+fn to_millis(nanos: i64) -> i64 {
+    nanos.div_euclid(NANOS_PER_MILLI)
+}
+
+fn column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a ArrayRef> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| QueryError::CommonError {
+            msg: format!("column {name} not found in the result of PromQL selector"),
+        })
+}
+
+/// The types of some aggregates depend on the input, so the columns are casted
+/// to the types expected by [`Bucket`].
+fn i64_column(batch: &RecordBatch, name: &str) -> Result<ArrayRef> {
+    Ok(compute::cast(column(batch, name)?, &DataType::Int64)?)
+}
+
+fn f64_column(batch: &RecordBatch, name: &str) -> Result<ArrayRef> {
+    Ok(compute::cast(column(batch, name)?, &DataType::Float64)?)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use coordinator::service_mock::MockCoordinator;
+    use datafusion::arrow::array::{ArrayRef, Float64Array, Int64Array, StringArray};
+    use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use datafusion::arrow::record_batch::RecordBatch;
+    use datafusion::datasource::provider_as_source;
+    use datafusion::logical_expr::{LogicalPlan, LogicalPlanBuilder};
+    use datafusion::prelude::{col, lit};
+    use meta::model::meta_tenant::TenantMeta;
+    use models::codec::Encoding;
+    use models::schema::{ColumnType, TableColumn, TskvTableSchema};
+    use models::ValueType;
+    use spi::server::prom::PromQueryRequest;
+
+    use super::{lower, LoweredSelector, Usage};
+    use crate::data_source::batch::tskv::ClusterTable;
+    use crate::data_source::split;
+    use crate::prom::promql::engine::{Bucket, Labels};
+    use crate::prom::promql::parser::parse;
+
+    fn lowered(query: &str, start: i64, end: i64, step: i64) -> Vec<(Usage, i64, i64, i64, i64)> {
+        let req = PromQueryRequest {
+            query: query.to_string(),
+            start,
+            end,
+            step,
+        };
+        lower(&parse(query).unwrap(), &req)
+            .into_iter()
+            .map(|s| (s.usage, s.width, s.origin, s.start, s.end))
+            .collect()
+    }
+
+    fn table() -> TskvTableSchema {
+        TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "requests".to_string(),
+            vec![
+                TableColumn::new_time_column(0, TimeUnit::Nanosecond),
+                TableColumn::new_tag_column(1, "instance".to_string()),
+                TableColumn::new(
+                    2,
+                    "value".to_string(),
+                    ColumnType::Field(ValueType::Float),
+                    Encoding::Default,
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_lower() {
+        assert_eq!(
+            lowered("requests offset 1m", 600_000, 600_000, 0),
+            vec![(Usage::Instant, 300_000, 240_000, 240_000, 540_000)]
+        );
+        assert_eq!(
+            lowered("requests[5m]", 600_000, 600_000, 0),
+            vec![(Usage::Raw, 0, 300_000, 300_000, 600_000)]
+        );
+        assert_eq!(
+            lowered(
+                "sum(rate(requests[45s])) + max_over_time(requests[1m]) - requests",
+                300_000,
+                390_000,
+                30_000
+            ),
+            vec![
+                (Usage::WindowWithPrev, 15_000, 255_000, 255_000, 390_000),
+                (Usage::Window, 30_000, 240_000, 240_000, 390_000),
+                (Usage::Instant, 30_000, 0, 0, 390_000),
+            ]
+        );
+        // The lookback window is not a multiple of the step.
+        assert_eq!(
+            lowered("requests", 70_000, 100_000, 40_000),
+            vec![(Usage::Instant, 40_000, -250_000, -230_000, 100_000)]
+        );
+    }
+
+    fn scan() -> LogicalPlanBuilder {
+        let provider = Arc::new(ClusterTable::new(
+            Arc::new(MockCoordinator::default()),
+            split::default_split_manager_ref_only_for_test(),
+            Arc::new(TenantMeta::mock()),
+            Arc::new(table()),
+        ));
+        LogicalPlanBuilder::scan("requests", provider_as_source(provider), None).unwrap()
+    }
+
+    fn field_names(plan: &LogicalPlan) -> Vec<String> {
+        plan.schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_to_plan() {
+        let req = PromQueryRequest {
+            query: "rate(requests[1m])".to_string(),
+            start: 600_000,
+            end: 600_000,
+            step: 0,
+        };
+        let selector = lower(&parse(&req.query).unwrap(), &req).remove(0);
+        let filter = col("instance").eq(lit("a"));
+        let plan = selector.to_plan(scan(), &table(), vec![filter]).unwrap();
+        assert_eq!(
+            field_names(&plan),
+            vec![
+                "instance",
+                "__bucket",
+                "__count",
+                "__sum",
+                "__min",
+                "__max",
+                "__first_time",
+                "__first_value",
+                "__last_time",
+                "__last_value",
+                "__increase",
+                "__first_increase",
+                "__prev_time",
+                "__prev_value",
+            ]
+        );
+        let display = format!("{}", plan.display_indent());
+        assert!(display.contains("WindowAggr:"), "{display}");
+        assert!(display.contains("Aggregate: groupBy=[["), "{display}");
+        assert!(display.contains("instance = Utf8(\"a\")"), "{display}");
+
+        let selector = LoweredSelector {
+            usage: Usage::Raw,
+            width: 0,
+            ..selector
+        };
+        let plan = selector.to_plan(scan(), &table(), vec![]).unwrap();
+        assert_eq!(field_names(&plan), vec!["instance", "__time", "__value"]);
+        let display = format!("{}", plan.display_indent());
+        assert!(!display.contains("Aggregate:"), "{display}");
+    }
+
+    #[test]
+    fn test_decode() {
+        let req = PromQueryRequest {
+            query: "irate(requests[1m])".to_string(),
+            start: 600_000,
+            end: 600_000,
+            step: 0,
+        };
+        let selector = lower(&parse(&req.query).unwrap(), &req).remove(0);
+
+        let int = |name: &str| Field::new(name, DataType::Int64, true);
+        let float = |name: &str| Field::new(name, DataType::Float64, true);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("instance", DataType::Utf8, true),
+            int("__bucket"),
+            int("__count"),
+            float("__sum"),
+            float("__min"),
+            float("__max"),
+            int("__first_time"),
+            float("__first_value"),
+            int("__last_time"),
+            float("__last_value"),
+            float("__increase"),
+            float("__first_increase"),
+            int("__prev_time"),
+            float("__prev_value"),
+        ]));
+        let ints = |v: i64| -> ArrayRef { Arc::new(Int64Array::from(vec![v, v])) };
+        let floats = |v: f64| -> ArrayRef { Arc::new(Float64Array::from(vec![v, v])) };
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec![Some("a"), None])) as ArrayRef,
+                ints(540_000_000_001),
+                ints(2),
+                floats(3.0),
+                floats(1.0),
+                floats(2.0),
+                ints(550_000_000_000),
+                floats(1.0),
+                Arc::new(Int64Array::from(vec![560_000_000_000, 550_000_000_000])),
+                floats(2.0),
+                floats(1.0),
+                floats(0.0),
+                // The previous sample of the second series is out of the range.
+                Arc::new(Int64Array::from(vec![550_000_000_000, 540_000_000_000])),
+                floats(1.0),
+            ],
+        )
+        .unwrap();
+
+        let mut series = BTreeMap::new();
+        selector.decode(&table(), &batch, &mut series).unwrap();
+
+        let bucket = Bucket {
+            end: 600_000,
+            count: 2,
+            sum: 3.0,
+            min: 1.0,
+            max: 2.0,
+            first: (550_000, 1.0),
+            last: (560_000, 2.0),
+            increase: 1.0,
+            first_increase: 0.0,
+            prev: Some((550_000, 1.0)),
+        };
+        let labels = |pairs: &[(&str, &str)]| -> Labels {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        assert_eq!(
+            series,
+            BTreeMap::from([
+                (
+                    labels(&[("__name__", "requests"), ("instance", "a")]),
+                    vec![bucket.clone()]
+                ),
+                (
+                    labels(&[("__name__", "requests")]),
+                    vec![Bucket {
+                        last: (550_000, 2.0),
+                        prev: None,
+                        ..bucket
+                    }]
+                ),
+            ])
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! PromQL support of the prometheus HTTP API `/api/v1/query` and `/api/v1/query_range`.
+//!
+//! The selectors of an expression are lowered to aggregate queries of the tables written
+//! by prometheus remote write, see [`lower`], then the rest of the expression is evaluated
+//! over the aggregated buckets by [`engine`].
+
+pub mod ast;
+pub mod engine;
+pub mod lower;
+pub mod parser;
+
+use chrono::DateTime;
+use spi::server::prom::PromQueryRequest;
+use spi::{QueryError, Result};
+
+/// How far to look back for the latest sample of a series, the same as prometheus.
+pub const LOOKBACK_DELTA_MS: i64 = 5 * 60 * 1000;
+/// Max points of a series returned by a range query, the same as prometheus.
+pub const MAX_POINTS_PER_SERIES: i64 = 11_000;
+
+/// Build the request of `/api/v1/query`, `time` defaults to now.
+pub fn instant_query_request(
+    query: String,
+    time: Option<&str>,
+    now: i64,
+) -> Result<PromQueryRequest> {
+    let time = time.map(parse_time).transpose()?.unwrap_or(now);
+
+    Ok(PromQueryRequest {
+        query,
+        start: time,
+        end: time,
+        step: 0,
+    })
+}
+
+/// Build the request of `/api/v1/query_range`.
+pub fn range_query_request(
+    query: String,
+    start: Option<&str>,
+    end: Option<&str>,
+    step: Option<&str>,
+) -> Result<PromQueryRequest> {
+    let missing = |param: &str| QueryError::InvalidPromQL {
+        reason: format!("missing parameter: {param}"),
+    };
+    let start = parse_time(start.ok_or_else(|| missing("start"))?)?;
+    let end = parse_time(end.ok_or_else(|| missing("end"))?)?;
+    let step = parse_step(step.ok_or_else(|| missing("step"))?)?;
+
+    if end < start {
+        return Err(QueryError::InvalidPromQL {
+            reason: "end timestamp must not be before start time".to_string(),
+        });
+    }
+    if step <= 0 {
+        return Err(QueryError::InvalidPromQL {
+            reason: "zero or negative query resolution step widths are not accepted. Try a positive integer".to_string(),
+        });
+    }
+    if (end - start) / step >= MAX_POINTS_PER_SERIES {
+        return Err(QueryError::InvalidPromQL {
+            reason: "exceeded maximum resolution of 11,000 points per timeseries. Try decreasing the query resolution (?step=XX)".to_string(),
+        });
+    }
+
+    Ok(PromQueryRequest {
+        query,
+        start,
+        end,
+        step,
+    })
+}
+
+/// Parse a unix timestamp in seconds or a RFC3339 time, returns milliseconds.
+pub fn parse_time(s: &str) -> Result<i64> {
+    if let Ok(seconds) = s.parse::<f64>() {
+        if seconds.is_finite() {
+            return Ok((seconds * 1000.0).round() as i64);
+        }
+    }
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.timestamp_millis())
+        .map_err(|_| QueryError::InvalidPromQL {
+            reason: format!("cannot parse {s:?} to a valid timestamp"),
+        })
+}
+
+/// Parse a step in seconds or a duration like `1m`, returns milliseconds.
+pub fn parse_step(s: &str) -> Result<i64> {
+    if let Ok(seconds) = s.parse::<f64>() {
+        if seconds.is_finite() {
+            return Ok((seconds * 1000.0).round() as i64);
+        }
+    }
+    parser::parse_duration(s).map_err(|_| QueryError::InvalidPromQL {
+        reason: format!("cannot parse {s:?} to a valid duration"),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_step, parse_time, range_query_request};
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("1435781451.781").unwrap(), 1_435_781_451_781);
+        assert_eq!(
+            parse_time("2015-07-01T20:10:51.781Z").unwrap(),
+            1_435_781_451_781
+        );
+        assert!(parse_time("yesterday").is_err());
+
+        assert_eq!(parse_step("15").unwrap(), 15_000);
+        assert_eq!(parse_step("1m").unwrap(), 60_000);
+    }
+
+    #[test]
+    fn test_range_query_request() {
+        let req =
+            range_query_request("up".to_string(), Some("0"), Some("60"), Some("15s")).unwrap();
+        assert_eq!((req.start, req.end, req.step), (0, 60_000, 15_000));
+
+        assert!(range_query_request("up".to_string(), Some("60"), Some("0"), Some("15")).is_err());
+        assert!(range_query_request("up".to_string(), Some("0"), Some("60"), None).is_err());
+        assert!(
+            range_query_request("up".to_string(), Some("0"), Some("86400"), Some("1")).is_err()
+        );
+    }
+}
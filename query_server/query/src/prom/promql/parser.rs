@@ -0,0 +1,819 @@
+//! A hand-written parser of the subset of PromQL that is supported by [`super::engine`].
+
+use protos::prompb::types::label_matcher::Type;
+use spi::{QueryError, Result};
+
+use super::ast::{
+    AggregateOp, BinaryOp, Expr, Function, Grouping, Matcher, ValueType, VectorSelector,
+};
+use crate::prom::METRIC_NAME_LABEL;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    /// Duration in milliseconds.
+    Duration(i64),
+    Str(String),
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Comma,
+    Colon,
+    Assign,
+    NotEq,
+    RegexMatch,
+    RegexNotMatch,
+    EqEq,
+    Gt,
+    Lt,
+    Ge,
+    Le,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+}
+
+impl Token {
+    fn binary_op(&self) -> Option<BinaryOp> {
+        let op = match self {
+            Self::Add => BinaryOp::Add,
+            Self::Sub => BinaryOp::Sub,
+            Self::Mul => BinaryOp::Mul,
+            Self::Div => BinaryOp::Div,
+            Self::Mod => BinaryOp::Mod,
+            Self::Pow => BinaryOp::Pow,
+            Self::EqEq => BinaryOp::Eq,
+            Self::NotEq => BinaryOp::Ne,
+            Self::Gt => BinaryOp::Gt,
+            Self::Lt => BinaryOp::Lt,
+            Self::Ge => BinaryOp::Ge,
+            Self::Le => BinaryOp::Le,
+            _ => return None,
+        };
+        Some(op)
+    }
+}
+
+fn syntax_error(reason: impl Into<String>) -> QueryError {
+    QueryError::InvalidPromQL {
+        reason: reason.into(),
+    }
+}
+
+/// Parse a PromQL expression.
+pub fn parse(input: &str) -> Result<Expr> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Err(syntax_error("no expression found in input"));
+    }
+
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        next_selector_id: 0,
+    };
+    let expr = parser.parse_expr(0)?;
+    if let Some(token) = parser.peek() {
+        return Err(syntax_error(format!("unexpected {token:?}")));
+    }
+
+    Ok(expr)
+}
+
+/// Parse a duration like `1h30m`, returns milliseconds.
+pub fn parse_duration(input: &str) -> Result<i64> {
+    match tokenize(input)?.as_slice() {
+        [Token::Duration(d)] => Ok(*d),
+        _ => Err(syntax_error(format!("invalid duration: {input}"))),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            ',' => Token::Comma,
+            '+' => Token::Add,
+            '-' => Token::Sub,
+            '*' => Token::Mul,
+            '/' => Token::Div,
+            '%' => Token::Mod,
+            '^' => Token::Pow,
+            '=' => match next {
+                Some('=') => {
+                    i += 1;
+                    Token::EqEq
+                }
+                Some('~') => {
+                    i += 1;
+                    Token::RegexMatch
+                }
+                _ => Token::Assign,
+            },
+            '!' => match next {
+                Some('=') => {
+                    i += 1;
+                    Token::NotEq
+                }
+                Some('~') => {
+                    i += 1;
+                    Token::RegexNotMatch
+                }
+                _ => return Err(syntax_error("unexpected character after '!'")),
+            },
+            '>' | '<' => {
+                let or_equal = next == Some('=');
+                if or_equal {
+                    i += 1;
+                }
+                match (c, or_equal) {
+                    ('>', true) => Token::Ge,
+                    ('>', false) => Token::Gt,
+                    ('<', true) => Token::Le,
+                    _ => Token::Lt,
+                }
+            }
+            '"' | '\'' | '`' => {
+                let (s, end) = lex_string(&chars, i)?;
+                tokens.push(Token::Str(s));
+                i = end;
+                continue;
+            }
+            c if c.is_ascii_digit() || (c == '.' && next.map_or(false, |n| n.is_ascii_digit())) => {
+                let (token, end) = lex_number_or_duration(&chars, i)?;
+                tokens.push(token);
+                i = end;
+                continue;
+            }
+            // A ':' not followed by an identifier is the separator of subqueries.
+            ':' if !next.map_or(false, |n| n.is_ascii_alphabetic() || n == '_' || n == ':') => {
+                Token::Colon
+            }
+            c if c.is_ascii_alphabetic() || c == '_' || c == ':' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == ':')
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+                continue;
+            }
+            c => return Err(syntax_error(format!("unexpected character: {c:?}"))),
+        };
+        tokens.push(token);
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+/// Returns the unescaped string and the position after the closing quote.
+fn lex_string(chars: &[char], start: usize) -> Result<(String, usize)> {
+    let quote = chars[start];
+    let mut s = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == quote {
+            return Ok((s, i + 1));
+        }
+        if c == '\\' && quote != '`' {
+            i += 1;
+            let escaped = chars
+                .get(i)
+                .ok_or_else(|| syntax_error("unterminated quoted string"))?;
+            match escaped {
+                'n' => s.push('\n'),
+                't' => s.push('\t'),
+                'r' => s.push('\r'),
+                // Keep the backslash of unknown escapes, e.g. `\d` in regular expressions.
+                '\\' | '"' | '\'' => s.push(*escaped),
+                other => {
+                    s.push('\\');
+                    s.push(*other);
+                }
+            }
+        } else {
+            s.push(c);
+        }
+        i += 1;
+    }
+
+    Err(syntax_error("unterminated quoted string"))
+}
+
+/// Returns a number or a duration like `1h30m`, and the position after it.
+fn lex_number_or_duration(chars: &[char], start: usize) -> Result<(Token, usize)> {
+    let digits_end = |mut i: usize| {
+        while i < chars.len() && chars[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+    let unit_at = |i: usize| -> Option<(i64, usize)> {
+        let unit = match (chars.get(i), chars.get(i + 1)) {
+            (Some('m'), Some('s')) => return Some((1, 2)),
+            (Some('s'), _) => 1_000,
+            (Some('m'), _) => 60_000,
+            (Some('h'), _) => 3_600_000,
+            (Some('d'), _) => 86_400_000,
+            (Some('w'), _) => 7 * 86_400_000,
+            (Some('y'), _) => 365 * 86_400_000,
+            _ => return None,
+        };
+        Some((unit, 1))
+    };
+
+    let mut i = digits_end(start);
+    if unit_at(i).is_some() {
+        let mut duration = 0_i64;
+        let mut component_start = start;
+        loop {
+            let value = chars[component_start..i]
+                .iter()
+                .collect::<String>()
+                .parse::<i64>()
+                .map_err(|e| syntax_error(format!("invalid duration: {e}")))?;
+            let (unit, unit_len) = match unit_at(i) {
+                Some(unit) => unit,
+                None => break,
+            };
+            duration = value
+                .checked_mul(unit)
+                .and_then(|d| d.checked_add(duration))
+                .ok_or_else(|| syntax_error("duration out of range"))?;
+            i += unit_len;
+            if !chars.get(i).map_or(false, |c| c.is_ascii_digit()) {
+                break;
+            }
+            component_start = i;
+            i = digits_end(i);
+        }
+        if chars
+            .get(i)
+            .map_or(false, |c| c.is_ascii_alphanumeric() || *c == '_')
+        {
+            return Err(syntax_error("invalid duration"));
+        }
+        return Ok((Token::Duration(duration), i));
+    }
+
+    if chars.get(i) == Some(&'.') {
+        i = digits_end(i + 1);
+    }
+    if matches!(chars.get(i), Some('e') | Some('E')) {
+        let mut j = i + 1;
+        if matches!(chars.get(j), Some('+') | Some('-')) {
+            j += 1;
+        }
+        if chars.get(j).map_or(false, |c| c.is_ascii_digit()) {
+            i = digits_end(j);
+        }
+    }
+    let literal = chars[start..i].iter().collect::<String>();
+    let number = literal
+        .parse::<f64>()
+        .map_err(|_| syntax_error(format!("invalid number: {literal}")))?;
+
+    Ok((Token::Number(number), i))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    next_selector_id: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_ident(&self) -> Option<&str> {
+        match self.peek() {
+            Some(Token::Ident(ident)) => Some(ident),
+            _ => None,
+        }
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| syntax_error("unexpected end of input"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        let token = self.next()?;
+        if token != expected {
+            return Err(syntax_error(format!(
+                "unexpected {token:?}, expected {expected:?}"
+            )));
+        }
+        Ok(())
+    }
+
+    fn parse_expr(&mut self, min_precedence: u8) -> Result<Expr> {
+        let mut lhs = self.parse_unary()?;
+        loop {
+            let op = match self.peek().and_then(Token::binary_op) {
+                Some(op) if op.precedence() >= min_precedence => op,
+                _ => break,
+            };
+            self.pos += 1;
+            if self.peek_ident() == Some("bool") {
+                return Err(syntax_error("bool modifier is not supported"));
+            }
+            let next_precedence = if op.is_right_associative() {
+                op.precedence()
+            } else {
+                op.precedence() + 1
+            };
+            let rhs = self.parse_expr(next_precedence)?;
+            lhs = binary_expr(op, lhs, rhs)?;
+        }
+
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        match self.peek() {
+            // `-a ^ b` is parsed as `-(a ^ b)`
+            Some(Token::Sub) => {
+                self.pos += 1;
+                let expr = self.parse_expr(BinaryOp::Pow.precedence())?;
+                match expr {
+                    Expr::Number(n) => Ok(Expr::Number(-n)),
+                    expr => {
+                        check_operand(&expr)?;
+                        Ok(Expr::Neg(Box::new(expr)))
+                    }
+                }
+            }
+            Some(Token::Add) => {
+                self.pos += 1;
+                self.parse_expr(BinaryOp::Pow.precedence())
+            }
+            _ => self.parse_postfix(),
+        }
+    }
+
+    /// Parse range and offset after a selector.
+    fn parse_postfix(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_primary()?;
+
+        if self.peek() == Some(&Token::LBracket) {
+            self.pos += 1;
+            let range = match self.next()? {
+                Token::Duration(d) if d > 0 => d,
+                token => {
+                    return Err(syntax_error(format!(
+                        "unexpected {token:?} in range, expected a positive duration"
+                    )))
+                }
+            };
+            if self.peek() == Some(&Token::Colon) {
+                return Err(syntax_error("subquery is not supported"));
+            }
+            self.expect(Token::RBracket)?;
+            expr = match expr {
+                Expr::VectorSelector(selector) => Expr::MatrixSelector { selector, range },
+                _ => return Err(syntax_error("ranges only allowed for vector selectors")),
+            };
+        }
+
+        if self.peek_ident() == Some("offset") {
+            self.pos += 1;
+            let offset = match self.next()? {
+                Token::Duration(d) => d,
+                Token::Sub => match self.next()? {
+                    Token::Duration(d) => -d,
+                    token => return Err(syntax_error(format!("unexpected {token:?} in offset"))),
+                },
+                token => return Err(syntax_error(format!("unexpected {token:?} in offset"))),
+            };
+            match &mut expr {
+                Expr::VectorSelector(selector) | Expr::MatrixSelector { selector, .. } => {
+                    selector.offset = offset;
+                }
+                _ => {
+                    return Err(syntax_error(
+                        "offset modifier must be preceded by a selector",
+                    ))
+                }
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        match self.next()? {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Duration(_) => Err(syntax_error("unexpected duration")),
+            Token::Str(_) => Err(syntax_error("string literals are not supported")),
+            Token::LParen => {
+                let expr = self.parse_expr(0)?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Token::LBrace => self.parse_selector(None, true),
+            Token::Ident(ident) => {
+                let followed_by_paren = self.peek() == Some(&Token::LParen);
+                if let Some(op) = AggregateOp::from_name(&ident) {
+                    if followed_by_paren || matches!(self.peek_ident(), Some("by" | "without")) {
+                        return self.parse_aggregate(op);
+                    }
+                }
+                if followed_by_paren {
+                    let func = Function::from_name(&ident)
+                        .ok_or_else(|| syntax_error(format!("unknown function: {ident}")))?;
+                    return self.parse_call(func);
+                }
+                match ident.to_lowercase().as_str() {
+                    "inf" => return Ok(Expr::Number(f64::INFINITY)),
+                    "nan" => return Ok(Expr::Number(f64::NAN)),
+                    _ => {}
+                }
+                let braced = self.peek() == Some(&Token::LBrace);
+                if braced {
+                    self.pos += 1;
+                }
+                self.parse_selector(Some(ident), braced)
+            }
+            token => Err(syntax_error(format!("unexpected {token:?}"))),
+        }
+    }
+
+    /// Parse a selector, `braced` means `{` has been consumed and label matchers follow.
+    fn parse_selector(&mut self, metric: Option<String>, braced: bool) -> Result<Expr> {
+        let mut matchers = Vec::new();
+        if let Some(metric) = metric {
+            matchers.push(Matcher::new(METRIC_NAME_LABEL, metric, Type::EQ));
+        }
+
+        if braced {
+            loop {
+                let name = match self.next()? {
+                    Token::RBrace => break,
+                    Token::Ident(name) => name,
+                    token => return Err(syntax_error(format!("unexpected {token:?} in matchers"))),
+                };
+                let type_ = match self.next()? {
+                    Token::Assign => Type::EQ,
+                    Token::NotEq => Type::NEQ,
+                    Token::RegexMatch => Type::RE,
+                    Token::RegexNotMatch => Type::NRE,
+                    token => {
+                        return Err(syntax_error(format!(
+                            "unexpected {token:?} in matchers, expected a match type"
+                        )))
+                    }
+                };
+                let value = match self.next()? {
+                    Token::Str(value) => value,
+                    token => {
+                        return Err(syntax_error(format!(
+                            "unexpected {token:?} in matchers, expected a string"
+                        )))
+                    }
+                };
+                if type_ == Type::RE || type_ == Type::NRE {
+                    regex::Regex::new(&value).map_err(|e| syntax_error(e.to_string()))?;
+                }
+                matchers.push(Matcher::new(name, value, type_));
+
+                match self.next()? {
+                    Token::Comma => continue,
+                    Token::RBrace => break,
+                    token => return Err(syntax_error(format!("unexpected {token:?} in matchers"))),
+                }
+            }
+        }
+
+        // The same as prometheus, a selector must not match all series.
+        let matches_empty = |m: &Matcher| match m.type_ {
+            Type::EQ => m.value.is_empty(),
+            Type::NEQ => !m.value.is_empty(),
+            Type::RE | Type::NRE => {
+                let is_match = regex::Regex::new(&format!("^(?:{})$", m.value))
+                    .map(|r| r.is_match(""))
+                    .unwrap_or(true);
+                (m.type_ == Type::RE) == is_match
+            }
+        };
+        if matchers.iter().all(matches_empty) {
+            return Err(syntax_error(
+                "vector selector must contain at least one non-empty matcher",
+            ));
+        }
+
+        let id = self.next_selector_id;
+        self.next_selector_id += 1;
+
+        Ok(Expr::VectorSelector(VectorSelector {
+            id,
+            matchers,
+            offset: 0,
+        }))
+    }
+
+    fn parse_grouping(&mut self) -> Result<Grouping> {
+        let without = match self.peek_ident() {
+            Some("by") => false,
+            Some("without") => true,
+            _ => return Err(syntax_error("expected by or without")),
+        };
+        self.pos += 1;
+        self.expect(Token::LParen)?;
+
+        let mut labels = Vec::new();
+        loop {
+            match self.next()? {
+                Token::RParen => break,
+                Token::Ident(label) => labels.push(label),
+                token => return Err(syntax_error(format!("unexpected {token:?} in grouping"))),
+            }
+            match self.next()? {
+                Token::Comma => continue,
+                Token::RParen => break,
+                token => return Err(syntax_error(format!("unexpected {token:?} in grouping"))),
+            }
+        }
+
+        Ok(if without {
+            Grouping::Without(labels)
+        } else {
+            Grouping::By(labels)
+        })
+    }
+
+    fn parse_aggregate(&mut self, op: AggregateOp) -> Result<Expr> {
+        let mut grouping = None;
+        if matches!(self.peek_ident(), Some("by" | "without")) {
+            grouping = Some(self.parse_grouping()?);
+        }
+
+        self.expect(Token::LParen)?;
+        let expr = self.parse_expr(0)?;
+        if self.peek() == Some(&Token::Comma) {
+            return Err(syntax_error("aggregation parameters are not supported"));
+        }
+        self.expect(Token::RParen)?;
+
+        if grouping.is_none() && matches!(self.peek_ident(), Some("by" | "without")) {
+            grouping = Some(self.parse_grouping()?);
+        }
+
+        if expr.value_type() != ValueType::Vector {
+            return Err(syntax_error(format!(
+                "expected type instant vector in aggregation expression, got {}",
+                expr.value_type()
+            )));
+        }
+
+        Ok(Expr::Aggregate {
+            op,
+            grouping: grouping.unwrap_or_default(),
+            expr: Box::new(expr),
+        })
+    }
+
+    fn parse_call(&mut self, func: Function) -> Result<Expr> {
+        self.expect(Token::LParen)?;
+        let mut args = Vec::new();
+        if self.peek() == Some(&Token::RParen) {
+            self.pos += 1;
+        } else {
+            loop {
+                args.push(self.parse_expr(0)?);
+                match self.next()? {
+                    Token::Comma => continue,
+                    Token::RParen => break,
+                    token => {
+                        return Err(syntax_error(format!(
+                            "unexpected {token:?} in function call"
+                        )))
+                    }
+                }
+            }
+        }
+
+        let arg_types = func.arg_types();
+        if args.len() != arg_types.len() {
+            return Err(syntax_error(format!(
+                "expected {} argument(s) in call to function {:?}, got {}",
+                arg_types.len(),
+                func.name(),
+                args.len()
+            )));
+        }
+        for (arg, expected) in args.iter().zip(arg_types) {
+            if arg.value_type() != *expected {
+                return Err(syntax_error(format!(
+                    "expected type {expected} in call to function {:?}, got {}",
+                    func.name(),
+                    arg.value_type()
+                )));
+            }
+        }
+
+        Ok(Expr::Call { func, args })
+    }
+}
+
+fn check_operand(expr: &Expr) -> Result<()> {
+    if expr.value_type() == ValueType::Matrix {
+        return Err(syntax_error(
+            "binary expression must contain only scalar and instant vector types",
+        ));
+    }
+    Ok(())
+}
+
+fn binary_expr(op: BinaryOp, lhs: Expr, rhs: Expr) -> Result<Expr> {
+    check_operand(&lhs)?;
+    check_operand(&rhs)?;
+    if op.is_comparison()
+        && lhs.value_type() == ValueType::Scalar
+        && rhs.value_type() == ValueType::Scalar
+    {
+        return Err(syntax_error(
+            "comparisons between scalars must use BOOL modifier",
+        ));
+    }
+
+    // Fold constants
+    if let (Expr::Number(l), Expr::Number(r)) = (&lhs, &rhs) {
+        return Ok(Expr::Number(op.apply(*l, *r)));
+    }
+
+    Ok(Expr::Binary {
+        op,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use protos::prompb::types::label_matcher::Type;
+
+    use super::{parse, parse_duration};
+    use crate::prom::promql::ast::{
+        AggregateOp, BinaryOp, Expr, Function, Grouping, Matcher, VectorSelector,
+    };
+
+    fn selector(id: usize, matchers: Vec<Matcher>, offset: i64) -> VectorSelector {
+        VectorSelector {
+            id,
+            matchers,
+            offset,
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5m").unwrap(), 300_000);
+        assert_eq!(parse_duration("1h30m").unwrap(), 5_400_000);
+        assert_eq!(parse_duration("100ms").unwrap(), 100);
+        assert!(parse_duration("5").is_err());
+        assert!(parse_duration("5mb").is_err());
+    }
+
+    #[test]
+    fn test_parse_selector() {
+        let expr = parse(r#"http_requests_total{job=~"api.*", code!="500"} offset 1h"#).unwrap();
+        assert_eq!(
+            expr,
+            Expr::VectorSelector(selector(
+                0,
+                vec![
+                    Matcher::new("__name__", "http_requests_total", Type::EQ),
+                    Matcher::new("job", "api.*", Type::RE),
+                    Matcher::new("code", "500", Type::NEQ),
+                ],
+                3_600_000
+            ))
+        );
+
+        let expr = parse(r#"{__name__=~"node_.+"}[5m]"#).unwrap();
+        assert_eq!(
+            expr,
+            Expr::MatrixSelector {
+                selector: selector(0, vec![Matcher::new("__name__", "node_.+", Type::RE)], 0),
+                range: 300_000,
+            }
+        );
+
+        assert!(parse(r#"{job=~".*"}"#).is_err());
+        assert!(parse("foo[5m:1m]").is_err());
+        assert!(parse("rate(foo)").is_err());
+        assert!(parse("abs(foo[5m])").unwrap_err().to_string().contains(
+            "expected type instant vector in call to function \"abs\", got range vector"
+        ));
+        assert!(parse("(foo + 1)[5m]").is_err());
+    }
+
+    #[test]
+    fn test_parse_aggregate() {
+        let by = parse("sum by (job) (rate(foo[5m]))").unwrap();
+        let by_suffix = parse("sum(rate(foo[5m])) by (job)").unwrap();
+        let expected = Expr::Aggregate {
+            op: AggregateOp::Sum,
+            grouping: Grouping::By(vec!["job".to_string()]),
+            expr: Box::new(Expr::Call {
+                func: Function::Rate,
+                args: vec![Expr::MatrixSelector {
+                    selector: selector(0, vec![Matcher::new("__name__", "foo", Type::EQ)], 0),
+                    range: 300_000,
+                }],
+            }),
+        };
+        assert_eq!(by, expected);
+        assert_eq!(by_suffix, expected);
+
+        let expr =
+            parse("histogram_quantile(0.9, sum without (instance) (rate(bucket[1m])))").unwrap();
+        match expr {
+            Expr::Call {
+                func: Function::HistogramQuantile,
+                args,
+            } => {
+                assert_eq!(args[0], Expr::Number(0.9));
+                assert!(matches!(
+                    &args[1],
+                    Expr::Aggregate {
+                        grouping: Grouping::Without(_),
+                        ..
+                    }
+                ));
+            }
+            _ => panic!("unexpected expression: {expr:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_binary() {
+        let expr = parse("a + b * 2 ^ 3 ^ 2").unwrap();
+        let a = Expr::VectorSelector(selector(
+            0,
+            vec![Matcher::new("__name__", "a", Type::EQ)],
+            0,
+        ));
+        let b = Expr::VectorSelector(selector(
+            1,
+            vec![Matcher::new("__name__", "b", Type::EQ)],
+            0,
+        ));
+        assert_eq!(
+            expr,
+            Expr::Binary {
+                op: BinaryOp::Add,
+                lhs: Box::new(a),
+                rhs: Box::new(Expr::Binary {
+                    op: BinaryOp::Mul,
+                    lhs: Box::new(b),
+                    rhs: Box::new(Expr::Number(512.0)),
+                }),
+            }
+        );
+
+        assert_eq!(parse("-2 ^ 2").unwrap(), Expr::Number(-4.0));
+        assert_eq!(parse("1e3 - .5").unwrap(), Expr::Number(999.5));
+        assert!(parse("1 > 2").is_err());
+        assert!(parse("foo > bool 1").is_err());
+    }
+}
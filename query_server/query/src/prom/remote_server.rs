@@ -1,16 +1,26 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
 
+use async_stream::try_stream;
 use async_trait::async_trait;
 use bytes::Bytes;
 use coordinator::service::CoordinatorRef;
 use datafusion::arrow::datatypes::ToByteSlice;
+use datafusion::common::{Column, OwnedTableReference};
+use datafusion::logical_expr::{LogicalPlan, LogicalPlanBuilder, Operator};
+use datafusion::optimizer::utils::conjunction;
+use datafusion::prelude::{binary_expr, lit, Expr};
+use datafusion::scalar::ScalarValue;
+use futures::stream::BoxStream;
+use futures::StreamExt;
 use meta::error::MetaError;
 use meta::model::MetaClientRef;
-use models::schema::{TskvTableSchemaRef, TIME_FIELD_NAME};
+use models::schema::{TskvTableSchema, TskvTableSchemaRef, TIME_FIELD_NAME};
 use models::snappy::SnappyCodec;
 use protocol_parser::Line;
 use protos::models_helper::{parse_proto_bytes, to_proto_bytes};
+use protos::prompb::remote::read_request::ResponseType;
 use protos::prompb::remote::{
     Query as PromQuery, QueryResult, ReadRequest, ReadResponse, WriteRequest,
 };
@@ -18,15 +28,21 @@ use protos::prompb::types::label_matcher::Type;
 use protos::prompb::types::TimeSeries;
 use protos::FieldValue;
 use regex::Regex;
+use serde_json::json;
+use spi::query::execution::StatementType;
+use spi::query::logical_planner::{Plan, QueryPlan};
 use spi::server::dbms::DBMSRef;
-use spi::server::prom::PromRemoteServer;
+use spi::server::prom::{PromQueryRequest, PromReadResponse, PromRemoteServer};
 use spi::service::protocol::{Context, Query, QueryHandle};
 use spi::{QueryError, Result};
 use trace::{debug, warn, SpanContext, SpanExt, SpanRecorder};
 
-use super::time_series::writer::WriterBuilder;
+use super::promql::engine::{self, SelectedSeries};
+use super::promql::lower::LoweredSelector;
+use super::time_series::chunk::ChunkedFrameEncoder;
+use super::time_series::writer::{concat_labels, WriterBuilder};
 use super::{METRIC_NAME_LABEL, METRIC_SAMPLE_COLUMN_NAME};
-use crate::prom::DEFAULT_PROM_TABLE_NAME;
+use crate::prom::{promql, DEFAULT_PROM_TABLE_NAME};
 
 pub struct PromRemoteSqlServer {
     db: DBMSRef,
@@ -41,7 +57,7 @@ impl PromRemoteServer for PromRemoteSqlServer {
         ctx: &Context,
         req: Bytes,
         span_ctx: Option<&SpanContext>,
-    ) -> Result<Vec<u8>> {
+    ) -> Result<PromReadResponse> {
         let meta = self
             .coord
             .meta_manager()
@@ -55,14 +71,94 @@ impl PromRemoteServer for PromRemoteSqlServer {
 
         debug!("Received remote read request: {:?}", read_request);
 
+        let response_type = negotiate_response_type(&read_request)?;
+
         let span_recorder = SpanRecorder::new(span_ctx.child_span("process read request"));
-        let read_response = self
-            .process_read_request(ctx, meta, read_request, span_recorder)
-            .await?;
+        match response_type {
+            ResponseType::SAMPLES => {
+                let results = self
+                    .process_read_request(ctx, meta, read_request, span_recorder)
+                    .await?;
+                let read_response = ReadResponse {
+                    results: results
+                        .into_iter()
+                        .map(|timeseries| QueryResult {
+                            timeseries,
+                            ..Default::default()
+                        })
+                        .collect(),
+                    special_fields: Default::default(),
+                };
+
+                debug!("Return remote read response: {:?}", read_response);
 
-        debug!("Return remote read response: {:?}", read_response);
+                Ok(PromReadResponse::Samples(
+                    self.serialize_read_response(read_response).await?,
+                ))
+            }
+            ResponseType::STREAMED_XOR_CHUNKS => {
+                let mut queries = Vec::new();
+                for (query_index, q) in read_request.queries.into_iter().enumerate() {
+                    let mut table_queries = build_read_queries(ctx, &meta, q)?;
+                    table_queries.sort_by(|a, b| a.table.name.cmp(&b.table.name));
+                    queries.extend(table_queries.into_iter().map(|q| (query_index as i64, q)));
+                }
+
+                debug!("Prepare to stream: {:?}", queries);
 
-        self.serialize_read_response(read_response).await
+                Ok(PromReadResponse::StreamedXorChunks(
+                    self.stream_chunked_frames(ctx, queries, span_recorder),
+                ))
+            }
+        }
+    }
+
+    async fn query(
+        &self,
+        ctx: &Context,
+        req: PromQueryRequest,
+        span_ctx: Option<&SpanContext>,
+    ) -> Result<Vec<u8>> {
+        let meta = self
+            .coord
+            .meta_manager()
+            .tenant_meta(ctx.tenant())
+            .await
+            .ok_or_else(|| MetaError::TenantNotFound {
+                tenant: ctx.tenant().to_string(),
+            })?;
+
+        debug!("Received PromQL query: {:?}", req);
+
+        let expr = promql::parser::parse(&req.query)?;
+
+        let span_recorder = SpanRecorder::new(span_ctx.child_span("select series"));
+        let selectors = promql::lower::lower(&expr, &req);
+        let mut series = HashMap::with_capacity(selectors.len());
+        for selector in selectors {
+            let selected = self
+                .select_series(
+                    ctx,
+                    &meta,
+                    &req.query,
+                    &selector,
+                    span_recorder.child(selector.selector.id.to_string()),
+                )
+                .await?;
+            series.insert(selector.selector.id, selected);
+        }
+
+        let result = if req.is_instant() {
+            engine::instant_query(&expr, &series, req.start)?
+        } else {
+            engine::range_query(&expr, &series, req.start, req.end, req.step)?
+        };
+
+        serde_json::to_vec(&json!({
+            "status": "success",
+            "data": result.to_json(),
+        }))
+        .map_err(|e| QueryError::CommonError { msg: e.to_string() })
     }
 
     fn remote_write(&self, req: Bytes) -> Result<WriteRequest> {
@@ -141,70 +237,188 @@ impl PromRemoteSqlServer {
         })
     }
 
+    /// Returns the merged TimeSeries of each query.
     async fn process_read_request(
         &self,
         ctx: &Context,
         meta: MetaClientRef,
         read_request: ReadRequest,
         span_recorder: SpanRecorder,
-    ) -> Result<ReadResponse> {
+    ) -> Result<Vec<Vec<TimeSeries>>> {
         let mut results = Vec::with_capacity(read_request.queries.len());
         for q in read_request.queries {
-            let mut timeseries: Vec<TimeSeries> = Vec::new();
-            let sqls = build_sql_with_table(ctx, &meta, q)?;
+            let mut labels_to_series: HashMap<String, TimeSeries> = HashMap::new();
+            let queries = build_read_queries(ctx, &meta, q)?;
 
-            debug!("Prepare to execute: {:?}", sqls);
+            debug!("Prepare to execute: {:?}", queries);
 
-            for (idx, sql) in sqls.into_iter().enumerate() {
-                timeseries.append(
-                    &mut self
-                        .process_single_sql(ctx, sql, span_recorder.child(idx.to_string()))
-                        .await?,
-                );
+            for (idx, query) in queries.into_iter().enumerate() {
+                let timeseries = self
+                    .process_table_query(ctx, query, span_recorder.child(idx.to_string()))
+                    .await?;
+                merge_time_series(&mut labels_to_series, timeseries);
             }
 
-            results.push(QueryResult {
-                timeseries,
-                ..Default::default()
-            });
+            let mut timeseries = labels_to_series.into_iter().collect::<Vec<_>>();
+            timeseries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            results.push(
+                timeseries
+                    .into_iter()
+                    .map(|(_, mut ts)| {
+                        // The samples may come from several tables and vnodes.
+                        ts.samples.sort_by_key(|s| s.timestamp);
+                        ts
+                    })
+                    .collect(),
+            );
         }
 
-        Ok(ReadResponse {
-            results,
-            special_fields: Default::default(),
-        })
+        Ok(results)
     }
 
-    async fn process_single_sql(
+    /// Returns the buckets of the series selected by `selector` of the PromQL `query`,
+    /// see [`promql::lower`].
+    async fn select_series(
         &self,
         ctx: &Context,
-        sql: SqlWithTable,
+        meta: &MetaClientRef,
+        query: &str,
+        selector: &LoweredSelector,
         span_recorder: SpanRecorder,
-    ) -> Result<Vec<TimeSeries>> {
-        let table_schema = sql.table;
-        let tag_name_indices = table_schema.tag_indices();
-        let sample_value_idx = table_schema
-            .column_index(METRIC_SAMPLE_COLUMN_NAME)
-            .ok_or_else(|| QueryError::ColumnNotExists {
-                table: table_schema.name.to_string(),
-                column: METRIC_SAMPLE_COLUMN_NAME.to_string(),
-            })?;
-        let sample_time_idx = table_schema.column_index(TIME_FIELD_NAME).ok_or_else(|| {
-            QueryError::ColumnNotExists {
-                table: table_schema.name.to_string(),
-                column: TIME_FIELD_NAME.to_string(),
-            }
-        })?;
+    ) -> Result<Vec<SelectedSeries>> {
+        let matchers = selector
+            .selector
+            .matchers
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect();
+        let tables = match build_table_queries(ctx, meta, matchers) {
+            Ok(tables) => tables,
+            // Nothing is selected if the metric does not exist.
+            Err(QueryError::Meta {
+                source: MetaError::TableNotFound { .. },
+            }) => vec![],
+            Err(e) => return Err(e),
+        };
 
-        let inner_query = Query::new(ctx.clone(), sql.sql);
-        let result = self
-            .db
-            .execute(&inner_query, span_recorder.span_ctx())
+        let mut series = BTreeMap::new();
+        for (idx, TableQuery { table, filters }) in tables.into_iter().enumerate() {
+            let span_recorder = span_recorder.child(idx.to_string());
+            let handle = execute_table_plan(
+                &self.db,
+                ctx,
+                query.to_string(),
+                &table,
+                span_recorder.span_ctx(),
+                |scan| {
+                    let plan = selector.to_plan(scan, &table, filters)?;
+                    debug!("Prepare to execute: {}", plan.display_indent());
+                    Ok(plan)
+                },
+            )
             .await?;
+            let mut output = handle.result();
+            while let Some(batch) = output.next().await {
+                selector.decode(&table, &batch?, &mut series)?;
+            }
+        }
+
+        Ok(series
+            .into_iter()
+            .map(|(labels, mut buckets)| {
+                // The buckets of a series may come from several vnodes.
+                buckets.sort_by_key(|b| b.end);
+                SelectedSeries { labels, buckets }
+            })
+            .collect())
+    }
+
+    async fn process_table_query(
+        &self,
+        ctx: &Context,
+        query: TableQuery,
+        span_recorder: SpanRecorder,
+    ) -> Result<Vec<TimeSeries>> {
+        let (tag_name_indices, sample_value_idx, sample_time_idx) = sample_indices(&query.table)?;
+
+        let TableQuery { table, filters } = query;
+        let result = execute_table_plan(
+            &self.db,
+            ctx,
+            remote_read_content(&table),
+            &table,
+            span_recorder.span_ctx(),
+            |scan| Ok(filter(scan, filters)?.build()?),
+        )
+        .await?;
 
         transform_time_series(result, tag_name_indices, sample_value_idx, sample_time_idx).await
     }
 
+    /// Execute the queries one by one, the rows are sorted by series, so that each series
+    /// is encoded and sent as soon as all of its samples are read.
+    fn stream_chunked_frames(
+        &self,
+        ctx: &Context,
+        queries: Vec<(i64, TableQuery)>,
+        span_recorder: SpanRecorder,
+    ) -> BoxStream<'static, Result<Vec<u8>>> {
+        let db = self.db.clone();
+        let ctx = ctx.clone();
+
+        let frames = try_stream! {
+            for (idx, (query_index, query)) in queries.into_iter().enumerate() {
+                let (tag_name_indices, sample_value_idx, sample_time_idx) =
+                    sample_indices(&query.table)?;
+                let order_by = tag_name_indices
+                    .iter()
+                    .map(|i| ident(&query.table.columns()[*i].name).sort(true, true))
+                    .chain(std::iter::once(ident(TIME_FIELD_NAME).sort(true, false)))
+                    .collect::<Vec<_>>();
+
+                let TableQuery { table, filters } = query;
+                let span_recorder = span_recorder.child(idx.to_string());
+                let handle = execute_table_plan(
+                    &db,
+                    &ctx,
+                    remote_read_content(&table),
+                    &table,
+                    span_recorder.span_ctx(),
+                    |scan| Ok(filter(scan, filters)?.sort(order_by)?.build()?),
+                )
+                .await?;
+                let mut output = handle.result();
+
+                let mut writer = WriterBuilder::try_new(
+                    tag_name_indices,
+                    sample_value_idx,
+                    sample_time_idx,
+                    output.schema(),
+                )?
+                .build_sorted();
+                let mut encoder = ChunkedFrameEncoder::new(query_index);
+                while let Some(batch) = output.next().await {
+                    for ts in writer.write(&batch?)? {
+                        if let Some(frame) = encoder.push(ts)? {
+                            yield frame;
+                        }
+                    }
+                }
+                if let Some(ts) = writer.finish() {
+                    if let Some(frame) = encoder.push(ts)? {
+                        yield frame;
+                    }
+                }
+                if let Some(frame) = encoder.finish()? {
+                    yield frame;
+                }
+            }
+        };
+
+        Box::pin(frames)
+    }
+
     async fn serialize_read_response(&self, read_response: ReadResponse) -> Result<Vec<u8>> {
         let mut compressed = Vec::new();
         let input_buf =
@@ -217,11 +431,13 @@ impl PromRemoteSqlServer {
     }
 }
 
-fn build_sql_with_table(
+/// Returns the tables selected by the remote read `query`, the time range of the query
+/// is added to the filters of each table.
+fn build_read_queries(
     ctx: &Context,
     meta: &MetaClientRef,
     query: PromQuery,
-) -> Result<Vec<SqlWithTable>> {
+) -> Result<Vec<TableQuery>> {
     let PromQuery {
         start_timestamp_ms,
         end_timestamp_ms,
@@ -230,94 +446,256 @@ fn build_sql_with_table(
         special_fields: _,
     } = query;
 
-    let mut tables = Vec::new();
-    let mut filters = Vec::with_capacity(matchers.len());
+    let tables = build_table_queries(ctx, meta, matchers)?;
+
+    Ok(tables
+        .into_iter()
+        .map(|mut query| {
+            // Convert to ns timestamp
+            let time = ident(TIME_FIELD_NAME);
+            query.filters.push(
+                time.clone()
+                    .gt_eq(timestamp(start_timestamp_ms * 1_000_000)),
+            );
+            query
+                .filters
+                .push(time.lt_eq(timestamp(end_timestamp_ms * 1_000_000)));
+            query
+        })
+        .collect())
+}
 
+/// Returns the tables selected by the matchers of metric name, and the filters
+/// of the other label matchers on each table.
+fn build_table_queries(
+    ctx: &Context,
+    meta: &MetaClientRef,
+    matchers: Vec<protos::prompb::types::LabelMatcher>,
+) -> Result<Vec<TableQuery>> {
+    let mut name_matchers = Vec::new();
+    let mut label_matchers = Vec::with_capacity(matchers.len());
     for m in matchers {
-        let type_ = m
+        let matcher = LabelMatcher::try_new(m)?;
+        if METRIC_NAME_LABEL == matcher.name {
+            name_matchers.push(matcher);
+        } else {
+            label_matchers.push(matcher);
+        }
+    }
+
+    let tables = match name_matchers.as_slice() {
+        // Get schema of the specified table
+        [LabelMatcher {
+            value,
+            type_: Type::EQ,
+            ..
+        }] => {
+            let table = meta
+                .get_tskv_table_schema(ctx.database(), value)?
+                .ok_or_else(|| MetaError::TableNotFound {
+                    table: value.to_string(),
+                })?;
+            vec![table]
+        }
+        // Filter table names through all the matchers of metric name,
+        // Get the schema of the remaining tables.
+        _ => meta
+            .list_tables(ctx.database())?
+            .iter()
+            .filter(|table_name| name_matchers.iter().all(|m| m.is_match(table_name)))
+            .flat_map(|table_name| {
+                if let Ok(s) = meta.get_tskv_table_schema(ctx.database(), table_name) {
+                    s
+                } else {
+                    warn!(
+                        "The table {} may have just been dropped, or it may be a bug.",
+                        table_name
+                    );
+                    None
+                }
+            })
+            // Skip the tables not written by prometheus
+            .filter(|table| table.contains_column(METRIC_SAMPLE_COLUMN_NAME))
+            .collect::<Vec<_>>(),
+    };
+
+    let mut result = Vec::with_capacity(tables.len());
+    'table: for table in tables {
+        let mut filters = Vec::with_capacity(label_matchers.len() + 2);
+        for m in label_matchers.iter() {
+            if table.contains_column(&m.name) {
+                filters.push(m.to_filter_expr());
+            } else if !m.is_match("") {
+                // A label that does not exist is treated as an empty label,
+                // so no series of this table matches.
+                continue 'table;
+            }
+        }
+
+        result.push(TableQuery { table, filters });
+    }
+
+    Ok(result)
+}
+
+/// Label matcher of prometheus, regular expressions are fully anchored.
+struct LabelMatcher {
+    name: String,
+    value: String,
+    type_: Type,
+    regex: Option<Regex>,
+}
+
+impl LabelMatcher {
+    fn try_new(matcher: protos::prompb::types::LabelMatcher) -> Result<Self> {
+        let type_ = matcher
             .type_
             .enum_value()
             .map_err(|e| QueryError::InvalidRemoteReadReq {
                 source: format!("Unknown label matcher type: {e}").into(),
             })?;
-
-        if METRIC_NAME_LABEL == m.name {
-            match type_ {
-                Type::EQ => {
-                    // Get schema of the specified table
-                    let table_name = &m.value;
-                    let table = meta
-                        .get_tskv_table_schema(ctx.database(), table_name)?
-                        .ok_or_else(|| MetaError::TableNotFound {
-                            table: table_name.to_string(),
-                        })?;
-                    tables = vec![table];
-                }
-                Type::RE => {
-                    // Filter table names through regular expressions,
-                    // Get the schema of the remaining tables.
-                    let pattern =
-                        Regex::new(&m.value).map_err(|err| QueryError::InvalidRemoteReadReq {
-                            source: Box::new(err),
-                        })?;
-
-                    tables = meta
-                        .list_tables(ctx.database())?
-                        .iter()
-                        .filter(|e| pattern.is_match(e))
-                        .flat_map(|table_name| {
-                            if let Ok(s) = meta.get_tskv_table_schema(ctx.database(), table_name) {
-                                s
-                            } else {
-                                warn!(
-                                    "The table {} may have just been dropped, or it may be a bug.",
-                                    table_name
-                                );
-                                None
-                            }
-                        })
-                        .collect::<Vec<_>>();
-                }
-                _ => {
-                    return Err(QueryError::InvalidRemoteReadReq { source: "non-equal or regex-non-equal matchers are not supported on the metric name yet".to_string().into() });
-                }
+        let regex = match type_ {
+            Type::RE | Type::NRE => {
+                Some(Regex::new(&anchored_regex(&matcher.value)).map_err(|err| {
+                    QueryError::InvalidRemoteReadReq {
+                        source: Box::new(err),
+                    }
+                })?)
             }
+            Type::EQ | Type::NEQ => None,
+        };
 
-            continue;
-        }
+        Ok(Self {
+            name: matcher.name,
+            value: matcher.value,
+            type_,
+            regex,
+        })
+    }
 
-        match type_ {
-            Type::EQ => {
-                filters.push(format!("{} = '{}'", m.name, m.value));
-            }
-            Type::NEQ => {
-                filters.push(format!("{} != '{}'", m.name, m.value));
-            }
-            Type::RE => {
-                filters.push(format!("{} ~ '{}'", m.name, m.value));
-            }
-            Type::NRE => {
-                filters.push(format!("{} !~ '{}'", m.name, m.value));
-            }
+    fn is_match(&self, value: &str) -> bool {
+        match (&self.type_, &self.regex) {
+            (Type::EQ, _) => self.value == value,
+            (Type::NEQ, _) => self.value != value,
+            (Type::RE, Some(regex)) => regex.is_match(value),
+            (Type::NRE, Some(regex)) => !regex.is_match(value),
+            _ => false,
         }
     }
-    // Convert to ns timestamp
-    filters.push(format!("time >= {}", start_timestamp_ms * 1_000_000));
-    filters.push(format!("time <= {}", end_timestamp_ms * 1_000_000));
 
-    let result = tables
-        .into_iter()
-        .map(|table| SqlWithTable {
-            sql: format!(
-                "SELECT * FROM {} WHERE {}",
-                table.name,
-                filters.join(" AND ")
+    fn to_filter_expr(&self) -> Expr {
+        let column = ident(&self.name);
+        let filter = match self.type_ {
+            Type::EQ => column.clone().eq(lit(self.value.as_str())),
+            Type::NEQ => column.clone().not_eq(lit(self.value.as_str())),
+            Type::RE => binary_expr(
+                column.clone(),
+                Operator::RegexMatch,
+                lit(anchored_regex(&self.value)),
+            ),
+            Type::NRE => binary_expr(
+                column.clone(),
+                Operator::RegexNotMatch,
+                lit(anchored_regex(&self.value)),
             ),
-            table,
+        };
+
+        // A missing label is an empty label in prometheus, but the NULL tag is
+        // filtered out by every comparison.
+        if self.is_match("") {
+            column.is_null().or(filter)
+        } else {
+            filter
+        }
+    }
+}
+
+fn anchored_regex(pattern: &str) -> String {
+    format!("^(?:{pattern})$")
+}
+
+/// The column named `name`, the name is not parsed as a qualified name.
+fn ident(name: &str) -> Expr {
+    Expr::Column(Column::from_name(name))
+}
+
+/// The timestamp literal is coerced to the precision of the table.
+fn timestamp(nanos: i64) -> Expr {
+    lit(ScalarValue::TimestampNanosecond(Some(nanos), None))
+}
+
+/// Filter the scan of a table by all the `filters`.
+fn filter(scan: LogicalPlanBuilder, filters: Vec<Expr>) -> Result<LogicalPlanBuilder> {
+    match conjunction(filters) {
+        Some(predicate) => Ok(scan.filter(predicate)?),
+        None => Ok(scan),
+    }
+}
+
+/// The content of the remote read query, shown by SHOW QUERIES.
+fn remote_read_content(table: &TskvTableSchema) -> String {
+    format!("prometheus remote read from {}", table.name)
+}
+
+/// Execute the plan built by `build` from the scan of `table`, the plan is not
+/// parsed from sql, so `content` only describes the query. The user must be able
+/// to read the table.
+async fn execute_table_plan(
+    db: &DBMSRef,
+    ctx: &Context,
+    content: String,
+    table: &TskvTableSchema,
+    span_ctx: Option<&SpanContext>,
+    build: impl FnOnce(LogicalPlanBuilder) -> Result<LogicalPlan>,
+) -> Result<QueryHandle> {
+    let query = Query::new(ctx.clone(), content);
+    let query_state_machine = db.build_query_state_machine(query, span_ctx).await?;
+    query_state_machine.set_statement_type(StatementType::Query);
+
+    let table_ref = OwnedTableReference::bare(table.name.clone());
+    let table_source = db
+        .get_table_source(table_ref.clone(), query_state_machine.clone())
+        .await?;
+    let df_plan = build(LogicalPlanBuilder::scan(table_ref, table_source, None)?)?;
+
+    db.execute_logical_plan(Plan::Query(QueryPlan { df_plan }), query_state_machine)
+        .await
+}
+
+/// Choose the first response type in `accepted_response_types` that is supported,
+/// SAMPLES is used if the request does not contain any.
+fn negotiate_response_type(read_request: &ReadRequest) -> Result<ResponseType> {
+    if read_request.accepted_response_types.is_empty() {
+        return Ok(ResponseType::SAMPLES);
+    }
+
+    read_request
+        .accepted_response_types
+        .iter()
+        .find_map(|e| e.enum_value().ok())
+        .ok_or_else(|| QueryError::InvalidRemoteReadReq {
+            source: format!(
+                "None of the accepted response types is supported: {:?}",
+                read_request.accepted_response_types
+            )
+            .into(),
         })
-        .collect();
+}
 
-    Ok(result)
+/// Merge the TimeSeries with the same labels, which may come from different tables.
+fn merge_time_series(
+    labels_to_series: &mut HashMap<String, TimeSeries>,
+    timeseries: Vec<TimeSeries>,
+) {
+    for mut ts in timeseries {
+        ts.labels.sort_by(|a, b| a.name.cmp(&b.name));
+        match labels_to_series.entry(concat_labels(&ts.labels)) {
+            Entry::Occupied(mut e) => e.get_mut().samples.append(&mut ts.samples),
+            Entry::Vacant(e) => {
+                e.insert(ts);
+            }
+        }
+    }
 }
 
 /// Convert the execution result of query to TimeSeries list of prometheus
@@ -345,10 +723,29 @@ async fn transform_time_series(
     Ok(timeseries.into_values().collect())
 }
 
+/// Returns the indices of tag columns, sample value and sample time of the table.
+fn sample_indices(table: &TskvTableSchemaRef) -> Result<(Vec<usize>, usize, usize)> {
+    let sample_value_idx = table
+        .column_index(METRIC_SAMPLE_COLUMN_NAME)
+        .ok_or_else(|| QueryError::ColumnNotExists {
+            table: table.name.to_string(),
+            column: METRIC_SAMPLE_COLUMN_NAME.to_string(),
+        })?;
+    let sample_time_idx =
+        table
+            .column_index(TIME_FIELD_NAME)
+            .ok_or_else(|| QueryError::ColumnNotExists {
+                table: table.name.to_string(),
+                column: TIME_FIELD_NAME.to_string(),
+            })?;
+
+    Ok((table.tag_indices(), sample_value_idx, sample_time_idx))
+}
+
 #[derive(Debug)]
-struct SqlWithTable {
-    pub sql: String,
-    pub table: TskvTableSchemaRef,
+struct TableQuery {
+    table: TskvTableSchemaRef,
+    filters: Vec<Expr>,
 }
 
 #[cfg(test)]
@@ -359,13 +756,79 @@ mod test {
     use datafusion::arrow::array::{Float64Array, StringArray, TimestampNanosecondArray};
     use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
     use datafusion::arrow::record_batch::RecordBatch;
+    use datafusion::common::Column;
+    use datafusion::logical_expr::Operator;
+    use datafusion::prelude::{binary_expr, lit, Expr};
     use models::auth::user::{User, UserDesc, UserOptions};
+    use protos::prompb::types::label_matcher::Type;
     use protos::prompb::types::{Label, Sample, TimeSeries};
     use spi::query::execution::Output;
     use spi::query::recordbatch::RecordBatchStreamWrapper;
     use spi::service::protocol::{ContextBuilder, Query, QueryHandle, QueryId};
 
-    use crate::prom::remote_server::transform_time_series;
+    use crate::prom::remote_server::{ident, transform_time_series, LabelMatcher};
+
+    fn label_matcher(name: &str, value: &str, type_: Type) -> LabelMatcher {
+        LabelMatcher::try_new(protos::prompb::types::LabelMatcher {
+            name: name.to_string(),
+            value: value.to_string(),
+            type_: type_.into(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_label_matcher() {
+        let m = label_matcher("__name__", "http_.*", Type::RE);
+        assert!(m.is_match("http_requests_total"));
+        assert!(!m.is_match("node_http_requests_total"));
+
+        let m = label_matcher("__name__", "http_.*", Type::NRE);
+        assert!(!m.is_match("http_requests_total"));
+        assert!(m.is_match("node_http_requests_total"));
+
+        let m = label_matcher("__name__", "up", Type::NEQ);
+        assert!(!m.is_match("up"));
+        assert!(m.is_match("down"));
+
+        let m = label_matcher("job", "it's", Type::EQ);
+        assert_eq!(m.to_filter_expr(), ident("job").eq(lit("it's")));
+        assert!(!m.is_match(""));
+
+        let m = label_matcher("job", "a|b", Type::NRE);
+        assert_eq!(
+            m.to_filter_expr(),
+            ident("job").is_null().or(binary_expr(
+                ident("job"),
+                Operator::RegexNotMatch,
+                lit("^(?:a|b)$")
+            ))
+        );
+        assert!(m.is_match(""));
+
+        let m = label_matcher("job", "a", Type::NEQ);
+        assert_eq!(
+            m.to_filter_expr(),
+            ident("job").is_null().or(ident("job").not_eq(lit("a")))
+        );
+
+        let m = label_matcher("job", "", Type::EQ);
+        assert_eq!(
+            m.to_filter_expr(),
+            ident("job").is_null().or(ident("job").eq(lit("")))
+        );
+
+        let m = label_matcher("job", "", Type::NEQ);
+        assert_eq!(m.to_filter_expr(), ident("job").not_eq(lit("")));
+
+        // The label name is not parsed as a qualified column name.
+        let m = label_matcher("a.b", "c", Type::EQ);
+        assert_eq!(
+            m.to_filter_expr(),
+            Expr::Column(Column::from_name("a.b")).eq(lit("c"))
+        );
+    }
 
     #[tokio::test]
     async fn test_transform_time_series() {
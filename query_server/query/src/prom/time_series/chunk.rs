@@ -0,0 +1,406 @@
+//! Encoding of prometheus `STREAMED_XOR_CHUNKS` remote read responses.
+//!
+//! Samples are encoded in the XOR chunk format of prometheus tsdb, and responses are sent
+//! as frames of `uvarint(size) | be32 crc32c(data) | data`, see
+//! https://github.com/prometheus/prometheus/blob/main/storage/remote/chunked.go
+
+use protos::models_helper::to_proto_bytes;
+use protos::prompb::remote::ChunkedReadResponse;
+use protos::prompb::types::chunk::Encoding;
+use protos::prompb::types::{Chunk, ChunkedSeries, TimeSeries};
+use spi::{QueryError, Result};
+
+/// Max samples of a chunk, the same as prometheus tsdb.
+pub const MAX_SAMPLES_PER_CHUNK: usize = 120;
+/// Max size of a frame, the same as the default value of prometheus.
+pub const MAX_BYTES_PER_FRAME: usize = 1024 * 1024;
+
+/// Packs the series of a query into frames of ChunkedReadResponse, a frame is emitted
+/// as soon as it is full, so the series can be encoded while they are read.
+pub struct ChunkedFrameEncoder {
+    query_index: i64,
+    chunked_series: Vec<ChunkedSeries>,
+    size: usize,
+}
+
+impl ChunkedFrameEncoder {
+    pub fn new(query_index: i64) -> Self {
+        Self {
+            query_index,
+            chunked_series: Vec::new(),
+            size: 0,
+        }
+    }
+
+    /// Add a series, returns the frame of the previous series if the frame is full.
+    pub fn push(&mut self, ts: TimeSeries) -> Result<Option<Vec<u8>>> {
+        let series = encode_chunked_series(ts);
+        let series_size = series.chunks.iter().map(|c| c.data.len()).sum::<usize>();
+
+        let frame =
+            if !self.chunked_series.is_empty() && self.size + series_size > MAX_BYTES_PER_FRAME {
+                self.size = 0;
+                Some(encode_frame(
+                    std::mem::take(&mut self.chunked_series),
+                    self.query_index,
+                )?)
+            } else {
+                None
+            };
+        self.chunked_series.push(series);
+        self.size += series_size;
+
+        Ok(frame)
+    }
+
+    /// Returns the frame of the remaining series.
+    pub fn finish(self) -> Result<Option<Vec<u8>>> {
+        if self.chunked_series.is_empty() {
+            return Ok(None);
+        }
+        encode_frame(self.chunked_series, self.query_index).map(Some)
+    }
+}
+
+fn encode_frame(chunked_series: Vec<ChunkedSeries>, query_index: i64) -> Result<Vec<u8>> {
+    let response = ChunkedReadResponse {
+        chunked_series,
+        query_index,
+        ..Default::default()
+    };
+    let data = to_proto_bytes(response).map_err(|source| QueryError::CommonError {
+        msg: source.to_string(),
+    })?;
+
+    let mut frame = Vec::with_capacity(data.len() + 14);
+    put_uvarint(&mut frame, data.len() as u64);
+    frame.extend_from_slice(&crc32c(&data).to_be_bytes());
+    frame.extend_from_slice(&data);
+
+    Ok(frame)
+}
+
+fn encode_chunked_series(ts: TimeSeries) -> ChunkedSeries {
+    let TimeSeries {
+        mut labels,
+        mut samples,
+        ..
+    } = ts;
+    labels.sort_by(|a, b| a.name.cmp(&b.name));
+    samples.sort_by_key(|s| s.timestamp);
+
+    let chunks = samples
+        .chunks(MAX_SAMPLES_PER_CHUNK)
+        .map(|samples| {
+            let mut encoder = XorChunkEncoder::default();
+            for s in samples {
+                encoder.append(s.timestamp, s.value);
+            }
+            Chunk {
+                min_time_ms: samples.first().map(|s| s.timestamp).unwrap_or_default(),
+                max_time_ms: samples.last().map(|s| s.timestamp).unwrap_or_default(),
+                type_: Encoding::XOR.into(),
+                data: encoder.finish(),
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    ChunkedSeries {
+        labels,
+        chunks,
+        ..Default::default()
+    }
+}
+
+/// Encoder of prometheus tsdb XOR chunk, timestamps are delta-of-delta encoded
+/// and values are XOR encoded.
+///
+/// The chunk starts with a big-endian u16 of the number of samples.
+pub struct XorChunkEncoder {
+    stream: BitStream,
+    num_samples: u16,
+    t: i64,
+    t_delta: u64,
+    v: f64,
+    leading: u8,
+    trailing: u8,
+}
+
+impl Default for XorChunkEncoder {
+    fn default() -> Self {
+        Self {
+            stream: BitStream::with_header(2),
+            num_samples: 0,
+            t: 0,
+            t_delta: 0,
+            v: 0.0,
+            leading: 0xff,
+            trailing: 0,
+        }
+    }
+}
+
+impl XorChunkEncoder {
+    pub fn append(&mut self, t: i64, v: f64) {
+        let mut t_delta = 0;
+        match self.num_samples {
+            0 => {
+                let mut buf = Vec::with_capacity(10);
+                put_varint(&mut buf, t);
+                buf.iter().for_each(|b| self.stream.write_byte(*b));
+                self.stream.write_bits(v.to_bits(), 64);
+            }
+            1 => {
+                t_delta = t.wrapping_sub(self.t) as u64;
+                let mut buf = Vec::with_capacity(10);
+                put_uvarint(&mut buf, t_delta);
+                buf.iter().for_each(|b| self.stream.write_byte(*b));
+                self.write_value_delta(v);
+            }
+            _ => {
+                t_delta = t.wrapping_sub(self.t) as u64;
+                let dod = t_delta.wrapping_sub(self.t_delta) as i64;
+                if dod == 0 {
+                    self.stream.write_bit(false);
+                } else if bit_range(dod, 14) {
+                    self.stream.write_bits(0b10, 2);
+                    self.stream.write_bits(dod as u64, 14);
+                } else if bit_range(dod, 17) {
+                    self.stream.write_bits(0b110, 3);
+                    self.stream.write_bits(dod as u64, 17);
+                } else if bit_range(dod, 20) {
+                    self.stream.write_bits(0b1110, 4);
+                    self.stream.write_bits(dod as u64, 20);
+                } else {
+                    self.stream.write_bits(0b1111, 4);
+                    self.stream.write_bits(dod as u64, 64);
+                }
+                self.write_value_delta(v);
+            }
+        }
+
+        self.t = t;
+        self.v = v;
+        self.t_delta = t_delta;
+        self.num_samples += 1;
+    }
+
+    fn write_value_delta(&mut self, v: f64) {
+        let delta = v.to_bits() ^ self.v.to_bits();
+        if delta == 0 {
+            self.stream.write_bit(false);
+            return;
+        }
+        self.stream.write_bit(true);
+
+        let leading = (delta.leading_zeros() as u8).min(31);
+        let trailing = delta.trailing_zeros() as u8;
+        if self.leading != 0xff && leading >= self.leading && trailing >= self.trailing {
+            self.stream.write_bit(false);
+            self.stream.write_bits(
+                delta >> self.trailing,
+                64 - self.leading as u32 - self.trailing as u32,
+            );
+            return;
+        }
+
+        self.leading = leading;
+        self.trailing = trailing;
+        self.stream.write_bit(true);
+        self.stream.write_bits(leading as u64, 5);
+        // 64 significant bits overflows to 0, the reader treats 0 as 64.
+        let sig_bits = 64 - leading as u32 - trailing as u32;
+        self.stream.write_bits(sig_bits as u64, 6);
+        self.stream.write_bits(delta >> trailing, sig_bits);
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.stream.bytes[..2].copy_from_slice(&self.num_samples.to_be_bytes());
+        self.stream.bytes
+    }
+}
+
+fn bit_range(x: i64, nbits: u32) -> bool {
+    -((1 << (nbits - 1)) - 1) <= x && x <= 1 << (nbits - 1)
+}
+
+struct BitStream {
+    bytes: Vec<u8>,
+    /// Number of bits available in the last byte.
+    count: u8,
+}
+
+impl BitStream {
+    fn with_header(header_len: usize) -> Self {
+        Self {
+            bytes: vec![0; header_len],
+            count: 0,
+        }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        if self.count == 0 {
+            self.bytes.push(0);
+            self.count = 8;
+        }
+        if bit {
+            *self.bytes.last_mut().expect("bytes is not empty") |= 1 << (self.count - 1);
+        }
+        self.count -= 1;
+    }
+
+    fn write_byte(&mut self, byte: u8) {
+        if self.count == 0 {
+            self.bytes.push(byte);
+            return;
+        }
+        let last = self.bytes.last_mut().expect("bytes is not empty");
+        *last |= byte >> (8 - self.count);
+        self.bytes.push(byte << self.count);
+    }
+
+    /// Write the lowest `nbits` bits of `value`, from the most significant one.
+    fn write_bits(&mut self, value: u64, nbits: u32) {
+        let mut value = if nbits < 64 {
+            value << (64 - nbits)
+        } else {
+            value
+        };
+        let mut nbits = nbits;
+        while nbits >= 8 {
+            self.write_byte((value >> 56) as u8);
+            value <<= 8;
+            nbits -= 8;
+        }
+        while nbits > 0 {
+            self.write_bit((value >> 63) == 1);
+            value <<= 1;
+            nbits -= 1;
+        }
+    }
+}
+
+fn put_uvarint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn put_varint(buf: &mut Vec<u8>, value: i64) {
+    put_uvarint(buf, ((value << 1) ^ (value >> 63)) as u64);
+}
+
+/// CRC-32 with the Castagnoli polynomial, which is used by prometheus to checksum frames.
+fn crc32c(data: &[u8]) -> u32 {
+    const POLY: u32 = 0x82F6_3B78;
+    static TABLE: once_cell::sync::Lazy<[u32; 256]> = once_cell::sync::Lazy::new(|| {
+        let mut table = [0_u32; 256];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let mut crc = i as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ POLY
+                } else {
+                    crc >> 1
+                };
+            }
+            *entry = crc;
+        }
+        table
+    });
+
+    !data.iter().fold(!0_u32, |crc, b| {
+        TABLE[((crc ^ *b as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use protos::prompb::types::{Sample, TimeSeries};
+
+    use super::{
+        bit_range, crc32c, put_uvarint, put_varint, BitStream, ChunkedFrameEncoder, XorChunkEncoder,
+    };
+
+    #[test]
+    fn test_crc32c() {
+        assert_eq!(crc32c(b""), 0);
+        assert_eq!(crc32c(b"123456789"), 0xE306_9283);
+    }
+
+    #[test]
+    fn test_varint() {
+        let mut buf = vec![];
+        put_uvarint(&mut buf, 300);
+        assert_eq!(buf, vec![0xac, 0x02]);
+
+        let mut buf = vec![];
+        put_varint(&mut buf, -1);
+        assert_eq!(buf, vec![0x01]);
+    }
+
+    #[test]
+    fn test_bit_stream() {
+        let mut stream = BitStream::with_header(0);
+        stream.write_bit(true);
+        stream.write_bits(0b0101, 4);
+        stream.write_byte(0xff);
+        stream.write_bits(0b101, 3);
+        assert_eq!(stream.bytes, vec![0b1010_1111, 0b1111_1101]);
+    }
+
+    #[test]
+    fn test_bit_range() {
+        assert!(bit_range(8192, 14));
+        assert!(!bit_range(8193, 14));
+        assert!(bit_range(-8191, 14));
+        assert!(!bit_range(-8192, 14));
+    }
+
+    #[test]
+    fn test_xor_chunk() {
+        let mut encoder = XorChunkEncoder::default();
+        encoder.append(1000, 1.0);
+        let data = encoder.finish();
+        // num samples | varint(1000) | 1.0
+        let mut expected = vec![0, 1, 0xd0, 0x0f];
+        expected.extend_from_slice(&1.0_f64.to_bits().to_be_bytes());
+        assert_eq!(data, expected);
+
+        let mut encoder = XorChunkEncoder::default();
+        encoder.append(1000, 1.0);
+        encoder.append(2000, 1.0);
+        encoder.append(3000, 1.0);
+        let data = encoder.finish();
+        // uvarint(1000) | value not changed | dod is 0 | value not changed
+        assert_eq!(&data[..2], &[0, 3]);
+        assert_eq!(&data[12..], &[0xe8, 0x07, 0b0000_0000]);
+    }
+
+    #[test]
+    fn test_chunked_frame_encoder() {
+        let series = |num_samples: i64| TimeSeries {
+            samples: (0..num_samples)
+                .map(|i| Sample {
+                    timestamp: i * 1000,
+                    value: (i as f64).sqrt(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut encoder = ChunkedFrameEncoder::new(0);
+        assert!(encoder.push(series(10)).unwrap().is_none());
+        assert!(encoder.push(series(10)).unwrap().is_none());
+        // The frame is full, the previous series are emitted.
+        let frame = encoder.push(series(200_000)).unwrap().unwrap();
+        assert!(frame.len() < 1024);
+        assert!(encoder.finish().unwrap().unwrap().len() > 1024 * 1024);
+
+        assert!(ChunkedFrameEncoder::new(0).finish().unwrap().is_none());
+    }
+}
@@ -13,20 +13,18 @@ use trace::debug;
 
 use crate::prom::METRIC_SAMPLE_COLUMN_NAME;
 
+/// Decodes the labels and sample of a row.
 #[derive(Debug)]
-pub struct Writer<'a> {
-    /// The object to write to
+struct RowDecoder {
     tag_name_indices: Vec<usize>,
     // The column name of the tag_name_indices index
     tag_names: Vec<String>,
     sample_value_idx: usize,
     sample_time_idx: usize,
     schema: SchemaRef,
-
-    labels_to_series: &'a mut HashMap<String, TimeSeries>,
 }
 
-impl Writer<'_> {
+impl RowDecoder {
     fn get_labels(&self, batch: &[ArrayRef], row_index: usize) -> Result<Vec<Label>> {
         let mut labels = Vec::with_capacity(self.tag_name_indices.len());
         for (tag_idx, tag_name) in self.tag_name_indices.iter().zip(&self.tag_names) {
@@ -130,8 +128,7 @@ impl Writer<'_> {
         Ok(sample_timestamp_ms)
     }
 
-    /// Convert a record to a metric
-    fn apply(&mut self, batch: &[ArrayRef], row_index: usize) -> Result<()> {
+    fn decode(&self, batch: &[ArrayRef], row_index: usize) -> Result<(Vec<Label>, Sample)> {
         // get labels
         let labels = self.get_labels(batch, row_index)?;
         // get sample value
@@ -145,6 +142,22 @@ impl Writer<'_> {
             ..Default::default()
         };
 
+        Ok((labels, sample))
+    }
+}
+
+#[derive(Debug)]
+pub struct Writer<'a> {
+    decoder: RowDecoder,
+    /// The object to write to
+    labels_to_series: &'a mut HashMap<String, TimeSeries>,
+}
+
+impl Writer<'_> {
+    /// Convert a record to a metric
+    fn apply(&mut self, batch: &[ArrayRef], row_index: usize) -> Result<()> {
+        let (labels, sample) = self.decoder.decode(batch, row_index)?;
+
         // save Sample
         let labels_str = concat_labels(&labels);
         debug!(
@@ -165,7 +178,7 @@ impl Writer<'_> {
 
     /// Write a vector of record batches to time series vec
     pub fn write(&mut self, batch: &RecordBatch) -> Result<()> {
-        debug_assert_eq!(self.schema.fields(), batch.schema().fields());
+        debug_assert_eq!(self.decoder.schema.fields(), batch.schema().fields());
 
         let columns = batch.columns();
 
@@ -177,6 +190,46 @@ impl Writer<'_> {
     }
 }
 
+/// Writer of the rows which are sorted by labels, a series is complete
+/// once a row of other labels is written, so only one series is kept in memory.
+#[derive(Debug)]
+pub struct SortedWriter {
+    decoder: RowDecoder,
+    current: Option<TimeSeries>,
+}
+
+impl SortedWriter {
+    /// Write a record batch, returns the series completed by it.
+    pub fn write(&mut self, batch: &RecordBatch) -> Result<Vec<TimeSeries>> {
+        debug_assert_eq!(self.decoder.schema.fields(), batch.schema().fields());
+
+        let columns = batch.columns();
+
+        let mut completed = vec![];
+        for row_index in 0..batch.num_rows() {
+            let (labels, sample) = self.decoder.decode(columns, row_index)?;
+            match self.current.as_mut() {
+                Some(ts) if ts.labels == labels => ts.samples.push(sample),
+                _ => {
+                    let ts = TimeSeries {
+                        labels,
+                        samples: vec![sample],
+                        ..Default::default()
+                    };
+                    completed.extend(self.current.replace(ts));
+                }
+            }
+        }
+
+        Ok(completed)
+    }
+
+    /// Returns the last series.
+    pub fn finish(self) -> Option<TimeSeries> {
+        self.current
+    }
+}
+
 /// A CSV writer builder
 #[derive(Debug)]
 pub struct WriterBuilder {
@@ -236,20 +289,34 @@ impl WriterBuilder {
         })
     }
 
-    /// Create a new `Writer`
-    pub fn build(self, labels_to_series: &mut HashMap<String, TimeSeries>) -> Writer<'_> {
-        Writer {
+    fn decoder(self) -> RowDecoder {
+        RowDecoder {
             tag_name_indices: self.tag_name_indices,
             tag_names: self.tag_names,
             sample_value_idx: self.sample_value_idx,
             sample_time_idx: self.sample_time_idx,
             schema: self.schema,
+        }
+    }
+
+    /// Create a new `Writer`
+    pub fn build(self, labels_to_series: &mut HashMap<String, TimeSeries>) -> Writer<'_> {
+        Writer {
+            decoder: self.decoder(),
             labels_to_series,
         }
     }
+
+    /// Create a new `SortedWriter`, the rows must be sorted by the tag columns.
+    pub fn build_sorted(self) -> SortedWriter {
+        SortedWriter {
+            decoder: self.decoder(),
+            current: None,
+        }
+    }
 }
 
-fn concat_labels(labels: &[Label]) -> String {
+pub fn concat_labels(labels: &[Label]) -> String {
     labels
         .iter()
         .flat_map(|e| [&e.name, &e.value])
@@ -259,3 +326,59 @@ fn concat_labels(labels: &[Label]) -> String {
         // as a separator here.
         .join("\x01")
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::{Float64Array, StringArray, TimestampNanosecondArray};
+    use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use datafusion::arrow::record_batch::RecordBatch;
+
+    use super::WriterBuilder;
+
+    #[test]
+    fn test_sorted_writer() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("tag", DataType::Utf8, false),
+            Field::new("value", DataType::Float64, false),
+        ]));
+        let batch = |tags: Vec<&str>| {
+            let times = (1..=tags.len() as i64).map(|i| i * 1_000_000).collect();
+            let values = vec![1.0; tags.len()];
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(TimestampNanosecondArray::from(times)),
+                    Arc::new(StringArray::from(tags)),
+                    Arc::new(Float64Array::from(values)),
+                ],
+            )
+            .unwrap()
+        };
+
+        let mut writer = WriterBuilder::try_new(vec![1], 2, 0, schema.clone())
+            .unwrap()
+            .build_sorted();
+
+        let completed = writer.write(&batch(vec!["a", "a", "b"])).unwrap();
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].labels[0].value, "a");
+        assert_eq!(completed[0].samples.len(), 2);
+
+        // series b continues in the next batch
+        let completed = writer.write(&batch(vec!["b", "c"])).unwrap();
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].labels[0].value, "b");
+        assert_eq!(completed[0].samples.len(), 2);
+
+        let last = writer.finish().unwrap();
+        assert_eq!(last.labels[0].value, "c");
+        assert_eq!(last.samples.len(), 1);
+    }
+}
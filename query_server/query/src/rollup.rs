@@ -0,0 +1,716 @@
+//! Rollup policies of databases, see [`RollupPolicy`].
+//!
+//! [`RollupJob`] periodically aggregates the closed windows of each table into its rollup
+//! tables by `INSERT INTO ... SELECT`, and records the time range each rollup table covers
+//! in meta, see [`RollupRange`]. The planner only reads a rollup table if the query is inside
+//! that range, see [`crate::extension::analyse::transform_rollup`].
+//!
+//! Data written or deleted after its window is aggregated is collected from the data changes
+//! of this node, see [`LateWrites`], and the windows are aggregated again in the next round.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use coordinator::data_change::{register_data_change_listener, DataChange, DataChangeListener};
+use coordinator::service::CoordinatorRef;
+use datafusion::arrow::array::{Array, Int64Array};
+use datafusion::arrow::record_batch::RecordBatch;
+use meta::model::MetaClientRef;
+use models::auth::user::{User, ROOT};
+use models::oid::Identifier;
+use models::schema::{
+    timestamp_convert, ColumnType, DurationUnit, Precision, RollupAgg, RollupPolicy, RollupRange,
+    TableSchema, TskvTableSchema, TIME_FIELD_NAME,
+};
+use models::utils::now_timestamp_nanos;
+use parking_lot::Mutex;
+use spi::server::dbms::DBMSRef;
+use spi::service::protocol::{ContextBuilder, Query};
+use spi::{QueryError, Result};
+use trace::{debug, error, info};
+
+/// Interval to check the rollup policies of all databases.
+const ROLLUP_CHECK_INTERVAL: StdDuration = StdDuration::from_secs(60);
+
+/// Changed time ranges are aligned to minutes, the finest interval of rollup policies,
+/// so that the ranges of continuous writes are merged.
+const LATE_WRITE_GRANULARITY: i64 = 60_000_000_000;
+
+/// All changed ranges of a table are merged into one if there are more than this.
+const MAX_LATE_WRITE_RANGES: usize = 1024;
+
+type TableKey = (String, String, String);
+
+fn table_key(tenant: &str, database: &str, table: &str) -> TableKey {
+    (tenant.to_string(), database.to_string(), table.to_string())
+}
+
+/// Time ranges of the source tables changed by the writes and deletes of this node.
+///
+/// Every node which accepts the writes of a table, or holds a replica of it, is notified,
+/// and the rollup job of that node aggregates the changed windows again. The ranges are kept
+/// in memory until the next round, so the changes in the last round are lost if it crashes.
+#[derive(Default)]
+pub struct LateWrites {
+    // (tenant, database, table) -> sorted and disjoint ranges `[start, end)` in nanoseconds
+    tables: Mutex<HashMap<TableKey, Vec<(i64, i64)>>>,
+}
+
+impl LateWrites {
+    fn add(&self, key: TableKey, start: i64, end: i64) {
+        let mut tables = self.tables.lock();
+        let ranges = tables.entry(key).or_default();
+        merge_range(ranges, (start, end));
+        if ranges.len() > MAX_LATE_WRITE_RANGES {
+            let merged = (ranges[0].0, ranges[ranges.len() - 1].1);
+            *ranges = vec![merged];
+        }
+    }
+
+    fn take(&self) -> HashMap<TableKey, Vec<(i64, i64)>> {
+        std::mem::take(&mut *self.tables.lock())
+    }
+
+    /// Put back the ranges which failed to be aggregated.
+    fn restore(&self, key: TableKey, ranges: Vec<(i64, i64)>) {
+        for (start, end) in ranges {
+            self.add(key.clone(), start, end);
+        }
+    }
+}
+
+impl DataChangeListener for LateWrites {
+    fn on_data_change(&self, change: DataChange) {
+        // Expired buckets and dropped databases don't change the rollup tables,
+        // which may keep the data longer than the source tables.
+        let table = match change.table {
+            Some(table) => table,
+            None => return,
+        };
+        let start = align_down(change.time_range.min_ts, LATE_WRITE_GRANULARITY);
+        let end = align_down(change.time_range.max_ts, LATE_WRITE_GRANULARITY)
+            .saturating_add(LATE_WRITE_GRANULARITY);
+        self.add(
+            table_key(&change.tenant, &change.database, &table),
+            start,
+            end,
+        );
+    }
+}
+
+/// Insert a range into sorted and disjoint ranges, the adjacent ranges are merged.
+fn merge_range(ranges: &mut Vec<(i64, i64)>, (mut start, mut end): (i64, i64)) {
+    if start >= end {
+        return;
+    }
+    let mut merged = Vec::with_capacity(ranges.len() + 1);
+    let mut inserted = false;
+    for &(s, e) in ranges.iter() {
+        if e < start {
+            merged.push((s, e));
+        } else if end < s {
+            if !inserted {
+                merged.push((start, end));
+                inserted = true;
+            }
+            merged.push((s, e));
+        } else {
+            start = start.min(s);
+            end = end.max(e);
+        }
+    }
+    if !inserted {
+        merged.push((start, end));
+    }
+    *ranges = merged;
+}
+
+/// Windows of a policy to be aggregated again for the changed ranges, only the windows
+/// already aggregated into the rollup table.
+fn late_windows(
+    changed: &[(i64, i64)],
+    aggregated: &RollupRange,
+    interval: i64,
+) -> Vec<(i64, i64)> {
+    let mut windows = vec![];
+    for &(start, end) in changed {
+        let start = align_down(start.max(aggregated.start), interval);
+        let end = end.min(aggregated.end);
+        if start >= end {
+            continue;
+        }
+        let end = match end.rem_euclid(interval) {
+            0 => end,
+            rem => end.saturating_add(interval - rem),
+        };
+        merge_range(&mut windows, (start, end));
+    }
+    windows
+}
+
+/// A column of a rollup table.
+struct RollupColumn {
+    name: String,
+    sql_type: &'static str,
+    // aggregation of the source table
+    expr: String,
+}
+
+/// Aggregate the tables of databases which have rollup policies.
+///
+/// Windows are aggregated after they are closed, the windows changed by late writes are
+/// deleted from the rollup table and aggregated again. The rollup tables are written by the
+/// same `INSERT INTO` of all query nodes, so the job is idempotent.
+pub struct RollupJob {
+    coord: CoordinatorRef,
+    dbms: DBMSRef,
+    late_writes: Arc<LateWrites>,
+    // rollup table -> last time of deleting expired data
+    last_expire: HashMap<TableKey, i64>,
+}
+
+impl RollupJob {
+    pub fn start(coord: CoordinatorRef, dbms: DBMSRef) {
+        let late_writes = Arc::new(LateWrites::default());
+        register_data_change_listener(late_writes.clone());
+        let mut job = Self {
+            coord,
+            dbms,
+            late_writes,
+            last_expire: HashMap::new(),
+        };
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(ROLLUP_CHECK_INTERVAL);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = job.run().await {
+                    error!("Rollup: failed to check rollup policies: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn run(&mut self) -> Result<()> {
+        // Ranges of the tables without rollup policies are dropped.
+        let mut late_writes = self.late_writes.take();
+        let meta = self.coord.meta_manager();
+        for tenant in meta.tenants().await? {
+            let tenant_name = tenant.name();
+            let client = match meta.tenant_meta(tenant_name).await {
+                Some(client) => client,
+                None => continue,
+            };
+            for (db_name, db_info) in client.list_databases()? {
+                let options = &db_info.schema.config;
+                if options.rollups().is_empty() || options.get_db_is_hidden() {
+                    continue;
+                }
+                let user = meta.user_with_privileges(ROOT, tenant_name).await?;
+                for table in db_info.tables.values() {
+                    let schema = match table {
+                        TableSchema::TsKvTableSchema(schema) => schema,
+                        _ => continue,
+                    };
+                    if options.is_rollup_table(&schema.name) {
+                        continue;
+                    }
+                    let key = table_key(tenant_name, &db_name, &schema.name);
+                    let changed = late_writes.remove(&key).unwrap_or_default();
+                    let mut failed = false;
+                    for policy in options.rollups() {
+                        let rollup_table = policy.rollup_table_name(&schema.name);
+                        if let Err(e) = self
+                            .run_policy(&client, &user, schema, &db_info.tables, policy, &changed)
+                            .await
+                        {
+                            failed = true;
+                            error!(
+                                "Rollup: failed to aggregate {}.{}.{} into {}: {}",
+                                tenant_name, db_name, schema.name, rollup_table, e
+                            );
+                        }
+                    }
+                    if failed {
+                        self.late_writes.restore(key, changed);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn run_policy(
+        &mut self,
+        meta: &MetaClientRef,
+        user: &User,
+        schema: &TskvTableSchema,
+        tables: &HashMap<String, TableSchema>,
+        policy: &RollupPolicy,
+        changed: &[(i64, i64)],
+    ) -> Result<()> {
+        let tenant = schema.tenant.as_str();
+        let database = schema.db.as_str();
+        let rollup_table = policy.rollup_table_name(&schema.name);
+        let key = table_key(tenant, database, &rollup_table);
+        let rollup_schema = match tables.get(&rollup_table) {
+            Some(TableSchema::TsKvTableSchema(rollup_schema)) => Some(rollup_schema.as_ref()),
+            Some(_) => {
+                return Err(QueryError::Semantic {
+                    err: format!("{} is not a tskv table", rollup_table),
+                })
+            }
+            None => None,
+        };
+
+        let interval = policy.interval().to_nanoseconds();
+        let keep = policy.keep().to_nanoseconds();
+        let now = now_timestamp_nanos();
+        // Only closed windows.
+        let end = align_down(now, interval);
+        let keep_start = align_down(now.saturating_sub(keep), interval);
+
+        let aggregated = meta.get_rollup_range(database, &rollup_table);
+        let (range_start, start) = match (aggregated, rollup_schema) {
+            (Some(range), _) => (range.start, range.end),
+            (None, Some(rollup_schema)) => {
+                match self.time_bounds(user, rollup_schema).await? {
+                    // Aggregate the last window again, it may be aggregated by an old version
+                    // which didn't record the range.
+                    Some((min_time, max_time)) => (min_time, max_time),
+                    None => (keep_start, keep_start),
+                }
+            }
+            (None, None) => (keep_start, keep_start),
+        };
+
+        let columns = rollup_columns(schema, policy);
+        if columns.is_empty() {
+            return Ok(());
+        }
+        self.create_or_alter_rollup_table(user, schema, rollup_schema, &rollup_table, &columns)
+            .await?;
+
+        // Windows changed after they were aggregated.
+        if let Some(range) = &aggregated {
+            for (window_start, window_end) in late_windows(changed, range, interval) {
+                let sql = format!(
+                    "DELETE FROM {} WHERE {} >= {} AND {} < {}",
+                    quote_ident(&rollup_table),
+                    quote_ident(TIME_FIELD_NAME),
+                    time_literal(window_start),
+                    quote_ident(TIME_FIELD_NAME),
+                    time_literal(window_end),
+                );
+                self.execute(user, tenant, database, sql).await?;
+                self.aggregate(
+                    user,
+                    schema,
+                    policy,
+                    &rollup_table,
+                    &columns,
+                    window_start,
+                    window_end,
+                )
+                .await?;
+            }
+        }
+
+        if start < end {
+            self.aggregate(user, schema, policy, &rollup_table, &columns, start, end)
+                .await?;
+        }
+
+        // Delete the expired data at most once per interval.
+        let mut range_start = range_start.min(start);
+        if keep != i64::MAX
+            && self
+                .last_expire
+                .get(&key)
+                .map_or(true, |last| now - last >= interval)
+        {
+            let sql = format!(
+                "DELETE FROM {} WHERE {} < {}",
+                quote_ident(&rollup_table),
+                quote_ident(TIME_FIELD_NAME),
+                time_literal(keep_start)
+            );
+            self.execute(user, tenant, database, sql).await?;
+            self.last_expire.insert(key, now);
+            range_start = range_start.max(keep_start);
+        }
+
+        let range = RollupRange {
+            start: range_start,
+            end: end.max(start),
+        };
+        if aggregated != Some(range) {
+            meta.update_rollup_range(database, &rollup_table, range)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Aggregate the windows in `[start, end)` of the source table into the rollup table.
+    #[allow(clippy::too_many_arguments)]
+    async fn aggregate(
+        &self,
+        user: &User,
+        schema: &TskvTableSchema,
+        policy: &RollupPolicy,
+        rollup_table: &str,
+        columns: &[RollupColumn],
+        start: i64,
+        end: i64,
+    ) -> Result<()> {
+        let tenant = schema.tenant.as_str();
+        let database = schema.db.as_str();
+        let tags = schema
+            .columns()
+            .iter()
+            .filter(|c| c.column_type.is_tag())
+            .map(|c| quote_ident(&c.name))
+            .collect::<Vec<_>>();
+        let window = format!(
+            "date_bin({}, {})",
+            interval_literal(policy),
+            quote_ident(TIME_FIELD_NAME)
+        );
+        let group_by = std::iter::once(window.clone())
+            .chain(tags.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "INSERT INTO {} ({}) SELECT {} FROM {} WHERE {} >= {} AND {} < {} GROUP BY {}",
+            quote_ident(rollup_table),
+            std::iter::once(quote_ident(TIME_FIELD_NAME))
+                .chain(tags.iter().cloned())
+                .chain(columns.iter().map(|c| quote_ident(&c.name)))
+                .collect::<Vec<_>>()
+                .join(", "),
+            std::iter::once(window)
+                .chain(tags.iter().cloned())
+                .chain(columns.iter().map(|c| c.expr.clone()))
+                .collect::<Vec<_>>()
+                .join(", "),
+            quote_ident(&schema.name),
+            quote_ident(TIME_FIELD_NAME),
+            time_literal(start),
+            quote_ident(TIME_FIELD_NAME),
+            time_literal(end),
+            group_by,
+        );
+        self.execute(user, tenant, database, sql).await?;
+        info!(
+            "Rollup: aggregated {}.{}.{} into {} in [{}, {})",
+            tenant, database, schema.name, rollup_table, start, end
+        );
+        Ok(())
+    }
+
+    async fn create_or_alter_rollup_table(
+        &self,
+        user: &User,
+        schema: &TskvTableSchema,
+        rollup_schema: Option<&TskvTableSchema>,
+        rollup_table: &str,
+        columns: &[RollupColumn],
+    ) -> Result<()> {
+        let tenant = schema.tenant.as_str();
+        let database = schema.db.as_str();
+        let tags = schema
+            .columns()
+            .iter()
+            .filter(|c| c.column_type.is_tag())
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+
+        match rollup_schema {
+            None => {
+                let mut definitions = columns
+                    .iter()
+                    .map(|c| format!("{} {}", quote_ident(&c.name), c.sql_type))
+                    .collect::<Vec<_>>();
+                if !tags.is_empty() {
+                    definitions.push(format!(
+                        "TAGS({})",
+                        tags.iter()
+                            .map(|t| quote_ident(t))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+                }
+                let sql = format!(
+                    "CREATE TABLE IF NOT EXISTS {} ({})",
+                    quote_ident(rollup_table),
+                    definitions.join(", ")
+                );
+                self.execute(user, tenant, database, sql).await?;
+            }
+            Some(rollup_schema) => {
+                // Columns added to the source table after the rollup table is created.
+                for tag in tags {
+                    if !rollup_schema.contains_column(tag) {
+                        let sql = format!(
+                            "ALTER TABLE {} ADD TAG {}",
+                            quote_ident(rollup_table),
+                            quote_ident(tag)
+                        );
+                        self.execute(user, tenant, database, sql).await?;
+                    }
+                }
+                for column in columns {
+                    if !rollup_schema.contains_column(&column.name) {
+                        let sql = format!(
+                            "ALTER TABLE {} ADD FIELD {} {}",
+                            quote_ident(rollup_table),
+                            quote_ident(&column.name),
+                            column.sql_type
+                        );
+                        self.execute(user, tenant, database, sql).await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Min and max time of a rollup table in nanoseconds.
+    async fn time_bounds(
+        &self,
+        user: &User,
+        rollup_schema: &TskvTableSchema,
+    ) -> Result<Option<(i64, i64)>> {
+        let sql = format!(
+            "SELECT CAST(min({time}) AS BIGINT), CAST(max({time}) AS BIGINT) FROM {}",
+            quote_ident(&rollup_schema.name),
+            time = quote_ident(TIME_FIELD_NAME),
+        );
+        let batches = self
+            .execute(user, &rollup_schema.tenant, &rollup_schema.db, sql)
+            .await?;
+        let precision = rollup_schema.time_column_precision();
+        let value = |batch: &RecordBatch, i: usize| {
+            batch
+                .column(i)
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .filter(|array| !array.is_empty() && array.is_valid(0))
+                .and_then(|array| timestamp_convert(precision, Precision::NS, array.value(0)))
+        };
+        Ok(batches
+            .first()
+            .and_then(|batch| Some((value(batch, 0)?, value(batch, 1)?))))
+    }
+
+    async fn execute(
+        &self,
+        user: &User,
+        tenant: &str,
+        database: &str,
+        sql: String,
+    ) -> Result<Vec<RecordBatch>> {
+        debug!("Rollup: execute {}", sql);
+        let ctx = ContextBuilder::new(user.clone())
+            .with_tenant(Some(tenant.to_string()))
+            .with_database(Some(database.to_string()))
+            .build();
+        let query = Query::new(ctx, sql);
+        let handle = self.dbms.execute(&query, None).await?;
+        handle.result().chunk_result().await
+    }
+}
+
+/// Aggregated columns of the fields of the source table.
+fn rollup_columns(schema: &TskvTableSchema, policy: &RollupPolicy) -> Vec<RollupColumn> {
+    let time = quote_ident(TIME_FIELD_NAME);
+    let mut columns = vec![];
+    for field in schema.fields() {
+        let value_type = match &field.column_type {
+            ColumnType::Field(value_type) => value_type,
+            _ => continue,
+        };
+        let field_name = quote_ident(&field.name);
+        for agg in policy.materialized_aggs() {
+            if !agg.support_type(value_type) {
+                continue;
+            }
+            let expr = match agg {
+                RollupAgg::Mean => format!("avg({})", field_name),
+                RollupAgg::First | RollupAgg::Last => {
+                    format!("{}({}, {})", agg, time, field_name)
+                }
+                _ => format!("{}({})", agg, field_name),
+            };
+            columns.push(RollupColumn {
+                name: agg.column_name(&field.name),
+                sql_type: agg.value_type(value_type).to_sql_type_str(),
+                expr,
+            });
+        }
+    }
+    columns
+}
+
+fn align_down(ts: i64, interval: i64) -> i64 {
+    ts - ts.rem_euclid(interval)
+}
+
+pub(crate) fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn interval_literal(policy: &RollupPolicy) -> String {
+    let interval = policy.interval();
+    let unit = match interval.unit {
+        DurationUnit::Minutes => "minute",
+        DurationUnit::Hour => "hour",
+        DurationUnit::Day | DurationUnit::Inf => "day",
+    };
+    format!("INTERVAL '{} {}'", interval.time_num, unit)
+}
+
+pub(crate) fn time_literal(ns: i64) -> String {
+    format!(
+        "'{}'",
+        Utc.timestamp_nanos(ns)
+            .to_rfc3339_opts(SecondsFormat::Nanos, true)
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::arrow::datatypes::TimeUnit;
+    use models::codec::Encoding;
+    use models::schema::{
+        ColumnType, Duration, RollupAgg, RollupPolicy, RollupRange, TableColumn, TskvTableSchema,
+    };
+    use models::ValueType;
+
+    use super::{
+        align_down, interval_literal, late_windows, merge_range, rollup_columns, time_literal,
+    };
+
+    #[test]
+    fn test_rollup_columns() {
+        let schema = TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "cpu".to_string(),
+            vec![
+                TableColumn::new_time_column(0, TimeUnit::Nanosecond),
+                TableColumn::new_tag_column(1, "host".to_string()),
+                TableColumn::new(
+                    2,
+                    "usage".to_string(),
+                    ColumnType::Field(ValueType::Float),
+                    Encoding::Default,
+                ),
+                TableColumn::new(
+                    3,
+                    "status".to_string(),
+                    ColumnType::Field(ValueType::String),
+                    Encoding::Default,
+                ),
+            ],
+        );
+        let policy = RollupPolicy::new(
+            Duration::new("1h").unwrap(),
+            Duration::new("1y").unwrap(),
+            vec![RollupAgg::Max, RollupAgg::Mean, RollupAgg::Last],
+        );
+        assert_eq!(policy.rollup_table_name("cpu"), "cpu_rollup_1h");
+        assert_eq!(interval_literal(&policy), "INTERVAL '1 hour'");
+
+        let columns = rollup_columns(&schema, &policy)
+            .into_iter()
+            .map(|c| (c.name, c.sql_type, c.expr))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            columns,
+            vec![
+                (
+                    "max_usage".to_string(),
+                    "DOUBLE",
+                    "max(\"usage\")".to_string()
+                ),
+                (
+                    "count_usage".to_string(),
+                    "BIGINT",
+                    "count(\"usage\")".to_string()
+                ),
+                (
+                    "mean_usage".to_string(),
+                    "DOUBLE",
+                    "avg(\"usage\")".to_string()
+                ),
+                (
+                    "last_usage".to_string(),
+                    "DOUBLE",
+                    "last(\"time\", \"usage\")".to_string()
+                ),
+                (
+                    "count_status".to_string(),
+                    "BIGINT",
+                    "count(\"status\")".to_string()
+                ),
+                (
+                    "last_status".to_string(),
+                    "STRING",
+                    "last(\"time\", \"status\")".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_time_literal() {
+        assert_eq!(
+            align_down(3_700_000_000_000, 3_600_000_000_000),
+            3_600_000_000_000
+        );
+        assert_eq!(align_down(-1, 3_600_000_000_000), -3_600_000_000_000);
+        assert_eq!(
+            time_literal(1_500_000_001),
+            "'1970-01-01T00:00:01.500000001Z'"
+        );
+    }
+
+    #[test]
+    fn test_merge_range() {
+        let mut ranges = vec![];
+        merge_range(&mut ranges, (10, 20));
+        merge_range(&mut ranges, (40, 50));
+        merge_range(&mut ranges, (0, 5));
+        assert_eq!(ranges, vec![(0, 5), (10, 20), (40, 50)]);
+
+        // Adjacent and overlapped ranges are merged.
+        merge_range(&mut ranges, (20, 30));
+        merge_range(&mut ranges, (25, 45));
+        assert_eq!(ranges, vec![(0, 5), (10, 50)]);
+
+        merge_range(&mut ranges, (7, 7));
+        assert_eq!(ranges, vec![(0, 5), (10, 50)]);
+    }
+
+    #[test]
+    fn test_late_windows() {
+        let aggregated = RollupRange {
+            start: 100,
+            end: 1000,
+        };
+        let changed = vec![(0, 50), (120, 130), (150, 260), (950, 1100), (2000, 3000)];
+        // The changed windows before the aggregated range are expired, and the windows
+        // after it are aggregated as usual.
+        assert_eq!(
+            late_windows(&changed, &aggregated, 100),
+            vec![(100, 300), (900, 1000)]
+        );
+        assert_eq!(
+            late_windows(&[(0, 2000)], &aggregated, 100),
+            vec![(100, 1000)]
+        );
+        assert!(late_windows(&[(1000, 2000)], &aggregated, 100).is_empty());
+    }
+}
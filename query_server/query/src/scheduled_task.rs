@@ -0,0 +1,233 @@
+//! Scheduled tasks created by `CREATE TASK`, see [`ScheduledTask`].
+//!
+//! Tasks are stored, scheduled and retried as `ResourceInfo` by the resource manager of
+//! the coordinator, [`ScheduledTaskRunner`] runs the statement of a task for its owner.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use coordinator::errors::{CoordinatorError, CoordinatorResult};
+use coordinator::resource_manager::{register_task_executor, ScheduledTaskExecutor};
+use coordinator::service::CoordinatorRef;
+use datafusion::arrow::array::{as_primitive_array, Array};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Int64Type};
+use datafusion::sql::sqlparser::ast::TableFactor;
+use datafusion::sql::sqlparser::parser::Parser;
+use meta::model::MetaRef;
+use models::schema::{ScheduledTask, TIME_FIELD_NAME};
+use models::utils::now_timestamp_nanos;
+use models::Timestamp;
+use spi::query::ast::{Copy, CopyIntoLocation, CopyTarget, ExtStatement};
+use spi::server::dbms::DBMSRef;
+use spi::service::protocol::{ContextBuilder, Query};
+use spi::{QueryError, Result};
+use trace::debug;
+
+use crate::rollup::{quote_ident, time_literal};
+use crate::sql::dialect::CnosDBDialect;
+use crate::sql::parser::ExtParser;
+
+/// Alias of the source of an incremental `COPY INTO`.
+const TASK_SOURCE_ALIAS: &str = "__task_source";
+
+pub struct ScheduledTaskRunner {
+    meta: MetaRef,
+    dbms: DBMSRef,
+}
+
+impl ScheduledTaskRunner {
+    /// Register the runner as the executor of the resource manager.
+    pub fn start(coord: CoordinatorRef, dbms: DBMSRef) {
+        register_task_executor(Arc::new(Self {
+            meta: coord.meta_manager(),
+            dbms,
+        }));
+    }
+
+    async fn run(&self, task: &ScheduledTask) -> Result<ScheduledTask> {
+        let now = now_timestamp_nanos();
+        let copy = parse_task_statement(&task.statement)?;
+
+        let user = self
+            .meta
+            .user_with_privileges(&task.owner, &task.tenant)
+            .await?;
+        let ctx = ContextBuilder::new(user)
+            .with_tenant(Some(task.tenant.clone()))
+            .with_database(Some(task.database.clone()))
+            .build();
+
+        // The high water mark is derived from the time of the data instead of the clock,
+        // so the rows with a future time or written by a node with a skewed clock
+        // are neither skipped nor exported twice.
+        let sql = max_time_query(&copy, task.high_water_mark)?;
+        debug!("Scheduled task {}: execute {}", task.name, sql);
+        let handle = self
+            .dbms
+            .execute(&Query::new(ctx.clone(), sql), None)
+            .await?;
+        let batches = handle.result().chunk_result().await?;
+        let max_time = match batches.iter().find(|b| b.num_rows() > 0) {
+            Some(batch) => {
+                let column = cast(batch.column(0), &DataType::Int64)?;
+                let column = as_primitive_array::<Int64Type>(&column);
+                column.is_valid(0).then(|| column.value(0))
+            }
+            None => None,
+        };
+        let high_water_mark = match max_time {
+            Some(max_time) => max_time + 1,
+            // No new rows
+            None => {
+                return Ok(ScheduledTask {
+                    last_run_time: Some(now),
+                    ..task.clone()
+                })
+            }
+        };
+
+        let sql = incremental_copy(copy, task.high_water_mark, high_water_mark)?.to_string();
+        debug!("Scheduled task {}: execute {}", task.name, sql);
+        let handle = self.dbms.execute(&Query::new(ctx, sql), None).await?;
+        handle.result().chunk_result().await?;
+
+        Ok(ScheduledTask {
+            high_water_mark: Some(high_water_mark),
+            last_run_time: Some(now),
+            ..task.clone()
+        })
+    }
+}
+
+#[async_trait]
+impl ScheduledTaskExecutor for ScheduledTaskRunner {
+    async fn execute(&self, task: &ScheduledTask) -> CoordinatorResult<ScheduledTask> {
+        self.run(task)
+            .await
+            .map_err(|e| CoordinatorError::CommonError { msg: e.to_string() })
+    }
+}
+
+fn parse_task_statement(sql: &str) -> Result<Copy> {
+    let mut statements = ExtParser::parse_sql(sql)?;
+    match (statements.pop_front(), statements.is_empty()) {
+        (Some(ExtStatement::Copy(copy)), true) => Ok(copy),
+        _ => Err(QueryError::Semantic {
+            err: format!("Invalid statement of task: {}", sql),
+        }),
+    }
+}
+
+fn into_location(copy: &mut Copy) -> Result<&mut CopyIntoLocation> {
+    match &mut copy.copy_target {
+        CopyTarget::IntoLocation(stmt) => Ok(stmt),
+        CopyTarget::IntoTable(_) => Err(QueryError::Semantic {
+            err: "Only COPY INTO <location> is supported by task".to_string(),
+        }),
+    }
+}
+
+/// The sql selecting the maximum time in nanoseconds of the rows of a
+/// `COPY INTO <location>` whose time is not less than `lower`.
+pub fn max_time_query(copy: &Copy, lower: Option<Timestamp>) -> Result<String> {
+    let mut copy = copy.clone();
+    let into_location = into_location(&mut copy)?;
+
+    let time = quote_ident(TIME_FIELD_NAME);
+    let mut sql = format!(
+        "SELECT CAST(CAST(max({}) AS TIMESTAMP) AS BIGINT) FROM {} AS {}",
+        time, into_location.from, TASK_SOURCE_ALIAS
+    );
+    if let Some(lower) = lower {
+        sql.push_str(&format!(" WHERE {} >= {}", time, time_literal(lower)));
+    }
+
+    Ok(sql)
+}
+
+/// Only export the rows of a `COPY INTO <location>` whose time is in `[lower, upper)`,
+/// the source is wrapped by a subquery.
+pub fn incremental_copy(
+    mut copy: Copy,
+    lower: Option<Timestamp>,
+    upper: Timestamp,
+) -> Result<Copy> {
+    let into_location = into_location(&mut copy)?;
+
+    let time = quote_ident(TIME_FIELD_NAME);
+    let mut predicate = format!("{} < {}", time, time_literal(upper));
+    if let Some(lower) = lower {
+        predicate = format!("{} >= {} AND {}", time, time_literal(lower), predicate);
+    }
+    let sql = format!(
+        "SELECT * FROM {} AS {} WHERE {}",
+        into_location.from, TASK_SOURCE_ALIAS, predicate
+    );
+    let subquery = Parser::new(&CnosDBDialect {})
+        .try_with_sql(&sql)?
+        .parse_query()?;
+    into_location.from = TableFactor::Derived {
+        lateral: false,
+        subquery: Box::new(subquery),
+        alias: None,
+    };
+
+    Ok(copy)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{incremental_copy, max_time_query, parse_task_statement};
+
+    #[test]
+    fn test_max_time_query() {
+        let copy = parse_task_statement(
+            "COPY INTO 'file:///tmp/backup/' FROM air FILE_FORMAT = (TYPE = 'CSV')",
+        )
+        .unwrap();
+        assert_eq!(
+            max_time_query(&copy, None).unwrap(),
+            "SELECT CAST(CAST(max(\"time\") AS TIMESTAMP) AS BIGINT) FROM air AS __task_source"
+        );
+        assert_eq!(
+            max_time_query(&copy, Some(1_000_000_000)).unwrap(),
+            "SELECT CAST(CAST(max(\"time\") AS TIMESTAMP) AS BIGINT) FROM air AS __task_source \
+            WHERE \"time\" >= '1970-01-01T00:00:01.000000000Z'"
+        );
+
+        let copy = parse_task_statement("COPY INTO air FROM 'file:///tmp/air/'").unwrap();
+        assert!(max_time_query(&copy, None).is_err());
+    }
+
+    #[test]
+    fn test_incremental_copy() {
+        let copy = parse_task_statement(
+            "COPY INTO 'file:///tmp/backup/' FROM air FILE_FORMAT = (TYPE = 'CSV')",
+        )
+        .unwrap();
+        let copy = incremental_copy(copy, None, 1_000_000_000).unwrap();
+        assert_eq!(
+            copy.to_string(),
+            "COPY INTO 'file:///tmp/backup/' FROM \
+            (SELECT * FROM air AS __task_source WHERE \"time\" < '1970-01-01T00:00:01.000000000Z') \
+            FILE_FORMAT = (TYPE = 'CSV')"
+        );
+
+        let copy = parse_task_statement(
+            "COPY INTO 'file:///tmp/backup/' FROM (SELECT time, temperature FROM air)",
+        )
+        .unwrap();
+        let copy = incremental_copy(copy, Some(0), 1_000_000_000).unwrap();
+        assert_eq!(
+            copy.to_string(),
+            "COPY INTO 'file:///tmp/backup/' FROM \
+            (SELECT * FROM (SELECT time, temperature FROM air) AS __task_source \
+            WHERE \"time\" >= '1970-01-01T00:00:00.000000000Z' \
+            AND \"time\" < '1970-01-01T00:00:01.000000000Z')"
+        );
+
+        let copy = parse_task_statement("COPY INTO air FROM 'file:///tmp/air/'").unwrap();
+        assert!(incremental_copy(copy, None, 0).is_err());
+    }
+}
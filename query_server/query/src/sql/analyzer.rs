@@ -7,8 +7,10 @@ use spi::query::session::SessionCtx;
 use spi::Result;
 
 use crate::extension::analyse::initial_plan_checker::InitialPlanChecker;
+use crate::extension::analyse::transform_aggregate_scan::TransformAggregateScanRule;
 use crate::extension::analyse::transform_bottom_func_to_topk_node::TransformBottomFuncToTopkNodeRule;
 use crate::extension::analyse::transform_gapfill::TransformGapFill;
+use crate::extension::analyse::transform_rollup::TransformRollupRule;
 use crate::extension::analyse::transform_time_window::TransformTimeWindowRule;
 use crate::extension::analyse::transform_topk_func_to_topk_node::TransformTopkFuncToTopkNodeRule;
 use crate::extension::analyse::transform_update::TransformUpdateRule;
@@ -24,6 +26,8 @@ impl DefaultAnalyzer {
         let rules = &mut analyzer.rules;
         rules.insert(0, Arc::new(TransformUpdateRule::new()));
         rules.push(Arc::new(InitialPlanChecker {}));
+        rules.push(Arc::new(TransformRollupRule {}));
+        rules.push(Arc::new(TransformAggregateScanRule {}));
         rules.push(Arc::new(TransformBottomFuncToTopkNodeRule {}));
         rules.push(Arc::new(TransformTopkFuncToTopkNodeRule {}));
         rules.push(Arc::new(TransformGapFill::new()));
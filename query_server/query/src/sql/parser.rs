@@ -16,13 +16,14 @@ use models::codec::Encoding;
 use models::meta_data::{NodeId, ReplicationSetId, VnodeId};
 use snafu::ResultExt;
 use spi::query::ast::{
-    self, parse_string_value, Action, AlterDatabase, AlterTable, AlterTableAction, AlterTenant,
-    AlterTenantOperation, AlterUser, AlterUserOperation, ChecksumGroup, ColumnOption, CompactVnode,
-    CopyIntoLocation, CopyIntoTable, CopyTarget, CopyVnode, CreateDatabase, CreateRole,
-    CreateStream, CreateTable, CreateTenant, CreateUser, DatabaseOptions, DescribeDatabase,
-    DescribeTable, DropDatabaseObject, DropGlobalObject, DropTenantObject, DropVnode, Explain,
-    ExtStatement, GrantRevoke, MoveVnode, OutputMode, Privilege, RecoverDatabase, RecoverTenant,
-    ShowSeries, ShowTagBody, ShowTagValues, Trigger, UriLocation, With,
+    self, parse_string_value, Action, AlterDatabase, AlterDatabaseOperation, AlterTable,
+    AlterTableAction, AlterTenant, AlterTenantOperation, AlterUser, AlterUserOperation,
+    ChecksumGroup, ColumnOption, CompactVnode, CopyIntoLocation, CopyIntoTable, CopyTarget,
+    CopyVnode, CreateDatabase, CreatePolicy, CreateRole, CreateStream, CreateTable, CreateTask,
+    CreateTenant, CreateUser, DatabaseOptions, DescribeDatabase, DescribeTable, DropDatabaseObject,
+    DropGlobalObject, DropPolicy, DropTenantObject, DropVnode, Explain, ExtStatement, GrantRevoke,
+    MoveVnode, OutputMode, Privilege, PrivilegeObject, RecoverDatabase, RecoverTenant, RepairGroup,
+    RollupOptions, ShowSeries, ShowTagBody, ShowTagValues, TableOption, Trigger, UriLocation, With,
 };
 use spi::query::logical_planner::{DatabaseObjectType, GlobalObjectType, TenantObjectType};
 use spi::query::parser::Parser as CnosdbParser;
@@ -58,9 +59,23 @@ enum CnosKeyWord {
     REPLICA,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     PRECISION,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    MAX_SERIES,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    MAX_SERIES_PER_TABLE,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    BLOOM_FILTER,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    DEDUP,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    POLICY,
 
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     QUERIES,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    CACHE,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    CLEAR,
 
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     INHERIT,
@@ -93,6 +108,8 @@ enum CnosKeyWord {
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     CHECKSUM,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    REPAIR,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     STREAM,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     STREAMS,
@@ -114,6 +131,23 @@ enum CnosKeyWord {
     AFTER,
     #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
     RECOVER,
+
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    ROLLUP,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    KEEP,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    AGG,
+
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    TASK,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    TASKS,
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    SCHEDULE,
+
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    UNLOCK,
 }
 
 impl FromStr for CnosKeyWord {
@@ -129,8 +163,15 @@ impl FromStr for CnosKeyWord {
             "VNODE_DURATION" => Ok(CnosKeyWord::VNODE_DURATION),
             "REPLICA" => Ok(CnosKeyWord::REPLICA),
             "PRECISION" => Ok(CnosKeyWord::PRECISION),
+            "MAX_SERIES" => Ok(CnosKeyWord::MAX_SERIES),
+            "MAX_SERIES_PER_TABLE" => Ok(CnosKeyWord::MAX_SERIES_PER_TABLE),
+            "BLOOM_FILTER" => Ok(CnosKeyWord::BLOOM_FILTER),
+            "DEDUP" => Ok(CnosKeyWord::DEDUP),
+            "POLICY" => Ok(CnosKeyWord::POLICY),
             "DATABASES" => Ok(CnosKeyWord::DATABASES),
             "QUERIES" => Ok(CnosKeyWord::QUERIES),
+            "CACHE" => Ok(CnosKeyWord::CACHE),
+            "CLEAR" => Ok(CnosKeyWord::CLEAR),
             "TENANT" => Ok(CnosKeyWord::TENANT),
             "INHERIT" => Ok(CnosKeyWord::INHERIT),
             "READ" => Ok(CnosKeyWord::READ),
@@ -146,6 +187,7 @@ impl FromStr for CnosKeyWord {
             "MOVE" => Ok(CnosKeyWord::MOVE),
             "COMPACT" => Ok(CnosKeyWord::COMPACT),
             "CHECKSUM" => Ok(CnosKeyWord::CHECKSUM),
+            "REPAIR" => Ok(CnosKeyWord::REPAIR),
             "STREAM" => Ok(CnosKeyWord::STREAM),
             "STREAMS" => Ok(CnosKeyWord::STREAMS),
             "TRIGGER" => Ok(CnosKeyWord::TRIGGER),
@@ -157,6 +199,13 @@ impl FromStr for CnosKeyWord {
             "UNSET" => Ok(CnosKeyWord::UNSET),
             "AFTER" => Ok(CnosKeyWord::AFTER),
             "RECOVER" => Ok(CnosKeyWord::RECOVER),
+            "ROLLUP" => Ok(CnosKeyWord::ROLLUP),
+            "KEEP" => Ok(CnosKeyWord::KEEP),
+            "AGG" => Ok(CnosKeyWord::AGG),
+            "TASK" => Ok(CnosKeyWord::TASK),
+            "TASKS" => Ok(CnosKeyWord::TASKS),
+            "SCHEDULE" => Ok(CnosKeyWord::SCHEDULE),
+            "UNLOCK" => Ok(CnosKeyWord::UNLOCK),
             _ => Err(ParserError::ParserError(format!(
                 "fail parse {} to CnosKeyWord",
                 s
@@ -300,10 +349,18 @@ impl<'a> ExtParser<'a> {
                                 self.parser.next_token();
                                 self.parse_checksum()
                             }
+                            CnosKeyWord::REPAIR => {
+                                self.parser.next_token();
+                                self.parse_repair()
+                            }
                             CnosKeyWord::RECOVER => {
                                 self.parser.next_token();
                                 self.parse_recover()
                             }
+                            CnosKeyWord::CLEAR => {
+                                self.parser.next_token();
+                                self.parse_clear()
+                            }
                             _ => Ok(ExtStatement::SqlStatement(Box::new(
                                 self.parser.parse_statement()?,
                             ))),
@@ -344,6 +401,11 @@ impl<'a> ExtParser<'a> {
             }
         } else if self.parse_cnos_keyword(CnosKeyWord::QUERIES) {
             self.parse_show_queries()
+        } else if self.parser.parse_keyword(Keyword::QUERY) {
+            self.expect_cnos_keyword(CnosKeyWord::CACHE)?;
+            Ok(ExtStatement::ShowQueryCache)
+        } else if self.parse_cnos_keyword(CnosKeyWord::TASKS) {
+            Ok(ExtStatement::ShowTasks)
         } else if self.parse_cnos_keyword(CnosKeyWord::STREAMS) {
             let verbose = self
                 .parser
@@ -497,6 +559,13 @@ impl<'a> ExtParser<'a> {
         Ok(ExtStatement::ShowQueries)
     }
 
+    /// Parse `CLEAR QUERY CACHE`
+    fn parse_clear(&mut self) -> Result<ExtStatement> {
+        self.parser.expect_keyword(Keyword::QUERY)?;
+        self.expect_cnos_keyword(CnosKeyWord::CACHE)?;
+        Ok(ExtStatement::ClearQueryCache)
+    }
+
     fn parse_show_databases(&mut self) -> Result<ExtStatement> {
         Ok(ExtStatement::ShowDatabases())
     }
@@ -561,8 +630,46 @@ impl<'a> ExtParser<'a> {
         } else if self.parser.parse_keyword(Keyword::RENAME) {
             let alter_tbl = self.parse_alter_table_rename(table_name)?;
             Ok(ExtStatement::AlterTable(alter_tbl))
+        } else if self.parser.parse_keyword(Keyword::SET) {
+            let options = self.parse_table_options()?;
+            Ok(ExtStatement::AlterTable(AlterTable {
+                table_name,
+                alter_action: AlterTableAction::SetOptions { options },
+            }))
+        } else {
+            self.expected(
+                "ADD or ALTER or DROP or RENAME or SET",
+                self.parser.peek_token(),
+            )
+        }
+    }
+
+    // parse: table_option [table_option ...]
+    fn parse_table_options(&mut self) -> Result<Vec<TableOption>> {
+        let mut options = vec![];
+        while let Some(option) = self.parse_table_option()? {
+            options.push(option);
+        }
+        if options.is_empty() {
+            return self.expected("BLOOM_FILTER or DEDUP POLICY", self.parser.peek_token());
+        }
+        Ok(options)
+    }
+
+    fn parse_table_option(&mut self) -> Result<Option<TableOption>> {
+        if self.parse_cnos_keyword(CnosKeyWord::BLOOM_FILTER) {
+            // parse: BLOOM_FILTER(column_name, ...)
+            let columns = self
+                .parser
+                .parse_parenthesized_column_list(IsOptional::Mandatory, true)?;
+            Ok(Some(TableOption::BloomFilter(columns)))
+        } else if self.parse_cnos_keyword(CnosKeyWord::DEDUP) {
+            // parse: DEDUP POLICY policy_name
+            self.expect_cnos_keyword(CnosKeyWord::POLICY)?;
+            let policy = self.parser.parse_identifier()?;
+            Ok(Some(TableOption::DedupPolicy(policy)))
         } else {
-            self.expected("ADD or ALTER or DROP or RENAME", self.parser.peek_token())
+            Ok(None)
         }
     }
 
@@ -628,20 +735,77 @@ impl<'a> ExtParser<'a> {
 
     fn parse_alter_database(&mut self) -> Result<ExtStatement> {
         let database_name = self.parser.parse_identifier()?;
-        self.parser.expect_keyword(Keyword::SET)?;
-        let mut options = DatabaseOptions::default();
-        if !self.parse_database_option(&mut options)? {
-            return parser_err!(format!(
-                "expected database option, but found {}",
-                self.parser.peek_token()
-            ));
-        }
+        let operation = if self.parser.parse_keyword(Keyword::ADD) {
+            if !self.parse_cnos_keyword(CnosKeyWord::ROLLUP) {
+                return self.expected("ROLLUP", self.parser.peek_token());
+            }
+            AlterDatabaseOperation::AddRollup(self.parse_rollup_options()?)
+        } else if self.parser.parse_keyword(Keyword::DROP) {
+            if !self.parse_cnos_keyword(CnosKeyWord::ROLLUP) {
+                return self.expected("ROLLUP", self.parser.peek_token());
+            }
+            AlterDatabaseOperation::DropRollup(self.parse_duration_value()?)
+        } else {
+            self.parser.expect_keyword(Keyword::SET)?;
+            let mut options = DatabaseOptions::default();
+            if !self.parse_database_option(&mut options)? {
+                return parser_err!(format!(
+                    "expected database option, but found {}",
+                    self.parser.peek_token()
+                ));
+            }
+            AlterDatabaseOperation::Set(options)
+        };
         Ok(ExtStatement::AlterDatabase(AlterDatabase {
             name: database_name,
-            options,
+            operation,
         }))
     }
 
+    /// Parse the rest of `ADD ROLLUP <interval> KEEP <duration> AGG (<agg>, ...)`
+    fn parse_rollup_options(&mut self) -> Result<RollupOptions> {
+        let interval = self.parse_duration_value()?;
+        if !self.parse_cnos_keyword(CnosKeyWord::KEEP) {
+            return self.expected("KEEP", self.parser.peek_token());
+        }
+        let keep = self.parse_duration_value()?;
+        if !self.parse_cnos_keyword(CnosKeyWord::AGG) {
+            return self.expected("AGG", self.parser.peek_token());
+        }
+        self.parser.expect_token(&Token::LParen)?;
+        let aggs = self
+            .parser
+            .parse_comma_separated(Parser::parse_identifier)?;
+        self.parser.expect_token(&Token::RParen)?;
+        Ok(RollupOptions {
+            interval,
+            keep,
+            aggs,
+        })
+    }
+
+    /// Parse a duration like `1h`, `'1h'` or `30` (days).
+    fn parse_duration_value(&mut self) -> Result<String> {
+        let token = self.parser.next_token();
+        match token.token {
+            Token::SingleQuotedString(s) => Ok(s),
+            Token::Number(num, _) => {
+                // The tokenizer splits `1h` into a number and a word.
+                if let Token::Word(w) = &self.parser.peek_token().token {
+                    if w.quote_style.is_none()
+                        && matches!(w.value.to_uppercase().as_str(), "Y" | "D" | "H" | "M")
+                    {
+                        let duration = format!("{}{}", num, w.value);
+                        self.parser.next_token();
+                        return Ok(duration);
+                    }
+                }
+                Ok(num)
+            }
+            other => self.expected("duration", other),
+        }
+    }
+
     fn parse_alter_tenant(&mut self) -> Result<ExtStatement> {
         let name = self.parser.parse_identifier()?;
 
@@ -685,8 +849,10 @@ impl<'a> ExtParser<'a> {
         } else if self.parser.parse_keyword(Keyword::SET) {
             let sql_option = ExtParser::parse_sql_option(&mut self.parser)?;
             AlterUserOperation::Set(sql_option)
+        } else if self.parse_cnos_keyword(CnosKeyWord::UNLOCK) {
+            AlterUserOperation::Unlock
         } else {
-            self.expected("RENAME,SET", self.parser.peek_token())?
+            self.expected("RENAME,SET,UNLOCK", self.parser.peek_token())?
         };
 
         Ok(ExtStatement::AlterUser(AlterUser { name, operation }))
@@ -866,11 +1032,17 @@ impl<'a> ExtParser<'a> {
                 .parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
         let table_name = self.parser.parse_object_name()?;
         let columns = self.parse_cnos_columns()?;
+        let options = if self.parser.parse_keyword(Keyword::WITH) {
+            self.parse_table_options()?
+        } else {
+            vec![]
+        };
 
         let create = CreateTable {
             name: table_name,
             if_not_exists,
             columns,
+            options,
         };
         Ok(ExtStatement::CreateTable(create))
     }
@@ -903,6 +1075,12 @@ impl<'a> ExtParser<'a> {
         } else if self.parse_cnos_keyword(CnosKeyWord::PRECISION) {
             let _ = self.parser.expect_token(&Token::Eq);
             options.precision = Some(self.parse_string_value()?);
+        } else if self.parse_cnos_keyword(CnosKeyWord::MAX_SERIES) {
+            let _ = self.parser.expect_token(&Token::Eq);
+            options.max_series = Some(self.parse_number::<u64>()?);
+        } else if self.parse_cnos_keyword(CnosKeyWord::MAX_SERIES_PER_TABLE) {
+            let _ = self.parser.expect_token(&Token::Eq);
+            options.max_series_per_table = Some(self.parse_number::<u64>()?);
         } else {
             return Ok(false);
         }
@@ -1008,15 +1186,24 @@ impl<'a> ExtParser<'a> {
     fn parse_privilege(&mut self) -> Result<Privilege, ParserError> {
         let action = self.parse_grant_permission()?;
         self.parser.expect_keyword(Keyword::ON)?;
-        self.parser.expect_keyword(Keyword::DATABASE)?;
-        let database = self.parser.parse_identifier()?;
-        Ok(Privilege { action, database })
+        let object = if self.parser.parse_keyword(Keyword::TABLE) {
+            let table = self.parser.parse_object_name()?;
+            let columns = self
+                .parser
+                .parse_parenthesized_column_list(IsOptional::Optional, false)?;
+            PrivilegeObject::Table(table, columns)
+        } else {
+            self.parser.expect_keyword(Keyword::DATABASE)?;
+            PrivilegeObject::Database(self.parser.parse_identifier()?)
+        };
+        Ok(Privilege { action, object })
     }
 
     fn parse_grant(&mut self) -> Result<ExtStatement> {
         // grant read on database "db1" to [role] rrr;
         // grant write on database "db2" to rrr;
         // grant all on database "db3" to rrr;
+        // grant read on table "db1"."t1" ("c1", "c2") to rrr;
         let privileges = self.parse_comma_separated(ExtParser::parse_privilege)?;
 
         self.parser.expect_keyword(Keyword::TO)?;
@@ -1035,6 +1222,7 @@ impl<'a> ExtParser<'a> {
         // revoke read on database "db1" from [role] rrr;
         // revoke write on database "db2" from rrr;
         // revoke all on database "db3" from rrr;
+        // revoke read on table "db1"."t1" from rrr;
         let privileges = self.parse_comma_separated(ExtParser::parse_privilege)?;
 
         self.parser.expect_keyword(Keyword::FROM)?;
@@ -1215,11 +1403,69 @@ impl<'a> ExtParser<'a> {
             self.parse_create_role()
         } else if self.parse_cnos_keyword(CnosKeyWord::STREAM) {
             self.parse_create_stream()
+        } else if self.parse_cnos_keyword(CnosKeyWord::TASK) {
+            self.parse_create_task()
+        } else if self.parse_cnos_keyword(CnosKeyWord::POLICY) {
+            self.parse_create_policy()
         } else {
             self.expected("an object type after CREATE", self.parser.peek_token())
         }
     }
 
+    /// Parse `CREATE TASK [IF NOT EXISTS] <name> SCHEDULE '<interval>' AS COPY INTO <location> ...`
+    fn parse_create_task(&mut self) -> Result<ExtStatement> {
+        let if_not_exists =
+            self.parser
+                .parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+        let name = self.parser.parse_identifier()?;
+        self.expect_cnos_keyword(CnosKeyWord::SCHEDULE)?;
+        let schedule = self.parse_string_value()?;
+        self.parser.expect_keyword(Keyword::AS)?;
+
+        let statement = self.parse_statement()?;
+        let copy = match statement {
+            ExtStatement::Copy(
+                copy @ ast::Copy {
+                    copy_target: CopyTarget::IntoLocation(_),
+                    ..
+                },
+            ) => copy,
+            _ => return parser_err!("only COPY INTO <location> is supported by task"),
+        };
+
+        Ok(ExtStatement::CreateTask(CreateTask {
+            name,
+            if_not_exists,
+            schedule,
+            statement: copy,
+        }))
+    }
+
+    /// Parse `CREATE POLICY [IF NOT EXISTS] <name> ON <table> FOR ROLE <role> USING (<expr>)`
+    fn parse_create_policy(&mut self) -> Result<ExtStatement> {
+        let if_not_exists =
+            self.parser
+                .parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+        let name = self.parser.parse_identifier()?;
+        self.parser.expect_keyword(Keyword::ON)?;
+        let table = self.parser.parse_object_name()?;
+        self.parser
+            .expect_keywords(&[Keyword::FOR, Keyword::ROLE])?;
+        let role = self.parser.parse_identifier()?;
+        self.parser.expect_keyword(Keyword::USING)?;
+        self.parser.expect_token(&Token::LParen)?;
+        let predicate = self.parser.parse_expr()?;
+        self.parser.expect_token(&Token::RParen)?;
+
+        Ok(ExtStatement::CreatePolicy(CreatePolicy {
+            name,
+            if_not_exists,
+            table,
+            role,
+            predicate,
+        }))
+    }
+
     /// Parse a copy statement
     fn parse_copy(&mut self) -> Result<ExtStatement> {
         if self.parse_cnos_keyword(CnosKeyWord::VNODE) {
@@ -1478,9 +1724,28 @@ impl<'a> ExtParser<'a> {
             let if_exist = self.parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
             let name = self.parser.parse_identifier()?;
             ExtStatement::DropStream(ast::DropStream { if_exist, name })
+        } else if self.parse_cnos_keyword(CnosKeyWord::TASK) {
+            let if_exist = self.parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+            let object_name = self.parser.parse_identifier()?;
+            ExtStatement::DropTenantObject(DropTenantObject {
+                object_name,
+                if_exist,
+                obj_type: TenantObjectType::Task,
+                after: None,
+            })
+        } else if self.parse_cnos_keyword(CnosKeyWord::POLICY) {
+            let if_exist = self.parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+            let name = self.parser.parse_identifier()?;
+            self.parser.expect_keyword(Keyword::ON)?;
+            let table = self.parser.parse_object_name()?;
+            ExtStatement::DropPolicy(DropPolicy {
+                name,
+                if_exist,
+                table,
+            })
         } else {
             return self.expected(
-                "TABLE,DATABASE,TENANT,USER,ROLE,VNODE,STREAM after DROP",
+                "TABLE,DATABASE,TENANT,USER,ROLE,VNODE,STREAM,TASK,POLICY after DROP",
                 self.parser.peek_token(),
             );
         };
@@ -1528,6 +1793,17 @@ impl<'a> ExtParser<'a> {
         }
     }
 
+    fn parse_repair(&mut self) -> Result<ExtStatement> {
+        if self.parser.parse_keyword(Keyword::GROUP) {
+            let replication_set_id = self.parse_number::<ReplicationSetId>()?;
+            Ok(ExtStatement::RepairGroup(RepairGroup {
+                replication_set_id,
+            }))
+        } else {
+            parser_err!("Expected GROUP. after REPAIR")
+        }
+    }
+
     fn consume_token(&mut self, expected: &Token) -> bool {
         if self.parser.peek_token().token == *expected {
             self.parser.next_token();
@@ -1912,7 +2188,8 @@ mod tests {
                     is_tag: false,
                     data_type: DataType::BigInt(None),
                     encoding: None
-                }]
+                }],
+                options: vec![],
             })
         );
 
@@ -1936,6 +2213,7 @@ mod tests {
                 name,
                 if_not_exists,
                 columns,
+                ..
             }) => {
                 assert_eq!(name.to_string(), "test".to_string());
                 assert_eq!(if_not_exists.to_string(), "true".to_string());
@@ -2126,12 +2404,89 @@ mod tests {
             ExtStatement::CreateDatabase(ref stmt) => {
                 let ans = format!("{:?}", stmt);
                 println!("{ans}");
-                let expectd = "CreateDatabase { name: Ident { value: \"test\", quote_style: None }, if_not_exists: false, options: DatabaseOptions { ttl: Some(\"10d\"), shard_num: Some(5), vnode_duration: Some(\"3d\"), replica: Some(10), precision: Some(\"us\") } }";
+                let expectd = "CreateDatabase { name: Ident { value: \"test\", quote_style: None }, if_not_exists: false, options: DatabaseOptions { ttl: Some(\"10d\"), shard_num: Some(5), vnode_duration: Some(\"3d\"), replica: Some(10), precision: Some(\"us\"), max_series: None, max_series_per_table: None } }";
                 assert_eq!(ans, expectd);
             }
             _ => panic!("impossible"),
         }
     }
+
+    #[test]
+    fn test_alter_database_rollup() {
+        let sql = r#"
+            ALTER DATABASE test ADD ROLLUP 1h KEEP 1y AGG (min, max, mean, last);
+            ALTER DATABASE test ADD ROLLUP '5m' KEEP 30 AGG (sum);
+            ALTER DATABASE test DROP ROLLUP 1h;
+            ALTER DATABASE test SET TTL '10d';
+        "#;
+        let statements: Vec<AlterDatabase> = ExtParser::parse_sql(sql)
+            .unwrap()
+            .into_iter()
+            .map(|s| match s {
+                ExtStatement::AlterDatabase(s) => s,
+                _ => panic!("Expect AlterDatabase"),
+            })
+            .collect();
+        assert_eq!(
+            statements
+                .into_iter()
+                .map(|s| s.operation)
+                .collect::<Vec<_>>(),
+            vec![
+                AlterDatabaseOperation::AddRollup(RollupOptions {
+                    interval: "1h".to_string(),
+                    keep: "1y".to_string(),
+                    aggs: vec![
+                        Ident::from("min"),
+                        Ident::from("max"),
+                        Ident::from("mean"),
+                        Ident::from("last")
+                    ],
+                }),
+                AlterDatabaseOperation::AddRollup(RollupOptions {
+                    interval: "5m".to_string(),
+                    keep: "30".to_string(),
+                    aggs: vec![Ident::from("sum")],
+                }),
+                AlterDatabaseOperation::DropRollup("1h".to_string()),
+                AlterDatabaseOperation::Set(DatabaseOptions {
+                    ttl: Some("10d".to_string()),
+                    ..Default::default()
+                }),
+            ]
+        );
+
+        assert!(ExtParser::parse_sql("ALTER DATABASE test ADD ROLLUP 1h AGG (min)").is_err());
+    }
+
+    #[test]
+    fn test_database_max_series() {
+        let sql = r#"
+            CREATE DATABASE test WITH MAX_SERIES 100000 MAX_SERIES_PER_TABLE = 1000;
+            ALTER DATABASE test SET MAX_SERIES_PER_TABLE 2000;
+        "#;
+        let statements = ExtParser::parse_sql(sql).unwrap();
+        match &statements[0] {
+            ExtStatement::CreateDatabase(stmt) => {
+                assert_eq!(stmt.options.max_series, Some(100000));
+                assert_eq!(stmt.options.max_series_per_table, Some(1000));
+            }
+            _ => panic!("Expect CreateDatabase"),
+        }
+        match &statements[1] {
+            ExtStatement::AlterDatabase(stmt) => assert_eq!(
+                stmt.operation,
+                AlterDatabaseOperation::Set(DatabaseOptions {
+                    max_series_per_table: Some(2000),
+                    ..Default::default()
+                })
+            ),
+            _ => panic!("Expect AlterDatabase"),
+        }
+
+        assert!(ExtParser::parse_sql("CREATE DATABASE test WITH MAX_SERIES -1").is_err());
+    }
+
     #[test]
     #[should_panic]
     fn test_create_table_without_fields() {
@@ -2139,6 +2494,74 @@ mod tests {
         ExtParser::parse_sql(sql).unwrap();
     }
 
+    #[test]
+    fn test_table_bloom_filter_options() {
+        let sql =
+            "CREATE TABLE test(column1 STRING, TAGS(device)) WITH BLOOM_FILTER(device, column1);\
+            ALTER TABLE test SET BLOOM_FILTER();";
+        let mut statements = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(statements.len(), 2);
+        match statements.pop_front().unwrap() {
+            ExtStatement::CreateTable(CreateTable { options, .. }) => {
+                assert_eq!(
+                    options,
+                    vec![TableOption::BloomFilter(vec![
+                        Ident::from("device"),
+                        Ident::from("column1")
+                    ])]
+                );
+            }
+            _ => panic!("failed to check create table statement"),
+        }
+        assert_eq!(
+            statements.pop_front().unwrap(),
+            ExtStatement::AlterTable(AlterTable {
+                table_name: ObjectName(vec![Ident::from("test")]),
+                alter_action: AlterTableAction::SetOptions {
+                    options: vec![TableOption::BloomFilter(vec![])]
+                },
+            })
+        );
+
+        let sql = "CREATE TABLE test(column1 STRING) WITH;";
+        ExtParser::parse_sql(sql).unwrap_err();
+        let sql = "ALTER TABLE test SET BLOOM_FILTER;";
+        ExtParser::parse_sql(sql).unwrap_err();
+    }
+
+    #[test]
+    fn test_table_dedup_policy_option() {
+        let sql = "CREATE TABLE test(column1 BIGINT, TAGS(device)) \
+            WITH BLOOM_FILTER(device) DEDUP POLICY keep_first;\
+            ALTER TABLE test SET DEDUP POLICY last_write_wins;";
+        let mut statements = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(statements.len(), 2);
+        match statements.pop_front().unwrap() {
+            ExtStatement::CreateTable(CreateTable { options, .. }) => {
+                assert_eq!(
+                    options,
+                    vec![
+                        TableOption::BloomFilter(vec![Ident::from("device")]),
+                        TableOption::DedupPolicy(Ident::from("keep_first")),
+                    ]
+                );
+            }
+            _ => panic!("failed to check create table statement"),
+        }
+        assert_eq!(
+            statements.pop_front().unwrap(),
+            ExtStatement::AlterTable(AlterTable {
+                table_name: ObjectName(vec![Ident::from("test")]),
+                alter_action: AlterTableAction::SetOptions {
+                    options: vec![TableOption::DedupPolicy(Ident::from("last_write_wins"))]
+                },
+            })
+        );
+
+        let sql = "ALTER TABLE test SET DEDUP merge;";
+        ExtParser::parse_sql(sql).unwrap_err();
+    }
+
     #[test]
     fn test_alter_table() {
         let sql = r#"
@@ -2286,6 +2709,15 @@ mod tests {
                 replication_set_id: 10
             })
         );
+        let sql6 = "repair group 11";
+        let statement = ExtParser::parse_sql(sql6).unwrap();
+        assert_eq!(
+            statement[0],
+            ExtStatement::RepairGroup(RepairGroup {
+                replication_set_id: 11
+            })
+        );
+        assert!(ExtParser::parse_sql("repair vnode 11").is_err());
     }
 
     #[test]
@@ -2519,4 +2951,171 @@ mod tests {
             _ => panic!("expect RenameColumn"),
         }
     }
+
+    #[test]
+    fn test_create_task() {
+        let sql = r#"
+            CREATE TASK IF NOT EXISTS backup SCHEDULE '1h'
+            AS COPY INTO 'file:///tmp/backup/'
+            FROM (SELECT * FROM air WHERE station = 'XiaoMaiDao')
+            FILE_FORMAT = (TYPE = 'PARQUET');
+            SHOW TASKS;
+            DROP TASK backup;
+        "#;
+        let statements = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(statements.len(), 3);
+
+        match &statements[0] {
+            ExtStatement::CreateTask(stmt) => {
+                assert_eq!(stmt.name, Ident::from("backup"));
+                assert!(stmt.if_not_exists);
+                assert_eq!(stmt.schedule, "1h");
+                assert_eq!(
+                    stmt.statement.to_string(),
+                    "COPY INTO 'file:///tmp/backup/' \
+                    FROM (SELECT * FROM air WHERE station = 'XiaoMaiDao') \
+                    FILE_FORMAT = (TYPE = 'PARQUET')"
+                );
+                assert_eq!(
+                    parse_sql(&stmt.statement.to_string()),
+                    ExtStatement::Copy(stmt.statement.clone())
+                );
+            }
+            _ => panic!("expect CreateTask"),
+        }
+        assert_eq!(statements[1], ExtStatement::ShowTasks);
+        match &statements[2] {
+            ExtStatement::DropTenantObject(stmt) => {
+                assert_eq!(stmt.object_name, Ident::from("backup"));
+                assert_eq!(stmt.obj_type, TenantObjectType::Task);
+            }
+            _ => panic!("expect DropTenantObject"),
+        }
+
+        let sql = "CREATE TASK t SCHEDULE '1h' AS SELECT * FROM air";
+        assert!(ExtParser::parse_sql(sql).is_err());
+    }
+
+    #[test]
+    fn test_query_cache() {
+        let sql = "SHOW QUERY CACHE; clear query cache;";
+        let statements = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0], ExtStatement::ShowQueryCache);
+        assert_eq!(statements[1], ExtStatement::ClearQueryCache);
+
+        assert!(ExtParser::parse_sql("SHOW QUERY").is_err());
+        assert!(ExtParser::parse_sql("CLEAR CACHE").is_err());
+    }
+
+    #[test]
+    fn test_grant_table() {
+        let sql = r#"grant read on table db1.t1 (c1, "C2"), write on table t2 to role r1;
+            revoke read on table "db1"."t1" from r1;"#;
+        let statements = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(statements.len(), 2);
+
+        match &statements[0] {
+            ExtStatement::GrantRevoke(GrantRevoke {
+                is_grant,
+                privileges,
+                role_name,
+            }) => {
+                assert!(is_grant);
+                assert_eq!(role_name.value, "r1");
+                assert_eq!(privileges.len(), 2);
+                assert_eq!(privileges[0].action, Action::Read);
+                match &privileges[0].object {
+                    PrivilegeObject::Table(table, columns) => {
+                        assert_eq!(table.to_string(), "db1.t1");
+                        assert_eq!(
+                            columns,
+                            &vec![Ident::new("c1"), Ident::with_quote('"', "C2")]
+                        );
+                    }
+                    _ => panic!("expect table privilege"),
+                }
+                assert_eq!(privileges[1].action, Action::Write);
+                match &privileges[1].object {
+                    PrivilegeObject::Table(table, columns) => {
+                        assert_eq!(table.to_string(), "t2");
+                        assert!(columns.is_empty());
+                    }
+                    _ => panic!("expect table privilege"),
+                }
+            }
+            _ => panic!("expect GrantRevoke"),
+        }
+
+        match &statements[1] {
+            ExtStatement::GrantRevoke(GrantRevoke {
+                is_grant,
+                privileges,
+                ..
+            }) => {
+                assert!(!is_grant);
+                match &privileges[0].object {
+                    PrivilegeObject::Table(table, columns) => {
+                        assert_eq!(table.to_string(), r#""db1"."t1""#);
+                        assert!(columns.is_empty());
+                    }
+                    _ => panic!("expect table privilege"),
+                }
+            }
+            _ => panic!("expect GrantRevoke"),
+        }
+    }
+
+    #[test]
+    fn test_create_drop_policy() {
+        let sql = "create policy if not exists p1 on db1.t1 for role r1 using (customer = current_user());
+            drop policy p1 on db1.t1;";
+        let statements = ExtParser::parse_sql(sql).unwrap();
+        assert_eq!(statements.len(), 2);
+
+        match &statements[0] {
+            ExtStatement::CreatePolicy(CreatePolicy {
+                name,
+                if_not_exists,
+                table,
+                role,
+                predicate,
+            }) => {
+                assert_eq!(name.value, "p1");
+                assert!(if_not_exists);
+                assert_eq!(table.to_string(), "db1.t1");
+                assert_eq!(role.value, "r1");
+                assert_eq!(predicate.to_string(), "customer = current_user()");
+            }
+            _ => panic!("expect CreatePolicy"),
+        }
+
+        assert_eq!(
+            statements[1],
+            ExtStatement::DropPolicy(DropPolicy {
+                name: Ident::new("p1"),
+                if_exist: false,
+                table: ObjectName(vec![Ident::new("db1"), Ident::new("t1")]),
+            })
+        );
+
+        assert!(
+            ExtParser::parse_sql("create policy p1 on t1 for role r1 using customer = 'a'")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_alter_user_unlock() {
+        let statements = ExtParser::parse_sql("ALTER USER u1 UNLOCK").unwrap();
+        assert_eq!(
+            statements[0],
+            ExtStatement::AlterUser(AlterUser {
+                name: Ident::from("u1"),
+                operation: AlterUserOperation::Unlock,
+            })
+        );
+
+        assert!(ExtParser::parse_sql("ALTER USER u1 LOCK").is_err());
+    }
 }
@@ -24,6 +24,8 @@ use spi::Result;
 
 use super::optimizer::PhysicalOptimizer;
 use crate::extension::physical::optimizer_rule::add_assert::AddAssertExec;
+use crate::extension::physical::transform_rule::aggregate_scan::AggregateScanPlanner;
+use crate::extension::physical::transform_rule::cached_result::CachedResultPlanner;
 use crate::extension::physical::transform_rule::expand::ExpandPlanner;
 use crate::extension::physical::transform_rule::gapfill::GapFillPlanner;
 use crate::extension::physical::transform_rule::table_writer::TableWriterPlanner;
@@ -64,6 +66,8 @@ impl Default for DefaultPhysicalPlanner {
             Arc::new(TableWriterPlanner {}),
             Arc::new(UpdateTagValuePlanner {}),
             Arc::new(TagScanPlanner {}),
+            Arc::new(AggregateScanPlanner {}),
+            Arc::new(CachedResultPlanner {}),
             Arc::new(ExpandPlanner::new()),
             Arc::new(GapFillPlanner::new()),
         ];
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::option::Option;
 use std::sync::Arc;
 use std::{iter, vec};
@@ -30,8 +30,8 @@ use datafusion::logical_expr::logical_plan::Analyze;
 use datafusion::logical_expr::utils::expr_to_columns;
 use datafusion::logical_expr::{
     lit, BinaryExpr, BuiltinScalarFunction, Case, CreateExternalTable as PlanCreateExternalTable,
-    EmptyRelation, Explain, Expr, Extension, LogicalPlan, LogicalPlanBuilder, Operator, PlanType,
-    SubqueryAlias, TableSource, ToStringifiedPlan, Union,
+    EmptyRelation, Explain, Expr, ExprSchemable, Extension, LogicalPlan, LogicalPlanBuilder,
+    Operator, PlanType, SubqueryAlias, TableSource, ToStringifiedPlan, Union,
 };
 use datafusion::optimizer::analyzer::type_coercion::TypeCoercionRewriter;
 use datafusion::optimizer::simplify_expressions::ConstEvaluator;
@@ -50,43 +50,48 @@ use datafusion::sql::TableReference;
 use lazy_static::__Deref;
 use meta::error::MetaError;
 use models::auth::privilege::{
-    DatabasePrivilege, GlobalPrivilege, Privilege, TenantObjectPrivilege,
+    DatabasePrivilege, GlobalPrivilege, Privilege, TablePrivilege, TenantObjectPrivilege,
 };
-use models::auth::role::{SystemTenantRole, TenantRoleIdentifier};
+use models::auth::role::{RowPolicy, SystemTenantRole, TenantRoleIdentifier};
 use models::auth::user::User;
+use models::auth::{AuthError, PasswordPolicy};
 use models::gis::data_type::{Geometry, GeometryType};
 use models::object_reference::{Resolve, ResolvedTable};
 use models::oid::{Identifier, Oid};
 use models::schema::{
-    ColumnType, DatabaseOptions, Duration, Precision, TableColumn, Tenant, TskvTableSchema,
+    ColumnType, DatabaseOptions, DedupPolicy, Duration, DurationUnit, Precision, RollupAgg,
+    RollupPolicy, ScheduledTask, TableColumn, TableOptions, Tenant, TskvTableSchema,
     TskvTableSchemaRef, Watermark, DEFAULT_CATALOG, TIME_FIELD,
 };
-use models::utils::SeqIdGenerator;
+use models::utils::{now_timestamp_nanos, SeqIdGenerator};
 use models::{ColumnId, ValueType};
 use object_store::ObjectStore;
 use spi::query::ast;
 use spi::query::ast::{
-    AlterDatabase as ASTAlterDatabase, AlterTable as ASTAlterTable,
-    AlterTableAction as ASTAlterTableAction, AlterTenantOperation, AlterUserOperation,
-    ChecksumGroup as ASTChecksumGroup, ColumnOption, CompactVnode as ASTCompactVnode,
-    CopyIntoTable, CopyTarget, CopyVnode as ASTCopyVnode, CreateDatabase as ASTCreateDatabase,
-    CreateTable as ASTCreateTable, DatabaseOptions as ASTDatabaseOptions,
-    DescribeDatabase as DescribeDatabaseOptions, DescribeTable as DescribeTableOptions,
-    DropVnode as ASTDropVnode, ExtStatement, MoveVnode as ASTMoveVnode,
-    ShowSeries as ASTShowSeries, ShowTagBody, ShowTagValues as ASTShowTagValues, UriLocation, With,
+    AlterDatabase as ASTAlterDatabase, AlterDatabaseOperation as ASTAlterDatabaseOperation,
+    AlterTable as ASTAlterTable, AlterTableAction as ASTAlterTableAction, AlterTenantOperation,
+    AlterUserOperation, ChecksumGroup as ASTChecksumGroup, ColumnOption,
+    CompactVnode as ASTCompactVnode, CopyIntoTable, CopyTarget, CopyVnode as ASTCopyVnode,
+    CreateDatabase as ASTCreateDatabase, CreateTable as ASTCreateTable,
+    DatabaseOptions as ASTDatabaseOptions, DescribeDatabase as DescribeDatabaseOptions,
+    DescribeTable as DescribeTableOptions, DropVnode as ASTDropVnode, ExtStatement,
+    MoveVnode as ASTMoveVnode, RepairGroup as ASTRepairGroup, RollupOptions,
+    ShowSeries as ASTShowSeries, ShowTagBody, ShowTagValues as ASTShowTagValues, TableOption,
+    UriLocation, With,
 };
 use spi::query::datasource::{self, UriSchema};
 use spi::query::logical_planner::{
     normalize_sql_object_name_to_string, parse_connection_options,
     sql_option_to_alter_tenant_action, sql_options_to_map, sql_options_to_tenant_options,
-    sql_options_to_user_options, unset_option_to_alter_tenant_action, AlterDatabase, AlterTable,
-    AlterTableAction, AlterTenant, AlterTenantAction, AlterTenantAddUser, AlterTenantSetUser,
-    AlterUser, AlterUserAction, ChecksumGroup, CompactVnode, CopyOptions, CopyOptionsBuilder,
-    CopyVnode, CreateDatabase, CreateRole, CreateStreamTable, CreateTable, CreateTenant,
-    CreateUser, DDLPlan, DMLPlan, DatabaseObjectType, DeleteFromTable, DropDatabaseObject,
-    DropGlobalObject, DropTenantObject, DropVnode, FileFormatOptions, FileFormatOptionsBuilder,
-    GlobalObjectType, GrantRevoke, LogicalPlanner, MoveVnode, Plan, PlanWithPrivileges, QueryPlan,
-    RecoverDatabase, RecoverTenant, SYSPlan, TenantObjectType, TENANT_OPTION_LIMITER,
+    sql_options_to_user_options, unset_option_to_alter_tenant_action, AlterDatabase,
+    AlterDatabaseOperation, AlterTable, AlterTableAction, AlterTenant, AlterTenantAction,
+    AlterTenantAddUser, AlterTenantSetUser, AlterUser, AlterUserAction, ChecksumGroup,
+    CompactVnode, CopyOptions, CopyOptionsBuilder, CopyVnode, CreateDatabase, CreatePolicy,
+    CreateRole, CreateStreamTable, CreateTable, CreateTask, CreateTenant, CreateUser, DDLPlan,
+    DMLPlan, DatabaseObjectType, DeleteFromTable, DropDatabaseObject, DropGlobalObject, DropPolicy,
+    DropTenantObject, DropVnode, FileFormatOptions, FileFormatOptionsBuilder, GlobalObjectType,
+    GrantRevoke, LogicalPlanner, MoveVnode, Plan, PlanWithPrivileges, QueryPlan, RecoverDatabase,
+    RecoverTenant, RepairGroup, SYSPlan, TenantObjectType, TENANT_OPTION_LIMITER,
 };
 use spi::query::session::SessionCtx;
 use spi::{QueryError, Result};
@@ -104,13 +109,15 @@ use crate::metadata::{
     COLUMNS_TABLE_NAME, DATABASES_DATABASE_NAME, DATABASES_PRECISION, DATABASES_REPLICA,
     DATABASES_SHARD, DATABASES_TTL, DATABASES_VNODE_DURATION, INFORMATION_SCHEMA,
     INFORMATION_SCHEMA_COLUMNS, INFORMATION_SCHEMA_DATABASES, INFORMATION_SCHEMA_QUERIES,
-    INFORMATION_SCHEMA_TABLES, TABLES_TABLE_DATABASE, TABLES_TABLE_NAME,
+    INFORMATION_SCHEMA_TABLES, INFORMATION_SCHEMA_TASKS, TABLES_TABLE_DATABASE, TABLES_TABLE_NAME,
 };
+use crate::scheduled_task::incremental_copy;
 
 /// CnosDB SQL query planner
 pub struct SqlPlanner<'a, S: ContextProviderExtension> {
     schema_provider: &'a S,
     df_planner: SqlToRel<'a, S>,
+    password_policy: PasswordPolicy,
 }
 
 #[async_trait]
@@ -131,6 +138,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync> LogicalPlanner for SqlPlanne
         };
 
         let _ = session.get_child_span_recorder("check privilege");
+        check_password_change(session.user(), &plan)?;
         check_privilege(session.user(), privileges)?;
         Ok(plan)
     }
@@ -142,9 +150,16 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         SqlPlanner {
             schema_provider,
             df_planner: SqlToRel::new(schema_provider),
+            password_policy: PasswordPolicy::default(),
         }
     }
 
+    /// Passwords set by CREATE USER and ALTER USER are checked by the policy
+    pub fn with_password_policy(mut self, password_policy: PasswordPolicy) -> Self {
+        self.password_policy = password_policy;
+        self
+    }
+
     /// Generate a logical plan from an  Extent SQL statement
     #[async_recursion]
     pub(crate) async fn statement_to_plan(
@@ -183,8 +198,12 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
             ExtStatement::AlterTenant(stmt) => self.alter_tenant_to_plan(stmt).await,
             ExtStatement::AlterUser(stmt) => self.alter_user_to_plan(stmt).await,
             ExtStatement::GrantRevoke(stmt) => self.grant_revoke_to_plan(stmt, session),
+            ExtStatement::CreatePolicy(stmt) => self.create_policy_to_plan(stmt, session),
+            ExtStatement::DropPolicy(stmt) => self.drop_policy_to_plan(stmt, session),
             // system statement
             ExtStatement::ShowQueries => self.show_queries_to_plan(session),
+            ExtStatement::ShowQueryCache => self.show_query_cache_to_plan(session),
+            ExtStatement::ClearQueryCache => self.clear_query_cache_to_plan(session),
             ExtStatement::Copy(stmt) => self.copy_to_plan(stmt, session).await,
             // vnode statement
             ExtStatement::DropVnode(stmt) => self.drop_vnode_to_plan(stmt),
@@ -192,6 +211,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
             ExtStatement::MoveVnode(stmt) => self.move_vnode_to_plan(stmt),
             ExtStatement::CompactVnode(stmt) => self.compact_vnode_to_plan(stmt),
             ExtStatement::ChecksumGroup(stmt) => self.checksum_group_to_plan(stmt),
+            ExtStatement::RepairGroup(stmt) => self.repair_group_to_plan(stmt),
             ExtStatement::CreateStream(_) => Err(QueryError::NotImplemented {
                 err: "CreateStream Planner.".to_string(),
             }),
@@ -206,6 +226,8 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
             }
             ExtStatement::RecoverTenant(stmt) => self.recovertenant_to_plan(stmt),
             ExtStatement::RecoverDatabase(stmt) => self.recoverdatabase_to_plan(stmt, session),
+            ExtStatement::CreateTask(stmt) => self.create_task_to_plan(stmt, session).await,
+            ExtStatement::ShowTasks => self.show_tasks_to_plan(session),
         }
     }
 
@@ -221,7 +243,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
 
                 // privileges
                 let access_databases = self.schema_provider.reset_access_databases();
-                let privileges = databases_privileges(
+                let privileges = tables_privileges(
                     DatabasePrivilege::Read,
                     *session.tenant_id(),
                     access_databases,
@@ -361,7 +383,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         let plan = Plan::Query(QueryPlan { df_plan });
 
         // privileges
-        let write_privileges = databases_privileges(
+        let write_privileges = tables_privileges(
             DatabasePrivilege::Write,
             *session.tenant_id(),
             self.schema_provider.reset_access_databases(),
@@ -431,7 +453,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
 
         // save database read privileges
         // This operation must be done before fetching the target table metadata
-        let mut read_privileges = databases_privileges(
+        let mut read_privileges = tables_privileges(
             DatabasePrivilege::Read,
             *session.tenant_id(),
             self.schema_provider.reset_access_databases(),
@@ -459,7 +481,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         let plan = Plan::Query(QueryPlan { df_plan });
 
         // privileges
-        let mut write_privileges = databases_privileges(
+        let mut write_privileges = tables_privileges(
             DatabasePrivilege::Write,
             *session.tenant_id(),
             self.schema_provider.reset_access_databases(),
@@ -652,6 +674,16 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
                     Privilege::TenantObject(TenantObjectPrivilege::RoleFull, Some(tenant_id)),
                 )
             }
+            TenantObjectType::Task => (
+                DDLPlan::DropTenantObject(DropTenantObject {
+                    tenant_name: tenant_name.to_string(),
+                    name: normalize_ident(object_name),
+                    if_exist,
+                    obj_type: TenantObjectType::Task,
+                    after: after_duration,
+                }),
+                Privilege::TenantObject(TenantObjectPrivilege::System, Some(tenant_id)),
+            ),
         };
 
         Ok(PlanWithPrivileges {
@@ -861,6 +893,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
             name,
             if_not_exists,
             columns,
+            options,
         } = statement;
         let id_generator = SeqIdGenerator::default();
         // all col: time col, tag col, field col
@@ -898,10 +931,13 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
             }
         }
 
+        let options = Self::table_options(options, &schema, TableOptions::default())?;
+
         let plan = Plan::DDL(DDLPlan::CreateTable(CreateTable {
             schema,
             name: resolved_table,
             if_not_exists,
+            options,
         }));
 
         // privilege
@@ -914,6 +950,52 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         })
     }
 
+    /// Apply the options to `table_options` of the table with the columns.
+    fn table_options(
+        options: Vec<TableOption>,
+        columns: &[TableColumn],
+        mut table_options: TableOptions,
+    ) -> Result<TableOptions> {
+        for option in options {
+            match option {
+                TableOption::BloomFilter(column_names) => {
+                    let mut column_ids = BTreeSet::new();
+                    for name in column_names {
+                        let name = normalize_ident(name);
+                        let column = columns.iter().find(|c| c.name == name).ok_or_else(|| {
+                            QueryError::Semantic {
+                                err: format!("bloom filter column {name} not exists"),
+                            }
+                        })?;
+                        let supported = column.column_type.is_tag()
+                            || column.column_type == ColumnType::Field(ValueType::String);
+                        if !supported {
+                            return Err(QueryError::Semantic {
+                                err: format!(
+                                    "bloom filter only supports tag or string field, but column {name} is {}",
+                                    column.column_type
+                                ),
+                            });
+                        }
+                        column_ids.insert(column.id);
+                    }
+                    table_options.with_bloom_filter_columns(column_ids);
+                }
+                TableOption::DedupPolicy(policy) => {
+                    let policy = DedupPolicy::new(&policy.value).ok_or_else(|| {
+                        QueryError::Semantic {
+                            err: format!(
+                                "dedup policy {policy} not supported, expected LAST_WRITE_WINS, KEEP_FIRST or MERGE"
+                            ),
+                        }
+                    })?;
+                    table_options.with_dedup_policy(policy);
+                }
+            }
+        }
+        Ok(table_options)
+    }
+
     fn column_opt_to_table_column(
         &self,
         column_opt: ColumnOption,
@@ -1149,6 +1231,14 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
                     new_column_name,
                 }
             }
+            ASTAlterTableAction::SetOptions { options } => {
+                let options = Self::table_options(
+                    options,
+                    table_schema.columns(),
+                    table_schema.options().clone(),
+                )?;
+                AlterTableAction::SetOptions { options }
+            }
         };
         let plan = Plan::DDL(DDLPlan::AlterTable(AlterTable {
             table_name,
@@ -1303,14 +1393,17 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         }
 
         let df_plan = plan_builder.build()?;
-        let db_name = &table_schema.db;
 
+        // privileges
+        let access_databases = self.schema_provider.reset_access_databases();
+        let privileges = tables_privileges(
+            DatabasePrivilege::Read,
+            *session.tenant_id(),
+            access_databases,
+        );
         Ok(PlanWithPrivileges {
             plan: Plan::Query(QueryPlan { df_plan }),
-            privileges: vec![Privilege::TenantObject(
-                TenantObjectPrivilege::Database(DatabasePrivilege::Read, Some(db_name.to_string())),
-                Some(*session.tenant_id()),
-            )],
+            privileges,
         })
     }
 
@@ -1462,17 +1555,28 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         stmt: ASTAlterDatabase,
         session: &SessionCtx,
     ) -> Result<PlanWithPrivileges> {
-        let ASTAlterDatabase { name, options } = stmt;
-        let options = self.make_database_option(options)?;
-        if options.precision().is_some() {
-            return Err(QueryError::Semantic {
-                err: "Can not alter database precision".to_string(),
-            });
-        }
+        let ASTAlterDatabase { name, operation } = stmt;
+        let operation = match operation {
+            ASTAlterDatabaseOperation::Set(options) => {
+                let options = self.make_database_option(options)?;
+                if options.precision().is_some() {
+                    return Err(QueryError::Semantic {
+                        err: "Can not alter database precision".to_string(),
+                    });
+                }
+                AlterDatabaseOperation::Set(options)
+            }
+            ASTAlterDatabaseOperation::AddRollup(rollup) => {
+                AlterDatabaseOperation::AddRollup(self.make_rollup_policy(rollup)?)
+            }
+            ASTAlterDatabaseOperation::DropRollup(interval) => {
+                AlterDatabaseOperation::DropRollup(self.str_to_duration(&interval)?)
+            }
+        };
         let database_name = normalize_ident(name);
         let plan = Plan::DDL(DDLPlan::AlterDatabase(AlterDatabase {
             database_name: database_name.clone(),
-            database_options: options,
+            operation,
         }));
         // privileges
         let tenant_id = *session.tenant_id();
@@ -1508,9 +1612,51 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
                 )),
             })?);
         }
+        if let Some(max_series) = options.max_series {
+            plan_options.with_max_series(max_series);
+        }
+        if let Some(max_series_per_table) = options.max_series_per_table {
+            plan_options.with_max_series_per_table(max_series_per_table);
+        }
         Ok(plan_options)
     }
 
+    fn make_rollup_policy(&self, rollup: RollupOptions) -> Result<RollupPolicy> {
+        let interval = self.str_to_duration(&rollup.interval)?;
+        let keep = self.str_to_duration(&rollup.keep)?;
+        if interval.time_num == 0 {
+            return Err(QueryError::Semantic {
+                err: "Rollup interval must be greater than 0".to_string(),
+            });
+        }
+        if keep.to_nanoseconds() < interval.to_nanoseconds() {
+            return Err(QueryError::Semantic {
+                err: format!(
+                    "Rollup keep time {} must not be less than the interval {}",
+                    keep, interval
+                ),
+            });
+        }
+        if rollup.aggs.is_empty() {
+            return Err(QueryError::Semantic {
+                err: "Rollup must have at least one aggregation".to_string(),
+            });
+        }
+        let aggs = rollup
+            .aggs
+            .into_iter()
+            .map(|agg| {
+                RollupAgg::new(&agg.value).ok_or_else(|| QueryError::Semantic {
+                    err: format!(
+                        "{} is not a valid rollup aggregation, use like min, max, sum, count, mean, first, last",
+                        agg
+                    ),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(RollupPolicy::new(interval, keep, aggs))
+    }
+
     fn str_to_duration(&self, text: &str) -> Result<Duration> {
         Duration::new(text).ok_or_else(|| QueryError::Parser {
             source: ParserError::ParserError(format!("{} is not a valid duration", text)),
@@ -1607,7 +1753,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         } = stmt;
 
         let name = normalize_ident(name);
-        let options = sql_options_to_user_options(with_options)?;
+        let options = sql_options_to_user_options(with_options, &self.password_policy)?;
 
         let privileges = vec![Privilege::Global(GlobalPrivilege::User(None))];
 
@@ -1809,7 +1955,9 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
                 AlterUserAction::RenameTo(normalize_ident(new_name))
             }
             AlterUserOperation::Set(sql_option) => {
-                let user_options = sql_options_to_user_options(vec![sql_option])?;
+                let option_name = normalize_ident(sql_option.name.clone());
+                let user_options =
+                    sql_options_to_user_options(vec![sql_option], &self.password_policy)?;
                 if user_options.granted_admin().is_some() {
                     if user_desc.is_root_admin() {
                         return Err(QueryError::InvalidParam {
@@ -1820,8 +1968,24 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
                     // 修改admin参数需要系统管理权限
                     privileges = vec![Privilege::Global(GlobalPrivilege::System)];
                 }
+                // 修改密码过期策略需要系统管理权限, 否则用户可以自行延长密码有效期;
+                // 配置了密码复杂度时, 直接设置 hash_password 会绕过校验, 也需要系统管理权限
+                let sets_password_expiry = matches!(
+                    option_name.as_str(),
+                    "password_expire_at" | "must_change_password"
+                );
+                let bypasses_password_policy =
+                    option_name == "hash_password" && self.password_policy.has_complexity_rules();
+                if sets_password_expiry || bypasses_password_policy {
+                    privileges = vec![Privilege::Global(GlobalPrivilege::System)];
+                }
                 AlterUserAction::Set(user_options)
             }
+            AlterUserOperation::Unlock => {
+                // 解锁用户需要系统管理权限
+                privileges = vec![Privilege::Global(GlobalPrivilege::System)];
+                AlterUserAction::Unlock
+            }
         };
 
         let plan = Plan::DDL(DDLPlan::AlterUser(AlterUser {
@@ -1857,19 +2021,50 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
             return Err(err);
         }
 
-        let database_privileges = privileges
-            .into_iter()
-            .map(|ast::Privilege { action, database }| {
-                let database_privilege = match action {
-                    ast::Action::Read => DatabasePrivilege::Read,
-                    ast::Action::Write => DatabasePrivilege::Write,
-                    ast::Action::All => DatabasePrivilege::Full,
-                };
-                let database_name = normalize_ident(database);
-
-                (database_privilege, database_name)
-            })
-            .collect::<Vec<(DatabasePrivilege, String)>>();
+        let mut database_privileges = vec![];
+        let mut table_privileges = vec![];
+        for ast::Privilege { action, object } in privileges {
+            let privilege = match action {
+                ast::Action::Read => DatabasePrivilege::Read,
+                ast::Action::Write => DatabasePrivilege::Write,
+                ast::Action::All => DatabasePrivilege::Full,
+            };
+            match object {
+                ast::PrivilegeObject::Database(database) => {
+                    database_privileges.push((privilege, normalize_ident(database)));
+                }
+                ast::PrivilegeObject::Table(table, columns) => {
+                    let table = object_name_to_resolved_table(session, table)?;
+                    if table.tenant() != tenant_name {
+                        return Err(QueryError::Semantic {
+                            err: format!(
+                                "Can't grant the privilege on table {} of other tenant",
+                                table
+                            ),
+                        });
+                    }
+                    let columns = if columns.is_empty() {
+                        None
+                    } else {
+                        if privilege != DatabasePrivilege::Read {
+                            return Err(QueryError::Semantic {
+                                err: format!(
+                                    "Only read privilege can be granted on the columns of table {}",
+                                    table
+                                ),
+                            });
+                        }
+                        Some(columns.into_iter().map(normalize_ident).collect())
+                    };
+                    table_privileges.push(TablePrivilege::new(
+                        privilege,
+                        table.database(),
+                        table.table(),
+                        columns,
+                    ));
+                }
+            }
+        }
 
         let privileges = vec![Privilege::TenantObject(
             TenantObjectPrivilege::RoleFull,
@@ -1879,6 +2074,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         let plan = Plan::DDL(DDLPlan::GrantRevoke(GrantRevoke {
             is_grant,
             database_privileges,
+            table_privileges,
             tenant_name: tenant_name.to_string(),
             role_name,
         }));
@@ -1886,6 +2082,118 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         Ok(PlanWithPrivileges { plan, privileges })
     }
 
+    fn create_policy_to_plan(
+        &self,
+        stmt: ast::CreatePolicy,
+        session: &SessionCtx,
+    ) -> Result<PlanWithPrivileges> {
+        let ast::CreatePolicy {
+            name,
+            if_not_exists,
+            table,
+            role,
+            predicate,
+        } = stmt;
+
+        let name = normalize_ident(name);
+        let role_name = normalize_ident(role);
+        let tenant_name = session.tenant();
+        let tenant_id = *session.tenant_id();
+
+        // only the members of custom roles are restricted by the policies
+        if SystemTenantRole::try_from(role_name.as_str()).is_ok() {
+            return Err(QueryError::SystemRoleModification);
+        }
+
+        let table = object_name_to_resolved_table(session, table)?;
+        if table.tenant() != tenant_name {
+            return Err(QueryError::Semantic {
+                err: format!("Can't create the policy on table {} of other tenant", table),
+            });
+        }
+
+        // the predicate is planned on every scan of the table, check it in advance
+        let table_ref = TableReference::partial(table.database(), table.table());
+        let table_schema = self.get_tskv_schema(table_ref)?;
+        let df_schema = table_schema.to_arrow_schema().to_dfschema()?;
+        let expr =
+            self.df_planner
+                .sql_to_expr(predicate.clone(), &df_schema, &mut Default::default())?;
+        if expr.get_type(&df_schema)? != DataType::Boolean {
+            return Err(QueryError::Semantic {
+                err: format!(
+                    "The predicate of policy {} must be a boolean expression",
+                    name
+                ),
+            });
+        }
+        let mut columns = HashSet::new();
+        expr_to_columns(&expr, &mut columns)?;
+        for column in columns {
+            let is_tag = table_schema
+                .column(&column.name)
+                .is_some_and(|c| c.column_type.is_tag());
+            if !is_tag {
+                return Err(QueryError::Semantic {
+                    err: format!(
+                        "The predicate of policy {} can only reference the tags of table {}, found {}",
+                        name, table, column
+                    ),
+                });
+            }
+        }
+
+        let plan = Plan::DDL(DDLPlan::CreatePolicy(CreatePolicy {
+            tenant_name: tenant_name.to_string(),
+            role_name,
+            if_not_exists,
+            policy: RowPolicy::new(name, table.database(), table.table(), predicate.to_string()),
+        }));
+
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::TenantObject(
+                TenantObjectPrivilege::RoleFull,
+                Some(tenant_id),
+            )],
+        })
+    }
+
+    fn drop_policy_to_plan(
+        &self,
+        stmt: ast::DropPolicy,
+        session: &SessionCtx,
+    ) -> Result<PlanWithPrivileges> {
+        let ast::DropPolicy {
+            name,
+            if_exist,
+            table,
+        } = stmt;
+
+        let table = object_name_to_resolved_table(session, table)?;
+        if table.tenant() != session.tenant() {
+            return Err(QueryError::Semantic {
+                err: format!("Can't drop the policy on table {} of other tenant", table),
+            });
+        }
+
+        let plan = Plan::DDL(DDLPlan::DropPolicy(DropPolicy {
+            tenant_name: session.tenant().to_string(),
+            name: normalize_ident(name),
+            database_name: table.database().to_string(),
+            table_name: table.table().to_string(),
+            if_exist,
+        }));
+
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::TenantObject(
+                TenantObjectPrivilege::RoleFull,
+                Some(*session.tenant_id()),
+            )],
+        })
+    }
+
     fn show_queries_to_plan(&self, session: &SessionCtx) -> Result<PlanWithPrivileges> {
         // QUERY_SCHEMA: query_id, query_type, query_text, user_name, tenant_name, state, duration
         let projections = vec![0, 1, 2, 4, 6, 7, 8];
@@ -1911,6 +2219,26 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         })
     }
 
+    fn show_query_cache_to_plan(&self, session: &SessionCtx) -> Result<PlanWithPrivileges> {
+        let tenant_id = *session.tenant_id();
+        let privilege = Privilege::TenantObject(
+            TenantObjectPrivilege::Database(DatabasePrivilege::Read, None),
+            Some(tenant_id),
+        );
+        Ok(PlanWithPrivileges {
+            plan: Plan::SYSTEM(SYSPlan::ShowQueryCache),
+            privileges: vec![privilege],
+        })
+    }
+
+    fn clear_query_cache_to_plan(&self, session: &SessionCtx) -> Result<PlanWithPrivileges> {
+        let tenant_id = *session.tenant_id();
+        Ok(PlanWithPrivileges {
+            plan: Plan::SYSTEM(SYSPlan::ClearQueryCache),
+            privileges: vec![Privilege::Global(GlobalPrivilege::Tenant(Some(tenant_id)))],
+        })
+    }
+
     fn drop_vnode_to_plan(&self, stmt: ASTDropVnode) -> Result<PlanWithPrivileges> {
         let ASTDropVnode { vnode_id } = stmt;
 
@@ -1961,6 +2289,16 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         })
     }
 
+    fn repair_group_to_plan(&self, stmt: ASTRepairGroup) -> Result<PlanWithPrivileges> {
+        let ASTRepairGroup { replication_set_id } = stmt;
+
+        let plan = Plan::DDL(DDLPlan::RepairGroup(RepairGroup { replication_set_id }));
+        Ok(PlanWithPrivileges {
+            plan,
+            privileges: vec![Privilege::Global(GlobalPrivilege::System)],
+        })
+    }
+
     fn create_stream_table_to_plan(
         &self,
         stmt: Statement,
@@ -2035,6 +2373,69 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
         Ok(result?)
     }
 
+    async fn create_task_to_plan(
+        &self,
+        stmt: ast::CreateTask,
+        session: &SessionCtx,
+    ) -> Result<PlanWithPrivileges> {
+        let ast::CreateTask {
+            name,
+            if_not_exists,
+            schedule,
+            statement,
+        } = stmt;
+
+        let schedule = self.str_to_duration(&schedule)?;
+        if schedule.time_num == 0 || schedule.unit == DurationUnit::Inf {
+            return Err(QueryError::Semantic {
+                err: format!("Invalid schedule of task: {}", schedule),
+            });
+        }
+
+        // Plan the statement of the first run, the owner of the task must be able to run it.
+        let first_run = incremental_copy(statement.clone(), None, now_timestamp_nanos())?;
+        let PlanWithPrivileges { mut privileges, .. } =
+            self.copy_to_plan(first_run, session).await?;
+        privileges.push(Privilege::TenantObject(
+            TenantObjectPrivilege::System,
+            Some(*session.tenant_id()),
+        ));
+
+        let task = ScheduledTask {
+            tenant: session.tenant().to_string(),
+            database: session.default_database().to_string(),
+            name: normalize_ident(name),
+            owner: session.user().desc().name().to_string(),
+            schedule,
+            statement: statement.to_string(),
+            high_water_mark: None,
+            last_run_time: None,
+        };
+
+        Ok(PlanWithPrivileges {
+            plan: Plan::DDL(DDLPlan::CreateTask(CreateTask {
+                if_not_exists,
+                task,
+            })),
+            privileges,
+        })
+    }
+
+    fn show_tasks_to_plan(&self, session: &SessionCtx) -> Result<PlanWithPrivileges> {
+        let table_ref = TableReference::partial(INFORMATION_SCHEMA, INFORMATION_SCHEMA_TASKS);
+        let table_source = self.get_table_source(table_ref.clone())?;
+        let df_plan = LogicalPlanBuilder::scan(table_ref, table_source, None)?.build()?;
+
+        let privilege = Privilege::TenantObject(
+            TenantObjectPrivilege::Database(DatabasePrivilege::Read, None),
+            Some(*session.tenant_id()),
+        );
+        Ok(PlanWithPrivileges {
+            plan: Plan::Query(QueryPlan { df_plan }),
+            privileges: vec![privilege],
+        })
+    }
+
     async fn copy_to_plan(
         &self,
         stmt: ast::Copy,
@@ -2090,7 +2491,7 @@ impl<'a, S: ContextProviderExtension + Send + Sync + 'a> SqlPlanner<'a, S> {
 
                 let database_set = self.schema_provider.reset_access_databases();
                 let privileges =
-                    databases_privileges(DatabasePrivilege::Read, tenant_id, database_set);
+                    tables_privileges(DatabasePrivilege::Read, tenant_id, database_set);
                 Ok(PlanWithPrivileges { plan, privileges })
             }
         }
@@ -2597,7 +2998,7 @@ fn show_tag_value_projections(
     Ok(union_distinct)
 }
 
-fn check_privilege(user: &User, privileges: Vec<Privilege<Oid>>) -> Result<()> {
+pub(crate) fn check_privilege(user: &User, privileges: Vec<Privilege<Oid>>) -> Result<()> {
     let privileges_str = privileges
         .iter()
         .map(|e| format!("{:?}", e))
@@ -2615,7 +3016,30 @@ fn check_privilege(user: &User, privileges: Vec<Privilege<Oid>>) -> Result<()> {
     Ok(())
 }
 
-fn databases_privileges(
+/// The user whose password has expired or must be changed can only change its own password
+fn check_password_change(user: &User, plan: &Plan) -> Result<()> {
+    if !user.password_change_required() {
+        return Ok(());
+    }
+
+    if let Plan::DDL(DDLPlan::AlterUser(AlterUser {
+        user_name,
+        alter_user_action: AlterUserAction::Set(options),
+    })) = plan
+    {
+        if user_name == user.desc().name() && options.hash_password().is_some() {
+            return Ok(());
+        }
+    }
+
+    Err(QueryError::Auth {
+        source: AuthError::PasswordChangeRequired {
+            user_name: user.desc().name().to_string(),
+        },
+    })
+}
+
+pub(crate) fn tables_privileges(
     db_priv: DatabasePrivilege,
     tenant_id: Oid,
     databases: DatabaseSet,
@@ -2623,12 +3047,19 @@ fn databases_privileges(
     databases
         .dbs()
         .into_iter()
-        .cloned()
-        .map(|db| {
-            Privilege::TenantObject(
-                TenantObjectPrivilege::Database(db_priv.clone(), Some(db)),
-                Some(tenant_id),
-            )
+        .filter_map(|db| databases.table_set(db).map(|tables| (db, tables)))
+        .flat_map(|(db, tables)| {
+            tables.tables().map(|(table, columns)| {
+                Privilege::TenantObject(
+                    TenantObjectPrivilege::Table(TablePrivilege::new(
+                        db_priv.clone(),
+                        db,
+                        table,
+                        columns.clone(),
+                    )),
+                    Some(tenant_id),
+                )
+            })
         })
         .collect()
 }
@@ -2808,7 +3239,7 @@ mod tests {
     use meta::error::MetaError;
     use models::auth::user::{User, UserDesc, UserOptions};
     use models::codec::Encoding;
-    use models::schema::{ColumnType, Tenant};
+    use models::schema::{ColumnType, TableOptions, Tenant};
     use models::ValueType;
     use spi::query::session::SessionCtxFactory;
     use spi::service::protocol::ContextBuilder;
@@ -2824,8 +3255,13 @@ mod tests {
 
     #[async_trait::async_trait]
     impl ContextProviderExtension for MockContext {
-        async fn get_user(&self, _name: &str) -> std::result::Result<UserDesc, MetaError> {
-            todo!()
+        async fn get_user(&self, name: &str) -> std::result::Result<UserDesc, MetaError> {
+            Ok(UserDesc::new(
+                1_u128,
+                name.to_string(),
+                UserOptions::default(),
+                false,
+            ))
         }
 
         async fn get_tenant(&self, _name: &str) -> std::result::Result<Tenant, MetaError> {
@@ -3066,6 +3502,7 @@ mod tests {
                         .resolve_object("cnosdb", "default_schema")
                         .unwrap(),
                     if_not_exists: true,
+                    options: TableOptions::default(),
                 }
             );
         } else {
@@ -3087,7 +3524,7 @@ mod tests {
         if let Plan::DDL(DDLPlan::CreateDatabase(create)) = plan.plan {
             let ans = format!("{:?}", create);
             println!("{ans}");
-            let expected = r#"CreateDatabase { name: "test", if_not_exists: false, options: DatabaseOptions { ttl: Some(Duration { time_num: 10, unit: Day }), shard_num: Some(5), vnode_duration: Some(Duration { time_num: 3, unit: Day }), replica: Some(10), precision: Some(US), db_is_hidden: false } }"#;
+            let expected = r#"CreateDatabase { name: "test", if_not_exists: false, options: DatabaseOptions { ttl: Some(Duration { time_num: 10, unit: Day }), shard_num: Some(5), vnode_duration: Some(Duration { time_num: 3, unit: Day }), replica: Some(10), precision: Some(US), db_is_hidden: false, rollups: [], max_series: None, max_series_per_table: None } }"#;
             assert_eq!(ans, expected);
         } else {
             panic!("expected create table plan")
@@ -3176,6 +3613,7 @@ mod tests {
                     .resolve_object("cnosdb", "public")
                     .unwrap(),
                 if_not_exists: false,
+                options: TableOptions::default(),
             };
 
             assert_eq!(expected, create)
@@ -3184,6 +3622,56 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_alter_user_privileges() {
+        let complexity = PasswordPolicy {
+            min_length: 8,
+            ..Default::default()
+        };
+        for (sql, password_policy, requires_system) in [
+            (
+                "alter user u1 set comment='abc'",
+                PasswordPolicy::default(),
+                false,
+            ),
+            (
+                "alter user u1 set password='abc'",
+                PasswordPolicy::default(),
+                false,
+            ),
+            (
+                "alter user u1 set password_expire_at='2099-01-01 00:00:00'",
+                PasswordPolicy::default(),
+                true,
+            ),
+            (
+                "alter user u1 set must_change_password=false",
+                PasswordPolicy::default(),
+                true,
+            ),
+            (
+                "alter user u1 set hash_password='abc'",
+                PasswordPolicy::default(),
+                false,
+            ),
+            ("alter user u1 set hash_password='abc'", complexity, true),
+        ] {
+            let mut statements = ExtParser::parse_sql(sql).unwrap();
+            let test = MockContext {};
+            let planner = SqlPlanner::new(&test).with_password_policy(password_policy);
+            let plan = planner
+                .statement_to_plan(statements.pop_back().unwrap(), &session())
+                .await
+                .unwrap();
+            let expected = if requires_system {
+                Privilege::Global(GlobalPrivilege::System)
+            } else {
+                Privilege::Global(GlobalPrivilege::User(Some(1)))
+            };
+            assert_eq!(plan.privileges, vec![expected], "{sql}");
+        }
+    }
+
     #[tokio::test]
     async fn test_insert_select() {
         let sql = "insert test_tb(field_int, field_string)
@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use datafusion::arrow::ipc::reader::FileReader;
+use datafusion::arrow::ipc::writer::FileWriter;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::{DataFusionError, Result};
+use datafusion::physical_plan::expressions::NotExpr;
+use datafusion::physical_plan::PhysicalExpr;
+use parking_lot::{Mutex, RwLock};
+
+use super::{StateStore, StateStoreFactory};
+use crate::extension::utils::batch_filter;
+
+const STATE_DIR_NAME: &str = "state";
+const STATE_FILE_EXTENSION: &str = "state";
+const STATE_TMP_FILE_EXTENSION: &str = "tmp";
+
+/// A [`StateStoreFactory`] that checkpoints states into local files.
+///
+/// The states of a stream query are located in `{root}/state/{partition_id}_{operator_id}/`,
+/// and each commit produces a new file named by its version, so the states can be recovered
+/// after the system restarts.
+#[derive(Debug)]
+pub struct DiskStateStoreFactory {
+    root: PathBuf,
+    state_store_map: RwLock<HashMap<(String, usize, usize), Arc<DiskStateStore>>>,
+}
+
+impl DiskStateStoreFactory {
+    /// `root` should be a directory dedicated to a stream query,
+    /// since states are not distinguished by query_id on disk.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let mut root: PathBuf = root.into();
+        root.push(STATE_DIR_NAME);
+
+        Self {
+            root,
+            state_store_map: Default::default(),
+        }
+    }
+}
+
+impl StateStoreFactory for DiskStateStoreFactory {
+    type SS = DiskStateStore;
+
+    fn get_or_default(
+        &self,
+        query_id: String,
+        partition_id: usize,
+        operator_id: usize,
+    ) -> Result<Arc<Self::SS>> {
+        let key = (query_id, partition_id, operator_id);
+        if let Some(state_store) = self.state_store_map.read().get(&key) {
+            return Ok(state_store.clone());
+        }
+
+        let mut state_store_map = self.state_store_map.write();
+        if let Some(state_store) = state_store_map.get(&key) {
+            return Ok(state_store.clone());
+        }
+
+        let dir = self.root.join(format!("{partition_id}_{operator_id}"));
+        let state_store = Arc::new(DiskStateStore::try_new(dir)?);
+        state_store_map.insert(key, state_store.clone());
+
+        Ok(state_store)
+    }
+}
+
+/// A [`StateStore`] whose committed states are persisted into a versioned file.
+///
+/// Uncommitted states are kept in memory, they will be written into
+/// `{dir}/{version}.state` on [`StateStore::commit`], after that all files
+/// of older versions are removed.
+#[derive(Debug)]
+pub struct DiskStateStore {
+    dir: PathBuf,
+    version: AtomicI64,
+    committed: RwLock<Arc<Vec<RecordBatch>>>,
+    uncommitted: Mutex<Vec<RecordBatch>>,
+}
+
+impl DiskStateStore {
+    /// Open the state store in `dir`, and restore the states of the latest version.
+    pub fn try_new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir: PathBuf = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let versions = list_versions(&dir)?;
+        let (version, committed) = match versions.last() {
+            Some(version) => {
+                let path = state_file_path(&dir, *version);
+                trace::info!("Restore states of version {version} from {path:?}");
+                (*version, read_state_file(&path)?)
+            }
+            None => (0, vec![]),
+        };
+        remove_obsolete_files(&dir, version)?;
+
+        Ok(Self {
+            dir,
+            version: AtomicI64::new(version),
+            committed: RwLock::new(Arc::new(committed)),
+            uncommitted: Mutex::new(vec![]),
+        })
+    }
+
+    pub fn version(&self) -> i64 {
+        self.version.load(Ordering::Acquire)
+    }
+}
+
+impl StateStore for DiskStateStore {
+    fn put(&self, batch: RecordBatch) -> Result<()> {
+        trace::trace!("Write batch to DiskStateStore: {:?}", batch);
+        self.uncommitted.lock().push(batch);
+
+        Ok(())
+    }
+
+    fn expire(&self, predicate: Arc<dyn PhysicalExpr>) -> Result<Vec<RecordBatch>> {
+        trace::debug!("Remove batches match {} from DiskStateStore", predicate);
+        let remained: Arc<dyn PhysicalExpr> = Arc::new(NotExpr::new(predicate.clone()));
+
+        let mut uncommitted = self.uncommitted.lock();
+        let mut remained_data = Vec::with_capacity(uncommitted.len());
+        let mut expired_data = Vec::with_capacity(uncommitted.len());
+        for batch in uncommitted.iter() {
+            let remained_batch = batch_filter(batch, &remained)?;
+            if remained_batch.num_rows() > 0 {
+                remained_data.push(remained_batch);
+            }
+            let expired_batch = batch_filter(batch, &predicate)?;
+            if expired_batch.num_rows() > 0 {
+                expired_data.push(expired_batch);
+            }
+        }
+        *uncommitted = remained_data;
+
+        Ok(expired_data)
+    }
+
+    fn commit(&self) -> Result<i64> {
+        let batches = std::mem::take(&mut *self.uncommitted.lock());
+        let version = self.version() + 1;
+
+        let path = state_file_path(&self.dir, version);
+        trace::trace!("DiskStateStore commit version {version} to {path:?}");
+        write_state_file(&path, &batches)?;
+
+        *self.committed.write() = Arc::new(batches);
+        self.version.store(version, Ordering::Release);
+
+        // States of older versions are not needed anymore,
+        // the rows behind the watermark have been removed before commit.
+        remove_obsolete_files(&self.dir, version)?;
+
+        Ok(version)
+    }
+
+    fn state(&self) -> Result<Vec<RecordBatch>> {
+        trace::trace!("Read all states from DiskStateStore");
+
+        Ok(self.committed.read().as_ref().clone())
+    }
+}
+
+fn state_file_path(dir: &Path, version: i64) -> PathBuf {
+    dir.join(format!("{version}.{STATE_FILE_EXTENSION}"))
+}
+
+/// Returns versions of all complete state files in `dir`, in ascending order.
+fn list_versions(dir: &Path) -> Result<Vec<i64>> {
+    let mut versions = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(STATE_FILE_EXTENSION) {
+            continue;
+        }
+        if let Some(version) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse::<i64>().ok())
+        {
+            versions.push(version);
+        }
+    }
+    versions.sort_unstable();
+
+    Ok(versions)
+}
+
+/// Remove state files older than `version`, and temporary files left by an interrupted commit.
+fn remove_obsolete_files(dir: &Path, version: i64) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let obsolete = match path.extension().and_then(|e| e.to_str()) {
+            Some(STATE_TMP_FILE_EXTENSION) => true,
+            Some(STATE_FILE_EXTENSION) => path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<i64>().ok())
+                .map(|v| v < version)
+                .unwrap_or(false),
+            _ => false,
+        };
+        if obsolete {
+            trace::debug!("Remove obsolete state file {path:?}");
+            fs::remove_file(&path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write batches into a temporary file, then rename it to `path`,
+/// so that a state file is either complete or absent.
+fn write_state_file(path: &Path, batches: &[RecordBatch]) -> Result<()> {
+    let tmp_path = path.with_extension(STATE_TMP_FILE_EXTENSION);
+    {
+        let file = File::create(&tmp_path)?;
+        // An empty file means there is no state.
+        if let Some(first) = batches.first() {
+            let mut writer = FileWriter::try_new(BufWriter::new(file), first.schema().as_ref())?;
+            for batch in batches {
+                writer.write(batch)?;
+            }
+            writer.finish()?;
+            writer.into_inner()?.get_ref().sync_all()?;
+        } else {
+            file.sync_all()?;
+        }
+    }
+    fs::rename(&tmp_path, path)?;
+
+    Ok(())
+}
+
+fn read_state_file(path: &Path) -> Result<Vec<RecordBatch>> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        return Ok(vec![]);
+    }
+
+    let reader = FileReader::try_new(BufReader::new(file), None)?;
+    reader
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|err| {
+            DataFusionError::Execution(format!("Failed to read state file {path:?}: {err}"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::Int64Array;
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::arrow::record_batch::RecordBatch;
+    use datafusion::logical_expr::Operator;
+    use datafusion::physical_plan::expressions::{binary, col, lit};
+
+    use super::{list_versions, DiskStateStore, DiskStateStoreFactory};
+    use crate::stream::state_store::{StateStore, StateStoreFactory};
+
+    fn batch(values: Vec<i64>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("t", DataType::Int64, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(values))]).unwrap()
+    }
+
+    #[test]
+    fn test_commit_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let factory = DiskStateStoreFactory::new(dir.path());
+
+        let store = factory.get_or_default("q".to_string(), 0, 0).unwrap();
+        store.put(batch(vec![1, 2, 3])).unwrap();
+        store.put(batch(vec![4, 5])).unwrap();
+        assert_eq!(store.commit().unwrap(), 1);
+        store.put(batch(vec![6])).unwrap();
+        assert_eq!(store.commit().unwrap(), 2);
+
+        let state_dir = dir.path().join("state").join("0_0");
+        assert_eq!(list_versions(&state_dir).unwrap(), vec![2]);
+
+        // Restart
+        let restored = DiskStateStore::try_new(&state_dir).unwrap();
+        assert_eq!(restored.version(), 2);
+        assert_eq!(restored.state().unwrap(), vec![batch(vec![6])]);
+    }
+
+    #[test]
+    fn test_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DiskStateStore::try_new(dir.path()).unwrap();
+        let b = batch(vec![1, 2, 3, 4]);
+        let schema = b.schema();
+        store.put(b).unwrap();
+
+        let predicate = binary(
+            col("t", &schema).unwrap(),
+            Operator::LtEq,
+            lit(2_i64),
+            &schema,
+        )
+        .unwrap();
+        let expired = store.expire(predicate).unwrap();
+        assert_eq!(expired, vec![batch(vec![1, 2])]);
+
+        store.commit().unwrap();
+        assert_eq!(store.state().unwrap(), vec![batch(vec![3, 4])]);
+
+        let restored = DiskStateStore::try_new(dir.path()).unwrap();
+        assert_eq!(restored.state().unwrap(), vec![batch(vec![3, 4])]);
+    }
+}
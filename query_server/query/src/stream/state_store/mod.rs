@@ -1,16 +1,23 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::common::Result;
 use datafusion::physical_plan::PhysicalExpr;
 
+use self::disk::DiskStateStoreFactory;
 use self::memory::MemoryStateStoreFactory;
+pub mod disk;
 pub mod memory;
 
 pub fn create_memory_state_store_factory() -> Arc<MemoryStateStoreFactory> {
     Arc::new(MemoryStateStoreFactory::default())
 }
 
+pub fn create_disk_state_store_factory(root: impl Into<PathBuf>) -> Arc<DiskStateStoreFactory> {
+    Arc::new(DiskStateStoreFactory::new(root))
+}
+
 pub trait StateStoreFactory {
     type SS: StateStore;
 
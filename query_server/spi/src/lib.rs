@@ -532,6 +532,44 @@ pub enum QueryError {
     ForbiddenCreateSystemRole {
         role: String,
     },
+
+    #[snafu(display("Invalid PromQL query, error: {}", reason))]
+    #[error_code(code = 78)]
+    InvalidPromQL {
+        reason: String,
+    },
+
+    #[snafu(display("Task {} already exists", name))]
+    #[error_code(code = 79)]
+    TaskAlreadyExists {
+        name: String,
+    },
+
+    #[snafu(display("Task {} not found", name))]
+    #[error_code(code = 80)]
+    TaskNotFound {
+        name: String,
+    },
+
+    #[snafu(display("Invalid InfluxQL query, error: {}", reason))]
+    #[error_code(code = 81)]
+    InvalidInfluxQL {
+        reason: String,
+    },
+
+    #[snafu(display("Policy {} on table {} already exists", name, table))]
+    #[error_code(code = 82)]
+    PolicyAlreadyExists {
+        name: String,
+        table: String,
+    },
+
+    #[snafu(display("Policy {} on table {} not found", name, table))]
+    #[error_code(code = 83)]
+    PolicyNotFound {
+        name: String,
+        table: String,
+    },
 }
 
 impl From<ParserError> for QueryError {
@@ -38,6 +38,10 @@ pub enum ExtStatement {
 
     GrantRevoke(GrantRevoke),
 
+    // row-level security policy
+    CreatePolicy(CreatePolicy),
+    DropPolicy(DropPolicy),
+
     DescribeTable(DescribeTable),
     DescribeDatabase(DescribeDatabase),
     ShowDatabases(),
@@ -48,6 +52,8 @@ pub enum ExtStatement {
 
     // system cmd
     ShowQueries,
+    ShowQueryCache,
+    ClearQueryCache,
     AlterDatabase(AlterDatabase),
     AlterTable(AlterTable),
     AlterTenant(AlterTenant),
@@ -59,10 +65,42 @@ pub enum ExtStatement {
     MoveVnode(MoveVnode),
     CompactVnode(CompactVnode),
     ChecksumGroup(ChecksumGroup),
+    RepairGroup(RepairGroup),
 
     // recover cmd
     RecoverTenant(RecoverTenant),
     RecoverDatabase(RecoverDatabase),
+
+    // scheduled task cmd
+    CreateTask(CreateTask),
+    ShowTasks,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateTask {
+    pub name: Ident,
+    pub if_not_exists: bool,
+    pub schedule: String,
+    /// Only `COPY INTO <location>` is supported
+    pub statement: Copy,
+}
+
+/// `CREATE POLICY [IF NOT EXISTS] <name> ON <table> FOR ROLE <role> USING (<expr>)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatePolicy {
+    pub name: Ident,
+    pub if_not_exists: bool,
+    pub table: ObjectName,
+    pub role: Ident,
+    pub predicate: Expr,
+}
+
+/// `DROP POLICY [IF EXISTS] <name> ON <table>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropPolicy {
+    pub name: Ident,
+    pub if_exist: bool,
+    pub table: ObjectName,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -70,6 +108,11 @@ pub struct ChecksumGroup {
     pub replication_set_id: ReplicationSetId,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairGroup {
+    pub replication_set_id: ReplicationSetId,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CompactVnode {
     pub vnode_ids: Vec<VnodeId>,
@@ -99,6 +142,55 @@ pub struct Copy {
     pub copy_options: Vec<SqlOption>,
 }
 
+impl fmt::Display for Copy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.copy_target {
+            CopyTarget::IntoTable(CopyIntoTable {
+                location,
+                table_name,
+                columns,
+            }) => {
+                write!(f, "COPY INTO {}", table_name)?;
+                if !columns.is_empty() {
+                    write!(f, " ({})", comma_separated(columns))?;
+                }
+                write!(
+                    f,
+                    " FROM {}",
+                    Value::SingleQuotedString(location.path.clone())
+                )?;
+                write_options(f, "CONNECTION", &location.connection_options)?;
+            }
+            CopyTarget::IntoLocation(CopyIntoLocation { from, location }) => {
+                write!(
+                    f,
+                    "COPY INTO {} FROM {}",
+                    Value::SingleQuotedString(location.path.clone()),
+                    from
+                )?;
+                write_options(f, "CONNECTION", &location.connection_options)?;
+            }
+        }
+        write_options(f, "FILE_FORMAT", &self.file_format_options)?;
+        write_options(f, "COPY_OPTIONS", &self.copy_options)
+    }
+}
+
+fn comma_separated<T: fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn write_options(f: &mut fmt::Formatter<'_>, name: &str, options: &[SqlOption]) -> fmt::Result {
+    if options.is_empty() {
+        return Ok(());
+    }
+    write!(f, " {} = ({})", name, comma_separated(options))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CopyTarget {
     IntoTable(CopyIntoTable),
@@ -155,12 +247,32 @@ pub enum AlterTableAction {
         old_column_name: Ident,
         new_column_name: Ident,
     },
+    /// `SET <table_option> ...`
+    SetOptions {
+        options: Vec<TableOption>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AlterDatabase {
     pub name: Ident,
-    pub options: DatabaseOptions,
+    pub operation: AlterDatabaseOperation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlterDatabaseOperation {
+    Set(DatabaseOptions),
+    AddRollup(RollupOptions),
+    // String: rollup interval
+    DropRollup(String),
+}
+
+/// ADD ROLLUP <interval> KEEP <duration> AGG (<agg>, ...)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollupOptions {
+    pub interval: String,
+    pub keep: String,
+    pub aggs: Vec<Ident>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -247,8 +359,16 @@ pub struct AlterUser {
 pub enum AlterUserOperation {
     RenameTo(Ident),
     Set(SqlOption),
+    Unlock,
 }
 
+/// `GRANT|REVOKE <privilege> ON {DATABASE <db> | TABLE <table> [(<column>, ...)]} {TO|FROM} [ROLE] <role>`
+///
+/// A role inheriting member reads all databases until READ on a table is granted to it, then it
+/// only reads the granted databases and tables; WRITE and ALL on tables keep the inherited read.
+/// READ on some columns of a table is rejected if the role already reads all columns of the
+/// table (by a database privilege, WRITE or ALL on the table, or inheriting owner), and
+/// privileges reading all columns are rejected while READ on some columns is granted.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GrantRevoke {
     pub is_grant: bool,
@@ -259,7 +379,14 @@ pub struct GrantRevoke {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Privilege {
     pub action: Action,
-    pub database: Ident,
+    pub object: PrivilegeObject,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrivilegeObject {
+    Database(Ident),
+    /// table name, columns (all columns if empty)
+    Table(ObjectName, Vec<Ident>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -295,6 +422,16 @@ pub struct CreateTable {
     pub name: ObjectName,
     pub if_not_exists: bool,
     pub columns: Vec<ColumnOption>,
+    pub options: Vec<TableOption>,
+}
+
+/// Option of `CREATE TABLE ... WITH <option>` and `ALTER TABLE ... SET <option>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableOption {
+    /// `BLOOM_FILTER(<column_name>, ...)`
+    BloomFilter(Vec<Ident>),
+    /// `DEDUP POLICY { LAST_WRITE_WINS | KEEP_FIRST | MERGE }`
+    DedupPolicy(Ident),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -337,6 +474,9 @@ pub struct DatabaseOptions {
     pub replica: Option<u64>,
     // timestamp precision
     pub precision: Option<String>,
+
+    pub max_series: Option<u64>,
+    pub max_series_per_table: Option<u64>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -463,6 +603,21 @@ pub fn parse_string_value(value: Value) -> std::result::Result<String, ParserErr
     }
 }
 
+pub fn parse_u64_value(value: Value) -> std::result::Result<u64, ParserError> {
+    match value {
+        Value::Number(ref s, _) => s.parse::<u64>().map_err(|_| {
+            ParserError::ParserError(format!(
+                "expected unsigned integer value, but found : {}",
+                value
+            ))
+        }),
+        _ => Err(ParserError::ParserError(format!(
+            "expected unsigned integer value, but found : {}",
+            value
+        ))),
+    }
+}
+
 pub fn parse_char_value(value: Value) -> std::result::Result<char, ParserError> {
     let token = parse_string_value(value)?;
     match token.len() {
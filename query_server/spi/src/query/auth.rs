@@ -13,5 +13,8 @@ pub type AccessControlRef = Arc<dyn AccessControl + Send + Sync>;
 pub trait AccessControl {
     async fn access_check(&self, user_info: &UserInfo, tenant_name: &str) -> Result<User>;
 
+    /// Load the user whose identity has already been proven by a verified bearer token.
+    async fn token_access_check(&self, user_name: &str, tenant_name: &str) -> Result<User>;
+
     async fn tenant_id(&self, tenant_name: &str) -> Result<Oid>;
 }
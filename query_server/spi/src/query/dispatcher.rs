@@ -2,6 +2,8 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
+use datafusion::common::OwnedTableReference;
+use datafusion::logical_expr::TableSource;
 use models::auth::user::User;
 use models::oid::{Identifier, Oid};
 use serde::{Deserialize, Serialize};
@@ -36,6 +38,14 @@ pub trait QueryDispatcher: Send + Sync {
         query_state_machine: Arc<QueryStateMachine>,
     ) -> Result<Option<Plan>>;
 
+    /// Returns the source of `table` read by the logical plans built without sql,
+    /// the user of the query must be able to read the table.
+    async fn get_table_source(
+        &self,
+        table: OwnedTableReference,
+        query_state_machine: Arc<QueryStateMachine>,
+    ) -> Result<Arc<dyn TableSource>>;
+
     async fn execute_logical_plan(
         &self,
         logical_plan: Plan,
@@ -1,7 +1,8 @@
 use std::fmt::Display;
 use std::pin::Pin;
+use std::str::FromStr;
 use std::sync::atomic::{AtomicPtr, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 
@@ -12,6 +13,7 @@ use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::physical_plan::SendableRecordBatchStream;
 use futures::{Stream, StreamExt, TryStreamExt};
 use meta::model::MetaRef;
+use serde::Serialize;
 use trace::SpanContext;
 
 use super::dispatcher::{QueryInfo, QueryStatus};
@@ -37,6 +39,53 @@ impl Display for QueryType {
     }
 }
 
+/// The classes of the statements, e.g. to choose the audited statements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementType {
+    /// Create, alter or drop the databases, tables, streams, tasks ...
+    Ddl,
+    /// Manage the users, roles, privileges and row policies
+    Dcl,
+    /// Insert, update, delete or copy the data
+    Dml,
+    /// Select, show, describe or explain
+    Query,
+}
+
+impl StatementType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ddl => "ddl",
+            Self::Dcl => "dcl",
+            Self::Dml => "dml",
+            Self::Query => "query",
+        }
+    }
+}
+
+impl Display for StatementType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for StatementType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ddl" => Ok(Self::Ddl),
+            "dcl" => Ok(Self::Dcl),
+            "dml" => Ok(Self::Dml),
+            "query" => Ok(Self::Query),
+            _ => Err(format!(
+                "unknown statement type '{s}', expected one of ddl, dcl, dml, query"
+            )),
+        }
+    }
+}
+
 #[async_trait]
 pub trait QueryExecution: Send + Sync {
     fn query_type(&self) -> QueryType {
@@ -192,6 +241,8 @@ pub struct QueryStateMachine {
 
     state: AtomicPtr<QueryState>,
     start: Instant,
+    /// Set when the statement is parsed
+    statement_type: OnceLock<StatementType>,
 }
 
 impl QueryStateMachine {
@@ -237,9 +288,19 @@ impl QueryStateMachine {
             coord,
             state: AtomicPtr::new(Box::into_raw(Box::new(QueryState::ACCEPTING))),
             start: Instant::now(),
+            statement_type: OnceLock::new(),
         }
     }
 
+    /// Only the first call takes effect.
+    pub fn set_statement_type(&self, statement_type: StatementType) {
+        let _ = self.statement_type.set(statement_type);
+    }
+
+    pub fn statement_type(&self) -> Option<StatementType> {
+        self.statement_type.get().copied()
+    }
+
     pub fn begin_analyze(&self) {
         // TODO record time
         self.translate_to(Box::new(QueryState::RUNNING(RUNNING::ANALYZING)));
@@ -304,6 +365,7 @@ impl QueryStateMachine {
             coord: self.coord.clone(),
             state,
             start: self.start,
+            statement_type: self.statement_type.clone(),
         }
     }
 }
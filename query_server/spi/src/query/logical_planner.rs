@@ -19,19 +19,23 @@ use datafusion::prelude::{col, Expr};
 use datafusion::sql::sqlparser::ast::{Ident, ObjectName, SqlOption, Value};
 use datafusion::sql::sqlparser::parser::ParserError;
 use lazy_static::lazy_static;
-use models::auth::privilege::{DatabasePrivilege, GlobalPrivilege, Privilege};
-use models::auth::role::{SystemTenantRole, TenantRoleIdentifier};
+use models::auth::privilege::{DatabasePrivilege, GlobalPrivilege, Privilege, TablePrivilege};
+use models::auth::role::{RowPolicy, SystemTenantRole, TenantRoleIdentifier};
 use models::auth::user::{UserOptions, UserOptionsBuilder};
+use models::auth::PasswordPolicy;
 use models::meta_data::{NodeId, ReplicationSetId, VnodeId};
 use models::object_reference::ResolvedTable;
 use models::oid::{Identifier, Oid};
 use models::schema::{
-    DatabaseOptions, Duration, TableColumn, Tenant, TenantOptions, TenantOptionsBuilder, Watermark,
+    DatabaseOptions, Duration, RollupPolicy, ScheduledTask, TableColumn, TableOptions, Tenant,
+    TenantOptions, TenantOptionsBuilder, Watermark,
 };
 use snafu::ResultExt;
 use tempfile::NamedTempFile;
 
-use super::ast::{parse_bool_value, parse_char_value, parse_string_value, ExtStatement};
+use super::ast::{
+    parse_bool_value, parse_char_value, parse_string_value, parse_u64_value, ExtStatement,
+};
 use super::datasource::azure::{AzblobStorageConfig, AzblobStorageConfigBuilder};
 use super::datasource::gcs::{
     GcsStorageConfig, ServiceAccountCredentials, ServiceAccountCredentialsBuilder,
@@ -46,6 +50,7 @@ use crate::{ParserSnafu, QueryError, Result};
 pub const TENANT_OPTION_LIMITER: &str = "_limiter";
 pub const TENANT_OPTION_COMMENT: &str = "comment";
 pub const TENANT_OPTION_DROP_AFTER: &str = "drop_after";
+pub const TENANT_OPTION_QUERY_CACHE_MAX_MEMORY: &str = "query_cache_max_memory";
 
 lazy_static! {
     static ref TABLE_WRITE_UDF: Arc<ScalarUDF> = Arc::new(ScalarUDF::new(
@@ -151,9 +156,17 @@ pub enum DDLPlan {
 
     ChecksumGroup(ChecksumGroup),
 
+    RepairGroup(RepairGroup),
+
     RecoverDatabase(RecoverDatabase),
 
     RecoverTenant(RecoverTenant),
+
+    CreateTask(CreateTask),
+
+    CreatePolicy(CreatePolicy),
+
+    DropPolicy(DropPolicy),
 }
 
 impl DDLPlan {
@@ -162,6 +175,7 @@ impl DDLPlan {
             DDLPlan::ChecksumGroup(_) => Arc::new(Schema::new(vec![
                 Field::new("VNODE_ID", DataType::UInt32, false),
                 Field::new("CHECK_SUM", DataType::Utf8, false),
+                Field::new("CONSISTENT", DataType::Boolean, false),
             ])),
             _ => Arc::new(Schema::empty()),
         }
@@ -173,6 +187,11 @@ pub struct ChecksumGroup {
     pub replication_set_id: ReplicationSetId,
 }
 
+#[derive(Debug, Clone)]
+pub struct RepairGroup {
+    pub replication_set_id: ReplicationSetId,
+}
+
 #[derive(Debug, Clone)]
 pub struct CompactVnode {
     pub vnode_ids: Vec<VnodeId>,
@@ -216,11 +235,25 @@ pub struct DeleteFromTable {
 #[derive(Debug, Clone)]
 pub enum SYSPlan {
     KillQuery(QueryId),
+    /// Show the query result cache of the tenant on this node
+    ShowQueryCache,
+    /// Clear the query result cache of the tenant on this node
+    ClearQueryCache,
 }
 
 impl SYSPlan {
     pub fn schema(&self) -> SchemaRef {
-        Arc::new(Schema::empty())
+        match self {
+            SYSPlan::ShowQueryCache => Arc::new(Schema::new(vec![
+                Field::new("tenant_name", DataType::Utf8, false),
+                Field::new("entries", DataType::UInt64, false),
+                Field::new("memory_bytes", DataType::UInt64, false),
+                Field::new("max_memory_bytes", DataType::UInt64, false),
+                Field::new("hits", DataType::UInt64, false),
+                Field::new("misses", DataType::UInt64, false),
+            ])),
+            _ => Arc::new(Schema::empty()),
+        }
     }
 }
 
@@ -253,6 +286,13 @@ pub struct DropTenantObject {
 pub enum TenantObjectType {
     Role,
     Database,
+    Task,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateTask {
+    pub if_not_exists: bool,
+    pub task: ScheduledTask,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -304,6 +344,8 @@ pub struct CreateTable {
     pub name: ResolvedTable,
     /// Option to not error if table already exists
     pub if_not_exists: bool,
+    /// The table options
+    pub options: TableOptions,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -354,10 +396,14 @@ pub fn unset_option_to_alter_tenant_action(
             tenant_options_builder.unset_drop_after();
             Privilege::Global(GlobalPrivilege::Tenant(Some(tenant_id)))
         }
+        TENANT_OPTION_QUERY_CACHE_MAX_MEMORY => {
+            tenant_options_builder.unset_query_cache_max_memory();
+            Privilege::Global(GlobalPrivilege::System)
+        }
         _ => {
             return Err(QueryError::Parser {
                 source: ParserError::ParserError(format!(
-                "Expected option [{TENANT_OPTION_COMMENT}], [{TENANT_OPTION_LIMITER}], [{TENANT_OPTION_DROP_AFTER}] found [{}]",
+                "Expected option [{TENANT_OPTION_COMMENT}], [{TENANT_OPTION_LIMITER}], [{TENANT_OPTION_DROP_AFTER}], [{TENANT_OPTION_QUERY_CACHE_MAX_MEMORY}] found [{}]",
                 ident
             )),
             })
@@ -400,10 +446,15 @@ pub fn sql_option_to_alter_tenant_action(
             tenant_options_builder.drop_after(drop_after);
             Privilege::Global(GlobalPrivilege::Tenant(Some(tenant_id)))
         }
+        TENANT_OPTION_QUERY_CACHE_MAX_MEMORY => {
+            let max_memory = parse_u64_value(value).context(ParserSnafu)?;
+            tenant_options_builder.query_cache_max_memory(max_memory);
+            Privilege::Global(GlobalPrivilege::System)
+        }
         _ => {
             return Err(QueryError::Parser {
                 source: ParserError::ParserError(format!(
-                "Expected option [{TENANT_OPTION_COMMENT}], [{TENANT_OPTION_LIMITER}], [{TENANT_OPTION_DROP_AFTER}] found [{}]",
+                "Expected option [{TENANT_OPTION_COMMENT}], [{TENANT_OPTION_LIMITER}], [{TENANT_OPTION_DROP_AFTER}], [{TENANT_OPTION_QUERY_CACHE_MAX_MEMORY}] found [{}]",
                 name
             )),
             })
@@ -437,10 +488,13 @@ pub fn sql_options_to_tenant_options(options: Vec<SqlOption>) -> Result<TenantOp
                 })?;
                 builder.drop_after(drop_after);
             }
+            TENANT_OPTION_QUERY_CACHE_MAX_MEMORY => {
+                builder.query_cache_max_memory(parse_u64_value(value).context(ParserSnafu)?);
+            }
             _ => {
                 return Err(QueryError::Parser {
                     source: ParserError::ParserError(format!(
-                        "Expected option [{TENANT_OPTION_COMMENT}], [{TENANT_OPTION_LIMITER}], [{TENANT_OPTION_DROP_AFTER}] found [{}]",
+                        "Expected option [{TENANT_OPTION_COMMENT}], [{TENANT_OPTION_LIMITER}], [{TENANT_OPTION_DROP_AFTER}], [{TENANT_OPTION_QUERY_CACHE_MAX_MEMORY}] found [{}]",
                         name
                     )),
                 })
@@ -462,15 +516,21 @@ pub struct CreateUser {
 
 pub fn sql_options_to_user_options(
     with_options: Vec<SqlOption>,
-) -> std::result::Result<UserOptions, ParserError> {
+    password_policy: &PasswordPolicy,
+) -> Result<UserOptions> {
     let mut builder = UserOptionsBuilder::default();
+    let mut password_set = false;
+    let mut password_expire_at_set = false;
 
     for SqlOption { ref name, value } in with_options {
         match normalize_ident(name).as_str() {
             "password" => {
+                let password = parse_string_value(value)?;
+                password_policy.validate(&password)?;
                 builder
-                    .password(parse_string_value(value)?)
+                    .password(password)
                     .map_err(|e| ParserError::ParserError(e.to_string()))?;
+                password_set = true;
             }
             "must_change_password" => {
                 builder.must_change_password(parse_bool_value(value)?);
@@ -486,19 +546,50 @@ pub fn sql_options_to_user_options(
             }
             "hash_password" => {
                 builder.hash_password(parse_string_value(value)?);
+                password_set = true;
+            }
+            "password_expire_at" => {
+                builder.password_expire_at(parse_timestamp_value(value)?);
+                password_expire_at_set = true;
             }
             _ => {
                 return Err(ParserError::ParserError(format!(
-                "Expected option [password | rsa_public_key | comment | granted_admin], found [{}]",
+                "Expected option [password | rsa_public_key | comment | granted_admin | password_expire_at], found [{}]",
                 name
-            )))
+            ))
+            .into())
             }
         }
     }
 
+    if password_set && !password_expire_at_set {
+        if let Some(expire_at) =
+            password_policy.password_expire_at(models::utils::now_timestamp_nanos())
+        {
+            builder.password_expire_at(expire_at);
+        }
+    }
+
     builder
         .build()
-        .map_err(|e| ParserError::ParserError(e.to_string()))
+        .map_err(|e| ParserError::ParserError(e.to_string()).into())
+}
+
+/// Parses 'yyyy-mm-dd hh:mm:ss' in UTC or a RFC3339 timestamp to nanoseconds
+fn parse_timestamp_value(value: Value) -> std::result::Result<i64, ParserError> {
+    let s = parse_string_value(value)?;
+    let nanos = match chrono::DateTime::parse_from_rfc3339(&s) {
+        Ok(datetime) => datetime.timestamp_nanos_opt(),
+        Err(_) => chrono::NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S")
+            .map_err(|_| {
+                ParserError::ParserError(format!(
+                    "expected timestamp value like '2024-01-01 00:00:00', but found : '{s}'"
+                ))
+            })?
+            .timestamp_nanos_opt(),
+    };
+
+    nanos.ok_or_else(|| ParserError::ParserError(format!("timestamp '{s}' is out of range")))
 }
 
 #[derive(Debug, Clone)]
@@ -509,11 +600,29 @@ pub struct CreateRole {
     pub inherit_tenant_role: SystemTenantRole,
 }
 
+#[derive(Debug, Clone)]
+pub struct CreatePolicy {
+    pub tenant_name: String,
+    pub role_name: String,
+    pub if_not_exists: bool,
+    pub policy: RowPolicy,
+}
+
+#[derive(Debug, Clone)]
+pub struct DropPolicy {
+    pub tenant_name: String,
+    pub name: String,
+    pub database_name: String,
+    pub table_name: String,
+    pub if_exist: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct GrantRevoke {
     pub is_grant: bool,
     // privilege, db name
     pub database_privileges: Vec<(DatabasePrivilege, String)>,
+    pub table_privileges: Vec<TablePrivilege>,
     pub tenant_name: String,
     pub role_name: String,
 }
@@ -528,6 +637,7 @@ pub struct AlterUser {
 pub enum AlterUserAction {
     RenameTo(String),
     Set(UserOptions),
+    Unlock,
 }
 
 #[derive(Debug, Clone)]
@@ -559,7 +669,15 @@ pub struct AlterTenantSetUser {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AlterDatabase {
     pub database_name: String,
-    pub database_options: DatabaseOptions,
+    pub operation: AlterDatabaseOperation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlterDatabaseOperation {
+    Set(DatabaseOptions),
+    AddRollup(RollupPolicy),
+    // Duration: interval of the rollup
+    DropRollup(Duration),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -584,6 +702,9 @@ pub enum AlterTableAction {
         old_column_name: String,
         new_column_name: String,
     },
+    SetOptions {
+        options: TableOptions,
+    },
 }
 
 #[async_trait]
@@ -4,15 +4,18 @@ use async_trait::async_trait;
 use datafusion::arrow::array::{Float32Array, Float64Array};
 use datafusion::arrow::datatypes::{DataType, Field, Schema};
 use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::OwnedTableReference;
+use datafusion::logical_expr::TableSource;
 use models::auth::role::UserRole;
 use models::auth::user::{User, UserDesc, UserInfo, UserOptionsBuilder};
+use models::auth::AuthError;
 use trace::SpanContext;
 
 use crate::query::execution::{Output, QueryStateMachine, QueryStateMachineRef};
 use crate::query::logical_planner::Plan;
 use crate::query::recordbatch::RecordBatchStreamWrapper;
 use crate::service::protocol::{Query, QueryHandle, QueryId};
-use crate::Result;
+use crate::{QueryError, Result};
 
 pub type DBMSRef = Arc<dyn DatabaseManagerSystem + Send + Sync>;
 
@@ -20,6 +23,7 @@ pub type DBMSRef = Arc<dyn DatabaseManagerSystem + Send + Sync>;
 pub trait DatabaseManagerSystem {
     async fn start(&self) -> Result<()>;
     async fn authenticate(&self, user_info: &UserInfo, tenant_name: &str) -> Result<User>;
+    async fn authenticate_token(&self, token: &str, tenant_name: &str) -> Result<User>;
     async fn execute(
         &self,
         query: &Query,
@@ -34,6 +38,11 @@ pub trait DatabaseManagerSystem {
         &self,
         query_state_machine: QueryStateMachineRef,
     ) -> Result<Option<Plan>>;
+    async fn get_table_source(
+        &self,
+        table: OwnedTableReference,
+        query_state_machine: QueryStateMachineRef,
+    ) -> Result<Arc<dyn TableSource>>;
     async fn execute_logical_plan(
         &self,
         logical_plan: Plan,
@@ -63,6 +72,13 @@ impl DatabaseManagerSystem for DatabaseManagerSystemMock {
         Ok(mock_user)
     }
 
+    async fn authenticate_token(&self, _token: &str, _tenant_name: &str) -> Result<User> {
+        Err(AuthError::InvalidToken {
+            reason: "bearer token authentication is not supported".to_string(),
+        }
+        .into())
+    }
+
     async fn execute(
         &self,
         query: &Query,
@@ -116,6 +132,16 @@ impl DatabaseManagerSystem for DatabaseManagerSystemMock {
         Ok(None)
     }
 
+    async fn get_table_source(
+        &self,
+        table: OwnedTableReference,
+        _query_state_machine: QueryStateMachineRef,
+    ) -> Result<Arc<dyn TableSource>> {
+        Err(QueryError::NotImplemented {
+            err: format!("DatabaseManagerSystemMock::get_table_source({})", table),
+        })
+    }
+
     async fn execute_logical_plan(
         &self,
         _logical_plan: Plan,
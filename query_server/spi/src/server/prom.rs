@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use futures::stream::BoxStream;
 use protocol_parser::Line;
 use protos::prompb::remote::WriteRequest;
 use trace::SpanContext;
@@ -11,6 +12,31 @@ use crate::Result;
 
 pub type PromRemoteServerRef = Arc<dyn PromRemoteServer + Send + Sync>;
 
+/// Response of prometheus remote read, the type is negotiated by `accepted_response_types` of the request.
+pub enum PromReadResponse {
+    /// Snappy compressed protobuf `ReadResponse`.
+    Samples(Vec<u8>),
+    /// Frames of protobuf `ChunkedReadResponse`, which are produced while the series are read
+    /// and should be streamed to the client.
+    StreamedXorChunks(BoxStream<'static, Result<Vec<u8>>>),
+}
+
+/// A PromQL query, timestamps and step are in milliseconds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromQueryRequest {
+    pub query: String,
+    pub start: i64,
+    pub end: i64,
+    /// Width between steps of a range query, 0 for an instant query.
+    pub step: i64,
+}
+
+impl PromQueryRequest {
+    pub fn is_instant(&self) -> bool {
+        self.step == 0
+    }
+}
+
 #[async_trait]
 pub trait PromRemoteServer {
     async fn remote_read(
@@ -18,6 +44,14 @@ pub trait PromRemoteServer {
         ctx: &Context,
         req: Bytes,
         span_ctx: Option<&SpanContext>,
+    ) -> Result<PromReadResponse>;
+
+    /// Evaluate a PromQL query, returns the JSON body of a successful response.
+    async fn query(
+        &self,
+        ctx: &Context,
+        req: PromQueryRequest,
+        span_ctx: Option<&SpanContext>,
     ) -> Result<Vec<u8>>;
 
     fn remote_write(&self, req: Bytes) -> Result<WriteRequest>;
@@ -73,6 +73,8 @@ pub struct Context {
     precision: String,
     chunked: bool,
     session_config: CnosSessionConfig,
+    /// The address of the client that sent the request, if known
+    client_addr: Option<String>,
 }
 
 impl Context {
@@ -98,6 +100,10 @@ impl Context {
     pub fn chunked(&self) -> bool {
         self.chunked
     }
+
+    pub fn client_addr(&self) -> Option<&str> {
+        self.client_addr.as_deref()
+    }
 }
 
 impl SpanRecorderExt for Context {
@@ -118,6 +124,7 @@ pub struct ContextBuilder {
     precision: String,
     chunked: bool,
     session_config: CnosSessionConfig,
+    client_addr: Option<String>,
 }
 
 impl ContextBuilder {
@@ -129,6 +136,7 @@ impl ContextBuilder {
             database: DEFAULT_DATABASE.to_string(),
             chunked: Default::default(),
             session_config: Default::default(),
+            client_addr: None,
         }
     }
 
@@ -176,6 +184,13 @@ impl ContextBuilder {
         self
     }
 
+    pub fn with_client_addr(mut self, client_addr: Option<String>) -> Self {
+        if let Some(client_addr) = client_addr {
+            self.client_addr = Some(client_addr);
+        }
+        self
+    }
+
     pub fn build(self) -> Context {
         Context {
             user: self.user,
@@ -184,6 +199,7 @@ impl ContextBuilder {
             precision: self.precision,
             chunked: self.chunked,
             session_config: self.session_config,
+            client_addr: self.client_addr,
         }
     }
 }
@@ -0,0 +1,97 @@
+#[macro_use]
+extern crate criterion;
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+use models::codec::Encoding;
+use rand::{Rng, SeedableRng};
+use tskv::tsm::codec::get_f64_codec;
+
+use crate::criterion::black_box;
+
+const FLOAT_ENCODINGS: [Encoding; 3] = [Encoding::Gorilla, Encoding::Quantile, Encoding::Chimp];
+const BLOCK_SIZE: usize = 1000;
+
+fn float_data() -> Vec<(&'static str, Vec<f64>)> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+
+    // Temperature sensor with 2 decimal places.
+    let sensor = (0..BLOCK_SIZE)
+        .map(|i| {
+            ((20.0 + (i as f64 / 50.0).sin() * 5.0 + rng.gen_range(-0.5..0.5)) * 100.0).round()
+                / 100.0
+        })
+        .collect();
+    // Gauge that switches between a few values.
+    let gauge = (0..BLOCK_SIZE)
+        .map(|_| [0.0, 0.25, 0.5, 0.75, 1.0][rng.gen_range(0..5)])
+        .collect();
+    // Monotonically increasing counter.
+    let counter = (0..BLOCK_SIZE)
+        .scan(0.0, |acc, _| {
+            *acc += rng.gen_range(0..100) as f64;
+            Some(*acc)
+        })
+        .collect();
+    let random = (0..BLOCK_SIZE).map(|_| rng.gen::<f64>()).collect();
+
+    vec![
+        ("sensor", sensor),
+        ("gauge", gauge),
+        ("counter", counter),
+        ("random", random),
+    ]
+}
+
+fn float_codec(c: &mut Criterion) {
+    for (name, data) in float_data() {
+        let raw_size = data.len() * std::mem::size_of::<f64>();
+
+        let mut group = c.benchmark_group(format!("f64_encode/{name}"));
+        group.throughput(Throughput::Elements(data.len() as u64));
+        for encoding in FLOAT_ENCODINGS {
+            let codec = get_f64_codec(encoding);
+            group.bench_with_input(
+                BenchmarkId::from_parameter(encoding.as_str()),
+                &data,
+                |b, data| {
+                    b.iter(|| {
+                        let mut buf = Vec::with_capacity(raw_size);
+                        codec.encode(black_box(data), &mut buf).unwrap();
+                        buf
+                    })
+                },
+            );
+        }
+        group.finish();
+
+        let mut group = c.benchmark_group(format!("f64_decode/{name}"));
+        group.throughput(Throughput::Elements(data.len() as u64));
+        for encoding in FLOAT_ENCODINGS {
+            let codec = get_f64_codec(encoding);
+            let mut encoded = vec![];
+            codec.encode(&data, &mut encoded).unwrap();
+            println!(
+                "{name}/{}: compression ratio {:.2} ({raw_size} -> {} bytes)",
+                encoding.as_str(),
+                raw_size as f64 / encoded.len() as f64,
+                encoded.len()
+            );
+
+            group.bench_with_input(
+                BenchmarkId::from_parameter(encoding.as_str()),
+                &encoded,
+                |b, encoded| {
+                    b.iter(|| {
+                        let mut buf = Vec::with_capacity(BLOCK_SIZE);
+                        codec.decode(black_box(encoded), &mut buf).unwrap();
+                        buf
+                    })
+                },
+            );
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, float_codec);
+criterion_main!(benches);
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use datafusion::arrow::datatypes::{DataType, TimeUnit};
+use datafusion::scalar::ScalarValue;
+use models::predicate::domain::{ColumnDomains, Domain, Range};
+use models::schema::{TableColumn, TskvTableSchema};
+use models::{SeriesKey, Tag};
+use tokio::runtime::Runtime;
+use tskv::index::ts_index::TSIndex;
+use tskv::index::IndexEngineType;
+
+const TABLE: &str = "cpu";
+const HOSTS: usize = 1000;
+const REGIONS: usize = 10;
+
+fn series_key(host: usize) -> SeriesKey {
+    SeriesKey {
+        tags: vec![
+            Tag::new_with_column_id(1, format!("host_{host:04}").into_bytes()),
+            Tag::new_with_column_id(2, format!("region_{}", host % REGIONS).into_bytes()),
+        ],
+        table: TABLE.to_string(),
+    }
+}
+
+fn table_schema() -> TskvTableSchema {
+    TskvTableSchema::new(
+        "cnosdb".to_string(),
+        "public".to_string(),
+        TABLE.to_string(),
+        vec![
+            TableColumn::new_time_column(0, TimeUnit::Nanosecond),
+            TableColumn::new_tag_column(1, "host".to_string()),
+            TableColumn::new_tag_column(2, "region".to_string()),
+        ],
+    )
+}
+
+fn build_index(rt: &Runtime, engine_type: IndexEngineType) -> Arc<TSIndex> {
+    let dir = format!("/tmp/test_bench/index/{engine_type}");
+    let _ = std::fs::remove_dir_all(&dir);
+    rt.block_on(async {
+        let ts_index = TSIndex::new(&dir, engine_type).await.unwrap();
+        let series_keys = (0..HOSTS).map(series_key).collect::<Vec<_>>();
+        ts_index
+            .add_series_if_not_exists(series_keys)
+            .await
+            .unwrap();
+        // Wait for the binlog to be applied to the inverted index.
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        ts_index.flush().await.unwrap();
+        ts_index
+    })
+}
+
+fn index_lookup(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let schema = table_schema();
+    let host_domain = ColumnDomains::of(
+        "host".to_string(),
+        &Domain::of_ranges(&[Range::lt(
+            &DataType::Utf8,
+            &ScalarValue::Utf8(Some("host_0100".to_string())),
+        )])
+        .unwrap(),
+    );
+    let region_domain = ColumnDomains::of(
+        "region".to_string(),
+        &Domain::of_values(
+            &DataType::Utf8,
+            true,
+            &[&ScalarValue::Utf8(Some("region_1".to_string()))],
+        ),
+    );
+
+    let mut group = c.benchmark_group("index");
+    for engine_type in [IndexEngineType::Radix, IndexEngineType::Sled] {
+        let ts_index = build_index(&rt, engine_type);
+
+        group.bench_function(BenchmarkId::new("series_lookup", engine_type), |b| {
+            let mut host = 0;
+            b.to_async(&rt).iter(|| {
+                host = (host + 1) % HOSTS;
+                let tags = series_key(host).tags;
+                let ts_index = ts_index.clone();
+                async move {
+                    let sids = ts_index.get_series_id_list(TABLE, &tags).await.unwrap();
+                    assert_eq!(sids.len(), 1);
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("domains_range", engine_type), |b| {
+            b.to_async(&rt).iter(|| async {
+                let sids = ts_index
+                    .get_series_ids_by_domains(&schema, &host_domain)
+                    .await
+                    .unwrap();
+                assert_eq!(sids.len(), 100);
+            })
+        });
+        group.bench_function(BenchmarkId::new("domains_equal", engine_type), |b| {
+            b.to_async(&rt).iter(|| async {
+                let sids = ts_index
+                    .get_series_ids_by_domains(&schema, &region_domain)
+                    .await
+                    .unwrap();
+                assert_eq!(sids.len(), HOSTS / REGIONS);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, index_lookup);
+criterion_main!(benches);
@@ -1,16 +1,28 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 
 use blake3::Hasher;
+use datafusion::arrow::array::{
+    ArrayRef, BinaryBuilder, BooleanArray, Float64Array, Int64Array, Int64Builder, StringArray,
+    StringBuilder, TimestampMicrosecondArray, TimestampMillisecondArray, TimestampNanosecondArray,
+    TimestampSecondArray, UInt32Builder, UInt64Array,
+};
 use datafusion::arrow::datatypes::{
-    DataType as ArrowDataType, Field as ArrowField, Schema, SchemaRef,
+    DataType as ArrowDataType, Field as ArrowField, Schema, SchemaRef, TimeUnit,
 };
 use datafusion::arrow::record_batch::RecordBatch;
+use models::field_value::FieldVal;
 use models::predicate::domain::TimeRange;
-use models::{utils as model_utils, ColumnId, FieldId, SeriesId, Timestamp};
+use models::schema::{ColumnType, TskvTableSchemaRef};
+use models::{
+    utils as model_utils, ColumnId, FieldId, PhysicalDType, SeriesId, SeriesKey, Timestamp,
+};
 use tokio::sync::RwLock;
 
 use crate::error::{Error, Result};
-use crate::tseries_family::TseriesFamily;
+use crate::tseries_family::{TseriesFamily, Version};
+use crate::tsm2::reader::TSM2Reader;
+use crate::tsm2::writer::DataBlock2;
 use crate::TseriesFamilyId;
 
 /// Duration of each TimeRangeHashTreeNode, 24 hour.
@@ -30,6 +42,8 @@ pub fn hash_to_string(hash: Hash) -> String {
 pub struct VnodeHashTreeNode {
     pub vnode_id: TseriesFamilyId,
     pub fields: Vec<FieldHashTreeNode>,
+    /// Series keys of series ids in `fields`, series ids may differ between replicas.
+    pub series_keys: HashMap<SeriesId, SeriesKey>,
     min_ts: Timestamp,
     max_ts: Timestamp,
 }
@@ -39,6 +53,7 @@ impl VnodeHashTreeNode {
         Self {
             vnode_id,
             fields: Vec::with_capacity(capacity),
+            series_keys: HashMap::new(),
             min_ts: Timestamp::MAX,
             max_ts: Timestamp::MIN,
         }
@@ -86,7 +101,7 @@ impl VnodeHashTreeNode {
 impl std::fmt::Display for VnodeHashTreeNode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{{ \"vnode_id\": {}, \"fields\": [ ", self.vnode_id)?;
-        let last_field_i = self.fields.len() - 1;
+        let last_field_i = self.fields.len().saturating_sub(1);
         for (i, node) in self.fields.iter().enumerate() {
             write!(f, "{node}")?;
             if i < last_field_i {
@@ -145,7 +160,7 @@ impl std::fmt::Display for FieldHashTreeNode {
             f,
             "{{ \"series_id\": {sid}, \"column_id\": {cid}, \"values\": [ "
         )?;
-        let last_tr_i = self.time_ranges.len() - 1;
+        let last_tr_i = self.time_ranges.len().saturating_sub(1);
         for (i, node) in self.time_ranges.iter().enumerate() {
             write!(f, "{node}")?;
             if i < last_tr_i {
@@ -198,12 +213,12 @@ pub fn vnode_table_checksum_schema() -> SchemaRef {
     ]))
 }
 
-pub fn vnode_field_time_range_table_checksum_schema() -> SchemaRef {
+pub fn vnode_series_checksum_schema() -> SchemaRef {
     Arc::new(Schema::new(vec![
         ArrowField::new("vnode_id", ArrowDataType::UInt32, false),
-        ArrowField::new("field_Id", ArrowDataType::UInt32, false),
-        ArrowField::new("min_ts", ArrowDataType::UInt32, false),
-        ArrowField::new("max_ts", ArrowDataType::UInt32, false),
+        ArrowField::new("series_key", ArrowDataType::Binary, false),
+        ArrowField::new("min_ts", ArrowDataType::Int64, false),
+        ArrowField::new("max_ts", ArrowDataType::Int64, false),
         ArrowField::new("checksum", ArrowDataType::Utf8, false),
     ]))
 }
@@ -213,966 +228,538 @@ pub fn vnode_field_time_range_table_checksum_schema() -> SchemaRef {
 /// | vnode_id | checksum |
 /// | -------- | -------- |
 /// | 1        | a1a2a3a4 |
-pub(crate) async fn vnode_checksum(_vnode: Arc<RwLock<TseriesFamily>>) -> Result<RecordBatch> {
-    // let vnode_id = vnode.read().await.tf_id();
-    // let root_node = vnode_hash_tree(vnode).await?;
-    //
-    // let capacity = root_node.len();
-    // let mut vnode_id_array = UInt32Array::builder(capacity);
-    // let mut check_sum_array = StringBuilder::with_capacity(capacity, 32 * capacity);
-    // vnode_id_array.append_value(vnode_id);
-    // check_sum_array.append_value(hash_to_string(root_node.into_checksum()));
-    // RecordBatch::try_new(
-    //     vnode_table_checksum_schema(),
-    //     vec![
-    //         Arc::new(vnode_id_array.finish()),
-    //         Arc::new(check_sum_array.finish()),
-    //     ],
-    // )
-    // .map_err(|err| Error::CommonError {
-    //     reason: format!("get checksum fail, {}", err),
-    // })
-    Err(Error::Unimplemented {
-        msg: "checksum unimplemented".to_string(),
+pub(crate) async fn vnode_checksum(vnode: Arc<RwLock<TseriesFamily>>) -> Result<RecordBatch> {
+    let vnode_id = vnode.read().await.tf_id();
+    let root_node = vnode_hash_tree(vnode).await?;
+
+    let mut vnode_id_array = UInt32Builder::with_capacity(1);
+    let mut check_sum_array = StringBuilder::with_capacity(1, 64);
+    vnode_id_array.append_value(vnode_id);
+    check_sum_array.append_value(hash_to_string(root_node.into_checksum()));
+    RecordBatch::try_new(
+        vnode_table_checksum_schema(),
+        vec![
+            Arc::new(vnode_id_array.finish()),
+            Arc::new(check_sum_array.finish()),
+        ],
+    )
+    .map_err(|err| Error::CommonError {
+        reason: format!("get checksum fail, {}", err),
+    })
+}
+
+/// Get checksums of all data of a vnode grouped by series and time range, returns RecordBatch
+/// with columns of [`vnode_series_checksum_schema`], for example:
+///
+/// | vnode_id | series_key    | min_ts   | max_ts   | checksum |
+/// | -------- | ------------- | -------- | -------- | -------- |
+/// | 1        | 0x0a0b0c...   | 10000000 | 10000099 | a1a2a3a4 |
+///
+/// The series key is encoded by [`SeriesKey::encode`].
+pub(crate) async fn vnode_series_checksum(
+    vnode: Arc<RwLock<TseriesFamily>>,
+) -> Result<RecordBatch> {
+    let vnode_id = vnode.read().await.tf_id();
+    let root_node = vnode_hash_tree(vnode).await?;
+
+    // Fields are sorted by field id, so the columns of a series are hashed in the same order.
+    let mut series_tr_hashers: BTreeMap<(SeriesId, Timestamp), (Timestamp, Hasher)> =
+        BTreeMap::new();
+    for field in root_node.fields.iter() {
+        let (column_id, series_id) = field.column_series();
+        for time_range in field.time_ranges.iter() {
+            let (_, hasher) = series_tr_hashers
+                .entry((series_id, time_range.min_ts))
+                .or_insert_with(|| (time_range.max_ts, Hasher::new()));
+            hasher.update(&column_id.to_be_bytes());
+            hasher.update(&time_range.hash);
+        }
+    }
+
+    let capacity = series_tr_hashers.len();
+    let mut vnode_id_array = UInt32Builder::with_capacity(capacity);
+    let mut series_key_array = BinaryBuilder::new();
+    let mut min_time_array = Int64Builder::with_capacity(capacity);
+    let mut max_time_array = Int64Builder::with_capacity(capacity);
+    let mut check_sum_array = StringBuilder::with_capacity(capacity, 64 * capacity);
+    for ((series_id, min_ts), (max_ts, hasher)) in series_tr_hashers {
+        let series_key =
+            root_node
+                .series_keys
+                .get(&series_id)
+                .ok_or_else(|| Error::CommonError {
+                    reason: format!("series key of series {} not found", series_id),
+                })?;
+        vnode_id_array.append_value(vnode_id);
+        series_key_array.append_value(series_key.encode());
+        min_time_array.append_value(min_ts);
+        max_time_array.append_value(max_ts);
+        check_sum_array.append_value(hash_to_string(hasher.finalize().into()));
+    }
+
+    RecordBatch::try_new(
+        vnode_series_checksum_schema(),
+        vec![
+            Arc::new(vnode_id_array.finish()),
+            Arc::new(series_key_array.finish()),
+            Arc::new(min_time_array.finish()),
+            Arc::new(max_time_array.finish()),
+            Arc::new(check_sum_array.finish()),
+        ],
+    )
+    .map_err(|err| Error::CommonError {
+        reason: format!("get checksum fail, {}", err),
+    })
+}
+
+/// Read data of all tsm files of a vnode, split by time range and then calculate hash.
+///
+/// The memcache of the vnode should be flushed before.
+pub(crate) async fn vnode_hash_tree(
+    vnode: Arc<RwLock<TseriesFamily>>,
+) -> Result<VnodeHashTreeNode> {
+    let (version, vnode_id) = {
+        let vnode_rlock = vnode.read().await;
+        (vnode_rlock.version(), vnode_rlock.tf_id())
+    };
+    let readers = version_readers(&version).await?;
+    let series_ids: BTreeSet<SeriesId> = readers
+        .iter()
+        .flat_map(|r| r.chunk().keys().copied())
+        .collect();
+
+    let mut fid_tr_hash_val_map: BTreeMap<FieldId, Vec<(TimeRange, Hash)>> = BTreeMap::new();
+    let mut series_keys = HashMap::with_capacity(series_ids.len());
+    for series_id in series_ids {
+        if let Some((series_key, data_block)) = read_series_data_block(&readers, series_id).await? {
+            hash_data_block(
+                &mut fid_tr_hash_val_map,
+                DEFAULT_DURATION,
+                series_id,
+                &data_block,
+            )?;
+            series_keys.insert(series_id, series_key);
+        }
+    }
+
+    let mut vnode_hash_tree_node =
+        VnodeHashTreeNode::with_capacity(vnode_id, fid_tr_hash_val_map.len());
+    for (fid, tr_hashes) in fid_tr_hash_val_map {
+        let mut filed_hash_tree_node = FieldHashTreeNode::with_capacity(fid, tr_hashes.len());
+        for (tr, hash) in tr_hashes {
+            filed_hash_tree_node.push(TimeRangeHashTreeNode::new(tr, hash));
+        }
+        vnode_hash_tree_node.push(filed_hash_tree_node);
+    }
+    vnode_hash_tree_node.series_keys = series_keys;
+    trace::trace!("VnodeHashTree({vnode_id}): {}", vnode_hash_tree_node);
+
+    Ok(vnode_hash_tree_node)
+}
+
+/// Read data of the given series in the given time ranges from a vnode, returns a RecordBatch
+/// with all columns of the table, tag values are taken from the series keys.
+///
+/// Rows that all fields are null(deleted) are skipped. The memcache of the vnode should be flushed before.
+pub(crate) async fn vnode_series_data(
+    vnode: Arc<RwLock<TseriesFamily>>,
+    table_schema: TskvTableSchemaRef,
+    series_time_ranges: &[(SeriesKey, TimeRange)],
+) -> Result<RecordBatch> {
+    let version = vnode.read().await.version();
+    let readers = version_readers(&version).await?;
+
+    let mut key_sids: HashMap<&SeriesKey, SeriesId> = HashMap::new();
+    for reader in readers.iter() {
+        for (sid, chunk) in reader.chunk().iter() {
+            key_sids.entry(chunk.series_key()).or_insert(*sid);
+        }
+    }
+    let mut series_time_ranges_map: HashMap<&SeriesKey, Vec<TimeRange>> = HashMap::new();
+    for (series_key, time_range) in series_time_ranges {
+        if series_key.table() == &table_schema.name {
+            series_time_ranges_map
+                .entry(series_key)
+                .or_default()
+                .push(*time_range);
+        }
+    }
+
+    let mut timestamps: Vec<Timestamp> = Vec::new();
+    let mut row_series_keys: Vec<&SeriesKey> = Vec::new();
+    let mut field_values: HashMap<ColumnId, Vec<Option<FieldVal>>> = HashMap::new();
+    for (series_key, time_ranges) in series_time_ranges_map {
+        let series_id = match key_sids.get(series_key) {
+            Some(sid) => *sid,
+            None => continue,
+        };
+        let data_block = match read_series_data_block(&readers, series_id).await? {
+            Some((_, data_block)) => data_block,
+            None => continue,
+        };
+        for i in 0..data_block.len() {
+            let ts = match data_block.ts().get(i) {
+                Some(FieldVal::Integer(ts)) => ts,
+                _ => continue,
+            };
+            if !time_ranges.iter().any(|tr| tr.contains(ts)) {
+                continue;
+            }
+            let values: Vec<(ColumnId, Option<FieldVal>)> = data_block
+                .columns()
+                .map(|(desc, column)| (desc.id, column.get(i)))
+                .collect();
+            if values.iter().all(|(_, v)| v.is_none()) {
+                continue;
+            }
+
+            let row = timestamps.len();
+            timestamps.push(ts);
+            row_series_keys.push(series_key);
+            for (column_id, value) in values {
+                let column_values = field_values.entry(column_id).or_default();
+                column_values.resize(row, None);
+                column_values.push(value);
+            }
+        }
+    }
+
+    let num_rows = timestamps.len();
+    let mut fields = Vec::with_capacity(table_schema.columns().len());
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(table_schema.columns().len());
+    for column in table_schema.columns() {
+        let array: ArrayRef = match &column.column_type {
+            ColumnType::Time(unit) => match unit {
+                TimeUnit::Second => Arc::new(TimestampSecondArray::from(timestamps.clone())),
+                TimeUnit::Millisecond => {
+                    Arc::new(TimestampMillisecondArray::from(timestamps.clone()))
+                }
+                TimeUnit::Microsecond => {
+                    Arc::new(TimestampMicrosecondArray::from(timestamps.clone()))
+                }
+                TimeUnit::Nanosecond => {
+                    Arc::new(TimestampNanosecondArray::from(timestamps.clone()))
+                }
+            },
+            ColumnType::Tag => {
+                let values = row_series_keys
+                    .iter()
+                    .map(|key| key.tag_string_val(&column.name))
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(|e| Error::CommonError {
+                        reason: format!("invalid tag value of column {}: {}", column.name, e),
+                    })?;
+                Arc::new(StringArray::from(values))
+            }
+            ColumnType::Field(_) => {
+                let mut values = field_values.remove(&column.id).unwrap_or_default();
+                values.resize(num_rows, None);
+                field_values_to_array(column.column_type.to_physical_data_type(), values)?
+            }
+        };
+        fields.push(ArrowField::from(column).with_nullable(true));
+        arrays.push(array);
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).map_err(|err| Error::CommonError {
+        reason: format!("read series data fail, {}", err),
     })
 }
 
-// /// Get checksum of all data of a vnode, returns RecordBatch with more columns, for example:
-// ///
-// /// | vnode_id | field_id | min_time | max_time | checksum |
-// /// | -------- | -------- | -------- | -------- | -------- |
-// /// | 1        | 1        | 10000100 | 10000200 | a1a2a3a4 |
-// pub(crate) async fn vnode_field_time_range_checksum(
-//     vnode: Arc<RwLock<TseriesFamily>>,
-// ) -> Result<RecordBatch> {
-//     let vnode_id = vnode.read().await.tf_id();
-//     let root_node = vnode_hash_tree(vnode).await?;
-//
-//     let capacity = root_node.len();
-//     let mut vnode_id_array = UInt32Array::builder(capacity);
-//     let mut field_id_array = UInt64Array::builder(capacity);
-//     let mut min_time_array = TimestampNanosecondArray::builder(capacity);
-//     let mut max_time_array = TimestampNanosecondArray::builder(capacity);
-//     let mut check_sum_array = StringBuilder::with_capacity(capacity, 32 * capacity);
-//
-//     for field in root_node.fields {
-//         for time_range in field.time_ranges {
-//             vnode_id_array.append_value(vnode_id);
-//             field_id_array.append_value(field.field_id);
-//             min_time_array.append_value(time_range.min_ts);
-//             max_time_array.append_value(time_range.max_ts);
-//             check_sum_array.append_value(hash_to_string(time_range.checksum()));
-//         }
-//     }
-//
-//     RecordBatch::try_new(
-//         vnode_field_time_range_table_checksum_schema(),
-//         vec![
-//             Arc::new(vnode_id_array.finish()),
-//             Arc::new(field_id_array.finish()),
-//             Arc::new(min_time_array.finish()),
-//             Arc::new(max_time_array.finish()),
-//             Arc::new(check_sum_array.finish()),
-//         ],
-//     )
-//     .map_err(|err| Error::CommonError {
-//         reason: format!("get checksum fail, {}", err),
-//     })
-// }
+/// Returns readers of all tsm files of a version, newer files are in front:
+/// files of lower level are newer, and files of greater file id are newer in a level.
+async fn version_readers(version: &Version) -> Result<Vec<Arc<TSM2Reader>>> {
+    let mut readers = Vec::new();
+    for level in version.levels_info().iter() {
+        let mut files: Vec<_> = level.files.iter().collect();
+        files.sort_by_key(|f| std::cmp::Reverse(f.file_id()));
+        for file in files {
+            readers.push(version.get_tsm_reader2(file.file_path()).await?);
+        }
+    }
+    Ok(readers)
+}
 
-// pub(crate) async fn vnode_hash_tree(
-//     _vnode: Arc<RwLock<TseriesFamily>>,
-// ) -> Result<VnodeHashTreeNode> {
-//     const MAX_DATA_BLOCK_SIZE: u32 = 1000;
+/// Read and merge data of a series from readers, tombstones are applied,
+/// values in the front readers overwrite values of the same timestamp in the back readers.
+async fn read_series_data_block(
+    readers: &[Arc<TSM2Reader>],
+    series_id: SeriesId,
+) -> Result<Option<(SeriesKey, DataBlock2)>> {
+    let mut series_key = None;
+    let mut merged_block: Option<DataBlock2> = None;
+    for reader in readers {
+        let chunk = match reader.chunk().get(&series_id) {
+            Some(chunk) => chunk.clone(),
+            None => continue,
+        };
+        for column_group_id in chunk.column_group().keys() {
+            let mut data_block = reader.read_datablock(series_id, *column_group_id).await?;
+            if reader.has_tombstone() {
+                data_block.filter_by_tomb(reader.tombstone(), series_id)?;
+            }
+            merged_block = match merged_block {
+                Some(mut block) => Some(block.merge(data_block)?),
+                None => Some(data_block),
+            };
+        }
+        if series_key.is_none() {
+            series_key = Some(chunk.series_key().clone());
+        }
+    }
+    Ok(series_key.zip(merged_block))
+}
 
-// let (version, vnode_id) = {
-//     let vnode_rlock = vnode.read().await;
-//     (vnode_rlock.version(), vnode_rlock.tf_id())
-// };
-// let mut readers: Vec<Arc<TSM2Reader>> = Vec::new();
-// let tsm_paths: Vec<&PathBuf> = version
-//     .levels_info()
-//     .iter()
-//     .flat_map(|l| l.files.iter().map(|f| f.file_path()))
-//     .collect();
-// for p in tsm_paths {
-//     let r = version.get_tsm_reader2(p).await?;
-//     readers.push(r);
-// }
-//
-// // Build a compact iterator, read data, split by time range and then calculate hash.
-// let iter = CompactIterator::new(readers, MAX_DATA_BLOCK_SIZE as usize, true);
-// let mut fid_tr_hash_val_map: HashMap<FieldId, Vec<(TimeRange, Hash)>> =
-//     read_from_compact_iterator(iter, DEFAULT_DURATION).await?;
-//
-// let mut field_ids: Vec<FieldId> = fid_tr_hash_val_map.keys().cloned().collect();
-// field_ids.sort();
-// let mut vnode_hash_tree_node = VnodeHashTreeNode::with_capacity(vnode_id, field_ids.len());
-// for fid in field_ids {
-//     let tr_hashes = fid_tr_hash_val_map.remove(&fid).unwrap();
-//     let mut filed_hash_tree_node = FieldHashTreeNode::with_capacity(fid, tr_hashes.len());
-//     for (tr, hash) in tr_hashes {
-//         filed_hash_tree_node.push(TimeRangeHashTreeNode::new(tr, hash));
-//     }
-//     vnode_hash_tree_node.push(filed_hash_tree_node);
-// }
-// trace::trace!("VnodeHashTree({vnode_id}): {}", vnode_hash_tree_node);
-//
-// Ok(vnode_hash_tree_node)
-// }
+/// Returns the time range of `duration_nanoseconds` that contains `ts_nanoseconds`.
+fn calc_time_range(ts_nanoseconds: Timestamp, duration_nanoseconds: i64) -> TimeRange {
+    let min_ts = ts_nanoseconds - ts_nanoseconds.rem_euclid(duration_nanoseconds);
+    TimeRange::new(min_ts, min_ts.saturating_add(duration_nanoseconds - 1))
+}
 
-// /// Returns a time range calculated by the given `ts_nanoseconds`
-// /// and `duration_nanoseconds`.
-// ///
-// /// **NOTE**: `duration_nanoseconds` must not greater than `ts_nanoseconds`.
-// fn calc_time_range(
-//     ts_nanoseconds: Timestamp,
-//     duration_nanoseconds: i64,
-// ) -> Result<(Timestamp, Timestamp)> {
-//     if duration_nanoseconds < 0 {
-//         return Err(Error::Transform {
-//             reason: format!("duration({duration_nanoseconds}) < 0 while doing duration_trunc",),
-//         });
-//     }
-//     if duration_nanoseconds > ts_nanoseconds.abs() {
-//         return Err(Error::Transform {
-//             reason: format!(
-//                 "duration({duration_nanoseconds}) > timestamp({ts_nanoseconds}) while doing duration_trunc",
-//             ),
-//         });
-//     }
-//
-//     let delta_down = ts_nanoseconds % duration_nanoseconds;
-//     // Copy from: chrono::round.rs duration_trunc()
-//     let min_ts = match delta_down.cmp(&0) {
-//         cmp::Ordering::Equal => ts_nanoseconds,
-//         cmp::Ordering::Greater => ts_nanoseconds - delta_down,
-//         cmp::Ordering::Less => ts_nanoseconds - (duration_nanoseconds - delta_down.abs()),
-//     };
-//     Ok((min_ts, min_ts + duration_nanoseconds))
-// }
+/// Calculate hash of each field of a data block by time ranges, null values are skipped.
+fn hash_data_block(
+    fid_tr_hash_val_map: &mut BTreeMap<FieldId, Vec<(TimeRange, Hash)>>,
+    time_range_nanosec: i64,
+    series_id: SeriesId,
+    data_block: &DataBlock2,
+) -> Result<()> {
+    let mut timestamps = Vec::with_capacity(data_block.len());
+    for i in 0..data_block.len() {
+        match data_block.ts().get(i) {
+            Some(FieldVal::Integer(ts)) => timestamps.push(ts),
+            _ => {
+                return Err(Error::DataBlockError {
+                    reason: "Time column does not support except i64 physical data type"
+                        .to_string(),
+                })
+            }
+        }
+    }
 
-// ///Find the index of the given value in a list using binary search,
-// ///then return the index and the next value of the index.
-// ///
-// /// If the value is i64::MIN, then return (list.len(), None).
-// fn find_value_index_and_next(list: &[Timestamp], value: Timestamp) -> (usize, Option<Timestamp>) {
-//     if value != Timestamp::MIN {
-//         match list.binary_search(&value) {
-//             Ok(i) => {
-//                 if i + 1 == list.len() {
-//                     (i, None)
-//                 } else {
-//                     (i, Some(list[i + 1]))
-//                 }
-//             }
-//             Err(i) => {
-//                 if i == list.len() {
-//                     (i, None)
-//                 } else {
-//                     (i, Some(list[i]))
-//                 }
-//             }
-//         }
-//     } else {
-//         (list.len(), None)
-//     }
-// }
+    for (desc, column) in data_block.columns() {
+        let mut tr_hashes: Vec<(TimeRange, Hash)> = Vec::new();
+        let mut current: Option<(TimeRange, Hasher)> = None;
+        for (i, ts) in timestamps.iter().enumerate() {
+            let value = match column.get(i) {
+                Some(v) => v,
+                None => continue,
+            };
+            if !matches!(&current, Some((time_range, _)) if time_range.contains(*ts)) {
+                if let Some((time_range, hasher)) = current.take() {
+                    tr_hashes.push((time_range, hasher.finalize().into()));
+                }
+                current = Some((calc_time_range(*ts, time_range_nanosec), Hasher::new()));
+            }
+            if let Some((_, hasher)) = current.as_mut() {
+                hasher.update(&ts.to_be_bytes());
+                hash_field_value(hasher, &value);
+            }
+        }
+        if let Some((time_range, hasher)) = current {
+            tr_hashes.push((time_range, hasher.finalize().into()));
+        }
+
+        if !tr_hashes.is_empty() {
+            fid_tr_hash_val_map.insert(model_utils::unite_id(desc.id, series_id), tr_hashes);
+        }
+    }
+
+    Ok(())
+}
+
+fn hash_field_value(hasher: &mut Hasher, value: &FieldVal) {
+    match value {
+        FieldVal::Float(v) => hasher.update(&v.to_be_bytes()),
+        FieldVal::Integer(v) => hasher.update(&v.to_be_bytes()),
+        FieldVal::Unsigned(v) => hasher.update(&v.to_be_bytes()),
+        FieldVal::Boolean(v) => hasher.update(if *v { &[1_u8] } else { &[0_u8] }),
+        FieldVal::Bytes(v) => hasher.update(v),
+    };
+}
+
+fn field_values_to_array(
+    data_type: PhysicalDType,
+    values: Vec<Option<FieldVal>>,
+) -> Result<ArrayRef> {
+    let array: ArrayRef = match data_type {
+        PhysicalDType::Float => Arc::new(
+            values
+                .into_iter()
+                .map(|v| match v {
+                    Some(FieldVal::Float(v)) => Some(v),
+                    _ => None,
+                })
+                .collect::<Float64Array>(),
+        ),
+        PhysicalDType::Integer => Arc::new(
+            values
+                .into_iter()
+                .map(|v| match v {
+                    Some(FieldVal::Integer(v)) => Some(v),
+                    _ => None,
+                })
+                .collect::<Int64Array>(),
+        ),
+        PhysicalDType::Unsigned => Arc::new(
+            values
+                .into_iter()
+                .map(|v| match v {
+                    Some(FieldVal::Unsigned(v)) => Some(v),
+                    _ => None,
+                })
+                .collect::<UInt64Array>(),
+        ),
+        PhysicalDType::Boolean => Arc::new(
+            values
+                .into_iter()
+                .map(|v| match v {
+                    Some(FieldVal::Boolean(v)) => Some(v),
+                    _ => None,
+                })
+                .collect::<BooleanArray>(),
+        ),
+        PhysicalDType::String => Arc::new(
+            values
+                .into_iter()
+                .map(|v| match v {
+                    Some(FieldVal::Bytes(v)) => Some(String::from_utf8_lossy(&v).to_string()),
+                    _ => None,
+                })
+                .collect::<StringArray>(),
+        ),
+        PhysicalDType::Unknown => {
+            return Err(Error::CommonError {
+                reason: "unknown data type of field".to_string(),
+            })
+        }
+    };
+    Ok(array)
+}
 
-// /// Calculate a hash from a range of a data block,
-// /// return the index of the range end, and the next value of the index.
-// fn hash_partial_datablock(
-//     hasher: &mut Hasher,
-//     data_block: &DataBlock,
-//     min_idx: usize,
-//     max_timestamp: Timestamp,
-// ) -> Option<(usize, Option<Timestamp>)> {
-//     match data_block {
-//         DataBlock::U64 { ts, val, .. } => {
-//             let (max_ts_i, after_max_ts) = find_value_index_and_next(&ts[min_idx..], max_timestamp);
-//             if max_ts_i == 0 {
-//                 return None;
-//             }
-//             let limit = min_idx + max_ts_i;
-//             let ts_iter = ts[min_idx..limit].iter().map(|v| v.to_be_bytes());
-//             let val_iter = val[min_idx..limit].iter().map(|v| v.to_be_bytes());
-//             for (t, v) in ts_iter.zip(val_iter) {
-//                 hasher.update(&t);
-//                 hasher.update(&v);
-//             }
-//             Some((limit, after_max_ts))
-//         }
-//         DataBlock::I64 { ts, val, .. } => {
-//             let (max_ts_i, after_max_ts) = find_value_index_and_next(&ts[min_idx..], max_timestamp);
-//             if max_ts_i == 0 {
-//                 return None;
-//             }
-//             let limit = min_idx + max_ts_i;
-//             let ts_iter = ts[min_idx..limit].iter().map(|v| v.to_be_bytes());
-//             let val_iter = val[min_idx..limit].iter().map(|v| v.to_be_bytes());
-//             for (t, v) in ts_iter.zip(val_iter) {
-//                 hasher.update(&t);
-//                 hasher.update(&v);
-//             }
-//             Some((limit, after_max_ts))
-//         }
-//         DataBlock::F64 { ts, val, .. } => {
-//             let (max_ts_i, after_max_ts) = find_value_index_and_next(&ts[min_idx..], max_timestamp);
-//             if max_ts_i == 0 {
-//                 return None;
-//             }
-//             let limit = min_idx + max_ts_i;
-//             let ts_iter = ts[min_idx..limit].iter().map(|v| v.to_be_bytes());
-//             let val_iter = val[min_idx..limit].iter().map(|v| v.to_be_bytes());
-//             for (t, v) in ts_iter.zip(val_iter) {
-//                 hasher.update(&t);
-//                 hasher.update(&v);
-//             }
-//             Some((limit, after_max_ts))
-//         }
-//         DataBlock::Str { ts, val, .. } => {
-//             let (max_ts_i, after_max_ts) = find_value_index_and_next(&ts[min_idx..], max_timestamp);
-//             if max_ts_i == 0 {
-//                 return None;
-//             }
-//             let limit = min_idx + max_ts_i;
-//             let ts_iter = ts[min_idx..limit].iter().map(|v| v.to_be_bytes());
-//             let val_iter = val[min_idx..limit].iter();
-//             for (t, v) in ts_iter.zip(val_iter) {
-//                 hasher.update(&t);
-//                 hasher.update(v);
-//             }
-//             Some((limit, after_max_ts))
-//         }
-//         DataBlock::Bool { ts, val, .. } => {
-//             let (max_ts_i, after_max_ts) = find_value_index_and_next(&ts[min_idx..], max_timestamp);
-//             if max_ts_i == 0 {
-//                 return None;
-//             }
-//             let limit = min_idx + max_ts_i;
-//             let ts_iter = ts[min_idx..limit].iter().map(|v| v.to_be_bytes());
-//             let val_iter = val[min_idx..limit].iter();
-//             for (t, v) in ts_iter.zip(val_iter) {
-//                 hasher.update(&t);
-//                 hasher.update(if *v { &[1_u8] } else { &[0_u8] });
-//             }
-//             Some((limit, after_max_ts))
-//         }
-//     }
-// }
-//
-// fn hash_data_block(
-//     fid_tr_hash_val_map: &mut HashMap<FieldId, Vec<(TimeRange, Hash)>>,
-//     time_range_nanosec: i64,
-//     field_id: FieldId,
-//     data_block: &DataBlock,
-// ) -> Result<()> {
-//     if let Some(blk_time_range) = data_block.time_range() {
-//         // Get truncated time range by DataBlock.time[0]
-//         // TODO: Support data block to be split into multi time ranges.
-//         let (mut min_ts, mut max_ts) = calc_time_range(blk_time_range.0, time_range_nanosec)?;
-//
-//         // Calculate and store the hash value of data in time range
-//         let hash_vec = fid_tr_hash_val_map.entry(field_id).or_default();
-//         let mut min_idx = 0;
-//         let mut ts_after_max_ts = None;
-//         let mut hasher = Hasher::new();
-//         while blk_time_range.1 > min_ts {
-//             if let Some((min_i, max_ts_next)) =
-//                 hash_partial_datablock(&mut hasher, data_block, min_idx, max_ts)
-//             {
-//                 min_idx = min_i;
-//                 ts_after_max_ts = max_ts_next;
-//                 hash_vec.push((TimeRange::new(min_ts, max_ts), hasher.finalize().into()));
-//                 hasher.reset();
-//             }
-//             if let Some(ts) = ts_after_max_ts {
-//                 max_ts += time_range_nanosec;
-//                 if max_ts < ts {
-//                     // Sometimes the next time_range is long after.
-//                     (min_ts, max_ts) = calc_time_range(ts, time_range_nanosec)?;
-//                 }
-//             } else {
-//                 break;
-//             }
-//         }
-//     } else {
-//         // Ignore: Because argument decode_non_overlap_blocks in CompactIterator::new()
-//         // is set to true, we may ignore this case.
-//     }
-//
-//     Ok(())
-// }
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use datafusion::arrow::datatypes::TimeUnit;
+    use models::codec::Encoding;
+    use models::field_value::FieldVal;
+    use models::predicate::domain::TimeRange;
+    use models::schema::{ColumnType, TableColumn, TskvTableSchema, TskvTableSchemaRef};
+    use models::{SeriesKey, ValueType};
+
+    use super::{calc_time_range, hash_data_block, read_series_data_block, DEFAULT_DURATION};
+    use crate::tsm2::reader::TSM2Reader;
+    use crate::tsm2::writer::{Column, DataBlock2, Tsm2Writer};
+
+    fn table_schema() -> TskvTableSchemaRef {
+        Arc::new(TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "test0".to_string(),
+            vec![
+                TableColumn::new_time_column(0, TimeUnit::Nanosecond),
+                TableColumn::new(
+                    1,
+                    "f1".to_string(),
+                    ColumnType::Field(ValueType::Integer),
+                    Encoding::default(),
+                ),
+            ],
+        ))
+    }
 
-// async fn read_from_compact_iterator(
-//     mut iter: CompactIterator,
-//     time_range_nanosec: i64,
-// ) -> Result<HashMap<FieldId, Vec<(TimeRange, Hash)>>> {
-//     let mut compacting_block_metas: Vec<CompactingBlockMeta> = Vec::new();
-//     let mut fid = iter.curr_fid();
-//     let mut fid_tr_hash_val_map: HashMap<FieldId, Vec<(TimeRange, Hash)>> = HashMap::new();
-//     while let Some(blk_meta_group) = iter.next().await {
-//         if fid.is_some() && fid != iter.curr_fid() {
-//             if let Some(data_block) = load_and_merge_data_block(&mut compacting_block_metas).await?
-//             {
-//                 hash_data_block(
-//                     &mut fid_tr_hash_val_map,
-//                     time_range_nanosec,
-//                     fid.unwrap(),
-//                     &data_block,
-//                 )?;
-//             }
-//             compacting_block_metas.clear();
-//         }
-//         fid = iter.curr_fid();
-//         compacting_block_metas.append(&mut blk_meta_group.into_compacting_block_metas());
-//     }
-//     if let Some(field_id) = fid {
-//         if let Some(data_block) = load_and_merge_data_block(&mut compacting_block_metas).await? {
-//             hash_data_block(
-//                 &mut fid_tr_hash_val_map,
-//                 time_range_nanosec,
-//                 field_id,
-//                 &data_block,
-//             )?;
-//         }
-//     }
-//
-//     Ok(fid_tr_hash_val_map)
-// }
+    fn data_block(schema: TskvTableSchemaRef, ts: Vec<i64>, values: Vec<i64>) -> DataBlock2 {
+        let mut ts_col = Column::empty(ColumnType::Time(TimeUnit::Nanosecond)).unwrap();
+        ts.into_iter()
+            .for_each(|v| ts_col.push(Some(FieldVal::Integer(v))));
+        let mut val_col = Column::empty(ColumnType::Field(ValueType::Integer)).unwrap();
+        values
+            .into_iter()
+            .for_each(|v| val_col.push(Some(FieldVal::Integer(v))));
+        DataBlock2::new(
+            schema.clone(),
+            ts_col,
+            schema.time_column(),
+            vec![val_col],
+            vec![schema.column("f1").cloned().unwrap()],
+        )
+    }
 
-// async fn load_and_merge_data_block(
-//     compacting_block_metas: &mut [CompactingBlockMeta],
-// ) -> Result<Option<DataBlock>> {
-//     if compacting_block_metas.is_empty() {
-//         return Ok(None);
-//     }
-//     let head = &mut compacting_block_metas[0];
-//     let mut head_block = head.get_data_block().await?;
-//     if compacting_block_metas.len() > 1 {
-//         for blk_meta in compacting_block_metas[1..].iter_mut() {
-//             let blk_block = blk_meta.get_data_block().await?;
-//             head_block = head_block.merge(blk_block);
-//         }
-//     }
-//     Ok(Some(head_block))
-// }
+    #[test]
+    fn test_calc_time_range() {
+        assert_eq!(
+            calc_time_range(5, DEFAULT_DURATION),
+            TimeRange::new(0, DEFAULT_DURATION - 1)
+        );
+        assert_eq!(
+            calc_time_range(DEFAULT_DURATION, DEFAULT_DURATION),
+            TimeRange::new(DEFAULT_DURATION, 2 * DEFAULT_DURATION - 1)
+        );
+        assert_eq!(
+            calc_time_range(-1, DEFAULT_DURATION),
+            TimeRange::new(-DEFAULT_DURATION, -1)
+        );
+    }
 
-// #[cfg(test)]
-// mod test {
-//     use std::collections::{BTreeMap, HashMap};
-//     use std::sync::Arc;
-//
-//     use blake3::Hasher;
-//     use chrono::{Duration, NaiveDateTime};
-//     use config::Config;
-//     use datafusion::arrow::datatypes::TimeUnit;
-//     use memory_pool::GreedyMemoryPool;
-//     use meta::model::meta_admin::AdminMeta;
-//     use meta::model::{MetaClientRef, MetaRef};
-//     use metrics::metric_register::MetricsRegister;
-//     use minivec::MiniVec;
-//     use models::predicate::domain::TimeRange;
-//     use models::schema::{
-//         ColumnType, DatabaseOptions, DatabaseSchema, Precision, TableColumn, TableSchema,
-//         TenantOptions, TskvTableSchema,
-//     };
-//     use models::{FieldId, Timestamp, ValueType};
-//     use protos::kv_service::{Meta, WritePointsRequest};
-//     use protos::models::{self as fb_models, FieldType};
-//     use protos::models_helper;
-//     use protos::models_helper::create_points;
-//     use tokio::runtime::{self, Runtime};
-//     use tokio::sync::RwLock;
-//
-//     use super::{
-//         calc_time_range, find_value_index_and_next, hash_data_block, hash_partial_datablock, Hash,
-//     };
-//     use crate::compaction::check::{vnode_hash_tree, TimeRangeHashTreeNode, DEFAULT_DURATION};
-//     use crate::compaction::flush;
-//     use crate::tseries_family::TseriesFamily;
-//     use crate::tsm::codec::DataBlockEncoding;
-//     use crate::tsm::DataBlock;
-//     use crate::{Engine, Options, TsKv, TseriesFamilyId};
-//
-//     fn parse_nanos(datetime: &str) -> Timestamp {
-//         NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M:%S")
-//             .unwrap()
-//             .timestamp_nanos()
-//     }
-//
-//     #[test]
-//     fn test_calc_time_range() {
-//         /// Returns timestamp in nanoseconds of a date-time, and duration in nanoseconds of 30 minutes.
-//         fn get_args(datetime: &str) -> (Timestamp, i64) {
-//             let datetime = NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M:%S").unwrap();
-//             let timestamp = datetime.timestamp_nanos();
-//             let duration = Duration::minutes(30);
-//             let duration_nanos = duration.num_nanoseconds().unwrap();
-//             (timestamp, duration_nanos)
-//         }
-//
-//         // 2023-01-01 00:29:01 is in between 2023-01-01 00:00:00 and 2023-01-01 00:30:00
-//         let (stamp, span) = get_args("2023-01-01 00:29:01");
-//         assert_eq!(
-//             (
-//                 parse_nanos("2023-01-01 00:00:00"),
-//                 parse_nanos("2023-01-01 00:30:00"),
-//             ),
-//             calc_time_range(stamp, span).unwrap(),
-//         );
-//         // 2023-01-01 00:30:00 is in between 2023-01-01 00:30:00 and 2023-01-01 01:00:00
-//         let (stamp, span) = get_args("2023-01-01 00:30:00");
-//         assert_eq!(
-//             (
-//                 parse_nanos("2023-01-01 00:30:00"),
-//                 parse_nanos("2023-01-01 01:00:00"),
-//             ),
-//             calc_time_range(stamp, span).unwrap(),
-//         );
-//         // 2023-01-01 00:30:01 is between 2023-01-01 00:30:00 and 2023-01-01 01:00:00
-//         let (stamp, span) = get_args("2023-01-01 00:30:01");
-//         assert_eq!(
-//             (
-//                 parse_nanos("2023-01-01 00:30:00"),
-//                 parse_nanos("2023-01-01 01:00:00")
-//             ),
-//             calc_time_range(stamp, span).unwrap(),
-//         );
-//     }
-//
-//     #[test]
-//     fn test_find_value_index_and_next() {
-//         let timestamps = vec![
-//             parse_nanos("2023-01-01 00:01:00"),
-//             parse_nanos("2023-01-01 00:02:00"),
-//             parse_nanos("2023-01-01 00:03:00"),
-//             parse_nanos("2023-01-01 00:04:00"),
-//             parse_nanos("2023-01-01 00:05:00"),
-//         ];
-//
-//         assert_eq!(
-//             (0, Some(parse_nanos("2023-01-01 00:01:00"))),
-//             find_value_index_and_next(&timestamps, parse_nanos("2023-01-01 00:00:00")),
-//         );
-//         assert_eq!(
-//             (2, Some(parse_nanos("2023-01-01 00:04:00"))),
-//             find_value_index_and_next(&timestamps, parse_nanos("2023-01-01 00:03:00"))
-//         );
-//         assert_eq!(
-//             (3, Some(parse_nanos("2023-01-01 00:04:00"))),
-//             find_value_index_and_next(&timestamps, parse_nanos("2023-01-01 00:03:30"))
-//         );
-//         assert_eq!(
-//             (3, Some(parse_nanos("2023-01-01 00:05:00"))),
-//             find_value_index_and_next(&timestamps, parse_nanos("2023-01-01 00:04:00"))
-//         );
-//         assert_eq!(
-//             (5, None),
-//             find_value_index_and_next(&timestamps, parse_nanos("2023-01-01 00:30:00"))
-//         );
-//         assert_eq!(
-//             (5, None),
-//             find_value_index_and_next(&timestamps, Timestamp::MIN),
-//         );
-//     }
-//
-//     #[test]
-//     fn test_hash_partial_datablock() {
-//         fn data_block_partial_to_bytes(data_block: &DataBlock, from: usize, to: usize) -> Vec<u8> {
-//             let mut ret: Vec<u8> = vec![];
-//             for i in from..to {
-//                 let v = data_block
-//                     .get(i)
-//                     .unwrap_or_else(|| panic!("data block has at least {} items", i));
-//                 ret.extend_from_slice(&v.to_bytes());
-//             }
-//             ret
-//         }
-//
-//         let timestamps = vec![
-//             parse_nanos("2023-01-01 00:01:00"),
-//             parse_nanos("2023-01-01 00:02:00"),
-//             parse_nanos("2023-01-01 00:03:00"),
-//             parse_nanos("2023-01-01 00:04:00"),
-//             parse_nanos("2023-01-01 00:05:00"),
-//             parse_nanos("2023-01-01 00:06:00"),
-//         ];
-//         #[rustfmt::skip]
-//         let data_blocks = vec![
-//             DataBlock::U64 { ts: timestamps.clone(), val: vec![1, 2, 3, 4, 5, 6], enc: DataBlockEncoding::default() },
-//             DataBlock::I64 { ts: timestamps.clone(), val: vec![1, 2, 3, 4, 5, 6], enc: DataBlockEncoding::default() },
-//             DataBlock::Str {
-//                 ts: timestamps.clone(),
-//                 val: vec![
-//                     MiniVec::from("1"), MiniVec::from("2"), MiniVec::from("3"), MiniVec::from("4"), MiniVec::from("5"), MiniVec::from("6"),
-//                 ],
-//                 enc: DataBlockEncoding::default(),
-//             },
-//             DataBlock::F64 { ts: timestamps.clone(), val: vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], enc: DataBlockEncoding::default() },
-//             DataBlock::Bool { ts: timestamps, val: vec![true, false, true, false, true, false], enc: DataBlockEncoding::default() },
-//         ];
-//
-//         for data_block in data_blocks {
-//             let mut hasher_blk = Hasher::new();
-//             let max_ts_i_and_next_ts = hash_partial_datablock(
-//                 &mut hasher_blk,
-//                 &data_block,
-//                 0,
-//                 parse_nanos("2023-01-01 00:04:00"),
-//             );
-//             assert_eq!(
-//                 Some((3, Some(parse_nanos("2023-01-01 00:05:00")))),
-//                 max_ts_i_and_next_ts
-//             );
-//             let mut hasher_expected = Hasher::new();
-//             assert_eq!(
-//                 hasher_expected
-//                     .update(data_block_partial_to_bytes(&data_block, 0, 3).as_slice())
-//                     .finalize(),
-//                 hasher_blk.finalize(),
-//                 "checking {}",
-//                 data_block
-//             );
-//
-//             let mut hasher_blk = Hasher::new();
-//             let min_idx = hash_partial_datablock(
-//                 &mut hasher_blk,
-//                 &data_block,
-//                 max_ts_i_and_next_ts.unwrap().0,
-//                 Timestamp::MIN,
-//             );
-//             assert_eq!(Some((6, None)), min_idx);
-//             let mut hasher_expected = Hasher::new();
-//             assert_eq!(
-//                 hasher_expected
-//                     .update(data_block_partial_to_bytes(&data_block, 3, 6).as_slice())
-//                     .finalize(),
-//                 hasher_blk.finalize()
-//             );
-//         }
-//     }
-//
-//     const U64_COL_NAME: &str = "col_u64";
-//     const I64_COL_NAME: &str = "col_i64";
-//     const F64_COL_NAME: &str = "col_f64";
-//     const STR_COL_NAME: &str = "col_str";
-//     const BOOL_COL_NAME: &str = "col_bool";
-//     const TIME_COL_NAME: &str = "time";
-//
-//     fn data_blocks_to_write_batch_args(
-//         data_blocks: &[DataBlock],
-//         _timestamps: &mut [Timestamp],
-//         fields: &mut HashMap<&str, Vec<Vec<u8>>>,
-//         time: &mut Vec<i64>,
-//     ) {
-//         let mut u64_vec = vec![];
-//         let mut i64_vec = vec![];
-//         let mut f64_vec = vec![];
-//         let mut str_vec = vec![];
-//         let mut bool_vec = vec![];
-//         for data_block in data_blocks {
-//             match data_block {
-//                 DataBlock::U64 { ts, val, .. } => {
-//                     for (t, v) in ts.iter().zip(val) {
-//                         u64_vec.push((*t, v.to_be_bytes().to_vec()));
-//                     }
-//                 }
-//                 DataBlock::I64 { ts, val, .. } => {
-//                     for (t, v) in ts.iter().zip(val) {
-//                         i64_vec.push((*t, v.to_be_bytes().to_vec()));
-//                     }
-//                 }
-//                 DataBlock::F64 { ts, val, .. } => {
-//                     for (t, v) in ts.iter().zip(val) {
-//                         f64_vec.push((*t, v.to_be_bytes().to_vec()));
-//                     }
-//                 }
-//                 DataBlock::Str { ts, val, .. } => {
-//                     for (t, v) in ts.iter().zip(val) {
-//                         str_vec.push((*t, v.to_vec()));
-//                     }
-//                 }
-//                 DataBlock::Bool { ts, val, .. } => {
-//                     for (t, v) in ts.iter().zip(val) {
-//                         bool_vec.push((*t, if *v { vec![1_u8] } else { vec![0_u8] }));
-//                     }
-//                 }
-//             }
-//         }
-//
-//         u64_vec.sort_by_key(|v| v.0);
-//         i64_vec.sort_by_key(|v| v.0);
-//         f64_vec.sort_by_key(|v| v.0);
-//         str_vec.sort_by_key(|v| v.0);
-//         bool_vec.sort_by_key(|v| v.0);
-//
-//         #[allow(clippy::type_complexity)]
-//         fn write_vec_into_map(
-//             vec: Vec<(i64, Vec<u8>)>,
-//             col_name: &'static str,
-//             map: &mut BTreeMap<Timestamp, Vec<(&str, Vec<u8>)>>,
-//         ) {
-//             vec.into_iter().for_each(|(t, v)| {
-//                 let entry = map.entry(t).or_default();
-//                 entry.push((col_name, v));
-//             });
-//         }
-//
-//         let entry = fields.entry(U64_COL_NAME).or_default();
-//         u64_vec.into_iter().for_each(|(t, v)| {
-//             time.push(t);
-//             entry.push(v);
-//         });
-//         let entry = fields.entry(I64_COL_NAME).or_default();
-//         i64_vec.into_iter().for_each(|(_, v)| {
-//             entry.push(v);
-//         });
-//         let entry = fields.entry(F64_COL_NAME).or_default();
-//         f64_vec.into_iter().for_each(|(_, v)| {
-//             entry.push(v);
-//         });
-//         let entry = fields.entry(STR_COL_NAME).or_default();
-//         str_vec.into_iter().for_each(|(_, v)| {
-//             entry.push(v);
-//         });
-//         let entry = fields.entry(BOOL_COL_NAME).or_default();
-//         bool_vec.into_iter().for_each(|(_, v)| {
-//             entry.push(v);
-//         });
-//     }
-//
-//     async fn do_write_batch(
-//         engine: &TsKv,
-//         vnode_id: TseriesFamilyId,
-//         _timestamps: Vec<i64>,
-//         tenant: &str,
-//         database: &str,
-//         table: &str,
-//         columns: HashMap<&str, Vec<Vec<u8>>>,
-//         time: Vec<i64>,
-//     ) {
-//         let mut fbb = flatbuffers::FlatBufferBuilder::new();
-//
-//         let mut tags = HashMap::new();
-//         tags.insert("ta", vec![]);
-//         tags.insert("tb", vec![]);
-//         let len = time.len();
-//         for _ in 0..len {
-//             tags.get_mut("ta").unwrap().push("a1".to_string());
-//             tags.get_mut("tb").unwrap().push("b1".to_string());
-//         }
-//         let fields_type = HashMap::from([
-//             (U64_COL_NAME, FieldType::Unsigned),
-//             (I64_COL_NAME, FieldType::Integer),
-//             (F64_COL_NAME, FieldType::Float),
-//             (STR_COL_NAME, FieldType::String),
-//             (BOOL_COL_NAME, FieldType::Boolean),
-//         ]);
-//
-//         let points = create_points(
-//             &mut fbb,
-//             database,
-//             table,
-//             tags.iter()
-//                 .map(|(k, v)| (*k, v.iter().map(|v| v.as_str()).collect()))
-//                 .collect(),
-//             columns
-//                 .iter()
-//                 .map(|(k, v)| (*k, v.iter().map(|v| v.as_slice()).collect()))
-//                 .collect(),
-//             fields_type,
-//             &time,
-//             time.len(),
-//         );
-//         fbb.finish(points, None);
-//         let points = fbb.finished_data().to_vec();
-//         let write_batch = WritePointsRequest {
-//             version: 1,
-//             meta: Some(Meta {
-//                 tenant: tenant.to_string(),
-//                 user: None,
-//                 password: None,
-//             }),
-//             points,
-//         };
-//         let points = flatbuffers::root::<fb_models::Points>(&write_batch.points).unwrap();
-//         models_helper::print_points(points);
-//         let _ = engine
-//             .write(None, vnode_id, Precision::NS, write_batch)
-//             .await;
-//     }
-//
-//     fn data_block_to_hash_tree(
-//         data_block: &DataBlock,
-//         duration_nanosecs: i64,
-//     ) -> Vec<(TimeRange, Hash)> {
-//         if !data_block.is_empty() {
-//             const FIELD_ID: FieldId = 0;
-//
-//             let mut fid_tr_hash_val_map: HashMap<FieldId, Vec<(TimeRange, Hash)>> = HashMap::new();
-//             hash_data_block(
-//                 &mut fid_tr_hash_val_map,
-//                 duration_nanosecs,
-//                 FIELD_ID,
-//                 data_block,
-//             )
-//             .unwrap();
-//             fid_tr_hash_val_map.remove(&FIELD_ID).unwrap()
-//         } else {
-//             Vec::new()
-//         }
-//     }
-//
-//     fn check_hash_tree_node(
-//         col_values: &[TimeRangeHashTreeNode],
-//         cmp_values: &[(TimeRange, Hash)],
-//         column_name: &str,
-//     ) {
-//         assert_eq!(col_values.len(), cmp_values.len());
-//         for (a, b) in col_values.iter().zip(cmp_values.iter()) {
-//             assert_eq!(a.min_ts, b.0.min_ts, "col '{}' min_ts compare", column_name);
-//             assert_eq!(a.max_ts, b.0.max_ts, "col '{}' max_ts compare", column_name);
-//             assert_eq!(a.hash, b.1, "col '{}' hash compare", column_name);
-//         }
-//     }
-//
-//     async fn init_meta(config: &Config, tenant: &str) -> (MetaRef, MetaClientRef) {
-//         let meta = AdminMeta::new(config.clone()).await;
-//
-//         meta.add_data_node().await.unwrap();
-//         // Create tenant may get 'TenantAlreadyExists.
-//         let _ = meta
-//             .create_tenant(tenant.to_string(), TenantOptions::default())
-//             .await;
-//         let meta_client = meta.tenant_meta(tenant).await.unwrap();
-//
-//         (meta, meta_client)
-//     }
-//
-//     #[allow(clippy::too_many_arguments)]
-//     async fn init_tskv(
-//         options: &Options,
-//         runtime: Arc<Runtime>,
-//         meta_manager: MetaRef,
-//         meta_client: MetaClientRef,
-//         vnode_id: TseriesFamilyId,
-//         tenant: &str,
-//         database: &str,
-//         table: &str,
-//         columns: Vec<TableColumn>,
-//     ) -> TsKv {
-//         let engine = TsKv::open(
-//             meta_manager,
-//             options.clone(),
-//             runtime.clone(),
-//             Arc::new(GreedyMemoryPool::default()),
-//             Arc::new(MetricsRegister::default()),
-//         )
-//         .await
-//         .unwrap();
-//         let _ = engine.drop_database(tenant, database).await;
-//         let _ = meta_client.drop_db(database).await;
-//
-//         // Create database and vnode
-//         let mut database_schema = DatabaseSchema::new(tenant, database);
-//         database_schema
-//             .config
-//             .with_ttl(DatabaseOptions::DEFAULT_TTL);
-//         meta_client
-//             .create_db(database_schema.clone())
-//             .await
-//             .unwrap();
-//         meta_client
-//             .create_table(&TableSchema::TsKvTableSchema(
-//                 TskvTableSchema::new(
-//                     tenant.to_string(),
-//                     database.to_string(),
-//                     table.to_string(),
-//                     columns,
-//                 )
-//                 .into(),
-//             ))
-//             .await
-//             .unwrap();
-//         let db = engine
-//             .get_db_or_else_create(tenant, database)
-//             .await
-//             .unwrap();
-//         let mut db = db.write().await;
-//         let tsf = db
-//             .add_tsfamily(vnode_id, None, engine.context())
-//             .await
-//             .unwrap();
-//         let tsf = tsf.read().await;
-//         assert_eq!(vnode_id, tsf.tf_id());
-//
-//         engine
-//     }
-//
-//     async fn write_tskv(
-//         engine: &TsKv,
-//         vnode: Arc<RwLock<TseriesFamily>>,
-//         tenant: &str,
-//         database: &str,
-//         table: &str,
-//         data_blocks: &[DataBlock],
-//     ) {
-//         let vnode_id = vnode.read().await.tf_id();
-//         // Write data to database and vnode
-//         let mut timestamps: Vec<Timestamp> = Vec::new();
-//         let _fields: Vec<Vec<(&str, Vec<u8>)>> = Vec::new();
-//         let mut fields: HashMap<&str, Vec<Vec<u8>>> = HashMap::new();
-//         let mut time = Vec::new();
-//         data_blocks_to_write_batch_args(data_blocks, &mut timestamps, &mut fields, &mut time);
-//         do_write_batch(
-//             engine, vnode_id, timestamps, tenant, database, table, fields, time,
-//         )
-//         .await;
-//
-//         let flush_req = {
-//             let mut vnode = vnode.write().await;
-//             vnode.switch_to_immutable();
-//             vnode.build_flush_req(true).unwrap()
-//         };
-//         flush::run_flush_memtable_job(flush_req, engine.context(), false)
-//             .await
-//             .unwrap();
-//
-//         let sec_1 = Duration::seconds(1).to_std().unwrap();
-//         let mut check_num = 0;
-//         loop {
-//             tokio::time::sleep(sec_1).await;
-//             // If flushing is finished, the newest super_version contains a level 1 file.
-//             let vnode = vnode.read().await;
-//             let super_version = vnode.super_version();
-//             if !super_version.version.levels_info()[1].files.is_empty() {
-//                 break;
-//             }
-//             check_num += 1;
-//             if check_num >= 10 {
-//                 println!("Checksum: warn: flushing takes more than {check_num} seconds.",);
-//             }
-//         }
-//     }
-//
-//     #[test]
-//     fn test_get_vnode_hash_tree() {
-//         let base_dir = "/tmp/test/repair/1".to_string();
-//         let wal_dir = "/tmp/test/repair/1/wal".to_string();
-//         let log_dir = "/tmp/test/repair/1/log".to_string();
-//         // trace::init_default_global_tracing(&log_dir, "test.log", "debug");
-//         let _ = std::fs::remove_dir_all(&base_dir);
-//         std::fs::create_dir_all(&base_dir).unwrap();
-//         let tenant_name = "cnosdb".to_string();
-//         let database_name = "test_get_vnode_hash_tree".to_string();
-//         let vnode_id: TseriesFamilyId = 1;
-//         let table_name = "test_table".to_string();
-//
-//         let timestamps = vec![
-//             parse_nanos("2023-01-01 00:01:00"),
-//             parse_nanos("2023-01-01 00:02:00"),
-//             parse_nanos("2023-01-01 00:03:00"),
-//             parse_nanos("2023-02-01 00:01:00"),
-//             parse_nanos("2023-02-01 00:02:00"),
-//             parse_nanos("2023-02-01 00:03:00"),
-//             parse_nanos("2023-03-01 00:01:00"),
-//             parse_nanos("2023-03-01 00:02:00"),
-//             parse_nanos("2023-03-01 00:03:00"),
-//         ];
-//         #[rustfmt::skip]
-//         let data_blocks = vec![
-//             DataBlock::U64 { ts: timestamps.clone(), val: vec![1, 2, 3, 4, 5, 6, 7, 8, 9], enc: DataBlockEncoding::default() },
-//             DataBlock::I64 { ts: timestamps.clone(), val: vec![1, 2, 3, 4, 5, 6, 7, 8, 9], enc: DataBlockEncoding::default() },
-//             DataBlock::F64 { ts: timestamps.clone(), val: vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0], enc: DataBlockEncoding::default() },
-//             DataBlock::Str {
-//                 ts: timestamps.clone(),
-//                 val: vec![
-//                     MiniVec::from("1"), MiniVec::from("2"), MiniVec::from("3"),
-//                     MiniVec::from("4"), MiniVec::from("5"), MiniVec::from("6"),
-//                     MiniVec::from("7"), MiniVec::from("8"), MiniVec::from("9"),
-//                 ],
-//                 enc: DataBlockEncoding::default(),
-//             },
-//             DataBlock::Bool { ts: timestamps, val: vec![true, false, true, false, true, false, true, false, true], enc: DataBlockEncoding::default() },
-//         ];
-//         let data_block_tr_hashes = vec![
-//             data_block_to_hash_tree(&data_blocks[0], DEFAULT_DURATION),
-//             data_block_to_hash_tree(&data_blocks[1], DEFAULT_DURATION),
-//             data_block_to_hash_tree(&data_blocks[2], DEFAULT_DURATION),
-//             data_block_to_hash_tree(&data_blocks[3], DEFAULT_DURATION),
-//             data_block_to_hash_tree(&data_blocks[4], DEFAULT_DURATION),
-//         ];
-//         #[rustfmt::skip]
-//         let columns = vec![
-//             TableColumn::new(0, U64_COL_NAME.to_string(), ColumnType::Field(ValueType::Unsigned), Default::default()),
-//             TableColumn::new(1, I64_COL_NAME.to_string(), ColumnType::Field(ValueType::Integer), Default::default()),
-//             TableColumn::new(2, F64_COL_NAME.to_string(), ColumnType::Field(ValueType::Float), Default::default()),
-//             TableColumn::new(3, STR_COL_NAME.to_string(), ColumnType::Field(ValueType::String), Default::default()),
-//             TableColumn::new(4, BOOL_COL_NAME.to_string(), ColumnType::Field(ValueType::Boolean), Default::default()),
-//             TableColumn::new(5, TIME_COL_NAME.to_string(), ColumnType::Time(TimeUnit::Nanosecond), Default::default()),
-//         ];
-//
-//         let mut config = config::get_config_for_test();
-//         config.storage.path = base_dir;
-//         config.wal.path = wal_dir;
-//         config.wal.sync = true;
-//         config.log.path = log_dir;
-//         let options = Options::from(&config);
-//
-//         let rt = Arc::new(
-//             runtime::Builder::new_multi_thread()
-//                 .enable_all()
-//                 .build()
-//                 .unwrap(),
-//         );
-//         let (meta_manager, meta_client) = rt.block_on(init_meta(&config, &tenant_name));
-//         let engine = rt.block_on(init_tskv(
-//             &options,
-//             rt.clone(),
-//             meta_manager,
-//             meta_client,
-//             vnode_id,
-//             &tenant_name,
-//             &database_name,
-//             &table_name,
-//             columns.clone(),
-//         ));
-//
-//         rt.block_on(async {
-//             // Get created database and vnode
-//             let database_ref = engine.get_db(&tenant_name, &database_name).await.unwrap();
-//             let vnode_ref = database_ref
-//                 .read()
-//                 .await
-//                 .get_tsfamily(vnode_id)
-//                 .unwrap_or_else(|| {
-//                     panic!("created vnode '{}' not exist", vnode_id);
-//                 });
-//             write_tskv(
-//                 &engine,
-//                 vnode_ref.clone(),
-//                 &tenant_name,
-//                 &database_name,
-//                 &table_name,
-//                 &data_blocks,
-//             )
-//             .await;
-//
-//             // Get hash values and check them.
-//             let tree_root = vnode_hash_tree(vnode_ref).await.unwrap();
-//             println!("{tree_root}");
-//             assert_eq!(tree_root.len(), 15);
-//             assert_eq!(tree_root.vnode_id, vnode_id);
-//             assert_eq!(tree_root.fields.len(), 5);
-//             for field in tree_root.fields.iter() {
-//                 let (cid, _) = field.column_series();
-//                 let col_tr_hashes = &data_block_tr_hashes[cid as usize];
-//                 let col_name = &columns[cid as usize].name;
-//                 check_hash_tree_node(&field.time_ranges, col_tr_hashes, col_name);
-//             }
-//
-//             engine.close().await;
-//         });
-//     }
-// }
+    #[tokio::test]
+    async fn test_merge_and_hash_series() {
+        let dir = "/tmp/test/compaction/check/merge_and_hash_series";
+        let _ = std::fs::remove_dir_all(dir);
+        let schema = table_schema();
+        let series_key = SeriesKey {
+            tags: vec![],
+            table: "test0".to_string(),
+        };
+
+        let mut readers = vec![];
+        // The newer file is in front.
+        for (file_id, ts, values) in [
+            (2, vec![2, DEFAULT_DURATION], vec![20, 30]),
+            (1, vec![1, 2], vec![1, 2]),
+        ] {
+            let mut writer = Tsm2Writer::open(&dir, file_id, 0, false).await.unwrap();
+            writer
+                .write_datablock(
+                    1,
+                    series_key.clone(),
+                    data_block(schema.clone(), ts, values),
+                )
+                .await
+                .unwrap();
+            writer.finish().await.unwrap();
+            readers.push(Arc::new(TSM2Reader::open(writer.path()).await.unwrap()));
+        }
+
+        let (key, merged) = read_series_data_block(&readers, 1).await.unwrap().unwrap();
+        assert_eq!(key, series_key);
+        assert!(read_series_data_block(&readers, 2).await.unwrap().is_none());
+        let expected = data_block(
+            schema.clone(),
+            vec![1, 2, DEFAULT_DURATION],
+            vec![1, 20, 30],
+        );
+
+        let mut hashes = BTreeMap::new();
+        hash_data_block(&mut hashes, DEFAULT_DURATION, 1, &merged).unwrap();
+        let mut expected_hashes = BTreeMap::new();
+        hash_data_block(&mut expected_hashes, DEFAULT_DURATION, 1, &expected).unwrap();
+        assert_eq!(hashes, expected_hashes);
+
+        let tr_hashes = hashes.values().next().unwrap();
+        assert_eq!(
+            tr_hashes.iter().map(|(tr, _)| *tr).collect::<Vec<_>>(),
+            vec![
+                TimeRange::new(0, DEFAULT_DURATION - 1),
+                TimeRange::new(DEFAULT_DURATION, 2 * DEFAULT_DURATION - 1),
+            ]
+        );
+
+        let changed = data_block(schema, vec![1, 2, DEFAULT_DURATION], vec![1, 21, 30]);
+        let mut changed_hashes = BTreeMap::new();
+        hash_data_block(&mut changed_hashes, DEFAULT_DURATION, 1, &changed).unwrap();
+        let changed_tr_hashes = changed_hashes.values().next().unwrap();
+        assert_ne!(changed_tr_hashes[0], tr_hashes[0]);
+        assert_eq!(changed_tr_hashes[1], tr_hashes[1]);
+    }
+}
@@ -135,6 +135,11 @@ impl CompactingBlockMeta {
     pub fn has_tombstone(&self) -> bool {
         self.reader.has_tombstone()
     }
+
+    /// Whether the pages can be copied to the new file with their statistics.
+    pub fn has_exact_page_statistics(&self) -> bool {
+        self.reader.footer().has_exact_page_statistics()
+    }
 }
 
 #[derive(Clone)]
@@ -172,12 +177,21 @@ impl CompactingBlockMetaGroup {
         if self.blk_metas.is_empty() {
             return Ok(vec![]);
         }
-        self.blk_metas
-            .sort_by(|a, b| a.reader_idx.cmp(&b.reader_idx).reverse());
+        // The newest block is at the front, see `run_compaction_job` for the order of readers.
+        self.blk_metas.sort_by(|a, b| {
+            (a.reader_idx, a.column_group_id)
+                .cmp(&(b.reader_idx, b.column_group_id))
+                .reverse()
+        });
 
         let merged_block;
-        if self.blk_metas.len() == 1 && !self.blk_metas[0].has_tombstone() {
+        if self.blk_metas.len() == 1
+            && !self.blk_metas[0].has_tombstone()
+            && self.blk_metas[0].has_exact_page_statistics()
+        {
             // Only one compacting block and has no tombstone, write as raw block.
+            // Pages of old files are decoded, the statistics of pages are computed again
+            // since the new file claims they are exact.
             trace!("only one compacting block, write as raw block");
             let meta_0 = &self.blk_metas[0].meta;
             let column_group_id = self.blk_metas[0].column_group_id;
@@ -231,7 +245,8 @@ impl CompactingBlockMetaGroup {
                 )]);
             }
         } else {
-            // One block with tombstone or multi compacting blocks, decode and merge these data block.
+            // One block with tombstone or inexact page statistics, or multi compacting blocks,
+            // decode and merge these data block.
             trace!(
                 "there are {} compacting blocks, need to decode and merge",
                 self.blk_metas.len()
@@ -772,10 +787,18 @@ pub async fn run_compaction_job(
         return Ok(None);
     }
 
-    // Buffers all tsm-files and it's indexes for this compaction
+    // Buffers all tsm-files and it's indexes for this compaction, the readers are
+    // in the order of writing (higher level files and then smaller file ids first),
+    // so that values with the same timestamp are deduplicated by the dedup policy.
     let tsf_id = request.ts_family_id;
+    let mut col_files = request.files.iter().collect::<Vec<_>>();
+    col_files.sort_by(|a, b| {
+        b.level()
+            .cmp(&a.level())
+            .then_with(|| a.file_id().cmp(&b.file_id()))
+    });
     let mut tsm_readers = Vec::new();
-    for col_file in request.files.iter() {
+    for col_file in col_files {
         let tsm_reader = request
             .version
             .get_tsm_reader2(col_file.file_path())
@@ -941,9 +964,10 @@ pub mod test {
 
     use arrow::datatypes::TimeUnit;
     use cache::ShardedAsyncCache;
+    use datafusion::scalar::ScalarValue;
     use models::codec::Encoding;
     use models::field_value::FieldVal;
-    use models::predicate::domain::TimeRange;
+    use models::predicate::domain::{PushedAggregateFunction, TimeRange};
     use models::schema::{ColumnType, TableColumn, TskvTableSchema};
     use models::{SeriesId, SeriesKey, ValueType};
 
@@ -952,10 +976,13 @@ pub mod test {
     use crate::file_system::file_manager;
     use crate::file_utils;
     use crate::kv_option::Options;
+    use crate::reader::aggregate::Accumulator;
     use crate::summary::VersionEdit;
     use crate::tseries_family::{ColumnFile, LevelInfo, Version};
     use crate::tsm::TsmTombstone;
+    use crate::tsm2::page::{PageStatistics, FOOTER_VERSION};
     use crate::tsm2::reader::TSM2Reader;
+    use crate::tsm2::statistics::ValueStatistics;
     use crate::tsm2::writer::{Column, DataBlock2, Tsm2Writer};
 
     pub(crate) async fn write_data_blocks_to_column_file(
@@ -1260,6 +1287,122 @@ pub mod test {
         check_column_file(dir, version_edit, expected_data).await;
     }
 
+    /// Files before footer version 3 have inexact null counts in page statistics,
+    /// the pages copied from them must be decoded and their statistics computed again.
+    #[tokio::test]
+    async fn test_compaction_legacy_page_statistics() {
+        let schema = TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "test0".to_string(),
+            vec![
+                TableColumn::new(
+                    0,
+                    "time".to_string(),
+                    ColumnType::Time(TimeUnit::Nanosecond),
+                    Encoding::default(),
+                ),
+                TableColumn::new(
+                    1,
+                    "f1".to_string(),
+                    ColumnType::Field(ValueType::Integer),
+                    Encoding::default(),
+                ),
+            ],
+        );
+        let schema = Arc::new(schema);
+        let data = DataBlock2::new(
+            schema.clone(),
+            ts_column(vec![1, 2, 3, 4]),
+            schema.time_column(),
+            vec![i64_some_column(vec![Some(1), None, None, Some(4)])],
+            vec![schema.column("f1").cloned().unwrap()],
+        );
+
+        let dir = "/tmp/test/compaction/legacy_page_statistics";
+        let _ = std::fs::remove_dir_all(dir);
+        let database = Arc::new("dba".to_string());
+        let opt = create_options(dir.to_string());
+        let dir = opt.storage.tsm_dir(&database, 1);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Footer version 2 always wrote 1 as the null count.
+        let mut pages = data.block_to_page().unwrap();
+        for page in pages.iter_mut() {
+            if let PageStatistics::I64(stat) = &page.meta.statistics {
+                page.meta.statistics =
+                    PageStatistics::I64(ValueStatistics::new(*stat.min(), *stat.max(), None, 1));
+            }
+        }
+        let mut writer = Tsm2Writer::open(&dir, 1, 0, false).await.unwrap();
+        writer.set_footer_version(2);
+        writer
+            .write_pages(
+                schema.clone(),
+                1,
+                SeriesKey::default(),
+                pages,
+                TimeRange::new(1, 4),
+            )
+            .await
+            .unwrap();
+        writer.finish().await.unwrap();
+        let mut cf = ColumnFile::new(
+            1,
+            2,
+            TimeRange::new(writer.min_ts(), writer.max_ts()),
+            writer.size() as u64,
+            false,
+            writer.path(),
+        );
+        cf.set_field_id_filter(Arc::new(writer.series_bloom_filter().clone()));
+        let reader = TSM2Reader::open(writer.path()).await.unwrap();
+        assert!(!reader.footer().has_exact_page_statistics());
+
+        let (compact_req, kernel) =
+            prepare_compact_req_and_kernel(database, opt, 2, vec![Arc::new(cf)]);
+        let (version_edit, _) = run_compaction_job(compact_req, kernel)
+            .await
+            .unwrap()
+            .unwrap();
+        let path = get_result_file_path(&dir, version_edit);
+        let reader = TSM2Reader::open(&path).await.unwrap();
+        assert_eq!(reader.footer().version(), FOOTER_VERSION);
+        assert!(reader.footer().has_exact_page_statistics());
+
+        // count, min and max pushed down to the scan are answered from page statistics.
+        let column = "f1".to_string();
+        let mut accumulators = [
+            Accumulator::new(&PushedAggregateFunction::Count(column.clone())),
+            Accumulator::new(&PushedAggregateFunction::Min(column.clone())),
+            Accumulator::new(&PushedAggregateFunction::Max(column)),
+        ];
+        for chunk in reader.chunk().values() {
+            for column_group in chunk.column_group().values() {
+                for page in column_group.pages() {
+                    if page.meta().column.name != "f1" {
+                        continue;
+                    }
+                    for accumulator in accumulators.iter_mut() {
+                        accumulator.update_statistics(page.meta()).unwrap();
+                    }
+                }
+            }
+        }
+        let results = accumulators
+            .iter()
+            .flat_map(|a| a.evaluate())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![
+                Some(ScalarValue::Int64(Some(2))),
+                Some(ScalarValue::Int64(Some(1))),
+                Some(ScalarValue::Int64(Some(4))),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_compaction_1() {
         let schema = TskvTableSchema::new(
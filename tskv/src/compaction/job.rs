@@ -4,16 +4,19 @@ use std::sync::{atomic, Arc};
 use std::time::{Duration, Instant};
 
 use flush::run_flush_memtable_job;
+use models::schema::{timestamp_convert, Precision};
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc::Receiver;
 use tokio::sync::{oneshot, RwLock, RwLockWriteGuard, Semaphore};
 use trace::{error, info};
 
-use crate::compaction::{flush, CompactTask, FlushReq, LevelCompactionPicker, Picker};
+use crate::compaction::{flush, tiering, CompactTask, FlushReq, LevelCompactionPicker, Picker};
+use crate::file_system::cold_store;
 use crate::summary::SummaryTask;
 use crate::{TsKvContext, TseriesFamilyId};
 
 const COMPACT_BATCH_CHECKING_SECONDS: u64 = 1;
+const COLD_TIER_CHECKING_SECONDS: u64 = 60;
 
 struct CompactProcessor {
     vnode_ids: HashMap<TseriesFamilyId, bool>,
@@ -331,6 +334,107 @@ impl std::fmt::Debug for FlushJob {
     }
 }
 
+pub struct TieringJob {
+    ctx: Arc<TsKvContext>,
+    runtime: Arc<Runtime>,
+}
+
+impl TieringJob {
+    pub fn new(runtime: Arc<Runtime>, ctx: Arc<TsKvContext>) -> Self {
+        Self { ctx, runtime }
+    }
+
+    /// Periodically move cold tsm files of all vnodes to the cold tier.
+    pub fn start_tiering_job(&self) {
+        let cold_store = match cold_store::get_cold_store(&self.ctx.options.storage) {
+            Some(s) => s,
+            None => return,
+        };
+        // Files are moved to the cold tier once they are cold for compaction.
+        let cold_duration = self.ctx.options.storage.compact_trigger_cold_duration;
+        if cold_duration == Duration::ZERO {
+            return;
+        }
+        let ctx = self.ctx.clone();
+        self.runtime.spawn(async move {
+            let mut check_interval =
+                tokio::time::interval(Duration::from_secs(COLD_TIER_CHECKING_SECONDS));
+            loop {
+                check_interval.tick().await;
+                let cold_before_ns = models::utils::now_timestamp_nanos()
+                    .saturating_sub(cold_duration.as_nanos() as i64);
+                let mut ts_families = vec![];
+                for db in ctx.version_set.read().await.get_all_db().values() {
+                    let db = db.read().await;
+                    let precision = match db.get_schemas().db_schema().await {
+                        Ok(schema) => *schema.config.precision_or_default(),
+                        Err(e) => {
+                            error!("Tiering: failed to get schema of {}: {:?}", db.owner(), e);
+                            continue;
+                        }
+                    };
+                    let cold_before = timestamp_convert(Precision::NS, precision, cold_before_ns)
+                        .unwrap_or(i64::MIN);
+                    ts_families.extend(
+                        db.ts_families()
+                            .values()
+                            .map(|tsf| (tsf.clone(), cold_before)),
+                    );
+                }
+
+                for (tsf, cold_before) in ts_families {
+                    let (vnode_id, version) = {
+                        let tsf = tsf.read().await;
+                        if !tsf.can_compaction() {
+                            continue;
+                        }
+                        (tsf.tf_id(), tsf.version())
+                    };
+                    let files = tiering::pick_cold_files(&version, cold_before);
+                    if files.is_empty() {
+                        continue;
+                    }
+                    match tiering::run_tiering_job(
+                        version,
+                        files,
+                        cold_store.clone(),
+                        ctx.global_ctx.clone(),
+                    )
+                    .await
+                    {
+                        Ok(Some((version_edit, file_metas))) => {
+                            let (summary_tx, summary_rx) = oneshot::channel();
+                            let _ = ctx
+                                .summary_task_sender
+                                .send(SummaryTask::new(
+                                    vec![version_edit],
+                                    Some(file_metas),
+                                    None,
+                                    summary_tx,
+                                ))
+                                .await;
+                            if let Ok(Err(e)) = summary_rx.await {
+                                error!("Failed to write summary of vnode {}: {:?}", vnode_id, e);
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            error!("Tiering job failed on vnode {}: {:?}", vnode_id, e);
+                        }
+                    }
+                }
+            }
+        });
+        info!("Tiering job started");
+    }
+}
+
+impl std::fmt::Debug for TieringJob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TieringJob").finish()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::sync::atomic::{self, AtomicI32};
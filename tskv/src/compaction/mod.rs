@@ -4,6 +4,7 @@ mod flush;
 mod iterator;
 pub mod job;
 mod picker;
+pub mod tiering;
 
 use std::sync::Arc;
 
@@ -0,0 +1,165 @@
+//! Move fully compacted tsm files to the cold tier, see [`crate::file_system::cold_store`].
+
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::sync::Arc;
+
+use models::Timestamp;
+use snafu::ResultExt;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use trace::{error, info};
+use utils::BloomFilter;
+
+use crate::context::GlobalContext;
+use crate::error::{self, Result};
+use crate::file_system::cold_store::{cold_object_key, ColdStore, ColdStub};
+use crate::file_system::file_manager;
+use crate::file_utils::{self, make_tsm_file};
+use crate::summary::{CompactMeta, VersionEdit};
+use crate::tseries_family::{ColumnFile, Version};
+use crate::{tsm, ColumnFileId};
+
+/// Pick tsm files in the max level whose data is older than `cold_before`, which is
+/// `now - compact_trigger_cold_duration` in the precision of the database.
+pub fn pick_cold_files(version: &Version, cold_before: Timestamp) -> Vec<Arc<ColumnFile>> {
+    let max_level = version.storage_opt().max_level as usize;
+    let level = match version.levels_info().get(max_level) {
+        Some(level) => level,
+        None => return vec![],
+    };
+
+    level
+        .files
+        .iter()
+        .filter(|f| !f.is_delta() && !f.is_compacting() && !f.is_deleted())
+        .filter(|f| f.time_range().max_ts < cold_before)
+        .cloned()
+        .collect()
+}
+
+/// Move tsm files to the cold tier.
+///
+/// Each file is uploaded to the cold store and replaced by a local stub with a new file id,
+/// the returned `VersionEdit` adds the stubs and deletes the moved files.
+pub async fn run_tiering_job(
+    version: Arc<Version>,
+    files: Vec<Arc<ColumnFile>>,
+    cold_store: Arc<ColdStore>,
+    kernel: Arc<GlobalContext>,
+) -> Result<Option<(VersionEdit, HashMap<ColumnFileId, Arc<BloomFilter>>)>> {
+    let tsf_id = version.tf_id();
+    let mut version_edit = VersionEdit::new(tsf_id);
+    let mut file_metas = HashMap::new();
+
+    for file in files {
+        if !file.mark_compacting() {
+            continue;
+        }
+        match move_to_cold_store(&version, &file, &cold_store, &kernel).await {
+            Ok(Some((compact_meta, bloom_filter))) => {
+                info!(
+                    "Tiering: moved tsm file {} of vnode {} to cold tier as file {}",
+                    file.file_id(),
+                    tsf_id,
+                    compact_meta.file_id
+                );
+                file_metas.insert(compact_meta.file_id, bloom_filter);
+                version_edit.add_file(compact_meta, version.max_level_ts());
+                version_edit.del_file(file.level(), file.file_id(), file.is_delta());
+            }
+            Ok(None) => file.unmark_compacting(),
+            Err(e) => {
+                error!(
+                    "Tiering: failed to move tsm file {} of vnode {} to cold tier: {:?}",
+                    file.file_id(),
+                    tsf_id,
+                    e
+                );
+                file.unmark_compacting();
+            }
+        }
+    }
+
+    if version_edit.add_files.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some((version_edit, file_metas)))
+}
+
+/// Upload a tsm file to the cold store and write it's local stub,
+/// returns None if the file is already in the cold tier.
+async fn move_to_cold_store(
+    version: &Version,
+    file: &ColumnFile,
+    cold_store: &ColdStore,
+    kernel: &GlobalContext,
+) -> Result<Option<(CompactMeta, Arc<BloomFilter>)>> {
+    let tsm_reader = version.get_tsm_reader2(file.file_path()).await?;
+    if tsm_reader.reader().is_cold() {
+        return Ok(None);
+    }
+    let index_offset = tsm_reader.footer().series().chunk_offset();
+    let bloom_filter = Arc::new(tsm_reader.footer().series().bloom_filter().clone());
+
+    // Only the index is read into memory, the data is uploaded in parts.
+    let mut tsm_file = tokio::fs::File::open(file.file_path())
+        .await
+        .context(error::IOSnafu)?;
+    let file_size = tsm_file.metadata().await.context(error::IOSnafu)?.len();
+    let mut index = Vec::with_capacity(file_size.saturating_sub(index_offset) as usize);
+    tsm_file
+        .seek(SeekFrom::Start(index_offset))
+        .await
+        .context(error::IOSnafu)?;
+    tsm_file
+        .read_to_end(&mut index)
+        .await
+        .context(error::IOSnafu)?;
+
+    let new_file_id = kernel.file_id_next();
+    let database = version.tenant_database();
+    let stub = ColdStub {
+        key: cold_object_key(&database, version.tf_id(), new_file_id),
+        index_offset,
+        file_size,
+    };
+    let stub_data = stub.encode(&index);
+    cold_store.upload_file(&stub.key, file.file_path()).await?;
+
+    let tsm_dir = version.storage_opt().tsm_dir(&database, version.tf_id());
+    let stub_path = make_tsm_file(&tsm_dir, new_file_id);
+    let tmp_path = stub_path.with_extension("tmp");
+    let write_stub = async {
+        tokio::fs::write(&tmp_path, &stub_data)
+            .await
+            .context(error::IOSnafu)?;
+        file_utils::rename(&tmp_path, &stub_path).await?;
+        // Tombstones are kept in local.
+        let tombstone_path = file.tombstone_path();
+        if file_manager::try_exists(&tombstone_path) {
+            let mut new_tombstone_path = stub_path.clone();
+            new_tombstone_path.set_extension(tsm::TOMBSTONE_FILE_SUFFIX);
+            tokio::fs::copy(&tombstone_path, &new_tombstone_path)
+                .await
+                .context(error::IOSnafu)?;
+        }
+        Ok::<(), crate::Error>(())
+    };
+    if let Err(e) = write_stub.await {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        let _ = tokio::fs::remove_file(&stub_path).await;
+        let _ = cold_store.delete(&stub.key).await;
+        return Err(e);
+    }
+
+    let compact_meta = CompactMeta {
+        file_id: new_file_id,
+        file_size: file.size(),
+        tsf_id: version.tf_id(),
+        level: file.level(),
+        min_ts: file.time_range().min_ts,
+        max_ts: file.time_range().max_ts,
+        ..Default::default()
+    };
+    Ok(Some((compact_meta, bloom_filter)))
+}
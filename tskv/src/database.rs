@@ -8,7 +8,7 @@ use memory_pool::MemoryPoolRef;
 use meta::model::MetaRef;
 use metrics::metric_register::MetricsRegister;
 use models::predicate::domain::TimeRange;
-use models::schema::{DatabaseSchema, Precision, TskvTableSchema, TskvTableSchemaRef};
+use models::schema::{DatabaseSchema, Precision, TableColumn, TskvTableSchema, TskvTableSchemaRef};
 use models::{SeriesId, SeriesKey};
 use protos::models::{Column, ColumnType, FieldType, Table};
 use snafu::ResultExt;
@@ -20,6 +20,7 @@ use utils::BloomFilter;
 
 use crate::compaction::CompactTask;
 use crate::error::{Result, SchemaSnafu};
+use crate::index::ts_index::SeriesLimit;
 use crate::index::{self, IndexResult};
 use crate::kv_option::{Options, INDEX_PATH};
 use crate::memcache::{OrderedRowsData, RowData, RowGroup};
@@ -307,10 +308,11 @@ impl Database {
                 range: TimeRange::none(),
                 size: size_of::<RowGroup>(),
             };
+            let dedup_policy = table_schema.dedup_policy();
             for row in rows {
                 row_group.range.merge(&TimeRange::new(row.ts, row.ts));
                 row_group.size += row.size();
-                row_group.rows.insert(row);
+                row_group.rows.insert_dedup(row, dedup_policy);
             }
             let res = map.insert(sid, (series_key_buf, row_group));
             // every sid of different table is different
@@ -319,6 +321,87 @@ impl Database {
         Ok(())
     }
 
+    /// Check whether writing the tables into the vnode index would exceed the series limits,
+    /// it's all or nothing for the tables. This is done by the raft leader before the points
+    /// are proposed, applying the points never fails on series limits.
+    pub async fn check_series_limit(
+        &self,
+        tables: FlatBufferTable<'_>,
+        ts_index: &index::ts_index::TSIndex,
+    ) -> Result<()> {
+        let series_limit = self.series_limit().await?;
+        if series_limit.is_unlimited() {
+            return Ok(());
+        }
+
+        let mut series_keys = vec![];
+        for table in tables {
+            let table_name = table.tab_ext()?;
+            let columns = table.columns().ok_or(Error::CommonError {
+                reason: "table missing columns".to_string(),
+            })?;
+            let fb_schema = FbSchema::from_fb_column(table_name, columns)?;
+
+            // Tags not in the schema yet are given temporary column ids,
+            // series with these tags are new series anyway.
+            let mut schema = match self.schemas.get_table_schema(fb_schema.table).await? {
+                Some(schema) => schema.as_ref().clone(),
+                None => TskvTableSchema::new(
+                    self.schemas.tenant_name().to_string(),
+                    self.schemas.database_name().to_string(),
+                    fb_schema.table.to_string(),
+                    vec![],
+                ),
+            };
+            for tag_name in fb_schema.tag_names.iter() {
+                if schema.column(tag_name).is_none() {
+                    let id = schema.next_column_id();
+                    schema.add_column(TableColumn::new_tag_column(id, tag_name.to_string()));
+                }
+            }
+
+            for row_count in 0..table.num_rows() as usize {
+                let series_key = SeriesKey::build_series_key(
+                    fb_schema.table,
+                    &columns,
+                    &schema,
+                    &fb_schema.tag_indexes,
+                    row_count,
+                )
+                .map_err(|e| Error::CommonError {
+                    reason: e.to_string(),
+                })?;
+                series_keys.push(series_key);
+            }
+        }
+
+        ts_index
+            .check_series_limit(&series_keys, &series_limit)
+            .await?;
+        Ok(())
+    }
+
+    /// Series limits of each vnode index. Series are distributed to the shards by hash,
+    /// so the limits of the database are divided by the number of shards.
+    ///
+    /// Each bucket (time range of `vnode_duration`) of the database has its own vnodes,
+    /// so the limits are checked per bucket per shard, not against the whole database.
+    async fn series_limit(&self) -> Result<SeriesLimit> {
+        let schema = self.schemas.db_schema().await?;
+        let options = schema.options();
+        let shard_num = options.shard_num_or_default().max(1);
+        let vnode_limit = |max_series: &Option<u64>| {
+            max_series
+                .filter(|max_series| *max_series > 0)
+                .map(|max_series| (max_series + shard_num - 1) / shard_num)
+        };
+
+        Ok(SeriesLimit {
+            max_series: vnode_limit(options.max_series()),
+            max_series_per_table: vnode_limit(options.max_series_per_table()),
+        })
+    }
+
     async fn build_index<'a>(
         fb_schema: &'a FbSchema<'a>,
         columns: &Vector<'a, ForwardsUOffset<Column<'a>>>,
@@ -463,7 +546,7 @@ impl Database {
 
         let path = self.opt.storage.index_dir(&self.owner, id);
 
-        let idx = index::ts_index::TSIndex::new(path).await?;
+        let idx = index::ts_index::TSIndex::new(path, self.opt.storage.index_engine).await?;
 
         self.ts_indexes.insert(id, idx.clone());
 
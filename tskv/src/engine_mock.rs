@@ -6,7 +6,7 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use datafusion::arrow::record_batch::RecordBatch;
 use models::meta_data::VnodeId;
-use models::predicate::domain::ColumnDomains;
+use models::predicate::domain::{ColumnDomains, TimeRange};
 use models::{SeriesId, SeriesKey};
 
 use crate::error::Result;
@@ -104,5 +104,36 @@ impl Engine for MockEngine {
         todo!()
     }
 
+    async fn get_vnode_series_checksum(&self, _vnode_id: VnodeId) -> Result<RecordBatch> {
+        todo!()
+    }
+
+    async fn get_vnode_series_data(
+        &self,
+        _vnode_id: VnodeId,
+        _table: &str,
+        _series_time_ranges: &[(SeriesKey, TimeRange)],
+    ) -> Result<RecordBatch> {
+        todo!()
+    }
+
+    async fn get_vnode_series_cardinality(
+        &self,
+        _vnode_id: VnodeId,
+        _top_n: usize,
+    ) -> Result<RecordBatch> {
+        todo!()
+    }
+
+    async fn check_series_limit(
+        &self,
+        _tenant: &str,
+        _database: &str,
+        _vnode_id: VnodeId,
+        _points: &[u8],
+    ) -> Result<()> {
+        Ok(())
+    }
+
     async fn close(&self) {}
 }
@@ -6,6 +6,7 @@ use error_code::{ErrorCode, ErrorCoder};
 use http_protocol::response::ErrorResponse;
 use meta::error::MetaError;
 use models::meta_data::VnodeId;
+use models::schema::ColumnType;
 use protos::PointsError;
 use snafu::Snafu;
 use tonic::{Code, Status};
@@ -128,6 +129,12 @@ pub enum Error {
         reason: String,
     },
 
+    #[snafu(display("Series limit exceeded: {}", reason))]
+    #[error_code(code = 17)]
+    SeriesLimitExceeded {
+        reason: String,
+    },
+
     // Internal Error
     #[snafu(display("{}", source))]
     IO {
@@ -352,7 +359,12 @@ impl From<SchemaError> for Error {
 
 impl From<IndexError> for Error {
     fn from(value: IndexError) -> Self {
-        Error::IndexErr { source: value }
+        match value {
+            IndexError::SeriesLimitExceeded { .. } => Error::SeriesLimitExceeded {
+                reason: value.to_string(),
+            },
+            other => Error::IndexErr { source: other },
+        }
     }
 }
 
@@ -423,6 +435,27 @@ impl Error {
         e.code() == code
     }
 
+    /// Whether the error is caused by writing a value whose type differs from
+    /// the existing column, the error may be returned by a remote node.
+    pub fn is_column_type_conflict(&self) -> bool {
+        match self {
+            Self::Schema {
+                source: SchemaError::ColumnTypeError { .. },
+            } => true,
+            Self::ErrorResponse { error } => {
+                let e = Self::Schema {
+                    source: SchemaError::ColumnTypeError {
+                        column: String::new(),
+                        found: ColumnType::Tag,
+                        expected: ColumnType::Tag,
+                    },
+                };
+                error.code() == e.code() && error.message().contains(" type error, found ")
+            }
+            _ => false,
+        }
+    }
+
     pub fn is_file_not_found_error(&self) -> bool {
         match self {
             Self::OpenFile { source, .. } => source.kind() == std::io::ErrorKind::NotFound,
@@ -0,0 +1,497 @@
+//! Cold tier of tsm files.
+//!
+//! A tsm file moved to the cold tier is uploaded to an object store, and the local file
+//! is replaced by a stub which only keeps the index of the tsm file (chunks, chunk groups,
+//! chunk group meta and footer), so the summary and the readers still work on local paths:
+//!
+//! ```text
+//! +------------------------------+-----+--------------+-----------+---------+-------+
+//! | tsm[index_offset..file_size] | key | index_offset | file_size | key_len | magic |
+//! +------------------------------+-----+--------------+-----------+---------+-------+
+//!                                        8 bytes        8 bytes     4 bytes   8 bytes
+//! ```
+//!
+//! Pages of a cold tsm file are fetched from the object store through a read cache.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use cache::{ShardedSyncCache, SyncCache};
+use futures::StreamExt;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path as ObjectPath;
+use object_store::ObjectStore;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use tokio::io::{AsyncWriteExt, BufReader};
+use trace::{error, info};
+
+use crate::file_system::file::async_file::AsyncFile;
+use crate::file_system::file::IFile;
+use crate::kv_option::StorageOptions;
+use crate::{ColumnFileId, Error, Result, TseriesFamilyId};
+
+const COLD_STUB_MAGIC: [u8; 8] = *b"CNOSCOLD";
+const COLD_STUB_TRAILER_SIZE: u64 = 8 + 8 + 4 + 8;
+/// Data of cold tsm files is fetched and cached in blocks of this size.
+const READ_CACHE_BLOCK_SIZE: u64 = 1024 * 1024;
+
+static COLD_STORES: Lazy<Mutex<HashMap<PathBuf, Arc<ColdStore>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Open the cold store configured by `storage.cold_tier_path`, returns None if the cold
+/// tier is disabled.
+pub fn open_cold_store(opt: &StorageOptions) -> Result<Option<Arc<ColdStore>>> {
+    let path = match &opt.cold_tier_path {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    let mut stores = COLD_STORES.lock();
+    if let Some(store) = stores.get(path) {
+        return Ok(Some(store.clone()));
+    }
+    let store = Arc::new(ColdStore::open(path, opt.cold_tier_read_cache_size)?);
+    stores.insert(path.clone(), store.clone());
+    info!("Opened cold tier at '{}'", path.display());
+    Ok(Some(store))
+}
+
+/// Get the cold store opened by [`open_cold_store`].
+pub fn get_cold_store(opt: &StorageOptions) -> Option<Arc<ColdStore>> {
+    let path = opt.cold_tier_path.as_ref()?;
+    COLD_STORES.lock().get(path).cloned()
+}
+
+/// Delete all tsm files of a vnode in the cold store, the local stubs are removed with the
+/// vnode directory when the vnode is dropped or replaced by a snapshot.
+pub async fn delete_vnode_files(
+    opt: &StorageOptions,
+    database: &str,
+    ts_family_id: TseriesFamilyId,
+) {
+    if let Some(store) = get_cold_store(opt) {
+        let prefix = cold_object_prefix(database, ts_family_id);
+        match store.delete_prefix(&prefix).await {
+            Ok(()) => info!("Removed cold tsm files '{prefix}'"),
+            Err(e) => error!("Failed to remove cold tsm files '{prefix}': {e}"),
+        }
+    }
+}
+
+/// Key prefix of all tsm files of a vnode in the cold store.
+pub fn cold_object_prefix(database: &str, ts_family_id: TseriesFamilyId) -> String {
+    format!("{database}/{ts_family_id}")
+}
+
+/// Key of a tsm file in the cold store.
+pub fn cold_object_key(
+    database: &str,
+    ts_family_id: TseriesFamilyId,
+    file_id: ColumnFileId,
+) -> String {
+    format!(
+        "{}/_{file_id:06}.tsm",
+        cold_object_prefix(database, ts_family_id)
+    )
+}
+
+pub struct ColdStore {
+    store: Arc<dyn ObjectStore>,
+    /// Key is (object key, block index).
+    read_cache: ShardedSyncCache<(String, u64), Bytes>,
+}
+
+impl ColdStore {
+    pub fn open(path: impl AsRef<Path>, read_cache_size: u64) -> Result<Self> {
+        let path = path.as_ref();
+        std::fs::create_dir_all(path).map_err(|e| Error::OpenFile {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        let store = LocalFileSystem::new_with_prefix(path).map_err(|e| Error::CommonError {
+            reason: format!("failed to open cold tier '{}': {e}", path.display()),
+        })?;
+        let cache_blocks = (read_cache_size / READ_CACHE_BLOCK_SIZE).max(1) as usize;
+
+        Ok(Self {
+            store: Arc::new(store),
+            read_cache: ShardedSyncCache::create_lru_sharded_cache(cache_blocks),
+        })
+    }
+
+    pub async fn upload(&self, key: &str, data: Bytes) -> Result<()> {
+        self.store
+            .put(&ObjectPath::from(key), data)
+            .await
+            .map_err(|e| Error::CommonError {
+                reason: format!("failed to upload '{key}' to cold tier: {e}"),
+            })
+    }
+
+    /// Upload a local file in parts, the file is not read into memory at once.
+    pub async fn upload_file(&self, key: &str, path: impl AsRef<Path>) -> Result<()> {
+        let location = ObjectPath::from(key);
+        let (multipart_id, mut writer) =
+            self.store
+                .put_multipart(&location)
+                .await
+                .map_err(|e| Error::CommonError {
+                    reason: format!("failed to upload '{key}' to cold tier: {e}"),
+                })?;
+        let upload = async {
+            let file = tokio::fs::File::open(path.as_ref()).await?;
+            let mut reader = BufReader::with_capacity(READ_CACHE_BLOCK_SIZE as usize, file);
+            tokio::io::copy_buf(&mut reader, &mut writer).await?;
+            writer.shutdown().await
+        };
+        if let Err(e) = upload.await {
+            let _ = self.store.abort_multipart(&location, &multipart_id).await;
+            return Err(Error::CommonError {
+                reason: format!("failed to upload '{key}' to cold tier: {e}"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Download an object to a local file, the object is not read into memory at once.
+    pub async fn download_file(&self, key: &str, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let download_error = |e: &dyn std::fmt::Display| Error::CommonError {
+            reason: format!(
+                "failed to download '{key}' from cold tier to '{}': {e}",
+                path.display()
+            ),
+        };
+        let mut stream = self
+            .store
+            .get(&ObjectPath::from(key))
+            .await
+            .map_err(|e| download_error(&e))?
+            .into_stream();
+        let mut file = tokio::fs::File::create(path)
+            .await
+            .map_err(|e| download_error(&e))?;
+        while let Some(data) = stream.next().await {
+            let data = data.map_err(|e| download_error(&e))?;
+            file.write_all(&data)
+                .await
+                .map_err(|e| download_error(&e))?;
+        }
+        file.sync_all().await.map_err(|e| download_error(&e))
+    }
+
+    /// Delete all objects with the key prefix, e.g. all tsm files of a vnode.
+    pub async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        let list_error = |e: object_store::Error| Error::CommonError {
+            reason: format!("failed to list '{prefix}' in cold tier: {e}"),
+        };
+        let keys = self
+            .store
+            .list(Some(&ObjectPath::from(prefix)))
+            .await
+            .map_err(list_error)?
+            .map(|meta| meta.map(|m| m.location.to_string()))
+            .collect::<Vec<_>>()
+            .await;
+        for key in keys {
+            self.delete(&key.map_err(list_error)?).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn delete(&self, key: &str) -> Result<()> {
+        match self.store.delete(&ObjectPath::from(key)).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(Error::CommonError {
+                reason: format!("failed to delete '{key}' from cold tier: {e}"),
+            }),
+        }
+    }
+
+    /// Read data of an object at `pos`, the data is cached in blocks.
+    pub async fn read_at(
+        &self,
+        key: &str,
+        object_size: u64,
+        pos: u64,
+        data: &mut [u8],
+    ) -> io::Result<usize> {
+        let end = object_size.min(pos + data.len() as u64);
+        let mut read_pos = pos;
+        while read_pos < end {
+            let block_index = read_pos / READ_CACHE_BLOCK_SIZE;
+            let block = self.read_block(key, object_size, block_index).await?;
+            let block_offset = (read_pos - block_index * READ_CACHE_BLOCK_SIZE) as usize;
+            let len = (end - read_pos).min((block.len() - block_offset) as u64) as usize;
+            if len == 0 {
+                break;
+            }
+            let data_offset = (read_pos - pos) as usize;
+            data[data_offset..data_offset + len]
+                .copy_from_slice(&block[block_offset..block_offset + len]);
+            read_pos += len as u64;
+        }
+        Ok((read_pos - pos) as usize)
+    }
+
+    async fn read_block(&self, key: &str, object_size: u64, block_index: u64) -> io::Result<Bytes> {
+        let cache_key = (key.to_string(), block_index);
+        if let Some(block) = self.read_cache.get(&cache_key) {
+            return Ok(block);
+        }
+        let start = block_index * READ_CACHE_BLOCK_SIZE;
+        let end = object_size.min(start + READ_CACHE_BLOCK_SIZE);
+        let block = self
+            .store
+            .get_range(&ObjectPath::from(key), start as usize..end as usize)
+            .await
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        self.read_cache.insert(cache_key, block.clone());
+        Ok(block)
+    }
+}
+
+impl std::fmt::Debug for ColdStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ColdStore")
+            .field("store", &self.store.to_string())
+            .finish()
+    }
+}
+
+/// Trailer of the local stub of a cold tsm file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColdStub {
+    /// Key of the tsm file in the cold store.
+    pub key: String,
+    /// Offset of the index in the tsm file, the index is kept in the stub.
+    pub index_offset: u64,
+    /// Size of the tsm file.
+    pub file_size: u64,
+}
+
+impl ColdStub {
+    /// Encode the stub file, `index` is the data of tsm file from `index_offset` to the end.
+    pub fn encode(&self, index: &[u8]) -> Vec<u8> {
+        let mut buf =
+            Vec::with_capacity(index.len() + self.key.len() + COLD_STUB_TRAILER_SIZE as usize);
+        buf.extend_from_slice(index);
+        buf.extend_from_slice(self.key.as_bytes());
+        buf.extend_from_slice(&self.index_offset.to_be_bytes());
+        buf.extend_from_slice(&self.file_size.to_be_bytes());
+        buf.extend_from_slice(&(self.key.len() as u32).to_be_bytes());
+        buf.extend_from_slice(&COLD_STUB_MAGIC);
+        buf
+    }
+
+    fn decode_trailer(trailer: &[u8]) -> Option<(u64, u64, usize)> {
+        if trailer.len() != COLD_STUB_TRAILER_SIZE as usize || trailer[20..] != COLD_STUB_MAGIC {
+            return None;
+        }
+        let index_offset = u64::from_be_bytes(trailer[0..8].try_into().ok()?);
+        let file_size = u64::from_be_bytes(trailer[8..16].try_into().ok()?);
+        let key_len = u32::from_be_bytes(trailer[16..20].try_into().ok()?) as usize;
+        Some((index_offset, file_size, key_len))
+    }
+
+    /// Read the stub of a local tsm file, returns None if it's not a cold tsm file.
+    pub async fn read(file: &AsyncFile) -> Result<Option<Self>> {
+        if file.len() < COLD_STUB_TRAILER_SIZE {
+            return Ok(None);
+        }
+        let mut trailer = [0_u8; COLD_STUB_TRAILER_SIZE as usize];
+        file.read_at(file.len() - COLD_STUB_TRAILER_SIZE, &mut trailer)
+            .await?;
+        let (index_offset, file_size, key_len) = match Self::decode_trailer(&trailer) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        let key_pos = file
+            .len()
+            .checked_sub(COLD_STUB_TRAILER_SIZE + key_len as u64)
+            .ok_or_else(|| Error::CommonError {
+                reason: "invalid cold tsm file stub".to_string(),
+            })?;
+        let mut key = vec![0_u8; key_len];
+        file.read_at(key_pos, &mut key).await?;
+
+        Ok(Some(Self {
+            key: String::from_utf8_lossy(&key).to_string(),
+            index_offset,
+            file_size,
+        }))
+    }
+
+    /// Read the key of a cold tsm file from it's local stub.
+    pub fn read_key_sync(path: impl AsRef<Path>) -> io::Result<Option<String>> {
+        let mut file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < COLD_STUB_TRAILER_SIZE {
+            return Ok(None);
+        }
+        let mut trailer = [0_u8; COLD_STUB_TRAILER_SIZE as usize];
+        file.seek(SeekFrom::Start(len - COLD_STUB_TRAILER_SIZE))?;
+        file.read_exact(&mut trailer)?;
+        let key_len = match Self::decode_trailer(&trailer) {
+            Some((_, _, key_len)) if key_len as u64 + COLD_STUB_TRAILER_SIZE <= len => key_len,
+            _ => return Ok(None),
+        };
+        let mut key = vec![0_u8; key_len];
+        file.seek(SeekFrom::Start(
+            len - COLD_STUB_TRAILER_SIZE - key_len as u64,
+        ))?;
+        file.read_exact(&mut key)?;
+        Ok(Some(String::from_utf8_lossy(&key).to_string()))
+    }
+}
+
+/// A tsm file in the cold tier, the index is read from the local stub,
+/// and pages are read from the cold store.
+pub struct ColdFile {
+    stub_file: AsyncFile,
+    stub: ColdStub,
+    store: Option<Arc<ColdStore>>,
+}
+
+impl ColdFile {
+    pub fn new(stub_file: AsyncFile, stub: ColdStub, store: Option<Arc<ColdStore>>) -> Self {
+        Self {
+            stub_file,
+            stub,
+            store,
+        }
+    }
+
+    pub fn stub(&self) -> &ColdStub {
+        &self.stub
+    }
+
+    pub async fn read_at(&self, pos: u64, data: &mut [u8]) -> io::Result<usize> {
+        let index_offset = self.stub.index_offset;
+        let mut read = 0;
+        if pos < index_offset {
+            let store = self.store.as_ref().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("cold tier is not enabled to read '{}'", self.stub.key),
+                )
+            })?;
+            let len = data.len().min((index_offset - pos) as usize);
+            read = store
+                .read_at(&self.stub.key, self.stub.file_size, pos, &mut data[..len])
+                .await?;
+            if read < len {
+                return Ok(read);
+            }
+        }
+        if read < data.len() {
+            let stub_pos = pos + read as u64 - index_offset;
+            let index_len = self.stub.file_size - index_offset;
+            let len = (data.len() - read).min(index_len.saturating_sub(stub_pos) as usize);
+            read += self
+                .stub_file
+                .read_at(stub_pos, &mut data[read..read + len])
+                .await?;
+        }
+        Ok(read)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.stub.file_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stub.file_size == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+
+    use super::{cold_object_prefix, ColdFile, ColdStore, ColdStub, READ_CACHE_BLOCK_SIZE};
+    use crate::file_system::file_manager;
+
+    #[tokio::test]
+    async fn test_cold_file() {
+        let dir = "/tmp/test/cold_store/test_cold_file";
+        let _ = std::fs::remove_dir_all(dir);
+        let store =
+            Arc::new(ColdStore::open(format!("{dir}/cold"), 4 * READ_CACHE_BLOCK_SIZE).unwrap());
+
+        let data: Vec<u8> = (0..READ_CACHE_BLOCK_SIZE * 2 + 100)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let index_offset = READ_CACHE_BLOCK_SIZE + 10;
+        let key = "db/1/_000001.tsm".to_string();
+        store.upload(&key, Bytes::from(data.clone())).await.unwrap();
+
+        let stub = ColdStub {
+            key: key.clone(),
+            index_offset,
+            file_size: data.len() as u64,
+        };
+        let stub_path = format!("{dir}/_000001.tsm");
+        std::fs::write(&stub_path, stub.encode(&data[index_offset as usize..])).unwrap();
+        assert_eq!(ColdStub::read_key_sync(&stub_path).unwrap(), Some(key));
+
+        let stub_file = file_manager::open_file(&stub_path).await.unwrap();
+        assert_eq!(
+            ColdStub::read(&stub_file).await.unwrap(),
+            Some(stub.clone())
+        );
+        let file = ColdFile::new(stub_file, stub, Some(store.clone()));
+        assert_eq!(file.len(), data.len() as u64);
+
+        // Read across blocks of the read cache, and across the cold store and the stub.
+        for (pos, len) in [
+            (0, 100),
+            (READ_CACHE_BLOCK_SIZE - 5, 10),
+            (index_offset - 5, 10),
+            (index_offset, 90),
+        ] {
+            let mut buf = vec![0_u8; len as usize];
+            let read = file.read_at(pos, &mut buf).await.unwrap();
+            assert_eq!(read, len as usize);
+            assert_eq!(buf, data[pos as usize..(pos + len) as usize]);
+        }
+
+        store.delete(&file.stub().key).await.unwrap();
+        store.delete(&file.stub().key).await.unwrap();
+
+        // Upload and download in parts.
+        let local_path = format!("{dir}/upload");
+        std::fs::write(&local_path, &data).unwrap();
+        store.upload_file(&key, &local_path).await.unwrap();
+        let download_path = format!("{dir}/download");
+        store.download_file(&key, &download_path).await.unwrap();
+        assert_eq!(std::fs::read(&download_path).unwrap(), data);
+
+        // Delete all objects of a vnode.
+        let other_key = "db/2/_000001.tsm";
+        store
+            .upload(other_key, Bytes::from(data.clone()))
+            .await
+            .unwrap();
+        store
+            .delete_prefix(&cold_object_prefix("db", 1))
+            .await
+            .unwrap();
+        assert!(store.download_file(&key, &download_path).await.is_err());
+        store
+            .download_file(other_key, &download_path)
+            .await
+            .unwrap();
+
+        let local_path = format!("{dir}/local");
+        std::fs::write(&local_path, &data).unwrap();
+        let local_file = file_manager::open_file(&local_path).await.unwrap();
+        assert_eq!(ColdStub::read(&local_file).await.unwrap(), None);
+        assert_eq!(ColdStub::read_key_sync(&local_path).unwrap(), None);
+    }
+}
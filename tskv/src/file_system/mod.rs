@@ -6,6 +6,7 @@
 use async_trait::async_trait;
 use tokio::fs::File;
 
+pub mod cold_store;
 pub(crate) mod file;
 pub mod file_info;
 pub mod file_manager;
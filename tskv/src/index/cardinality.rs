@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use datafusion::arrow::array::{StringBuilder, UInt32Builder, UInt64Builder};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use models::meta_data::VnodeId;
+use models::schema::TskvTableSchemaRef;
+
+use super::ts_index::TSIndex;
+use crate::Result;
+
+pub const CARDINALITY_VNODE_ID: &str = "vnode_id";
+pub const CARDINALITY_TABLE_NAME: &str = "table_name";
+pub const CARDINALITY_TAG_KEY: &str = "tag_key";
+pub const CARDINALITY_TAG_VALUE: &str = "tag_value";
+pub const CARDINALITY_SERIES_COUNT: &str = "series_count";
+pub const CARDINALITY_TAG_VALUE_COUNT: &str = "tag_value_count";
+
+/// Schema of the series cardinality of a vnode, there are three kinds of rows:
+///
+/// | tag_key | tag_value | series_count                | tag_value_count        |
+/// | ------- | --------- | --------------------------- | ---------------------- |
+/// | NULL    | NULL      | series of the table         | NULL                   |
+/// | key     | NULL      | series with the tag key     | distinct values of key |
+/// | key     | value     | series with the tag value   | NULL                   |
+///
+/// Only the top-N values with the most series of each tag key are returned.
+pub fn series_cardinality_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new(CARDINALITY_VNODE_ID, DataType::UInt32, false),
+        Field::new(CARDINALITY_TABLE_NAME, DataType::Utf8, false),
+        Field::new(CARDINALITY_TAG_KEY, DataType::Utf8, true),
+        Field::new(CARDINALITY_TAG_VALUE, DataType::Utf8, true),
+        Field::new(CARDINALITY_SERIES_COUNT, DataType::UInt64, false),
+        Field::new(CARDINALITY_TAG_VALUE_COUNT, DataType::UInt64, true),
+    ]))
+}
+
+#[derive(Default)]
+struct SeriesCardinalityBuilder {
+    vnode_ids: UInt32Builder,
+    table_names: StringBuilder,
+    tag_keys: StringBuilder,
+    tag_values: StringBuilder,
+    series_counts: UInt64Builder,
+    tag_value_counts: UInt64Builder,
+}
+
+impl SeriesCardinalityBuilder {
+    fn append_row(
+        &mut self,
+        vnode_id: VnodeId,
+        table_name: &str,
+        tag_key: Option<&str>,
+        tag_value: Option<&str>,
+        series_count: u64,
+        tag_value_count: Option<u64>,
+    ) {
+        self.vnode_ids.append_value(vnode_id);
+        self.table_names.append_value(table_name);
+        self.tag_keys.append_option(tag_key);
+        self.tag_values.append_option(tag_value);
+        self.series_counts.append_value(series_count);
+        self.tag_value_counts.append_option(tag_value_count);
+    }
+
+    fn finish(mut self) -> Result<RecordBatch> {
+        let batch = RecordBatch::try_new(
+            series_cardinality_schema(),
+            vec![
+                Arc::new(self.vnode_ids.finish()),
+                Arc::new(self.table_names.finish()),
+                Arc::new(self.tag_keys.finish()),
+                Arc::new(self.tag_values.finish()),
+                Arc::new(self.series_counts.finish()),
+                Arc::new(self.tag_value_counts.finish()),
+            ],
+        )?;
+        Ok(batch)
+    }
+}
+
+/// Compute the series cardinality of the tables in the index of a vnode,
+/// see [`series_cardinality_schema`].
+pub async fn series_cardinality(
+    vnode_id: VnodeId,
+    ts_index: &TSIndex,
+    tables: &[TskvTableSchemaRef],
+    top_n: usize,
+) -> Result<RecordBatch> {
+    let mut builder = SeriesCardinalityBuilder::default();
+    for table in tables {
+        let series_count = ts_index.series_count(&table.name).await?;
+        if series_count == 0 {
+            continue;
+        }
+        builder.append_row(vnode_id, &table.name, None, None, series_count, None);
+
+        for column in table.columns().iter().filter(|c| c.column_type.is_tag()) {
+            // Tag keys in the index are ids of the tag columns.
+            let tag_key = column.id.to_string();
+            let cardinality = ts_index
+                .tag_cardinality(&table.name, tag_key.as_bytes(), top_n)
+                .await?;
+            if cardinality.value_count == 0 {
+                continue;
+            }
+            builder.append_row(
+                vnode_id,
+                &table.name,
+                Some(&column.name),
+                None,
+                cardinality.series_count,
+                Some(cardinality.value_count),
+            );
+            for (value, count) in cardinality.top_values {
+                builder.append_row(
+                    vnode_id,
+                    &table.name,
+                    Some(&column.name),
+                    Some(&String::from_utf8_lossy(&value)),
+                    count,
+                    None,
+                );
+            }
+        }
+    }
+
+    builder.finish()
+}
@@ -0,0 +1,280 @@
+mod radix_engine;
+mod sled_engine;
+
+use std::fmt::Debug;
+use std::fs;
+use std::ops::{Bound, RangeBounds};
+use std::path::Path;
+
+pub use config::IndexEngineType;
+pub use radix_engine::RadixIndexEngine;
+pub use sled_engine::SledIndexEngine;
+use trace::info;
+
+use super::{IndexError, IndexResult};
+
+pub type KeyRange = (Bound<Vec<u8>>, Bound<Vec<u8>>);
+
+pub type KeyValueIter<'a> = Box<dyn Iterator<Item = IndexResult<(Vec<u8>, Vec<u8>)>> + 'a>;
+
+/// Key-value storage of the series index, keys are iterated in ascending order.
+pub trait IndexEngine: Send + Sync + Debug {
+    fn engine_type(&self) -> IndexEngineType;
+
+    fn get(&self, key: &[u8]) -> IndexResult<Option<Vec<u8>>>;
+
+    fn set(&mut self, key: &[u8], value: &[u8]) -> IndexResult<()>;
+
+    fn delete(&mut self, key: &[u8]) -> IndexResult<()>;
+
+    fn exist(&self, key: &[u8]) -> IndexResult<bool> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    fn range(&self, range: KeyRange) -> IndexResult<KeyValueIter<'_>>;
+
+    fn prefix<'a>(&'a self, prefix: &'a [u8]) -> IndexResult<KeyValueIter<'a>>;
+
+    /// Persist all changes to disk.
+    fn flush(&mut self) -> IndexResult<()>;
+
+    fn get_rb(&self, key: &[u8]) -> IndexResult<Option<roaring::RoaringBitmap>> {
+        match self.get(key)? {
+            Some(data) => Ok(Some(self.load_rb(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn load_rb(&self, data: &[u8]) -> IndexResult<roaring::RoaringBitmap> {
+        roaring::RoaringBitmap::deserialize_from(data)
+            .map_err(|e| IndexError::RoaringBitmap { source: e })
+    }
+
+    fn build_revert_index(&self, key: &[u8], id: u32, add: bool) -> IndexResult<Vec<u8>> {
+        let mut rb = match self.get(key)? {
+            Some(val) => roaring::RoaringBitmap::deserialize_from(&*val)
+                .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?,
+
+            None => roaring::RoaringBitmap::new(),
+        };
+
+        if add {
+            rb.insert(id);
+        } else {
+            rb.remove(id);
+        }
+
+        let mut bytes = vec![];
+        rb.serialize_into(&mut bytes)
+            .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?;
+
+        Ok(bytes)
+    }
+
+    fn modify(&mut self, key: &[u8], id: u32, add: bool) -> IndexResult<()> {
+        let bytes = self.build_revert_index(key, id, add)?;
+        self.set(key, &bytes)
+    }
+}
+
+pub fn to_key_range(range: &impl RangeBounds<Vec<u8>>) -> KeyRange {
+    (range.start_bound().cloned(), range.end_bound().cloned())
+}
+
+fn has_index_data(path: &Path, engine_type: IndexEngineType) -> bool {
+    match engine_type {
+        IndexEngineType::Radix => RadixIndexEngine::has_data(path),
+        IndexEngineType::Sled => SledIndexEngine::has_data(path),
+    }
+}
+
+/// Returns the engine of the index stored in the directory, the configured engine
+/// is preferred if there are more than one, `None` if the directory holds no index.
+pub fn detect_index_engine(
+    path: impl AsRef<Path>,
+    preferred: IndexEngineType,
+) -> Option<IndexEngineType> {
+    let path = path.as_ref();
+    [preferred, IndexEngineType::Radix, IndexEngineType::Sled]
+        .into_iter()
+        .find(|t| has_index_data(path, *t))
+}
+
+fn new_index_engine(
+    path: &Path,
+    engine_type: IndexEngineType,
+) -> IndexResult<Box<dyn IndexEngine>> {
+    let engine: Box<dyn IndexEngine> = match engine_type {
+        IndexEngineType::Radix => Box::new(RadixIndexEngine::new(path)?),
+        IndexEngineType::Sled => Box::new(SledIndexEngine::new(path)?),
+    };
+    Ok(engine)
+}
+
+/// Open the index in the directory by the engine, the index stored by another
+/// engine must be migrated by [`migrate_index_engine`] first.
+pub fn open_index_engine(
+    path: impl AsRef<Path>,
+    engine_type: IndexEngineType,
+) -> IndexResult<Box<dyn IndexEngine>> {
+    let path = path.as_ref();
+    match detect_index_engine(path, engine_type) {
+        Some(found) if found != engine_type => Err(IndexError::IndexEngineMismatch {
+            dir: path.display().to_string(),
+            found: found.to_string(),
+            expected: engine_type.to_string(),
+        }),
+        _ => new_index_engine(path, engine_type),
+    }
+}
+
+/// Copy the index in the directory to the engine offline, the index must not be opened,
+/// e.g. the node is stopped or the index is installed from a snapshot.
+///
+/// The index is copied to a temporary directory and then moved into the directory,
+/// the source is removed at last, so it's safe to run again after an interruption.
+/// Returns the number of keys copied.
+pub fn migrate_index_engine(path: impl AsRef<Path>, to: IndexEngineType) -> IndexResult<u64> {
+    let path = path.as_ref();
+    let from = match detect_index_engine(path, to) {
+        Some(from) if from != to => from,
+        // No index or it was migrated, remove remains of the interrupted migration.
+        _ => {
+            for from in [IndexEngineType::Radix, IndexEngineType::Sled] {
+                if from != to {
+                    remove_index_data(path, from)?;
+                }
+            }
+            return Ok(0);
+        }
+    };
+
+    let tmp_dir = path.join("migrating");
+    if tmp_dir.exists() {
+        fs::remove_dir_all(&tmp_dir)?;
+    }
+    let mut count = 0;
+    {
+        let source = new_index_engine(path, from)?;
+        let mut target = new_index_engine(&tmp_dir, to)?;
+        for item in source.prefix(&[])? {
+            let (key, value) = item?;
+            target.set(&key, &value)?;
+            count += 1;
+        }
+        target.flush()?;
+    }
+    match to {
+        IndexEngineType::Radix => RadixIndexEngine::move_data(&tmp_dir, path)?,
+        IndexEngineType::Sled => SledIndexEngine::move_data(&tmp_dir, path)?,
+    }
+    fs::remove_dir_all(&tmp_dir)?;
+    remove_index_data(path, from)?;
+    info!(
+        "Migrated {count} keys of index '{}' from {from} to {to}",
+        path.display()
+    );
+
+    Ok(count)
+}
+
+fn remove_index_data(path: &Path, engine_type: IndexEngineType) -> IndexResult<()> {
+    match engine_type {
+        IndexEngineType::Radix => RadixIndexEngine::remove_data(path),
+        IndexEngineType::Sled => SledIndexEngine::remove_data(path),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::ops::Bound;
+
+    use super::{
+        migrate_index_engine, open_index_engine, IndexEngine, IndexEngineType, KeyValueIter,
+    };
+    use crate::index::IndexError;
+
+    fn collect(iter: KeyValueIter) -> Vec<(Vec<u8>, Vec<u8>)> {
+        iter.map(|item| item.unwrap()).collect()
+    }
+
+    fn check_engine(engine: &mut dyn IndexEngine) {
+        for key in ["a.1", "a.2", "a.3", "b.1"] {
+            engine.set(key.as_bytes(), key.as_bytes()).unwrap();
+        }
+        engine.delete(b"a.3").unwrap();
+        engine.flush().unwrap();
+
+        assert_eq!(engine.get(b"a.1").unwrap(), Some(b"a.1".to_vec()));
+        assert_eq!(engine.get(b"a.3").unwrap(), None);
+        assert!(engine.exist(b"b.1").unwrap());
+        assert!(!engine.exist(b"b").unwrap());
+
+        let keys = collect(engine.prefix(b"a.").unwrap())
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![b"a.1".to_vec(), b"a.2".to_vec()]);
+
+        let range = (
+            Bound::Excluded(b"a.1".to_vec()),
+            Bound::Included(b"b.1".to_vec()),
+        );
+        let keys = collect(engine.range(range).unwrap())
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![b"a.2".to_vec(), b"b.1".to_vec()]);
+
+        engine.modify(b"rb", 1, true).unwrap();
+        engine.modify(b"rb", 2, true).unwrap();
+        engine.modify(b"rb", 1, false).unwrap();
+        let rb = engine.get_rb(b"rb").unwrap().unwrap();
+        assert_eq!(rb.iter().collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_index_engines() {
+        let dir = tempfile::tempdir().unwrap();
+        for engine_type in [IndexEngineType::Radix, IndexEngineType::Sled] {
+            let path = dir.path().join(engine_type.to_string());
+            let mut engine = open_index_engine(&path, engine_type).unwrap();
+            assert_eq!(engine.engine_type(), engine_type);
+            check_engine(engine.as_mut());
+        }
+    }
+
+    #[test]
+    fn test_migrate_index_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        {
+            let mut engine = open_index_engine(path, IndexEngineType::Radix).unwrap();
+            for i in 0..100_u32 {
+                engine.set(&i.to_be_bytes(), &i.to_le_bytes()).unwrap();
+            }
+            engine.flush().unwrap();
+        }
+
+        let err = open_index_engine(path, IndexEngineType::Sled).unwrap_err();
+        assert!(matches!(err, IndexError::IndexEngineMismatch { .. }));
+
+        assert_eq!(
+            migrate_index_engine(path, IndexEngineType::Sled).unwrap(),
+            100
+        );
+        assert_eq!(
+            migrate_index_engine(path, IndexEngineType::Sled).unwrap(),
+            0
+        );
+        assert!(open_index_engine(path, IndexEngineType::Radix).is_err());
+
+        let engine = open_index_engine(path, IndexEngineType::Sled).unwrap();
+        let items = collect(engine.prefix(&[]).unwrap());
+        assert_eq!(items.len(), 100);
+        for (i, (key, value)) in items.into_iter().enumerate() {
+            assert_eq!(key, (i as u32).to_be_bytes());
+            assert_eq!(value, (i as u32).to_le_bytes());
+        }
+    }
+}
@@ -1,5 +1,4 @@
 use std::fs;
-use std::ops::RangeBounds;
 use std::path::{Path, PathBuf};
 
 use radixdb;
@@ -7,23 +6,26 @@ use radixdb::store;
 use radixdb::store::BlobStore;
 use trace::debug;
 
-use super::{IndexError, IndexResult};
+use super::{IndexEngine, IndexEngineType, KeyRange, KeyValueIter};
+use crate::index::{IndexError, IndexResult};
+
+const INDEX_FILE: &str = "index.db";
 
 #[derive(Debug)]
-pub struct IndexEngine {
+pub struct RadixIndexEngine {
     dir: PathBuf,
 
     db: radixdb::RadixTree<store::PagedFileStore>,
     store: store::PagedFileStore,
 }
 
-impl IndexEngine {
+impl RadixIndexEngine {
     pub fn new(path: impl AsRef<Path>) -> IndexResult<Self> {
         let path = path.as_ref();
         let _ = fs::create_dir_all(path);
         debug!("Creating index engine : {:?}", &path);
 
-        let db_path = path.join("index.db");
+        let db_path = path.join(INDEX_FILE);
         let file = fs::OpenOptions::new()
             .create(true)
             .read(true)
@@ -43,28 +45,21 @@ impl IndexEngine {
         })
     }
 
-    pub fn set(&mut self, key: &[u8], value: &[u8]) -> IndexResult<()> {
-        self.db
-            .try_insert(key, value)
-            .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?;
+    pub(super) fn has_data(path: &Path) -> bool {
+        fs::metadata(path.join(INDEX_FILE)).map_or(false, |m| m.len() > 0)
+    }
 
+    pub(super) fn move_data(from: &Path, to: &Path) -> IndexResult<()> {
+        fs::rename(from.join(INDEX_FILE), to.join(INDEX_FILE))?;
         Ok(())
     }
 
-    pub fn get(&self, key: &[u8]) -> IndexResult<Option<Vec<u8>>> {
-        let val = self
-            .db
-            .try_get(key)
-            .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?;
-
-        match val {
-            Some(v) => {
-                let data = self.load(&v)?;
-
-                Ok(Some(data))
-            }
-            None => Ok(None),
+    pub(super) fn remove_data(path: &Path) -> IndexResult<()> {
+        let db_path = path.join(INDEX_FILE);
+        if db_path.exists() {
+            fs::remove_file(db_path)?;
         }
+        Ok(())
     }
 
     pub fn load(&self, val: &radixdb::node::Value<store::PagedFileStore>) -> IndexResult<Vec<u8>> {
@@ -75,74 +70,48 @@ impl IndexEngine {
         Ok(blob.to_vec())
     }
 
-    pub fn get_rb(&self, key: &[u8]) -> IndexResult<Option<roaring::RoaringBitmap>> {
-        if let Some(data) = self.get(key)? {
-            let rb = roaring::RoaringBitmap::deserialize_from(&*data)
-                .map_err(|e| IndexError::RoaringBitmap { source: e })?;
+    pub fn combine(&mut self, tree: radixdb::RadixTree) -> IndexResult<()> {
+        self.db
+            .try_outer_combine_with(&tree, radixdb::node::DetachConverter, |a, b| {
+                a.set(Some(b.downcast()));
+                Ok(())
+            })
+            .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?;
 
-            Ok(Some(rb))
-        } else {
-            Ok(None)
-        }
+        Ok(())
     }
+}
 
-    pub fn load_rb(
-        &self,
-        val: &radixdb::node::Value<store::PagedFileStore>,
-    ) -> IndexResult<roaring::RoaringBitmap> {
-        let data = self.load(val)?;
-
-        let rb = roaring::RoaringBitmap::deserialize_from(&*data)
-            .map_err(|e| IndexError::RoaringBitmap { source: e })?;
-
-        Ok(rb)
+impl IndexEngine for RadixIndexEngine {
+    fn engine_type(&self) -> IndexEngineType {
+        IndexEngineType::Radix
     }
 
-    pub fn build_revert_index(&self, key: &[u8], id: u32, add: bool) -> IndexResult<Vec<u8>> {
-        let mut rb = match self.get(key)? {
-            Some(val) => roaring::RoaringBitmap::deserialize_from(&*val)
-                .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?,
-
-            None => roaring::RoaringBitmap::new(),
-        };
-
-        if add {
-            rb.insert(id);
-        } else {
-            rb.remove(id);
-        }
-
-        let mut bytes = vec![];
-        rb.serialize_into(&mut bytes)
+    fn set(&mut self, key: &[u8], value: &[u8]) -> IndexResult<()> {
+        self.db
+            .try_insert(key, value)
             .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?;
 
-        Ok(bytes)
+        Ok(())
     }
 
-    pub fn modify(&mut self, key: &[u8], id: u32, add: bool) -> IndexResult<()> {
-        let mut rb = match self.get(key)? {
-            Some(val) => roaring::RoaringBitmap::deserialize_from(&*val)
-                .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?,
-
-            None => roaring::RoaringBitmap::new(),
-        };
-
-        if add {
-            rb.insert(id);
-        } else {
-            rb.remove(id);
-        }
-
-        let mut bytes = vec![];
-        rb.serialize_into(&mut bytes)
+    fn get(&self, key: &[u8]) -> IndexResult<Option<Vec<u8>>> {
+        let val = self
+            .db
+            .try_get(key)
             .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?;
 
-        self.set(key, &bytes)?;
+        match val {
+            Some(v) => {
+                let data = self.load(&v)?;
 
-        Ok(())
+                Ok(Some(data))
+            }
+            None => Ok(None),
+        }
     }
 
-    pub fn delete(&mut self, key: &[u8]) -> IndexResult<()> {
+    fn delete(&mut self, key: &[u8]) -> IndexResult<()> {
         self.db
             .try_remove(key)
             .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?;
@@ -150,18 +119,7 @@ impl IndexEngine {
         Ok(())
     }
 
-    pub fn combine(&mut self, tree: radixdb::RadixTree) -> IndexResult<()> {
-        self.db
-            .try_outer_combine_with(&tree, radixdb::node::DetachConverter, |a, b| {
-                a.set(Some(b.downcast()));
-                Ok(())
-            })
-            .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?;
-
-        Ok(())
-    }
-
-    pub fn exist(&self, key: &[u8]) -> IndexResult<bool> {
+    fn exist(&self, key: &[u8]) -> IndexResult<bool> {
         let result = self
             .db
             .try_contains_key(key)
@@ -170,24 +128,33 @@ impl IndexEngine {
         Ok(result)
     }
 
-    pub fn range(&self, range: impl RangeBounds<Vec<u8>>) -> RangeKeyValIter {
-        RangeKeyValIter::new_iterator(
-            copy_bound(range.start_bound()),
-            copy_bound(range.end_bound()),
-            self.db.try_iter(),
-        )
+    fn range(&self, range: KeyRange) -> IndexResult<KeyValueIter<'_>> {
+        let iter = RangeKeyValIter::new_iterator(range.0, range.1, self.db.try_iter());
+        Ok(Box::new(iter.map(
+            |item| -> IndexResult<(Vec<u8>, Vec<u8>)> {
+                let (key, val) = item?;
+                let key: &[u8] = key.as_ref();
+                Ok((key.to_vec(), self.load(&val)?))
+            },
+        )))
     }
 
-    pub fn prefix<'a>(
-        &'a self,
-        key: &'a [u8],
-    ) -> IndexResult<radixdb::node::KeyValueIter<store::PagedFileStore>> {
-        self.db
+    fn prefix<'a>(&'a self, key: &'a [u8]) -> IndexResult<KeyValueIter<'a>> {
+        let iter = self
+            .db
             .try_scan_prefix(key)
-            .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })
+            .map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?;
+        Ok(Box::new(iter.map(
+            |item| -> IndexResult<(Vec<u8>, Vec<u8>)> {
+                let (key, val) =
+                    item.map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?;
+                let key: &[u8] = key.as_ref();
+                Ok((key.to_vec(), self.load(&val)?))
+            },
+        )))
     }
 
-    pub fn flush(&mut self) -> IndexResult<()> {
+    fn flush(&mut self) -> IndexResult<()> {
         let _id = self
             .db
             .try_reattach()
@@ -201,14 +168,7 @@ impl IndexEngine {
     }
 }
 
-fn copy_bound(bound: std::ops::Bound<&Vec<u8>>) -> std::ops::Bound<Vec<u8>> {
-    match bound {
-        std::ops::Bound::Included(val) => std::ops::Bound::Included(val.to_vec()),
-        std::ops::Bound::Excluded(val) => std::ops::Bound::Excluded(val.to_vec()),
-        std::ops::Bound::Unbounded => std::ops::Bound::Unbounded,
-    }
-}
-pub struct RangeKeyValIter {
+struct RangeKeyValIter {
     start: std::ops::Bound<Vec<u8>>,
     end: std::ops::Bound<Vec<u8>>,
 
@@ -216,7 +176,7 @@ pub struct RangeKeyValIter {
 }
 
 impl RangeKeyValIter {
-    pub fn new_iterator(
+    fn new_iterator(
         start: std::ops::Bound<Vec<u8>>,
         end: std::ops::Bound<Vec<u8>>,
         iter: radixdb::node::KeyValueIter<store::PagedFileStore>,
@@ -329,11 +289,11 @@ mod test {
     use models::utils::now_timestamp_nanos;
     use tokio::time::{self, Duration};
 
-    use super::IndexEngine;
+    use super::{IndexEngine, RadixIndexEngine};
 
     #[tokio::test]
     async fn test_engine() {
-        let mut engine = IndexEngine::new("/tmp/test/1").unwrap();
+        let mut engine = RadixIndexEngine::new("/tmp/test/1").unwrap();
         // engine.set(b"key1", b"v11111").unwrap();
         // engine.set(b"key2", b"v22222").unwrap();
         // engine.set(b"key3", b"v33333").unwrap();
@@ -352,7 +312,7 @@ mod test {
     }
 
     async fn test_engine_write_perf() {
-        let mut engine = IndexEngine::new("/tmp/test/2").unwrap();
+        let mut engine = RadixIndexEngine::new("/tmp/test/2").unwrap();
 
         let mut begin = now_timestamp_nanos() / 1000000;
         for i in 1..10001 {
@@ -370,7 +330,7 @@ mod test {
     }
 
     async fn test_engine_read_perf() {
-        let engine = IndexEngine::new("/tmp/test/3").unwrap();
+        let engine = RadixIndexEngine::new("/tmp/test/3").unwrap();
         let engine = Arc::new(engine);
 
         let atomic = Arc::new(AtomicU64::new(0));
@@ -384,19 +344,18 @@ mod test {
         time::sleep(Duration::from_secs(3)).await;
     }
 
-    fn engine_iter(engine: Arc<IndexEngine>) {
+    fn engine_iter(engine: Arc<RadixIndexEngine>) {
         let it = engine.prefix("key".as_bytes()).unwrap();
         for item in it {
             let item = item.unwrap();
             let key = std::str::from_utf8(item.0.as_ref()).unwrap();
-            let val = engine.load(&item.1).unwrap();
-            let val = std::str::from_utf8(&val).unwrap();
+            let val = std::str::from_utf8(&item.1).unwrap();
 
             println!("{}: {}", key, val)
         }
     }
 
-    fn random_read(engine: Arc<IndexEngine>, count: Arc<AtomicU64>) {
+    fn random_read(engine: Arc<RadixIndexEngine>, count: Arc<AtomicU64>) {
         for _i in 1..10000000 {
             let random: i32 = rand::Rng::gen_range(&mut rand::thread_rng(), 1..=10000000);
 
@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::Path;
+
+use sled;
+use trace::debug;
+
+use super::{IndexEngine, IndexEngineType, KeyRange, KeyValueIter};
+use crate::index::IndexResult;
+
+const INDEX_DIR: &str = "index.sled";
+
+#[derive(Debug)]
+pub struct SledIndexEngine {
+    db: sled::Db,
+}
+
+impl SledIndexEngine {
+    pub fn new(path: impl AsRef<Path>) -> IndexResult<Self> {
+        let path = path.as_ref();
+        debug!("Creating sled index engine : {:?}", &path);
+
+        let db = sled::Config::new().path(path.join(INDEX_DIR)).open()?;
+
+        Ok(Self { db })
+    }
+
+    pub(super) fn has_data(path: &Path) -> bool {
+        path.join(INDEX_DIR).is_dir()
+    }
+
+    pub(super) fn move_data(from: &Path, to: &Path) -> IndexResult<()> {
+        fs::rename(from.join(INDEX_DIR), to.join(INDEX_DIR))?;
+        Ok(())
+    }
+
+    pub(super) fn remove_data(path: &Path) -> IndexResult<()> {
+        let db_path = path.join(INDEX_DIR);
+        if db_path.exists() {
+            fs::remove_dir_all(db_path)?;
+        }
+        Ok(())
+    }
+}
+
+fn to_key_value(item: sled::Result<(sled::IVec, sled::IVec)>) -> IndexResult<(Vec<u8>, Vec<u8>)> {
+    let (key, val) = item?;
+    Ok((key.to_vec(), val.to_vec()))
+}
+
+impl IndexEngine for SledIndexEngine {
+    fn engine_type(&self) -> IndexEngineType {
+        IndexEngineType::Sled
+    }
+
+    fn get(&self, key: &[u8]) -> IndexResult<Option<Vec<u8>>> {
+        Ok(self.db.get(key)?.map(|v| v.to_vec()))
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) -> IndexResult<()> {
+        self.db.insert(key, value)?;
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &[u8]) -> IndexResult<()> {
+        self.db.remove(key)?;
+        Ok(())
+    }
+
+    fn exist(&self, key: &[u8]) -> IndexResult<bool> {
+        Ok(self.db.contains_key(key)?)
+    }
+
+    fn range(&self, range: KeyRange) -> IndexResult<KeyValueIter<'_>> {
+        Ok(Box::new(self.db.range(range).map(to_key_value)))
+    }
+
+    fn prefix<'a>(&'a self, prefix: &'a [u8]) -> IndexResult<KeyValueIter<'a>> {
+        Ok(Box::new(self.db.scan_prefix(prefix).map(to_key_value)))
+    }
+
+    fn flush(&mut self) -> IndexResult<()> {
+        self.db.flush()?;
+        Ok(())
+    }
+}
@@ -56,6 +56,21 @@ pub enum IndexError {
 
     #[snafu(display("Decode index binlog block failed for '{}'", msg))]
     DecodeIndexBinlog { msg: String },
+
+    #[snafu(display(
+        "index in '{}' is stored by {} instead of the configured {}, migrate it by 'cnosdb migrate-index' first",
+        dir,
+        found,
+        expected
+    ))]
+    IndexEngineMismatch {
+        dir: String,
+        found: String,
+        expected: String,
+    },
+
+    #[snafu(display("number of series of {} exceeds the limit {}", scope, max_series))]
+    SeriesLimitExceeded { scope: String, max_series: u64 },
 }
 
 impl From<sled::Error> for IndexError {
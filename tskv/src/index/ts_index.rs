@@ -1,4 +1,5 @@
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::fmt;
 use std::ops::{BitAnd, BitOr, Bound, RangeBounds};
 use std::path::{Path, PathBuf};
@@ -8,16 +9,20 @@ use std::sync::Arc;
 use bytes::BufMut;
 use datafusion::arrow::datatypes::DataType;
 use datafusion::scalar::ScalarValue;
-use models::predicate::domain::{utf8_from, ColumnDomains, Domain, Range};
+use models::predicate::domain::{utf8_from, ColumnDomains, Domain, PatternValueSet, Range};
 use models::schema::TskvTableSchema;
 use models::{tag, utils, SeriesId, SeriesKey, Tag, TagKey, TagValue};
+use parking_lot::Mutex;
+use regex::Regex;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use tokio::sync::RwLock;
 use trace::{debug, error, info};
 
 use super::binlog::{AddSeries, DeleteSeries, IndexBinlog, IndexBinlogBlock, UpdateSeriesKey};
 use super::cache::ForwardIndexCache;
-use super::{IndexEngine, IndexError, IndexResult};
+use super::{
+    open_index_engine, to_key_range, IndexEngine, IndexEngineType, IndexError, IndexResult,
+};
 use crate::file_system::file::IFile;
 use crate::file_system::file_manager;
 use crate::index::binlog::{BinlogReader, BinlogWriter};
@@ -29,6 +34,51 @@ const SERIES_KEY_PREFIX: &str = "_key_";
 const DELETED_SERIES_KEY_PREFIX: &str = "_deleted_key_";
 const AUTO_INCR_ID_KEY: &str = "_auto_incr_id";
 
+/// Max number of series in an index, `None` means unlimited.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SeriesLimit {
+    pub max_series: Option<u64>,
+    pub max_series_per_table: Option<u64>,
+}
+
+impl SeriesLimit {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_series.is_none() && self.max_series_per_table.is_none()
+    }
+}
+
+/// Number of series in the index, counted on first use and then maintained
+/// by the new series, recounted after series are deleted.
+#[derive(Debug, Default)]
+struct SeriesCounter {
+    total: Option<u64>,
+    tables: HashMap<String, u64>,
+}
+
+impl SeriesCounter {
+    /// Count a new series of the table, if the numbers have been counted.
+    fn incr(&mut self, tab: &str) {
+        if let Some(total) = self.total.as_mut() {
+            *total += 1;
+        }
+        if let Some(total) = self.tables.get_mut(tab) {
+            *total += 1;
+        }
+    }
+}
+
+/// Cardinality of a tag key of a table in the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagCardinality {
+    pub tag_key: TagKey,
+    /// Number of series with the tag key.
+    pub series_count: u64,
+    /// Number of distinct values of the tag key.
+    pub value_count: u64,
+    /// Values with the most series, in descending order of the number of series.
+    pub top_values: Vec<(TagValue, u64)>,
+}
+
 /// Used to maintain forward and inverted indexes
 ///
 /// # Example
@@ -91,17 +141,21 @@ pub struct TSIndex {
     write_count: AtomicU32,
 
     binlog: Arc<RwLock<IndexBinlog>>,
-    storage: Arc<RwLock<IndexEngine>>,
+    storage: Arc<RwLock<Box<dyn IndexEngine>>>,
     forward_cache: ForwardIndexCache,
     binlog_change_sender: UnboundedSender<()>,
+    series_counter: Mutex<SeriesCounter>,
 }
 
 impl TSIndex {
-    pub async fn new(path: impl AsRef<Path>) -> IndexResult<Arc<Self>> {
+    pub async fn new(
+        path: impl AsRef<Path>,
+        engine_type: IndexEngineType,
+    ) -> IndexResult<Arc<Self>> {
         let path = path.as_ref();
 
+        let storage = open_index_engine(path, engine_type)?;
         let binlog = IndexBinlog::new(path).await?;
-        let storage = IndexEngine::new(path)?;
 
         let incr_id = match storage.get(AUTO_INCR_ID_KEY.as_bytes())? {
             Some(data) => byte_utils::decode_be_u32(&data),
@@ -118,6 +172,7 @@ impl TSIndex {
             path: path.into(),
             forward_cache: ForwardIndexCache::new(1_000_000),
             binlog_change_sender,
+            series_counter: Mutex::new(SeriesCounter::default()),
         };
 
         ts_index.recover().await?;
@@ -199,6 +254,7 @@ impl TSIndex {
         }
 
         self.add_series(id, key).await?;
+        *self.series_counter.lock() = SeriesCounter::default();
 
         Ok(())
     }
@@ -318,6 +374,7 @@ impl TSIndex {
                 }
                 let id = self.incr_id.fetch_add(1, Ordering::Relaxed) + 1;
                 storage_w.set(&key_buf, &id.to_be_bytes())?;
+                self.series_counter.lock().incr(series_key.table());
                 let block = AddSeries::new(utils::now_timestamp_nanos(), id, series_key.clone());
                 ids.push((id, series_key.clone()));
                 blocks_data.push(block);
@@ -333,6 +390,119 @@ impl TSIndex {
         Ok(ids)
     }
 
+    /// Check whether adding the series would exceed the `limit`. It's all or nothing: if the
+    /// new series in `series_keys` don't fit in the limit, an error is returned for all of them.
+    ///
+    /// Series are not added here, the numbers of series are counted by the series keys of the
+    /// storage on first use, and then maintained by `add_series_if_not_exists`.
+    pub async fn check_series_limit(
+        &self,
+        series_keys: &[SeriesKey],
+        limit: &SeriesLimit,
+    ) -> IndexResult<()> {
+        if limit.is_unlimited() {
+            return Ok(());
+        }
+
+        let storage_r = self.storage.read().await;
+        let mut new_series = HashSet::new();
+        let mut new_table_series: HashMap<&str, u64> = HashMap::new();
+        for series_key in series_keys {
+            let key_buf = encode_series_key(series_key.table(), series_key.tags());
+            if new_series.contains(&key_buf) || storage_r.get(&key_buf)?.is_some() {
+                continue;
+            }
+            new_series.insert(key_buf);
+            *new_table_series.entry(series_key.table()).or_default() += 1;
+        }
+        if new_series.is_empty() {
+            return Ok(());
+        }
+
+        let mut counter = self.series_counter.lock();
+        if let Some(max_series) = limit.max_series {
+            let total = match counter.total {
+                Some(total) => total,
+                None => count_keys(&storage_r, SERIES_KEY_PREFIX.as_bytes())?,
+            };
+            counter.total = Some(total);
+            if total + new_series.len() as u64 > max_series {
+                return Err(IndexError::SeriesLimitExceeded {
+                    scope: "database".to_string(),
+                    max_series,
+                });
+            }
+        }
+
+        if let Some(max_series) = limit.max_series_per_table {
+            for (tab, new_series) in new_table_series {
+                let table_total = match counter.tables.get(tab) {
+                    Some(total) => *total,
+                    None => count_keys(&storage_r, &encode_series_key(tab, &[]))?,
+                };
+                counter.tables.insert(tab.to_string(), table_total);
+                if table_total + new_series > max_series {
+                    return Err(IndexError::SeriesLimitExceeded {
+                        scope: format!("table '{tab}'"),
+                        max_series,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Number of series of the table.
+    pub async fn series_count(&self, tab: &str) -> IndexResult<u64> {
+        let storage_r = self.storage.read().await;
+        count_keys(&storage_r, &encode_series_key(tab, &[]))
+    }
+
+    /// Cardinality of the tag key of the table, computed from the inverted index.
+    pub async fn tag_cardinality(
+        &self,
+        tab: &str,
+        tag_key: &[u8],
+        top_n: usize,
+    ) -> IndexResult<TagCardinality> {
+        let lower_bound = encode_inverted_min_index_key(tab, tag_key);
+        let upper_bound = encode_inverted_max_index_key(tab, tag_key);
+        let value_offset = encode_inverted_index_key(tab, tag_key, &[]).len();
+
+        let mut bitmap = roaring::RoaringBitmap::new();
+        let mut value_count = 0;
+        // Min-heap of the top values, ordered by (number of series, value).
+        let mut top_values = BinaryHeap::with_capacity(top_n + 1);
+        let storage_r = self.storage.read().await;
+        for item in storage_r.range(to_key_range(&(lower_bound..upper_bound)))? {
+            let item = item?;
+            let rb = storage_r.load_rb(&item.1)?;
+            if rb.is_empty() {
+                continue;
+            }
+            value_count += 1;
+            top_values.push(Reverse((rb.len(), item.0[value_offset..].to_vec())));
+            if top_values.len() > top_n {
+                top_values.pop();
+            }
+            bitmap = bitmap.bitor(rb);
+        }
+
+        let top_values = top_values
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((count, value))| (value, count))
+            .collect();
+
+        Ok(TagCardinality {
+            tag_key: tag_key.to_vec(),
+            series_count: bitmap.len(),
+            value_count,
+            top_values,
+        })
+    }
+
     async fn check_to_flush(&self, force: bool) -> IndexResult<()> {
         let count = self.write_count.fetch_add(1, Ordering::Relaxed);
         if !force && count < 20000 {
@@ -394,6 +564,7 @@ impl TSIndex {
             },
         };
         let _ = storage_w.delete(&encode_series_id_key(sid));
+        *self.series_counter.lock() = SeriesCounter::default();
         if let Some(series_key) = series_key {
             self.forward_cache.del(sid, series_key.hash());
             let key_buf = encode_series_key(series_key.table(), series_key.tags());
@@ -478,7 +649,7 @@ impl TSIndex {
             let prefix = format!("{}.", tab);
             let it = storage_r.prefix(prefix.as_bytes())?;
             for val in it {
-                let val = val.map_err(|e| IndexError::IndexStroage { msg: e.to_string() })?;
+                let val = val?;
                 let rb = storage_r.load_rb(&val.1)?;
 
                 bitmap = bitmap.bitor(rb);
@@ -514,7 +685,7 @@ impl TSIndex {
         let mut bitmap = roaring::RoaringBitmap::new();
         // Search the sid list corresponding to qualified tags in the range
         let storage_r = self.storage.read().await;
-        let iter = storage_r.range(lower_bound..upper_bound);
+        let iter = storage_r.range(to_key_range(&(lower_bound..upper_bound)))?;
         for item in iter {
             let item = item?;
             let rb = storage_r.load_rb(&item.1)?;
@@ -548,7 +719,7 @@ impl TSIndex {
                     }
 
                     // Search the sid list corresponding to qualified tags in the range
-                    let iter = storage_r.range(key_range);
+                    let iter = storage_r.range(to_key_range(&key_range))?;
                     for item in iter {
                         let item = item?;
                         let rb = storage_r.load_rb(&item.1)?;
@@ -572,6 +743,11 @@ impl TSIndex {
                     bitmap = self.get_series_id_bitmap(tab, &[]).await?;
                 }
             }
+            Domain::Pattern(pattern_set) => {
+                bitmap = self
+                    .get_series_ids_by_pattern(tab, tag_key, pattern_set)
+                    .await?;
+            }
             Domain::None => {
                 // Normally, it will not go here unless no judgment is made at the ColumnDomains level
                 // If you go here, you will directly return an empty series, because the tag condition in the map is' and '
@@ -587,6 +763,46 @@ impl TSIndex {
         Ok(bitmap)
     }
 
+    /// Series with the tag values matching all of the patterns, only the tag values starting
+    /// with the literal prefix of the patterns are scanned from the inverted index.
+    async fn get_series_ids_by_pattern(
+        &self,
+        tab: &str,
+        tag_key: &str,
+        pattern_set: &PatternValueSet,
+    ) -> IndexResult<roaring::RoaringBitmap> {
+        let regexes = match pattern_set
+            .patterns()
+            .into_iter()
+            .map(|p| Regex::new(p))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(regexes) => regexes,
+            Err(e) => {
+                // The invalid pattern will be reported by the filter later.
+                debug!("Index get sids: invalid pattern: {e}");
+                return self.get_series_id_bitmap(tab, &[]).await;
+            }
+        };
+
+        let prefix = pattern_set.literal_prefix();
+        let key_prefix = encode_inverted_index_key(tab, tag_key.as_bytes(), prefix.as_bytes());
+        let value_offset = encode_inverted_index_key(tab, tag_key.as_bytes(), &[]).len();
+
+        let mut bitmap = roaring::RoaringBitmap::new();
+        let storage_r = self.storage.read().await;
+        for item in storage_r.prefix(&key_prefix)? {
+            let (key, val) = item?;
+            let matched = std::str::from_utf8(&key[value_offset..])
+                .map_or(false, |value| regexes.iter().all(|r| r.is_match(value)));
+            if matched {
+                bitmap = bitmap.bitor(storage_r.load_rb(&val)?);
+            }
+        }
+
+        Ok(bitmap)
+    }
+
     pub fn path(&self) -> PathBuf {
         self.path.clone()
     }
@@ -831,6 +1047,16 @@ pub fn tag_value_to_index_key(tab: &str, tag_key: &str, v: &ScalarValue) -> Vec<
     unsafe { utf8_from(v).map(generate_index_key).unwrap_unchecked() }
 }
 
+fn count_keys(storage: &dyn IndexEngine, prefix: &[u8]) -> IndexResult<u64> {
+    let mut count = 0;
+    for item in storage.prefix(prefix)? {
+        item?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
 pub fn encode_series_id_key(id: u32) -> Vec<u8> {
     let len = SERIES_ID_PREFIX.len() + 4;
     let mut buf = Vec::with_capacity(len);
@@ -1063,10 +1289,12 @@ mod test {
     use std::time::Duration;
 
     use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use models::predicate::domain::Domain;
     use models::schema::ExternalTableSchema;
     use models::{SeriesId, SeriesKey, Tag};
 
-    use super::TSIndex;
+    use super::{SeriesLimit, TSIndex};
+    use crate::index::{IndexEngineType, IndexError};
     use crate::UpdateSetValue;
 
     /// ( sid, database, table, [(tag_key, tag_value)] )
@@ -1118,7 +1346,7 @@ mod test {
                 }
             }
 
-            let ts_index = TSIndex::new(dir).await.unwrap();
+            let ts_index = TSIndex::new(dir, IndexEngineType::Radix).await.unwrap();
             // Insert series into index.
             let mut series_keys_sids = Vec::with_capacity(series_keys_desc.len());
             for (i, series_key) in series_keys.iter().enumerate() {
@@ -1179,7 +1407,7 @@ mod test {
 
         {
             // Test re-open, query and insert.
-            let ts_index = TSIndex::new(dir).await.unwrap();
+            let ts_index = TSIndex::new(dir, IndexEngineType::Radix).await.unwrap();
             let list = ts_index
                 .get_series_id_list("table_test", &[])
                 .await
@@ -1215,9 +1443,9 @@ mod test {
         }
 
         // Test re-open, do not insert and then re-open.
-        let ts_index = TSIndex::new(dir).await.unwrap();
+        let ts_index = TSIndex::new(dir, IndexEngineType::Radix).await.unwrap();
         drop(ts_index);
-        let ts_index = TSIndex::new(dir).await.unwrap();
+        let ts_index = TSIndex::new(dir, IndexEngineType::Radix).await.unwrap();
         #[rustfmt::skip]
         let series_keys_desc: Vec<SeriesKeyDesc> = vec![
             (0, database, "table_test", vec![("loc", "dbj"), ("host", "h1")]),
@@ -1275,7 +1503,7 @@ mod test {
         let dir = "/tmp/test/cnosdb/ts_index/rename_tag";
         let _ = std::fs::remove_dir_all(dir);
 
-        let ts_index = TSIndex::new(dir).await.unwrap();
+        let ts_index = TSIndex::new(dir, IndexEngineType::Radix).await.unwrap();
 
         let tags1 = vec![
             Tag::new("station".as_bytes().to_vec(), "a0".as_bytes().to_vec()),
@@ -1360,13 +1588,111 @@ mod test {
         assert_eq!(expected_sids, actual_sids);
     }
 
+    #[tokio::test]
+    async fn test_series_limit_and_cardinality() {
+        let dir = "/tmp/test/cnosdb/ts_index/series_limit";
+        let _ = std::fs::remove_dir_all(dir);
+        let ts_index = TSIndex::new(dir, IndexEngineType::Radix).await.unwrap();
+
+        #[rustfmt::skip]
+        let series_keys = build_series_keys(&[
+            (0, "db", "cpu", vec![("host", "a"), ("region", "r1")]),
+            (0, "db", "cpu", vec![("host", "b"), ("region", "r1")]),
+            (0, "db", "cpu", vec![("host", "c"), ("region", "r2")]),
+            (0, "db", "mem", vec![("host", "a")]),
+        ]);
+        let limit = SeriesLimit {
+            max_series: Some(4),
+            max_series_per_table: Some(3),
+        };
+        ts_index
+            .check_series_limit(&series_keys, &limit)
+            .await
+            .unwrap();
+        let sids = ts_index
+            .add_series_if_not_exists(series_keys.clone())
+            .await
+            .unwrap();
+        assert_eq!(sids.len(), 4);
+
+        // Existing series are not limited.
+        ts_index
+            .check_series_limit(&series_keys, &limit)
+            .await
+            .unwrap();
+
+        #[rustfmt::skip]
+        let cpu_d = build_series_keys(&[(0, "db", "cpu", vec![("host", "d")])]);
+        let err = ts_index
+            .check_series_limit(
+                &cpu_d,
+                &SeriesLimit {
+                    max_series: None,
+                    ..limit
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            IndexError::SeriesLimitExceeded { max_series: 3, .. }
+        ));
+
+        // All or nothing: the existing series don't make the new one fit.
+        #[rustfmt::skip]
+        let mem_b = build_series_keys(&[
+            (0, "db", "mem", vec![("host", "a")]),
+            (0, "db", "mem", vec![("host", "b")]),
+        ]);
+        let err = ts_index
+            .check_series_limit(&mem_b, &limit)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            IndexError::SeriesLimitExceeded { max_series: 4, .. }
+        ));
+
+        // Numbers of series are maintained by the new series.
+        ts_index.add_series_if_not_exists(cpu_d).await.unwrap();
+        let err = ts_index
+            .check_series_limit(
+                &mem_b,
+                &SeriesLimit {
+                    max_series: Some(5),
+                    max_series_per_table: None,
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            IndexError::SeriesLimitExceeded { max_series: 5, .. }
+        ));
+        assert_eq!(ts_index.series_count("cpu").await.unwrap(), 4);
+        assert_eq!(ts_index.series_count("mem").await.unwrap(), 1);
+
+        // Wait for binlog to be consumed
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let region = ts_index.tag_cardinality("cpu", b"region", 1).await.unwrap();
+        assert_eq!(region.series_count, 3);
+        assert_eq!(region.value_count, 2);
+        assert_eq!(region.top_values, vec![(b"r1".to_vec(), 2)]);
+
+        let host = ts_index.tag_cardinality("cpu", b"host", 10).await.unwrap();
+        assert_eq!(host.series_count, 4);
+        assert_eq!(host.value_count, 4);
+        assert_eq!(host.top_values.len(), 4);
+    }
+
     #[tokio::test]
     async fn test_update_tags_value() {
         let table_name = "table";
         let dir = "/tmp/test/cnosdb/ts_index/update_tags_value";
         let _ = std::fs::remove_dir_all(dir);
 
-        let ts_index = TSIndex::new(dir).await.unwrap();
+        let ts_index = TSIndex::new(dir, IndexEngineType::Radix).await.unwrap();
 
         let tags1 = vec![
             Tag::new("station".as_bytes().to_vec(), "a0".as_bytes().to_vec()),
@@ -1446,4 +1772,40 @@ mod test {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_series_ids_by_pattern() {
+        let dir = "/tmp/test/ts_index/pattern";
+        let _ = std::fs::remove_dir_all(dir);
+        let ts_index = TSIndex::new(dir, IndexEngineType::Radix).await.unwrap();
+
+        #[rustfmt::skip]
+        let series_keys_desc: Vec<SeriesKeyDesc> = vec![
+            (0, "db_test", "tab", vec![("host", "web-01")]),
+            (0, "db_test", "tab", vec![("host", "web-02")]),
+            (0, "db_test", "tab", vec![("host", "web-10")]),
+            (0, "db_test", "tab", vec![("host", "db-01")]),
+        ];
+        let sids = ts_index
+            .add_series_if_not_exists(build_series_keys(&series_keys_desc))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        for (domain, expected) in [
+            (Domain::of_pattern("^web-0[0-9]$"), vec![0, 1]),
+            (Domain::of_pattern("(?i)^WEB"), vec![0, 1, 2]),
+            (Domain::of_pattern("01$"), vec![0, 3]),
+            (Domain::of_pattern("^web\\-1"), vec![2]),
+            (Domain::of_prefix("web-1"), vec![2]),
+            (Domain::of_prefix("db"), vec![3]),
+        ] {
+            let bitmap = ts_index
+                .get_series_ids_by_domain("tab", "host", &domain)
+                .await
+                .unwrap();
+            let expected = expected.into_iter().map(|i| sids[i].0).collect::<Vec<_>>();
+            assert_eq!(bitmap.into_iter().collect::<Vec<_>>(), expected, "{domain}");
+        }
+    }
 }
@@ -4,7 +4,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use config::Config;
+use config::{Config, IndexEngineType, JwtConfig};
 use models::meta_data::{NodeId, VnodeId};
 
 use crate::TseriesFamilyId;
@@ -50,6 +50,9 @@ pub struct StorageOptions {
     pub max_compact_size: u64,
     pub max_concurrent_compaction: u16,
     pub strict_write: bool,
+    pub cold_tier_path: Option<PathBuf>,
+    pub cold_tier_read_cache_size: u64,
+    pub index_engine: IndexEngineType,
 }
 
 // database/data/ts_family_id/tsm
@@ -69,8 +72,12 @@ impl StorageOptions {
         self.path.join(SUMMARY_PATH)
     }
 
+    pub fn data_dir(&self) -> PathBuf {
+        self.path.join(DATA_PATH)
+    }
+
     pub fn database_dir(&self, database: &str) -> PathBuf {
-        self.path.join(DATA_PATH).join(database)
+        self.data_dir().join(database)
     }
 
     pub fn ts_family_dir(&self, database: &str, ts_family_id: TseriesFamilyId) -> PathBuf {
@@ -165,6 +172,13 @@ impl From<&Config> for StorageOptions {
             max_compact_size: config.storage.max_compact_size,
             max_concurrent_compaction: config.storage.max_concurrent_compaction,
             strict_write: config.storage.strict_write,
+            cold_tier_path: if config.storage.cold_tier_enabled() {
+                Some(PathBuf::from(config.storage.cold_tier_path.clone()))
+            } else {
+                None
+            },
+            cold_tier_read_cache_size: config.storage.cold_tier_read_cache_size,
+            index_engine: config.storage.index_engine,
         }
     }
 }
@@ -179,6 +193,25 @@ pub struct QueryOptions {
     pub write_timeout_ms: u64,
     pub stream_trigger_cpu: usize,
     pub stream_executor_cpu: usize,
+    pub query_cache_enabled: bool,
+    pub query_cache_max_memory: u64,
+    pub query_cache_bucket: Duration,
+    pub query_cache_live_tail: Duration,
+    pub audit_enabled: bool,
+    pub audit_path: String,
+    pub audit_statements: Vec<String>,
+    pub audit_max_file_size: u64,
+    pub audit_max_files: usize,
+    pub audit_table_max_records: usize,
+    pub password_min_length: usize,
+    pub password_require_uppercase: bool,
+    pub password_require_lowercase: bool,
+    pub password_require_digit: bool,
+    pub password_require_special: bool,
+    pub password_lifetime: Duration,
+    pub login_max_failed_attempts: u32,
+    pub login_lock_time: Duration,
+    pub jwt_config: Option<JwtConfig>,
 }
 
 impl From<&Config> for QueryOptions {
@@ -192,6 +225,25 @@ impl From<&Config> for QueryOptions {
             write_timeout_ms: config.query.write_timeout_ms,
             stream_trigger_cpu: config.query.stream_trigger_cpu,
             stream_executor_cpu: config.query.stream_executor_cpu,
+            query_cache_enabled: config.query.query_cache_enabled,
+            query_cache_max_memory: config.query.query_cache_max_memory,
+            query_cache_bucket: config.query.query_cache_bucket,
+            query_cache_live_tail: config.query.query_cache_live_tail,
+            audit_enabled: config.query.audit_enabled,
+            audit_path: config.query.audit_path.clone(),
+            audit_statements: config.query.audit_statements.clone(),
+            audit_max_file_size: config.query.audit_max_file_size,
+            audit_max_files: config.query.audit_max_files,
+            audit_table_max_records: config.query.audit_table_max_records,
+            password_min_length: config.query.password_min_length,
+            password_require_uppercase: config.query.password_require_uppercase,
+            password_require_lowercase: config.query.password_require_lowercase,
+            password_require_digit: config.query.password_require_digit,
+            password_require_special: config.query.password_require_special,
+            password_lifetime: config.query.password_lifetime,
+            login_max_failed_attempts: config.query.login_max_failed_attempts,
+            login_lock_time: config.query.login_lock_time,
+            jwt_config: config.security.jwt_config.clone(),
         }
     }
 }
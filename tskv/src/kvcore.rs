@@ -5,7 +5,7 @@ use memory_pool::{MemoryPool, MemoryPoolRef};
 use meta::model::MetaRef;
 use metrics::metric_register::MetricsRegister;
 use models::meta_data::VnodeId;
-use models::predicate::domain::ColumnDomains;
+use models::predicate::domain::{ColumnDomains, TimeRange};
 use models::schema::{make_owner, DatabaseSchema};
 use models::{SeriesId, SeriesKey};
 use snafu::ResultExt;
@@ -15,14 +15,14 @@ use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::sync::{oneshot, RwLock};
 use trace::{debug, error, info, warn};
 
-use crate::compaction::job::{CompactJob, FlushJob};
+use crate::compaction::job::{CompactJob, FlushJob, TieringJob};
 use crate::compaction::{
     self, check, run_flush_memtable_job, CompactTask, FlushReq, LevelCompactionPicker, Picker,
 };
 use crate::database::Database;
 use crate::error::{self, Result};
-use crate::file_system::file_manager;
-use crate::index::ts_index;
+use crate::file_system::{cold_store, file_manager};
+use crate::index::{cardinality, ts_index};
 use crate::kv_option::{Options, StorageOptions};
 use crate::summary::{Summary, SummaryProcessor, SummaryTask, VersionEdit};
 use crate::tseries_family::{SuperVersion, TseriesFamily};
@@ -40,6 +40,7 @@ pub struct TsKv {
     meta_manager: MetaRef,
     flush_job: FlushJob,
     compact_job: CompactJob,
+    tiering_job: TieringJob,
     runtime: Arc<Runtime>,
     metrics: Arc<MetricsRegister>,
     memory_pool: Arc<dyn MemoryPool>,
@@ -62,6 +63,7 @@ impl TsKv {
         let (summary_task_sender, summary_task_receiver) =
             mpsc::channel::<SummaryTask>(SUMMARY_REQ_CHANNEL_CAP);
         let (close_sender, _close_receiver) = broadcast::channel(1);
+        cold_store::open_cold_store(&shared_options.storage)?;
 
         let (version_set, summary) = Self::recover_summary(
             runtime.clone(),
@@ -84,6 +86,7 @@ impl TsKv {
 
         let compact_job = CompactJob::new(runtime.clone(), ctx.clone());
         let flush_job = FlushJob::new(runtime.clone(), ctx.clone());
+        let tiering_job = TieringJob::new(runtime.clone(), ctx.clone());
 
         let core = Self {
             ctx,
@@ -91,6 +94,7 @@ impl TsKv {
             memory_pool,
             compact_job,
             flush_job,
+            tiering_job,
             close_sender,
             metrics,
             runtime,
@@ -102,6 +106,7 @@ impl TsKv {
             .await;
         core.compact_job.start_vnode_compaction_job().await;
         core.flush_job.start_vnode_flush_job(flush_task_receiver);
+        core.tiering_job.start_tiering_job();
         Ok(core)
     }
 
@@ -164,6 +169,33 @@ impl TsKv {
         self.ctx.version_set.read().await.get_db(tenant, database)
     }
 
+    /// Flush the memcache of a vnode into tsm files without compaction,
+    /// returns the vnode and the database it belongs to.
+    async fn flush_vnode_for_check(
+        &self,
+        vnode_id: VnodeId,
+    ) -> Result<Option<(Arc<RwLock<TseriesFamily>>, Arc<RwLock<Database>>)>> {
+        for database in self.ctx.version_set.read().await.get_all_db().values() {
+            let db = database.read().await;
+            if let Some(vnode) = db.ts_families().get(&vnode_id).cloned() {
+                drop(db);
+                let request = {
+                    let mut tsfamily = vnode.write().await;
+                    tsfamily.switch_to_immutable();
+                    tsfamily.build_flush_req(true)
+                };
+
+                if let Some(req) = request {
+                    // Run flush job but do not trigger compaction.
+                    run_flush_memtable_job(req, self.ctx.clone(), false).await?;
+                }
+                return Ok(Some((vnode, database.clone())));
+            }
+        }
+
+        Ok(None)
+    }
+
     pub(crate) async fn get_db_or_else_create(
         &self,
         tenant: &str,
@@ -248,11 +280,8 @@ impl Engine for TsKv {
                 .del_tsfamily(vnode_id, self.ctx.summary_task_sender.clone())
                 .await;
 
-            let ts_dir = self
-                .ctx
-                .options
-                .storage
-                .ts_family_dir(&make_owner(tenant, database), vnode_id);
+            let owner = make_owner(tenant, database);
+            let ts_dir = self.ctx.options.storage.ts_family_dir(&owner, vnode_id);
             match std::fs::remove_dir_all(&ts_dir) {
                 Ok(()) => {
                     info!("Removed TsFamily directory '{}'", ts_dir.display());
@@ -265,6 +294,7 @@ impl Engine for TsKv {
                     );
                 }
             }
+            cold_store::delete_vnode_files(&self.ctx.options.storage, &owner, vnode_id).await;
         }
 
         Ok(())
@@ -429,25 +459,90 @@ impl Engine for TsKv {
     }
 
     async fn get_vnode_hash_tree(&self, vnode_id: VnodeId) -> Result<RecordBatch> {
+        match self.flush_vnode_for_check(vnode_id).await? {
+            Some((vnode, _)) => check::vnode_checksum(vnode).await,
+            None => Ok(RecordBatch::new_empty(check::vnode_table_checksum_schema())),
+        }
+    }
+
+    async fn get_vnode_series_checksum(&self, vnode_id: VnodeId) -> Result<RecordBatch> {
+        match self.flush_vnode_for_check(vnode_id).await? {
+            Some((vnode, _)) => check::vnode_series_checksum(vnode).await,
+            None => Ok(RecordBatch::new_empty(check::vnode_series_checksum_schema())),
+        }
+    }
+
+    async fn get_vnode_series_data(
+        &self,
+        vnode_id: VnodeId,
+        table: &str,
+        series_time_ranges: &[(SeriesKey, TimeRange)],
+    ) -> Result<RecordBatch> {
+        let (vnode, db) = self
+            .flush_vnode_for_check(vnode_id)
+            .await?
+            .ok_or(error::Error::VnodeNotFound { vnode_id })?;
+        let table_schema = db
+            .read()
+            .await
+            .get_table_schema(table)
+            .await?
+            .ok_or_else(|| error::Error::TableNotFound {
+                table: table.to_string(),
+            })?;
+
+        check::vnode_series_data(vnode, table_schema, series_time_ranges).await
+    }
+
+    async fn get_vnode_series_cardinality(
+        &self,
+        vnode_id: VnodeId,
+        top_n: usize,
+    ) -> Result<RecordBatch> {
         for database in self.ctx.version_set.read().await.get_all_db().values() {
             let db = database.read().await;
-            if let Some(vnode) = db.ts_families().get(&vnode_id).cloned() {
-                drop(db);
-                let request = {
-                    let mut tsfamily = vnode.write().await;
-                    tsfamily.switch_to_immutable();
-                    tsfamily.build_flush_req(true)
-                };
-
-                if let Some(req) = request {
-                    // Run flush job but do not trigger compaction.
-                    run_flush_memtable_job(req, self.ctx.clone(), false).await?;
+            let ts_index = match db.get_ts_index(vnode_id) {
+                Some(ts_index) => ts_index,
+                None => continue,
+            };
+            let schemas = db.get_schemas();
+            drop(db);
+
+            let mut tables = vec![];
+            for table in schemas.list_tables().await? {
+                if let Some(schema) = schemas.get_table_schema(&table).await? {
+                    tables.push(schema);
                 }
-                return check::vnode_checksum(vnode).await;
             }
+            return cardinality::series_cardinality(vnode_id, &ts_index, &tables, top_n).await;
         }
 
-        Ok(RecordBatch::new_empty(check::vnode_table_checksum_schema()))
+        Ok(RecordBatch::new_empty(
+            cardinality::series_cardinality_schema(),
+        ))
+    }
+
+    async fn check_series_limit(
+        &self,
+        tenant: &str,
+        database: &str,
+        vnode_id: VnodeId,
+        points: &[u8],
+    ) -> Result<()> {
+        let db = match self.ctx.version_set.read().await.get_db(tenant, database) {
+            Some(db) => db,
+            None => return Ok(()),
+        };
+        let db = db.read().await;
+        let ts_index = match db.get_ts_index(vnode_id) {
+            Some(ts_index) => ts_index,
+            None => return Ok(()),
+        };
+
+        let fb_points = flatbuffers::root::<protos::models::Points>(points)
+            .context(error::InvalidFlatbufferSnafu)?;
+        let tables = fb_points.tables().ok_or(error::Error::InvalidPointTable)?;
+        db.check_series_limit(tables, &ts_index).await
     }
 
     async fn close(&self) {
@@ -9,13 +9,13 @@ use std::fmt::Debug;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-pub use compaction::check::vnode_table_checksum_schema;
+pub use compaction::check::{vnode_series_checksum_schema, vnode_table_checksum_schema};
 use compaction::{CompactTask, FlushReq};
 use context::GlobalContext;
 use datafusion::arrow::record_batch::RecordBatch;
 use file_system::file_info::FileInfo;
 use models::meta_data::{NodeId, VnodeId};
-use models::predicate::domain::ColumnDomains;
+use models::predicate::domain::{ColumnDomains, TimeRange};
 use models::{SeriesId, SeriesKey};
 use serde::{Deserialize, Serialize};
 use summary::SummaryTask;
@@ -129,6 +129,36 @@ pub trait Engine: Send + Sync + Debug {
     /// Get a compressed hash_tree(ID and checksum of each vnode) of engine.
     async fn get_vnode_hash_tree(&self, vnode_id: VnodeId) -> Result<RecordBatch>;
 
+    /// Get checksums of each series and time range of a vnode, used to find the
+    /// different data between replicas.
+    async fn get_vnode_series_checksum(&self, vnode_id: VnodeId) -> Result<RecordBatch>;
+
+    /// Read data of the given series in the given time ranges of a table in a vnode.
+    async fn get_vnode_series_data(
+        &self,
+        vnode_id: VnodeId,
+        table: &str,
+        series_time_ranges: &[(SeriesKey, TimeRange)],
+    ) -> Result<RecordBatch>;
+
+    /// Get the number of series of each table and tag key in the index of a vnode,
+    /// with the `top_n` tag values with the most series of each tag key.
+    async fn get_vnode_series_cardinality(
+        &self,
+        vnode_id: VnodeId,
+        top_n: usize,
+    ) -> Result<RecordBatch>;
+
+    /// Check whether writing the points into the vnode would exceed the series limits
+    /// of the database, it's called by the raft leader before the points are proposed.
+    async fn check_series_limit(
+        &self,
+        tenant: &str,
+        database: &str,
+        vnode_id: VnodeId,
+        points: &[u8],
+    ) -> Result<()>;
+
     /// Close all background jobs of engine.
     async fn close(&self);
 }
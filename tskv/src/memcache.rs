@@ -1,5 +1,5 @@
 use std::cmp;
-use std::collections::{BTreeMap, HashMap, HashSet, LinkedList};
+use std::collections::{BTreeMap, HashMap, LinkedList};
 use std::mem::size_of_val;
 use std::ops::Bound::Included;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
@@ -12,7 +12,8 @@ use minivec::MiniVec;
 use models::field_value::FieldVal;
 use models::predicate::domain::{TimeRange, TimeRanges};
 use models::schema::{
-    timestamp_convert, ColumnType, Precision, TableColumn, TskvTableSchema, TskvTableSchemaRef,
+    timestamp_convert, ColumnType, DedupPolicy, Precision, TableColumn, TskvTableSchema,
+    TskvTableSchemaRef,
 };
 use models::{ColumnId, RwLockRef, SeriesId, SeriesKey, Timestamp};
 use parking_lot::RwLock;
@@ -193,6 +194,17 @@ impl RowData {
         size += size_of_val(&self.fields);
         size
     }
+
+    /// Deduplicate the row written later with the same timestamp into this row.
+    pub fn dedup(&mut self, later: RowData, policy: DedupPolicy) {
+        let mut later_fields = later.fields;
+        let len = cmp::max(self.fields.len(), later_fields.len());
+        self.fields.resize(len, None);
+        later_fields.resize(len, None);
+        for (field, later_field) in self.fields.iter_mut().zip(later_fields) {
+            *field = policy.dedup_value(field.take(), later_field);
+        }
+    }
 }
 
 impl PartialOrd for RowData {
@@ -229,6 +241,17 @@ impl OrderedRowsData {
         self.rows.insert(row);
     }
 
+    /// Insert a row, the existing row with the same timestamp is deduplicated by the policy.
+    pub fn insert_dedup(&mut self, row: RowData, policy: DedupPolicy) {
+        match self.rows.remove(&row) {
+            Some(mut earlier) => {
+                earlier.dedup(row, policy);
+                self.rows.insert(earlier);
+            }
+            None => self.rows.insert(row),
+        }
+    }
+
     pub fn retain(&mut self, mut f: impl FnMut(&RowData) -> bool) {
         self.rows.retain(|row| f(row));
     }
@@ -287,8 +310,9 @@ impl SeriesData {
         for item in self.groups.iter_mut() {
             if item.schema.schema_id == group.schema.schema_id {
                 item.range.merge(&group.range);
+                let dedup_policy = group.schema.dedup_policy();
                 group.rows.get_rows().into_iter().for_each(|row| {
-                    item.rows.insert(row);
+                    item.rows.insert_dedup(row, dedup_policy);
                 });
                 item.schema = group.schema;
                 return;
@@ -529,33 +553,33 @@ impl SeriesData {
 
             let mut delta_time_array = time_array.clone();
             let mut cols_desc = vec![None; schema.field_num()];
-            for (schema, rows) in self.flat_groups() {
-                let values = dedup_and_sort_row_data(rows);
-                for row in values {
-                    match row.ts.cmp(&version.max_level_ts()) {
-                        cmp::Ordering::Greater => {
-                            time_array.push(Some(FieldVal::Integer(row.ts)));
-                        }
-                        _ => {
-                            delta_time_array.push(Some(FieldVal::Integer(row.ts)));
-                        }
-                    }
-                    for col in schema.fields().iter() {
-                        if let Some(index) = field_ids.get(&col.id) {
-                            let field = row.fields.get(*index).and_then(|v| v.clone());
-                            match row.ts.cmp(&version.max_level_ts()) {
-                                cmp::Ordering::Greater => {
-                                    cols[*index].push(field);
-                                }
-                                _ => {
-                                    delta_cols[*index].push(field);
-                                }
-                            }
+            // 按最新的表结构对齐所有 rowgroup 的数据, 再对相同时间戳的数据去重
+            let mut rows = Vec::new();
+            for (group_schema, group_rows) in self.flat_groups() {
+                let group_field_ids = group_schema.fields_id();
+                for row in group_rows.get_ref_rows() {
+                    let mut fields = vec![None; field_ids.len()];
+                    for col in group_schema.fields().iter() {
+                        if let (Some(index), Some(group_index)) =
+                            (field_ids.get(&col.id), group_field_ids.get(&col.id))
+                        {
+                            fields[*index] = row.fields.get(*group_index).cloned().flatten();
                             if cols_desc[*index].is_none() {
                                 cols_desc[*index] = Some(col.clone());
                             }
                         }
                     }
+                    rows.push(RowData { ts: row.ts, fields });
+                }
+            }
+            for row in dedup_and_sort_row_data(rows, schema.dedup_policy()) {
+                let (time_array, cols) = match row.ts.cmp(&version.max_level_ts()) {
+                    cmp::Ordering::Greater => (&mut time_array, &mut cols),
+                    _ => (&mut delta_time_array, &mut delta_cols),
+                };
+                time_array.push(Some(FieldVal::Integer(row.ts)));
+                for (index, field) in row.fields.into_iter().enumerate() {
+                    cols[index].push(field);
                 }
             }
 
@@ -957,30 +981,19 @@ pub(crate) mod test {
     }
 }
 
-pub fn dedup_and_sort_row_data(data: &OrderedRowsData) -> Vec<RowData> {
-    // let mut data = data.iter().cloned().collect::<Vec<_>>();
-    // data.sort_by(|a, b| a.ts.cmp(&b.ts));
-    let mut dedup_ts = HashSet::new();
-    data.get_ref_rows().iter().for_each(|row_data| {
-        if !dedup_ts.contains(&row_data.ts) {
-            dedup_ts.insert(row_data.ts);
-        }
-    });
-
-    let mut result: Vec<RowData> = Vec::with_capacity(dedup_ts.len());
-    for row_data in data.get_ref_rows() {
-        if let Some(existing_row) = result.last_mut() {
-            if existing_row.ts == row_data.ts {
-                for (index, field) in row_data.fields.iter().enumerate() {
-                    if let Some(field) = field {
-                        existing_row.fields[index] = Some(field.clone());
-                    }
-                }
-            } else {
-                result.push(row_data.clone());
+/// Sort rows by timestamp and deduplicate rows with the same timestamp by the policy,
+/// rows with the same timestamp are in the order of writing.
+pub fn dedup_and_sort_row_data(mut data: Vec<RowData>, policy: DedupPolicy) -> Vec<RowData> {
+    // 稳定排序, 保持相同时间戳的数据的写入顺序
+    data.sort_by_key(|row| row.ts);
+
+    let mut result: Vec<RowData> = Vec::with_capacity(data.len());
+    for row_data in data {
+        match result.last_mut() {
+            Some(existing_row) if existing_row.ts == row_data.ts => {
+                existing_row.dedup(row_data, policy);
             }
-        } else {
-            result.push(row_data.clone());
+            _ => result.push(row_data),
         }
     }
     result
@@ -994,10 +1007,49 @@ mod test_memcache {
     use memory_pool::{GreedyMemoryPool, MemoryPool};
     use models::field_value::FieldVal;
     use models::predicate::domain::TimeRange;
-    use models::schema::{ColumnType, TableColumn, TskvTableSchema};
+    use models::schema::{ColumnType, DedupPolicy, TableColumn, TskvTableSchema};
     use models::{SeriesId, SeriesKey, ValueType};
 
-    use super::{MemCache, OrderedRowsData, RowData, RowGroup};
+    use super::{dedup_and_sort_row_data, MemCache, OrderedRowsData, RowData, RowGroup};
+
+    #[test]
+    fn test_dedup_and_sort_row_data() {
+        let row = |ts: i64, fields: Vec<Option<i64>>| RowData {
+            ts,
+            fields: fields
+                .into_iter()
+                .map(|f| f.map(FieldVal::Integer))
+                .collect(),
+        };
+        let rows = vec![
+            row(2, vec![Some(1), Some(1), None]),
+            row(1, vec![Some(1), None, None]),
+            row(2, vec![None, Some(2), Some(2)]),
+            row(2, vec![Some(3), None, None]),
+        ];
+
+        let cases = [
+            (
+                DedupPolicy::LastWriteWins,
+                row(2, vec![Some(3), None, None]),
+            ),
+            (DedupPolicy::KeepFirst, row(2, vec![Some(1), Some(1), None])),
+            (DedupPolicy::Merge, row(2, vec![Some(3), Some(2), Some(2)])),
+        ];
+        for (policy, expected) in cases {
+            let result = dedup_and_sort_row_data(rows.clone(), policy);
+            assert_eq!(result, vec![row(1, vec![Some(1), None, None]), expected]);
+        }
+
+        let mut rows = OrderedRowsData::new();
+        rows.insert_dedup(row(1, vec![Some(1), None]), DedupPolicy::KeepFirst);
+        rows.insert_dedup(row(1, vec![Some(2), Some(2)]), DedupPolicy::KeepFirst);
+        rows.insert_dedup(row(2, vec![Some(2), None]), DedupPolicy::KeepFirst);
+        assert_eq!(
+            rows.get_ref_rows().iter().cloned().collect::<Vec<_>>(),
+            vec![row(1, vec![Some(1), None]), row(2, vec![Some(2), None])]
+        );
+    }
 
     #[test]
     fn test_write_group() {
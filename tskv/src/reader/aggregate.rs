@@ -0,0 +1,711 @@
+//! Aggregations pushed down to the scan, see [`PushedAggregate`].
+//!
+//! Partial aggregations are calculated for each series (and window if any):
+//! - `count`/`min`/`max` of column groups fully covered by the time ranges and a window are
+//!   answered from page statistics,
+//! - `sum`/`first`/`last` and column groups on the boundary are answered from decoded pages,
+//! - series with data in memcache, overlapping column groups or tombstones are read by
+//!   [`SeriesGroupBatchReaderFactory`] and aggregated row by row.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use arrow::array::{new_null_array, Array, ArrayRef, Int64Array, StringArray};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
+use datafusion::scalar::ScalarValue;
+use futures::TryStreamExt;
+use models::field_value::FieldVal;
+use models::predicate::domain::{PushedAggregate, PushedAggregateFunction, TimeRanges};
+use models::schema::{TableColumn, TskvTableSchema};
+use models::{ColumnId, SeriesId, SeriesKey, Timestamp};
+use trace::SpanRecorder;
+
+use super::iterator_v2::SeriesGroupBatchReaderFactory;
+use super::{
+    DataReference, EmptySchemableTskvRecordBatchStream, QueryOption,
+    SchemableMemoryBatchReaderStream, SendableTskvRecordBatchStream,
+};
+use crate::tseries_family::SuperVersion;
+use crate::tsm2::page::{ColumnGroup, PageMeta, PageStatistics};
+use crate::tsm2::reader::TSM2Reader;
+use crate::tsm2::writer::Column;
+use crate::{EngineRef, Error, Result};
+
+pub async fn execute(
+    engine: EngineRef,
+    super_version: Arc<SuperVersion>,
+    query_option: QueryOption,
+    series_ids: &[SeriesId],
+    span_recorder: SpanRecorder,
+) -> Result<SendableTskvRecordBatchStream> {
+    let schema = query_option.df_schema.clone();
+    let mut aggregator = SeriesAggregator::try_new(&query_option)?;
+
+    let time_ranges = query_option.split.time_ranges();
+    let column_files = super_version.column_files_by_sid_and_time(series_ids, &time_ranges);
+    let mut column_files_with_reader = Vec::with_capacity(column_files.len());
+    for f in column_files {
+        let reader = super_version.version.get_tsm_reader2(f.file_path()).await?;
+        column_files_with_reader.push((f, reader));
+    }
+
+    let table_schema = &query_option.table_schema;
+    let series_keys = engine
+        .get_series_key(
+            &table_schema.tenant,
+            &table_schema.db,
+            &table_schema.name,
+            super_version.ts_family_id,
+            series_ids,
+        )
+        .await?;
+
+    let mut unaggregated_series_ids = vec![];
+    for (sid, series_key) in series_ids.iter().zip(series_keys) {
+        let mut data =
+            SeriesGroupBatchReaderFactory::filter_chunks(&column_files_with_reader, *sid).await?;
+        data.append(
+            SeriesGroupBatchReaderFactory::filter_rowgroups(
+                super_version.caches.clone(),
+                *sid,
+                time_ranges.clone(),
+            )
+            .await?
+            .as_mut(),
+        );
+        data.retain(|d| !d.time_range().is_none() && time_ranges.overlaps(&d.time_range()));
+
+        if !aggregator.is_aggregatable(*sid, &data) {
+            unaggregated_series_ids.push(*sid);
+            continue;
+        }
+
+        let tags = aggregator.series_tags(&series_key)?;
+        for d in data {
+            if let DataReference::Chunk(chunk, reader) = d {
+                for column_group in chunk.column_group().values() {
+                    aggregator
+                        .aggregate_column_group(&tags, &reader, column_group)
+                        .await?;
+                }
+            }
+        }
+    }
+
+    if !unaggregated_series_ids.is_empty() {
+        let factory = SeriesGroupBatchReaderFactory::new(
+            engine,
+            aggregator.scan_option(&query_option),
+            super_version,
+            span_recorder.child("SeriesGroupBatchReaderFactory"),
+            ExecutionPlanMetricsSet::new(),
+        );
+        if let Some(reader) = factory
+            .create(
+                span_recorder.child("SeriesGroupBatchReader"),
+                &unaggregated_series_ids,
+                None,
+            )
+            .await?
+        {
+            let mut stream = reader.process()?;
+            while let Some(batch) = stream.try_next().await? {
+                aggregator.aggregate_batch(&batch)?;
+            }
+        }
+    }
+
+    match aggregator.finish(schema.clone())? {
+        Some(batch) => Ok(Box::pin(SchemableMemoryBatchReaderStream::new(
+            schema,
+            vec![batch],
+        ))),
+        None => Ok(Box::pin(EmptySchemableTskvRecordBatchStream::new(schema))),
+    }
+}
+
+/// Accumulators of the windows of each group of tags.
+type Groups = BTreeMap<Vec<Option<String>>, BTreeMap<Option<Timestamp>, Vec<Accumulator>>>;
+
+struct SeriesAggregator {
+    aggregate: PushedAggregate,
+    table_schema: Arc<TskvTableSchema>,
+    time_column: TableColumn,
+    group_tags: Vec<TableColumn>,
+    /// Field columns of `aggregate.functions`.
+    columns: Vec<TableColumn>,
+    time_ranges: Arc<TimeRanges>,
+    groups: Groups,
+}
+
+impl SeriesAggregator {
+    /// The columns of `df_schema` are the time column (if aggregated by window),
+    /// the grouped tags, and then the results of the functions.
+    fn try_new(query_option: &QueryOption) -> Result<Self> {
+        let table_schema = query_option.table_schema.clone();
+        let aggregate = query_option
+            .aggregates
+            .clone()
+            .ok_or_else(|| Error::CommonError {
+                reason: "no aggregate is pushed down".to_string(),
+            })?;
+        let time_column = table_schema.time_column();
+
+        let fields = query_option.df_schema.fields();
+        let num_keys = fields
+            .len()
+            .checked_sub(aggregate.output_columns())
+            .ok_or_else(|| Error::CommonError {
+                reason: format!(
+                    "invalid schema of pushed aggregate: {}",
+                    query_option.df_schema
+                ),
+            })?;
+        let mut keys = fields[..num_keys].iter();
+        if aggregate.window.is_some() {
+            match keys.next() {
+                Some(field) if field.name() == &time_column.name => {}
+                _ => {
+                    return Err(Error::CommonError {
+                        reason: format!(
+                            "the first column of pushed aggregate by window must be {}",
+                            time_column.name
+                        ),
+                    })
+                }
+            }
+        }
+        let group_tags = keys
+            .map(|field| match table_schema.column(field.name()) {
+                Some(column) if column.column_type.is_tag() => Ok(column.clone()),
+                _ => Err(Error::CommonError {
+                    reason: format!(
+                        "column {} can't be the group key of pushed aggregate",
+                        field.name()
+                    ),
+                }),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let columns = aggregate
+            .functions
+            .iter()
+            .map(|function| match table_schema.column(function.column()) {
+                Some(column) if column.column_type.is_field() => Ok(column.clone()),
+                _ => Err(Error::CommonError {
+                    reason: format!(
+                        "column {} can't be aggregated by pushed aggregate",
+                        function.column()
+                    ),
+                }),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            aggregate,
+            table_schema,
+            time_column,
+            group_tags,
+            columns,
+            time_ranges: query_option.split.time_ranges(),
+            groups: BTreeMap::new(),
+        })
+    }
+
+    fn window_start(&self, ts: Timestamp) -> Option<Timestamp> {
+        self.aggregate.window.map(|w| w.window_start(ts))
+    }
+
+    fn series_tags(&self, series_key: &SeriesKey) -> Result<Vec<Option<String>>> {
+        self.group_tags
+            .iter()
+            .map(|column| {
+                series_key
+                    .tag_string_val(&column.name)
+                    .map_err(|e| Error::CommonError {
+                        reason: format!("invalid tag value of column {}: {}", column.name, e),
+                    })
+            })
+            .collect()
+    }
+
+    /// Data of a series can be aggregated by column groups only if none of the data is in
+    /// memcache, column groups don't overlap, and no tombstone overlaps the aggregated columns.
+    fn is_aggregatable(&self, series_id: SeriesId, data: &[DataReference]) -> bool {
+        let mut time_ranges = vec![];
+        for d in data {
+            match d {
+                DataReference::Memcache(..) => return false,
+                DataReference::Chunk(chunk, reader) => {
+                    if reader.has_tombstone() {
+                        let tombstone = reader.tombstone();
+                        let has_tombstone = std::iter::once(&self.time_column)
+                            .chain(self.columns.iter())
+                            .any(|c| tombstone.overlaps(series_id, c.id, chunk.time_range()));
+                        if has_tombstone {
+                            return false;
+                        }
+                    }
+                    time_ranges.extend(chunk.column_group().values().map(|cg| *cg.time_range()));
+                }
+            }
+        }
+
+        time_ranges.sort_unstable_by_key(|tr| tr.min_ts);
+        time_ranges.windows(2).all(|w| w[0].max_ts < w[1].min_ts)
+    }
+
+    async fn aggregate_column_group(
+        &mut self,
+        tags: &[Option<String>],
+        reader: &TSM2Reader,
+        column_group: &ColumnGroup,
+    ) -> Result<()> {
+        let time_range = *column_group.time_range();
+        if !self.time_ranges.overlaps(&time_range) {
+            return Ok(());
+        }
+        let covered = reader.footer().has_exact_page_statistics()
+            && self.time_ranges.includes(&time_range)
+            && self.window_start(time_range.min_ts) == self.window_start(time_range.max_ts);
+
+        // Decode the pages that can't be answered from statistics.
+        let decode_rows = !covered
+            || !self
+                .aggregate
+                .functions
+                .iter()
+                .all(is_answered_by_statistics);
+        let mut pages: HashMap<ColumnId, Column> = HashMap::new();
+        for page in column_group.pages() {
+            let column_id = page.meta().column.id;
+            let needed = if column_id == self.time_column.id {
+                decode_rows
+            } else {
+                self.aggregate
+                    .functions
+                    .iter()
+                    .zip(self.columns.iter())
+                    .any(|(f, c)| c.id == column_id && (!covered || !is_answered_by_statistics(f)))
+            };
+            if needed {
+                pages.insert(column_id, reader.read_page(page).await?.to_column()?);
+            }
+        }
+        let time = pages.get(&self.time_column.id);
+
+        let window_start = self.window_start(time_range.min_ts);
+        let windows = self.groups.entry(tags.to_vec()).or_default();
+        let functions = &self.aggregate.functions;
+
+        if covered {
+            let accumulators = windows
+                .entry(window_start)
+                .or_insert_with(|| new_accumulators(functions));
+            for ((accumulator, function), column) in accumulators
+                .iter_mut()
+                .zip(functions.iter())
+                .zip(self.columns.iter())
+            {
+                if is_answered_by_statistics(function) {
+                    if let Some(page) = column_group
+                        .pages()
+                        .iter()
+                        .find(|p| p.meta().column.id == column.id)
+                    {
+                        accumulator.update_statistics(page.meta())?;
+                    }
+                } else if let (Some(time), Some(values)) = (time, pages.get(&column.id)) {
+                    for i in 0..values.len() {
+                        if let (Some(FieldVal::Integer(ts)), Some(value)) =
+                            (time.get(i), values.get(i))
+                        {
+                            accumulator.update(ts, field_val_to_scalar(value))?;
+                        }
+                    }
+                }
+            }
+            return Ok(());
+        }
+
+        let time = time.ok_or_else(|| Error::CommonError {
+            reason: format!(
+                "time page of column group {} not found",
+                column_group.column_group_id()
+            ),
+        })?;
+        for i in 0..time.len() {
+            let ts = match time.get(i) {
+                Some(FieldVal::Integer(ts)) => ts,
+                _ => continue,
+            };
+            if !self.time_ranges.contains(ts) {
+                continue;
+            }
+            let accumulators = windows
+                .entry(self.aggregate.window.map(|w| w.window_start(ts)))
+                .or_insert_with(|| new_accumulators(functions));
+            for (accumulator, column) in accumulators.iter_mut().zip(self.columns.iter()) {
+                if let Some(value) = pages.get(&column.id).and_then(|c| c.get(i)) {
+                    accumulator.update(ts, field_val_to_scalar(value))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Option to read the series that can't be aggregated by column groups,
+    /// the columns are time, the grouped tags and the aggregated fields.
+    fn scan_option(&self, query_option: &QueryOption) -> QueryOption {
+        let mut columns = vec![self.time_column.clone()];
+        columns.extend(self.group_tags.iter().cloned());
+        for column in self.columns.iter() {
+            if !columns.iter().any(|c| c.id == column.id) {
+                columns.push(column.clone());
+            }
+        }
+        let table_schema = TskvTableSchema::new(
+            self.table_schema.tenant.clone(),
+            self.table_schema.db.clone(),
+            self.table_schema.name.clone(),
+            columns,
+        );
+
+        QueryOption::new(
+            query_option.batch_size,
+            query_option.split.clone(),
+            None,
+            table_schema.to_arrow_schema(),
+            Arc::new(table_schema),
+        )
+    }
+
+    /// Aggregate record batches read with [`Self::scan_option`].
+    fn aggregate_batch(&mut self, batch: &RecordBatch) -> Result<()> {
+        let time = batch
+            .column_by_name(&self.time_column.name)
+            .ok_or_else(|| Error::CommonError {
+                reason: format!("column {} not found", self.time_column.name),
+            })?;
+        let time = cast(time, &DataType::Int64)?;
+        let time =
+            time.as_any()
+                .downcast_ref::<Int64Array>()
+                .ok_or_else(|| Error::CommonError {
+                    reason: format!("column {} is not a timestamp", self.time_column.name),
+                })?;
+        let tags = self
+            .group_tags
+            .iter()
+            .map(|column| {
+                batch
+                    .column_by_name(&column.name)
+                    .and_then(|a| a.as_any().downcast_ref::<StringArray>())
+                    .ok_or_else(|| Error::CommonError {
+                        reason: format!("tag column {} not found", column.name),
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let values = self
+            .columns
+            .iter()
+            .map(|column| batch.column_by_name(&column.name))
+            .collect::<Vec<_>>();
+
+        for row in 0..batch.num_rows() {
+            if time.is_null(row) || !self.time_ranges.contains(time.value(row)) {
+                continue;
+            }
+            let ts = time.value(row);
+            let key = tags
+                .iter()
+                .map(|a| a.is_valid(row).then(|| a.value(row).to_string()))
+                .collect::<Vec<_>>();
+            let window_start = self.window_start(ts);
+            let functions = &self.aggregate.functions;
+            let accumulators = self
+                .groups
+                .entry(key)
+                .or_default()
+                .entry(window_start)
+                .or_insert_with(|| new_accumulators(functions));
+            for (accumulator, array) in accumulators.iter_mut().zip(values.iter()) {
+                if let Some(array) = array.filter(|a| a.is_valid(row)) {
+                    accumulator.update(ts, ScalarValue::try_from_array(array, row)?)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `None` if there is no data.
+    fn finish(self, schema: SchemaRef) -> Result<Option<RecordBatch>> {
+        let mut columns: Vec<Vec<Option<ScalarValue>>> = vec![vec![]; schema.fields().len()];
+        for (tags, windows) in self.groups {
+            for (window_start, accumulators) in windows {
+                let row = window_start
+                    .map(|ts| Some(ScalarValue::Int64(Some(ts))))
+                    .into_iter()
+                    .chain(tags.iter().map(|t| Some(ScalarValue::Utf8(t.clone()))))
+                    .chain(accumulators.iter().flat_map(|a| a.evaluate()));
+                for (column, value) in columns.iter_mut().zip(row) {
+                    column.push(value);
+                }
+            }
+        }
+        if columns.first().map(|c| c.is_empty()).unwrap_or(true) {
+            return Ok(None);
+        }
+
+        let arrays = columns
+            .into_iter()
+            .zip(schema.fields().iter())
+            .map(|(values, field)| scalars_to_array(values, field.data_type()))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(RecordBatch::try_new(schema, arrays)?))
+    }
+}
+
+/// Page statistics only have the null count, min and max of values,
+/// `sum`/`first`/`last` are calculated from the decoded pages.
+fn is_answered_by_statistics(function: &PushedAggregateFunction) -> bool {
+    matches!(
+        function,
+        PushedAggregateFunction::Count(_)
+            | PushedAggregateFunction::Min(_)
+            | PushedAggregateFunction::Max(_)
+    )
+}
+
+fn new_accumulators(functions: &[PushedAggregateFunction]) -> Vec<Accumulator> {
+    functions.iter().map(Accumulator::new).collect()
+}
+
+/// Partial aggregation of a [`PushedAggregateFunction`].
+#[derive(Debug, Clone)]
+pub(crate) enum Accumulator {
+    Count(i64),
+    Min(Option<ScalarValue>),
+    Max(Option<ScalarValue>),
+    Sum(Option<ScalarValue>),
+    First(Option<(Timestamp, ScalarValue)>),
+    Last(Option<(Timestamp, ScalarValue)>),
+}
+
+impl Accumulator {
+    pub(crate) fn new(function: &PushedAggregateFunction) -> Self {
+        match function {
+            PushedAggregateFunction::Count(_) => Self::Count(0),
+            PushedAggregateFunction::Min(_) => Self::Min(None),
+            PushedAggregateFunction::Max(_) => Self::Max(None),
+            PushedAggregateFunction::Sum(_) => Self::Sum(None),
+            PushedAggregateFunction::First(_) => Self::First(None),
+            PushedAggregateFunction::Last(_) => Self::Last(None),
+        }
+    }
+
+    /// Update by a non-null value.
+    fn update(&mut self, ts: Timestamp, value: ScalarValue) -> Result<()> {
+        match self {
+            Self::Count(count) => *count += 1,
+            Self::Min(min) => {
+                if min.as_ref().map_or(true, |m| &value < m) {
+                    *min = Some(value);
+                }
+            }
+            Self::Max(max) => {
+                if max.as_ref().map_or(true, |m| &value > m) {
+                    *max = Some(value);
+                }
+            }
+            Self::Sum(sum) => {
+                *sum = Some(match sum.take() {
+                    Some(s) => s.add(&value)?,
+                    None => value,
+                });
+            }
+            Self::First(first) => {
+                if first.as_ref().map_or(true, |(t, _)| ts < *t) {
+                    *first = Some((ts, value));
+                }
+            }
+            Self::Last(last) => {
+                if last.as_ref().map_or(true, |(t, _)| ts > *t) {
+                    *last = Some((ts, value));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Update by the statistics of a page, only for `count`, `min` and `max`.
+    pub(crate) fn update_statistics(&mut self, meta: &PageMeta) -> Result<()> {
+        let (null_count, min, max) = statistics_to_scalars(&meta.statistics);
+        match self {
+            Self::Count(count) => *count += meta.num_values as i64 - null_count as i64,
+            Self::Min(_) => {
+                if let Some(min) = min {
+                    self.update(Timestamp::MIN, min)?;
+                }
+            }
+            Self::Max(_) => {
+                if let Some(max) = max {
+                    self.update(Timestamp::MIN, max)?;
+                }
+            }
+            _ => {
+                return Err(Error::CommonError {
+                    reason: format!("{:?} can't be answered from page statistics", self),
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// `first` and `last` output the time and the value.
+    pub(crate) fn evaluate(&self) -> Vec<Option<ScalarValue>> {
+        match self {
+            Self::Count(count) => vec![Some(ScalarValue::Int64(Some(*count)))],
+            Self::Min(v) | Self::Max(v) | Self::Sum(v) => vec![v.clone()],
+            Self::First(v) | Self::Last(v) => match v {
+                Some((ts, value)) => vec![Some(ScalarValue::Int64(Some(*ts))), Some(value.clone())],
+                None => vec![None, None],
+            },
+        }
+    }
+}
+
+fn field_val_to_scalar(value: FieldVal) -> ScalarValue {
+    match value {
+        FieldVal::Float(v) => ScalarValue::Float64(Some(v)),
+        FieldVal::Integer(v) => ScalarValue::Int64(Some(v)),
+        FieldVal::Unsigned(v) => ScalarValue::UInt64(Some(v)),
+        FieldVal::Boolean(v) => ScalarValue::Boolean(Some(v)),
+        FieldVal::Bytes(v) => ScalarValue::Utf8(Some(String::from_utf8_lossy(&v).to_string())),
+    }
+}
+
+/// Returns `(null_count, min, max)`.
+fn statistics_to_scalars(
+    statistics: &PageStatistics,
+) -> (u64, Option<ScalarValue>, Option<ScalarValue>) {
+    match statistics {
+        PageStatistics::Bool(s) => (
+            s.null_count(),
+            s.min().map(|v| ScalarValue::Boolean(Some(v))),
+            s.max().map(|v| ScalarValue::Boolean(Some(v))),
+        ),
+        PageStatistics::F64(s) => (
+            s.null_count(),
+            s.min().map(|v| ScalarValue::Float64(Some(v))),
+            s.max().map(|v| ScalarValue::Float64(Some(v))),
+        ),
+        PageStatistics::I64(s) => (
+            s.null_count(),
+            s.min().map(|v| ScalarValue::Int64(Some(v))),
+            s.max().map(|v| ScalarValue::Int64(Some(v))),
+        ),
+        PageStatistics::U64(s) => (
+            s.null_count(),
+            s.min().map(|v| ScalarValue::UInt64(Some(v))),
+            s.max().map(|v| ScalarValue::UInt64(Some(v))),
+        ),
+        PageStatistics::Bytes(s) => (
+            s.null_count(),
+            s.min()
+                .as_ref()
+                .map(|v| ScalarValue::Utf8(Some(String::from_utf8_lossy(v).to_string()))),
+            s.max()
+                .as_ref()
+                .map(|v| ScalarValue::Utf8(Some(String::from_utf8_lossy(v).to_string()))),
+        ),
+    }
+}
+
+/// Build an array of `data_type` from the values, `None` is null.
+fn scalars_to_array(values: Vec<Option<ScalarValue>>, data_type: &DataType) -> Result<ArrayRef> {
+    let value_type = match values.iter().flatten().next() {
+        Some(value) => value.get_datatype(),
+        None => return Ok(new_null_array(data_type, values.len())),
+    };
+    let null = ScalarValue::try_from(&value_type)?;
+    let array = ScalarValue::iter_to_array(
+        values
+            .into_iter()
+            .map(|v| v.unwrap_or_else(|| null.clone())),
+    )?;
+    Ok(cast(&array, data_type)?)
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::arrow::array::{Array, TimestampNanosecondArray};
+    use datafusion::arrow::datatypes::{DataType, TimeUnit};
+    use datafusion::scalar::ScalarValue;
+    use models::predicate::domain::PushedAggregateFunction;
+
+    use super::{scalars_to_array, Accumulator};
+
+    #[test]
+    fn test_accumulator() {
+        let column = "value".to_string();
+        let mut accumulators = [
+            Accumulator::new(&PushedAggregateFunction::Count(column.clone())),
+            Accumulator::new(&PushedAggregateFunction::Min(column.clone())),
+            Accumulator::new(&PushedAggregateFunction::Max(column.clone())),
+            Accumulator::new(&PushedAggregateFunction::Sum(column.clone())),
+            Accumulator::new(&PushedAggregateFunction::First(column.clone())),
+            Accumulator::new(&PushedAggregateFunction::Last(column)),
+        ];
+        for (ts, value) in [(2, 3.0), (1, 5.0), (3, -1.0)] {
+            for accumulator in accumulators.iter_mut() {
+                accumulator
+                    .update(ts, ScalarValue::Float64(Some(value)))
+                    .unwrap();
+            }
+        }
+
+        let results = accumulators
+            .iter()
+            .flat_map(|a| a.evaluate())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![
+                Some(ScalarValue::Int64(Some(3))),
+                Some(ScalarValue::Float64(Some(-1.0))),
+                Some(ScalarValue::Float64(Some(5.0))),
+                Some(ScalarValue::Float64(Some(7.0))),
+                Some(ScalarValue::Int64(Some(1))),
+                Some(ScalarValue::Float64(Some(5.0))),
+                Some(ScalarValue::Int64(Some(3))),
+                Some(ScalarValue::Float64(Some(-1.0))),
+            ]
+        );
+
+        let empty = Accumulator::new(&PushedAggregateFunction::First("value".to_string()));
+        assert_eq!(empty.evaluate(), vec![None, None]);
+    }
+
+    #[test]
+    fn test_scalars_to_array() {
+        let data_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let array =
+            scalars_to_array(vec![Some(ScalarValue::Int64(Some(1))), None], &data_type).unwrap();
+        let array = array
+            .as_any()
+            .downcast_ref::<TimestampNanosecondArray>()
+            .unwrap();
+        assert_eq!(array.value(0), 1);
+        assert!(array.is_null(1));
+
+        let array = scalars_to_array(vec![None, None], &DataType::Float64).unwrap();
+        assert_eq!(array.data_type(), &DataType::Float64);
+        assert_eq!(array.null_count(), 2);
+    }
+}
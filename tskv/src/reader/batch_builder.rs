@@ -6,6 +6,7 @@ use arrow::datatypes::SchemaRef;
 use arrow_array::{Array, RecordBatch};
 use datafusion::common::DataFusionError;
 use models::datafusion::cursor::{FieldArray, FieldValues};
+use models::schema::DedupPolicy;
 
 use crate::{Error, Result};
 
@@ -38,12 +39,21 @@ pub struct BatchMergeBuilder<T: FieldArray> {
 
     last_same_rows: Vec<(usize, usize)>,
 
+    /// How to deduplicate the rows with the same sort value
+    dedup_policy: DedupPolicy,
+
     phantom: PhantomData<T>,
 }
 
 impl<T: FieldArray> BatchMergeBuilder<T> {
-    /// Create a new [`BatchMergeBuilder`] with the provided `stream_count` and `batch_size`
-    pub fn new(schema: SchemaRef, stream_count: usize, batch_size: usize) -> Self {
+    /// Create a new [`BatchMergeBuilder`] with the provided `stream_count` and `batch_size`,
+    /// rows with the same sort value are in the order of streams and deduplicated by `dedup_policy`
+    pub fn new(
+        schema: SchemaRef,
+        stream_count: usize,
+        batch_size: usize,
+        dedup_policy: DedupPolicy,
+    ) -> Self {
         let field_len = schema.fields.len();
         Self {
             schema,
@@ -54,6 +64,7 @@ impl<T: FieldArray> BatchMergeBuilder<T> {
                 .collect(),
             last: None,
             last_same_rows: vec![],
+            dedup_policy,
             phantom: Default::default(),
         }
     }
@@ -134,13 +145,25 @@ impl<T: FieldArray> BatchMergeBuilder<T> {
                 self.indices.iter_mut().for_each(|i| i.push(idx))
             }
             _ => {
-                for c_i in 0..self.schema.fields().len() {
-                    for (i, (b_i, r_i)) in self.last_same_rows.iter().enumerate().rev() {
-                        let b_i = *b_i;
-                        let r_i = *r_i;
-                        if self.batches[b_i].1.column(c_i).is_valid(r_i) || i == 0 {
-                            self.indices[c_i].push((b_i, r_i));
-                            break;
+                match self.dedup_policy {
+                    DedupPolicy::LastWriteWins => {
+                        let idx = self.last_same_rows[self.last_same_rows.len() - 1];
+                        self.indices.iter_mut().for_each(|i| i.push(idx))
+                    }
+                    DedupPolicy::KeepFirst => {
+                        let idx = self.last_same_rows[0];
+                        self.indices.iter_mut().for_each(|i| i.push(idx))
+                    }
+                    DedupPolicy::Merge => {
+                        for c_i in 0..self.schema.fields().len() {
+                            for (i, (b_i, r_i)) in self.last_same_rows.iter().enumerate().rev() {
+                                let b_i = *b_i;
+                                let r_i = *r_i;
+                                if self.batches[b_i].1.column(c_i).is_valid(r_i) || i == 0 {
+                                    self.indices[c_i].push((b_i, r_i));
+                                    break;
+                                }
+                            }
                         }
                     }
                 }
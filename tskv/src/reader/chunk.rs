@@ -1,7 +1,12 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use arrow::datatypes::SchemaRef;
+use datafusion::logical_expr::Operator;
 use datafusion::physical_optimizer::pruning::PruningPredicate;
+use datafusion::physical_plan::expressions::{BinaryExpr, Column, InListExpr, Literal};
+use datafusion::physical_plan::PhysicalExpr;
+use datafusion::scalar::ScalarValue;
 
 use super::column_group::statistics::ColumnGroupsStatisticsWrapper;
 use super::Predicate;
@@ -48,6 +53,85 @@ fn filter_column_groups_indices(
     }
 }
 
+/// Returns the string values of columns that rows must equal to one of to satisfy the
+/// predicate, e.g. `a = 'x' AND b IN ('y', 'z')` returns `{a: [x], b: [y, z]}`.
+/// They are used to prune files by bloom filters of columns.
+pub fn equal_string_values(predicate: &Option<Arc<Predicate>>) -> BTreeMap<String, Vec<String>> {
+    let mut values = BTreeMap::new();
+    if let Some(expr) = predicate.as_ref().and_then(|p| p.expr()) {
+        collect_equal_string_values(&expr, &mut values);
+    }
+    values
+}
+
+fn collect_equal_string_values(
+    expr: &Arc<dyn PhysicalExpr>,
+    values: &mut BTreeMap<String, Vec<String>>,
+) {
+    let any = expr.as_any();
+    if let Some(binary) = any.downcast_ref::<BinaryExpr>() {
+        match binary.op() {
+            Operator::And => {
+                collect_equal_string_values(binary.left(), values);
+                collect_equal_string_values(binary.right(), values);
+            }
+            Operator::Eq => {
+                let (left, right) = (binary.left().as_any(), binary.right().as_any());
+                let column_literal = match (
+                    left.downcast_ref::<Column>(),
+                    right.downcast_ref::<Literal>(),
+                ) {
+                    (Some(c), Some(l)) => Some((c, l)),
+                    _ => right
+                        .downcast_ref::<Column>()
+                        .zip(left.downcast_ref::<Literal>()),
+                };
+                if let Some((column, literal)) = column_literal {
+                    if let Some(value) = string_literal(literal) {
+                        push_equal_values(values, column.name(), vec![value]);
+                    }
+                }
+            }
+            _ => {}
+        }
+    } else if let Some(in_list) = any.downcast_ref::<InListExpr>() {
+        if in_list.negated() {
+            return;
+        }
+        if let Some(column) = in_list.expr().as_any().downcast_ref::<Column>() {
+            let list = in_list
+                .list()
+                .iter()
+                .map(|e| {
+                    e.as_any()
+                        .downcast_ref::<Literal>()
+                        .and_then(string_literal)
+                })
+                .collect::<Option<Vec<_>>>();
+            if let Some(list) = list {
+                push_equal_values(values, column.name(), list);
+            }
+        }
+    }
+}
+
+fn string_literal(literal: &Literal) -> Option<String> {
+    match literal.value() {
+        ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => Some(v.clone()),
+        _ => None,
+    }
+}
+
+fn push_equal_values(values: &mut BTreeMap<String, Vec<String>>, column: &str, new: Vec<String>) {
+    // Rows satisfy all of the conjunctions, keep the values in both of them.
+    match values.get_mut(column) {
+        Some(old) => old.retain(|v| new.contains(v)),
+        None => {
+            values.insert(column.to_string(), new);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -55,13 +139,13 @@ mod tests {
     use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
     use datafusion::logical_expr::{BuiltinScalarFunction, Operator};
     use datafusion::physical_expr::execution_props::ExecutionProps;
-    use datafusion::physical_plan::expressions::{lit, BinaryExpr, Column};
+    use datafusion::physical_plan::expressions::{in_list, lit, BinaryExpr, Column};
     use datafusion::physical_plan::functions::create_physical_expr;
     use datafusion::scalar::ScalarValue;
     use models::schema::{ColumnType, TableColumn};
     use models::ValueType;
 
-    use crate::reader::chunk::filter_column_groups_indices;
+    use crate::reader::chunk::{equal_string_values, filter_column_groups_indices};
     use crate::reader::Predicate;
     use crate::tsm2::page::{ColumnGroup, PageMeta, PageStatistics, PageWriteSpec};
     use crate::tsm2::statistics::ValueStatistics;
@@ -279,6 +363,45 @@ mod tests {
         assert_eq!(cgs, vec![true, true, true, true]);
     }
 
+    #[test]
+    fn test_equal_string_values() {
+        let schema = schema();
+        let tag_eq = Arc::new(BinaryExpr::new(
+            lit(ScalarValue::Utf8(Some("a".to_string()))),
+            Operator::Eq,
+            Arc::new(Column::new("tag1", 1)),
+        ));
+        let tag_in = in_list(
+            Arc::new(Column::new("tag1", 1)),
+            vec![
+                lit(ScalarValue::Utf8(Some("a".to_string()))),
+                lit(ScalarValue::Utf8(Some("b".to_string()))),
+            ],
+            &false,
+            &schema,
+        )
+        .unwrap();
+        let field_gt = Arc::new(BinaryExpr::new(
+            Arc::new(Column::new("field1", 2)),
+            Operator::Gt,
+            lit(ScalarValue::Int64(Some(10))),
+        ));
+        let expr = Arc::new(BinaryExpr::new(
+            Arc::new(BinaryExpr::new(tag_in, Operator::And, tag_eq.clone())),
+            Operator::And,
+            field_gt.clone(),
+        ));
+        let predicate = Some(Arc::new(Predicate::new(Some(expr), schema.clone(), None)));
+        let values = equal_string_values(&predicate);
+        assert_eq!(values.len(), 1);
+        assert_eq!(values.get("tag1"), Some(&vec!["a".to_string()]));
+
+        let expr = Arc::new(BinaryExpr::new(tag_eq, Operator::Or, field_gt));
+        let predicate = Some(Arc::new(Predicate::new(Some(expr), schema, None)));
+        assert!(equal_string_values(&predicate).is_empty());
+        assert!(equal_string_values(&None).is_empty());
+    }
+
     #[test]
     fn test_filter_not_exists_column_groups_indices() {
         let schema = schema();
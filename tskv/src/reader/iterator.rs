@@ -13,9 +13,9 @@ use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::physical_plan::metrics::{self, ExecutionPlanMetricsSet, MetricBuilder};
 use models::field_value::DataType;
 use models::meta_data::VnodeId;
-use models::predicate::domain::{self, QueryArgs, QueryExpr};
+use models::predicate::domain::{self, PushedAggregate, QueryArgs, QueryExpr};
 use models::predicate::PlacedSplit;
-use models::schema::{PhysicalCType as ColumnType, TableColumn, TskvTableSchemaRef};
+use models::schema::{PhysicalCType as ColumnType, TskvTableSchemaRef};
 use models::{PhysicalDType as ValueType, SeriesId, Timestamp};
 use protos::kv_service::QueryRecordBatchRequest;
 use tokio::runtime::Runtime;
@@ -334,7 +334,7 @@ pub struct QueryOption {
     pub split: PlacedSplit,
     pub df_schema: SchemaRef,
     pub table_schema: TskvTableSchemaRef,
-    pub aggregates: Option<Vec<TableColumn>>, // TODO: Use PushedAggregateFunction
+    pub aggregates: Option<PushedAggregate>,
 }
 
 impl QueryOption {
@@ -342,7 +342,7 @@ impl QueryOption {
     pub fn new(
         batch_size: usize,
         split: PlacedSplit,
-        aggregates: Option<Vec<TableColumn>>, // TODO: Use PushedAggregateFunction
+        aggregates: Option<PushedAggregate>,
         df_schema: SchemaRef,
         table_schema: TskvTableSchemaRef,
     ) -> Self {
@@ -409,8 +409,8 @@ impl RowIterator {
     fn build_record_builders(query_option: &QueryOption) -> Result<Vec<ArrayBuilderPtr>> {
         // Get builders for aggregating.
         if let Some(aggregates) = query_option.aggregates.as_ref() {
-            let mut builders: Vec<ArrayBuilderPtr> = Vec::with_capacity(aggregates.len());
-            for _ in 0..aggregates.len() {
+            let mut builders: Vec<ArrayBuilderPtr> = Vec::with_capacity(aggregates.functions.len());
+            for _ in 0..aggregates.functions.len() {
                 builders.push(ArrayBuilderPtr::new(
                     Box::new(Int64Builder::with_capacity(query_option.batch_size)),
                     ColumnType::Field(ValueType::Integer),
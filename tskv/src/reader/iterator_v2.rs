@@ -20,10 +20,10 @@ use super::merge::DataMerger;
 use super::series::SeriesReader;
 use super::trace::Recorder;
 use super::{
-    DataReference, EmptySchemableTskvRecordBatchStream, Predicate, PredicateRef, Projection,
-    QueryOption, SendableTskvRecordBatchStream,
+    aggregate, DataReference, EmptySchemableTskvRecordBatchStream, Predicate, PredicateRef,
+    Projection, QueryOption, SendableTskvRecordBatchStream,
 };
-use crate::reader::chunk::filter_column_groups;
+use crate::reader::chunk::{equal_string_values, filter_column_groups};
 use crate::reader::column_group::ColumnGroupReader;
 use crate::reader::filter::DataFilter;
 use crate::reader::function_register::NoRegistry;
@@ -123,10 +123,14 @@ async fn build_stream(
     }
 
     if query_option.aggregates.is_some() {
-        // TODO: 重新实现聚合下推
-        return Err(Error::CommonError {
-            reason: "aggregates push down is not supported yet".to_string(),
-        });
+        return aggregate::execute(
+            engine,
+            super_version,
+            query_option,
+            &series_ids,
+            span_recorder.child("aggregate"),
+        )
+        .await;
     }
 
     let factory = SeriesGroupBatchReaderFactory::new(
@@ -256,8 +260,14 @@ impl SeriesGroupBatchReaderFactory {
         // TODO time column id 需要从上面传下来，当前schema中一定包含time列，所以这里写死为0
         let projection = Projection::from_schema(kv_schema.as_ref(), 0);
         let time_ranges = self.query_option.split.time_ranges();
-        let column_files =
+        let mut column_files =
             super_version.column_files_by_sid_and_time(series_ids, time_ranges.as_ref());
+        // 文件按照写入顺序排列 (level 高的以及 file id 小的文件在前), 用于合并时去重
+        column_files.sort_by(|a, b| {
+            b.level()
+                .cmp(&a.level())
+                .then_with(|| a.file_id().cmp(&b.file_id()))
+        });
 
         // 采集过滤后的文件数量
         metrics
@@ -274,6 +284,24 @@ impl SeriesGroupBatchReaderFactory {
             }
         }
 
+        // 通过表和列的 bloom filter 过滤文件
+        let equal_values = equal_string_values(&predicate);
+        let column_values = equal_values
+            .iter()
+            .filter_map(|(name, values)| {
+                let column = kv_schema.column(name)?;
+                kv_schema.has_bloom_filter(column.id).then(|| {
+                    let values = values.iter().map(|v| v.as_bytes()).collect::<Vec<_>>();
+                    (column.id, values)
+                })
+            })
+            .collect::<Vec<_>>();
+        column_files_with_reader
+            .retain(|(_, reader)| reader.maybe_contains(&kv_schema.name, &column_values));
+        metrics
+            .file_nums_filtered_by_bloom_filter()
+            .set(column_files_with_reader.len());
+
         // 通过sid获取serieskey
         let sid_keys = {
             let _timer = metrics.elapsed_get_series_keys_time().timer();
@@ -384,7 +412,7 @@ impl SeriesGroupBatchReaderFactory {
     }
 
     /// 从给定的文件列表中选择含有指定series的所有chunk及其对应的TSMReader
-    async fn filter_chunks(
+    pub(crate) async fn filter_chunks(
         column_files: &[(Arc<ColumnFile>, Arc<TSM2Reader>)],
         sid: SeriesId,
     ) -> Result<Vec<DataReference>> {
@@ -409,7 +437,7 @@ impl SeriesGroupBatchReaderFactory {
     }
 
     /// filter rowgroup by sid
-    async fn filter_rowgroups(
+    pub(crate) async fn filter_rowgroups(
         caches: CacheGroup,
         sid: SeriesId,
         time_ranges: Arc<TimeRanges>,
@@ -545,9 +573,10 @@ impl SeriesGroupBatchReaderFactory {
             .chunk_nums_filtered_by_statistics()
             .add(chunks.len());
 
-        // 对 chunk 按照时间顺序排序
+        // 记录 chunk 的写入顺序 (文件在前, memcache 在后), 再按照时间顺序排序
         // 使用 group_overlapping_segments 函数来对具有重叠关系的chunk进行分组。
-        chunks.sort_unstable_by_key(|e| e.time_range());
+        let mut chunks = chunks.into_iter().enumerate().collect::<Vec<_>>();
+        chunks.sort_unstable_by_key(|(_, e)| e.time_range());
         let grouped_chunks = group_overlapping_segments(&chunks);
 
         debug!(
@@ -561,8 +590,11 @@ impl SeriesGroupBatchReaderFactory {
         let readers = grouped_chunks
             .into_iter()
             .map(|chunks| -> Result<BatchReaderRef> {
+                // 重叠的 chunk 按照写入顺序合并, 相同时间戳的数据按照表的去重策略处理
+                let mut chunks = chunks.segments();
+                chunks.sort_unstable_by_key(|(write_order, _)| *write_order);
                 let chunk_readers = self.build_chunk_readers(
-                    chunks.segments(),
+                    chunks.into_iter().map(|(_, c)| c).collect(),
                     batch_size,
                     projection,
                     predicate,
@@ -587,6 +619,7 @@ impl SeriesGroupBatchReaderFactory {
                         time_fields_schema.clone(),
                         chunk_readers,
                         batch_size,
+                        query_schema.dedup_policy(),
                         self.merge_reader_metrics_set.clone(),
                     ))
                 } else {
@@ -666,6 +699,7 @@ pub struct SeriesGroupBatchReaderMetrics {
     elapsed_build_batch_reader_time: metrics::Time,
     series_nums: metrics::Gauge,
     file_nums_filtered_by_time_range: metrics::Gauge,
+    file_nums_filtered_by_bloom_filter: metrics::Gauge,
     chunk_nums: metrics::Gauge,
     chunk_nums_filtered_by_statistics: metrics::Count,
     grouped_chunk_nums: metrics::Count,
@@ -690,6 +724,9 @@ impl SeriesGroupBatchReaderMetrics {
         let file_nums_filtered_by_time_range =
             MetricBuilder::new(metrics).gauge("file_nums_filtered_by_time_range", partition);
 
+        let file_nums_filtered_by_bloom_filter =
+            MetricBuilder::new(metrics).gauge("file_nums_filtered_by_bloom_filter", partition);
+
         let chunk_nums = MetricBuilder::new(metrics).gauge("chunk_nums", partition);
 
         let chunk_nums_filtered_by_statistics =
@@ -709,6 +746,7 @@ impl SeriesGroupBatchReaderMetrics {
             elapsed_build_batch_reader_time,
             series_nums,
             file_nums_filtered_by_time_range,
+            file_nums_filtered_by_bloom_filter,
             chunk_nums,
             chunk_nums_filtered_by_statistics,
             grouped_chunk_nums,
@@ -737,6 +775,10 @@ impl SeriesGroupBatchReaderMetrics {
         &self.file_nums_filtered_by_time_range
     }
 
+    pub fn file_nums_filtered_by_bloom_filter(&self) -> &metrics::Gauge {
+        &self.file_nums_filtered_by_bloom_filter
+    }
+
     pub fn chunk_nums(&self) -> &metrics::Gauge {
         &self.chunk_nums
     }
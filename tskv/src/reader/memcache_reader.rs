@@ -11,13 +11,12 @@ use models::predicate::domain::{TimeRange, TimeRanges};
 use models::schema::{ColumnType, TableColumn};
 use models::{ColumnId, Timestamp};
 use parking_lot::RwLock;
-use skiplist::OrderedSkipList;
 
 use super::{
     BatchReader, BatchReaderRef, SchemableTskvRecordBatchStream,
     SendableSchemableTskvRecordBatchStream,
 };
-use crate::memcache::{RowData, SeriesData};
+use crate::memcache::{dedup_and_sort_row_data, RowData, SeriesData};
 use crate::reader::iterator::{ArrayBuilderPtr, RowIterator};
 use crate::reader::utils::TimeRangeProvider;
 use crate::Result;
@@ -91,31 +90,21 @@ impl MemCacheReader {
         match self.read_mode {
             MemcacheReadMode::FieldScan => {
                 // 1.read all columns data to Vec<RowData>
-                let mut row_data_vec: OrderedSkipList<RowData> = OrderedSkipList::new();
+                let mut row_data_vec: Vec<RowData> = Vec::new();
                 let column_ids: Vec<u32> = self.columns.iter().map(|c| c.id).collect();
-                self.series_data
-                    .read()
-                    .read_data_v2(&column_ids[1..], &self.time_ranges, |d| {
-                        row_data_vec.insert(d)
+                let dedup_policy = {
+                    let series_data = self.series_data.read();
+                    series_data.read_data_v2(&column_ids[1..], &self.time_ranges, |d| {
+                        row_data_vec.push(d)
                     });
+                    series_data
+                        .get_schema()
+                        .map(|schema| schema.dedup_policy())
+                        .unwrap_or_default()
+                };
 
                 // 2.merge RowData by ts
-                let mut merge_row_data_vec: Vec<RowData> = Vec::with_capacity(row_data_vec.len());
-                for row_data in row_data_vec {
-                    if let Some(existing_row) = merge_row_data_vec.last_mut() {
-                        if existing_row.ts == row_data.ts {
-                            for (index, field) in row_data.fields.iter().enumerate() {
-                                if let Some(field) = field {
-                                    existing_row.fields[index] = Some(field.clone());
-                                }
-                            }
-                        } else {
-                            merge_row_data_vec.push(row_data.clone());
-                        }
-                    } else {
-                        merge_row_data_vec.push(row_data.clone());
-                    }
-                }
+                let merge_row_data_vec = dedup_and_sort_row_data(row_data_vec, dedup_policy);
 
                 // 3.by Vec<RowData>, build multi ArrayBuilderPtr
                 for row_data in merge_row_data_vec {
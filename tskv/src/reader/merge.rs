@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use arrow::datatypes::SchemaRef;
 use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
-use models::schema::TIME_FIELD;
+use models::schema::{DedupPolicy, TIME_FIELD};
 
 use super::{
     BatchReader, BatchReaderRef, EmptySchemableTskvRecordBatchStream,
@@ -16,21 +16,26 @@ pub struct DataMerger {
     schema: SchemaRef,
     inputs: Vec<BatchReaderRef>,
     batch_size: usize,
+    dedup_policy: DedupPolicy,
 
     metrics: Arc<ExecutionPlanMetricsSet>,
 }
 
 impl DataMerger {
+    /// Data of the same timestamp in `inputs` are in the order of writing,
+    /// and deduplicated by `dedup_policy`.
     pub fn new(
         schema: SchemaRef,
         inputs: Vec<BatchReaderRef>,
         batch_size: usize,
+        dedup_policy: DedupPolicy,
         metrics: Arc<ExecutionPlanMetricsSet>,
     ) -> Self {
         Self {
             schema,
             inputs,
             batch_size,
+            dedup_policy,
             metrics,
         }
     }
@@ -51,6 +56,7 @@ impl DataMerger {
             self.schema.clone(),
             self.batch_size,
             TIME_FIELD,
+            self.dedup_policy,
             &self.metrics,
         )
     }
@@ -62,7 +68,7 @@ impl BatchReader for DataMerger {
     }
 
     fn fmt_as(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "DataMerger: ")
+        write!(f, "DataMerger: dedup_policy={}", self.dedup_policy)
     }
 
     fn children(&self) -> Vec<BatchReaderRef> {
@@ -25,6 +25,7 @@ use crate::tsm2::page::Chunk;
 use crate::tsm2::reader::TSM2Reader;
 use crate::{Error, Result};
 
+pub(crate) mod aggregate;
 mod batch_builder;
 mod chunk;
 mod column_group;
@@ -7,6 +7,7 @@ use arrow_array::RecordBatch;
 use datafusion::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricBuilder, Time};
 use futures::{ready, Stream};
 use models::datafusion::cursor::{Cursor, FieldArray, FieldValues};
+use models::schema::DedupPolicy;
 
 use crate::reader::batch_builder::BatchMergeBuilder;
 use crate::reader::metrics::BaselineMetrics;
@@ -21,22 +22,26 @@ macro_rules! primitive_merge_helper {
 }
 
 macro_rules! merge_helper {
-    ($t:ty, $streams:ident, $schema:ident, $batch_size:ident, $sort_column:ident, $metrics:ident) => {{
+    ($t:ty, $streams:ident, $schema:ident, $batch_size:ident, $sort_column:ident, $dedup_policy:ident, $metrics:ident) => {{
         let streams = ColumnCursorStream::<$t>::new($streams, $sort_column)?;
         return Ok(Box::pin(SortPreservingMergeStream::<$t>::new(
             streams,
             $schema,
             $batch_size,
+            $dedup_policy,
             $metrics,
         )));
     }};
 }
 
+/// Merge the streams sorted by `column_name`, rows with the same value of the column are
+/// in the order of streams, and deduplicated by `dedup_policy`.
 pub fn sort_merge(
     streams: Vec<SendableSchemableTskvRecordBatchStream>,
     schema: SchemaRef,
     batch_size: usize,
     column_name: &str,
+    dedup_policy: DedupPolicy,
     metrics: &ExecutionPlanMetricsSet,
 ) -> Result<SendableSchemableTskvRecordBatchStream> {
     use arrow_array::*;
@@ -44,7 +49,7 @@ pub fn sort_merge(
     // Special case single column comparisons with optimized cursor implementations
     let data_type = schema.field_with_name(column_name)?.data_type();
     downcast_primitive! {
-        data_type => (primitive_merge_helper, streams, schema, batch_size, column_name, metrics),
+        data_type => (primitive_merge_helper, streams, schema, batch_size, column_name, dedup_policy, metrics),
         _ => {}
     }
 
@@ -206,11 +211,12 @@ impl<T: FieldArray + Unpin> SortPreservingMergeStream<T> {
         streams: ColumnCursorStream<T>,
         schema: SchemaRef,
         batch_size: usize,
+        dedup_policy: DedupPolicy,
         metrics: &ExecutionPlanMetricsSet,
     ) -> SortPreservingMergeStream<T> {
         let stream_count = streams.partitions();
         Self {
-            in_progress: BatchMergeBuilder::new(schema, stream_count, batch_size),
+            in_progress: BatchMergeBuilder::new(schema, stream_count, batch_size, dedup_policy),
             batch_size,
             streams,
             cursors: (0..stream_count).map(|_| None).collect(),
@@ -415,6 +421,7 @@ mod test {
     use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
     use futures::StreamExt;
     use models::datafusion::cursor::FieldArray;
+    use models::schema::DedupPolicy;
 
     use crate::reader::partitioned_stream::ColumnCursorStream;
     use crate::reader::sort_merge::SortPreservingMergeStream;
@@ -425,6 +432,7 @@ mod test {
         batches: Vec<RecordBatch>,
         batch_size: usize,
         sort_column: &str,
+        dedup_policy: DedupPolicy,
     ) -> crate::Result<SortPreservingMergeStream<T>> {
         let streams = batches
             .into_iter()
@@ -440,6 +448,7 @@ mod test {
             cursor_stream,
             schema.clone(),
             batch_size,
+            dedup_policy,
             &ExecutionPlanMetricsSet::new(),
         ))
     }
@@ -463,8 +472,14 @@ mod test {
 
         let batches = vec![record_batch1, record_batch2, record_batch3];
 
-        let mut stream =
-            new_with_batches::<Int64Array>(schema.clone(), batches, 4096, "column1").unwrap();
+        let mut stream = new_with_batches::<Int64Array>(
+            schema.clone(),
+            batches,
+            4096,
+            "column1",
+            DedupPolicy::Merge,
+        )
+        .unwrap();
         let res = stream.next().await.unwrap().unwrap();
 
         let array1 = Arc::new(Int64Array::from_iter_values([1, 2]));
@@ -496,8 +511,14 @@ mod test {
 
         let batches = vec![record_batch1, record_batch2, record_batch3];
 
-        let mut stream =
-            new_with_batches::<Int64Array>(schema.clone(), batches, 4096, "column1").unwrap();
+        let mut stream = new_with_batches::<Int64Array>(
+            schema.clone(),
+            batches,
+            4096,
+            "column1",
+            DedupPolicy::Merge,
+        )
+        .unwrap();
         let res = stream.next().await.unwrap().unwrap();
 
         let array1 = Arc::new(Int64Array::from_iter_values([1, 2]));
@@ -509,6 +530,67 @@ mod test {
         assert!(stream.next().await.is_none());
     }
 
+    #[tokio::test]
+    async fn test_merge_dedup_policy() {
+        let fields = Fields::from(vec![
+            Field::new("column1", DataType::Int64, true),
+            Field::new("column2", DataType::Int64, true),
+            Field::new("column3", DataType::Int64, true),
+        ]);
+        let schema = SchemaRef::new(Schema::new(fields));
+
+        let record_batch1 = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from_iter_values([1, 2])),
+                Arc::new(Int64Array::from(vec![Some(1), Some(2)])),
+                Arc::new(Int64Array::from(vec![Some(1), Some(2)])),
+            ],
+        )
+        .unwrap();
+        let record_batch2 = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from_iter_values([2, 3])),
+                Arc::new(Int64Array::from(vec![Some(20), Some(30)])),
+                Arc::new(Int64Array::from(vec![None, Some(30)])),
+            ],
+        )
+        .unwrap();
+
+        let cases = [
+            (DedupPolicy::LastWriteWins, vec![Some(20)], vec![None]),
+            (DedupPolicy::KeepFirst, vec![Some(2)], vec![Some(2)]),
+            (DedupPolicy::Merge, vec![Some(20)], vec![Some(2)]),
+        ];
+        for (policy, column2, column3) in cases {
+            let mut stream = new_with_batches::<Int64Array>(
+                schema.clone(),
+                vec![record_batch1.clone(), record_batch2.clone()],
+                4096,
+                "column1",
+                policy,
+            )
+            .unwrap();
+            let res = stream.next().await.unwrap().unwrap();
+
+            let column2 = [vec![Some(1)], column2, vec![Some(30)]].concat();
+            let column3 = [vec![Some(1)], column3, vec![Some(30)]].concat();
+            let expected_batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from_iter_values([1, 2, 3])),
+                    Arc::new(Int64Array::from(column2)),
+                    Arc::new(Int64Array::from(column3)),
+                ],
+            )
+            .unwrap();
+
+            assert_eq!(res, expected_batch, "dedup policy: {policy}");
+            assert!(stream.next().await.is_none());
+        }
+    }
+
     #[tokio::test]
     async fn test_merge_time() {
         let fields = Fields::from(vec![Field::new("column1", DataType::Int64, true)]);
@@ -523,8 +605,14 @@ mod test {
 
         let batches = vec![record_batch1, record_batch2, record_batch3];
 
-        let mut stream =
-            new_with_batches::<Int64Array>(schema.clone(), batches, 4096, "column1").unwrap();
+        let mut stream = new_with_batches::<Int64Array>(
+            schema.clone(),
+            batches,
+            4096,
+            "column1",
+            DedupPolicy::Merge,
+        )
+        .unwrap();
         let res = stream.next().await.unwrap().unwrap();
 
         let array1 = Arc::new(Int64Array::from_iter_values([1, 2]));
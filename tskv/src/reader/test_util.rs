@@ -12,6 +12,7 @@ use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
 use datafusion::physical_plan::SendableRecordBatchStream;
 use futures::executor::block_on;
 use futures::StreamExt;
+use models::schema::DedupPolicy;
 
 use crate::reader::{SchemableMemoryBatchReaderStream, SendableSchemableTskvRecordBatchStream};
 
@@ -91,6 +92,7 @@ pub fn create_sort_merge_stream(
         schema,
         batch_size,
         column_name,
+        DedupPolicy::default(),
         &ExecutionPlanMetricsSet::new(),
     )
     .unwrap()
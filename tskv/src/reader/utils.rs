@@ -61,6 +61,12 @@ pub trait TimeRangeProvider {
     fn time_range(&self) -> TimeRange;
 }
 
+impl<T: TimeRangeProvider> TimeRangeProvider for (usize, T) {
+    fn time_range(&self) -> TimeRange {
+        self.1.time_range()
+    }
+}
+
 /// Given a slice of range-like items `ordered_chunks`, this function groups overlapping segments
 /// and returns a vector of `OverlappingSegments`. It checks for overlaps between the segments
 /// and groups them accordingly.
@@ -25,6 +25,7 @@ use utils::BloomFilter;
 
 use crate::compaction::{CompactTask, FlushReq};
 use crate::error::Result;
+use crate::file_system::cold_store::{self, ColdStore, ColdStub};
 use crate::file_system::file_manager;
 use crate::file_utils::{self, make_delta_file, make_tsm_file};
 use crate::index::ts_index::TSIndex;
@@ -51,6 +52,7 @@ pub struct ColumnFile {
 
     path: PathBuf,
     tsm_reader_cache: Weak<ShardedAsyncCache<String, Arc<TSM2Reader>>>,
+    cold_store: Option<Arc<ColdStore>>,
 }
 
 impl ColumnFile {
@@ -59,6 +61,7 @@ impl ColumnFile {
         path: impl AsRef<Path>,
         series_id_filter: Arc<BloomFilter>,
         tsm_reader_cache: Weak<ShardedAsyncCache<String, Arc<TSM2Reader>>>,
+        cold_store: Option<Arc<ColdStore>>,
     ) -> Self {
         Self {
             file_id: meta.file_id,
@@ -71,6 +74,7 @@ impl ColumnFile {
             compacting: AtomicBool::new(false),
             path: path.as_ref().into(),
             tsm_reader_cache,
+            cold_store,
         }
     }
 
@@ -188,6 +192,22 @@ impl Drop for ColumnFile {
                     cache.remove(&k).await;
                 });
             }
+            if let Some(cold_store) = self.cold_store.clone() {
+                match ColdStub::read_key_sync(path) {
+                    Ok(Some(key)) => {
+                        tokio::spawn(async move {
+                            if let Err(e) = cold_store.delete(&key).await {
+                                error!("Failed to remove cold tsm file '{key}': {e}");
+                            }
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(e) => error!(
+                        "Failed to read cold tsm file stub '{}': {e}",
+                        path.display()
+                    ),
+                }
+            }
             if let Err(e) = std::fs::remove_file(path) {
                 error!(
                     "Failed to remove tsm file {} at '{}': {e}",
@@ -235,6 +255,7 @@ impl ColumnFile {
             compacting: AtomicBool::new(false),
             path: path.as_ref().into(),
             tsm_reader_cache: Weak::new(),
+            cold_store: None,
         }
     }
 
@@ -312,6 +333,7 @@ impl LevelInfo {
             file_path,
             series_filter,
             tsm_reader_cache,
+            cold_store::get_cold_store(&self.storage_opt),
         )));
         self.tsf_id = compact_meta.tsf_id;
         self.cur_size += compact_meta.file_size;
@@ -514,7 +536,9 @@ impl Version {
             None => match self.tsm2_reader_cache.get(&path).await {
                 Some(val) => val,
                 None => {
-                    let tsm_reader = Arc::new(TSM2Reader::open(&path).await?);
+                    let cold_store = cold_store::get_cold_store(&self.storage_opt);
+                    let tsm_reader =
+                        Arc::new(TSM2Reader::open_with_cold_store(&path, cold_store).await?);
                     self.tsm2_reader_cache
                         .insert(path, tsm_reader.clone())
                         .await;
@@ -1175,6 +1199,27 @@ impl TseriesFamily {
                 )
             };
 
+            // Files in the cold tier are downloaded, as the snapshot may be applied on
+            // another node, or after the vnode and it's cold files are deleted.
+            if !f.is_delta {
+                let cold_key =
+                    ColdStub::read_key_sync(&file_path).context(crate::error::IOSnafu)?;
+                if let Some(key) = cold_key {
+                    let cold_store = cold_store::get_cold_store(&opt).ok_or_else(|| {
+                        crate::Error::CommonError {
+                            reason: format!("cold tier is not enabled to read '{key}'"),
+                        }
+                    })?;
+                    debug!(
+                        "Bakcup: downloading cold tsm file {} to {}.",
+                        key,
+                        snapshot_path.display()
+                    );
+                    cold_store.download_file(&key, &snapshot_path).await?;
+                    continue;
+                }
+            }
+
             // Create hard link to tsm/delta file.
             debug!(
                 "Bakcup: creating hard link {} to {}.",
@@ -1200,6 +1245,7 @@ impl TseriesFamily {
         let index = TSIndex::new(
             self.storage_opt
                 .index_dir(self.tenant_database.as_str(), self.tf_id),
+            self.storage_opt.index_engine,
         )
         .await?;
 
@@ -574,6 +574,213 @@ fn decode_with_sentinel(
     Ok(())
 }
 
+// Chimp128 is adapted from "Chimp: Efficient Lossless Floating Point Compression
+// for Time Series Databases" (VLDB 2022), the XOR is calculated with one of the
+// previous 128 values that has the most trailing zeros in common, which works better
+// than Gorilla for values that are not changing smoothly, like sensor data.
+//
+// Layout: encoding(1B) | number of values(4B, big-endian) | bit stream.
+
+const CHIMP_PREVIOUS_VALUES: usize = 128;
+const CHIMP_PREVIOUS_VALUES_LOG2: u32 = 7;
+const CHIMP_THRESHOLD: u32 = 6 + CHIMP_PREVIOUS_VALUES_LOG2;
+const CHIMP_INDICES_MASK: u64 = (1 << (CHIMP_THRESHOLD + 1)) - 1;
+/// Leading zeros are rounded down to one of these values, and stored as the index in 3 bits.
+const CHIMP_LEADING_ZEROS: [u32; 8] = [0, 8, 12, 16, 18, 20, 22, 24];
+
+fn chimp_leading_zeros_index(leading_zeros: u32) -> usize {
+    CHIMP_LEADING_ZEROS
+        .iter()
+        .rposition(|lz| *lz <= leading_zeros)
+        .unwrap_or(0)
+}
+
+struct BitWriter<'a> {
+    dst: &'a mut Vec<u8>,
+    /// Number of bits available in the last byte.
+    available: u32,
+}
+
+impl<'a> BitWriter<'a> {
+    fn new(dst: &'a mut Vec<u8>) -> Self {
+        Self { dst, available: 0 }
+    }
+
+    /// Write the lowest `nbits` bits of `value`, from the most significant one.
+    fn write(&mut self, value: u64, mut nbits: u32) {
+        while nbits > 0 {
+            if self.available == 0 {
+                self.dst.push(0);
+                self.available = 8;
+            }
+            let n = nbits.min(self.available);
+            let bits = (value >> (nbits - n)) & ((1 << n) - 1);
+            *self.dst.last_mut().expect("dst is not empty") |= (bits << (self.available - n)) as u8;
+            self.available -= n;
+            nbits -= n;
+        }
+    }
+}
+
+struct BitReader<'a> {
+    src: &'a [u8],
+    /// Position of the next bit.
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(src: &'a [u8]) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    fn read(&mut self, mut nbits: u32) -> Result<u64, Box<dyn Error + Send + Sync>> {
+        if self.pos + nbits as usize > self.src.len() * 8 {
+            return Err(From::from("chimp: unexpected end of data"));
+        }
+        let mut value = 0_u64;
+        while nbits > 0 {
+            let byte = self.src[self.pos >> 3] as u64;
+            let available = 8 - (self.pos & 7) as u32;
+            let n = nbits.min(available);
+            let bits = (byte >> (available - n)) & ((1 << n) - 1);
+            value = (value << n) | bits;
+            self.pos += n as usize;
+            nbits -= n;
+        }
+        Ok(value)
+    }
+}
+
+pub fn f64_chimp_encode(
+    src: &[f64],
+    dst: &mut Vec<u8>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if src.is_empty() {
+        return Ok(());
+    }
+    let len = u32::try_from(src.len()).map_err(|_| "chimp: too many values")?;
+
+    dst.push(Encoding::Chimp as u8);
+    dst.extend_from_slice(&len.to_be_bytes());
+
+    let mut writer = BitWriter::new(dst);
+    let mut stored_values = [0_u64; CHIMP_PREVIOUS_VALUES];
+    // Position + 1 of the latest value with the same lowest bits, 0 means none.
+    let mut indices = vec![0_usize; CHIMP_INDICES_MASK as usize + 1];
+    let mut stored_leading_zeros = u32::MAX;
+
+    let first = src[0].to_bits();
+    writer.write(first, 64);
+    stored_values[0] = first;
+    indices[(first & CHIMP_INDICES_MASK) as usize] = 1;
+
+    for (i, v) in src.iter().enumerate().skip(1) {
+        let value = v.to_bits();
+        let key = (value & CHIMP_INDICES_MASK) as usize;
+
+        let mut previous_index = (i - 1) % CHIMP_PREVIOUS_VALUES;
+        let mut xor = stored_values[previous_index] ^ value;
+        let mut trailing_zeros = 0;
+        if indices[key] > 0 && i - (indices[key] - 1) <= CHIMP_PREVIOUS_VALUES {
+            let candidate_index = (indices[key] - 1) % CHIMP_PREVIOUS_VALUES;
+            let candidate_xor = stored_values[candidate_index] ^ value;
+            if candidate_xor.trailing_zeros() > CHIMP_THRESHOLD {
+                previous_index = candidate_index;
+                xor = candidate_xor;
+                trailing_zeros = candidate_xor.trailing_zeros();
+            }
+        }
+
+        if xor == 0 {
+            // flag 00 | index of the previous value
+            writer.write(previous_index as u64, 2 + CHIMP_PREVIOUS_VALUES_LOG2);
+            stored_leading_zeros = u32::MAX;
+        } else {
+            let leading_index = chimp_leading_zeros_index(xor.leading_zeros());
+            let leading_zeros = CHIMP_LEADING_ZEROS[leading_index];
+            if trailing_zeros > CHIMP_THRESHOLD {
+                // flag 01 | index of the previous value | leading zeros | significant bits
+                let significant_bits = 64 - leading_zeros - trailing_zeros;
+                writer.write(0b01, 2);
+                writer.write(previous_index as u64, CHIMP_PREVIOUS_VALUES_LOG2);
+                writer.write(leading_index as u64, 3);
+                writer.write(significant_bits as u64, 6);
+                writer.write(xor >> trailing_zeros, significant_bits);
+                stored_leading_zeros = u32::MAX;
+            } else if leading_zeros == stored_leading_zeros {
+                // flag 10 | xor without the leading zeros of the last 11 flag
+                writer.write(0b10, 2);
+                writer.write(xor, 64 - leading_zeros);
+            } else {
+                // flag 11 | leading zeros | xor without the leading zeros
+                stored_leading_zeros = leading_zeros;
+                writer.write(0b11, 2);
+                writer.write(leading_index as u64, 3);
+                writer.write(xor, 64 - leading_zeros);
+            }
+        }
+
+        stored_values[i % CHIMP_PREVIOUS_VALUES] = value;
+        indices[key] = i + 1;
+    }
+
+    Ok(())
+}
+
+pub fn f64_chimp_decode(
+    src: &[u8],
+    dst: &mut Vec<f64>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if src.is_empty() {
+        return Ok(());
+    }
+    if src.len() < 5 {
+        return Err(From::from("chimp: data is too short"));
+    }
+
+    let len = u32::from_be_bytes(src[1..5].try_into()?) as usize;
+    if len == 0 {
+        return Ok(());
+    }
+    dst.reserve(len);
+
+    let mut reader = BitReader::new(&src[5..]);
+    let mut stored_values = [0_u64; CHIMP_PREVIOUS_VALUES];
+    let mut stored_leading_zeros = 0;
+
+    let first = reader.read(64)?;
+    stored_values[0] = first;
+    dst.push(f64::from_bits(first));
+
+    for i in 1..len {
+        let previous = stored_values[(i - 1) % CHIMP_PREVIOUS_VALUES];
+        let value = match reader.read(2)? {
+            0b00 => {
+                let previous_index = reader.read(CHIMP_PREVIOUS_VALUES_LOG2)? as usize;
+                stored_values[previous_index]
+            }
+            0b01 => {
+                let previous_index = reader.read(CHIMP_PREVIOUS_VALUES_LOG2)? as usize;
+                let leading_zeros = CHIMP_LEADING_ZEROS[reader.read(3)? as usize];
+                let significant_bits = reader.read(6)? as u32;
+                let trailing_zeros = 64 - leading_zeros - significant_bits;
+                let xor = reader.read(significant_bits)? << trailing_zeros;
+                stored_values[previous_index] ^ xor
+            }
+            0b10 => previous ^ reader.read(64 - stored_leading_zeros)?,
+            _ => {
+                stored_leading_zeros = CHIMP_LEADING_ZEROS[reader.read(3)? as usize];
+                previous ^ reader.read(64 - stored_leading_zeros)?
+            }
+        };
+
+        stored_values[i % CHIMP_PREVIOUS_VALUES] = value;
+        dst.push(f64::from_bits(value));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 #[allow(clippy::unreadable_literal)]
 #[allow(clippy::excessive_precision)] // TODO: Audit test values for truncation
@@ -581,7 +788,8 @@ mod tests {
     // use test_helpers::approximately_equal;
 
     use crate::tsm::codec::float::{
-        f64_gorilla_decode, f64_gorilla_encode, f64_q_compress_decode, f64_q_compress_encode,
+        f64_chimp_decode, f64_chimp_encode, f64_gorilla_decode, f64_gorilla_encode,
+        f64_q_compress_decode, f64_q_compress_encode,
     };
 
     #[test]
@@ -1795,5 +2003,47 @@ mod tests {
             // verify got same values back
             assert_eq!(got, src, "{}", test.name);
         }
+
+        for test in tests.iter() {
+            let mut dst = vec![];
+            let src = test.input.clone();
+
+            f64_chimp_encode(&src, &mut dst).expect("failed to encode");
+
+            let mut got = vec![];
+            f64_chimp_decode(&dst, &mut got).expect("failed to decode");
+            // verify got same values back
+            assert_eq!(got, src, "{}", test.name);
+        }
+    }
+
+    #[test]
+    fn test_chimp_special_values() {
+        let mut src: Vec<f64> = vec![
+            0.0,
+            -0.0,
+            f64::NAN,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+            f64::MIN,
+        ];
+        // Repeated values out of the window of previous values
+        src.extend((0..300).map(|i| (i % 150) as f64 * 0.1));
+        src.extend((0..300).map(|i| 20.5 + (i % 7) as f64 * 0.25));
+
+        let mut dst = vec![];
+        f64_chimp_encode(&src, &mut dst).expect("failed to encode");
+
+        let mut got = vec![];
+        f64_chimp_decode(&dst, &mut got).expect("failed to decode");
+        assert_eq!(
+            got.iter().map(|v| v.to_bits()).collect::<Vec<_>>(),
+            src.iter().map(|v| v.to_bits()).collect::<Vec<_>>()
+        );
+
+        let mut got = vec![];
+        f64_chimp_decode(&dst[..dst.len() / 2], &mut got).expect_err("data is truncated");
     }
 }
@@ -8,8 +8,9 @@ use crate::tsm::codec::boolean::{
     bool_without_compress_encode,
 };
 use crate::tsm::codec::float::{
-    f64_gorilla_decode, f64_gorilla_encode, f64_q_compress_decode, f64_q_compress_encode,
-    f64_without_compress_decode, f64_without_compress_encode,
+    f64_chimp_decode, f64_chimp_encode, f64_gorilla_decode, f64_gorilla_encode,
+    f64_q_compress_decode, f64_q_compress_encode, f64_without_compress_decode,
+    f64_without_compress_encode,
 };
 use crate::tsm::codec::integer::{
     i64_q_compress_decode, i64_q_compress_encode, i64_without_compress_decode,
@@ -176,6 +177,18 @@ impl FloatCodec for QuantileFloatCodec {
     }
 }
 
+struct ChimpFloatCodec();
+
+impl FloatCodec for ChimpFloatCodec {
+    fn encode(&self, src: &[f64], dst: &mut Vec<u8>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        f64_chimp_encode(src, dst)
+    }
+
+    fn decode(&self, src: &[u8], dst: &mut Vec<f64>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        f64_chimp_decode(src, dst)
+    }
+}
+
 pub trait UnsignedCodec {
     fn encode(&self, src: &[u64], dst: &mut Vec<u8>) -> Result<(), Box<dyn Error + Send + Sync>>;
     fn decode(&self, src: &[u8], dst: &mut Vec<u64>) -> Result<(), Box<dyn Error + Send + Sync>>;
@@ -392,6 +405,7 @@ pub fn get_f64_codec(algo: Encoding) -> Box<dyn FloatCodec + Send + Sync> {
         Encoding::Null => Box::new(NullFloatCodec()),
         Encoding::Gorilla => Box::new(GorillaFloatCodec()),
         Encoding::Quantile => Box::new(QuantileFloatCodec()),
+        Encoding::Chimp => Box::new(ChimpFloatCodec()),
         _ => Box::new(GorillaFloatCodec()),
     }
 }
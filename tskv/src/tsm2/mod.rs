@@ -18,6 +18,8 @@ const INDEX_META_SIZE: usize = 11;
 const BLOCK_META_SIZE: usize = 44;
 const BLOOM_FILTER_SIZE: usize = 64;
 const BLOOM_FILTER_BITS: u64 = 512;
+// 8 KiB for each column with bloom filter of a table in a file
+const COLUMN_BLOOM_FILTER_BITS: u64 = 64 * 1024;
 // 64 * 8
 const FOOTER_SIZE: usize = 129;
 
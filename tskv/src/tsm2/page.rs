@@ -5,8 +5,10 @@ use arrow::datatypes::{Field, Schema, SchemaRef};
 use datafusion::parquet::data_type::AsBytes;
 use models::field_value::FieldVal;
 use models::predicate::domain::TimeRange;
-use models::schema::{ColumnType, TableColumn, TskvTableSchema, TskvTableSchemaRef};
-use models::{SeriesId, SeriesKey, ValueType};
+use models::schema::{
+    ColumnType, LegacyTskvTableSchema, TableColumn, TskvTableSchema, TskvTableSchemaRef,
+};
+use models::{ColumnId, SeriesId, SeriesKey, ValueType};
 use serde::{Deserialize, Serialize};
 use utils::bitset::ImmutBitSet;
 use utils::BloomFilter;
@@ -18,7 +20,7 @@ use crate::tsm::codec::{
     get_bool_codec, get_encoding, get_f64_codec, get_i64_codec, get_str_codec, get_u64_codec,
 };
 use crate::tsm2::writer::Column;
-use crate::tsm2::ColumnGroupID;
+use crate::tsm2::{ColumnGroupID, BLOOM_FILTER_SIZE, FOOTER_SIZE};
 use crate::Error;
 
 #[derive(Debug)]
@@ -484,6 +486,9 @@ pub struct ChunkGroupWriteSpec {
     pub(crate) chunk_group_size: usize,
     pub(crate) time_range: TimeRange,
     pub(crate) count: usize,
+    // bloom filters of values of the tag and string field columns,
+    // see `TableOptions::bloom_filter_columns`
+    pub(crate) column_bloom_filters: BTreeMap<ColumnId, BloomFilter>,
 }
 
 impl ChunkGroupWriteSpec {
@@ -500,6 +505,7 @@ impl ChunkGroupWriteSpec {
             chunk_group_size,
             time_range,
             count,
+            column_bloom_filters: BTreeMap::new(),
         }
     }
 
@@ -507,6 +513,18 @@ impl ChunkGroupWriteSpec {
         &self.table_schema.name
     }
 
+    /// Returns false if the column of the table is indexed by bloom filter
+    /// and the value definitely not exists in this file.
+    pub fn maybe_column_value_exist(&self, column_id: ColumnId, value: &[u8]) -> bool {
+        self.column_bloom_filters
+            .get(&column_id)
+            .map_or(true, |bloom_filter| bloom_filter.maybe_contains(value))
+    }
+
+    pub fn column_bloom_filters(&self) -> &BTreeMap<ColumnId, BloomFilter> {
+        &self.column_bloom_filters
+    }
+
     pub fn chunk_group_offset(&self) -> u64 {
         self.chunk_group_offset
     }
@@ -546,7 +564,34 @@ impl ChunkGroupMeta {
         bincode::serialize(&self).map_err(|e| Error::Serialize { source: e.into() })
     }
 
-    pub fn deserialize(bytes: &[u8]) -> Result<Self> {
+    /// Serialize in the layout before footer version 4, see [`LegacyChunkGroupWriteSpec`].
+    pub fn serialize_legacy(&self) -> Result<Vec<u8>> {
+        fn serialize<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+            bincode::serialize(value).map_err(|e| Error::Serialize { source: e.into() })
+        }
+        let mut buf = serialize(&(self.tables.len() as u64))?;
+        for (name, spec) in self.tables.iter() {
+            buf.extend(serialize(name)?);
+            // The table options are the last field of the schema, which were not written.
+            let schema = serialize(spec.table_schema.as_ref())?;
+            let options = serialize(spec.table_schema.options())?;
+            buf.extend_from_slice(&schema[..schema.len() - options.len()]);
+            buf.extend(serialize(&(
+                spec.chunk_group_offset,
+                spec.chunk_group_size,
+                spec.time_range,
+                spec.count,
+            ))?);
+        }
+        Ok(buf)
+    }
+
+    pub fn deserialize(bytes: &[u8], footer_version: u8) -> Result<Self> {
+        if footer_version < 4 {
+            let meta: LegacyChunkGroupMeta =
+                bincode::deserialize(bytes).map_err(|e| Error::Deserialize { source: e.into() })?;
+            return Ok(meta.into());
+        }
         bincode::deserialize(bytes).map_err(|e| Error::Deserialize { source: e.into() })
     }
 
@@ -573,8 +618,48 @@ impl ChunkGroupMeta {
     }
 }
 
+/// [`ChunkGroupWriteSpec`] written before footer version 4, without bloom filters of columns.
+#[derive(Deserialize)]
+struct LegacyChunkGroupWriteSpec {
+    table_schema: LegacyTskvTableSchema,
+    chunk_group_offset: u64,
+    chunk_group_size: usize,
+    time_range: TimeRange,
+    count: usize,
+}
+
+#[derive(Deserialize)]
+struct LegacyChunkGroupMeta {
+    tables: BTreeMap<String, LegacyChunkGroupWriteSpec>,
+}
+
+impl From<LegacyChunkGroupMeta> for ChunkGroupMeta {
+    fn from(meta: LegacyChunkGroupMeta) -> Self {
+        let tables = meta
+            .tables
+            .into_iter()
+            .map(|(name, spec)| {
+                let spec = ChunkGroupWriteSpec::new(
+                    Arc::new(spec.table_schema.into()),
+                    spec.chunk_group_offset,
+                    spec.chunk_group_size,
+                    spec.time_range,
+                    spec.count,
+                );
+                (name, spec)
+            })
+            .collect();
+        Self { tables }
+    }
+}
+
 // pub const FOOTER_SIZE: i64 = ;
 
+/// Version of the footer written by [`crate::tsm2::writer::Tsm2Writer`],
+/// the `null_count` of page statistics is exact since version 3,
+/// bloom filters of tables and columns are written since version 4.
+pub const FOOTER_VERSION: u8 = 4;
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Footer {
     pub(crate) version: u8,
@@ -609,12 +694,45 @@ impl Footer {
         self.version
     }
 
+    /// Whether the statistics of pages can be used to answer aggregations.
+    pub fn has_exact_page_statistics(&self) -> bool {
+        self.version >= 3
+    }
+
+    /// The bloom filter of table names is written in the [`BLOOM_FILTER_SIZE`] bytes
+    /// before the fixed-size footer, so that the footer of old files can be read.
     pub fn serialize(&self) -> Result<Vec<u8>> {
-        bincode::serialize(&self).map_err(|e| Error::Serialize { source: e.into() })
+        let mut buf = Vec::with_capacity(BLOOM_FILTER_SIZE + FOOTER_SIZE);
+        if let Some(bloom_filter) = &self.table.bloom_filter {
+            buf.extend_from_slice(bloom_filter.bytes());
+        }
+        bincode::serialize_into(&mut buf, &self)
+            .map_err(|e| Error::Serialize { source: e.into() })?;
+        Ok(buf)
     }
 
+    /// Deserialize from the tail of a tsm file, which is at least [`FOOTER_SIZE`] bytes.
     pub fn deserialize(bytes: &[u8]) -> Result<Self> {
-        bincode::deserialize(bytes).map_err(|e| Error::Deserialize { source: e.into() })
+        let footer_pos =
+            bytes
+                .len()
+                .checked_sub(FOOTER_SIZE)
+                .ok_or_else(|| Error::CommonError {
+                    reason: format!("footer of tsm file is too short: {}", bytes.len()),
+                })?;
+        let mut footer: Self = bincode::deserialize(&bytes[footer_pos..])
+            .map_err(|e| Error::Deserialize { source: e.into() })?;
+        if footer.version >= 4 {
+            let bloom_filter_pos =
+                footer_pos
+                    .checked_sub(BLOOM_FILTER_SIZE)
+                    .ok_or_else(|| Error::CommonError {
+                        reason: "bloom filter of tables of tsm file is missing".to_string(),
+                    })?;
+            footer.table.bloom_filter =
+                Some(BloomFilter::with_data(&bytes[bloom_filter_pos..footer_pos]));
+        }
+        Ok(footer)
     }
 
     pub fn maybe_series_exist(&self, series_id: &SeriesId) -> bool {
@@ -622,13 +740,25 @@ impl Footer {
             .bloom_filter
             .maybe_contains((*series_id).as_bytes().as_ref())
     }
+
+    /// Returns false if the table definitely not exists in this file,
+    /// files before footer version 4 have no bloom filter of tables.
+    pub fn maybe_table_exist(&self, table: &str) -> bool {
+        self.table
+            .bloom_filter
+            .as_ref()
+            .map_or(true, |bloom_filter| {
+                bloom_filter.maybe_contains(table.as_bytes())
+            })
+    }
 }
 
 ///  7 + 8 + 8 = 23
 #[derive(Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
 pub struct TableMeta {
-    // todo: bloomfilter, store table object id
-    // bloom_filter: BloomFilter,
+    // bloom filter of table names, serialized by `Footer` out of the fixed-size part
+    #[serde(skip)]
+    bloom_filter: Option<BloomFilter>,
     chunk_group_offset: u64,
     chunk_group_size: usize,
 }
@@ -636,11 +766,21 @@ pub struct TableMeta {
 impl TableMeta {
     pub fn new(chunk_group_offset: u64, chunk_group_size: usize) -> Self {
         Self {
+            bloom_filter: None,
             chunk_group_offset,
             chunk_group_size,
         }
     }
 
+    pub fn with_bloom_filter(mut self, bloom_filter: BloomFilter) -> Self {
+        self.bloom_filter = Some(bloom_filter);
+        self
+    }
+
+    pub fn bloom_filter(&self) -> Option<&BloomFilter> {
+        self.bloom_filter.as_ref()
+    }
+
     pub fn chunk_group_offset(&self) -> u64 {
         self.chunk_group_offset
     }
@@ -695,12 +835,13 @@ mod test {
     use models::predicate::domain::TimeRange;
     use utils::BloomFilter;
 
-    use crate::tsm2::page::{Footer, SeriesMeta, TableMeta};
-    use crate::tsm2::BLOOM_FILTER_BITS;
+    use crate::tsm2::page::{Footer, SeriesMeta, TableMeta, FOOTER_VERSION};
+    use crate::tsm2::{BLOOM_FILTER_BITS, BLOOM_FILTER_SIZE, FOOTER_SIZE};
 
     #[test]
     fn test1() {
         let table_meta = TableMeta {
+            bloom_filter: None,
             chunk_group_offset: 100,
             chunk_group_size: 100,
         };
@@ -722,4 +863,41 @@ mod test {
         let footer = Footer::deserialize(&bytess).unwrap();
         assert_eq!(footer, expect_footer);
     }
+
+    #[test]
+    fn test_footer_table_bloom_filter() {
+        let mut table_bloom_filter = BloomFilter::new(BLOOM_FILTER_BITS);
+        table_bloom_filter.insert(b"cpu");
+        let expect_footer = Footer::new(
+            FOOTER_VERSION,
+            TimeRange::new(0, 100),
+            TableMeta::new(100, 100).with_bloom_filter(table_bloom_filter),
+            SeriesMeta::new(
+                BloomFilter::new(BLOOM_FILTER_BITS).bytes().to_vec(),
+                100,
+                100,
+            ),
+        );
+        let mut bytes = vec![1_u8; 10];
+        bytes.extend(expect_footer.serialize().unwrap());
+        assert_eq!(bytes.len(), 10 + BLOOM_FILTER_SIZE + FOOTER_SIZE);
+
+        let footer = Footer::deserialize(&bytes).unwrap();
+        assert_eq!(footer, expect_footer);
+        assert!(footer.maybe_table_exist("cpu"));
+        assert!(!footer.maybe_table_exist("mem"));
+
+        // Footers before version 4 have no bloom filter of tables.
+        let old_footer = Footer::new(
+            3,
+            TimeRange::new(0, 100),
+            TableMeta::new(100, 100),
+            SeriesMeta::default(),
+        );
+        let mut bytes = vec![1_u8; BLOOM_FILTER_SIZE];
+        bytes.extend(old_footer.serialize().unwrap());
+        let footer = Footer::deserialize(&bytes).unwrap();
+        assert_eq!(footer, old_footer);
+        assert!(footer.maybe_table_exist("mem"));
+    }
 }
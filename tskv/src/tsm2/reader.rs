@@ -6,16 +6,17 @@ use std::sync::Arc;
 use bytes::Bytes;
 use models::predicate::domain::TimeRange;
 use models::schema::{TskvTableSchemaRef, TIME_FIELD};
-use models::SeriesId;
+use models::{ColumnId, SeriesId};
 
 use crate::error::Result;
+use crate::file_system::cold_store::{ColdFile, ColdStore, ColdStub};
 use crate::file_system::file::async_file::AsyncFile;
 use crate::file_system::file::IFile;
 use crate::file_system::file_manager;
 use crate::tsm::TsmTombstone;
 use crate::tsm2::page::{Chunk, ChunkGroup, ChunkGroupMeta, Footer, Page, PageMeta, PageWriteSpec};
 use crate::tsm2::writer::{Column, DataBlock2};
-use crate::tsm2::{ColumnGroupID, FOOTER_SIZE};
+use crate::tsm2::{ColumnGroupID, BLOOM_FILTER_SIZE, FOOTER_SIZE};
 use crate::{file_utils, Error};
 
 pub struct TSM2MetaData {
@@ -65,6 +66,26 @@ impl TSM2MetaData {
         self.table_schema(table_name)
     }
 
+    /// Returns false if the file definitely has no rows of the table that every
+    /// column of `column_values` equals to one of the values, checked by bloom filters.
+    pub fn maybe_contains(
+        &self,
+        table_name: &str,
+        column_values: &[(ColumnId, Vec<&[u8]>)],
+    ) -> bool {
+        if !self.footer.maybe_table_exist(table_name) {
+            return false;
+        }
+        match self.chunk_group_meta.tables().get(table_name) {
+            Some(chunk_group) => column_values.iter().all(|(column_id, values)| {
+                values
+                    .iter()
+                    .any(|v| chunk_group.maybe_column_value_exist(*column_id, v))
+            }),
+            None => false,
+        }
+    }
+
     pub fn table_name(&self, series_id: SeriesId) -> Option<&str> {
         for (table_name, series_map) in self.chunk_group.iter() {
             if series_map.chunks.iter().any(|c| c.series_id == series_id) {
@@ -75,19 +96,61 @@ impl TSM2MetaData {
     }
 }
 
+/// A local tsm file, or a tsm file moved to the cold tier.
+pub enum TsmFile {
+    Local(AsyncFile),
+    Cold(ColdFile),
+}
+
+impl TsmFile {
+    pub async fn read_at(&self, pos: u64, data: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Local(file) => file.read_at(pos, data).await,
+            Self::Cold(file) => file.read_at(pos, data).await,
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        match self {
+            Self::Local(file) => file.len(),
+            Self::Cold(file) => file.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_cold(&self) -> bool {
+        matches!(self, Self::Cold(_))
+    }
+}
+
 #[derive(Clone)]
 pub struct TSM2Reader {
     file_location: PathBuf,
     file_id: u64,
-    reader: Arc<AsyncFile>,
+    reader: Arc<TsmFile>,
     tsm_meta: Arc<TSM2MetaData>,
     tombstone: Arc<TsmTombstone>,
 }
 
 impl TSM2Reader {
     pub async fn open(tsm_path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_with_cold_store(tsm_path, None).await
+    }
+
+    /// Open a tsm file, pages of the file in the cold tier are read from `cold_store`.
+    pub async fn open_with_cold_store(
+        tsm_path: impl AsRef<Path>,
+        cold_store: Option<Arc<ColdStore>>,
+    ) -> Result<Self> {
         let path = tsm_path.as_ref().to_path_buf();
-        let reader = Arc::new(file_manager::open_file(&path).await?);
+        let file = file_manager::open_file(&path).await?;
+        let reader = match ColdStub::read(&file).await? {
+            Some(stub) => Arc::new(TsmFile::Cold(ColdFile::new(file, stub, cold_store))),
+            None => Arc::new(TsmFile::Local(file)),
+        };
 
         let file_id = file_utils::get_tsm_file_id_by_path(&path)?;
 
@@ -115,7 +178,7 @@ impl TSM2Reader {
         })
     }
 
-    pub fn reader(&self) -> Arc<AsyncFile> {
+    pub fn reader(&self) -> Arc<TsmFile> {
         self.reader.clone()
     }
 
@@ -143,6 +206,15 @@ impl TSM2Reader {
         &self.tsm_meta.chunk
     }
 
+    /// See [`TSM2MetaData::maybe_contains`].
+    pub fn maybe_contains(
+        &self,
+        table_name: &str,
+        column_values: &[(ColumnId, Vec<&[u8]>)],
+    ) -> bool {
+        self.tsm_meta.maybe_contains(table_name, column_values)
+    }
+
     pub fn tsm_meta_data(&self) -> Arc<TSM2MetaData> {
         self.tsm_meta.clone()
     }
@@ -325,26 +397,28 @@ impl Debug for TSM2Reader {
     }
 }
 
-pub async fn read_footer(reader: Arc<AsyncFile>) -> Result<Footer> {
-    let pos = reader.len() - (FOOTER_SIZE as u64);
-    let mut buffer = vec![0u8; FOOTER_SIZE];
+pub async fn read_footer(reader: Arc<TsmFile>) -> Result<Footer> {
+    // The bloom filter of tables is before the footer since version 4.
+    let size = reader.len().min((BLOOM_FILTER_SIZE + FOOTER_SIZE) as u64);
+    let pos = reader.len() - size;
+    let mut buffer = vec![0u8; size as usize];
     reader.read_at(pos, &mut buffer).await?;
     Footer::deserialize(&buffer)
 }
 
 pub async fn read_chunk_group_meta(
-    reader: Arc<AsyncFile>,
+    reader: Arc<TsmFile>,
     footer: &Footer,
 ) -> Result<ChunkGroupMeta> {
     let pos = footer.table.chunk_group_offset();
     let mut buffer = vec![0u8; footer.table.chunk_group_size()];
     reader.read_at(pos, &mut buffer).await?; // read chunk group meta
-    let specs = ChunkGroupMeta::deserialize(&buffer)?;
+    let specs = ChunkGroupMeta::deserialize(&buffer, footer.version())?;
     Ok(specs)
 }
 
 pub async fn read_chunk_groups(
-    reader: Arc<AsyncFile>,
+    reader: Arc<TsmFile>,
     chunk_group_meta: &ChunkGroupMeta,
 ) -> Result<BTreeMap<String, Arc<ChunkGroup>>> {
     let mut specs = BTreeMap::new();
@@ -359,7 +433,7 @@ pub async fn read_chunk_groups(
 }
 
 pub async fn read_chunk(
-    reader: Arc<AsyncFile>,
+    reader: Arc<TsmFile>,
     chunk_group: &BTreeMap<String, Arc<ChunkGroup>>,
 ) -> Result<BTreeMap<SeriesId, Arc<Chunk>>> {
     let mut chunks = BTreeMap::new();
@@ -375,7 +449,7 @@ pub async fn read_chunk(
     Ok(chunks)
 }
 
-async fn read_page(reader: Arc<AsyncFile>, page_spec: &PageWriteSpec) -> Result<Page> {
+async fn read_page(reader: Arc<TsmFile>, page_spec: &PageWriteSpec) -> Result<Page> {
     let pos = page_spec.offset();
     let mut buffer = vec![0u8; page_spec.size()];
     reader.read_at(pos, &mut buffer).await?;
@@ -1,5 +1,5 @@
 use std::cmp::{max, min};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::io::IoSlice;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -8,8 +8,8 @@ use minivec::MiniVec;
 use models::codec::Encoding;
 use models::field_value::FieldVal;
 use models::predicate::domain::TimeRange;
-use models::schema::{ColumnType, TableColumn, TskvTableSchemaRef};
-use models::{SeriesId, SeriesKey, ValueType};
+use models::schema::{ColumnType, TableColumn, TskvTableSchema, TskvTableSchemaRef};
+use models::{ColumnId, SeriesId, SeriesKey, ValueType};
 use snafu::ResultExt;
 use utils::bitset::BitSet;
 use utils::BloomFilter;
@@ -27,8 +27,10 @@ use crate::tsm::TsmTombstone;
 use crate::tsm2::page::{
     Chunk, ChunkGroup, ChunkGroupMeta, ChunkGroupWriteSpec, ChunkStatics, ChunkWriteSpec,
     ColumnGroup, Footer, Page, PageMeta, PageStatistics, PageWriteSpec, SeriesMeta, TableMeta,
+    FOOTER_VERSION,
 };
-use crate::tsm2::{TsmWriteData, BLOOM_FILTER_BITS};
+use crate::tsm2::reader::decode_buf_to_pages;
+use crate::tsm2::{TsmWriteData, BLOOM_FILTER_BITS, COLUMN_BLOOM_FILTER_BITS};
 use crate::{Error, Result};
 
 // #[derive(Debug, Clone)]
@@ -267,8 +269,16 @@ impl Column {
             }
         }
     }
+    /// Number of the null values.
+    pub fn null_count(&self) -> usize {
+        let valid_len = self.valid.len().min(self.len());
+        self.len() - (0..valid_len).filter(|i| self.valid.get(*i)).count()
+    }
+
     pub fn col_to_page(&self, desc: &TableColumn) -> Result<Page> {
-        let null_count = 1;
+        let null_count = self.null_count() as u64;
+        // min and max are meaningless if all values are null.
+        let has_value = (null_count as usize) < self.len();
         let len_bitset = self.valid.byte_len() as u32;
         let data_len = self.len() as u64;
         let mut buf = vec![];
@@ -279,8 +289,8 @@ impl Column {
                     .encode(array, &mut buf)
                     .map_err(|e| Error::Encode { source: e })?;
                 PageStatistics::F64(ValueStatistics::new(
-                    Some(*min),
-                    Some(*max),
+                    has_value.then_some(*min),
+                    has_value.then_some(*max),
                     None,
                     null_count,
                 ))
@@ -291,8 +301,8 @@ impl Column {
                     .encode(array, &mut buf)
                     .map_err(|e| Error::Encode { source: e })?;
                 PageStatistics::I64(ValueStatistics::new(
-                    Some(*min),
-                    Some(*max),
+                    has_value.then_some(*min),
+                    has_value.then_some(*max),
                     None,
                     null_count,
                 ))
@@ -303,8 +313,8 @@ impl Column {
                     .encode(array, &mut buf)
                     .map_err(|e| Error::Encode { source: e })?;
                 PageStatistics::U64(ValueStatistics::new(
-                    Some(*min),
-                    Some(*max),
+                    has_value.then_some(*min),
+                    has_value.then_some(*max),
                     None,
                     null_count,
                 ))
@@ -318,8 +328,8 @@ impl Column {
                     )
                     .map_err(|e| Error::Encode { source: e })?;
                 PageStatistics::Bytes(ValueStatistics::new(
-                    Some(min.as_bytes().to_vec()),
-                    Some(max.as_bytes().to_vec()),
+                    has_value.then(|| min.as_bytes().to_vec()),
+                    has_value.then(|| max.as_bytes().to_vec()),
                     None,
                     null_count,
                 ))
@@ -330,8 +340,8 @@ impl Column {
                     .encode(array, &mut buf)
                     .map_err(|e| Error::Encode { source: e })?;
                 PageStatistics::Bool(ValueStatistics::new(
-                    Some(*min),
-                    Some(*max),
+                    has_value.then_some(*min),
+                    has_value.then_some(*max),
                     None,
                     null_count,
                 ))
@@ -482,6 +492,15 @@ impl DataBlock2 {
         self.schema.clone()
     }
 
+    pub fn ts(&self) -> &Column {
+        &self.ts
+    }
+
+    /// Returns the field columns with their descriptions.
+    pub fn columns(&self) -> impl Iterator<Item = (&TableColumn, &Column)> {
+        self.cols_desc.iter().zip(self.cols.iter())
+    }
+
     pub fn block_to_page(&self) -> Result<Vec<Page>> {
         let mut pages = Vec::with_capacity(self.cols.len() + 1);
         pages.push(self.ts.col_to_page(&self.ts_desc)?);
@@ -491,6 +510,8 @@ impl DataBlock2 {
         Ok(pages)
     }
 
+    /// Merge the data block `other` written before this data block, values with the
+    /// same timestamp are deduplicated by the policy of the newer table schema.
     pub fn merge(&mut self, other: DataBlock2) -> Result<DataBlock2> {
         self.schema_check(&other)?;
 
@@ -499,6 +520,7 @@ impl DataBlock2 {
         } else {
             other.schema.clone()
         };
+        let dedup_policy = schema.dedup_policy();
         let (sort_index, time_array) = self.sort_index_and_time_col(&other)?;
         let mut columns = Vec::new();
         let mut columns_des = Vec::new();
@@ -534,11 +556,7 @@ impl DataBlock2 {
                         } else {
                             None
                         };
-                        if field_self.is_some() {
-                            merge_column.push(field_self);
-                        } else {
-                            merge_column.push(field_other);
-                        }
+                        merge_column.push(dedup_policy.dedup_value(field_other, field_self));
                     }
                 }
             }
@@ -754,6 +772,77 @@ impl Default for WriteOptions {
     }
 }
 
+/// Bloom filters of values of the tag and string field columns of a table,
+/// see `TableOptions::bloom_filter_columns`.
+#[derive(Default)]
+struct ColumnBloomFilters {
+    filters: BTreeMap<ColumnId, BloomFilter>,
+    // columns written by schemas without bloom filter, filters of them are incomplete
+    missed: BTreeSet<ColumnId>,
+}
+
+impl ColumnBloomFilters {
+    fn insert(&mut self, column_id: ColumnId, value: &[u8]) {
+        self.filters
+            .entry(column_id)
+            .or_insert_with(|| BloomFilter::new(COLUMN_BLOOM_FILTER_BITS))
+            .insert(value);
+    }
+
+    fn update(
+        &mut self,
+        schema: &TskvTableSchema,
+        series_key: &SeriesKey,
+        pages: &[Page],
+    ) -> Result<()> {
+        for tag in series_key.tags() {
+            let column_id = match std::str::from_utf8(&tag.key)
+                .ok()
+                .and_then(|k| k.parse::<ColumnId>().ok())
+            {
+                Some(id) => id,
+                None => continue,
+            };
+            if schema.has_bloom_filter(column_id) {
+                self.insert(column_id, &tag.value);
+            } else {
+                self.missed.insert(column_id);
+            }
+        }
+        for page in pages {
+            let desc = page.desc();
+            if desc.column_type != ColumnType::Field(ValueType::String) {
+                continue;
+            }
+            if !schema.has_bloom_filter(desc.id) {
+                self.missed.insert(desc.id);
+                continue;
+            }
+            let column = page.to_column()?;
+            if let ColumnData::String(values, ..) = &column.data {
+                for (i, value) in values.iter().enumerate() {
+                    if column.valid.get(i) {
+                        self.insert(desc.id, value.as_bytes());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn miss_columns<'a>(&mut self, columns: impl Iterator<Item = &'a TableColumn>) {
+        self.missed.extend(columns.map(|c| c.id));
+    }
+
+    fn complete_filters(&self) -> BTreeMap<ColumnId, BloomFilter> {
+        self.filters
+            .iter()
+            .filter(|(id, _)| !self.missed.contains(id))
+            .map(|(id, filter)| (*id, filter.clone()))
+            .collect()
+    }
+}
+
 const HEADER_LEN: u64 = 5;
 const TSM_MAGIC: [u8; 4] = 0x12CDA16_u32.to_be_bytes();
 const VERSION: [u8; 1] = [1];
@@ -767,8 +856,9 @@ pub struct Tsm2Writer {
     path: PathBuf,
 
     series_bloom_filter: BloomFilter,
-    // todo: table object id bloom filter
-    // table_bloom_filter: BloomFilter,
+    table_bloom_filter: BloomFilter,
+    /// <table, ColumnBloomFilters>
+    column_bloom_filters: HashMap<String, ColumnBloomFilters>,
     writer: FileCursor,
     options: WriteOptions,
     table_schemas: HashMap<String, TskvTableSchemaRef>,
@@ -779,6 +869,7 @@ pub struct Tsm2Writer {
     chunk_specs: BTreeMap<String, ChunkGroup>,
     /// [ChunkGroupWriteSpec]
     chunk_group_specs: ChunkGroupMeta,
+    footer_version: u8,
     footer: Footer,
     state: State,
 }
@@ -809,17 +900,26 @@ impl Tsm2Writer {
             max_size,
             path,
             series_bloom_filter: BloomFilter::new(BLOOM_FILTER_BITS),
+            table_bloom_filter: BloomFilter::new(BLOOM_FILTER_BITS),
+            column_bloom_filters: Default::default(),
             writer,
             options: Default::default(),
             table_schemas: Default::default(),
             page_specs: Default::default(),
             chunk_specs: Default::default(),
             chunk_group_specs: Default::default(),
+            footer_version: FOOTER_VERSION,
             footer: Default::default(),
             state: State::Initialised,
         }
     }
 
+    /// Write the file in the layout of an old footer version, to test reading old files.
+    #[cfg(test)]
+    pub(crate) fn set_footer_version(&mut self, version: u8) {
+        self.footer_version = version;
+    }
+
     pub fn file_id(&self) -> u64 {
         self.file_id
     }
@@ -886,6 +986,11 @@ impl Tsm2Writer {
                 time_range: group.time_range(),
                 // The number of chunks in the group.
                 count: 0,
+                column_bloom_filters: self
+                    .column_bloom_filters
+                    .get(table)
+                    .map(|f| f.complete_filters())
+                    .unwrap_or_default(),
             };
             self.chunk_group_specs.push(chunk_group_spec);
         }
@@ -894,14 +999,22 @@ impl Tsm2Writer {
 
     pub async fn write_chunk_group_specs(&mut self, series: SeriesMeta) -> Result<()> {
         let chunk_group_specs_offset = self.writer.pos();
-        let buf = self.chunk_group_specs.serialize()?;
+        let buf = if self.footer_version < 4 {
+            self.chunk_group_specs.serialize_legacy()?
+        } else {
+            self.chunk_group_specs.serialize()?
+        };
         let chunk_group_specs_size = self.writer.write(&buf).await?;
         self.size += chunk_group_specs_size;
         let time_range = self.chunk_group_specs.time_range();
+        let mut table = TableMeta::new(chunk_group_specs_offset, chunk_group_specs_size);
+        if self.footer_version >= 4 {
+            table = table.with_bloom_filter(self.table_bloom_filter.clone());
+        }
         let footer = Footer {
-            version: 2_u8,
+            version: self.footer_version,
             time_range,
-            table: TableMeta::new(chunk_group_specs_offset, chunk_group_specs_size),
+            table,
             series,
         };
         self.footer = footer;
@@ -911,6 +1024,7 @@ impl Tsm2Writer {
     pub async fn write_chunk(&mut self) -> Result<SeriesMeta> {
         let chunk_offset = self.writer.pos();
         for (table, group) in &self.page_specs {
+            self.table_bloom_filter.insert(table.as_bytes());
             for (series, chunk) in group {
                 let chunk_offset = self.writer.pos();
                 let buf = chunk.serialize()?;
@@ -1004,6 +1118,10 @@ impl Tsm2Writer {
         let mut column_group = self.create_column_group(schema.clone(), series_id, &series_key);
 
         let table = schema.name.clone();
+        self.column_bloom_filters
+            .entry(table.clone())
+            .or_default()
+            .update(&schema, &series_key, &pages)?;
         for page in pages {
             let offset = self.writer.pos();
             let size = self.writer.write(&page.bytes).await?;
@@ -1051,6 +1169,15 @@ impl Tsm2Writer {
             .ok_or(Error::CommonError {
                 reason: format!("column group not found: {}", column_group_id),
             })?;
+        let bloom_filters = self.column_bloom_filters.entry(table.clone()).or_default();
+        if schema.options().bloom_filter_columns().is_empty() {
+            bloom_filters.update(&schema, meta.series_key(), &[])?;
+            bloom_filters.miss_columns(column_group.pages().iter().map(|p| &p.meta.column));
+        } else {
+            // Values of the columns are needed by bloom filters of the table.
+            let pages = decode_buf_to_pages(meta.clone(), column_group_id, &raw)?;
+            bloom_filters.update(&schema, meta.series_key(), &pages)?;
+        }
         for spec in column_group.pages() {
             let spec = PageWriteSpec {
                 offset,
@@ -1130,6 +1257,7 @@ impl Tsm2Writer {
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeSet;
     use std::path::PathBuf;
     use std::sync::Arc;
 
@@ -1137,8 +1265,8 @@ mod test {
     use models::codec::Encoding;
     use models::field_value::FieldVal;
     use models::predicate::domain::TimeRange;
-    use models::schema::{ColumnType, TableColumn, TskvTableSchema};
-    use models::{SeriesKey, ValueType};
+    use models::schema::{ColumnType, TableColumn, TableOptions, TskvTableSchema};
+    use models::{SeriesKey, Tag, ValueType};
 
     use crate::tsm2::reader::TSM2Reader;
     use crate::tsm2::writer::{Column, DataBlock2, Tsm2Writer};
@@ -1225,4 +1353,72 @@ mod test {
         assert_eq!(time_range, TimeRange::new(1, 3));
         println!("time range: {:?}", time_range);
     }
+
+    #[tokio::test]
+    async fn test_write_bloom_filters() {
+        let mut schema = TskvTableSchema::new(
+            "cnosdb".to_string(),
+            "public".to_string(),
+            "test1".to_string(),
+            vec![
+                TableColumn::new_time_column(0, TimeUnit::Nanosecond),
+                TableColumn::new_tag_column(1, "device".to_string()),
+                TableColumn::new(
+                    2,
+                    "f1".to_string(),
+                    ColumnType::Field(ValueType::String),
+                    Encoding::default(),
+                ),
+                TableColumn::new(
+                    3,
+                    "f2".to_string(),
+                    ColumnType::Field(ValueType::String),
+                    Encoding::default(),
+                ),
+            ],
+        );
+        let mut options = TableOptions::default();
+        options.with_bloom_filter_columns(BTreeSet::from([1, 2]));
+        schema.set_options(options);
+        let schema = Arc::new(schema);
+
+        let mut f1 = Column::empty(ColumnType::Field(ValueType::String)).unwrap();
+        let mut f2 = Column::empty(ColumnType::Field(ValueType::String)).unwrap();
+        for v in ["a", "b", "c"] {
+            f1.push(Some(FieldVal::Bytes(v.into())));
+            f2.push(Some(FieldVal::Bytes(v.into())));
+        }
+        let data = DataBlock2::new(
+            schema.clone(),
+            ts_column(vec![1, 2, 3]),
+            schema.time_column(),
+            vec![f1, f2],
+            vec![
+                schema.column("f1").cloned().unwrap(),
+                schema.column("f2").cloned().unwrap(),
+            ],
+        );
+        let series_key = SeriesKey {
+            tags: vec![Tag::new_with_column_id(1, b"d1".to_vec())],
+            table: "test1".to_string(),
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut tsm_writer = Tsm2Writer::open(&dir.path(), 1, 0, false).await.unwrap();
+        tsm_writer
+            .write_datablock(1, series_key, data)
+            .await
+            .unwrap();
+        tsm_writer.finish().await.unwrap();
+
+        let tsm_reader = TSM2Reader::open(tsm_writer.path).await.unwrap();
+        assert!(tsm_reader.maybe_contains("test1", &[]));
+        assert!(!tsm_reader.maybe_contains("test2", &[]));
+        assert!(tsm_reader.maybe_contains("test1", &[(1, vec![b"d1".as_slice()])]));
+        assert!(!tsm_reader.maybe_contains("test1", &[(1, vec![b"d2".as_slice()])]));
+        assert!(tsm_reader.maybe_contains("test1", &[(2, vec![b"x".as_slice(), b"b".as_slice()])]));
+        assert!(!tsm_reader.maybe_contains("test1", &[(2, vec![b"x".as_slice()])]));
+        // Column f2 has no bloom filter.
+        assert!(tsm_reader.maybe_contains("test1", &[(3, vec![b"x".as_slice()])]));
+    }
 }
@@ -14,11 +14,11 @@ use trace::{debug, error, info, warn, SpanContext, SpanExt, SpanRecorder};
 use crate::compaction::run_flush_memtable_job;
 use crate::database::Database;
 use crate::error::Result;
-use crate::file_system::file_info;
+use crate::file_system::{cold_store, file_info};
 use crate::index::ts_index::TSIndex;
 use crate::schema::error::SchemaError;
 use crate::tseries_family::TseriesFamily;
-use crate::{Error, TsKvContext, VnodeSnapshot};
+use crate::{index, Error, TsKvContext, VnodeSnapshot};
 
 #[derive(Clone)]
 pub struct VnodeStorage {
@@ -74,6 +74,17 @@ impl VnodeStorage {
                 self.delete_from_table(&cmd).await?;
                 Ok(vec![])
             }
+
+            raft_write_command::Command::RepairVnode(cmd) => {
+                if let Err(err) = self.repair(ctx, &cmd).await {
+                    if ctx.apply_type == replication::APPLY_TYPE_WAL {
+                        info!("recover: repair vnode: {}", err);
+                    } else {
+                        return Err(err);
+                    }
+                }
+                Ok(vec![])
+            }
         }
     }
 
@@ -327,6 +338,52 @@ impl VnodeStorage {
         self.delete(&cmd.table, &series_ids, &time_ranges).await
     }
 
+    /// Repair the vnode with data read from a healthy replica: data of the given series in the
+    /// given time ranges (points only found on divergent replicas) is deleted, then the points
+    /// (divergent rows of the healthy replica) are written.
+    ///
+    /// The command is applied from the raft log on every replica of the replication set, it only
+    /// touches the divergent rows, so points written by other commands are kept.
+    async fn repair(
+        &self,
+        ctx: &replication::ApplyContext,
+        cmd: &RepairVnodeRequest,
+    ) -> Result<()> {
+        let series_time_ranges = bincode::deserialize::<Vec<(SeriesKey, TimeRange)>>(
+            &cmd.series_time_ranges,
+        )
+        .map_err(|err| Error::InvalidParam {
+            reason: format!("Series time ranges of repair_vnode is invalid, error: {err}"),
+        })?;
+
+        let mut series_time_ranges_map: HashMap<SeriesId, Vec<TimeRange>> = HashMap::new();
+        for (series_key, time_range) in series_time_ranges.iter() {
+            if let Some(series_id) = self.ts_index.get_series_id(series_key).await? {
+                series_time_ranges_map
+                    .entry(series_id)
+                    .or_default()
+                    .push(*time_range);
+            }
+        }
+        for (series_id, time_ranges) in series_time_ranges_map {
+            self.delete(&cmd.table, &[series_id], &TimeRanges::new(time_ranges))
+                .await?;
+        }
+
+        if !cmd.points.is_empty() {
+            let precision = Precision::from(cmd.precision as u8);
+            self.write(ctx, cmd.points.clone(), precision, None).await?;
+        }
+        info!(
+            "Repair: repaired {} series of table '{}' on vnode {}",
+            series_time_ranges.len(),
+            cmd.table,
+            self.id
+        );
+
+        Ok(())
+    }
+
     /// Flush caches into TSM file, create a new Version of the Vnode, then:
     /// 1. Make hard links point to all TSM files in the Version in snapshot directory,
     /// 2. Copy series index in Vnode into snapshot directory,
@@ -438,6 +495,7 @@ impl VnodeStorage {
         db_wlock.del_ts_index(vnode_id);
         let vnode_dir = storage_opt.ts_family_dir(&owner, vnode_id);
         let _ = std::fs::remove_dir_all(&vnode_dir);
+        cold_store::delete_vnode_files(&storage_opt, &owner, vnode_id).await;
 
         // move snashot data to vnode move dir
         let move_dir = storage_opt.move_dir(&owner, vnode_id);
@@ -451,6 +509,12 @@ impl VnodeStorage {
         let ts_family = db_wlock
             .add_tsfamily(vnode_id, Some(version_edit), self.ctx.clone())
             .await?;
+        // The index of the snapshot is copied from a node which may use another index engine.
+        let index_dir = storage_opt.index_dir(&owner, vnode_id);
+        if let Err(e) = index::migrate_index_engine(&index_dir, storage_opt.index_engine) {
+            error!("Snapshot: failed to migrate index dir {:?}: {e}", index_dir);
+            return Err(Error::IndexErr { source: e });
+        }
         let ts_index = db_wlock.get_ts_index_or_add(vnode_id).await?;
 
         self.ts_index = ts_index;
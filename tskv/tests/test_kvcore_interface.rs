@@ -17,6 +17,7 @@ mod tests {
     use tokio::runtime::Runtime;
     use trace::{debug, error, info, init_default_global_tracing, warn};
     use tskv::file_system::file_manager;
+    use tskv::index::IndexEngineType;
     use tskv::{file_utils, kv_option, Engine, TsKv};
 
     /// Initializes a TsKv instance in specified directory, with an optional runtime,
@@ -36,7 +37,13 @@ mod tests {
     }
 
     fn get_tskv(dir: impl AsRef<Path>, runtime: Option<Arc<Runtime>>) -> (Arc<Runtime>, TsKv) {
-        let global_config = get_config(dir);
+        get_tskv_with_config(get_config(dir), runtime)
+    }
+
+    fn get_tskv_with_config(
+        global_config: config::Config,
+        runtime: Option<Arc<Runtime>>,
+    ) -> (Arc<Runtime>, TsKv) {
         let opt = kv_option::Options::from(&global_config);
         let rt = match runtime {
             Some(rt) => rt,
@@ -396,6 +403,73 @@ mod tests {
         println!("Leave serial test: test_kvcore_snapshot_create_apply_delete");
     }
 
+    #[test]
+    #[serial]
+    fn test_kvcore_snapshot_apply_other_index_engine() {
+        println!("Enter serial test: test_kvcore_snapshot_apply_other_index_engine");
+        let dir = PathBuf::from("/tmp/test/kvcore/kvcore_snapshot_apply_other_index_engine");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        init_default_global_tracing(dir.join("log"), "tskv.log", "debug");
+        let tenant = "cnosdb";
+        let database = "db_test_snapshot_index";
+        let table = "tab_test_snapshot_index";
+        let vnode_backup_dir = dir.join("backup_for_test");
+
+        // Create the snapshot on a node using radix index engine.
+        let mut config = get_config(dir.join("radix"));
+        config.storage.index_engine = IndexEngineType::Radix;
+        let (runtime, tskv) = get_tskv_with_config(config, None);
+        {
+            let mut fbb = flatbuffers::FlatBufferBuilder::new();
+            let points =
+                models_helper::create_random_points_include_delta(&mut fbb, database, table, 20);
+            fbb.finish(points, None);
+            let request = WriteDataRequest {
+                data: fbb.finished_data().to_vec(),
+                precision: Precision::NS as u32,
+            };
+            tskv_write(runtime.clone(), &tskv, tenant, database, 21, 1, request);
+        }
+        let vnode = runtime
+            .block_on(tskv.open_tsfamily(tenant, database, 21))
+            .unwrap();
+        let series_ids = runtime
+            .block_on(vnode.ts_index.get_series_id_list(table, &[]))
+            .unwrap();
+        assert!(!series_ids.is_empty());
+        sleep_in_runtime(runtime.clone(), Duration::from_secs(3));
+        let vnode_snapshot = runtime.block_on(vnode.create_snapshot()).unwrap();
+        let vnode_snapshot_dir = tskv
+            .get_storage_options()
+            .snapshot_dir(&make_owner(tenant, database), 21)
+            .join(&vnode_snapshot.snapshot_id);
+        dircpy::copy_dir(vnode_snapshot_dir, &vnode_backup_dir).unwrap();
+        runtime.block_on(tskv.close());
+
+        // Install the snapshot on a node using sled index engine.
+        let mut config = get_config(dir.join("sled"));
+        config.storage.index_engine = IndexEngineType::Sled;
+        let (runtime, tskv) = get_tskv_with_config(config, Some(runtime));
+        let mut vnode = runtime
+            .block_on(tskv.open_tsfamily(tenant, database, 22))
+            .unwrap();
+        runtime
+            .block_on(vnode.apply_snapshot(vnode_snapshot, &vnode_backup_dir))
+            .unwrap();
+        let mut installed_series_ids = runtime
+            .block_on(vnode.ts_index.get_series_id_list(table, &[]))
+            .unwrap();
+        installed_series_ids.sort();
+        let mut series_ids = series_ids;
+        series_ids.sort();
+        assert_eq!(installed_series_ids, series_ids);
+
+        runtime.block_on(tskv.close());
+        println!("Leave serial test: test_kvcore_snapshot_apply_other_index_engine");
+    }
+
     fn sleep_in_runtime(runtime: Arc<Runtime>, duration: Duration) {
         let rt = runtime.clone();
         runtime.block_on(async move {